@@ -259,6 +259,8 @@ impl Account {
                     )
                 })?,
         );
+        self.context
+            .check_state_key_replicated(&state_key, &self.latest_ledger_info)?;
 
         let state_value = self.context.get_state_value_poem(
             &state_key,
@@ -308,8 +310,10 @@ impl Account {
     /// Note: For the BCS response, if results are being returned in pages, i.e. with the
     /// `start` and `limit` query parameters, the results will only be sorted within each page.
     pub fn resources(self, accept_type: &AcceptType) -> BasicResultWith404<Vec<MoveResource>> {
-        // check account exists
+        // check account exists, and that all its resources are replicated by this node
         self.verify_account_or_object_resource()?;
+        self.context
+            .check_account_replicated(&self.address.into(), &self.latest_ledger_info)?;
         let max_account_resources_page_size = self.context.max_account_resources_page_size();
         let (resources, next_state_key) = self
             .context
@@ -380,8 +384,10 @@ impl Account {
     /// Note: For the BCS response, if results are being returned in pages, i.e. with the
     /// `start` and `limit` query parameters, the results will only be sorted within each page.
     pub fn modules(self, accept_type: &AcceptType) -> BasicResultWith404<Vec<MoveModuleBytecode>> {
-        // check account exists
+        // check account exists, and that all its modules are replicated by this node
         self.verify_account_or_object_resource()?;
+        self.context
+            .check_account_replicated(&self.address.into(), &self.latest_ledger_info)?;
         let max_account_modules_page_size = self.context.max_account_modules_page_size();
         let (modules, next_state_key) = self
             .context
@@ -516,6 +522,19 @@ impl Account {
     ) -> Result<Vec<(Identifier, move_core_types::value::MoveValue)>, BasicErrorWith404> {
         let (ledger_info, ledger_version, state_view) =
             self.context.state_view(Some(self.ledger_version))?;
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(self.address.into(), resource_type.clone()).map_err(
+                |err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                },
+            )?,
+        );
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let resolver = state_view.as_move_resolver();
 
         let bytes = resolver
@@ -6,8 +6,9 @@ use crate::{
     accept_type::AcceptType,
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
-        block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        block_pruned_by_height, json_api_disabled, state_value_not_replicated, version_not_found,
+        version_pruned, ForbiddenError, GoneError, InternalError, NotFoundError,
+        ServiceUnavailableError, StdApiError,
     },
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
@@ -38,10 +39,13 @@ use aptos_types::{
     on_chain_config::{GasSchedule, GasScheduleV2, OnChainConfig, OnChainExecutionConfig},
     state_store::{
         state_key::{StateKey, StateKeyInner},
+        state_key_filter::StateKeyFilter,
         state_key_prefix::StateKeyPrefix,
-        state_value::StateValue,
+        state_value::{StateValue, StateValueWithProof},
+    },
+    transaction::{
+        SignedTransaction, TransactionWithLedgerInfoProof, TransactionWithProof, Version,
     },
-    transaction::{SignedTransaction, TransactionWithProof, Version},
 };
 use aptos_vm::{
     data_cache::{AsMoveResolver, StorageAdapter},
@@ -66,6 +70,7 @@ pub struct Context {
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
     gas_estimation_cache: Arc<RwLock<GasEstimationCache>>,
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
+    replicated_state_keys: Option<StateKeyFilter>,
 }
 
 impl std::fmt::Debug for Context {
@@ -81,6 +86,10 @@ impl Context {
         mp_sender: MempoolClientSender,
         node_config: NodeConfig,
     ) -> Self {
+        let replicated_state_keys = node_config
+            .state_sync
+            .partial_replication
+            .state_key_filter();
         Self {
             chain_id,
             db,
@@ -100,6 +109,7 @@ impl Context {
                 last_updated_epoch: None,
                 block_gas_limit: None,
             })),
+            replicated_state_keys,
         }
     }
 
@@ -263,6 +273,83 @@ impl Context {
             .get_state_value_bytes(state_key)
     }
 
+    /// Returns the value of the given state key at the latest state snapshot, with the proofs
+    /// connecting it to the latest ledger info with signatures. The state merkle tree is only
+    /// persisted at snapshot versions, so the value can't be proven at the ledger info version.
+    pub fn get_state_value_with_proof(&self, state_key: &StateKey) -> Result<StateValueWithProof> {
+        let ledger_info_with_signatures = self.get_latest_ledger_info_with_signatures()?;
+        let ledger_version = ledger_info_with_signatures.ledger_info().version();
+        let (version, _root_hash) = self
+            .db
+            .get_state_snapshot_before(ledger_version + 1)?
+            .ok_or_else(|| format_err!("No state snapshot at or before {}", ledger_version))?;
+        let transaction_info_with_proof = self
+            .db
+            .get_transaction_by_version(version, ledger_version, false)?
+            .proof;
+        let (state_value, sparse_merkle_proof) = self
+            .db
+            .get_state_value_with_proof_by_version(state_key, version)?;
+
+        Ok(StateValueWithProof::new(
+            version,
+            ledger_info_with_signatures,
+            transaction_info_with_proof,
+            state_value,
+            sparse_merkle_proof,
+        ))
+    }
+
+    /// Returns an error if this node doesn't replicate the value of the given state key
+    pub fn check_state_key_replicated<E: GoneError>(
+        &self,
+        state_key: &StateKey,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(), E> {
+        match &self.replicated_state_keys {
+            Some(filter) if !filter.matches(state_key) => Err(state_value_not_replicated(
+                format!("StateKey({:?})", state_key),
+                ledger_info,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns an error if this node only replicates part of the state, for the requests that may
+    /// read any state value, e.g., to execute Move code or to validate transactions
+    pub fn check_state_fully_replicated<E: ServiceUnavailableError>(
+        &self,
+        operation: &str,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(), E> {
+        if self.replicated_state_keys.is_some() {
+            return Err(E::service_unavailable_with_code(
+                format!(
+                    "{} is not supported, the state is only partially replicated by this node",
+                    operation
+                ),
+                AptosErrorCode::StateValueNotReplicated,
+                ledger_info,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if this node doesn't replicate all the state values of the given account
+    pub fn check_account_replicated<E: GoneError>(
+        &self,
+        address: &AccountAddress,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(), E> {
+        match &self.replicated_state_keys {
+            Some(filter) if !filter.contains_account(address) => Err(state_value_not_replicated(
+                format!("Account({})", address),
+                ledger_info,
+            )),
+            _ => Ok(()),
+        }
+    }
+
     pub fn get_state_value_poem<E: InternalError>(
         &self,
         state_key: &StateKey,
@@ -763,6 +850,25 @@ impl Context {
         )?)
     }
 
+    /// Returns the transaction at the given version with its events, and the proof connecting it
+    /// to the latest ledger info with signatures.
+    pub fn get_transaction_with_proof(
+        &self,
+        version: u64,
+    ) -> Result<TransactionWithLedgerInfoProof> {
+        let ledger_info_with_signatures = self.get_latest_ledger_info_with_signatures()?;
+        let transaction_with_proof = self.db.get_transaction_by_version(
+            version,
+            ledger_info_with_signatures.ledger_info().version(),
+            true,
+        )?;
+
+        Ok(TransactionWithLedgerInfoProof::new(
+            ledger_info_with_signatures,
+            transaction_with_proof,
+        ))
+    }
+
     pub fn get_accumulator_root_hash(&self, version: u64) -> Result<HashValue> {
         self.db.get_accumulator_root_hash(version)
     }
@@ -612,6 +612,17 @@ pub fn version_pruned<E: GoneError>(ledger_version: u64, ledger_info: &LedgerInf
     )
 }
 
+pub fn state_value_not_replicated<S: Display, E: GoneError>(
+    identifier: S,
+    ledger_info: &LedgerInfo,
+) -> E {
+    E::gone_with_code(
+        format!("{} is not replicated by this node", identifier),
+        AptosErrorCode::StateValueNotReplicated,
+        ledger_info,
+    )
+}
+
 pub fn account_not_found<E: NotFoundError>(
     address: Address,
     ledger_version: u64,
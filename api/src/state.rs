@@ -13,7 +13,7 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
+    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
     RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
 };
@@ -84,6 +84,49 @@ impl StateApi {
         )
     }
 
+    /// Get account resource with proof
+    ///
+    /// Retrieves an individual resource from a given account at the latest state snapshot
+    /// version, together with the proof of its value (or of its absence) against the latest ledger
+    /// info with signatures. The response is a BCS encoded `StateValueWithProof`, which can be
+    /// verified by clients that don't trust the node, e.g., light clients.
+    ///
+    /// Only BCS is supported as an AcceptType.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/proof",
+        method = "get",
+        operation_id = "get_account_resource_with_proof",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+    ) -> BasicResultWith404<MoveValue> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get account resource with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get account resource with proof", &accept_type)?;
+
+        self.resource_with_proof(address.0, resource_type.0)
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -215,6 +258,43 @@ impl StateApi {
         )
     }
 
+    /// Get raw table item with proof
+    ///
+    /// Get a table item from the table identified by {table_handle} in the path and the "key"
+    /// (RawTableItemRequest) provided in the request body, at the latest state snapshot version,
+    /// together with the proof of its value (or of its absence) against the latest ledger info with
+    /// signatures. The response is a BCS encoded `StateValueWithProof`, which can be verified by
+    /// clients that don't trust the node, e.g., light clients.
+    ///
+    /// Only BCS is supported as an AcceptType.
+    #[oai(
+        path = "/tables/:table_handle/raw_item/proof",
+        method = "post",
+        operation_id = "get_raw_table_item_with_proof",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_raw_table_item_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key
+        table_item_request: Json<RawTableItemRequest>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_raw_table_item_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get raw table item with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get raw table item with proof", &accept_type)?;
+
+        self.raw_table_item_with_proof(table_handle.0, table_item_request.0)
+    }
+
     /// Get raw state value.
     ///
     /// Get a state value at a specific ledger version, identified by the key provided
@@ -274,6 +354,19 @@ impl StateApi {
             })?;
 
         let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(address.into(), resource_type.clone()).map_err(
+                |err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                },
+            )?,
+        );
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let bytes = state_view
             .as_move_resolver()
             .get_resource(&address.into(), &resource_type)
@@ -315,6 +408,72 @@ impl StateApi {
         }
     }
 
+    /// Read a resource with its proof at the latest state snapshot version
+    fn resource_with_proof(
+        &self,
+        address: Address,
+        resource_type: MoveStructTag,
+    ) -> BasicResultWith404<MoveValue> {
+        let resource_type: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(address.into(), resource_type).map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?,
+        );
+
+        self.state_value_with_proof(&state_key, &ledger_info)
+    }
+
+    /// Read a table item with its proof at the latest state snapshot version
+    fn raw_table_item_with_proof(
+        &self,
+        table_handle: Address,
+        table_item_request: RawTableItemRequest,
+    ) -> BasicResultWith404<MoveValue> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let state_key =
+            StateKey::table_item(TableHandle(table_handle.into()), table_item_request.key.0);
+
+        self.state_value_with_proof(&state_key, &ledger_info)
+    }
+
+    /// Read a state value with its proof at the latest state snapshot version
+    ///
+    /// The value is BCS encoded as a `StateValueWithProof`
+    fn state_value_with_proof(
+        &self,
+        state_key: &StateKey,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<MoveValue> {
+        self.context
+            .check_state_key_replicated(state_key, ledger_info)?;
+
+        let state_value_with_proof = self
+            .context
+            .get_state_value_with_proof(state_key)
+            .context(format!("Failed to query DB to prove {:?}", state_key))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_bcs((state_value_with_proof, ledger_info, BasicResponseStatus::Ok))
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
@@ -332,6 +491,8 @@ impl StateApi {
         let (ledger_info, ledger_version, state_view) = self
             .context
             .state_view(ledger_version.map(|inner| inner.0))?;
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let bytes = state_view
             .get_state_value_bytes(&state_key)
             .context(format!("Failed to query DB to check for {:?}", state_key))
@@ -420,6 +581,8 @@ impl StateApi {
 
         // Retrieve value from the state key
         let state_key = StateKey::table_item(TableHandle(table_handle.into()), raw_key);
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let bytes = state_view
             .get_state_value_bytes(&state_key)
             .context(format!(
@@ -475,6 +638,8 @@ impl StateApi {
             TableHandle(table_handle.into()),
             table_item_request.key.0.clone(),
         );
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let bytes = state_view
             .get_state_value_bytes(&state_key)
             .context(format!(
@@ -535,6 +700,8 @@ impl StateApi {
                     &ledger_info,
                 )
             })?;
+        self.context
+            .check_state_key_replicated(&state_key, &ledger_info)?;
         let state_value = state_view
             .get_state_value(&state_key)
             .context(format!("Failed fetching state value. key: {}", request.key,))
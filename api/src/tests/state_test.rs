@@ -3,13 +3,22 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::mime_types::BCS;
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
-use move_core_types::account_address::AccountAddress;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::AccountResource,
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValueWithProof, table::TableHandle},
+    transaction::SignedTransaction,
+};
+use move_core_types::{account_address::AccountAddress, move_resource::MoveStructType};
 use move_package::BuildConfig;
 use serde::Serialize;
 use serde_json::{json, Value};
 use std::{convert::TryInto, path::PathBuf};
+use warp::http::header::ACCEPT;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -95,6 +104,163 @@ async fn test_get_account_resource_with_version_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path(&get_account_resource_with_proof(
+                    &account.address().to_hex_literal(),
+                    "0x1::account::Account",
+                )))
+                .header(ACCEPT, BCS),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let proof: StateValueWithProof = bcs::from_bytes(resp.body()).unwrap();
+
+    // The proof is against the latest ledger info and proves the account resource at the latest
+    // state snapshot
+    let latest_ledger_info = context.db.get_latest_ledger_info().unwrap();
+    assert_eq!(proof.ledger_info_with_signatures, latest_ledger_info);
+    let (snapshot_version, _) = context
+        .db
+        .get_state_snapshot_before(latest_ledger_info.ledger_info().version() + 1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proof.version(), snapshot_version);
+    let state_key = StateKey::access_path(
+        AccessPath::resource_access_path(account.address(), AccountResource::struct_tag()).unwrap(),
+    );
+    proof
+        .verify(latest_ledger_info.ledger_info(), &state_key)
+        .unwrap();
+    assert_eq!(
+        proof.state_value,
+        context
+            .db
+            .get_state_value_by_version(&state_key, proof.version())
+            .unwrap()
+    );
+
+    // The proof doesn't verify for another key
+    let other_state_key = StateKey::access_path(
+        AccessPath::resource_access_path(AccountAddress::ONE, AccountResource::struct_tag())
+            .unwrap(),
+    );
+    assert!(proof
+        .verify(latest_ledger_info.ledger_info(), &other_state_key)
+        .is_err());
+
+    // JSON isn't supported
+    context
+        .expect_status_code(403)
+        .get(&get_account_resource_with_proof(
+            &account.address().to_hex_literal(),
+            "0x1::account::Account",
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_raw_table_item_with_proof_buffered_commits() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let creator = &mut ctx.gen_account();
+    let txn = ctx.mint_user_account(creator).await;
+    let collection_txn = create_collection(ctx, creator, "collection name");
+    ctx.commit_block(&vec![txn, collection_txn]).await;
+
+    // The state of this block is only buffered, so the latest state snapshot is behind the
+    // latest ledger info
+    let other_collection_txn = create_collection(ctx, creator, "other collection name");
+    ctx.commit_block_ext(&vec![other_collection_txn], false)
+        .await;
+    let latest_ledger_info = ctx.db.get_latest_ledger_info().unwrap();
+    let (snapshot_version, _) = ctx
+        .db
+        .get_state_snapshot_before(latest_ledger_info.ledger_info().version() + 1)
+        .unwrap()
+        .unwrap();
+    assert!(snapshot_version < latest_ledger_info.ledger_info().version());
+
+    let collections = ctx
+        .api_get_account_resource(creator.address(), "0x3", "token", "Collections")
+        .await;
+    let table_handle = AccountAddress::from_hex_literal(
+        collections["data"]["collection_data"]["handle"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+
+    // The collection created before the snapshot is proven at the snapshot, against the latest
+    // ledger info
+    let key = bcs::to_bytes("collection name").unwrap();
+    let proof = get_raw_table_item_with_proof(ctx, table_handle, &key).await;
+    assert_eq!(proof.ledger_info_with_signatures, latest_ledger_info);
+    assert_eq!(proof.version(), snapshot_version);
+    let state_key = StateKey::table_item(TableHandle(table_handle), key);
+    proof
+        .verify(latest_ledger_info.ledger_info(), &state_key)
+        .unwrap();
+    assert!(proof.state_value.is_some());
+    assert_eq!(
+        proof.state_value,
+        ctx.db
+            .get_state_value_by_version(&state_key, snapshot_version)
+            .unwrap()
+    );
+
+    // The collection created after the snapshot is proven absent at the snapshot
+    let key = bcs::to_bytes("other collection name").unwrap();
+    let proof = get_raw_table_item_with_proof(ctx, table_handle, &key).await;
+    assert_eq!(proof.version(), snapshot_version);
+    proof
+        .verify(
+            latest_ledger_info.ledger_info(),
+            &StateKey::table_item(TableHandle(table_handle), key),
+        )
+        .unwrap();
+    assert!(proof.state_value.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_state_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path("/state_proof?known_version=0"))
+                .header(ACCEPT, BCS),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let state_proof: StateProof = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        state_proof.latest_ledger_info_w_sigs(),
+        &context.db.get_latest_ledger_info().unwrap()
+    );
+
+    // A known version ahead of the ledger is rejected
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path("/state_proof?known_version=100000000"))
+                .header(ACCEPT, BCS),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -309,6 +475,44 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+fn get_account_resource_with_proof(address: &str, struct_tag: &str) -> String {
+    format!("/accounts/{}/resource/{}/proof", address, struct_tag)
+}
+
+fn create_collection(
+    ctx: &TestContext,
+    creator: &mut LocalAccount,
+    collection_name: &str,
+) -> SignedTransaction {
+    creator.sign_with_transaction_builder(ctx.transaction_factory().payload(
+        aptos_token_stdlib::token_create_collection_script(
+            collection_name.to_owned().into_bytes(),
+            "description".to_owned().into_bytes(),
+            "uri".to_owned().into_bytes(),
+            20_000_000,
+            vec![false, false, false],
+        ),
+    ))
+}
+
+async fn get_raw_table_item_with_proof(
+    ctx: &TestContext,
+    table_handle: AccountAddress,
+    key: &[u8],
+) -> StateValueWithProof {
+    let resp = ctx
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path(&ctx.prepend_path(&format!("/tables/{}/raw_item/proof", table_handle)))
+                .header(ACCEPT, BCS)
+                .json(&json!({ "key": format!("0x{}", hex::encode(key)) })),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    bcs::from_bytes(resp.body()).unwrap()
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
@@ -5,6 +5,7 @@
 use super::new_test_context;
 use crate::tests::new_test_context_with_config;
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_types::mime_types::BCS;
 use aptos_config::config::NodeConfig;
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
@@ -12,11 +13,12 @@ use aptos_crypto::{
     PrivateKey, SigningKey, Uniform,
 };
 use aptos_sdk::types::LocalAccount;
+use aptos_storage_interface::DbReader;
 use aptos_types::{
     account_address::AccountAddress,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
-        EntryFunction, Script, SignedTransaction,
+        EntryFunction, Script, SignedTransaction, TransactionWithLedgerInfoProof,
     },
     utility_coin::APTOS_COIN_TYPE,
 };
@@ -29,6 +31,7 @@ use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json::json;
 use std::{path::PathBuf, time::Duration};
 use tokio::time::sleep;
+use warp::http::header::ACCEPT;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_deserialize_genesis_transaction() {
@@ -202,7 +205,7 @@ async fn test_multi_agent_signed_transaction() {
     // Create a new account with a multi-agent signer
     let txn = root_account.sign_multi_agent_with_transaction_builder(
         vec![&secondary],
-        factory.create_user_account(account.public_key()),
+        factory.create_user_account(account.public_key().unwrap()),
     );
 
     let body = bcs::to_bytes(&txn).unwrap();
@@ -385,6 +388,52 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_by_version_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path("/transactions/by_version/2/proof"))
+                .header(ACCEPT, BCS),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let proof: TransactionWithLedgerInfoProof = bcs::from_bytes(resp.body()).unwrap();
+
+    // The transaction and its events are proven against the latest ledger info
+    let latest_ledger_info = context.db.get_latest_ledger_info().unwrap();
+    assert_eq!(proof.ledger_info_with_signatures, latest_ledger_info);
+    proof.verify(latest_ledger_info.ledger_info(), 2).unwrap();
+    assert!(proof.transaction_with_proof.events.is_some());
+    proof
+        .transaction_with_proof
+        .verify_user_txn(
+            latest_ledger_info.ledger_info(),
+            2,
+            txn.sender(),
+            txn.sequence_number(),
+        )
+        .unwrap();
+    assert!(proof.verify(latest_ledger_info.ledger_info(), 1).is_err());
+
+    // Versions ahead of the ledger are not found
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path("/transactions/by_version/10000/proof"))
+                .header(ACCEPT, BCS),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -475,6 +524,7 @@ async fn test_signing_message_with_payload(
         .root_account()
         .await
         .private_key()
+        .unwrap()
         .sign_arbitrary_message(signing_msg.inner());
     let expected_sig = match txn.authenticator() {
         TransactionAuthenticator::Ed25519 {
@@ -488,7 +538,7 @@ async fn test_signing_message_with_payload(
     // assert transaction can be submitted into mempool and execute.
     body["signature"] = json!({
         "type": "ed25519_signature",
-        "public_key": format!("0x{}", hex::encode(sender.public_key().to_bytes())),
+        "public_key": format!("0x{}", hex::encode(sender.public_key().unwrap().to_bytes())),
         "signature": format!("0x{}", hex::encode(sig.to_bytes())),
     });
 
@@ -12,8 +12,8 @@ use crate::{
     page::Page,
     response::{
         api_disabled, api_forbidden, transaction_not_found_by_hash,
-        transaction_not_found_by_version, BadRequestError, BasicError, BasicErrorWith404,
-        BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
+        transaction_not_found_by_version, version_pruned, BadRequestError, BasicError,
+        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         InsufficientStorageError, InternalError,
     },
     ApiTags,
@@ -22,7 +22,7 @@ use anyhow::{anyhow, Context as AnyhowContext};
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
+    HexEncodedBytes, LedgerInfo, MoveType, MoveValue, PendingTransaction, SubmitTransactionRequest,
     Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
@@ -46,7 +46,7 @@ use poem_openapi::{
 };
 use std::sync::Arc;
 
-generate_success_response!(SubmitTransactionResponse, (202, Accepted));
+generate_success_response!(SubmitTransactionResponse, (202, Accepted), (200, Replaced));
 
 generate_error_response!(
     SubmitTransactionError,
@@ -228,6 +228,40 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get transaction by version with proof
+    ///
+    /// Retrieves a transaction by a given version, with its events and the proof of both against
+    /// the latest ledger info with signatures. The response is a BCS encoded
+    /// `TransactionWithLedgerInfoProof`, which can be verified by clients that don't trust the
+    /// node, e.g., light clients. If the version has been pruned, a 410 will be returned.
+    ///
+    /// Only BCS is supported as an AcceptType.
+    #[oai(
+        path = "/transactions/by_version/:txn_version/proof",
+        method = "get",
+        operation_id = "get_transaction_by_version_with_proof",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transaction_by_version_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Version of transaction to retrieve
+        txn_version: Path<U64>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_transaction_by_version_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get transaction by version with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get transaction by version with proof", &accept_type)?;
+
+        self.get_transaction_by_version_with_proof_inner(txn_version.0)
+    }
+
     /// Get account transactions
     ///
     /// Retrieves on-chain committed transactions from an account. If the start
@@ -285,6 +319,10 @@ impl TransactionsApi {
     /// To submit a transaction as BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
     /// Make sure to use the `application/x.aptos.signed_transaction+bcs` Content-Type.
+    ///
+    /// If a transaction with the same sender and sequence number is already pending,
+    /// the submission replaces it if it raises the gas unit price by enough. In that
+    /// case the response status is 200 (Replaced) instead of 202 (Accepted).
     // TODO: Point to examples of both of these flows, in multiple languages.
     #[oai(
         path = "/transactions",
@@ -312,6 +350,8 @@ impl TransactionsApi {
         self.context
             .check_api_output_enabled("Submit transaction", &accept_type)?;
         let ledger_info = self.context.get_latest_ledger_info()?;
+        self.context
+            .check_state_fully_replicated("Submit transaction", &ledger_info)?;
         let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
         self.create(&accept_type, &ledger_info, signed_transaction)
             .await
@@ -365,6 +405,8 @@ impl TransactionsApi {
         self.context
             .check_api_output_enabled("Submit batch transactions", &accept_type)?;
         let ledger_info = self.context.get_latest_ledger_info()?;
+        self.context
+            .check_state_fully_replicated("Submit batch transactions", &ledger_info)?;
         let signed_transactions_batch = self.get_signed_transactions_batch(&ledger_info, data)?;
         if self.context.max_submit_transaction_batch_size() < signed_transactions_batch.len() {
             return Err(SubmitTransactionError::bad_request_with_code(
@@ -429,6 +471,8 @@ impl TransactionsApi {
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
         let ledger_info = self.context.get_latest_ledger_info()?;
+        self.context
+            .check_state_fully_replicated("Simulate transaction", &ledger_info)?;
         let mut signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
 
         let estimated_gas_unit_price = match (
@@ -456,14 +500,14 @@ impl TransactionsApi {
                 / u64::from(gas_params.txn.gas_unit_scaling_factor);
             let max_number_of_gas_units = u64::from(gas_params.txn.maximum_number_of_gas_units);
 
-            // Retrieve account balance to determine max gas available
+            // Retrieve the balance of the account paying for gas to determine max gas available
+            let gas_payer = signed_transaction
+                .authenticator_ref()
+                .fee_payer_address()
+                .unwrap_or_else(|| signed_transaction.sender());
             let account_state = self
                 .context
-                .get_account_state(
-                    signed_transaction.sender(),
-                    ledger_info.version(),
-                    &ledger_info,
-                )?
+                .get_account_state(gas_payer, ledger_info.version(), &ledger_info)?
                 .ok_or_else(|| {
                     SubmitTransactionError::bad_request_with_code(
                         "Account not found",
@@ -474,12 +518,7 @@ impl TransactionsApi {
             let coin_store: CoinStoreResource = account_state
                 .get_coin_store_resource()
                 .and_then(|inner| {
-                    inner.ok_or_else(|| {
-                        anyhow!(
-                            "No coin store found for account {}",
-                            signed_transaction.sender()
-                        )
-                    })
+                    inner.ok_or_else(|| anyhow!("No coin store found for account {}", gas_payer))
                 })
                 .map_err(|err| {
                     SubmitTransactionError::internal_with_code(
@@ -711,6 +750,39 @@ impl TransactionsApi {
             .await
     }
 
+    fn get_transaction_by_version_with_proof_inner(
+        &self,
+        version: U64,
+    ) -> BasicResultWith404<MoveValue> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if version.0 > ledger_info.version() {
+            return Err(transaction_not_found_by_version(version.0, &ledger_info));
+        } else if version.0 < ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(version.0, &ledger_info));
+        }
+
+        let transaction_with_proof = self
+            .context
+            .get_transaction_with_proof(version.0)
+            .context(format!(
+                "Failed to get transaction with proof by version {}",
+                version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_bcs((
+            transaction_with_proof,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
     /// Converts a transaction into the outgoing type
     async fn get_transaction_inner(
         &self,
@@ -998,8 +1070,12 @@ impl TransactionsApi {
         }
     }
 
-    /// Submits a single transaction, and converts mempool codes to errors
-    async fn create_internal(&self, txn: SignedTransaction) -> Result<(), AptosError> {
+    /// Submits a single transaction, and converts mempool codes to errors.
+    /// On success, returns whether the transaction was accepted or replaced a pending one.
+    async fn create_internal(
+        &self,
+        txn: SignedTransaction,
+    ) -> Result<MempoolStatusCode, AptosError> {
         let (mempool_status, vm_status_opt) = self
             .context
             .submit_transaction(txn)
@@ -1009,7 +1085,7 @@ impl TransactionsApi {
                 aptos_api_types::AptosError::new_with_error_code(err, AptosErrorCode::InternalError)
             })?;
         match mempool_status.code {
-            MempoolStatusCode::Accepted => Ok(()),
+            MempoolStatusCode::Accepted | MempoolStatusCode::Replaced => Ok(mempool_status.code),
             MempoolStatusCode::MempoolIsFull | MempoolStatusCode::TooManyTransactions => {
                 Err(AptosError::new_with_error_code(
                     &mempool_status.message,
@@ -1058,45 +1134,45 @@ impl TransactionsApi {
         txn: SignedTransaction,
     ) -> SubmitTransactionResult<PendingTransaction> {
         match self.create_internal(txn.clone()).await {
-            Ok(()) => match accept_type {
-                AcceptType::Json => {
-                    let state_view = self
-                        .context
-                        .latest_state_view()
-                        .context("Failed to read latest state checkpoint from DB")
-                        .map_err(|e| {
-                            SubmitTransactionError::internal_with_code(
-                                e,
-                                AptosErrorCode::InternalError,
-                                ledger_info,
-                            )
-                        })?;
-                    let resolver = state_view.as_move_resolver();
-
-                    // We provide the pending transaction so that users have the hash associated
-                    let pending_txn = resolver
-                            .as_converter(self.context.db.clone())
-                            .try_into_pending_transaction_poem(txn)
-                            .context("Failed to build PendingTransaction from mempool response, even though it said the request was accepted")
-                            .map_err(|err| SubmitTransactionError::internal_with_code(
-                                err,
-                                AptosErrorCode::InternalError,
-                                ledger_info,
-                            ))?;
-                    SubmitTransactionResponse::try_from_json((
-                        pending_txn,
-                        ledger_info,
-                        SubmitTransactionResponseStatus::Accepted,
-                    ))
-                },
-                // With BCS, we don't return the pending transaction for efficiency, because there
-                // is no new information.  The hash can be retrieved by hashing the original
-                // transaction.
-                AcceptType::Bcs => SubmitTransactionResponse::try_from_bcs((
-                    (),
-                    ledger_info,
-                    SubmitTransactionResponseStatus::Accepted,
-                )),
+            Ok(mempool_status_code) => {
+                let status = match mempool_status_code {
+                    MempoolStatusCode::Replaced => SubmitTransactionResponseStatus::Replaced,
+                    _ => SubmitTransactionResponseStatus::Accepted,
+                };
+                match accept_type {
+                    AcceptType::Json => {
+                        let state_view = self
+                            .context
+                            .latest_state_view()
+                            .context("Failed to read latest state checkpoint from DB")
+                            .map_err(|e| {
+                                SubmitTransactionError::internal_with_code(
+                                    e,
+                                    AptosErrorCode::InternalError,
+                                    ledger_info,
+                                )
+                            })?;
+                        let resolver = state_view.as_move_resolver();
+
+                        // We provide the pending transaction so that users have the hash associated
+                        let pending_txn = resolver
+                                .as_converter(self.context.db.clone())
+                                .try_into_pending_transaction_poem(txn)
+                                .context("Failed to build PendingTransaction from mempool response, even though it said the request was accepted")
+                                .map_err(|err| SubmitTransactionError::internal_with_code(
+                                    err,
+                                    AptosErrorCode::InternalError,
+                                    ledger_info,
+                                ))?;
+                        SubmitTransactionResponse::try_from_json((pending_txn, ledger_info, status))
+                    },
+                    // With BCS, we don't return the pending transaction for efficiency, because there
+                    // is no new information.  The hash can be retrieved by hashing the original
+                    // transaction.
+                    AcceptType::Bcs => {
+                        SubmitTransactionResponse::try_from_bcs(((), ledger_info, status))
+                    },
+                }
             },
             Err(error) => match error.error_code {
                 AptosErrorCode::InternalError => Err(
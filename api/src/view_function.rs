@@ -53,6 +53,8 @@ impl ViewFunctionApi {
             .get_latest_ledger_info_and_verify_lookup_version(
                 ledger_version.map(|inner| inner.0),
             )?;
+        self.context
+            .check_state_fully_replicated("View function", &ledger_info)?;
 
         let state_view = self.context.latest_state_view_poem(&ledger_info)?;
         let resolver = state_view.as_move_resolver();
@@ -513,7 +513,7 @@ impl TestContext {
         let factory = self.transaction_factory();
         creator.sign_with_transaction_builder(
             factory
-                .create_user_account(account.public_key())
+                .create_user_account(account.public_key().unwrap())
                 .expiration_timestamp_secs(u64::MAX),
         )
     }
@@ -527,9 +527,12 @@ impl TestContext {
             .sequence_number(root_account.sequence_number())
             .build();
         let invalid_key = AccountKey::generate(self.rng());
-        txn.sign(invalid_key.private_key(), root_account.public_key().clone())
-            .unwrap()
-            .into_inner()
+        txn.sign(
+            invalid_key.private_key(),
+            root_account.public_key().unwrap().clone(),
+        )
+        .unwrap()
+        .into_inner()
     }
 
     pub fn get_latest_ledger_info(&self) -> aptos_api_types::LedgerInfo {
@@ -588,6 +591,13 @@ impl TestContext {
     }
 
     pub async fn commit_block(&mut self, signed_txns: &[SignedTransaction]) {
+        self.commit_block_ext(signed_txns, true).await
+    }
+
+    /// Commits a block, persisting its state right away only if `sync_commit` is set. Otherwise
+    /// the state updates are buffered as on a live node, so the state merkle tree is only
+    /// persisted at the state snapshot versions.
+    pub async fn commit_block_ext(&mut self, signed_txns: &[SignedTransaction], sync_commit: bool) {
         let metadata = self.new_block_metadata();
         let timestamp = metadata.timestamp_usecs();
         let txns: Vec<Transaction> = std::iter::once(Transaction::BlockMetadata(metadata.clone()))
@@ -622,9 +632,10 @@ impl TestContext {
         }
 
         self.executor
-            .commit_blocks(
+            .commit_blocks_ext(
                 vec![metadata.id()],
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len()),
+                sync_commit,
             )
             .unwrap();
 
@@ -784,11 +795,12 @@ impl TestContext {
 
         let sig = account
             .private_key()
+            .unwrap()
             .sign_arbitrary_message(signing_msg.inner());
 
         request["signature"] = json!({
             "type": "ed25519_signature",
-            "public_key": HexEncodedBytes::from(account.public_key().to_bytes().to_vec()),
+            "public_key": HexEncodedBytes::from(account.public_key().unwrap().to_bytes().to_vec()),
             "signature": HexEncodedBytes::from(sig.to_bytes().to_vec()),
         });
 
@@ -854,10 +866,11 @@ impl TestContext {
 
         let sig = random_account
             .private_key()
+            .unwrap()
             .sign_arbitrary_message(signing_msg.inner());
         request["signature"] = json!({
             "type": "ed25519_signature",
-            "public_key": HexEncodedBytes::from(sender.public_key().to_bytes().to_vec()),
+            "public_key": HexEncodedBytes::from(sender.public_key().unwrap().to_bytes().to_vec()),
             "signature": HexEncodedBytes::from(sig.to_bytes().to_vec()),
         });
 
@@ -86,6 +86,8 @@ pub enum AptosErrorCode {
     VersionPruned = 200,
     /// Block is fully or partially pruned
     BlockPruned = 201,
+    /// State value is not replicated by this (partially replicated) node
+    StateValueNotReplicated = 202,
 
     /// The API's inputs were invalid
     InvalidInput = 300,
@@ -6,7 +6,7 @@ use aptos_gas::{
     AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters, StandardGasMeter,
     LATEST_GAS_FEATURE_VERSION,
 };
-use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_gas_profiling::{GasProfiler, GasReport, TransactionGasLog};
 use aptos_resource_viewer::{AnnotatedAccountStateBlob, AptosValueAnnotator};
 use aptos_rest_client::Client;
 use aptos_state_view::TStateView;
@@ -62,6 +62,9 @@ impl AptosDebugger {
             .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
     }
 
+    /// Executes the transaction with the gas profiler enabled. The returned log can be rendered
+    /// as flamegraphs or, through `TransactionGasLog::to_report`, as Markdown/HTML/JSON reports,
+    /// see `profile_gas_of_past_transactions`.
     pub fn execute_transaction_at_version_with_gas_profiler(
         &self,
         version: Version,
@@ -133,6 +136,42 @@ impl AptosDebugger {
         Ok(ret)
     }
 
+    /// Replays each user transaction in the range on its own with the gas profiler, and
+    /// summarizes its gas usage in a report. Other transactions, and user transactions with
+    /// payloads the profiler doesn't support, are skipped.
+    pub async fn profile_gas_of_past_transactions(
+        &self,
+        begin: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, GasReport)>> {
+        let (txns, _txn_infos) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let mut ret = vec![];
+        for (idx, txn) in txns.into_iter().enumerate() {
+            let version = begin + idx as Version;
+            if let Transaction::UserTransaction(txn) = txn {
+                // Same as the profiler, only scripts and entry functions are supported.
+                if !matches!(
+                    txn.payload(),
+                    TransactionPayload::Script(_) | TransactionPayload::EntryFunction(_)
+                ) {
+                    println!(
+                        "Skipping transaction {}, its payload can't be profiled",
+                        version
+                    );
+                    continue;
+                }
+                let (_status, _output, gas_log) =
+                    self.execute_transaction_at_version_with_gas_profiler(version, txn)?;
+                ret.push((version, gas_log.to_report()));
+            }
+        }
+        Ok(ret)
+    }
+
     fn print_mismatches(
         txn_outputs: &[TransactionOutput],
         expected_txn_infos: &[TransactionInfo],
@@ -29,6 +29,11 @@ pub struct Argument {
 
     #[clap(long, default_value = "1")]
     concurrency_level: usize,
+
+    /// Instead of executing the transactions as blocks, replay each user transaction on its own
+    /// with the gas profiler, and save its gas report as Markdown, HTML and JSON into this dir.
+    #[clap(long, parse(from_os_str))]
+    gas_report_dir: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -44,6 +49,25 @@ async fn main() -> Result<()> {
         Target::DB { path } => AptosDebugger::db(path)?,
     };
 
+    if let Some(dir) = args.gas_report_dir {
+        std::fs::create_dir_all(&dir)?;
+        for (version, report) in debugger
+            .profile_gas_of_past_transactions(args.begin_version, args.limit)
+            .await?
+        {
+            let path = |extension: &str| dir.join(format!("txn-{}.report.{}", version, extension));
+            std::fs::write(path("md"), report.to_markdown())?;
+            std::fs::write(path("html"), report.to_html())?;
+            std::fs::write(path("json"), report.to_json()?)?;
+            println!(
+                "Gas report of transaction {} saved to {}",
+                version,
+                path("{md,html,json}").display()
+            );
+        }
+        return Ok(());
+    }
+
     println!(
         "{:#?}",
         debugger
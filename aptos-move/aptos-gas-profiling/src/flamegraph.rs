@@ -138,7 +138,7 @@ impl<'a> Display for Render<'a, WriteOpType> {
     }
 }
 
-fn strip_trailing_zeros_and_decimal_point(mut s: &str) -> &str {
+pub(crate) fn strip_trailing_zeros_and_decimal_point(mut s: &str) -> &str {
     while let Some(stripped) = s.strip_suffix('0') {
         s = stripped
     }
@@ -4,6 +4,12 @@
 mod flamegraph;
 mod log;
 mod profiler;
+mod render;
+mod report;
 
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
+pub use report::{
+    CostDelta, EventCost, FunctionCost, GasReport, GasReportDiff, InstructionCost, Refunds,
+    ResourceLoadCost, WriteCost,
+};
@@ -0,0 +1,466 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    flamegraph::strip_trailing_zeros_and_decimal_point,
+    report::{CostDelta, GasReport, GasReportDiff},
+};
+use std::fmt::Write;
+
+/// A titled table, rendered the same way into both Markdown and HTML.
+struct Table {
+    title: &'static str,
+    headers: &'static [&'static str],
+    /// The first cell of each row is a name, rendered as code.
+    rows: Vec<Vec<String>>,
+}
+
+struct Document {
+    title: String,
+    summary: Vec<(&'static str, String)>,
+    tables: Vec<Table>,
+}
+
+impl Document {
+    fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# {}\n", self.title).unwrap();
+        for (key, value) in &self.summary {
+            writeln!(out, "- **{}**: {}", key, value).unwrap();
+        }
+
+        for table in &self.tables {
+            writeln!(out, "\n## {}\n", table.title).unwrap();
+            if table.rows.is_empty() {
+                writeln!(out, "_None_").unwrap();
+                continue;
+            }
+            writeln!(out, "| {} |", table.headers.join(" | ")).unwrap();
+            writeln!(
+                out,
+                "|{}",
+                table.headers.iter().map(|_| " --- |").collect::<String>()
+            )
+            .unwrap();
+            for row in &table.rows {
+                let cells = row
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, cell)| {
+                        if idx == 0 {
+                            format!("`{}`", cell)
+                        } else {
+                            cell.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+            }
+        }
+        out
+    }
+
+    fn to_html(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+            escape_html(&self.title)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<style>\
+            body {{ font-family: sans-serif; }} \
+            table {{ border-collapse: collapse; margin-bottom: 1em; }} \
+            th, td {{ border: 1px solid #ccc; padding: 2px 8px; text-align: right; }} \
+            td:first-child {{ text-align: left; font-family: monospace; }}\
+            </style>\n</head>\n<body>"
+        )
+        .unwrap();
+        writeln!(out, "<h1>{}</h1>\n<ul>", escape_html(&self.title)).unwrap();
+        for (key, value) in &self.summary {
+            writeln!(
+                out,
+                "<li><b>{}</b>: {}</li>",
+                escape_html(key),
+                escape_html(value)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</ul>").unwrap();
+
+        for table in &self.tables {
+            writeln!(out, "<h2>{}</h2>", escape_html(table.title)).unwrap();
+            if table.rows.is_empty() {
+                writeln!(out, "<p><i>None</i></p>").unwrap();
+                continue;
+            }
+            writeln!(out, "<table>\n<tr>").unwrap();
+            for header in table.headers {
+                write!(out, "<th>{}</th>", escape_html(header)).unwrap();
+            }
+            writeln!(out, "</tr>").unwrap();
+            for row in &table.rows {
+                write!(out, "<tr>").unwrap();
+                for cell in row {
+                    write!(out, "<td>{}</td>", escape_html(cell)).unwrap();
+                }
+                writeln!(out, "</tr>").unwrap();
+            }
+            writeln!(out, "</table>").unwrap();
+        }
+        writeln!(out, "</body>\n</html>").unwrap();
+        out
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats internal gas as gas units.
+fn gas_units(internal_gas: u64, scaling_factor: u64) -> String {
+    strip_trailing_zeros_and_decimal_point(&format!(
+        "{:.8}",
+        internal_gas as f64 / scaling_factor as f64
+    ))
+    .to_string()
+}
+
+fn signed_gas_units(delta: i128, scaling_factor: u64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!(
+        "{}{}",
+        sign,
+        gas_units(delta.unsigned_abs() as u64, scaling_factor)
+    )
+}
+
+fn signed_octas(delta: i128) -> String {
+    format!("{:+}", delta)
+}
+
+fn percentage(part: u64, total: u64) -> String {
+    if total == 0 {
+        return "-".to_string();
+    }
+    format!("{:.2}%", part as f64 * 100.0 / total as f64)
+}
+
+impl GasReport {
+    fn document(&self) -> Document {
+        let scaling = self.gas_scaling_factor;
+        let total = self.total_execution_and_io_cost;
+
+        Document {
+            title: format!("Gas report for {}", self.entry_point),
+            summary: vec![
+                (
+                    "Execution & IO (gas units)",
+                    gas_units(self.total_execution_and_io_cost, scaling),
+                ),
+                (
+                    "Intrinsic (gas units)",
+                    gas_units(self.intrinsic_cost, scaling),
+                ),
+                ("Storage fee (Octas)", self.total_storage_fee.to_string()),
+                (
+                    "Transaction storage fee (Octas)",
+                    self.txn_storage_fee.to_string(),
+                ),
+            ],
+            tables: vec![
+                Table {
+                    title: "Functions",
+                    headers: &[
+                        "Function",
+                        "Calls",
+                        "Self (gas units)",
+                        "Self %",
+                        "Inclusive (gas units)",
+                    ],
+                    rows: self
+                        .functions
+                        .iter()
+                        .map(|f| {
+                            vec![
+                                if f.is_native {
+                                    format!("{} (native)", f.name)
+                                } else {
+                                    f.name.clone()
+                                },
+                                f.calls.to_string(),
+                                gas_units(f.self_cost, scaling),
+                                percentage(f.self_cost, total),
+                                gas_units(f.inclusive_cost, scaling),
+                            ]
+                        })
+                        .collect(),
+                },
+                Table {
+                    title: "Instructions",
+                    headers: &["Instruction", "Count", "Cost (gas units)", "Cost %"],
+                    rows: self
+                        .instructions
+                        .iter()
+                        .map(|i| {
+                            vec![
+                                i.op.clone(),
+                                i.count.to_string(),
+                                gas_units(i.cost, scaling),
+                                percentage(i.cost, total),
+                            ]
+                        })
+                        .collect(),
+                },
+                Table {
+                    title: "Resource loads",
+                    headers: &["Type", "Count", "Cost (gas units)"],
+                    rows: self
+                        .resource_loads
+                        .iter()
+                        .map(|l| {
+                            vec![
+                                l.ty.clone(),
+                                l.count.to_string(),
+                                gas_units(l.cost, scaling),
+                            ]
+                        })
+                        .collect(),
+                },
+                Table {
+                    title: "Writes",
+                    headers: &[
+                        "Item type",
+                        "Creations",
+                        "Modifications",
+                        "Deletions",
+                        "IO (gas units)",
+                        "Storage fee (Octas)",
+                        "Storage fee %",
+                    ],
+                    rows: self
+                        .writes
+                        .iter()
+                        .map(|w| {
+                            vec![
+                                w.item_type.clone(),
+                                w.creations.to_string(),
+                                w.modifications.to_string(),
+                                w.deletions.to_string(),
+                                gas_units(w.io_cost, scaling),
+                                w.storage_fee.to_string(),
+                                percentage(w.storage_fee, self.total_storage_fee),
+                            ]
+                        })
+                        .collect(),
+                },
+                Table {
+                    title: "Events",
+                    headers: &["Type", "Count", "Storage fee (Octas)"],
+                    rows: self
+                        .events
+                        .iter()
+                        .map(|e| vec![e.ty.clone(), e.count.to_string(), e.storage_fee.to_string()])
+                        .collect(),
+                },
+                Table {
+                    title: "Refunds",
+                    headers: &["Refund", "Amount (Octas)"],
+                    rows: vec![vec![
+                        "event_discount".to_string(),
+                        self.refunds.event_discount.to_string(),
+                    ]],
+                },
+            ],
+        }
+    }
+
+    /// Renders the report as a Markdown document.
+    pub fn to_markdown(&self) -> String {
+        self.document().to_markdown()
+    }
+
+    /// Renders the report as a self-contained HTML page.
+    pub fn to_html(&self) -> String {
+        self.document().to_html()
+    }
+
+    /// Serializes to pretty-printed JSON, for tooling.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl GasReportDiff {
+    fn document(&self) -> Document {
+        let scaling = self.gas_scaling_factor;
+        let gas_rows = |rows: &[CostDelta]| {
+            rows.iter()
+                .map(|row| {
+                    vec![
+                        row.name.clone(),
+                        gas_units(row.before, scaling),
+                        gas_units(row.after, scaling),
+                        signed_gas_units(row.delta(), scaling),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        };
+        let fee_rows = |rows: &[CostDelta]| {
+            rows.iter()
+                .map(|row| {
+                    vec![
+                        row.name.clone(),
+                        row.before.to_string(),
+                        row.after.to_string(),
+                        signed_octas(row.delta()),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        };
+        let title = if self.before_entry_point == self.after_entry_point {
+            format!("Gas report diff for {}", self.after_entry_point)
+        } else {
+            format!(
+                "Gas report diff, {} vs {}",
+                self.before_entry_point, self.after_entry_point
+            )
+        };
+
+        Document {
+            title,
+            summary: vec![
+                (
+                    "Execution & IO (gas units)",
+                    format!(
+                        "{} -> {} ({})",
+                        gas_units(self.total_execution_and_io_cost.before, scaling),
+                        gas_units(self.total_execution_and_io_cost.after, scaling),
+                        signed_gas_units(self.total_execution_and_io_cost.delta(), scaling),
+                    ),
+                ),
+                (
+                    "Storage fee (Octas)",
+                    format!(
+                        "{} -> {} ({})",
+                        self.total_storage_fee.before,
+                        self.total_storage_fee.after,
+                        signed_octas(self.total_storage_fee.delta()),
+                    ),
+                ),
+            ],
+            tables: vec![
+                Table {
+                    title: "Functions (self cost)",
+                    headers: &[
+                        "Function",
+                        "Before (gas units)",
+                        "After (gas units)",
+                        "Change",
+                    ],
+                    rows: gas_rows(&self.functions),
+                },
+                Table {
+                    title: "Instructions",
+                    headers: &[
+                        "Instruction",
+                        "Before (gas units)",
+                        "After (gas units)",
+                        "Change",
+                    ],
+                    rows: gas_rows(&self.instructions),
+                },
+                Table {
+                    title: "Write storage fees",
+                    headers: &["Item type", "Before (Octas)", "After (Octas)", "Change"],
+                    rows: fee_rows(&self.writes),
+                },
+                Table {
+                    title: "Event storage fees",
+                    headers: &["Type", "Before (Octas)", "After (Octas)", "Change"],
+                    rows: fee_rows(&self.events),
+                },
+            ],
+        }
+    }
+
+    /// Renders the diff as a Markdown document.
+    pub fn to_markdown(&self) -> String {
+        self.document().to_markdown()
+    }
+
+    /// Renders the diff as a self-contained HTML page.
+    pub fn to_html(&self) -> String {
+        self.document().to_html()
+    }
+
+    /// Serializes to pretty-printed JSON, for tooling.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::{tests::transfer_log, GasReport};
+
+    #[test]
+    fn test_render_report() {
+        let report = transfer_log(5).to_report();
+
+        let markdown = report.to_markdown();
+        assert!(markdown
+            .starts_with("# Gas report for 0x1::coin::transfer<0x1::aptos_coin::AptosCoin>\n"));
+        assert!(markdown.contains("- **Execution & IO (gas units)**: 15.02\n"));
+        assert!(markdown.contains("- **Storage fee (Octas)**: 560\n"));
+        assert!(markdown.contains(
+            "| `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>` | 1 | 0.4 | 2.66% | 1.52 |\n"
+        ));
+        assert!(markdown.contains("| `0x1::signer::borrow_address (native)` | 1 | 0.07 |"));
+        assert!(markdown.contains("| `LD_U64` | 3 | 0.25 | 1.66% |\n"));
+        assert!(markdown.contains("| `0x1::coin::DepositEvent` | 2 | 40 |\n"));
+        assert!(markdown.contains("| `event_discount` | 10 |\n"));
+        // Tables keep the order of the report, most expensive first.
+        assert!(
+            markdown.find("transfer<").unwrap() < markdown.find("borrow_address").unwrap()
+                && markdown.find("borrow_address").unwrap() < markdown.find("withdraw<").unwrap()
+        );
+
+        let html = report.to_html();
+        assert!(html.contains(
+            "<h1>Gas report for 0x1::coin::transfer&lt;0x1::aptos_coin::AptosCoin&gt;</h1>"
+        ));
+        assert!(html.contains("<td>0x1::signer::borrow_address (native)</td>"));
+        assert!(!html.contains("<0x1"));
+
+        let parsed: GasReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.to_markdown(), markdown);
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let diff = transfer_log(5)
+            .to_report()
+            .diff(&transfer_log(45).to_report());
+
+        let markdown = diff.to_markdown();
+        assert!(markdown.starts_with(
+            "# Gas report diff for 0x1::coin::transfer<0x1::aptos_coin::AptosCoin>\n"
+        ));
+        assert!(markdown.contains("- **Execution & IO (gas units)**: 15.02 -> 15.42 (+0.4)\n"));
+        assert!(markdown.contains("- **Storage fee (Octas)**: 560 -> 560 (+0)\n"));
+        assert!(markdown.contains(
+            "| `0x1::coin::withdraw<0x1::aptos_coin::AptosCoin>` | 0.05 | 0.45 | +0.4 |\n"
+        ));
+        assert!(markdown.contains("## Write storage fees\n\n_None_\n"));
+
+        let html = diff.to_html();
+        assert!(html.contains("<td>+0.4</td>"));
+        assert!(html.contains("<p><i>None</i></p>"));
+    }
+}
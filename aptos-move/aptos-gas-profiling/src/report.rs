@@ -0,0 +1,650 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{CallFrame, ExecutionGasEvent, FrameName, TransactionGasLog, WriteOpType};
+use aptos_types::{
+    access_path::Path,
+    state_store::state_key::{StateKey, StateKeyInner},
+};
+use move_core_types::{
+    identifier::IdentStr,
+    language_storage::{ModuleId, TypeTag},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, ops::Deref};
+
+/// Aggregated cost of a function over all of its calls. Native functions have no instructions,
+/// so their self cost is the cost charged by the native.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FunctionCost {
+    pub name: String,
+    pub is_native: bool,
+    pub calls: u64,
+    /// Cost of the instructions in the function body, in internal gas units.
+    pub self_cost: u64,
+    /// Self cost plus the cost of everything called by the function, in internal gas units.
+    /// Recursive calls are counted once per level.
+    pub inclusive_cost: u64,
+}
+
+/// Aggregated cost of an instruction, in internal gas units.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstructionCost {
+    pub op: String,
+    pub count: u64,
+    pub cost: u64,
+}
+
+/// Aggregated cost of loading resources of a type, in internal gas units.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResourceLoadCost {
+    pub ty: String,
+    pub count: u64,
+    pub cost: u64,
+}
+
+/// IO gas (in internal gas units) and storage fees (in Octas) for writes grouped by the type of
+/// the item written, which is the resource type for resources, `code` for modules and the table
+/// handle for table items.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WriteCost {
+    pub item_type: String,
+    pub creations: u64,
+    pub modifications: u64,
+    pub deletions: u64,
+    pub io_cost: u64,
+    pub storage_fee: u64,
+}
+
+/// Storage fees (in Octas) for events of a type.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventCost {
+    pub ty: String,
+    pub count: u64,
+    pub storage_fee: u64,
+}
+
+/// Amounts (in Octas) taken off the storage fees.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Refunds {
+    /// Discount on event storage fees, from the free quota.
+    pub event_discount: u64,
+}
+
+/// A report summarizing a `TransactionGasLog` in tables, each sorted by cost with the most
+/// expensive entry first.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GasReport {
+    pub entry_point: String,
+    /// Number of internal gas units per gas unit.
+    pub gas_scaling_factor: u64,
+    pub intrinsic_cost: u64,
+    /// Intrinsic, execution and IO costs, in internal gas units.
+    pub total_execution_and_io_cost: u64,
+    /// Storage fees after refunds, in Octas.
+    pub total_storage_fee: u64,
+    pub txn_storage_fee: u64,
+    pub functions: Vec<FunctionCost>,
+    pub instructions: Vec<InstructionCost>,
+    pub resource_loads: Vec<ResourceLoadCost>,
+    pub writes: Vec<WriteCost>,
+    pub events: Vec<EventCost>,
+    pub refunds: Refunds,
+}
+
+#[derive(Default)]
+struct Aggregator {
+    functions: BTreeMap<String, FunctionCost>,
+    instructions: BTreeMap<String, InstructionCost>,
+    resource_loads: BTreeMap<String, ResourceLoadCost>,
+}
+
+impl Aggregator {
+    /// Returns the inclusive cost of the frame.
+    fn visit(&mut self, frame: &CallFrame) -> u64 {
+        let mut self_cost = 0;
+        let mut inclusive_cost = 0;
+
+        for event in &frame.events {
+            use ExecutionGasEvent::*;
+
+            match event {
+                Loc(_) => (),
+                Bytecode { op, cost } => {
+                    let cost = u64::from(*cost);
+                    let entry = self
+                        .instructions
+                        .entry(format!("{:?}", op))
+                        .or_insert_with(|| InstructionCost {
+                            op: format!("{:?}", op),
+                            count: 0,
+                            cost: 0,
+                        });
+                    entry.count += 1;
+                    entry.cost += cost;
+                    self_cost += cost;
+                },
+                Call(inner_frame) => inclusive_cost += self.visit(inner_frame),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                } => {
+                    let cost = u64::from(*cost);
+                    self.record_function(
+                        function_name(module_id, fn_name, ty_args),
+                        true, /* is_native */
+                        cost,
+                        cost,
+                    );
+                    inclusive_cost += cost;
+                },
+                LoadResource { addr: _, ty, cost } => {
+                    let cost = u64::from(*cost);
+                    let entry = self
+                        .resource_loads
+                        .entry(ty.to_string())
+                        .or_insert_with(|| ResourceLoadCost {
+                            ty: ty.to_string(),
+                            count: 0,
+                            cost: 0,
+                        });
+                    entry.count += 1;
+                    entry.cost += cost;
+                    inclusive_cost += cost;
+                },
+            }
+        }
+
+        inclusive_cost += self_cost;
+        self.record_function(
+            frame_name(&frame.name),
+            false, /* is_native */
+            self_cost,
+            inclusive_cost,
+        );
+        inclusive_cost
+    }
+
+    fn record_function(&mut self, name: String, is_native: bool, self_cost: u64, inclusive: u64) {
+        let entry = self
+            .functions
+            .entry(name.clone())
+            .or_insert_with(|| FunctionCost {
+                name,
+                is_native,
+                calls: 0,
+                self_cost: 0,
+                inclusive_cost: 0,
+            });
+        entry.calls += 1;
+        entry.self_cost += self_cost;
+        entry.inclusive_cost += inclusive;
+    }
+}
+
+/// Full name of a function, with addresses shortened but not truncated.
+fn function_name(module_id: &ModuleId, name: &IdentStr, ty_args: &[TypeTag]) -> String {
+    let mut res = format!("{}::{}", module_id.short_str_lossless(), name);
+    if !ty_args.is_empty() {
+        res.push_str(&format!(
+            "<{}>",
+            ty_args
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ));
+    }
+    res
+}
+
+fn frame_name(name: &FrameName) -> String {
+    match name {
+        FrameName::Script => "<script>".to_string(),
+        FrameName::Function {
+            module_id,
+            name,
+            ty_args,
+        } => function_name(module_id, name, ty_args),
+    }
+}
+
+/// The type of the item a state key points to, used to group writes.
+fn item_type(key: &StateKey) -> String {
+    match key.deref() {
+        StateKeyInner::AccessPath(ap) => match ap.get_path() {
+            Path::Code(_) => "code".to_string(),
+            Path::Resource(struct_ty) | Path::ResourceGroup(struct_ty) => struct_ty.to_string(),
+        },
+        StateKeyInner::TableItem { handle, .. } => format!("table_item<{}>", handle.0),
+        StateKeyInner::Raw(_) => "raw".to_string(),
+    }
+}
+
+fn write_entry<'a>(
+    writes: &'a mut BTreeMap<String, WriteCost>,
+    key: &StateKey,
+) -> &'a mut WriteCost {
+    let item_type = item_type(key);
+    writes
+        .entry(item_type.clone())
+        .or_insert_with(|| WriteCost {
+            item_type,
+            ..Default::default()
+        })
+}
+
+fn sorted_by<T>(map: BTreeMap<String, T>, cost: impl Fn(&T) -> u64) -> Vec<T> {
+    let mut res = map.into_values().collect::<Vec<_>>();
+    // Stable sort, so entries with the same cost stay ordered by name.
+    res.sort_by_key(|item| std::cmp::Reverse(cost(item)));
+    res
+}
+
+impl TransactionGasLog {
+    /// Summarizes the log into a report with cost tables.
+    pub fn to_report(&self) -> GasReport {
+        let mut aggregator = Aggregator::default();
+        let execution_cost = aggregator.visit(&self.call_graph);
+
+        let mut writes: BTreeMap<String, WriteCost> = BTreeMap::new();
+        let mut io_cost = 0;
+        for item in &self.write_set_transient {
+            let entry = write_entry(&mut writes, &item.key);
+            entry.io_cost += u64::from(item.cost);
+            io_cost += u64::from(item.cost);
+        }
+        // Writes are counted from the storage fee log, which has exactly one item per write.
+        for item in &self.storage.write_set_storage {
+            let entry = write_entry(&mut writes, &item.key);
+            match item.op_type {
+                WriteOpType::Creation => entry.creations += 1,
+                WriteOpType::Modification => entry.modifications += 1,
+                WriteOpType::Deletion => entry.deletions += 1,
+            }
+            entry.storage_fee += u64::from(item.cost);
+        }
+
+        let mut events: BTreeMap<String, EventCost> = BTreeMap::new();
+        for event in &self.storage.events {
+            let entry = events
+                .entry(event.ty.to_string())
+                .or_insert_with(|| EventCost {
+                    ty: event.ty.to_string(),
+                    count: 0,
+                    storage_fee: 0,
+                });
+            entry.count += 1;
+            entry.storage_fee += u64::from(event.cost);
+        }
+
+        let write_fee: u64 = writes.values().map(|w| w.storage_fee).sum();
+        let event_fee: u64 = events.values().map(|e| e.storage_fee).sum();
+        let txn_storage_fee = u64::from(self.storage.txn_storage);
+        let event_discount = u64::from(self.storage.event_discount);
+        let intrinsic_cost = u64::from(self.intrinsic_cost);
+
+        GasReport {
+            entry_point: frame_name(self.entry_point()),
+            gas_scaling_factor: u64::from(self.gas_scaling_factor),
+            intrinsic_cost,
+            total_execution_and_io_cost: intrinsic_cost + execution_cost + io_cost,
+            total_storage_fee: write_fee + event_fee + txn_storage_fee - event_discount,
+            txn_storage_fee,
+            functions: sorted_by(aggregator.functions, |f| f.self_cost),
+            instructions: sorted_by(aggregator.instructions, |i| i.cost),
+            resource_loads: sorted_by(aggregator.resource_loads, |l| l.cost),
+            writes: sorted_by(writes, |w| w.storage_fee + w.io_cost),
+            events: sorted_by(events, |e| e.storage_fee),
+            refunds: Refunds { event_discount },
+        }
+    }
+}
+
+/// A row comparing a cost before and after a change.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CostDelta {
+    pub name: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl CostDelta {
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+}
+
+/// Compares two gas reports, typically of the same transaction before and after a contract
+/// change. Each table is sorted by the absolute change, with the largest change first, and leaves
+/// out unchanged entries.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GasReportDiff {
+    pub before_entry_point: String,
+    pub after_entry_point: String,
+    pub gas_scaling_factor: u64,
+    pub total_execution_and_io_cost: CostDelta,
+    pub total_storage_fee: CostDelta,
+    /// Function self costs.
+    pub functions: Vec<CostDelta>,
+    pub instructions: Vec<CostDelta>,
+    /// Storage fees by item type.
+    pub writes: Vec<CostDelta>,
+    pub events: Vec<CostDelta>,
+}
+
+fn diff_rows<'a, T: 'a>(
+    before: impl IntoIterator<Item = &'a T>,
+    after: impl IntoIterator<Item = &'a T>,
+    key: impl Fn(&T) -> String,
+    cost: impl Fn(&T) -> u64,
+) -> Vec<CostDelta> {
+    let mut rows: BTreeMap<String, CostDelta> = BTreeMap::new();
+    for (items, is_after) in [
+        (before.into_iter().collect::<Vec<_>>(), false),
+        (after.into_iter().collect::<Vec<_>>(), true),
+    ] {
+        for item in items {
+            let name = key(item);
+            let row = rows.entry(name.clone()).or_insert_with(|| CostDelta {
+                name,
+                before: 0,
+                after: 0,
+            });
+            if is_after {
+                row.after += cost(item);
+            } else {
+                row.before += cost(item);
+            }
+        }
+    }
+
+    let mut res = rows
+        .into_values()
+        .filter(|row| row.before != row.after)
+        .collect::<Vec<_>>();
+    res.sort_by_key(|row| std::cmp::Reverse(row.delta().unsigned_abs()));
+    res
+}
+
+impl GasReport {
+    pub fn diff(&self, after: &GasReport) -> GasReportDiff {
+        GasReportDiff {
+            before_entry_point: self.entry_point.clone(),
+            after_entry_point: after.entry_point.clone(),
+            gas_scaling_factor: after.gas_scaling_factor,
+            total_execution_and_io_cost: CostDelta {
+                name: "execution & io".to_string(),
+                before: self.total_execution_and_io_cost,
+                after: after.total_execution_and_io_cost,
+            },
+            total_storage_fee: CostDelta {
+                name: "storage fee".to_string(),
+                before: self.total_storage_fee,
+                after: after.total_storage_fee,
+            },
+            functions: diff_rows(
+                &self.functions,
+                &after.functions,
+                |f| f.name.clone(),
+                |f| f.self_cost,
+            ),
+            instructions: diff_rows(
+                &self.instructions,
+                &after.instructions,
+                |i| i.op.clone(),
+                |i| i.cost,
+            ),
+            writes: diff_rows(
+                &self.writes,
+                &after.writes,
+                |w| w.item_type.clone(),
+                |w| w.storage_fee,
+            ),
+            events: diff_rows(
+                &self.events,
+                &after.events,
+                |e| e.ty.clone(),
+                |e| e.storage_fee,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::log::{
+        CallFrame, EventStorage, ExecutionGasEvent, StorageFees, TransactionGasLog, WriteOpType,
+        WriteStorage, WriteTransient,
+    };
+    use aptos_gas::{Fee, GasScalingFactor};
+    use aptos_types::{
+        access_path::AccessPath,
+        account_address::AccountAddress,
+        state_store::{state_key::StateKey, table::TableHandle},
+    };
+    use move_binary_format::file_format_common::Opcodes;
+    use move_core_types::{
+        gas_algebra::InternalGas,
+        identifier::Identifier,
+        language_storage::{ModuleId, StructTag, TypeTag},
+    };
+    use std::str::FromStr;
+
+    fn module(name: &str) -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new(name).unwrap())
+    }
+
+    fn bytecode(op: Opcodes, cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op,
+            cost: InternalGas::new(cost),
+        }
+    }
+
+    fn coin_store_key() -> StateKey {
+        StateKey::access_path(
+            AccessPath::resource_access_path(
+                AccountAddress::ONE,
+                StructTag::from_str("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn table_item_key() -> StateKey {
+        StateKey::table_item(TableHandle(AccountAddress::TWO), vec![1])
+    }
+
+    /// A transfer calling `withdraw`, which loads a resource and calls a native.
+    /// Execution costs 152, plus 350 for IO and 1000 intrinsic, with a scaling factor of 100.
+    pub(crate) fn transfer_log(withdraw_cost: u64) -> TransactionGasLog {
+        let coin = TypeTag::from_str("0x1::aptos_coin::AptosCoin").unwrap();
+        let mut withdraw =
+            CallFrame::new_function(module("coin"), Identifier::new("withdraw").unwrap(), vec![
+                coin.clone(),
+            ]);
+        withdraw.events = vec![
+            bytecode(Opcodes::LD_U64, withdraw_cost),
+            ExecutionGasEvent::LoadResource {
+                addr: AccountAddress::ONE,
+                ty: TypeTag::from_str("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap(),
+                cost: InternalGas::new(100),
+            },
+            ExecutionGasEvent::CallNative {
+                module_id: module("signer"),
+                fn_name: Identifier::new("borrow_address").unwrap(),
+                ty_args: vec![],
+                cost: InternalGas::new(7),
+            },
+        ];
+        let mut transfer =
+            CallFrame::new_function(module("coin"), Identifier::new("transfer").unwrap(), vec![
+                coin,
+            ]);
+        transfer.events = vec![
+            bytecode(Opcodes::LD_U64, 10),
+            ExecutionGasEvent::Loc(1),
+            bytecode(Opcodes::CALL, 20),
+            ExecutionGasEvent::Call(withdraw),
+            bytecode(Opcodes::LD_U64, 10),
+        ];
+
+        let deposit_event = TypeTag::from_str("0x1::coin::DepositEvent").unwrap();
+        TransactionGasLog {
+            gas_scaling_factor: GasScalingFactor::new(100),
+            intrinsic_cost: InternalGas::new(1000),
+            call_graph: transfer,
+            write_set_transient: vec![
+                WriteTransient {
+                    key: coin_store_key(),
+                    op_type: WriteOpType::Modification,
+                    cost: InternalGas::new(300),
+                },
+                WriteTransient {
+                    key: table_item_key(),
+                    op_type: WriteOpType::Creation,
+                    cost: InternalGas::new(50),
+                },
+            ],
+            storage: StorageFees {
+                write_set_storage: vec![
+                    WriteStorage {
+                        key: coin_store_key(),
+                        op_type: WriteOpType::Modification,
+                        cost: Fee::new(0),
+                    },
+                    WriteStorage {
+                        key: table_item_key(),
+                        op_type: WriteOpType::Creation,
+                        cost: Fee::new(500),
+                    },
+                ],
+                events: vec![
+                    EventStorage {
+                        ty: deposit_event.clone(),
+                        cost: Fee::new(20),
+                    },
+                    EventStorage {
+                        ty: deposit_event,
+                        cost: Fee::new(20),
+                    },
+                ],
+                event_discount: Fee::new(10),
+                txn_storage: Fee::new(30),
+            },
+        }
+    }
+
+    #[test]
+    fn test_report_aggregates_log() {
+        let report = transfer_log(5).to_report();
+
+        assert_eq!(
+            report.entry_point,
+            "0x1::coin::transfer<0x1::aptos_coin::AptosCoin>"
+        );
+        assert_eq!(report.gas_scaling_factor, 100);
+        assert_eq!(report.intrinsic_cost, 1000);
+        assert_eq!(report.total_execution_and_io_cost, 1000 + 152 + 350);
+        assert_eq!(report.total_storage_fee, 500 + 40 + 30 - 10);
+        assert_eq!(report.txn_storage_fee, 30);
+        assert_eq!(report.refunds.event_discount, 10);
+
+        let functions = report
+            .functions
+            .iter()
+            .map(|f| {
+                (
+                    f.name.as_str(),
+                    f.is_native,
+                    f.calls,
+                    f.self_cost,
+                    f.inclusive_cost,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(functions, vec![
+            (
+                "0x1::coin::transfer<0x1::aptos_coin::AptosCoin>",
+                false,
+                1,
+                40,
+                152
+            ),
+            ("0x1::signer::borrow_address", true, 1, 7, 7),
+            (
+                "0x1::coin::withdraw<0x1::aptos_coin::AptosCoin>",
+                false,
+                1,
+                5,
+                112
+            ),
+        ]);
+
+        let instructions = report
+            .instructions
+            .iter()
+            .map(|i| (i.op.as_str(), i.count, i.cost))
+            .collect::<Vec<_>>();
+        assert_eq!(instructions, vec![("LD_U64", 3, 25), ("CALL", 1, 20)]);
+
+        assert_eq!(report.resource_loads.len(), 1);
+        assert_eq!(report.resource_loads[0].cost, 100);
+
+        let writes = report
+            .writes
+            .iter()
+            .map(|w| {
+                (
+                    w.item_type.as_str(),
+                    w.creations,
+                    w.modifications,
+                    w.io_cost,
+                    w.storage_fee,
+                )
+            })
+            .collect::<Vec<_>>();
+        let table_item_type = format!("table_item<{}>", AccountAddress::TWO);
+        assert_eq!(writes, vec![
+            (table_item_type.as_str(), 1, 0, 50, 500),
+            (
+                "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                0,
+                1,
+                300,
+                0
+            ),
+        ]);
+
+        assert_eq!(report.events.len(), 1);
+        assert_eq!(report.events[0].count, 2);
+        assert_eq!(report.events[0].storage_fee, 40);
+    }
+
+    #[test]
+    fn test_report_diff() {
+        let before = transfer_log(5).to_report();
+        let after = transfer_log(45).to_report();
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.total_execution_and_io_cost.delta(), 40);
+        assert_eq!(diff.total_storage_fee.delta(), 0);
+        let functions = diff
+            .functions
+            .iter()
+            .map(|row| (row.name.as_str(), row.before, row.after))
+            .collect::<Vec<_>>();
+        assert_eq!(functions, vec![(
+            "0x1::coin::withdraw<0x1::aptos_coin::AptosCoin>",
+            5,
+            45
+        )]);
+        assert_eq!(diff.instructions.len(), 1);
+        assert_eq!(diff.instructions[0].delta(), 40);
+        assert!(diff.writes.is_empty());
+        assert!(diff.events.is_empty());
+    }
+}
@@ -40,6 +40,8 @@ pub enum FeatureFlag {
     StorageSlotMetadata,
     ChargeInvariantViolation,
     DelegationPoolPartialGovernanceVoting,
+    Secp256k1EcdsaAuthenticator,
+    FeePayerEnabled,
 }
 
 fn generate_features_blob(writer: &CodeWriter, data: &[u64]) {
@@ -160,6 +162,10 @@ impl From<FeatureFlag> for AptosFeatureFlag {
             FeatureFlag::DelegationPoolPartialGovernanceVoting => {
                 AptosFeatureFlag::DELEGATION_POOL_PARTIAL_GOVERNANCE_VOTING
             },
+            FeatureFlag::Secp256k1EcdsaAuthenticator => {
+                AptosFeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR
+            },
+            FeatureFlag::FeePayerEnabled => AptosFeatureFlag::FEE_PAYER_ENABLED,
         }
     }
 }
@@ -203,6 +209,10 @@ impl From<AptosFeatureFlag> for FeatureFlag {
             AptosFeatureFlag::DELEGATION_POOL_PARTIAL_GOVERNANCE_VOTING => {
                 FeatureFlag::DelegationPoolPartialGovernanceVoting
             },
+            AptosFeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR => {
+                FeatureFlag::Secp256k1EcdsaAuthenticator
+            },
+            AptosFeatureFlag::FEE_PAYER_ENABLED => FeatureFlag::FeePayerEnabled,
         }
     }
 }
@@ -0,0 +1,799 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed Rust bindings for the modules of a compiled Move package.
+//!
+//! For every Move module, the generated code contains a Rust module with:
+//! * a builder for every entry function, returning a `TransactionPayload`;
+//! * an async caller for every view function, going through `Client::view`;
+//! * a serde struct for every resource and event of the module, as well as for the structs of
+//!   the package those refer to, together with the `StructTag` it is keyed by.
+//!
+//! Entry function arguments use the BCS-compatible Rust types (`u64`, `AccountAddress`, ...).
+//! View function arguments and return values, as well as struct fields, use the JSON
+//! representation of the REST API (`U64`, `Address`, `HexEncodedBytes`, ...).
+//!
+//! The generated code depends on the `aptos-sdk`, `serde` and `serde_json` crates.
+
+use crate::common;
+use aptos_types::transaction::{EntryABI, EntryFunctionABI};
+use heck::SnakeCase;
+use move_binary_format::{normalized, CompiledModule};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+};
+use serde_generate::indent::{IndentConfig, IndentedWriter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Result, Write},
+};
+
+/// Names which are in scope in every generated module. Move structs with one of these names are
+/// prefixed with `Move`.
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "AccountAddress",
+    "Box",
+    "Client",
+    "Deserialize",
+    "EntryFunction",
+    "ModuleId",
+    "Option",
+    "Response",
+    "RestError",
+    "Result",
+    "Serialize",
+    "String",
+    "StructTag",
+    "TransactionPayload",
+    "TypeTag",
+    "Vec",
+];
+
+/// Rust keywords which may appear as Move identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "loop", "match", "mod", "move", "mut", "priv", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use", "where",
+    "while", "yield",
+];
+
+/// Output typed bindings for the given modules of a package.
+///
+/// `abis` are used, if available, for the parameter names and doc comments of entry functions.
+/// `view_functions` lists the functions carrying the `#[view]` attribute.
+pub fn output(
+    out: &mut dyn Write,
+    modules: &[&CompiledModule],
+    abis: &[EntryABI],
+    view_functions: &BTreeSet<(ModuleId, Identifier)>,
+) -> Result<()> {
+    let modules: BTreeMap<_, _> = modules
+        .iter()
+        .map(|module| {
+            let module = normalized::Module::new(module);
+            (module.module_id(), module)
+        })
+        .collect();
+    if modules.is_empty() {
+        return Ok(());
+    }
+    let abis = common::entry_function_abis(abis)
+        .into_iter()
+        .map(|abi| ((abi.module_name().clone(), abi.name().to_string()), abi))
+        .collect();
+
+    let mut emitter = BindingsEmitter {
+        out: IndentedWriter::new(out, IndentConfig::Space(4)),
+        structs: BTreeMap::new(),
+        modules,
+        abis,
+        view_functions,
+    };
+    emitter.collect_structs();
+
+    emitter.output_preamble()?;
+    let module_ids: Vec<_> = emitter.modules.keys().cloned().collect();
+    for module_id in module_ids {
+        emitter.output_module(&module_id)?;
+    }
+    Ok(())
+}
+
+/// Shared state for the bindings generator.
+struct BindingsEmitter<'a, T> {
+    /// Writer.
+    out: IndentedWriter<T>,
+    /// The normalized modules of the package.
+    modules: BTreeMap<ModuleId, normalized::Module>,
+    /// The structs to generate, with the type parameters their fields make use of.
+    structs: BTreeMap<(ModuleId, Identifier), Vec<u16>>,
+    /// The ABIs of the entry functions of the package.
+    abis: BTreeMap<(ModuleId, String), EntryFunctionABI>,
+    /// The view functions of the package.
+    view_functions: &'a BTreeSet<(ModuleId, Identifier)>,
+}
+
+impl<'a, T> BindingsEmitter<'a, T>
+where
+    T: Write,
+{
+    /// Collects the resources and events of the package, the package structs appearing in view
+    /// functions, and transitively all package structs their fields refer to.
+    fn collect_structs(&mut self) {
+        let mut pending = vec![];
+        for (module_id, module) in &self.modules {
+            for (name, struct_) in &module.structs {
+                if struct_.abilities.has_key() {
+                    pending.push((module_id.clone(), name.clone()));
+                }
+                for field in &struct_.fields {
+                    visit_type(&field.type_, &mut |ty| {
+                        if let Some(event) = event_handle_type(ty) {
+                            visit_type(event, &mut |ty| {
+                                pending.extend(struct_key(ty));
+                            });
+                        }
+                    });
+                }
+            }
+            for (name, function) in &module.exposed_functions {
+                if self.is_view_function(module_id, name) {
+                    for ty in function.parameters.iter().chain(function.return_.iter()) {
+                        visit_type(ty, &mut |ty| pending.extend(struct_key(ty)));
+                    }
+                }
+            }
+        }
+
+        let mut visited = BTreeSet::new();
+        while let Some(key) = pending.pop() {
+            let struct_ = match self.find_struct(&key) {
+                Some(struct_) if !is_special_struct(&key.0, &key.1) => struct_,
+                _ => continue,
+            };
+            if !visited.insert(key.clone()) {
+                continue;
+            }
+            let mut used_type_params = BTreeSet::new();
+            for field in &struct_.fields {
+                visit_type(&field.type_, &mut |ty| match ty {
+                    normalized::Type::TypeParameter(idx) => {
+                        used_type_params.insert(*idx);
+                    },
+                    _ => pending.extend(struct_key(ty)),
+                });
+            }
+            self.structs
+                .insert(key, used_type_params.into_iter().collect());
+        }
+    }
+
+    fn find_struct(
+        &self,
+        (module_id, name): &(ModuleId, Identifier),
+    ) -> Option<&normalized::Struct> {
+        self.modules
+            .get(module_id)
+            .and_then(|module| module.structs.get(name))
+    }
+
+    fn is_view_function(&self, module_id: &ModuleId, name: &IdentStr) -> bool {
+        self.view_functions
+            .contains(&(module_id.clone(), name.to_owned()))
+    }
+
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"// Typed bindings for a Move package, mapping its entry functions, view functions, resources
+// and events to Rust.
+//
+// This code was generated by compiling the modules of the package with the tool `aptos-sdk-builder`.
+// It requires the `aptos-sdk`, `serde` and `serde_json` crates.
+
+#![allow(dead_code)]
+#![allow(unused_imports)]
+#![allow(clippy::too_many_arguments)]
+
+use serde::{{Deserialize, Serialize}};
+
+/// JSON representation of a Move `0x1::option::Option<T>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveOption<T> {{
+    pub vec: Vec<T>,
+}}
+
+/// JSON representation of a Move `0x1::object::Object<T>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveObject {{
+    pub inner: aptos_sdk::rest_client::aptos_api_types::Address,
+}}"#
+        )
+    }
+
+    fn output_module(&mut self, module_id: &ModuleId) -> Result<()> {
+        let module = self.modules[module_id].clone();
+        writeln!(
+            self.out,
+            "\n/// Bindings for `{}`.",
+            module_id.short_str_lossless()
+        )?;
+        writeln!(self.out, "pub mod {} {{", rust_ident(module.name.as_str()))?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            r#"use aptos_sdk::{{
+    bcs,
+    move_types::{{
+        self,
+        account_address::AccountAddress,
+        ident_str,
+        language_storage::{{ModuleId, StructTag, TypeTag}},
+    }},
+    rest_client::{{aptos_api_types, error::RestError, Client, Response}},
+    types::transaction::{{EntryFunction, TransactionPayload}},
+}};
+use serde::{{Deserialize, Serialize}};
+
+pub const MODULE_ADDRESS: AccountAddress = {};
+
+pub fn module_id() -> ModuleId {{
+    ModuleId::new(MODULE_ADDRESS, ident_str!("{}").to_owned())
+}}"#,
+            quote_address(module_id.address()),
+            module.name,
+        )?;
+
+        for (name, struct_) in &module.structs {
+            let key = (module_id.clone(), name.clone());
+            if let Some(used_type_params) = self.structs.get(&key).cloned() {
+                self.output_struct(&key, struct_, &used_type_params)?;
+            }
+        }
+        for (name, function) in &module.exposed_functions {
+            if function.is_entry {
+                self.output_entry_function(module_id, name, function)?;
+            }
+        }
+        for (name, function) in &module.exposed_functions {
+            if self.is_view_function(module_id, name) {
+                self.output_view_function(module_id, name, function)?;
+            }
+        }
+
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_struct(
+        &mut self,
+        (module_id, name): &(ModuleId, Identifier),
+        struct_: &normalized::Struct,
+        used_type_params: &[u16],
+    ) -> Result<()> {
+        let rust_name = struct_name(name);
+        let generics = quote_generics(used_type_params);
+        let fields = struct_
+            .fields
+            .iter()
+            .map(|field| {
+                let ident = rust_ident(field.name.as_str());
+                format!(
+                    "{}pub {}: {},",
+                    if ident.ends_with('_') {
+                        format!("#[serde(rename = \"{}\")]\n", field.name)
+                    } else {
+                        String::new()
+                    },
+                    ident,
+                    self.quote_json_type(&field.type_, &|idx| Some(format!("T{}", idx)))
+                )
+            })
+            .collect::<Vec<_>>();
+        let kind = if struct_.abilities.has_key() {
+            "Resource"
+        } else {
+            "Struct"
+        };
+        writeln!(
+            self.out,
+            "\n/// {} `{}::{}`.",
+            kind,
+            module_id.short_str_lossless(),
+            name
+        )?;
+        writeln!(
+            self.out,
+            "#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]"
+        )?;
+        writeln!(self.out, "pub struct {}{} {{", rust_name, generics)?;
+        self.out.indent();
+        for field in fields {
+            writeln!(self.out, "{}", field)?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        let is_generic = !struct_.type_parameters.is_empty();
+        writeln!(self.out, "\nimpl{} {}{} {{", generics, rust_name, generics)?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            r#"/// The `StructTag` values of this type are stored and emitted under.
+pub fn struct_tag({}) -> StructTag {{
+    StructTag {{
+        address: MODULE_ADDRESS,
+        module: ident_str!("{}").to_owned(),
+        name: ident_str!("{}").to_owned(),
+        type_params: {},
+    }}
+}}"#,
+            if is_generic {
+                "type_args: Vec<TypeTag>"
+            } else {
+                ""
+            },
+            module_id.name(),
+            name,
+            if is_generic { "type_args" } else { "vec![]" },
+        )?;
+        if struct_.abilities.has_key() {
+            writeln!(
+                self.out,
+                r#"
+/// Fetches the resource stored at `address`, if any.
+pub async fn fetch(
+    client: &Client,
+    address: AccountAddress,{}
+) -> Result<Response<Option<Self>>, RestError>
+where
+    Self: serde::de::DeserializeOwned,
+{{
+    Ok(client
+        .get_account_resource(address, &Self::struct_tag({}).to_string())
+        .await?
+        .and_then(|resource| {{
+            resource
+                .map(|resource| serde_json::from_value(resource.data))
+                .transpose()
+        }})?)
+}}"#,
+                if is_generic {
+                    "\n    type_args: Vec<TypeTag>,"
+                } else {
+                    ""
+                },
+                if is_generic { "type_args" } else { "" },
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_entry_function(
+        &mut self,
+        module_id: &ModuleId,
+        name: &IdentStr,
+        function: &normalized::Function,
+    ) -> Result<()> {
+        let abi = self
+            .abis
+            .get(&(module_id.clone(), name.to_string()))
+            .cloned();
+        let parameters: Vec<_> = function
+            .parameters
+            .iter()
+            .skip_while(|ty| is_signer(ty))
+            .collect();
+        let types = match parameters
+            .iter()
+            .map(|ty| quote_bcs_type(ty))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(types) => types,
+            None => {
+                return writeln!(
+                    self.out,
+                    "\n// Skipped entry function `{}`: it takes arguments which cannot be passed in a transaction.",
+                    name
+                );
+            },
+        };
+        let type_arg_names: Vec<_> = (0..function.type_parameters.len())
+            .map(|idx| match &abi {
+                Some(abi) if abi.ty_args().len() == function.type_parameters.len() => {
+                    rust_ident(&abi.ty_args()[idx].name().to_snake_case())
+                },
+                _ => format!("type_arg{}", idx),
+            })
+            .collect();
+        let arg_names: Vec<_> = (0..types.len())
+            .map(|idx| match &abi {
+                Some(abi) if abi.args().len() == types.len() => {
+                    rust_ident(&abi.args()[idx].name().to_snake_case())
+                },
+                _ => format!("arg{}", idx),
+            })
+            .collect();
+
+        let doc = abi
+            .as_ref()
+            .map(|abi| common::prepare_doc_string(abi.doc()))
+            .filter(|doc| !doc.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "Calls the entry function `{}::{}`.",
+                    module_id.short_str_lossless(),
+                    name
+                )
+            });
+        self.output_comment(&doc)?;
+        let params = type_arg_names
+            .iter()
+            .map(|name| format!("{}: TypeTag", name))
+            .chain(
+                arg_names
+                    .iter()
+                    .zip(types.iter())
+                    .map(|(name, ty)| format!("{}: {}", name, ty)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            self.out,
+            r#"pub fn {}({}) -> TransactionPayload {{
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        self::module_id(),
+        ident_str!("{}").to_owned(),
+        vec![{}],
+        vec![{}],
+    ))
+}}"#,
+            rust_ident(name.as_str()),
+            params,
+            name,
+            type_arg_names.join(", "),
+            arg_names
+                .iter()
+                .map(|name| format!("bcs::to_bytes(&{}).unwrap()", name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    fn output_view_function(
+        &mut self,
+        module_id: &ModuleId,
+        name: &IdentStr,
+        function: &normalized::Function,
+    ) -> Result<()> {
+        // Type parameters of functions are not known statically, hence fall back to raw JSON.
+        let json_type = |ty: &normalized::Type| self.quote_json_type(ty, &|_| None);
+        let type_arg_names: Vec<_> = (0..function.type_parameters.len())
+            .map(|idx| format!("type_arg{}", idx))
+            .collect();
+        let args: Vec<_> = function
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| (format!("arg{}", idx), json_type(ty)))
+            .collect();
+        let returns: Vec<_> = function.return_.iter().map(json_type).collect();
+        let (return_type, decode) = match returns.len() {
+            0 => (
+                "()".to_string(),
+                "Ok::<_, serde_json::Error>(())".to_string(),
+            ),
+            1 => (
+                returns[0].clone(),
+                format!(
+                    "serde_json::from_value::<({},)>(serde_json::Value::Array(values)).map(|(value,)| value)",
+                    returns[0]
+                ),
+            ),
+            _ => {
+                let tuple = format!("({})", returns.join(", "));
+                (
+                    tuple.clone(),
+                    format!(
+                        "serde_json::from_value::<{}>(serde_json::Value::Array(values))",
+                        tuple
+                    ),
+                )
+            },
+        };
+        // An entry function which is also a view function keeps its name for the builder.
+        let rust_name = if function.is_entry {
+            format!("view_{}", name)
+        } else {
+            rust_ident(name.as_str())
+        };
+
+        self.output_comment(&format!(
+            "Calls the view function `{}::{}`.",
+            module_id.short_str_lossless(),
+            name
+        ))?;
+        let params = std::iter::once("client: &Client".to_string())
+            .chain(
+                type_arg_names
+                    .iter()
+                    .map(|name| format!("{}: TypeTag", name)),
+            )
+            .chain(args.iter().map(|(name, ty)| format!("{}: {}", name, ty)))
+            .chain(std::iter::once("version: Option<u64>".to_string()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            self.out,
+            r#"pub async fn {}({}) -> Result<Response<{}>, RestError> {{
+    let request = aptos_api_types::ViewRequest {{
+        function: aptos_api_types::EntryFunctionId {{
+            module: self::module_id().into(),
+            name: ident_str!("{}").to_owned().into(),
+        }},
+        type_arguments: vec![{}],
+        arguments: vec![{}],
+    }};
+    Ok(client
+        .view(&request, version)
+        .await?
+        .and_then(|{}values| {})?)
+}}"#,
+            rust_name,
+            params,
+            return_type,
+            name,
+            type_arg_names
+                .iter()
+                .map(|name| format!("{}.into()", name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            args.iter()
+                .map(|(name, _)| format!("serde_json::to_value(&{})?", name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if returns.is_empty() { "_" } else { "" },
+            decode,
+        )
+    }
+
+    fn output_comment(&mut self, doc: &str) -> Result<()> {
+        writeln!(self.out)?;
+        for line in doc.lines() {
+            if line.is_empty() {
+                writeln!(self.out, "///")?;
+            } else {
+                writeln!(self.out, "/// {}", line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the Rust type of the JSON representation of `ty` in the REST API. `type_param`
+    /// names the Rust type of a type parameter, if it has one.
+    fn quote_json_type(
+        &self,
+        ty: &normalized::Type,
+        type_param: &dyn Fn(u16) -> Option<String>,
+    ) -> String {
+        use normalized::Type::*;
+        match ty {
+            Bool => "bool".into(),
+            U8 => "u8".into(),
+            U16 => "u16".into(),
+            U32 => "u32".into(),
+            U64 => "aptos_api_types::U64".into(),
+            U128 => "aptos_api_types::U128".into(),
+            U256 => "aptos_api_types::U256".into(),
+            Address | Signer => "aptos_api_types::Address".into(),
+            Vector(inner) => match inner.as_ref() {
+                U8 => "aptos_api_types::HexEncodedBytes".into(),
+                inner => format!("Vec<{}>", self.quote_json_type(inner, type_param)),
+            },
+            Reference(inner) | MutableReference(inner) => self.quote_json_type(inner, type_param),
+            TypeParameter(idx) => type_param(*idx).unwrap_or_else(|| "serde_json::Value".into()),
+            Struct {
+                address,
+                module,
+                name,
+                type_arguments,
+            } => {
+                if is_framework_struct(address, module, name, "string", "String") {
+                    "String".into()
+                } else if is_framework_struct(address, module, name, "option", "Option") {
+                    format!(
+                        "super::MoveOption<{}>",
+                        self.quote_json_type(&type_arguments[0], type_param)
+                    )
+                } else if is_framework_struct(address, module, name, "object", "Object") {
+                    "super::MoveObject".into()
+                } else {
+                    let key = (ModuleId::new(*address, module.clone()), name.clone());
+                    match self.structs.get(&key) {
+                        Some(used_type_params) => {
+                            let type_args = used_type_params
+                                .iter()
+                                .map(|idx| {
+                                    self.quote_json_type(&type_arguments[*idx as usize], type_param)
+                                })
+                                .collect::<Vec<_>>();
+                            format!(
+                                "super::{}::{}{}",
+                                rust_ident(module.as_str()),
+                                struct_name(name),
+                                if type_args.is_empty() {
+                                    String::new()
+                                } else {
+                                    format!("<{}>", type_args.join(", "))
+                                }
+                            )
+                        },
+                        // Structs outside of the package are kept as raw JSON.
+                        None => "serde_json::Value".into(),
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Calls `f` on `ty` and all types nested in it.
+fn visit_type(ty: &normalized::Type, f: &mut dyn FnMut(&normalized::Type)) {
+    use normalized::Type::*;
+    f(ty);
+    match ty {
+        Vector(inner) | Reference(inner) | MutableReference(inner) => visit_type(inner, f),
+        Struct { type_arguments, .. } => {
+            for ty in type_arguments {
+                visit_type(ty, f);
+            }
+        },
+        _ => (),
+    }
+}
+
+fn struct_key(ty: &normalized::Type) -> Option<(ModuleId, Identifier)> {
+    match ty {
+        normalized::Type::Struct {
+            address,
+            module,
+            name,
+            ..
+        } => Some((ModuleId::new(*address, module.clone()), name.clone())),
+        _ => None,
+    }
+}
+
+/// Returns the event type `T` if `ty` is a `0x1::event::EventHandle<T>`.
+fn event_handle_type(ty: &normalized::Type) -> Option<&normalized::Type> {
+    match ty {
+        normalized::Type::Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } if is_framework_struct(address, module, name, "event", "EventHandle") => {
+            type_arguments.first()
+        },
+        _ => None,
+    }
+}
+
+fn is_framework_struct(
+    address: &AccountAddress,
+    module: &IdentStr,
+    name: &IdentStr,
+    expected_module: &str,
+    expected_name: &str,
+) -> bool {
+    address == &AccountAddress::ONE
+        && module.as_str() == expected_module
+        && name.as_str() == expected_name
+}
+
+/// Whether the struct has a dedicated JSON representation instead of generated bindings.
+fn is_special_struct(module_id: &ModuleId, name: &IdentStr) -> bool {
+    [
+        ("string", "String"),
+        ("option", "Option"),
+        ("object", "Object"),
+    ]
+    .iter()
+    .any(|(module, struct_name)| {
+        is_framework_struct(
+            module_id.address(),
+            module_id.name(),
+            name,
+            module,
+            struct_name,
+        )
+    })
+}
+
+fn is_signer(ty: &normalized::Type) -> bool {
+    match ty {
+        normalized::Type::Signer => true,
+        normalized::Type::Reference(inner) => matches!(inner.as_ref(), normalized::Type::Signer),
+        _ => false,
+    }
+}
+
+/// Returns the Rust type whose BCS encoding matches `ty`, if `ty` can be passed to an entry
+/// function.
+fn quote_bcs_type(ty: &normalized::Type) -> Option<String> {
+    use normalized::Type::*;
+    Some(match ty {
+        Bool => "bool".into(),
+        U8 => "u8".into(),
+        U16 => "u16".into(),
+        U32 => "u32".into(),
+        U64 => "u64".into(),
+        U128 => "u128".into(),
+        U256 => "move_types::u256::U256".into(),
+        Address => "AccountAddress".into(),
+        Vector(inner) => format!("Vec<{}>", quote_bcs_type(inner)?),
+        Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } => {
+            if is_framework_struct(address, module, name, "string", "String") {
+                "String".into()
+            } else if is_framework_struct(address, module, name, "option", "Option") {
+                format!("Option<{}>", quote_bcs_type(&type_arguments[0])?)
+            } else if is_framework_struct(address, module, name, "object", "Object") {
+                "AccountAddress".into()
+            } else {
+                return None;
+            }
+        },
+        Signer | TypeParameter(_) | Reference(_) | MutableReference(_) => return None,
+    })
+}
+
+fn quote_generics(used_type_params: &[u16]) -> String {
+    if used_type_params.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<{}>",
+            used_type_params
+                .iter()
+                .map(|idx| format!("T{}", idx))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+fn quote_address(address: &AccountAddress) -> String {
+    format!(
+        "AccountAddress::new([{}])",
+        address
+            .to_vec()
+            .iter()
+            .map(|x| format!("{}", x))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn struct_name(name: &IdentStr) -> String {
+    if RESERVED_TYPE_NAMES.contains(&name.as_str()) {
+        format!("Move{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "super" | "crate" | "Self" => format!("{}_", name),
+        name if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        name => name.to_string(),
+    }
+}
@@ -4,6 +4,7 @@
 
 use aptos_sdk_builder as buildgen;
 use aptos_types::transaction::EntryABI;
+use move_core_types::{account_address::AccountAddress, ident_str, language_storage::ModuleId};
 use serde_generate as serdegen;
 use serde_generate::SourceInstaller as _;
 use serde_reflection::Registry;
@@ -93,3 +94,85 @@ fn test_that_rust_entry_fun_code_compiles() {
         EXPECTED_SCRIPT_FUN_OUTPUT,
     );
 }
+
+#[test]
+fn test_bindings_for_framework_modules() {
+    let modules = aptos_cached_packages::head_release_bundle().compiled_modules();
+    let modules = modules.iter().collect::<Vec<_>>();
+    let coin = ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned());
+    let view_functions = vec![(coin, ident_str!("balance").to_owned())]
+        .into_iter()
+        .collect();
+
+    let mut out = vec![];
+    buildgen::bindings::output(&mut out, &modules, &[], &view_functions).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.contains("pub mod coin {"));
+    // Resources and the events of their handles get bindings.
+    assert!(out.contains("pub struct CoinStore {"));
+    assert!(out.contains("pub struct DepositEvent {"));
+    assert!(out.contains(
+        "pub fn transfer(type_arg0: TypeTag, arg0: AccountAddress, arg1: u64) -> TransactionPayload {"
+    ));
+    assert!(out.contains("pub async fn balance(client: &Client, type_arg0: TypeTag, arg0: aptos_api_types::Address, version: Option<u64>) -> Result<Response<aptos_api_types::U64>, RestError> {"));
+
+    check_bindings_compile(&out);
+}
+
+/// Builds `source` as the library of a crate depending on the `aptos-sdk` of this repository.
+fn check_bindings_compile(source: &str) {
+    let dir = tempdir().unwrap();
+    let workspace_dir = std::env::current_dir()
+        .unwrap()
+        .join("../..")
+        .canonicalize()
+        .unwrap();
+
+    // Resolve the dependencies the way the workspace does.
+    let workspace_manifest = std::fs::read_to_string(workspace_dir.join("Cargo.toml")).unwrap();
+    let patches = workspace_manifest
+        .split_once("[patch.crates-io]")
+        .map_or("", |(_, patches)| patches);
+    if workspace_dir.join("Cargo.lock").exists() {
+        std::fs::copy(
+            workspace_dir.join("Cargo.lock"),
+            dir.path().join("Cargo.lock"),
+        )
+        .unwrap();
+    }
+
+    let mut cargo = std::fs::File::create(dir.path().join("Cargo.toml")).unwrap();
+    write!(
+        cargo,
+        r#"[package]
+name = "bindings"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+aptos-sdk = {{ path = "{}" }}
+serde = {{ version = "1.0.137", features = ["derive"] }}
+serde_json = "1.0.81"
+
+[workspace]
+
+[patch.crates-io]
+{}"#,
+        workspace_dir.join("sdk").display(),
+        patches,
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), source).unwrap();
+
+    // Use a stable `target` dir to avoid downloading and recompiling crates everytime.
+    let status = Command::new("cargo")
+        .current_dir(dir.path())
+        .arg("check")
+        .arg("--target-dir")
+        .arg(workspace_dir.join("target"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -245,18 +245,17 @@ where
         let (parallel_block_executor, block_partitioner) = if num_executor_shards == 1 {
             (None, None)
         } else {
-            let parallel_block_executor =
-                if let Some(remote_executor_addresses) = remote_executor_addresses {
-                    let remote_executor_clients = remote_executor_addresses
-                        .into_iter()
-                        .map(|addr| RemoteExecutorClient::new(addr, 10000))
-                        .collect::<Vec<RemoteExecutorClient>>();
-                    Arc::new(ShardedBlockExecutor::new(remote_executor_clients))
-                } else {
-                    let local_executor_client =
-                        LocalExecutorClient::create_local_clients(num_executor_shards, None);
-                    Arc::new(ShardedBlockExecutor::new(local_executor_client))
-                };
+            let parallel_block_executor = if let Some(remote_executor_addresses) =
+                remote_executor_addresses
+            {
+                let remote_executor_clients =
+                    RemoteExecutorClient::create_remote_clients(remote_executor_addresses, 10000);
+                Arc::new(ShardedBlockExecutor::new(remote_executor_clients))
+            } else {
+                let local_executor_client =
+                    LocalExecutorClient::create_local_clients(num_executor_shards, None);
+                Arc::new(ShardedBlockExecutor::new(local_executor_client))
+            };
             (
                 Some(parallel_block_executor),
                 Some(ShardedBlockPartitioner::new(num_executor_shards)),
@@ -35,10 +35,10 @@ use aptos_types::{
     fee_statement::FeeStatement,
     on_chain_config::{new_epoch_event_key, FeatureFlag, TimedFeatureOverride},
     transaction::{
-        EntryFunction, ExecutionError, ExecutionStatus, ModuleBundle, Multisig,
-        MultisigTransactionPayload, SignatureCheckedTransaction, SignedTransaction, Transaction,
-        TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
-        WriteSetPayload,
+        authenticator::AccountAuthenticator, EntryFunction, ExecutionError, ExecutionStatus,
+        ModuleBundle, Multisig, MultisigTransactionPayload, SignatureCheckedTransaction,
+        SignedTransaction, Transaction, TransactionOutput, TransactionPayload, TransactionStatus,
+        VMValidatorResult, WriteSetPayload,
     },
     vm_status::{AbortLocation, StatusCode, VMStatus},
     write_set::WriteSet,
@@ -1484,6 +1484,10 @@ impl AptosVM {
                 if MODULE_BUNDLE_DISALLOWED.load(Ordering::Relaxed) {
                     return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING, None));
                 }
+                // The module prologue has no notion of a separate gas payer.
+                if txn_data.fee_payer().is_some() {
+                    return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING, None));
+                }
                 self.0.check_gas(resolver, txn_data, log_context)?;
                 self.0.run_module_prologue(session, txn_data, log_context)
             },
@@ -1638,6 +1642,28 @@ impl VMAdapter for AptosVM {
             ));
         }
 
+        if !self
+            .0
+            .get_features()
+            .is_enabled(FeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR)
+            && txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|auth| matches!(auth, AccountAuthenticator::Secp256k1Ecdsa { .. }))
+        {
+            return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING, None));
+        }
+
+        if !self
+            .0
+            .get_features()
+            .is_enabled(FeatureFlag::FEE_PAYER_ENABLED)
+            && txn.authenticator_ref().fee_payer_address().is_some()
+        {
+            return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING, None));
+        }
+
         Ok(())
     }
 
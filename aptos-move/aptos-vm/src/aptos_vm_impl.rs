@@ -7,7 +7,10 @@ use crate::{
     data_cache::StorageAdapter,
     errors::{convert_epilogue_error, convert_prologue_error, expect_only_successful_execution},
     move_vm_ext::{MoveResolverExt, MoveVmExt, SessionExt, SessionId},
-    system_module_names::{MULTISIG_ACCOUNT_MODULE, VALIDATE_MULTISIG_TRANSACTION},
+    system_module_names::{
+        FEE_PAYER_EPILOGUE_NAME, FEE_PAYER_PROLOGUE_NAME, MULTISIG_ACCOUNT_MODULE,
+        VALIDATE_MULTISIG_TRANSACTION,
+    },
     transaction_metadata::TransactionMetadata,
 };
 use aptos_framework::RuntimeModuleMetadataV1;
@@ -34,6 +37,7 @@ use fail::fail_point;
 use move_binary_format::{errors::VMResult, CompiledModule};
 use move_core_types::{
     gas_algebra::NumArgs,
+    identifier::IdentStr,
     language_storage::ModuleId,
     move_resource::MoveStructType,
     value::{serialize_values, MoveValue},
@@ -361,8 +365,9 @@ impl AptosVMImpl {
         Ok(())
     }
 
-    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME` function
-    /// or `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` function stored in the `ACCOUNT_MODULE` on chain.
+    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME` function,
+    /// `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` function or, for transactions with a fee payer,
+    /// `FEE_PAYER_PROLOGUE_NAME` function stored in the `ACCOUNT_MODULE` on chain.
     pub(crate) fn run_script_prologue(
         &self,
         session: &mut SessionExt,
@@ -382,34 +387,54 @@ impl AptosVMImpl {
             .iter()
             .map(|auth_key| MoveValue::vector_u8(auth_key.to_vec()))
             .collect();
-        let args = if txn_data.is_multi_agent() {
-            vec![
+        let (prologue_function_name, args) = if let (Some(fee_payer), Some(fee_payer_auth_key)) = (
+            txn_data.fee_payer(),
+            txn_data.fee_payer_authentication_key.as_ref(),
+        ) {
+            (FEE_PAYER_PROLOGUE_NAME, vec![
                 MoveValue::Signer(txn_data.sender),
                 MoveValue::U64(txn_sequence_number),
                 MoveValue::vector_u8(txn_authentication_key),
                 MoveValue::vector_address(txn_data.secondary_signers()),
                 MoveValue::Vector(secondary_auth_keys),
+                MoveValue::Address(fee_payer),
+                MoveValue::vector_u8(fee_payer_auth_key.to_vec()),
                 MoveValue::U64(txn_gas_price.into()),
                 MoveValue::U64(txn_max_gas_units.into()),
                 MoveValue::U64(txn_expiration_timestamp_secs),
                 MoveValue::U8(chain_id.id()),
-            ]
-        } else {
-            vec![
-                MoveValue::Signer(txn_data.sender),
-                MoveValue::U64(txn_sequence_number),
-                MoveValue::vector_u8(txn_authentication_key),
-                MoveValue::U64(txn_gas_price.into()),
-                MoveValue::U64(txn_max_gas_units.into()),
-                MoveValue::U64(txn_expiration_timestamp_secs),
-                MoveValue::U8(chain_id.id()),
-                MoveValue::vector_u8(txn_data.script_hash.clone()),
-            ]
-        };
-        let prologue_function_name = if txn_data.is_multi_agent() {
-            &transaction_validation.multi_agent_prologue_name
+            ])
+        } else if txn_data.is_multi_agent() {
+            (
+                transaction_validation
+                    .multi_agent_prologue_name
+                    .as_ident_str(),
+                vec![
+                    MoveValue::Signer(txn_data.sender),
+                    MoveValue::U64(txn_sequence_number),
+                    MoveValue::vector_u8(txn_authentication_key),
+                    MoveValue::vector_address(txn_data.secondary_signers()),
+                    MoveValue::Vector(secondary_auth_keys),
+                    MoveValue::U64(txn_gas_price.into()),
+                    MoveValue::U64(txn_max_gas_units.into()),
+                    MoveValue::U64(txn_expiration_timestamp_secs),
+                    MoveValue::U8(chain_id.id()),
+                ],
+            )
         } else {
-            &transaction_validation.script_prologue_name
+            (
+                transaction_validation.script_prologue_name.as_ident_str(),
+                vec![
+                    MoveValue::Signer(txn_data.sender),
+                    MoveValue::U64(txn_sequence_number),
+                    MoveValue::vector_u8(txn_authentication_key),
+                    MoveValue::U64(txn_gas_price.into()),
+                    MoveValue::U64(txn_max_gas_units.into()),
+                    MoveValue::U64(txn_expiration_timestamp_secs),
+                    MoveValue::U8(chain_id.id()),
+                    MoveValue::vector_u8(txn_data.script_hash.clone()),
+                ],
+            )
         };
         session
             .execute_function_bypass_visibility(
@@ -519,22 +544,15 @@ impl AptosVMImpl {
         });
 
         let transaction_validation = self.transaction_validation();
-        let txn_sequence_number = txn_data.sequence_number();
-        let txn_gas_price = txn_data.gas_unit_price();
-        let txn_max_gas_units = txn_data.max_gas_amount();
+        let (epilogue_function_name, args) =
+            self.epilogue_function_and_args(gas_remaining, txn_data);
         session
             .execute_function_bypass_visibility(
                 &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
+                epilogue_function_name,
                 // TODO: Deprecate this once we remove gas currency on the Move side.
                 vec![],
-                serialize_values(&vec![
-                    MoveValue::Signer(txn_data.sender),
-                    MoveValue::U64(txn_sequence_number),
-                    MoveValue::U64(txn_gas_price.into()),
-                    MoveValue::U64(txn_max_gas_units.into()),
-                    MoveValue::U64(gas_remaining.into()),
-                ]),
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
@@ -552,35 +570,58 @@ impl AptosVMImpl {
         log_context: &AdapterLogSchema,
     ) -> Result<(), VMStatus> {
         let transaction_validation = self.transaction_validation();
-        let txn_sequence_number = txn_data.sequence_number();
-        let txn_gas_price = txn_data.gas_unit_price();
-        let txn_max_gas_units = txn_data.max_gas_amount();
+        let (epilogue_function_name, args) =
+            self.epilogue_function_and_args(gas_remaining, txn_data);
         session
             .execute_function_bypass_visibility(
                 &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
+                epilogue_function_name,
                 // TODO: Deprecate this once we remove gas currency on the Move side.
                 vec![],
-                serialize_values(&vec![
-                    MoveValue::Signer(txn_data.sender),
-                    MoveValue::U64(txn_sequence_number),
-                    MoveValue::U64(txn_gas_price.into()),
-                    MoveValue::U64(txn_max_gas_units.into()),
-                    MoveValue::U64(gas_remaining.into()),
-                ]),
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
             .map_err(expect_no_verification_errors)
             .or_else(|e| {
-                expect_only_successful_execution(
-                    e,
-                    transaction_validation.user_epilogue_name.as_str(),
-                    log_context,
-                )
+                expect_only_successful_execution(e, epilogue_function_name.as_str(), log_context)
             })
     }
 
+    /// Returns the epilogue to call for the transaction together with its arguments. Transactions
+    /// with a fee payer charge gas to the fee payer through `FEE_PAYER_EPILOGUE_NAME`.
+    fn epilogue_function_and_args(
+        &self,
+        gas_remaining: Gas,
+        txn_data: &TransactionMetadata,
+    ) -> (&IdentStr, Vec<MoveValue>) {
+        let txn_sequence_number = txn_data.sequence_number();
+        let txn_gas_price = txn_data.gas_unit_price();
+        let txn_max_gas_units = txn_data.max_gas_amount();
+        match txn_data.fee_payer() {
+            Some(fee_payer) => (FEE_PAYER_EPILOGUE_NAME, vec![
+                MoveValue::Signer(txn_data.sender),
+                MoveValue::Address(fee_payer),
+                MoveValue::U64(txn_sequence_number),
+                MoveValue::U64(txn_gas_price.into()),
+                MoveValue::U64(txn_max_gas_units.into()),
+                MoveValue::U64(gas_remaining.into()),
+            ]),
+            None => (
+                self.transaction_validation()
+                    .user_epilogue_name
+                    .as_ident_str(),
+                vec![
+                    MoveValue::Signer(txn_data.sender),
+                    MoveValue::U64(txn_sequence_number),
+                    MoveValue::U64(txn_gas_price.into()),
+                    MoveValue::U64(txn_max_gas_units.into()),
+                    MoveValue::U64(gas_remaining.into()),
+                ],
+            ),
+        }
+    }
+
     pub(crate) fn extract_abort_info(
         &self,
         module: &ModuleId,
@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    on_chain_config::FeatureFlag,
+    transaction::{SignedTransaction, TransactionStatus},
+};
+use move_core_types::{parser::parse_struct_tag, vm_status::StatusCode};
+
+const APTOS_COIN_STORE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+const GAS_UNIT_PRICE: u64 = 100;
+
+fn balance(h: &MoveHarness, address: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(address, parse_struct_tag(APTOS_COIN_STORE).unwrap())
+        .unwrap()
+        .coin()
+}
+
+/// Creates a transaction from `sender` whose gas is paid by `fee_payer_address`, with the fee
+/// payer signature made by `fee_payer_signer`.
+fn fee_payer_transaction(
+    h: &MoveHarness,
+    sender: &Account,
+    fee_payer_address: AccountAddress,
+    fee_payer_signer: &Account,
+) -> SignedTransaction {
+    sender
+        .transaction()
+        .sequence_number(h.sequence_number(sender.address()))
+        .max_gas_amount(100_000)
+        .gas_unit_price(GAS_UNIT_PRICE)
+        .payload(aptos_stdlib::aptos_account_set_allow_direct_coin_transfers(
+            true,
+        ))
+        .raw()
+        .sign_fee_payer(
+            &sender.privkey,
+            vec![],
+            vec![],
+            fee_payer_address,
+            &fee_payer_signer.privkey,
+        )
+        .unwrap()
+        .into_inner()
+}
+
+#[test]
+fn test_fee_payer_pays_gas() {
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::FEE_PAYER_ENABLED], vec![]);
+    // The sender holds no coins at all, so it can't pay for the transaction itself.
+    let sender = h.new_account_with_balance_and_sequence_number(0, 0);
+    let fee_payer = h.new_account_with_key_pair();
+    let fee_payer_balance = balance(&h, fee_payer.address());
+
+    let txn = fee_payer_transaction(&h, &sender, *fee_payer.address(), &fee_payer);
+    let output = h.run_raw(txn);
+    assert_success!(output.status().to_owned());
+
+    assert_eq!(balance(&h, sender.address()), 0);
+    assert_eq!(
+        balance(&h, fee_payer.address()),
+        fee_payer_balance - output.gas_used() * GAS_UNIT_PRICE
+    );
+    // The sequence number of the sender, not of the fee payer, is bumped.
+    assert_eq!(h.sequence_number(sender.address()), 1);
+    assert_eq!(h.sequence_number(fee_payer.address()), 0);
+}
+
+#[test]
+fn test_fee_payer_auth_key_is_checked() {
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::FEE_PAYER_ENABLED], vec![]);
+    let sender = h.new_account_with_balance_and_sequence_number(0, 0);
+    let fee_payer = h.new_account_with_key_pair();
+    let impostor = h.new_account_with_key_pair();
+    let fee_payer_balance = balance(&h, fee_payer.address());
+
+    // A valid signature, but by a key that doesn't control the fee payer account.
+    let txn = fee_payer_transaction(&h, &sender, *fee_payer.address(), &impostor);
+    assert_eq!(
+        h.run(txn),
+        TransactionStatus::Discard(StatusCode::INVALID_AUTH_KEY)
+    );
+    assert_eq!(balance(&h, fee_payer.address()), fee_payer_balance);
+    assert_eq!(h.sequence_number(sender.address()), 0);
+}
+
+#[test]
+fn test_fee_payer_feature_disabled() {
+    let mut h = MoveHarness::new_with_features(vec![], vec![FeatureFlag::FEE_PAYER_ENABLED]);
+    let sender = h.new_account_with_balance_and_sequence_number(0, 0);
+    let fee_payer = h.new_account_with_key_pair();
+    let fee_payer_balance = balance(&h, fee_payer.address());
+
+    let txn = fee_payer_transaction(&h, &sender, *fee_payer.address(), &fee_payer);
+    assert_eq!(
+        h.run(txn),
+        TransactionStatus::Discard(StatusCode::FEATURE_UNDER_GATING)
+    );
+    assert_eq!(balance(&h, fee_payer.address()), fee_payer_balance);
+    assert_eq!(h.sequence_number(sender.address()), 0);
+}
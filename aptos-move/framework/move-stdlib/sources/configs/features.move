@@ -194,6 +194,22 @@ module std::features {
         is_enabled(DELEGATION_POOL_PARTIAL_GOVERNANCE_VOTING)
     }
 
+    /// Whether accounts can be authenticated with secp256k1 ECDSA keys.
+    /// Lifetime: transient
+    const SECP256K1_ECDSA_AUTHENTICATOR: u64 = 22;
+    public fun get_secp256k1_ecdsa_authenticator_feature(): u64 { SECP256K1_ECDSA_AUTHENTICATOR }
+    public fun secp256k1_ecdsa_authenticator_enabled(): bool acquires Features {
+        is_enabled(SECP256K1_ECDSA_AUTHENTICATOR)
+    }
+
+    /// Whether a separate fee payer account can pay the gas of a transaction.
+    /// Lifetime: transient
+    const FEE_PAYER_ENABLED: u64 = 23;
+    public fun get_fee_payer_feature(): u64 { FEE_PAYER_ENABLED }
+    public fun fee_payer_enabled(): bool acquires Features {
+        is_enabled(FEE_PAYER_ENABLED)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
@@ -3,7 +3,7 @@
 
 use crate::{
     docgen::DocgenOptions,
-    extended_checks,
+    extended_checks, get_metadata_from_compiled_module,
     natives::code::{ModuleMetadata, MoveOption, PackageDep, PackageMetadata, UpgradePolicy},
     zip_metadata, zip_metadata_str, RuntimeModuleMetadataV1, APTOS_METADATA_KEY,
     APTOS_METADATA_KEY_V1, METADATA_V1_MIN_FILE_FORMAT_VERSION,
@@ -19,7 +19,7 @@ use itertools::Itertools;
 use move_binary_format::CompiledModule;
 use move_command_line_common::files::MOVE_COMPILED_EXTENSION;
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
-use move_core_types::{language_storage::ModuleId, metadata::Metadata};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, metadata::Metadata};
 use move_model::model::GlobalEnv;
 use move_package::{
     compilation::{compiled_package::CompiledPackage, package_layout::CompiledPackageLayout},
@@ -242,6 +242,37 @@ impl BuiltPackage {
             })
     }
 
+    /// Writes typed Rust bindings for the modules of this package, as described in
+    /// `aptos_sdk_builder::bindings`.
+    pub fn generate_rust_bindings(&self, out: &mut dyn std::io::Write) -> anyhow::Result<()> {
+        let modules = self.modules().collect::<Vec<_>>();
+        let view_functions = modules
+            .iter()
+            .filter_map(|module| {
+                get_metadata_from_compiled_module(module)
+                    .map(|metadata| (module.self_id(), metadata))
+            })
+            .flat_map(|(module_id, metadata)| {
+                metadata
+                    .fun_attributes
+                    .into_iter()
+                    .filter(|(_, attrs)| attrs.iter().any(|attr| attr.is_view_function()))
+                    .filter_map(move |(name, _)| {
+                        Identifier::new(name)
+                            .ok()
+                            .map(|name| (module_id.clone(), name))
+                    })
+            })
+            .collect::<BTreeSet<_>>();
+        aptos_sdk_builder::bindings::output(
+            out,
+            &modules,
+            &self.extract_abis().unwrap_or_default(),
+            &view_functions,
+        )?;
+        Ok(())
+    }
+
     /// Returns the number of scripts in the package.
     pub fn script_count(&self) -> usize {
         self.package.scripts().count()
@@ -456,9 +456,17 @@ pub fn setup_environment_and_start_node(
     // Log the node config at node startup
     info!("Using node config {:?}", &node_config);
 
+    // Create the mempool client channel (used by the API and the inspection service)
+    let (mempool_client_sender, mempool_client_receiver) =
+        services::create_mempool_client_channel();
+
     // Start the node inspection service
     let peers_and_metadata = network::create_peers_and_metadata(&node_config);
-    services::start_node_inspection_service(&node_config, peers_and_metadata.clone());
+    services::start_node_inspection_service(
+        &node_config,
+        peers_and_metadata.clone(),
+        mempool_client_sender.clone(),
+    );
 
     // Set up the storage database and any RocksDB checkpoints
     let (aptos_db, db_rw, backup_service, genesis_waypoint) =
@@ -520,8 +528,12 @@ pub fn setup_environment_and_start_node(
         )?;
 
     // Bootstrap the API and indexer
-    let (mempool_client_receiver, api_runtime, indexer_runtime, indexer_grpc_runtime) =
-        services::bootstrap_api_and_indexer(&node_config, aptos_db, chain_id)?;
+    let (api_runtime, indexer_runtime, indexer_grpc_runtime) = services::bootstrap_api_and_indexer(
+        &node_config,
+        aptos_db,
+        chain_id,
+        mempool_client_sender,
+    )?;
 
     // Create mempool and get the consensus to mempool sender
     let (mempool_runtime, consensus_to_mempool_sender) =
@@ -9,7 +9,9 @@ use aptos_consensus_notifications::ConsensusNotifier;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_indexer_grpc_fullnode::runtime::bootstrap as bootstrap_indexer_grpc;
 use aptos_logger::{debug, telemetry_log_writer::TelemetryLog, LoggerFilterUpdater};
-use aptos_mempool::{network::MempoolSyncMsg, MempoolClientRequest, QuorumStoreRequest};
+use aptos_mempool::{
+    network::MempoolSyncMsg, MempoolClientRequest, MempoolClientSender, QuorumStoreRequest,
+};
 use aptos_mempool_notifications::MempoolNotificationListener;
 use aptos_network::application::{interface::NetworkClientInterface, storage::PeersAndMetadata};
 use aptos_peer_monitoring_service_server::{
@@ -27,22 +29,18 @@ use tokio::runtime::Runtime;
 const AC_SMP_CHANNEL_BUFFER_SIZE: usize = 1_024;
 const INTRA_NODE_CHANNEL_BUFFER_SIZE: usize = 1;
 
-/// Bootstraps the API and the indexer. Returns the Mempool client
-/// receiver, and both the api and indexer runtimes.
+/// Creates the channel used by clients (e.g., the API) to send requests to mempool
+pub fn create_mempool_client_channel() -> (MempoolClientSender, Receiver<MempoolClientRequest>) {
+    mpsc::channel(AC_SMP_CHANNEL_BUFFER_SIZE)
+}
+
+/// Bootstraps the API and the indexer. Returns the api and indexer runtimes.
 pub fn bootstrap_api_and_indexer(
     node_config: &NodeConfig,
     aptos_db: Arc<dyn DbReader>,
     chain_id: ChainId,
-) -> anyhow::Result<(
-    Receiver<MempoolClientRequest>,
-    Option<Runtime>,
-    Option<Runtime>,
-    Option<Runtime>,
-)> {
-    // Create the mempool client and sender
-    let (mempool_client_sender, mempool_client_receiver) =
-        mpsc::channel(AC_SMP_CHANNEL_BUFFER_SIZE);
-
+    mempool_client_sender: MempoolClientSender,
+) -> anyhow::Result<(Option<Runtime>, Option<Runtime>, Option<Runtime>)> {
     // Create the API runtime
     let api_runtime = if node_config.api.enabled {
         Some(bootstrap_api(
@@ -67,12 +65,7 @@ pub fn bootstrap_api_and_indexer(
     let indexer_runtime =
         indexer::bootstrap_indexer(node_config, chain_id, aptos_db, mempool_client_sender)?;
 
-    Ok((
-        mempool_client_receiver,
-        api_runtime,
-        indexer_runtime,
-        indexer_grpc,
-    ))
+    Ok((api_runtime, indexer_runtime, indexer_grpc))
 }
 
 /// Starts consensus and returns the runtime
@@ -135,8 +128,13 @@ pub fn start_mempool_runtime_and_get_consensus_sender(
 pub fn start_node_inspection_service(
     node_config: &NodeConfig,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    mempool_client_sender: MempoolClientSender,
 ) {
-    aptos_inspection_service::start_inspection_service(node_config.clone(), peers_and_metadata)
+    aptos_inspection_service::start_inspection_service(
+        node_config.clone(),
+        peers_and_metadata,
+        mempool_client_sender,
+    )
 }
 
 /// Starts the peer monitoring service and returns the runtime
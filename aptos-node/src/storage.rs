@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::anyhow;
-use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
+use aptos_config::{
+    config::{BootstrappingMode, NodeConfig},
+    utils::get_genesis_txn,
+};
 use aptos_db::AptosDB;
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_logger::{debug, info};
@@ -82,6 +85,43 @@ fn create_rocksdb_checkpoint_and_change_working_dir(
         .expect("StateSyncDB checkpoint creation failed.");
 }
 
+/// Restores the latest state snapshot from the configured backup storage into
+/// the (empty) storage database, so that state sync can continue from there.
+#[cfg(feature = "restore-from-backup")]
+fn restore_database_from_backup(node_config: &NodeConfig) -> anyhow::Result<()> {
+    use aptos_backup_cli::coordinators::bootstrap::BootstrapCoordinator;
+
+    let runtime = aptos_runtimes::spawn_named_runtime("db-restore".into(), None);
+    let restored_version = runtime.block_on(async {
+        BootstrapCoordinator::new_with_config(
+            &node_config.state_sync.backup_restore,
+            node_config.storage.dir(),
+            node_config.storage.rocksdb_configs,
+            node_config.base.waypoint.genesis_waypoint(),
+        )
+        .await?
+        .run()
+        .await
+    })?;
+    if let Some(version) = restored_version {
+        info!(
+            "Restored the database from the backup at version {}",
+            version
+        );
+    }
+
+    Ok(())
+}
+
+/// Restoring from a backup requires the backup CLI, which is only linked
+/// into the node with the `restore-from-backup` feature.
+#[cfg(not(feature = "restore-from-backup"))]
+fn restore_database_from_backup(_node_config: &NodeConfig) -> anyhow::Result<()> {
+    Err(anyhow!(
+        "The node is built without the restore-from-backup feature"
+    ))
+}
+
 /// Creates any rocksdb checkpoints, opens the storage database,
 /// starts the backup service, handles genesis initialization and returns
 /// the various handles.
@@ -94,17 +134,41 @@ pub fn initialize_database_and_checkpoints(
         create_rocksdb_checkpoint_and_change_working_dir(node_config, working_dir);
     }
 
-    // Open the database
+    // If required, restore the database from a backup before opening it
     let instant = Instant::now();
-    let aptos_db = AptosDB::open(
-        &node_config.storage.dir(),
-        false, /* readonly */
-        node_config.storage.storage_pruner_config,
-        node_config.storage.rocksdb_configs,
-        node_config.storage.enable_indexer,
-        node_config.storage.buffered_state_target_items,
-        node_config.storage.max_num_nodes_per_lru_cache_shard,
-    )
+    if node_config.state_sync.state_sync_driver.bootstrapping_mode
+        == BootstrappingMode::RestoreFromBackup
+    {
+        restore_database_from_backup(node_config)
+            .map_err(|err| anyhow!("DB failed to restore from the backup {}", err))?;
+    }
+
+    // Open the database (only persisting the replicated state values, if required)
+    let aptos_db = match node_config
+        .state_sync
+        .partial_replication
+        .state_key_filter()
+    {
+        Some(replicated_state_keys) => AptosDB::open_partially_replicated(
+            &node_config.storage.dir(),
+            false, /* readonly */
+            node_config.storage.storage_pruner_config,
+            node_config.storage.rocksdb_configs,
+            node_config.storage.enable_indexer,
+            node_config.storage.buffered_state_target_items,
+            node_config.storage.max_num_nodes_per_lru_cache_shard,
+            replicated_state_keys,
+        ),
+        None => AptosDB::open(
+            &node_config.storage.dir(),
+            false, /* readonly */
+            node_config.storage.storage_pruner_config,
+            node_config.storage.rocksdb_configs,
+            node_config.storage.enable_indexer,
+            node_config.storage.buffered_state_target_items,
+            node_config.storage.max_num_nodes_per_lru_cache_shard,
+        ),
+    }
     .map_err(|err| anyhow!("DB failed to open {}", err))?;
     let (aptos_db, db_rw, backup_service) =
         bootstrap_db(aptos_db, node_config.storage.backup_service_address);
@@ -4,9 +4,9 @@
 use crate::config::{
     node_config_loader::NodeType,
     utils::{are_failpoints_enabled, get_config_name},
-    ApiConfig, BaseConfig, ConsensusConfig, Error, ExecutionConfig, IndexerConfig,
-    IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig, NodeConfig,
-    PeerMonitoringServiceConfig, StateSyncConfig, StorageConfig,
+    ApiConfig, BaseConfig, ConsensusConfig, DiscoveryMethod, Error, ExecutionConfig, IndexerConfig,
+    IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig, NetworkConfig,
+    NodeConfig, PeerMonitoringServiceConfig, PeerRole, StateSyncConfig, StorageConfig,
 };
 use aptos_types::chain_id::ChainId;
 use std::collections::HashSet;
@@ -146,6 +146,15 @@ fn sanitize_fullnode_network_configs(
 
         // Prepare the network id
         fullnode_network_config.set_listen_address_and_prepare_identity()?;
+
+        // Verify that the listen address can be served by the transport
+        sanitize_transport_protocol(&sanitizer_name, fullnode_network_config)?;
+
+        // Verify the outbound traffic shaping
+        sanitize_outbound_traffic_shaping(&sanitizer_name, fullnode_network_config)?;
+
+        // Verify the DNS discovery methods
+        sanitize_dns_discovery(&sanitizer_name, fullnode_network_config)?;
     }
 
     Ok(())
@@ -196,6 +205,133 @@ fn sanitize_validator_network_config(
 
         // Prepare the network id
         validator_network_config.set_listen_address_and_prepare_identity()?;
+
+        // Verify that the listen address can be served by the transport
+        sanitize_transport_protocol(&sanitizer_name, validator_network_config)?;
+
+        // Verify the outbound traffic shaping
+        sanitize_outbound_traffic_shaping(&sanitizer_name, validator_network_config)?;
+
+        // Verify the DNS discovery methods
+        sanitize_dns_discovery(&sanitizer_name, validator_network_config)?;
+    }
+
+    Ok(())
+}
+
+/// Sanitize the transport protocol of the network config according to its listen address
+fn sanitize_transport_protocol(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    if !network_config.listen_address_matches_transport() {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The listen address {} cannot be used with the {:?} transport of network {}!",
+                network_config.listen_address,
+                network_config.transport_protocol,
+                network_config.network_id
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Sanitize the outbound traffic shaping of the network config: the weights must be positive, and
+/// the rate limits must allow the largest messages through.
+fn sanitize_outbound_traffic_shaping(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let shaping_config = &network_config.outbound_traffic_shaping;
+    if !shaping_config.enabled {
+        return Ok(());
+    }
+
+    // Verify the protocol weights
+    if shaping_config.default_protocol_weight == 0
+        || shaping_config
+            .protocol_weights
+            .values()
+            .any(|weight| *weight == 0)
+    {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The outbound protocol weights of network {} must be positive!",
+                network_config.network_id
+            ),
+        ));
+    }
+
+    // Verify the rate limits
+    let rate_limits = shaping_config
+        .peer_rate_limit
+        .iter()
+        .chain(shaping_config.protocol_rate_limits.values());
+    for rate_limit in rate_limits {
+        if rate_limit.byte_bucket_rate == 0
+            || rate_limit.byte_bucket_size < rate_limit.byte_bucket_rate
+            || rate_limit.byte_bucket_size < network_config.max_message_size
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name.to_string(),
+                format!(
+                    "The outbound rate limit {:?} of network {} is invalid! The bucket rate must be \
+                    positive, and the bucket size must be at least the rate and the max message size ({}).",
+                    rate_limit, network_config.network_id, network_config.max_message_size
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Sanitize the DNS discovery methods of the network config: each must name an SRV record,
+/// and have a valid refresh interval range. Unvalidated answers can be spoofed, so DNSSEC
+/// validation is required on mutually authenticated networks, and to assign roles more trusted
+/// than `Upstream`.
+fn sanitize_dns_discovery(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let discovery_methods =
+        std::iter::once(&network_config.discovery_method).chain(&network_config.discovery_methods);
+    for discovery_method in discovery_methods {
+        if let DiscoveryMethod::Dns(dns_discovery) = discovery_method {
+            if dns_discovery.srv_name.is_empty()
+                || dns_discovery.min_interval_secs == 0
+                || dns_discovery.min_interval_secs > dns_discovery.max_interval_secs
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_string(),
+                    format!(
+                        "The DNS discovery {:?} of network {} is invalid! The SRV name must be set, \
+                        and the min interval must be positive and no larger than the max interval.",
+                        dns_discovery, network_config.network_id
+                    ),
+                ));
+            }
+            if !dns_discovery.validate_dnssec
+                && (network_config.mutual_authentication
+                    || dns_discovery.peer_role != dns_discovery.max_peer_role())
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_string(),
+                    format!(
+                        "The DNS discovery {:?} of network {} must validate DNSSEC! Validation is \
+                        required on mutually authenticated networks, and for peer roles more \
+                        trusted than {}.",
+                        dns_discovery,
+                        network_config.network_id,
+                        PeerRole::Upstream.as_str()
+                    ),
+                ));
+            }
+        }
     }
 
     Ok(())
@@ -204,7 +340,13 @@ fn sanitize_validator_network_config(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{config::NetworkConfig, network_id::NetworkId};
+    use crate::{
+        config::{
+            ByteRateLimitConfig, DnsDiscovery, OutboundTrafficShapingConfig, TransportProtocol,
+        },
+        network_id::NetworkId,
+    };
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_sanitize_missing_pfn_network_configs() {
@@ -372,4 +514,183 @@ mod tests {
         .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_quic_tcp_listen_address() {
+        // Create a QUIC fullnode network config that listens on a TCP address
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                transport_protocol: TransportProtocol::Quic,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Listen on a UDP address and verify that the config is valid
+        node_config.full_node_networks[0].listen_address = "/ip4/0.0.0.0/udp/6182".parse().unwrap();
+        sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_outbound_traffic_shaping() {
+        // Create a validator network config with a rate limit smaller than the max message size
+        let max_message_size = 1024;
+        let mut protocol_rate_limits = BTreeMap::new();
+        protocol_rate_limits.insert("StorageServiceRpc".into(), ByteRateLimitConfig {
+            byte_bucket_rate: 512,
+            byte_bucket_size: 512,
+        });
+        let mut node_config = NodeConfig {
+            validator_network: Some(NetworkConfig {
+                max_message_size,
+                outbound_traffic_shaping: OutboundTrafficShapingConfig {
+                    enabled: true,
+                    protocol_rate_limits,
+                    ..Default::default()
+                },
+                ..NetworkConfig::network_with_id(NetworkId::Validator)
+            }),
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = sanitize_validator_network_config(
+            &mut node_config,
+            NodeType::Validator,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Fit the max message size in the bucket and verify that the config is valid
+        node_config
+            .validator_network
+            .as_mut()
+            .unwrap()
+            .outbound_traffic_shaping
+            .protocol_rate_limits
+            .get_mut("StorageServiceRpc")
+            .unwrap()
+            .byte_bucket_size = max_message_size;
+        sanitize_validator_network_config(
+            &mut node_config,
+            NodeType::Validator,
+            ChainId::testnet(),
+        )
+        .unwrap();
+
+        // Zero out a protocol weight and verify that it fails
+        node_config
+            .validator_network
+            .as_mut()
+            .unwrap()
+            .outbound_traffic_shaping
+            .protocol_weights
+            .insert("MempoolDirectSend".into(), 0);
+        let error = sanitize_validator_network_config(
+            &mut node_config,
+            NodeType::Validator,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_dns_discovery() {
+        // Create a PFN config with a DNS discovery method that has an inverted interval range
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                discovery_methods: vec![DiscoveryMethod::Dns(DnsDiscovery {
+                    srv_name: "_aptos._tcp.peers.example.com".into(),
+                    peer_role: PeerRole::Upstream,
+                    bootstrap_peers: Default::default(),
+                    min_interval_secs: 60,
+                    max_interval_secs: 30,
+                    validate_dnssec: false,
+                })],
+                ..NetworkConfig::network_with_id(NetworkId::Public)
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Fix the interval range and verify that the config is valid
+        if let DiscoveryMethod::Dns(dns_discovery) =
+            &mut node_config.full_node_networks[0].discovery_methods[0]
+        {
+            dns_discovery.max_interval_secs = 600;
+        }
+        sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap();
+
+        // Trust the discovered peers as validators and verify that DNSSEC is required
+        if let DiscoveryMethod::Dns(dns_discovery) =
+            &mut node_config.full_node_networks[0].discovery_methods[0]
+        {
+            dns_discovery.peer_role = PeerRole::Validator;
+        }
+        let error = sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Require mutual authentication and verify that DNSSEC is still required
+        node_config.full_node_networks[0].mutual_authentication = true;
+        if let DiscoveryMethod::Dns(dns_discovery) =
+            &mut node_config.full_node_networks[0].discovery_methods[0]
+        {
+            dns_discovery.peer_role = PeerRole::Upstream;
+        }
+        let error = sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Validate DNSSEC and verify that the config is valid
+        if let DiscoveryMethod::Dns(dns_discovery) =
+            &mut node_config.full_node_networks[0].discovery_methods[0]
+        {
+            dns_discovery.validate_dnssec = true;
+        }
+        sanitize_fullnode_network_configs(
+            &mut node_config,
+            NodeType::PublicFullnode,
+            ChainId::testnet(),
+        )
+        .unwrap();
+    }
 }
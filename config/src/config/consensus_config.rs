@@ -3,8 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::{
-    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
-    QuorumStoreConfig, SafetyRulesConfig,
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, DagConsensusConfig, Error,
+    NodeConfig, QuorumStoreConfig, SafetyRulesConfig,
 };
 use aptos_types::chain_id::ChainId;
 use cfg_if::cfg_if;
@@ -65,6 +65,7 @@ pub struct ConsensusConfig {
     // must match one of the CHAIN_HEALTH_WINDOW_SIZES values.
     pub window_for_chain_health: usize,
     pub chain_health_backoff: Vec<ChainHealthBackoffValues>,
+    pub dag_consensus: DagConsensusConfig,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -205,6 +206,7 @@ impl Default for ConsensusConfig {
                     backoff_proposal_delay_ms: 300,
                 },
             ],
+            dag_consensus: DagConsensusConfig::default(),
         }
     }
 }
@@ -357,9 +359,10 @@ impl ConfigSanitizer for ConsensusConfig {
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
 
-        // Verify that the safety rules and quorum store configs are valid
+        // Verify that the safety rules, quorum store and dag consensus configs are valid
         SafetyRulesConfig::sanitize(node_config, node_type, chain_id)?;
         QuorumStoreConfig::sanitize(node_config, node_type, chain_id)?;
+        DagConsensusConfig::sanitize(node_config, node_type, chain_id)?;
 
         // Verify that the consensus-only feature is not enabled in mainnet
         if chain_id.is_mainnet() && is_consensus_only_perf_test_enabled() {
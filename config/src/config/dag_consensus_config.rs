@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DagConsensusConfig {
+    // Replaces the Jolteon round manager with the DAG ordering path. Experimental, and only
+    // meant for local swarms and benchmarks for now.
+    pub enabled: bool,
+    // Payload limits for a single DAG node. A validator creates one node per round, so these
+    // are smaller than the corresponding block limits.
+    pub max_node_txns: u64,
+    pub max_node_bytes: u64,
+    // Minimum time between entering two consecutive DAG rounds (in milliseconds)
+    pub round_min_interval_ms: u64,
+    // Timeout for a single reliable broadcast or fetch rpc (in milliseconds)
+    pub rpc_timeout_ms: u64,
+    // Number of rounds kept in memory below the highest ordered anchor, or below the latest
+    // commit if it's lower
+    pub dag_window_rounds: u64,
+}
+
+impl Default for DagConsensusConfig {
+    fn default() -> DagConsensusConfig {
+        DagConsensusConfig {
+            enabled: false,
+            max_node_txns: 1000,
+            max_node_bytes: 1024 * 1024, // 1MB
+            round_min_interval_ms: 100,
+            rpc_timeout_ms: 1000,
+            dag_window_rounds: 40,
+        }
+    }
+}
+
+impl ConfigSanitizer for DagConsensusConfig {
+    fn sanitize(
+        node_config: &mut NodeConfig,
+        _node_type: NodeType,
+        chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let dag_config = &node_config.consensus.dag_consensus;
+
+        // DAG consensus is experimental and must not be enabled in mainnet
+        if chain_id.is_mainnet() && dag_config.enabled {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "dag_consensus should not be enabled in mainnet!".to_string(),
+            ));
+        }
+
+        // The anchor ordering rule needs at least two rounds to commit anything
+        if dag_config.dag_window_rounds < 2 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "dag_window_rounds must be at least 2, found {}",
+                    dag_config.dag_window_rounds
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConsensusConfig;
+
+    #[test]
+    fn test_sanitize_dag_enabled_mainnet() {
+        let mut node_config = NodeConfig {
+            consensus: ConsensusConfig {
+                dag_consensus: DagConsensusConfig {
+                    enabled: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails for mainnet but passes for testnet
+        let error =
+            DagConsensusConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::mainnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        DagConsensusConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_dag_window_too_small() {
+        let mut node_config = NodeConfig {
+            consensus: ConsensusConfig {
+                dag_consensus: DagConsensusConfig {
+                    dag_window_rounds: 1,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let error =
+            DagConsensusConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+}
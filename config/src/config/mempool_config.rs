@@ -7,9 +7,10 @@ use crate::config::{
     node_config_loader::NodeType, Error, NodeConfig, MAX_APPLICATION_MESSAGE_SIZE,
 };
 use aptos_global_constants::DEFAULT_BUCKETS;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::{collections::HashSet, str::FromStr};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -53,6 +54,51 @@ pub struct MempoolConfig {
     pub broadcast_buckets: Vec<u64>,
     pub eager_expire_threshold_ms: Option<u64>,
     pub eager_expire_time_ms: u64,
+    /// Minimum gas unit price increase, in percent, required for a transaction to replace a
+    /// pending transaction with the same sender and sequence number.
+    pub replace_by_fee_min_gas_price_bump_percentage: u64,
+    /// Priority lanes with reserved space in every batch pulled by consensus.
+    ///
+    /// A transaction is assigned to the first lane it matches. Transactions that match
+    /// no lane only compete for the space left over by the lanes.
+    pub lanes: Vec<MempoolLaneConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MempoolLaneConfig {
+    /// Name of the lane, used as the metrics label
+    pub name: String,
+    /// Transactions sent by any of these accounts match the lane
+    #[serde(default)]
+    pub senders: Vec<AccountAddress>,
+    /// Entry function calls into any of these modules match the lane, e.g. `0x1::aptos_governance`
+    #[serde(default)]
+    pub entry_function_modules: Vec<String>,
+    /// Transactions with at least this gas unit price match the lane
+    #[serde(default)]
+    pub min_gas_unit_price: Option<u64>,
+    /// Percentage of the transactions of every batch reserved for this lane
+    pub reserved_batch_percentage: u64,
+    /// Maximum number of transactions allowed in the lane
+    pub capacity: usize,
+}
+
+impl MempoolLaneConfig {
+    /// Parses `entry_function_modules` into (address, module name) pairs.
+    pub fn parse_entry_function_modules(&self) -> Result<Vec<(AccountAddress, String)>, String> {
+        self.entry_function_modules
+            .iter()
+            .map(|module| {
+                let (address, name) = module.split_once("::").ok_or_else(|| {
+                    format!("Invalid module {}, expected <address>::<name>", module)
+                })?;
+                let address = AccountAddress::from_str(address)
+                    .map_err(|e| format!("Invalid address in module {}: {}", module, e))?;
+                Ok((address, name.to_string()))
+            })
+            .collect()
+    }
 }
 
 impl Default for MempoolConfig {
@@ -77,17 +123,54 @@ impl Default for MempoolConfig {
             broadcast_buckets: DEFAULT_BUCKETS.to_vec(),
             eager_expire_threshold_ms: Some(10_000),
             eager_expire_time_ms: 3_000,
+            replace_by_fee_min_gas_price_bump_percentage: 10,
+            lanes: vec![],
         }
     }
 }
 
 impl ConfigSanitizer for MempoolConfig {
     fn sanitize(
-        _node_config: &mut NodeConfig,
+        node_config: &mut NodeConfig,
         _node_type: NodeType,
         _chain_id: ChainId,
     ) -> Result<(), Error> {
-        Ok(()) // TODO: add reasonable verifications
+        let sanitizer_name = Self::get_sanitizer_name();
+        let lanes = &node_config.mempool.lanes;
+
+        // Verify that the lanes are uniquely named and well formed
+        let mut names = HashSet::new();
+        for lane in lanes {
+            if !names.insert(&lane.name) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("Duplicate mempool lane name: {}", lane.name),
+                ));
+            }
+            if let Err(error) = lane.parse_entry_function_modules() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!("Mempool lane {}: {}", lane.name, error),
+                ));
+            }
+        }
+
+        // Verify that the lanes don't reserve more than the full batch
+        let total_reserved: u64 = lanes
+            .iter()
+            .map(|lane| lane.reserved_batch_percentage)
+            .sum();
+        if total_reserved > 100 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "Mempool lanes reserve {}% of each batch, which is more than 100%!",
+                    total_reserved
+                ),
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -256,4 +339,49 @@ mod tests {
             default_mempool_config.shared_mempool_tick_interval_ms
         );
     }
+
+    #[test]
+    fn test_sanitize_lanes() {
+        let lane = |name: &str, reserved_batch_percentage: u64| MempoolLaneConfig {
+            name: name.into(),
+            senders: vec![AccountAddress::ONE],
+            entry_function_modules: vec!["0x1::aptos_governance".into()],
+            min_gas_unit_price: None,
+            reserved_batch_percentage,
+            capacity: 1_000,
+        };
+
+        // Verify that well formed lanes pass the sanitizer
+        let mut node_config = NodeConfig {
+            mempool: MempoolConfig {
+                lanes: vec![lane("governance", 20), lane("operators", 10)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        MempoolConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet()).unwrap();
+
+        // Verify that duplicate lane names are rejected
+        node_config.mempool.lanes = vec![lane("governance", 20), lane("governance", 10)];
+        let error =
+            MempoolConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that reserving more than the full batch is rejected
+        node_config.mempool.lanes = vec![lane("governance", 60), lane("operators", 50)];
+        let error =
+            MempoolConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that malformed modules are rejected
+        let mut malformed_lane = lane("governance", 20);
+        malformed_lane.entry_function_modules = vec!["aptos_governance".into()];
+        node_config.mempool.lanes = vec![malformed_lane];
+        let error =
+            MempoolConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
@@ -14,8 +14,10 @@ use aptos_crypto::{x25519, Uniform};
 use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_types::{
-    account_address::from_identity_public_key, network_address::NetworkAddress,
-    transaction::authenticator::AuthenticationKey, PeerId,
+    account_address::from_identity_public_key,
+    network_address::{NetworkAddress, Protocol},
+    transaction::authenticator::AuthenticationKey,
+    PeerId,
 };
 use rand::{
     rngs::{OsRng, StdRng},
@@ -23,7 +25,8 @@ use rand::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::max,
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     fmt,
     path::PathBuf,
@@ -53,6 +56,9 @@ pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const CONSENSUS_PROTOCOL_WEIGHT: u32 = 16;
+pub const DEFAULT_PROTOCOL_WEIGHT: u32 = 4;
+pub const STATE_SYNC_PROTOCOL_WEIGHT: u32 = 1;
 pub const INBOUND_TCP_RX_BUFFER_SIZE: u32 = 3 * 1024 * 1024; // 3MB ~6MB/s with 500ms latency
 pub const INBOUND_TCP_TX_BUFFER_SIZE: u32 = 512 * 1024; // 1MB use a bigger spoon
 pub const OUTBOUND_TCP_RX_BUFFER_SIZE: u32 = 3 * 1024 * 1024; // 3MB ~6MB/s with 500ms latency
@@ -82,6 +88,9 @@ pub struct NetworkConfig {
     // TODO: Add support for multiple listen/advertised addresses in config.
     /// The address that this node is listening on for new connections.
     pub listen_address: NetworkAddress,
+    /// The transport used to connect to peers on this network. QUIC networks must listen on a
+    /// UDP address, e.g., `/ip4/0.0.0.0/udp/6180`.
+    pub transport_protocol: TransportProtocol,
     /// Select this to enforce that both peers should authenticate each other, otherwise
     /// authentication only occurs for outgoing connections.
     pub mutual_authentication: bool,
@@ -117,6 +126,8 @@ pub struct NetworkConfig {
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     /// Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    /// Weighted priority and byte-rate limits for the outbound messages to each peer
+    pub outbound_traffic_shaping: OutboundTrafficShapingConfig,
     /// The maximum size of an inbound or outbound message (it may be divided into multiple frame)
     pub max_message_size: usize,
 }
@@ -135,6 +146,7 @@ impl NetworkConfig {
             discovery_methods: Vec::new(),
             identity: Identity::None,
             listen_address: "/ip4/0.0.0.0/tcp/6180".parse().unwrap(),
+            transport_protocol: TransportProtocol::default(),
             mutual_authentication,
             network_id,
             runtime_threads: None,
@@ -154,6 +166,7 @@ impl NetworkConfig {
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            outbound_traffic_shaping: OutboundTrafficShapingConfig::default(),
             max_message_size: MAX_MESSAGE_SIZE,
             inbound_rx_buffer_size_bytes: Some(INBOUND_TCP_RX_BUFFER_SIZE),
             inbound_tx_buffer_size_bytes: Some(INBOUND_TCP_TX_BUFFER_SIZE),
@@ -283,6 +296,19 @@ impl NetworkConfig {
         self.identity = Identity::from_config(identity_key, peer_id);
     }
 
+    /// Returns true iff the listen address can be served by the transport protocol
+    pub fn listen_address_matches_transport(&self) -> bool {
+        let is_udp_address = self
+            .listen_address
+            .as_slice()
+            .iter()
+            .any(|protocol| matches!(protocol, Protocol::Udp(_)));
+        match self.transport_protocol {
+            TransportProtocol::Tcp => !is_udp_address,
+            TransportProtocol::Quic => is_udp_address,
+        }
+    }
+
     fn verify_address(peer_id: &PeerId, addr: &NetworkAddress) -> Result<(), Error> {
         if !addr.is_aptosnet_addr() {
             return Err(Error::InvariantViolation(format!(
@@ -326,9 +352,27 @@ pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
+/// The transport that carries the (Noise authenticated) connections of a network
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportProtocol {
+    /// All messages are multiplexed over a single TCP stream per peer
+    Tcp,
+    /// Each protocol has its own QUIC stream per peer, so a large message on one protocol
+    /// doesn't delay the messages of the others
+    Quic,
+}
+
+impl Default for TransportProtocol {
+    fn default() -> Self {
+        TransportProtocol::Tcp
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct FileDiscovery {
@@ -343,6 +387,41 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovers peers from DNS. Each target of the SRV record `srv_name` is dialed on the
+/// advertised port, and must publish a TXT record of the form
+/// `peer_id=<peer id> x25519=<public key> [role=<peer role>]`. The records are re-resolved
+/// once their TTL expires, bounded by `min_interval_secs` and `max_interval_secs`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DnsDiscovery {
+    /// The SRV record to resolve, e.g., `_aptos._tcp.peers.example.com`
+    pub srv_name: String,
+    /// The most trusted role a peer discovered via DNS can be assigned. A TXT record can
+    /// only lower the trust of its peer.
+    pub peer_role: PeerRole,
+    /// Peers that are always included in the discovered peer set, e.g., to bootstrap the
+    /// network when DNS is unavailable
+    #[serde(default)]
+    pub bootstrap_peers: PeerSet,
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+    /// Whether the answers must be DNSSEC validated. Without validation, the answers can be
+    /// spoofed, so the discovered peers are never trusted more than `Upstream`.
+    #[serde(default)]
+    pub validate_dnssec: bool,
+}
+
+impl DnsDiscovery {
+    /// The most trusted role that can be assigned to the discovered peers
+    pub fn max_peer_role(&self) -> PeerRole {
+        if self.validate_dnssec {
+            self.peer_role
+        } else {
+            max(self.peer_role, PeerRole::Upstream)
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
@@ -367,6 +446,70 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Shapes the outbound traffic to each peer: the messages of each protocol are queued separately,
+/// and the queues are served in proportion to their weights (in bytes), subject to the byte-rate
+/// limits. Protocols are identified by their names, e.g., `ConsensusRpcCompressed`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundTrafficShapingConfig {
+    /// Enables the traffic shaping, otherwise messages are written in the order they are sent
+    pub enabled: bool,
+    /// The weight of the protocols without an entry in `protocol_weights`
+    pub default_protocol_weight: u32,
+    /// The weight of each protocol
+    pub protocol_weights: BTreeMap<String, u32>,
+    /// The byte-rate limit on all outbound messages to a peer, if any
+    pub peer_rate_limit: Option<ByteRateLimitConfig>,
+    /// The byte-rate limits on the outbound messages of each protocol to a peer
+    pub protocol_rate_limits: BTreeMap<String, ByteRateLimitConfig>,
+}
+
+impl Default for OutboundTrafficShapingConfig {
+    fn default() -> Self {
+        let consensus_protocols = [
+            "ConsensusRpcBcs",
+            "ConsensusDirectSendBcs",
+            "ConsensusRpcJson",
+            "ConsensusDirectSendJson",
+            "ConsensusRpcCompressed",
+            "ConsensusDirectSendCompressed",
+        ];
+        let mut protocol_weights: BTreeMap<String, u32> = consensus_protocols
+            .iter()
+            .map(|protocol| (protocol.to_string(), CONSENSUS_PROTOCOL_WEIGHT))
+            .collect();
+        protocol_weights.insert("StorageServiceRpc".into(), STATE_SYNC_PROTOCOL_WEIGHT);
+
+        Self {
+            enabled: false,
+            default_protocol_weight: DEFAULT_PROTOCOL_WEIGHT,
+            protocol_weights,
+            peer_rate_limit: None,
+            protocol_rate_limits: BTreeMap::new(),
+        }
+    }
+}
+
+impl OutboundTrafficShapingConfig {
+    /// Returns the weight of the given protocol
+    pub fn protocol_weight(&self, protocol: &str) -> u32 {
+        self.protocol_weights
+            .get(protocol)
+            .copied()
+            .unwrap_or(self.default_protocol_weight)
+    }
+}
+
+/// A token bucket of bytes
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ByteRateLimitConfig {
+    /// Maximum number of bytes/s
+    pub byte_bucket_rate: usize,
+    /// Maximum burst of bytes. Must fit the largest message.
+    pub byte_bucket_size: usize,
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -6,9 +6,13 @@ use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
     node_config_loader::NodeType, Error, NodeConfig,
 };
-use aptos_types::chain_id::ChainId;
+use aptos_types::{
+    account_address::AccountAddress, chain_id::ChainId,
+    state_store::state_key_filter::StateKeyFilter,
+};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::path::PathBuf;
 
 // The maximum message size per state sync message
 const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024; /* 4 MiB */
@@ -23,11 +27,13 @@ const MAX_TRANSACTION_OUTPUT_CHUNK_SIZE: u64 = 1000;
 const MAX_CONCURRENT_REQUESTS: u64 = 6;
 const MAX_CONCURRENT_STATE_REQUESTS: u64 = 6;
 
-#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncConfig {
+    pub backup_restore: BackupRestoreConfig,
     pub data_streaming_service: DataStreamingServiceConfig,
     pub aptos_data_client: AptosDataClientConfig,
+    pub partial_replication: PartialReplicationConfig,
     pub state_sync_driver: StateSyncDriverConfig,
     pub storage_service: StorageServiceConfig,
 }
@@ -44,6 +50,10 @@ pub enum BootstrappingMode {
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
     ExecuteOrApplyFromGenesis,
+    /// Restores the latest state snapshot from the configured backup storage
+    /// and then applies transaction outputs (starting at the restored version).
+    /// Requires the node to be built with the `restore-from-backup` feature.
+    RestoreFromBackup,
 }
 
 impl BootstrappingMode {
@@ -57,7 +67,76 @@ impl BootstrappingMode {
                 "execute_transactions_from_genesis"
             },
             BootstrappingMode::ExecuteOrApplyFromGenesis => "execute_or_apply_from_genesis",
+            BootstrappingMode::RestoreFromBackup => "restore_from_backup",
+        }
+    }
+}
+
+/// The backup storage to restore from when bootstrapping with
+/// `BootstrappingMode::RestoreFromBackup`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStorageConfig {
+    /// A backup on the local file system (e.g., a mounted volume)
+    LocalFs { dir: PathBuf },
+    /// A backup accessed through the commands in the given command adapter config
+    CommandAdapter { config_path: PathBuf },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupRestoreConfig {
+    /// The backup storage to restore from (required to restore from a backup)
+    pub backup_storage: Option<BackupStorageConfig>,
+    /// The maximum number of concurrent downloads from the backup storage
+    pub concurrent_downloads: usize,
+    /// The directory to cache the backup metadata in (a temporary directory if not set)
+    pub metadata_cache_dir: Option<PathBuf>,
+}
+
+impl Default for BackupRestoreConfig {
+    fn default() -> Self {
+        Self {
+            backup_storage: None,
+            concurrent_downloads: 8,
+            metadata_cache_dir: None,
+        }
+    }
+}
+
+/// The partial replication config lets lightweight fullnodes persist only the
+/// state values they serve. All ledger infos and transaction outputs are still
+/// verified, and the state Merkle tree is maintained in full.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PartialReplicationConfig {
+    /// Enables partial replication of the state
+    pub enabled: bool,
+    /// The accounts for which all state values are persisted
+    pub accounts: Vec<AccountAddress>,
+    /// The resource type prefixes (e.g., "0x1::coin::CoinStore") for which state
+    /// values are persisted, under any account
+    pub resource_prefixes: Vec<String>,
+}
+
+impl PartialReplicationConfig {
+    /// Returns the filter for the state keys to persist, or None if the state
+    /// is fully replicated. The framework account is always replicated, as the
+    /// node reads the on-chain configs from it.
+    pub fn state_key_filter(&self) -> Option<StateKeyFilter> {
+        if !self.enabled {
+            return None;
         }
+
+        let accounts = self
+            .accounts
+            .iter()
+            .copied()
+            .chain(std::iter::once(AccountAddress::ONE));
+        Some(StateKeyFilter::new(
+            accounts,
+            self.resource_prefixes.clone(),
+        ))
     }
 }
 
@@ -286,10 +365,49 @@ impl Default for AptosDataClientConfig {
 
 impl ConfigSanitizer for StateSyncConfig {
     fn sanitize(
-        _node_config: &mut NodeConfig,
-        _node_type: NodeType,
+        node_config: &mut NodeConfig,
+        node_type: NodeType,
         _chain_id: ChainId,
     ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let state_sync_config = &node_config.state_sync;
+
+        // Verify that a backup storage is configured if we restore from a backup
+        if state_sync_config.state_sync_driver.bootstrapping_mode
+            == BootstrappingMode::RestoreFromBackup
+            && state_sync_config.backup_restore.backup_storage.is_none()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The bootstrapping mode is RestoreFromBackup, but no backup storage is configured!"
+                    .into(),
+            ));
+        }
+
+        // Verify that partial replication is only used by fullnodes that apply outputs
+        if state_sync_config.partial_replication.enabled {
+            if node_type.is_validator() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Partial replication is not supported by validators!".into(),
+                ));
+            }
+
+            let driver_config = &state_sync_config.state_sync_driver;
+            if driver_config.bootstrapping_mode != BootstrappingMode::DownloadLatestStates
+                || driver_config.continuous_syncing_mode
+                    != ContinuousSyncingMode::ApplyTransactionOutputs
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Partial replication requires the DownloadLatestStates bootstrapping mode and \
+                    the ApplyTransactionOutputs continuous syncing mode, as transactions can't be \
+                    executed without the full state!"
+                        .into(),
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -573,6 +691,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sanitize_restore_from_backup() {
+        // Create a node config that restores from a backup, without a backup storage
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::RestoreFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Configure a backup storage, and verify that the config is now valid
+        node_config.state_sync.backup_restore.backup_storage = Some(BackupStorageConfig::LocalFs {
+            dir: PathBuf::from("/opt/aptos/backup"),
+        });
+        StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_partial_replication() {
+        // Create a node config with partial replication enabled, that executes transactions
+        let mut node_config = create_execution_mode_config();
+        node_config.state_sync.partial_replication = PartialReplicationConfig {
+            enabled: true,
+            accounts: vec![AccountAddress::ONE],
+            resource_prefixes: vec![],
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Download the latest states and apply outputs, and verify that the config is now valid
+        let driver_config = &mut node_config.state_sync.state_sync_driver;
+        driver_config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+        driver_config.continuous_syncing_mode = ContinuousSyncingMode::ApplyTransactionOutputs;
+        StateSyncConfig::sanitize(&mut node_config, NodeType::PublicFullnode, ChainId::test())
+            .unwrap();
+
+        // Verify that partial replication is not allowed for validators
+        let error =
+            StateSyncConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::test())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
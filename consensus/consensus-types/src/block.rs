@@ -223,6 +223,14 @@ impl Block {
         Self::new_proposal_from_block_data(block_data, validator_signer)
     }
 
+    pub fn new_for_dag(block_data: BlockData) -> Self {
+        Block {
+            id: block_data.hash(),
+            block_data,
+            signature: None,
+        }
+    }
+
     pub fn new_proposal_from_block_data(
         block_data: BlockData,
         validator_signer: &ValidatorSigner,
@@ -250,6 +258,7 @@ impl Block {
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
             BlockType::NilBlock { .. } => self.quorum_cert().verify(validator),
+            BlockType::DAGBlock { .. } => bail!("We should not accept DAG blocks from others"),
             BlockType::Proposal { author, .. } => {
                 let signature = self
                     .signature
@@ -42,6 +42,18 @@ pub enum BlockType {
     /// from the previous epoch.  The genesis block is used as the first root block of the
     /// BlockTree for all epochs.
     Genesis,
+    /// A block ordered by the DAG consensus path. It is never proposed or voted on: every
+    /// validator constructs it identically from the same ordered anchor and its causal history.
+    DAGBlock {
+        /// Author of the anchor node that ordered this block
+        author: Author,
+        /// Authors of the anchors that were skipped since the previously ordered anchor.
+        failed_authors: Vec<(Round, Author)>,
+        /// Combined payload of all the nodes ordered by the anchor, in order.
+        payload: Payload,
+        /// Digests of the DAG nodes ordered by the anchor, in order.
+        node_digests: Vec<HashValue>,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
@@ -79,10 +91,9 @@ pub struct BlockData {
 
 impl BlockData {
     pub fn author(&self) -> Option<Author> {
-        if let BlockType::Proposal { author, .. } = self.block_type {
-            Some(author)
-        } else {
-            None
+        match self.block_type {
+            BlockType::Proposal { author, .. } | BlockType::DAGBlock { author, .. } => Some(author),
+            BlockType::NilBlock { .. } | BlockType::Genesis => None,
         }
     }
 
@@ -99,10 +110,11 @@ impl BlockData {
     }
 
     pub fn payload(&self) -> Option<&Payload> {
-        if let BlockType::Proposal { payload, .. } = &self.block_type {
-            Some(payload)
-        } else {
-            None
+        match &self.block_type {
+            BlockType::Proposal { payload, .. } | BlockType::DAGBlock { payload, .. } => {
+                Some(payload)
+            },
+            BlockType::NilBlock { .. } | BlockType::Genesis => None,
         }
     }
 
@@ -126,6 +138,10 @@ impl BlockData {
         matches!(self.block_type, BlockType::NilBlock { .. })
     }
 
+    pub fn is_dag_block(&self) -> bool {
+        matches!(self.block_type, BlockType::DAGBlock { .. })
+    }
+
     /// the list of consecutive proposers from the immediately preceeding
     /// rounds that didn't produce a successful block
     pub fn failed_authors(&self) -> Option<&Vec<(Round, Author)>> {
@@ -134,6 +150,9 @@ impl BlockData {
                 ref failed_authors, ..
             } => Some(failed_authors),
             BlockType::NilBlock { ref failed_authors } => Some(failed_authors),
+            BlockType::DAGBlock {
+                ref failed_authors, ..
+            } => Some(failed_authors),
             BlockType::Genesis => None,
         }
     }
@@ -232,6 +251,41 @@ impl BlockData {
         }
     }
 
+    /// DAG blocks are chained onto the previously ordered block with a placeholder quorum
+    /// certificate: the ordering is justified by the DAG itself, the signature bitvec only
+    /// records which validators voted for the anchor.
+    pub fn new_for_dag(
+        epoch: u64,
+        round: Round,
+        timestamp_usecs: u64,
+        payload: Payload,
+        author: Author,
+        failed_authors: Vec<(Round, Author)>,
+        node_digests: Vec<HashValue>,
+        parent_block_info: BlockInfo,
+        anchor_voters: AggregateSignature,
+    ) -> Self {
+        let quorum_cert = QuorumCert::new(
+            VoteData::new(parent_block_info.clone(), parent_block_info.clone()),
+            LedgerInfoWithSignatures::new(
+                LedgerInfo::new(parent_block_info, HashValue::zero()),
+                anchor_voters,
+            ),
+        );
+        Self {
+            epoch,
+            round,
+            timestamp_usecs,
+            quorum_cert,
+            block_type: BlockType::DAGBlock {
+                author,
+                failed_authors,
+                payload,
+                node_digests,
+            },
+        }
+    }
+
     /// It's a reconfiguration suffix block if the parent block's executed state indicates next epoch.
     pub fn is_reconfiguration_suffix(&self) -> bool {
         self.quorum_cert.certified_block().has_reconfiguration()
@@ -130,6 +130,22 @@ impl Payload {
         matches!(self, Payload::DirectMempool(_))
     }
 
+    /// Appends the transactions (or proofs) of `other` to this payload. Both payloads must be
+    /// of the same kind.
+    pub fn extend(self, other: Payload) -> Self {
+        match (self, other) {
+            (Payload::DirectMempool(mut txns), Payload::DirectMempool(other_txns)) => {
+                txns.extend(other_txns);
+                Payload::DirectMempool(txns)
+            },
+            (Payload::InQuorumStore(mut proof_with_data), Payload::InQuorumStore(other)) => {
+                proof_with_data.proofs.extend(other.proofs);
+                Payload::InQuorumStore(ProofWithData::new(proof_with_data.proofs))
+            },
+            (_, _) => unreachable!("Cannot extend payloads of different kinds"),
+        }
+    }
+
     /// This is computationally expensive on the first call
     pub fn size(&self) -> usize {
         match self {
@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use aptos_consensus_types::block::block_test_utils::certificate_for_genesis;
+use crate::dag::{recover_votes, DAGStorage, Node};
+use aptos_consensus_types::{block::block_test_utils::certificate_for_genesis, common::Payload};
 use aptos_temppath::TempPath;
+use aptos_types::validator_signer::ValidatorSigner;
 
 #[test]
 fn test_put_get() {
@@ -70,3 +72,45 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_dag_votes() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+    let signer = ValidatorSigner::random(None);
+    let create_vote = |epoch, round| {
+        let node = Node::new(
+            epoch,
+            round,
+            signer.author(),
+            0,
+            Payload::empty(false),
+            vec![],
+        );
+        Vote::new(node.metadata(), signer.sign(&node.metadata()).unwrap())
+    };
+
+    assert_eq!(db.get_dag_votes().unwrap().len(), 0);
+
+    let old_vote = create_vote(1, 5);
+    let votes = vec![create_vote(2, 1), create_vote(2, 2)];
+    db.save_dag_vote(&old_vote).unwrap();
+    for vote in &votes {
+        db.save_dag_vote(vote).unwrap();
+    }
+    assert_eq!(db.get_dag_votes().unwrap().len(), 3);
+
+    // Only the votes of the epoch are recovered, the older ones are deleted
+    db.save_pruned_round(1, 3).unwrap();
+    let recovered_votes = recover_votes(&db, 2).unwrap();
+    assert_eq!(recovered_votes.votes, votes);
+    assert_eq!(recovered_votes.pruned_round, 0);
+    assert_eq!(db.get_dag_votes().unwrap(), votes);
+
+    // Verify that the pruned round of the epoch is recovered with the remaining votes
+    db.save_pruned_round(2, 2).unwrap();
+    db.delete_dag_votes(vec![votes[0].clone()]).unwrap();
+    let recovered_votes = recover_votes(&db, 2).unwrap();
+    assert_eq!(recovered_votes.votes, vec![votes[1].clone()]);
+    assert_eq!(recovered_votes.pruned_round, 2);
+}
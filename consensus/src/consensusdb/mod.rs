@@ -9,9 +9,11 @@ mod schema;
 use crate::{
     consensusdb::schema::{
         block::BlockSchema,
+        dag_vote::{dag_vote_key, DagVoteSchema},
         quorum_certificate::QCSchema,
         single_entry::{SingleEntryKey, SingleEntrySchema},
     },
+    dag::Vote,
     error::DbError,
 };
 use anyhow::Result;
@@ -19,7 +21,7 @@ use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
-use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
+use schema::{BLOCK_CF_NAME, DAG_VOTE_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
 /// The name of the consensus db file
@@ -52,6 +54,7 @@ impl ConsensusDB {
             BLOCK_CF_NAME,
             QC_CF_NAME,
             SINGLE_ENTRY_CF_NAME,
+            DAG_VOTE_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
@@ -140,6 +143,42 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    pub fn save_dag_vote(&self, vote: &Vote) -> Result<(), DbError> {
+        let batch = SchemaBatch::new();
+        batch.put::<DagVoteSchema>(&dag_vote_key(vote), vote)?;
+        self.commit(batch)
+    }
+
+    /// Get all the votes sent on DAG nodes.
+    pub fn get_dag_votes(&self) -> Result<Vec<Vote>, DbError> {
+        let mut iter = self.db.iter::<DagVoteSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter
+            .map(|result| result.map(|(_, vote)| vote))
+            .collect::<Result<Vec<Vote>>>()?)
+    }
+
+    pub fn save_dag_pruned_round(&self, pruned_round: Vec<u8>) -> Result<(), DbError> {
+        let batch = SchemaBatch::new();
+        batch.put::<SingleEntrySchema>(&SingleEntryKey::DagPrunedRound, &pruned_round)?;
+        self.commit(batch)
+    }
+
+    /// Get serialized epoch and round below which the DAG votes were pruned (if available)
+    pub fn get_dag_pruned_round(&self) -> Result<Option<Vec<u8>>, DbError> {
+        Ok(self
+            .db
+            .get::<SingleEntrySchema>(&SingleEntryKey::DagPrunedRound)?)
+    }
+
+    pub fn delete_dag_votes(&self, votes: Vec<Vote>) -> Result<(), DbError> {
+        let batch = SchemaBatch::new();
+        votes
+            .iter()
+            .try_for_each(|vote| batch.delete::<DagVoteSchema>(&dag_vote_key(vote)))?;
+        self.commit(batch)
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
@@ -0,0 +1,64 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the votes sent on DAG nodes.
+//!
+//! Serialized vote identified by the epoch, round and author of the node it signs.
+//! ```text
+//! |<--------key-------->|<-value->|
+//! | epoch, round, author |  Vote   |
+//! ```
+
+use super::{ensure_slice_len_eq, DAG_VOTE_CF_NAME};
+use crate::dag::Vote;
+use anyhow::Result;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::mem::size_of;
+
+pub type DagVoteKey = (u64, Round, Author);
+
+define_schema!(DagVoteSchema, DagVoteKey, Vote, DAG_VOTE_CF_NAME);
+
+/// Votes are keyed by the (epoch, round, author) of the node they sign, an honest validator votes
+/// on at most one node per key.
+pub fn dag_vote_key(vote: &Vote) -> DagVoteKey {
+    let metadata = vote.metadata();
+    (metadata.epoch(), metadata.round(), *metadata.author())
+}
+
+impl KeyCodec<DagVoteSchema> for DagVoteKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (epoch, round, author) = self;
+        let mut encoded = Vec::with_capacity(2 * size_of::<u64>() + Author::LENGTH);
+        encoded.write_u64::<BigEndian>(*epoch)?;
+        encoded.write_u64::<BigEndian>(*round)?;
+        encoded.extend_from_slice(author.as_ref());
+        Ok(encoded)
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 2 * size_of::<u64>() + Author::LENGTH)?;
+        let epoch = data.read_u64::<BigEndian>()?;
+        let round = data.read_u64::<BigEndian>()?;
+        let author = Author::try_from(data)?;
+        Ok((epoch, round, author))
+    }
+}
+
+impl ValueCodec<DagVoteSchema> for Vote {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
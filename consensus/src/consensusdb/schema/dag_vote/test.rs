@@ -0,0 +1,18 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::dag::Node;
+use aptos_consensus_types::common::Payload;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let node = Node::new(1, 2, signer.author(), 0, Payload::empty(false), vec![]);
+    let vote = Vote::new(node.metadata(), signer.sign(&node.metadata()).unwrap());
+    assert_encode_decode::<DagVoteSchema>(&(1, 2, signer.author()), &vote);
+}
+
+test_no_panic_decoding!(DagVoteSchema);
@@ -38,6 +38,8 @@ pub enum SingleEntryKey {
     LastVote = 0,
     // Two chain timeout cert
     Highest2ChainTimeoutCert = 1,
+    // Epoch and round below which the DAG votes were pruned
+    DagPrunedRound = 2,
 }
 
 impl KeyCodec<SingleEntrySchema> for SingleEntryKey {
@@ -738,6 +738,44 @@ pub static RPC_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counters(queued,dequeued,dropped) related to dag rpc request channel
+pub static DAG_RPC_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_dag_rpc_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to dag rpc request channel",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Counters(queued,dequeued,dropped) related to dag driver per epoch task
+pub static DAG_DRIVER_TASK_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_dag_driver_task_msgs_count",
+        "Counters(queued,dequeued,dropped) related to dag driver per epoch task",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Number of DAG anchors ordered
+pub static DAG_ORDERED_ANCHORS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_dag_ordered_anchors_count",
+        "Number of DAG anchors ordered"
+    )
+    .unwrap()
+});
+
+/// Current DAG round of this validator
+pub static DAG_CURRENT_ROUND: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_dag_current_round",
+        "Current DAG round of this validator"
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to block retrieval per epoch task
 pub static BLOCK_RETRIEVAL_TASK_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
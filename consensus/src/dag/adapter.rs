@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::order_rule::OrderedNodes, experimental::buffer_manager::OrderedBlocks,
+    payload_manager::PayloadManager,
+};
+use aptos_consensus_types::{
+    block::Block,
+    block_data::BlockData,
+    common::{Payload, Round},
+    executed_block::ExecutedBlock,
+};
+use aptos_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue};
+use aptos_executor_types::StateComputeResult;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
+use futures::{channel::mpsc::UnboundedSender, SinkExt};
+use std::sync::Arc;
+
+/// The block info the first ordered block is chained on, when ordering after `ledger_info`, the
+/// latest commit.
+pub fn committed_block_info(ledger_info: &LedgerInfo) -> BlockInfo {
+    if ledger_info.ends_epoch() {
+        Block::make_genesis_block_from_ledger_info(ledger_info).gen_block_info(
+            *ACCUMULATOR_PLACEHOLDER_HASH,
+            0,
+            None,
+        )
+    } else {
+        let commit_info = ledger_info.commit_info();
+        BlockInfo::new(
+            commit_info.epoch(),
+            commit_info.round(),
+            commit_info.id(),
+            *ACCUMULATOR_PLACEHOLDER_HASH,
+            0,
+            commit_info.timestamp_usecs(),
+            None,
+        )
+    }
+}
+
+/// The round and digest of the anchor committed by `ledger_info`, none if it's the ledger info
+/// of the previous epoch.
+pub fn committed_anchor(ledger_info: &LedgerInfo) -> Option<(Round, HashValue)> {
+    (!ledger_info.ends_epoch()).then(|| (ledger_info.round(), ledger_info.consensus_data_hash()))
+}
+
+/// Turns every ordered anchor into a block chained on the previous one and hands it to the
+/// decoupled execution pipeline, the same way `OrderingStateComputer` does for Jolteon.
+pub struct OrderedNotifier {
+    parent_block_info: BlockInfo,
+    quorum_store_enabled: bool,
+    payload_manager: Arc<PayloadManager>,
+    ordered_blocks_tx: UnboundedSender<OrderedBlocks>,
+    // Receives the round of every committed anchor
+    committed_tx: UnboundedSender<Round>,
+}
+
+impl OrderedNotifier {
+    pub fn new(
+        parent_block_info: BlockInfo,
+        quorum_store_enabled: bool,
+        payload_manager: Arc<PayloadManager>,
+        ordered_blocks_tx: UnboundedSender<OrderedBlocks>,
+        committed_tx: UnboundedSender<Round>,
+    ) -> Self {
+        Self {
+            parent_block_info,
+            quorum_store_enabled,
+            payload_manager,
+            ordered_blocks_tx,
+            committed_tx,
+        }
+    }
+
+    /// Chains the next ordered block on `parent_block_info`, after state syncing to it.
+    pub fn reset(&mut self, parent_block_info: BlockInfo) {
+        self.parent_block_info = parent_block_info;
+    }
+
+    pub async fn send_ordered_nodes(&mut self, ordered: OrderedNodes) {
+        let OrderedNodes {
+            anchor,
+            nodes,
+            failed_authors,
+            voters,
+        } = ordered;
+        let payload = nodes
+            .iter()
+            .map(|node| node.payload().clone())
+            .fold(Payload::empty(self.quorum_store_enabled), Payload::extend);
+        // block timestamps must be strictly increasing
+        let timestamp_usecs = anchor
+            .timestamp()
+            .max(self.parent_block_info.timestamp_usecs() + 1);
+        let block = Block::new_for_dag(BlockData::new_for_dag(
+            anchor.epoch(),
+            anchor.round(),
+            timestamp_usecs,
+            payload,
+            *anchor.author(),
+            failed_authors,
+            nodes.iter().map(|node| node.digest()).collect(),
+            self.parent_block_info.clone(),
+            AggregateSignature::new(voters, None),
+        ));
+        self.payload_manager.prefetch_payload_data(&block).await;
+
+        let block_info = block.gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None);
+        // the consensus data hash is part of the commit ledger info that gets signed, so it has
+        // to be the same on every validator
+        let ordered_proof = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info.clone(), anchor.digest()),
+            AggregateSignature::empty(),
+        );
+        self.parent_block_info = block_info;
+
+        let committed_tx = self.committed_tx.clone();
+        if self
+            .ordered_blocks_tx
+            .send(OrderedBlocks {
+                ordered_blocks: vec![ExecutedBlock::new(block, StateComputeResult::new_dummy())],
+                ordered_proof,
+                callback: Box::new(move |_, ledger_info| {
+                    let _ = committed_tx.unbounded_send(ledger_info.commit_info().round());
+                }),
+            })
+            .await
+            .is_err()
+        {
+            debug!("Failed to send to buffer manager, maybe epoch ends");
+        }
+    }
+}
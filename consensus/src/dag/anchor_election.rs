@@ -0,0 +1,154 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    leader_reputation::{LeaderReputation, MetadataBackend},
+    proposer_election::ProposerElection,
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_storage_interface::{DbReader, Order};
+use aptos_types::account_config::{new_block_event_key, NewBlockEvent};
+use std::{collections::VecDeque, sync::Arc};
+
+/// Decides which validator's node is the anchor of a round.
+///
+/// The anchor of a round must only depend on the anchors ordered so far: every validator
+/// evaluates it with the same ordered history, so that they all order the same anchors.
+pub trait AnchorElection: Send + Sync {
+    fn get_anchor(&self, round: Round) -> Author;
+
+    /// Records a newly ordered anchor, it's taken into account for all the following elections.
+    fn commit(&self, event: NewBlockEvent);
+
+    /// Records the anchors of the epoch committed in storage, so that a validator that restarts
+    /// or state syncs elects the same anchors as the others. Anchors that are already recorded
+    /// are skipped.
+    fn recover(&self, _aptos_db: &dyn DbReader, _epoch: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+pub struct RoundRobinAnchorElection {
+    validators: Vec<Author>,
+}
+
+impl RoundRobinAnchorElection {
+    pub fn new(validators: Vec<Author>) -> Self {
+        Self { validators }
+    }
+}
+
+impl AnchorElection for RoundRobinAnchorElection {
+    fn get_anchor(&self, round: Round) -> Author {
+        // anchors are on every other round, rotate on the anchor rounds only
+        self.validators[(round / 2) as usize % self.validators.len()]
+    }
+
+    fn commit(&self, _event: NewBlockEvent) {}
+}
+
+/// Metadata backend built from the ordered anchors instead of the committed blocks in storage.
+/// Ordering is ahead of execution, and unlike storage it's the same on every validator at the
+/// time an anchor is elected.
+#[derive(Clone)]
+pub struct DagMetadataBackend {
+    window_size: usize,
+    // newest first
+    events: Arc<Mutex<VecDeque<NewBlockEvent>>>,
+}
+
+impl DagMetadataBackend {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window_size,
+            events: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Records an anchor, unless it's not newer than the last recorded one.
+    pub fn push(&self, event: NewBlockEvent) {
+        let mut events = self.events.lock();
+        if events.front().map_or(false, |last| {
+            (last.epoch(), last.round()) >= (event.epoch(), event.round())
+        }) {
+            return;
+        }
+        events.push_front(event);
+        events.truncate(self.window_size);
+    }
+
+    /// Records the last anchors of the epoch committed in storage. The committed block events
+    /// carry the same author, voters and failed authors as the ordered anchors.
+    pub fn recover(&self, aptos_db: &dyn DbReader, epoch: u64) -> anyhow::Result<()> {
+        let latest_version = aptos_db.get_latest_version()?;
+        let events = aptos_db.get_events(
+            &new_block_event_key(),
+            u64::max_value(),
+            Order::Descending,
+            self.window_size as u64,
+            latest_version,
+        )?;
+        let mut committed = vec![];
+        for event in events {
+            let event = bcs::from_bytes::<NewBlockEvent>(event.event.event_data())?;
+            if event.epoch() != epoch {
+                break;
+            }
+            committed.push(event);
+        }
+        for event in committed.into_iter().rev() {
+            self.push(event);
+        }
+        Ok(())
+    }
+}
+
+impl MetadataBackend for DagMetadataBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let events = self
+            .events
+            .lock()
+            .iter()
+            .filter(|event| (event.epoch(), event.round()) <= (target_epoch, target_round))
+            .cloned()
+            .collect();
+        // there's no accumulator root before execution, leader reputation must be created
+        // with use_root_hash = false
+        (events, HashValue::zero())
+    }
+}
+
+/// Reuses Jolteon's leader reputation to elect the anchors, fed with the ordered anchors.
+pub struct LeaderReputationAnchorElection {
+    backend: DagMetadataBackend,
+    leader_reputation: LeaderReputation,
+}
+
+impl LeaderReputationAnchorElection {
+    pub fn new(backend: DagMetadataBackend, leader_reputation: LeaderReputation) -> Self {
+        Self {
+            backend,
+            leader_reputation,
+        }
+    }
+}
+
+impl AnchorElection for LeaderReputationAnchorElection {
+    fn get_anchor(&self, round: Round) -> Author {
+        self.leader_reputation.get_valid_proposer(round)
+    }
+
+    fn commit(&self, event: NewBlockEvent) {
+        self.backend.push(event);
+    }
+
+    fn recover(&self, aptos_db: &dyn DbReader, epoch: u64) -> anyhow::Result<()> {
+        self.backend.recover(aptos_db, epoch)
+    }
+}
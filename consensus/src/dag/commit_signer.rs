@@ -0,0 +1,56 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::experimental::signing_phase::CommitSignerProvider;
+use aptos_crypto::bls12381;
+use aptos_safety_rules::Error;
+use aptos_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
+use std::sync::Arc;
+
+/// Signs commit votes for the DAG ordered blocks.
+///
+/// Unlike Jolteon, the ordered ledger info isn't certified by a quorum, the ordering is derived
+/// from the certified nodes of the DAG, so there are no signatures to verify before signing.
+/// That's also why it can't go through SafetyRules, which only signs the execution of quorum
+/// certified blocks. Execution is deterministic, so signing the same ordered block again after a
+/// restart produces the same commit vote.
+pub struct DagCommitSigner {
+    signer: Arc<ValidatorSigner>,
+}
+
+impl DagCommitSigner {
+    pub fn new(signer: Arc<ValidatorSigner>) -> Self {
+        Self { signer }
+    }
+}
+
+impl CommitSignerProvider for DagCommitSigner {
+    fn sign_commit_vote(
+        &self,
+        ledger_info: LedgerInfoWithSignatures,
+        new_ledger_info: LedgerInfo,
+    ) -> Result<bls12381::Signature, Error> {
+        let old_ledger_info = ledger_info.ledger_info();
+
+        if !old_ledger_info.commit_info().is_ordered_only() {
+            return Err(Error::InvalidOrderedLedgerInfo(old_ledger_info.to_string()));
+        }
+
+        if !old_ledger_info
+            .commit_info()
+            .match_ordered_only(new_ledger_info.commit_info())
+        {
+            return Err(Error::InconsistentExecutionResult(
+                old_ledger_info.commit_info().to_string(),
+                new_ledger_info.commit_info().to_string(),
+            ));
+        }
+
+        self.signer
+            .sign(&new_ledger_info)
+            .map_err(|err| Error::SerializationError(err.to_string()))
+    }
+}
@@ -0,0 +1,728 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters,
+    dag::{
+        adapter::{committed_anchor, committed_block_info, OrderedNotifier},
+        anchor_election::AnchorElection,
+        dag_store::{CertifiedNode, Dag, Node, NodeMetadata},
+        order_rule::OrderRule,
+        reliable_broadcast::{DAGMessage, DAGNetworkSender, ReliableBroadcast},
+        storage::{DAGStorage, RecoveredVotes},
+        types::{
+            CertificateAckState, CertifiedAck, FetchRequest, FetchResponse, SignatureBuilder,
+            SyncRequest, SyncResponse, Vote, MAX_FETCH_TARGETS,
+        },
+    },
+    monitor,
+    network::IncomingDAGRequest,
+    network_interface::ConsensusMsg,
+    state_replication::{PayloadClient, StateComputer},
+    util::time_service::TimeService,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_channels::aptos_channel;
+use aptos_config::config::DagConsensusConfig;
+use aptos_consensus_types::common::{Author, Payload, PayloadFilter, Round};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use aptos_network::protocols::rpc::error::RpcError;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
+use bytes::Bytes;
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    FutureExt, StreamExt,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Nodes with a timestamp further in the future are rejected, same bound as for proposals.
+const TIMESTAMP_BOUND: Duration = Duration::from_secs(5 * 60);
+
+/// Drives the DAG protocol of a single epoch: creates a node every round, votes on the nodes of
+/// the other validators, adds the certified nodes to the DAG and hands the ordered anchors to
+/// the execution pipeline.
+///
+/// The DAG starts below the latest commit, and only rounds far enough below the commit are
+/// pruned. A validator that restarts fetches the missing nodes from there. One that can't fetch
+/// them anymore, as the others pruned them, state syncs to the latest commit of the others and
+/// restarts its DAG below it.
+pub struct DagDriver {
+    author: Author,
+    epoch_state: Arc<EpochState>,
+    signer: Arc<ValidatorSigner>,
+    dag: Dag,
+    order_rule: OrderRule,
+    notifier: OrderedNotifier,
+    reliable_broadcast: ReliableBroadcast,
+    network_sender: Arc<dyn DAGNetworkSender>,
+    payload_client: Arc<dyn PayloadClient>,
+    storage: Arc<dyn DAGStorage>,
+    aptos_db: Arc<dyn DbReader>,
+    state_computer: Arc<dyn StateComputer>,
+    time_service: Arc<dyn TimeService>,
+    config: DagConsensusConfig,
+    quorum_store_enabled: bool,
+    current_round: Round,
+    // Round of the latest committed anchor
+    committed_round: Round,
+    // Whether the latest commit is being requested from the other validators
+    syncing: bool,
+    last_round_time: Duration,
+    // Votes sent for the nodes of every (round, author), to never sign two different nodes.
+    // They're persisted before being sent, and recovered when the driver is created.
+    votes: BTreeMap<(Round, Author), Vote>,
+    // Nodes below this round aren't voted on, their votes are no longer stored
+    pruned_vote_round: Round,
+    // Certified nodes received before (some of) their parents
+    pending_nodes: HashMap<HashValue, CertifiedNode>,
+    // Nodes that are being fetched from other validators
+    fetching: HashSet<HashValue>,
+    // Dropping a sender cancels the corresponding broadcast of our own node
+    broadcast_handles: BTreeMap<Round, oneshot::Sender<()>>,
+    certified_tx: UnboundedSender<CertifiedNode>,
+    certified_rx: Option<UnboundedReceiver<CertifiedNode>>,
+    fetched_tx: UnboundedSender<(FetchRequest, Vec<CertifiedNode>)>,
+    fetched_rx: Option<UnboundedReceiver<(FetchRequest, Vec<CertifiedNode>)>>,
+    committed_rx: Option<UnboundedReceiver<Round>>,
+    synced_tx: UnboundedSender<Option<LedgerInfoWithSignatures>>,
+    synced_rx: Option<UnboundedReceiver<Option<LedgerInfoWithSignatures>>>,
+}
+
+impl DagDriver {
+    /// Creates the driver of the epoch, `recovered_votes` are the ones already sent in the epoch
+    /// (see `recover_votes`). The anchors after `ledger_info`, the latest commit, are ordered
+    /// and chained on it, `committed_rx` receives the rounds of the anchors committed since.
+    pub fn new(
+        author: Author,
+        epoch_state: Arc<EpochState>,
+        signer: Arc<ValidatorSigner>,
+        anchor_election: Box<dyn AnchorElection>,
+        max_failed_authors: usize,
+        notifier: OrderedNotifier,
+        network_sender: Arc<dyn DAGNetworkSender>,
+        payload_client: Arc<dyn PayloadClient>,
+        storage: Arc<dyn DAGStorage>,
+        recovered_votes: RecoveredVotes,
+        ledger_info: &LedgerInfo,
+        committed_rx: UnboundedReceiver<Round>,
+        aptos_db: Arc<dyn DbReader>,
+        state_computer: Arc<dyn StateComputer>,
+        time_service: Arc<dyn TimeService>,
+        config: DagConsensusConfig,
+        quorum_store_enabled: bool,
+    ) -> Self {
+        let votes: BTreeMap<_, _> = recovered_votes
+            .votes
+            .into_iter()
+            .map(|vote| ((vote.metadata().round(), *vote.metadata().author()), vote))
+            .collect();
+
+        let committed_anchor = committed_anchor(ledger_info);
+        let start_round = OrderRule::start_round(committed_anchor, config.dag_window_rounds);
+        let dag = Dag::new(
+            epoch_state.verifier.address_to_validator_index().clone(),
+            start_round,
+        );
+        let order_rule = OrderRule::new(
+            epoch_state.clone(),
+            anchor_election,
+            max_failed_authors,
+            config.dag_window_rounds,
+            committed_anchor,
+        );
+        // our own nodes are voted on as well, never create a node again for a round we already
+        // created one for before a restart
+        let current_round = votes
+            .keys()
+            .filter(|(_, voted_author)| *voted_author == author)
+            .map(|(round, _)| *round)
+            .max()
+            .unwrap_or(0)
+            .max(start_round);
+        let reliable_broadcast = ReliableBroadcast::new_with_rpc_timeout(
+            epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .collect(),
+            network_sender.clone(),
+            Duration::from_millis(config.rpc_timeout_ms),
+        );
+        let (certified_tx, certified_rx) = unbounded();
+        let (fetched_tx, fetched_rx) = unbounded();
+        let (synced_tx, synced_rx) = unbounded();
+        let driver = Self {
+            author,
+            epoch_state,
+            signer,
+            dag,
+            order_rule,
+            notifier,
+            reliable_broadcast,
+            network_sender,
+            payload_client,
+            storage,
+            aptos_db,
+            state_computer,
+            time_service,
+            config,
+            quorum_store_enabled,
+            current_round,
+            committed_round: committed_anchor.map_or(0, |(round, _)| round),
+            syncing: false,
+            last_round_time: Duration::ZERO,
+            votes,
+            pruned_vote_round: recovered_votes.pruned_round,
+            pending_nodes: HashMap::new(),
+            fetching: HashSet::new(),
+            broadcast_handles: BTreeMap::new(),
+            certified_tx,
+            certified_rx: Some(certified_rx),
+            fetched_tx,
+            fetched_rx: Some(fetched_rx),
+            committed_rx: Some(committed_rx),
+            synced_tx,
+            synced_rx: Some(synced_rx),
+        };
+        driver.recover_anchor_election();
+        driver
+    }
+
+    fn epoch(&self) -> u64 {
+        self.epoch_state.epoch
+    }
+
+    fn recover_anchor_election(&self) {
+        if let Err(e) = self
+            .order_rule
+            .anchor_election()
+            .recover(self.aptos_db.as_ref(), self.epoch())
+        {
+            warn!(epoch = self.epoch(), error = ?e, "Fail to recover the anchor election");
+        }
+    }
+
+    /// Tries to create the node of the next round, which requires 2f+1 nodes of the highest
+    /// round in the DAG (except for the first round of the epoch).
+    async fn try_enter_new_round(&mut self) {
+        let now = self.time_service.get_current_timestamp();
+        if now < self.last_round_time + Duration::from_millis(self.config.round_min_interval_ms) {
+            return;
+        }
+        let (round, parents) = if self.current_round == 0 {
+            (1, vec![])
+        } else {
+            let round = self.dag.highest_round() + 1;
+            if round <= self.current_round {
+                return;
+            }
+            match self
+                .dag
+                .get_unlinked_nodes_for_new_round(&self.epoch_state.verifier)
+            {
+                Some(parents) => (round, parents),
+                None => return,
+            }
+        };
+        self.dag.mark_nodes_linked(&parents);
+
+        // Exclude the payloads that are already in the DAG, they'll be ordered eventually
+        let window_nodes: Vec<_> = (self.dag.lowest_round()..=self.dag.highest_round())
+            .flat_map(|round| self.dag.get_nodes_by_round(round))
+            .filter(|node| !self.order_rule.is_ordered(&node.digest()))
+            .collect();
+        let exclude_payloads: Vec<_> = window_nodes.iter().map(|node| node.payload()).collect();
+        let payload = match self
+            .payload_client
+            .pull_payload(
+                Duration::ZERO,
+                self.config.max_node_txns,
+                self.config.max_node_bytes,
+                PayloadFilter::from(&exclude_payloads),
+                async {}.boxed(),
+                false,
+                0,
+                0.0,
+            )
+            .await
+        {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!(epoch = self.epoch(), round = round, error = ?e, "Fail to pull payload");
+                Payload::empty(self.quorum_store_enabled)
+            },
+        };
+
+        let node = Node::new(
+            self.epoch(),
+            round,
+            self.author,
+            now.as_micros() as u64,
+            payload,
+            parents,
+        );
+        self.current_round = round;
+        self.last_round_time = now;
+        counters::DAG_CURRENT_ROUND.set(round as i64);
+        debug!(epoch = self.epoch(), round = round, "Broadcast DAG node");
+        self.broadcast_node(node);
+    }
+
+    fn broadcast_node(&mut self, node: Node) {
+        let (certificate_tx, certificate_rx) = oneshot::channel();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let round = node.round();
+        let broadcast = self.reliable_broadcast.broadcast(
+            node.clone(),
+            SignatureBuilder::new(node.metadata(), self.epoch_state.clone()),
+            certificate_tx,
+            cancel_rx,
+        );
+        let certified_tx = self.certified_tx.clone();
+        tokio::spawn(async move {
+            broadcast.await;
+            if let Ok(certificate) = certificate_rx.await {
+                let _ = certified_tx.unbounded_send(CertifiedNode::new(node, certificate));
+            }
+        });
+        self.broadcast_handles.insert(round, cancel_tx);
+    }
+
+    fn broadcast_certified_node(&mut self, node: CertifiedNode) {
+        let (done_tx, _done_rx) = oneshot::channel();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let round = node.round();
+        // the node reaches our own DAG through the broadcast as well
+        tokio::spawn(self.reliable_broadcast.broadcast(
+            node,
+            CertificateAckState::new(self.epoch_state.verifier.len()),
+            done_tx,
+            cancel_rx,
+        ));
+        self.broadcast_handles.insert(round, cancel_tx);
+    }
+
+    fn process_node(&mut self, sender: Author, node: Node) -> anyhow::Result<Vote> {
+        ensure!(sender == *node.author(), "node is not sent by its author");
+        ensure!(node.epoch() == self.epoch(), "node from a different epoch");
+        ensure!(
+            node.round() >= self.dag.lowest_round(),
+            "node round {} is already pruned",
+            node.round()
+        );
+        ensure!(
+            node.round() >= self.pruned_vote_round,
+            "votes of round {} are already pruned",
+            node.round()
+        );
+        ensure!(
+            node.timestamp()
+                <= (self.time_service.get_current_timestamp() + TIMESTAMP_BOUND).as_micros() as u64,
+            "node timestamp is too far in the future"
+        );
+        node.verify(&self.epoch_state.verifier)?;
+        node.payload()
+            .verify(&self.epoch_state.verifier, self.quorum_store_enabled)?;
+
+        if let Some(vote) = self.votes.get(&(node.round(), *node.author())) {
+            ensure!(
+                *vote.metadata() == node.metadata(),
+                "equivocating node from {} in round {}",
+                node.author(),
+                node.round()
+            );
+            return Ok(vote.clone());
+        }
+        let missing_parents = self.dag.missing_parents(&node);
+        if !missing_parents.is_empty() {
+            self.fetch(sender, missing_parents);
+            bail!("missing parents of node {}", node.digest());
+        }
+
+        let vote = Vote::new(node.metadata(), self.signer.sign(&node.metadata())?);
+        self.storage.save_vote(&vote)?;
+        self.votes
+            .insert((node.round(), *node.author()), vote.clone());
+        Ok(vote)
+    }
+
+    async fn process_certified_node(
+        &mut self,
+        sender: Author,
+        node: CertifiedNode,
+    ) -> anyhow::Result<CertifiedAck> {
+        ensure!(node.epoch() == self.epoch(), "node from a different epoch");
+        if node.round() < self.dag.lowest_round() || self.dag.exists(&node.digest()) {
+            return Ok(CertifiedAck::new(self.epoch()));
+        }
+        node.verify(&self.epoch_state.verifier)?;
+        let missing_parents = self.dag.missing_parents(&node);
+        if !missing_parents.is_empty() {
+            self.fetch(sender, missing_parents);
+            let digest = node.digest();
+            self.pending_nodes.insert(digest, node);
+            bail!("missing parents of certified node {}", digest);
+        }
+        self.add_certified_node(node).await?;
+        Ok(CertifiedAck::new(self.epoch()))
+    }
+
+    fn process_fetch_request(&self, request: FetchRequest) -> anyhow::Result<FetchResponse> {
+        request.verify()?;
+        let certified_nodes = request
+            .targets()
+            .iter()
+            .filter_map(|target| self.dag.get_node(target.digest()))
+            .map(|node| (*node).clone())
+            .collect();
+        Ok(FetchResponse::new(self.epoch(), certified_nodes))
+    }
+
+    async fn process_fetched_nodes(&mut self, request: FetchRequest, nodes: Vec<CertifiedNode>) {
+        for target in request.targets() {
+            self.fetching.remove(target.digest());
+        }
+        // nodes that nobody returns are pruned already, they can only be caught up on through
+        // state sync
+        if let Some(target) = request.targets().iter().find(|target| {
+            target.round() >= self.dag.lowest_round()
+                && !self.dag.exists(target.digest())
+                && nodes.iter().all(|node| node.digest() != *target.digest())
+        }) {
+            self.sync(*target.author());
+        }
+        let mut nodes = nodes;
+        nodes.sort_by_key(|node| node.round());
+        for node in nodes {
+            if node.round() < self.dag.lowest_round() || self.dag.exists(&node.digest()) {
+                continue;
+            }
+            let missing_parents = self.dag.missing_parents(&node);
+            if !missing_parents.is_empty() {
+                self.fetch(*node.author(), missing_parents);
+                self.pending_nodes.insert(node.digest(), node);
+            } else if let Err(e) = self.add_certified_node(node).await {
+                warn!(epoch = self.epoch(), error = ?e, "Fail to add fetched node");
+            }
+        }
+    }
+
+    /// The validators to send a request to, `peer` first.
+    fn peers_from(&self, peer: Author) -> Vec<Author> {
+        std::iter::once(peer)
+            .chain(
+                self.epoch_state
+                    .verifier
+                    .get_ordered_account_addresses_iter()
+                    .filter(|validator| *validator != peer && *validator != self.author),
+            )
+            .collect()
+    }
+
+    /// Fetches the given nodes, from `peer` first and then from the other validators.
+    fn fetch(&mut self, peer: Author, targets: Vec<NodeMetadata>) {
+        let targets: Vec<_> = targets
+            .into_iter()
+            .filter(|target| self.fetching.insert(*target.digest()))
+            .collect();
+        let peers = self.peers_from(peer);
+        let rpc_timeout = Duration::from_millis(self.config.rpc_timeout_ms);
+        for targets in targets.chunks(MAX_FETCH_TARGETS) {
+            let request = FetchRequest::new(self.epoch(), targets.to_vec());
+            let network_sender = self.network_sender.clone();
+            let epoch_state = self.epoch_state.clone();
+            let fetched_tx = self.fetched_tx.clone();
+            let peers = peers.clone();
+            tokio::spawn(async move {
+                for peer in peers {
+                    let result = network_sender
+                        .send_rpc(peer, request.clone().into_network_message(), rpc_timeout)
+                        .await
+                        .and_then(FetchResponse::from_network_message)
+                        .and_then(|response| {
+                            response.verify(&request, &epoch_state)?;
+                            Ok(response)
+                        });
+                    match result {
+                        Ok(response) => {
+                            let _ =
+                                fetched_tx.unbounded_send((request, response.certified_nodes()));
+                            return;
+                        },
+                        Err(e) => {
+                            warn!(remote_peer = peer, error = ?e, "Fail to fetch DAG nodes");
+                        },
+                    }
+                }
+                let _ = fetched_tx.unbounded_send((request, vec![]));
+            });
+        }
+    }
+
+    /// Requests the latest commit, from `peer` first and then from the other validators, unless
+    /// it's being requested already.
+    fn sync(&mut self, peer: Author) {
+        if self.syncing {
+            return;
+        }
+        self.syncing = true;
+        let request = SyncRequest::new(self.epoch());
+        let peers = self.peers_from(peer);
+        let rpc_timeout = Duration::from_millis(self.config.rpc_timeout_ms);
+        let network_sender = self.network_sender.clone();
+        let epoch_state = self.epoch_state.clone();
+        let synced_tx = self.synced_tx.clone();
+        tokio::spawn(async move {
+            for peer in peers {
+                let result = network_sender
+                    .send_rpc(peer, request.clone().into_network_message(), rpc_timeout)
+                    .await
+                    .and_then(SyncResponse::from_network_message)
+                    .and_then(|response| {
+                        response.verify(&request, &epoch_state)?;
+                        Ok(response)
+                    });
+                match result {
+                    Ok(response) => {
+                        let _ = synced_tx.unbounded_send(Some(response.ledger_info()));
+                        return;
+                    },
+                    Err(e) => {
+                        warn!(remote_peer = peer, error = ?e, "Fail to get the latest commit");
+                    },
+                }
+            }
+            let _ = synced_tx.unbounded_send(None);
+        });
+    }
+
+    /// State syncs to the latest commit of another validator if it's ahead of what the DAG can
+    /// order, and restarts the DAG below it.
+    async fn process_synced_ledger_info(&mut self, ledger_info: Option<LedgerInfoWithSignatures>) {
+        self.syncing = false;
+        let ledger_info = match ledger_info {
+            Some(ledger_info) => ledger_info,
+            None => return,
+        };
+        let round = ledger_info.commit_info().round();
+        if round < self.order_rule.lowest_unordered_anchor_round() {
+            return;
+        }
+        info!(
+            epoch = self.epoch(),
+            round = round,
+            "DAG is behind, state sync to the latest commit"
+        );
+        if let Err(e) = monitor!(
+            "dag_sync_to",
+            self.state_computer.sync_to(ledger_info.clone()).await
+        ) {
+            error!(epoch = self.epoch(), error = ?e, "Fail to state sync");
+            return;
+        }
+        self.committed_round = round;
+        // the next epoch is started on the reconfiguration notification
+        if ledger_info.ledger_info().ends_epoch() {
+            return;
+        }
+
+        let committed_anchor = committed_anchor(ledger_info.ledger_info());
+        let start_round = OrderRule::start_round(committed_anchor, self.config.dag_window_rounds);
+        self.dag = Dag::new(
+            self.epoch_state
+                .verifier
+                .address_to_validator_index()
+                .clone(),
+            start_round,
+        );
+        self.order_rule.reset(committed_anchor);
+        self.recover_anchor_election();
+        self.notifier
+            .reset(committed_block_info(ledger_info.ledger_info()));
+        self.current_round = self.current_round.max(start_round);
+        self.pending_nodes
+            .retain(|_, node| node.round() >= start_round);
+        self.broadcast_handles = self.broadcast_handles.split_off(&start_round);
+    }
+
+    /// Adds the node and every pending node it unblocks, then orders what can be ordered.
+    async fn add_certified_node(&mut self, node: CertifiedNode) -> anyhow::Result<()> {
+        self.dag.add_node(node)?;
+        loop {
+            let mut ready: Vec<_> = self
+                .pending_nodes
+                .iter()
+                .filter(|(_, node)| self.dag.missing_parents(node).is_empty())
+                .map(|(digest, node)| (node.round(), *digest))
+                .collect();
+            if ready.is_empty() {
+                break;
+            }
+            ready.sort();
+            for (_, digest) in ready {
+                let node = self
+                    .pending_nodes
+                    .remove(&digest)
+                    .expect("pending node must exist");
+                if self.dag.exists(&digest) {
+                    continue;
+                }
+                if let Err(e) = self.dag.add_node(node) {
+                    warn!(epoch = self.epoch(), error = ?e, "Fail to add pending node");
+                }
+            }
+        }
+        self.order().await;
+        Ok(())
+    }
+
+    async fn order(&mut self) {
+        for ordered_nodes in monitor!("dag_order", self.order_rule.process(&self.dag)) {
+            counters::DAG_ORDERED_ANCHORS.inc();
+            self.notifier.send_ordered_nodes(ordered_nodes).await;
+        }
+
+        // the rounds below the latest commit are kept as well, for the others to restart from it
+        let lowest_round = self
+            .order_rule
+            .lowest_unordered_anchor_round()
+            .min(self.committed_round + 2)
+            .saturating_sub(self.config.dag_window_rounds);
+        if lowest_round > self.dag.lowest_round() {
+            self.dag.prune(lowest_round);
+            self.order_rule.prune(lowest_round);
+            self.prune_votes(lowest_round);
+            self.pending_nodes
+                .retain(|_, node| node.round() >= lowest_round);
+            self.broadcast_handles = self.broadcast_handles.split_off(&lowest_round);
+        }
+    }
+
+    /// Deletes the votes below `lowest_round`, once it's persisted that those rounds are pruned.
+    fn prune_votes(&mut self, lowest_round: Round) {
+        let votes = self.votes.split_off(&(lowest_round, Author::ZERO));
+        let pruned_votes = std::mem::replace(&mut self.votes, votes);
+        self.pruned_vote_round = lowest_round;
+        if let Err(e) = self
+            .storage
+            .save_pruned_round(self.epoch(), lowest_round)
+            .and_then(|_| {
+                self.storage
+                    .delete_votes(pruned_votes.into_values().collect())
+            })
+        {
+            warn!(epoch = self.epoch(), error = ?e, "Fail to delete pruned votes");
+        }
+    }
+
+    fn process_sync_request(&self, request: SyncRequest) -> anyhow::Result<SyncResponse> {
+        ensure!(
+            request.epoch() == self.epoch(),
+            "sync request from a different epoch"
+        );
+        Ok(SyncResponse::new(
+            self.epoch(),
+            self.aptos_db.get_latest_ledger_info()?,
+        ))
+    }
+
+    async fn process_rpc(&mut self, request: IncomingDAGRequest) {
+        let IncomingDAGRequest {
+            req,
+            sender,
+            protocol,
+            response_sender,
+        } = request;
+        let response = match req {
+            ConsensusMsg::DAGNodeMsg(node) => monitor!(
+                "dag_process_node",
+                self.process_node(sender, *node)
+                    .map(Vote::into_network_message)
+            ),
+            ConsensusMsg::DAGCertifiedNodeMsg(node) => monitor!(
+                "dag_process_certified_node",
+                self.process_certified_node(sender, *node)
+                    .await
+                    .map(CertifiedAck::into_network_message)
+            ),
+            ConsensusMsg::DAGFetchRequestMsg(request) => monitor!(
+                "dag_process_fetch_request",
+                self.process_fetch_request(*request)
+                    .map(FetchResponse::into_network_message)
+            ),
+            ConsensusMsg::DAGSyncRequestMsg(request) => monitor!(
+                "dag_process_sync_request",
+                self.process_sync_request(*request)
+                    .map(SyncResponse::into_network_message)
+            ),
+            msg => Err(anyhow!("unexpected DAG request {}", msg.name())),
+        };
+        let response = response
+            .and_then(|msg| protocol.to_bytes(&msg).map(Bytes::from))
+            .map_err(|e| {
+                debug!(epoch = self.epoch(), remote_peer = sender, error = ?e, "DAG request failed");
+                RpcError::ApplicationError(e)
+            });
+        let _ = response_sender.send(response);
+    }
+
+    pub async fn start(
+        mut self,
+        mut rpc_rx: aptos_channel::Receiver<Author, IncomingDAGRequest>,
+        close_rx: oneshot::Receiver<oneshot::Sender<()>>,
+    ) {
+        info!(epoch = self.epoch(), "DagDriver started");
+        let mut close_rx = close_rx.into_stream();
+        let mut certified_rx = self.certified_rx.take().expect("DagDriver already started");
+        let mut fetched_rx = self.fetched_rx.take().expect("DagDriver already started");
+        let mut committed_rx = self.committed_rx.take().expect("DagDriver already started");
+        let mut synced_rx = self.synced_rx.take().expect("DagDriver already started");
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(self.config.round_min_interval_ms));
+        loop {
+            tokio::select! {
+                biased;
+                close_req = close_rx.select_next_some() => {
+                    if let Ok(ack_sender) = close_req {
+                        ack_sender.send(()).expect("[DagDriver] Fail to ack shutdown");
+                    }
+                    break;
+                },
+                Some(node) = certified_rx.next() => {
+                    self.broadcast_certified_node(node);
+                },
+                Some(round) = committed_rx.next() => {
+                    self.committed_round = self.committed_round.max(round);
+                },
+                Some(ledger_info) = synced_rx.next() => {
+                    self.process_synced_ledger_info(ledger_info).await;
+                    self.try_enter_new_round().await;
+                },
+                Some((request, nodes)) = fetched_rx.next() => {
+                    self.process_fetched_nodes(request, nodes).await;
+                    self.try_enter_new_round().await;
+                },
+                request = rpc_rx.select_next_some() => {
+                    self.process_rpc(request).await;
+                    self.try_enter_new_round().await;
+                },
+                _ = interval.tick() => {
+                    self.try_enter_new_round().await;
+                },
+            }
+        }
+        info!(epoch = self.epoch(), "DagDriver stopped");
+    }
+}
@@ -7,17 +7,19 @@ use aptos_crypto::{
     hash::{CryptoHash, CryptoHasher},
     HashValue,
 };
-use aptos_crypto_derive::CryptoHasher;
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use aptos_types::{aggregate_signature::AggregateSignature, validator_verifier::ValidatorVerifier};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     ops::Deref,
     sync::Arc,
 };
 
 /// Represents the metadata about the node, without payload and parents from Node
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, CryptoHasher, BCSCryptoHash,
+)]
 pub struct NodeMetadata {
     epoch: u64,
     round: Round,
@@ -26,9 +28,31 @@ pub struct NodeMetadata {
     digest: HashValue,
 }
 
+impl NodeMetadata {
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    pub fn author(&self) -> &Author {
+        &self.author
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn digest(&self) -> &HashValue {
+        &self.digest
+    }
+}
+
 /// Node representation in the DAG, parents contain 2f+1 strong links (links to previous round)
 /// plus weak links (links to lower round)
-#[derive(Clone, Serialize, Deserialize, CryptoHasher)]
+#[derive(Clone, Debug, Serialize, Deserialize, CryptoHasher)]
 pub struct Node {
     metadata: NodeMetadata,
     payload: Payload,
@@ -107,22 +131,107 @@ impl Node {
     pub fn metadata(&self) -> NodeMetadata {
         self.metadata.clone()
     }
+
+    pub fn epoch(&self) -> u64 {
+        self.metadata.epoch
+    }
+
+    pub fn round(&self) -> Round {
+        self.metadata.round
+    }
+
+    pub fn author(&self) -> &Author {
+        &self.metadata.author
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.metadata.timestamp
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    pub fn parents(&self) -> &[NodeMetadata] {
+        &self.parents
+    }
+
+    /// Verifies that the digest matches the content and that the node links to at least 2f+1
+    /// voting power of the previous round (except for the first round of the DAG).
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.digest()
+                == Self::calculate_digest(
+                    self.metadata.epoch,
+                    self.metadata.round,
+                    self.metadata.author,
+                    self.metadata.timestamp,
+                    &self.payload,
+                    &self.parents,
+                ),
+            "node digest mismatch"
+        );
+        ensure!(
+            verifier.get_voting_power(&self.metadata.author).is_some(),
+            "unknown author"
+        );
+        let mut parent_authors = HashSet::new();
+        for parent in &self.parents {
+            ensure!(
+                parent.epoch == self.metadata.epoch,
+                "parent from a different epoch"
+            );
+            ensure!(parent.round < self.metadata.round, "parent round too high");
+            ensure!(
+                parent_authors.insert(parent.author),
+                "multiple parents from the same author"
+            );
+        }
+        if self.metadata.round > 1 {
+            verifier
+                .check_voting_power(
+                    self.parents
+                        .iter()
+                        .filter(|parent| parent.round + 1 == self.metadata.round)
+                        .map(|parent| &parent.author),
+                )
+                .map_err(|e| anyhow!("not enough strong links: {}", e))?;
+        }
+        Ok(())
+    }
 }
 
-/// Quorum signatures over the node digest
-#[derive(Clone)]
+/// Quorum signatures over the node metadata
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeCertificate {
-    digest: HashValue,
+    metadata: NodeMetadata,
     signatures: AggregateSignature,
 }
 
 impl NodeCertificate {
-    pub fn new(digest: HashValue, signatures: AggregateSignature) -> Self {
-        Self { digest, signatures }
+    pub fn new(metadata: NodeMetadata, signatures: AggregateSignature) -> Self {
+        Self {
+            metadata,
+            signatures,
+        }
+    }
+
+    pub fn metadata(&self) -> &NodeMetadata {
+        &self.metadata
+    }
+
+    pub fn signatures(&self) -> &AggregateSignature {
+        &self.signatures
+    }
+
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        verifier
+            .verify_multi_signatures(&self.metadata, &self.signatures)
+            .map_err(|e| anyhow!("invalid node certificate: {}", e))
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CertifiedNode {
     node: Node,
     certificate: NodeCertificate,
@@ -132,6 +241,19 @@ impl CertifiedNode {
     pub fn new(node: Node, certificate: NodeCertificate) -> Self {
         Self { node, certificate }
     }
+
+    pub fn certificate(&self) -> &NodeCertificate {
+        &self.certificate
+    }
+
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.node.metadata == self.certificate.metadata,
+            "certificate does not match the node"
+        );
+        self.node.verify(verifier)?;
+        self.certificate.verify(verifier)
+    }
 }
 
 impl Deref for CertifiedNode {
@@ -166,7 +288,7 @@ impl Dag {
         }
     }
 
-    fn lowest_round(&self) -> Round {
+    pub fn lowest_round(&self) -> Round {
         *self
             .nodes_by_round
             .first_key_value()
@@ -174,7 +296,7 @@ impl Dag {
             .unwrap_or(&0)
     }
 
-    fn highest_round(&self) -> Round {
+    pub fn highest_round(&self) -> Round {
         *self
             .nodes_by_round
             .last_key_value()
@@ -191,9 +313,7 @@ impl Dag {
         let round = node.metadata.round;
         ensure!(round >= self.lowest_round(), "round too low");
         ensure!(round <= self.highest_round() + 1, "round too high");
-        for parent in &node.parents {
-            ensure!(self.exists(&parent.digest), "parent not exist");
-        }
+        ensure!(self.missing_parents(&node).is_empty(), "parent not exist");
         ensure!(
             self.nodes_by_digest
                 .insert(node.metadata.digest, node.clone())
@@ -222,10 +342,105 @@ impl Dag {
         self.nodes_by_digest.contains_key(digest)
     }
 
+    /// Parents that are not in the DAG yet. Parents below the lowest round are considered
+    /// present, as they have been pruned already.
+    pub fn missing_parents(&self, node: &Node) -> Vec<NodeMetadata> {
+        let lowest_round = self.lowest_round();
+        node.parents
+            .iter()
+            .filter(|parent| parent.round >= lowest_round && !self.exists(&parent.digest))
+            .cloned()
+            .collect()
+    }
+
     pub fn get_node(&self, digest: &HashValue) -> Option<Arc<CertifiedNode>> {
         self.nodes_by_digest.get(digest).cloned()
     }
 
+    pub fn get_node_by_round_author(
+        &self,
+        round: Round,
+        author: &Author,
+    ) -> Option<Arc<CertifiedNode>> {
+        let index = *self.author_to_index.get(author)?;
+        self.nodes_by_round.get(&round)?[index].clone()
+    }
+
+    pub fn get_nodes_by_round(&self, round: Round) -> Vec<Arc<CertifiedNode>> {
+        self.nodes_by_round
+            .get(&round)
+            .map(|nodes| nodes.iter().flatten().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if there's a path from `from` to `to` following the parent links.
+    pub fn is_reachable(&self, from: &NodeMetadata, to: &NodeMetadata) -> bool {
+        if from.round < to.round {
+            return false;
+        }
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([from.digest]);
+        while let Some(digest) = queue.pop_front() {
+            if digest == to.digest {
+                return true;
+            }
+            if let Some(node) = self.nodes_by_digest.get(&digest) {
+                for parent in &node.parents {
+                    if parent.round >= to.round && visited.insert(parent.digest) {
+                        queue.push_back(parent.digest);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the causal history of `from` (including itself) that `skip` returns false for,
+    /// in no particular order.
+    pub fn causal_history(
+        &self,
+        from: &NodeMetadata,
+        skip: impl Fn(&NodeMetadata) -> bool,
+    ) -> Vec<Arc<CertifiedNode>> {
+        let mut visited = HashSet::from([from.digest]);
+        let mut queue = VecDeque::from([from.digest]);
+        let mut history = vec![];
+        while let Some(digest) = queue.pop_front() {
+            if let Some(node) = self.nodes_by_digest.get(&digest) {
+                for parent in &node.parents {
+                    if !skip(parent) && visited.insert(parent.digest) {
+                        queue.push_back(parent.digest);
+                    }
+                }
+                history.push(node.clone());
+            }
+        }
+        history
+    }
+
+    /// Removes all the rounds below `lowest_round`.
+    pub fn prune(&mut self, lowest_round: Round) {
+        if lowest_round <= self.lowest_round() || lowest_round > self.highest_round() {
+            return;
+        }
+        let retained = self.nodes_by_round.split_off(&lowest_round);
+        for node in std::mem::replace(&mut self.nodes_by_round, retained)
+            .into_values()
+            .flatten()
+            .flatten()
+        {
+            self.nodes_by_digest.remove(&node.metadata.digest);
+        }
+        for maybe_node in &mut self.highest_unlinked_nodes_by_author {
+            if maybe_node
+                .as_ref()
+                .map_or(false, |node| node.metadata.round < lowest_round)
+            {
+                *maybe_node = None;
+            }
+        }
+    }
+
     pub fn get_unlinked_nodes_for_new_round(
         &self,
         validator_verifier: &ValidatorVerifier,
@@ -3,7 +3,40 @@
 // SPDX-License-Identifier: Apache-2.0
 #![allow(dead_code)]
 
+//! Experimental DAG based ordering, enabled with `consensus.dag_consensus.enabled`.
+//!
+//! Every validator creates one node per round, certifies it through reliable broadcast and
+//! orders the anchors following `OrderRule`. The ordered anchors are handed to the decoupled
+//! execution pipeline as blocks, which executes and commits them like Jolteon blocks.
+//!
+//! The DAG is only kept in memory, it starts below the latest commit in `AptosDB` and the rounds
+//! below the commit are kept for a window, so that a restarted validator can fetch the nodes it
+//! needs to order the following anchors. A validator that falls further behind state syncs to
+//! the latest commit of its peers and restarts its DAG below it. The votes are persisted in
+//! `ConsensusDB`, so that a restarted validator never signs a different node for a (round,
+//! author) it already voted on.
+
+mod adapter;
+mod anchor_election;
+mod commit_signer;
+mod dag_driver;
 mod dag_store;
+mod order_rule;
 mod reliable_broadcast;
+mod storage;
 #[cfg(test)]
 mod tests;
+mod types;
+
+pub use adapter::{committed_anchor, committed_block_info, OrderedNotifier};
+pub use anchor_election::{
+    AnchorElection, DagMetadataBackend, LeaderReputationAnchorElection, RoundRobinAnchorElection,
+};
+pub use commit_signer::DagCommitSigner;
+pub use dag_driver::DagDriver;
+pub use dag_store::{CertifiedNode, Node};
+pub use reliable_broadcast::DAGNetworkSender;
+pub use storage::{recover_votes, DAGStorage, RecoveredVotes};
+pub use types::{
+    dag_request_epoch, CertifiedAck, FetchRequest, FetchResponse, SyncRequest, SyncResponse, Vote,
+};
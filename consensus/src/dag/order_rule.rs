@@ -0,0 +1,237 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    anchor_election::AnchorElection,
+    dag_store::{CertifiedNode, Dag},
+};
+use aptos_bitvec::BitVec;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_address::AccountAddress, account_config::NewBlockEvent, epoch_state::EpochState,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// An anchor together with the part of its causal history that hasn't been ordered before.
+pub struct OrderedNodes {
+    pub anchor: Arc<CertifiedNode>,
+    /// Sorted by (round, validator index), the anchor is the last one
+    pub nodes: Vec<Arc<CertifiedNode>>,
+    /// Anchors skipped since the previously ordered one
+    pub failed_authors: Vec<(Round, Author)>,
+    /// Authors of the previous round nodes the anchor links to
+    pub voters: BitVec,
+}
+
+/// Bullshark-style ordering: anchors are on odd rounds, an anchor is committed once f+1 voting
+/// power of the next round links to it. Before ordering a committed anchor, we walk back to the
+/// earliest anchor that it reaches since the last ordered one, and order that one first.
+///
+/// Only a single anchor is ordered at a time, the election is updated with it and the remaining
+/// anchors are re-evaluated. This keeps the elected anchors a function of the ordered prefix,
+/// which is the same on every validator.
+///
+/// Everything that ends up in the ordered block only depends on the anchor and its causal
+/// history, never on the nodes that happen to be in the local DAG at the time of ordering.
+pub struct OrderRule {
+    epoch_state: Arc<EpochState>,
+    anchor_election: Box<dyn AnchorElection>,
+    max_failed_authors: usize,
+    // Nodes more than this many rounds below the anchor are never ordered with it
+    dag_window_rounds: Round,
+    lowest_unordered_anchor_round: Round,
+    ordered_nodes: HashMap<HashValue, Round>,
+    // The anchor committed before the DAG was (re)started, its causal history is marked ordered
+    // once it's in the DAG
+    committed_anchor: Option<(Round, HashValue)>,
+}
+
+impl OrderRule {
+    /// Creates the rule ordering the anchors after `committed_anchor` (round and digest), or from
+    /// the start of the epoch if there's none.
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        anchor_election: Box<dyn AnchorElection>,
+        max_failed_authors: usize,
+        dag_window_rounds: Round,
+        committed_anchor: Option<(Round, HashValue)>,
+    ) -> Self {
+        let mut order_rule = Self {
+            epoch_state,
+            anchor_election,
+            max_failed_authors,
+            dag_window_rounds,
+            lowest_unordered_anchor_round: 1,
+            ordered_nodes: HashMap::new(),
+            committed_anchor: None,
+        };
+        order_rule.reset(committed_anchor);
+        order_rule
+    }
+
+    /// Restarts the ordering after `committed_anchor`, e.g., after state syncing to it. Nothing
+    /// is ordered until the committed anchor is in the DAG, as the nodes it ordered are only
+    /// known from its causal history.
+    pub fn reset(&mut self, committed_anchor: Option<(Round, HashValue)>) {
+        self.lowest_unordered_anchor_round = committed_anchor.map_or(1, |(round, _)| round + 2);
+        self.ordered_nodes.clear();
+        self.committed_anchor = committed_anchor;
+    }
+
+    /// The lowest round the DAG must start at for the anchors after `committed_anchor` to be
+    /// ordered the same way as on the validators that ordered them before.
+    pub fn start_round(
+        committed_anchor: Option<(Round, HashValue)>,
+        dag_window_rounds: Round,
+    ) -> Round {
+        committed_anchor.map_or(0, |(round, _)| {
+            (round + 2).saturating_sub(dag_window_rounds).min(round)
+        })
+    }
+
+    pub fn anchor_election(&self) -> &dyn AnchorElection {
+        self.anchor_election.as_ref()
+    }
+
+    pub fn is_anchor_round(round: Round) -> bool {
+        round % 2 == 1
+    }
+
+    pub fn lowest_unordered_anchor_round(&self) -> Round {
+        self.lowest_unordered_anchor_round
+    }
+
+    pub fn is_ordered(&self, digest: &HashValue) -> bool {
+        self.ordered_nodes.contains_key(digest)
+    }
+
+    /// Orders as many anchors as the DAG allows, returns them in order.
+    pub fn process(&mut self, dag: &Dag) -> Vec<OrderedNodes> {
+        if let Some((_, digest)) = self.committed_anchor {
+            let anchor = match dag.get_node(&digest) {
+                Some(anchor) => anchor,
+                None => return vec![],
+            };
+            // the nodes of the DAG ordered with or before the committed anchor are exactly its
+            // causal history, as every ordered anchor is reachable from the later ones
+            for node in dag.causal_history(&anchor.metadata(), |_| false) {
+                self.ordered_nodes.insert(node.digest(), node.round());
+            }
+            self.committed_anchor = None;
+        }
+
+        let mut ordered = vec![];
+        while let Some(anchor) = self.find_first_anchor_to_order(dag) {
+            ordered.push(self.order(dag, anchor));
+        }
+        ordered
+    }
+
+    /// Forgets the ordered nodes that were pruned from the DAG.
+    pub fn prune(&mut self, lowest_round: Round) {
+        self.ordered_nodes.retain(|_, round| *round >= lowest_round);
+    }
+
+    fn anchor(&self, dag: &Dag, round: Round) -> Option<Arc<CertifiedNode>> {
+        dag.get_node_by_round_author(round, &self.anchor_election.get_anchor(round))
+    }
+
+    fn voters(&self, dag: &Dag, anchor: &CertifiedNode) -> Vec<Author> {
+        dag.get_nodes_by_round(anchor.round() + 1)
+            .iter()
+            .filter(|node| {
+                node.parents()
+                    .iter()
+                    .any(|parent| *parent.digest() == anchor.digest())
+            })
+            .map(|node| *node.author())
+            .collect()
+    }
+
+    fn has_enough_votes(&self, voters: &[Author]) -> bool {
+        let verifier = &self.epoch_state.verifier;
+        let voting_power: u128 = voters
+            .iter()
+            .map(|voter| verifier.get_voting_power(voter).unwrap_or(0) as u128)
+            .sum();
+        // f+1, guarantees that every quorum of the next round includes a voter
+        voting_power > verifier.total_voting_power() - verifier.quorum_voting_power()
+    }
+
+    fn find_first_anchor_to_order(&self, dag: &Dag) -> Option<Arc<CertifiedNode>> {
+        let highest_round = dag.highest_round();
+        let mut round = self.lowest_unordered_anchor_round;
+        let committed = loop {
+            if round >= highest_round {
+                return None;
+            }
+            if let Some(anchor) = self.anchor(dag, round) {
+                if self.has_enough_votes(&self.voters(dag, &anchor)) {
+                    break anchor;
+                }
+            }
+            round += 2;
+        };
+
+        let mut first = committed;
+        let mut round = first.round();
+        while round > self.lowest_unordered_anchor_round {
+            round -= 2;
+            if let Some(anchor) = self.anchor(dag, round) {
+                if dag.is_reachable(&first.metadata(), &anchor.metadata()) {
+                    first = anchor;
+                }
+            }
+        }
+        Some(first)
+    }
+
+    fn order(&mut self, dag: &Dag, anchor: Arc<CertifiedNode>) -> OrderedNodes {
+        let failed_authors: Vec<_> = (self.lowest_unordered_anchor_round..anchor.round())
+            .step_by(2)
+            .map(|round| (round, self.anchor_election.get_anchor(round)))
+            .collect();
+        let failed_authors =
+            failed_authors[failed_authors.len().saturating_sub(self.max_failed_authors)..].to_vec();
+
+        // the DAG is never pruned above this round before the anchor is ordered
+        let lowest_round = anchor.round().saturating_sub(self.dag_window_rounds);
+        let mut nodes = dag.causal_history(&anchor.metadata(), |parent| {
+            parent.round() < lowest_round || self.ordered_nodes.contains_key(parent.digest())
+        });
+        let author_to_index = self.epoch_state.verifier.address_to_validator_index();
+        nodes.sort_by_key(|node| (node.round(), author_to_index[node.author()]));
+        for node in &nodes {
+            self.ordered_nodes.insert(node.digest(), node.round());
+        }
+        self.lowest_unordered_anchor_round = anchor.round() + 2;
+
+        let mut voters = BitVec::with_num_bits(self.epoch_state.verifier.len() as u16);
+        for parent in anchor.parents() {
+            if parent.round() + 1 == anchor.round() {
+                voters.set(author_to_index[parent.author()] as u16);
+            }
+        }
+        self.anchor_election.commit(NewBlockEvent::new(
+            AccountAddress::new(*anchor.digest()),
+            anchor.epoch(),
+            anchor.round(),
+            0,
+            voters.clone().into(),
+            *anchor.author(),
+            failed_authors
+                .iter()
+                .map(|(_, author)| author_to_index[author] as u64)
+                .collect(),
+            anchor.timestamp(),
+        ));
+
+        OrderedNodes {
+            anchor,
+            nodes,
+            failed_authors,
+            voters,
+        }
+    }
+}
@@ -19,8 +19,6 @@ pub trait BroadcastStatus {
     type Ack: DAGMessage;
     type Aggregated;
 
-    fn empty(validators: Vec<Author>) -> Self;
-
     fn add(&mut self, peer: Author, ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>>;
 }
 
@@ -37,27 +35,38 @@ pub trait DAGNetworkSender: Send + Sync {
 pub struct ReliableBroadcast {
     validators: Vec<Author>,
     network_sender: Arc<dyn DAGNetworkSender>,
+    rpc_timeout: Duration,
 }
 
 impl ReliableBroadcast {
     pub fn new(validators: Vec<Author>, network_sender: Arc<dyn DAGNetworkSender>) -> Self {
+        Self::new_with_rpc_timeout(validators, network_sender, Duration::from_millis(500))
+    }
+
+    pub fn new_with_rpc_timeout(
+        validators: Vec<Author>,
+        network_sender: Arc<dyn DAGNetworkSender>,
+        rpc_timeout: Duration,
+    ) -> Self {
         Self {
             validators,
             network_sender,
+            rpc_timeout,
         }
     }
 
     pub fn broadcast<S: BroadcastStatus>(
         &self,
         message: S::Message,
+        mut aggregating: S,
         return_tx: oneshot::Sender<S::Aggregated>,
         mut cancel_rx: oneshot::Receiver<()>,
     ) -> impl Future<Output = ()> {
         let receivers: Vec<_> = self.validators.clone();
         let network_message = message.into_network_message();
         let network_sender = self.network_sender.clone();
+        let rpc_timeout = self.rpc_timeout;
         async move {
-            let mut aggregating = S::empty(receivers.clone());
             let mut fut = FuturesUnordered::new();
             let send_message = |receiver, message| {
                 let network_sender = network_sender.clone();
@@ -65,7 +74,7 @@ impl ReliableBroadcast {
                     (
                         receiver,
                         network_sender
-                            .send_rpc(receiver, message, Duration::from_millis(500))
+                            .send_rpc(receiver, message, rpc_timeout)
                             .await,
                     )
                 }
@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{consensusdb::ConsensusDB, dag::types::Vote};
+use aptos_consensus_types::common::Round;
+
+/// Persists the votes sent on DAG nodes, so that a validator that restarts within an epoch
+/// doesn't vote for a different node of the same (round, author).
+pub trait DAGStorage: Send + Sync {
+    fn save_vote(&self, vote: &Vote) -> anyhow::Result<()>;
+
+    fn get_votes(&self) -> anyhow::Result<Vec<Vote>>;
+
+    fn delete_votes(&self, votes: Vec<Vote>) -> anyhow::Result<()>;
+
+    /// Records that the votes below `round` of `epoch` are about to be deleted, the nodes below
+    /// it must not be voted on anymore.
+    fn save_pruned_round(&self, epoch: u64, round: Round) -> anyhow::Result<()>;
+
+    fn get_pruned_round(&self) -> anyhow::Result<Option<(u64, Round)>>;
+}
+
+impl DAGStorage for ConsensusDB {
+    fn save_vote(&self, vote: &Vote) -> anyhow::Result<()> {
+        Ok(self.save_dag_vote(vote)?)
+    }
+
+    fn get_votes(&self) -> anyhow::Result<Vec<Vote>> {
+        Ok(self.get_dag_votes()?)
+    }
+
+    fn delete_votes(&self, votes: Vec<Vote>) -> anyhow::Result<()> {
+        Ok(self.delete_dag_votes(votes)?)
+    }
+
+    fn save_pruned_round(&self, epoch: u64, round: Round) -> anyhow::Result<()> {
+        Ok(self.save_dag_pruned_round(bcs::to_bytes(&(epoch, round))?)?)
+    }
+
+    fn get_pruned_round(&self) -> anyhow::Result<Option<(u64, Round)>> {
+        Ok(self
+            .get_dag_pruned_round()?
+            .map(|bytes| bcs::from_bytes(&bytes))
+            .transpose()?)
+    }
+}
+
+/// The votes sent in the current epoch before a restart
+pub struct RecoveredVotes {
+    pub votes: Vec<Vote>,
+    /// Nodes below this round were pruned along with their votes, so they're never voted on
+    pub pruned_round: Round,
+}
+
+/// Recovers the votes sent in the epoch, deleting the ones of previous epochs.
+pub fn recover_votes(storage: &dyn DAGStorage, epoch: u64) -> anyhow::Result<RecoveredVotes> {
+    let (votes, expired_votes): (Vec<_>, Vec<_>) = storage
+        .get_votes()?
+        .into_iter()
+        .partition(|vote| vote.metadata().epoch() == epoch);
+    if !expired_votes.is_empty() {
+        storage.delete_votes(expired_votes)?;
+    }
+    let pruned_round = match storage.get_pruned_round()? {
+        Some((pruned_epoch, round)) if pruned_epoch == epoch => round,
+        _ => 0,
+    };
+    Ok(RecoveredVotes {
+        votes,
+        pruned_round,
+    })
+}
@@ -87,11 +87,11 @@ fn test_dag_insertion_failure() {
     assert!(dag.add_node(node).is_err());
 }
 
-fn new_node(round: Round, author: Author, parents: Vec<NodeMetadata>) -> CertifiedNode {
+pub(super) fn new_node(round: Round, author: Author, parents: Vec<NodeMetadata>) -> CertifiedNode {
     let node = Node::new(1, round, author, 0, Payload::empty(false), parents);
-    let digest = node.digest();
+    let metadata = node.metadata();
     CertifiedNode::new(
         node,
-        NodeCertificate::new(digest, AggregateSignature::empty()),
+        NodeCertificate::new(metadata, AggregateSignature::empty()),
     )
 }
@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod dag_test;
+mod order_rule_tests;
 mod reliable_broadcast_tests;
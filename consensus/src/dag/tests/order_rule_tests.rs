@@ -0,0 +1,159 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::dag_test::new_node;
+use crate::dag::{
+    anchor_election::RoundRobinAnchorElection,
+    dag_store::{Dag, NodeMetadata},
+    order_rule::OrderRule,
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_types::{epoch_state::EpochState, validator_verifier::random_validator_verifier};
+use std::sync::Arc;
+
+fn setup() -> (Vec<Author>, Dag, OrderRule) {
+    let (_, validator_verifier) = random_validator_verifier(4, None, false);
+    let validators: Vec<_> = validator_verifier
+        .get_ordered_account_addresses_iter()
+        .collect();
+    let dag = Dag::new(validator_verifier.address_to_validator_index().clone(), 0);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let order_rule = OrderRule::new(
+        epoch_state,
+        Box::new(RoundRobinAnchorElection::new(validators.clone())),
+        10,
+        40,
+        None,
+    );
+    (validators, dag, order_rule)
+}
+
+fn add_round(
+    dag: &mut Dag,
+    round: Round,
+    authors: &[Author],
+    parents: &[NodeMetadata],
+) -> Vec<NodeMetadata> {
+    authors
+        .iter()
+        .map(|author| {
+            let node = new_node(round, *author, parents.to_vec());
+            let metadata = node.metadata();
+            dag.add_node(node).unwrap();
+            metadata
+        })
+        .collect()
+}
+
+#[test]
+fn test_order_anchor_with_votes() {
+    let (validators, mut dag, mut order_rule) = setup();
+
+    let round_1 = add_round(&mut dag, 1, &validators, &[]);
+    // the anchor of round 1 can't be ordered before the next round links to it
+    assert!(order_rule.process(&dag).is_empty());
+
+    let round_2 = add_round(&mut dag, 2, &validators, &round_1);
+    let ordered = order_rule.process(&dag);
+    assert_eq!(ordered.len(), 1);
+    assert_eq!(*ordered[0].anchor.author(), validators[0]);
+    assert_eq!(ordered[0].nodes.len(), 1);
+    assert!(ordered[0].failed_authors.is_empty());
+
+    let round_3 = add_round(&mut dag, 3, &validators, &round_2);
+    add_round(&mut dag, 4, &validators, &round_3);
+    let ordered = order_rule.process(&dag);
+    assert_eq!(ordered.len(), 1);
+    let anchor = &ordered[0].anchor;
+    assert_eq!((anchor.round(), *anchor.author()), (3, validators[1]));
+    // the rest of round 1, round 2 and the anchor itself
+    assert_eq!(ordered[0].nodes.len(), 8);
+    assert_eq!(ordered[0].nodes.last().unwrap().digest(), anchor.digest());
+    assert!(ordered[0]
+        .nodes
+        .windows(2)
+        .all(|pair| pair[0].round() <= pair[1].round()));
+
+    // nothing is ordered twice
+    assert!(order_rule.process(&dag).is_empty());
+}
+
+#[test]
+fn test_order_skips_missing_anchor() {
+    let (validators, mut dag, mut order_rule) = setup();
+
+    // the anchor of round 1 never shows up
+    let round_1 = add_round(&mut dag, 1, &validators[1..], &[]);
+    let round_2 = add_round(&mut dag, 2, &validators, &round_1);
+    let round_3 = add_round(&mut dag, 3, &validators, &round_2);
+    add_round(&mut dag, 4, &validators, &round_3);
+
+    let ordered = order_rule.process(&dag);
+    assert_eq!(ordered.len(), 1);
+    assert_eq!(ordered[0].anchor.round(), 3);
+    assert_eq!(ordered[0].failed_authors, vec![(1, validators[0])]);
+    assert_eq!(ordered[0].nodes.len(), 8);
+}
+
+#[test]
+fn test_order_indirect_anchor_first() {
+    let (validators, mut dag, mut order_rule) = setup();
+
+    let round_1 = add_round(&mut dag, 1, &validators, &[]);
+    let anchor_1 = round_1[0].clone();
+    let without_anchor: Vec<_> = round_1[1..].to_vec();
+    // only a single node links to the anchor of round 1, not enough to commit it directly
+    let mut round_2 = add_round(&mut dag, 2, &validators[..1], &round_1);
+    round_2.extend(add_round(&mut dag, 2, &validators[1..], &without_anchor));
+    assert!(order_rule.process(&dag).is_empty());
+
+    let round_3 = add_round(&mut dag, 3, &validators, &round_2);
+    add_round(&mut dag, 4, &validators, &round_3);
+
+    // the anchor of round 3 reaches the anchor of round 1, which is ordered first
+    let ordered = order_rule.process(&dag);
+    assert_eq!(ordered.len(), 2);
+    assert_eq!(ordered[0].anchor.metadata(), anchor_1);
+    assert_eq!(ordered[0].nodes.len(), 1);
+    assert_eq!(ordered[1].anchor.round(), 3);
+    assert!(ordered[1].failed_authors.is_empty());
+    assert_eq!(ordered[1].nodes.len(), 8);
+}
+
+#[test]
+fn test_order_after_committed_anchor() {
+    let (validators, mut dag, mut order_rule) = setup();
+
+    let round_1 = add_round(&mut dag, 1, &validators, &[]);
+    let round_2 = add_round(&mut dag, 2, &validators, &round_1);
+    // e.g., restarted after the anchor of round 3 was committed
+    let anchor_3 = new_node(3, validators[1], round_2.clone()).metadata();
+    order_rule.reset(Some((anchor_3.round(), *anchor_3.digest())));
+    // nothing is ordered until the committed anchor is in the DAG
+    assert!(order_rule.process(&dag).is_empty());
+
+    let round_3 = add_round(&mut dag, 3, &validators, &round_2);
+    assert_eq!(round_3[1], anchor_3);
+    let round_4 = add_round(&mut dag, 4, &validators, &round_3);
+    let round_5 = add_round(&mut dag, 5, &validators, &round_4);
+    add_round(&mut dag, 6, &validators, &round_5);
+
+    // the causal history of the committed anchor was ordered before
+    let ordered = order_rule.process(&dag);
+    assert_eq!(ordered.len(), 1);
+    let anchor = &ordered[0].anchor;
+    assert_eq!((anchor.round(), *anchor.author()), (5, validators[2]));
+    assert!(ordered[0].failed_authors.is_empty());
+    // the rest of round 3, round 4 and the anchor itself
+    assert_eq!(ordered[0].nodes.len(), 8);
+
+    assert_eq!(OrderRule::start_round(None, 40), 0);
+    assert_eq!(OrderRule::start_round(Some((3, *anchor_3.digest())), 40), 0);
+    assert_eq!(
+        OrderRule::start_round(Some((41, *anchor_3.digest())), 40),
+        3
+    );
+}
@@ -0,0 +1,372 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    dag::{
+        dag_store::{CertifiedNode, Node, NodeCertificate, NodeMetadata},
+        reliable_broadcast::{BroadcastStatus, DAGMessage},
+    },
+    network_interface::ConsensusMsg,
+};
+use anyhow::{anyhow, bail, ensure};
+use aptos_consensus_types::common::Author;
+use aptos_crypto::bls12381;
+use aptos_types::{
+    aggregate_signature::PartialSignatures, epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+
+/// Maximum number of nodes that can be requested in a single fetch
+pub const MAX_FETCH_TARGETS: usize = 100;
+
+/// A signature over the node metadata, an honest validator signs at most one node per
+/// (round, author).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "DAGVote")]
+pub struct Vote {
+    metadata: NodeMetadata,
+    signature: bls12381::Signature,
+}
+
+impl Vote {
+    pub fn new(metadata: NodeMetadata, signature: bls12381::Signature) -> Self {
+        Self {
+            metadata,
+            signature,
+        }
+    }
+
+    pub fn metadata(&self) -> &NodeMetadata {
+        &self.metadata
+    }
+
+    pub fn signature(&self) -> &bls12381::Signature {
+        &self.signature
+    }
+
+    pub fn verify(&self, author: Author, epoch_state: &EpochState) -> anyhow::Result<()> {
+        epoch_state
+            .verifier
+            .verify(author, &self.metadata, &self.signature)
+            .map_err(|e| anyhow!("invalid vote from {}: {}", author, e))
+    }
+}
+
+/// Collects the votes for our own node until they form a certificate.
+pub struct SignatureBuilder {
+    metadata: NodeMetadata,
+    partial_signatures: PartialSignatures,
+    epoch_state: Arc<EpochState>,
+}
+
+impl SignatureBuilder {
+    pub fn new(metadata: NodeMetadata, epoch_state: Arc<EpochState>) -> Self {
+        Self {
+            metadata,
+            partial_signatures: PartialSignatures::empty(),
+            epoch_state,
+        }
+    }
+}
+
+impl BroadcastStatus for SignatureBuilder {
+    type Ack = Vote;
+    type Aggregated = NodeCertificate;
+    type Message = Node;
+
+    fn add(&mut self, peer: Author, ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
+        ensure!(ack.metadata == self.metadata, "vote for a different node");
+        ack.verify(peer, &self.epoch_state)?;
+        self.partial_signatures.add_signature(peer, ack.signature);
+        let verifier = &self.epoch_state.verifier;
+        if verifier
+            .check_voting_power(self.partial_signatures.signatures().keys())
+            .is_ok()
+        {
+            let signatures = verifier.aggregate_signatures(&self.partial_signatures)?;
+            Ok(Some(NodeCertificate::new(
+                self.metadata.clone(),
+                signatures,
+            )))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Acknowledges that a certified node has been added to the receiver's DAG.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CertifiedAck {
+    epoch: u64,
+}
+
+impl CertifiedAck {
+    pub fn new(epoch: u64) -> Self {
+        Self { epoch }
+    }
+}
+
+/// Waits for every validator to acknowledge the certified node, so that it's delivered to
+/// everyone reachable before the broadcast is cancelled.
+pub struct CertificateAckState {
+    num_validators: usize,
+    received: HashSet<Author>,
+}
+
+impl CertificateAckState {
+    pub fn new(num_validators: usize) -> Self {
+        Self {
+            num_validators,
+            received: HashSet::new(),
+        }
+    }
+}
+
+impl BroadcastStatus for CertificateAckState {
+    type Ack = CertifiedAck;
+    type Aggregated = ();
+    type Message = CertifiedNode;
+
+    fn add(&mut self, peer: Author, _ack: Self::Ack) -> anyhow::Result<Option<Self::Aggregated>> {
+        self.received.insert(peer);
+        if self.received.len() == self.num_validators {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Requests the certified nodes that are missing locally, usually the parents of a node that
+/// couldn't be added to the DAG yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FetchRequest {
+    epoch: u64,
+    targets: Vec<NodeMetadata>,
+}
+
+impl FetchRequest {
+    pub fn new(epoch: u64, targets: Vec<NodeMetadata>) -> Self {
+        Self { epoch, targets }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn targets(&self) -> &[NodeMetadata] {
+        &self.targets
+    }
+
+    pub fn verify(&self) -> anyhow::Result<()> {
+        ensure!(!self.targets.is_empty(), "empty fetch request");
+        ensure!(
+            self.targets.len() <= MAX_FETCH_TARGETS,
+            "too many fetch targets: {}",
+            self.targets.len()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FetchResponse {
+    epoch: u64,
+    certified_nodes: Vec<CertifiedNode>,
+}
+
+impl FetchResponse {
+    pub fn new(epoch: u64, certified_nodes: Vec<CertifiedNode>) -> Self {
+        Self {
+            epoch,
+            certified_nodes,
+        }
+    }
+
+    pub fn certified_nodes(self) -> Vec<CertifiedNode> {
+        self.certified_nodes
+    }
+
+    /// Verifies that the response only carries requested nodes, each with a valid certificate.
+    pub fn verify(&self, request: &FetchRequest, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(
+            self.epoch == request.epoch,
+            "fetch response from a different epoch"
+        );
+        for node in &self.certified_nodes {
+            ensure!(
+                request.targets.contains(node.certificate().metadata()),
+                "fetch response carries a node that wasn't requested"
+            );
+            node.verify(&epoch_state.verifier)?;
+        }
+        Ok(())
+    }
+}
+
+/// Asks a validator for its latest commit, sent when the local DAG is too far behind the DAG of
+/// the other validators to fetch the missing nodes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncRequest {
+    epoch: u64,
+}
+
+impl SyncRequest {
+    pub fn new(epoch: u64) -> Self {
+        Self { epoch }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncResponse {
+    epoch: u64,
+    ledger_info: LedgerInfoWithSignatures,
+}
+
+impl SyncResponse {
+    pub fn new(epoch: u64, ledger_info: LedgerInfoWithSignatures) -> Self {
+        Self { epoch, ledger_info }
+    }
+
+    pub fn ledger_info(self) -> LedgerInfoWithSignatures {
+        self.ledger_info
+    }
+
+    /// Verifies that the commit is certified by a quorum of the epoch.
+    pub fn verify(&self, request: &SyncRequest, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(
+            self.epoch == request.epoch && self.ledger_info.ledger_info().epoch() == request.epoch,
+            "sync response from a different epoch"
+        );
+        self.ledger_info
+            .verify_signatures(&epoch_state.verifier)
+            .map_err(|e| anyhow!("invalid commit ledger info: {}", e))
+    }
+}
+
+impl DAGMessage for Node {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGNodeMsg(node) => Ok(*node),
+            _ => bail!("unexpected message, expected node: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGNodeMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for Vote {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGVoteMsg(vote) => Ok(*vote),
+            _ => bail!("unexpected message, expected vote: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGVoteMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for CertifiedNode {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGCertifiedNodeMsg(node) => Ok(*node),
+            _ => bail!(
+                "unexpected message, expected certified node: {}",
+                msg.name()
+            ),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGCertifiedNodeMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for CertifiedAck {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGCertifiedAckMsg(ack) => Ok(*ack),
+            _ => bail!("unexpected message, expected certified ack: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGCertifiedAckMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for FetchRequest {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGFetchRequestMsg(request) => Ok(*request),
+            _ => bail!("unexpected message, expected fetch request: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGFetchRequestMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for FetchResponse {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGFetchResponseMsg(response) => Ok(*response),
+            _ => bail!(
+                "unexpected message, expected fetch response: {}",
+                msg.name()
+            ),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGFetchResponseMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for SyncRequest {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGSyncRequestMsg(request) => Ok(*request),
+            _ => bail!("unexpected message, expected sync request: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGSyncRequestMsg(Box::new(self))
+    }
+}
+
+impl DAGMessage for SyncResponse {
+    fn from_network_message(msg: ConsensusMsg) -> anyhow::Result<Self> {
+        match msg {
+            ConsensusMsg::DAGSyncResponseMsg(response) => Ok(*response),
+            _ => bail!("unexpected message, expected sync response: {}", msg.name()),
+        }
+    }
+
+    fn into_network_message(self) -> ConsensusMsg {
+        ConsensusMsg::DAGSyncResponseMsg(Box::new(self))
+    }
+}
+
+/// Returns the epoch of a DAG rpc request, or None if the message isn't one.
+pub fn dag_request_epoch(msg: &ConsensusMsg) -> Option<u64> {
+    match msg {
+        ConsensusMsg::DAGNodeMsg(node) => Some(node.epoch()),
+        ConsensusMsg::DAGCertifiedNodeMsg(node) => Some(node.epoch()),
+        ConsensusMsg::DAGFetchRequestMsg(request) => Some(request.epoch()),
+        ConsensusMsg::DAGSyncRequestMsg(request) => Some(request.epoch()),
+        _ => None,
+    }
+}
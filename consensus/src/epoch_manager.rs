@@ -8,16 +8,22 @@ use crate::{
         BlockStore,
     },
     counters,
+    dag::{
+        committed_block_info, dag_request_epoch, recover_votes, AnchorElection, DAGStorage,
+        DagCommitSigner, DagDriver, DagMetadataBackend, LeaderReputationAnchorElection,
+        OrderedNotifier, RoundRobinAnchorElection,
+    },
     error::{error_kind, DbError},
     experimental::{
         buffer_manager::{OrderedBlocks, ResetRequest},
         decoupled_execution_utils::prepare_phases_and_buffer_manager,
         ordering_state_computer::OrderingStateComputer,
+        signing_phase::CommitSignerProvider,
     },
     liveness::{
         cached_proposer_election::CachedProposerElection,
         leader_reputation::{
-            extract_epoch_to_proposers, AptosDBBackend, LeaderReputation,
+            extract_epoch_to_proposers, first_epoch_to_consider, AptosDBBackend, LeaderReputation,
             ProposerAndVoterHeuristic, ReputationHeuristic,
         },
         proposal_generator::{
@@ -32,11 +38,12 @@ use crate::{
     metrics_safety_rules::MetricsSafetyRules,
     monitor,
     network::{
-        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, IncomingRpcRequest,
-        NetworkReceivers, NetworkSender,
+        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, IncomingDAGRequest,
+        IncomingRpcRequest, NetworkReceivers, NetworkSender,
     },
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     payload_client::QuorumStoreClient,
+    payload_manager::PayloadManager,
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
     quorum_store::{
         quorum_store_builder::{DirectMempoolInnerBuilder, InnerBuilder, QuorumStoreBuilder},
@@ -63,7 +70,7 @@ use aptos_infallible::{duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
 use aptos_mempool::QuorumStoreRequest;
 use aptos_network::{application::interface::NetworkClient, protocols::network::Event};
-use aptos_safety_rules::SafetyRulesManager;
+use aptos_safety_rules::{safety_rules_manager, PersistentSafetyStorage, SafetyRulesManager};
 use aptos_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
@@ -72,6 +79,7 @@ use aptos_types::{
         LeaderReputationType, OnChainConfigPayload, OnChainConsensusConfig, OnChainExecutionConfig,
         ProposerElectionType, ValidatorSet,
     },
+    validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
 use fail::fail_point;
@@ -120,6 +128,9 @@ pub struct EpochManager {
     commit_state_computer: Arc<dyn StateComputer>,
     storage: Arc<dyn PersistentLivenessStorage>,
     safety_rules_manager: SafetyRulesManager,
+    // only used to load the consensus key for DAG consensus, which doesn't use safety rules,
+    // so it's only opened when DAG consensus is enabled
+    key_storage: Option<PersistentSafetyStorage>,
     reconfig_events: ReconfigNotificationListener,
     // channels to buffer manager
     buffer_manager_msg_tx: Option<aptos_channel::Sender<AccountAddress, VerifiedEvent>>,
@@ -129,6 +140,9 @@ pub struct EpochManager {
         aptos_channel::Sender<(Author, Discriminant<VerifiedEvent>), (Author, VerifiedEvent)>,
     >,
     round_manager_close_tx: Option<oneshot::Sender<oneshot::Sender<()>>>,
+    // channels to dag driver
+    dag_rpc_tx: Option<aptos_channel::Sender<Author, IncomingDAGRequest>>,
+    dag_close_tx: Option<oneshot::Sender<oneshot::Sender<()>>>,
     epoch_state: Option<Arc<EpochState>>,
     block_retrieval_tx:
         Option<aptos_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>>,
@@ -160,6 +174,11 @@ impl EpochManager {
         let config = node_config.consensus.clone();
         let sr_config = &node_config.consensus.safety_rules;
         let safety_rules_manager = SafetyRulesManager::new(sr_config);
+        let key_storage = node_config
+            .consensus
+            .dag_consensus
+            .enabled
+            .then(|| safety_rules_manager::storage(sr_config));
         Self {
             author,
             config,
@@ -173,11 +192,14 @@ impl EpochManager {
             commit_state_computer,
             storage,
             safety_rules_manager,
+            key_storage,
             reconfig_events,
             buffer_manager_msg_tx: None,
             buffer_manager_reset_tx: None,
             round_manager_tx: None,
             round_manager_close_tx: None,
+            dag_rpc_tx: None,
+            dag_close_tx: None,
             epoch_state: None,
             block_retrieval_tx: None,
             quorum_store_msg_tx: None,
@@ -232,37 +254,20 @@ impl EpochManager {
                 Box::new(RotatingProposer::new(vec![proposer], *contiguous_rounds))
             },
             ProposerElectionType::LeaderReputation(leader_reputation_type) => {
-                let (
-                    heuristic,
-                    window_size,
-                    weight_by_voting_power,
-                    use_history_from_previous_epoch_max_count,
-                ) = match &leader_reputation_type {
-                    LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
-                    | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => {
-                        let proposer_window_size = proposers.len()
-                            * proposer_and_voter_config.proposer_window_num_validators_multiplier;
-                        let voter_window_size = proposers.len()
-                            * proposer_and_voter_config.voter_window_num_validators_multiplier;
-                        let heuristic: Box<dyn ReputationHeuristic> =
-                            Box::new(ProposerAndVoterHeuristic::new(
-                                self.author,
-                                proposer_and_voter_config.active_weight,
-                                proposer_and_voter_config.inactive_weight,
-                                proposer_and_voter_config.failed_weight,
-                                proposer_and_voter_config.failure_threshold_percent,
-                                voter_window_size,
-                                proposer_window_size,
-                                leader_reputation_type.use_reputation_window_from_stale_end(),
-                            ));
-                        (
-                            heuristic,
-                            std::cmp::max(proposer_window_size, voter_window_size),
+                let (weight_by_voting_power, use_history_from_previous_epoch_max_count) =
+                    match &leader_reputation_type {
+                        LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
+                        | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => (
                             proposer_and_voter_config.weight_by_voting_power,
                             proposer_and_voter_config.use_history_from_previous_epoch_max_count,
-                        )
-                    },
-                };
+                        ),
+                    };
+                let (heuristic, window_size) = ProposerAndVoterHeuristic::from_config(
+                    self.author,
+                    proposers.len(),
+                    leader_reputation_type,
+                );
+                let heuristic: Box<dyn ReputationHeuristic> = Box::new(heuristic);
 
                 let seek_len = onchain_config.leader_reputation_exclude_round() as usize
                     + onchain_config.max_failed_authors_to_store()
@@ -282,15 +287,9 @@ impl EpochManager {
                     vec![1; proposers.len()]
                 };
 
-                // Genesis is epoch=0
-                // First block (after genesis) is epoch=1, and is the only block in that epoch.
-                // It has no votes, so we skip it unless we are in epoch 1, as otherwise it will
-                // skew leader elections for exclude_round number of rounds.
-                let first_epoch_to_consider = std::cmp::max(
-                    if epoch_state.epoch == 1 { 1 } else { 2 },
-                    epoch_state
-                        .epoch
-                        .saturating_sub(use_history_from_previous_epoch_max_count as u64),
+                let first_epoch_to_consider = first_epoch_to_consider(
+                    epoch_state.epoch,
+                    use_history_from_previous_epoch_max_count,
                 );
                 // If we are considering beyond the current epoch, we need to fetch validators for those epochs
                 let epoch_to_proposers = if epoch_state.epoch > first_epoch_to_consider {
@@ -348,6 +347,61 @@ impl EpochManager {
         }
     }
 
+    /// Create the anchor election for DAG consensus. Leader reputation is only fed with the
+    /// anchors ordered in the current epoch, as it needs to be the same on every validator.
+    fn create_anchor_election(
+        &self,
+        epoch_state: &EpochState,
+        onchain_config: &OnChainConsensusConfig,
+    ) -> Box<dyn AnchorElection> {
+        let proposers = epoch_state
+            .verifier
+            .get_ordered_account_addresses_iter()
+            .collect::<Vec<_>>();
+        match &onchain_config.proposer_election_type() {
+            ProposerElectionType::LeaderReputation(leader_reputation_type) => {
+                let proposer_and_voter_config = match &leader_reputation_type {
+                    LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
+                    | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => {
+                        proposer_and_voter_config
+                    },
+                };
+                let (heuristic, window_size) = ProposerAndVoterHeuristic::from_config(
+                    self.author,
+                    proposers.len(),
+                    leader_reputation_type,
+                );
+                let heuristic: Box<dyn ReputationHeuristic> = Box::new(heuristic);
+                let backend = DagMetadataBackend::new(window_size);
+                let voting_powers: Vec<_> = if proposer_and_voter_config.weight_by_voting_power {
+                    proposers
+                        .iter()
+                        .map(|p| epoch_state.verifier.get_voting_power(p).unwrap())
+                        .collect()
+                } else {
+                    vec![1; proposers.len()]
+                };
+                let leader_reputation = LeaderReputation::new(
+                    epoch_state.epoch,
+                    HashMap::from([(epoch_state.epoch, proposers)]),
+                    voting_powers,
+                    Box::new(backend.clone()),
+                    heuristic,
+                    // the history only contains ordered anchors, there's nothing to exclude
+                    0,
+                    // there's no accumulator root hash before execution
+                    false,
+                    self.config.window_for_chain_health,
+                );
+                Box::new(LeaderReputationAnchorElection::new(
+                    backend,
+                    leader_reputation,
+                ))
+            },
+            _ => Box::new(RoundRobinAnchorElection::new(proposers)),
+        }
+    }
+
     fn process_epoch_retrieval(
         &mut self,
         request: EpochRetrievalRequest,
@@ -480,12 +534,16 @@ impl EpochManager {
     }
 
     /// this function spawns the phases and a buffer manager
-    /// it sets `self.commit_msg_tx` to a new aptos_channel::Sender and returns an OrderingStateComputer
+    /// it sets `self.commit_msg_tx` to a new aptos_channel::Sender and returns the channels to
+    /// send ordered blocks and reset requests to the buffer manager
     fn spawn_decoupled_execution(
         &mut self,
-        safety_rules_container: Arc<Mutex<MetricsSafetyRules>>,
+        commit_signer_provider: Arc<dyn CommitSignerProvider>,
         verifier: ValidatorVerifier,
-    ) -> OrderingStateComputer {
+    ) -> (
+        UnboundedSender<OrderedBlocks>,
+        UnboundedSender<ResetRequest>,
+    ) {
         let network_sender = NetworkSender::new(
             self.author,
             self.network_sender.clone(),
@@ -509,7 +567,7 @@ impl EpochManager {
             prepare_phases_and_buffer_manager(
                 self.author,
                 self.commit_state_computer.clone(),
-                safety_rules_container,
+                commit_signer_provider,
                 network_sender,
                 commit_msg_rx,
                 self.commit_state_computer.clone(),
@@ -523,7 +581,7 @@ impl EpochManager {
         tokio::spawn(persisting_phase.start());
         tokio::spawn(buffer_manager.start());
 
-        OrderingStateComputer::new(block_tx, self.commit_state_computer.clone(), reset_tx)
+        (block_tx, reset_tx)
     }
 
     async fn shutdown_current_processor(&mut self) {
@@ -539,6 +597,18 @@ impl EpochManager {
         }
         self.round_manager_tx = None;
 
+        if let Some(close_tx) = self.dag_close_tx.take() {
+            // Release the previous DagDriver, together with its broadcasts
+            let (ack_tx, ack_rx) = oneshot::channel();
+            close_tx
+                .send(ack_tx)
+                .expect("[EpochManager] Fail to drop dag driver");
+            ack_rx
+                .await
+                .expect("[EpochManager] Fail to drop dag driver");
+        }
+        self.dag_rpc_tx = None;
+
         // Shutdown the previous buffer manager, to release the SafetyRule client
         self.buffer_manager_msg_tx = None;
         if let Some(mut tx) = self.buffer_manager_reset_tx.take() {
@@ -599,6 +669,82 @@ impl EpochManager {
         tokio::spawn(recovery_manager.start(recovery_manager_rx, close_rx));
     }
 
+    /// Builds the QuorumStore (or DirectMempool), the payload is pulled through the returned
+    /// client. The QuorumStore is only started with `start_quorum_store`.
+    fn init_payload_provider(
+        &mut self,
+        epoch_state: &EpochState,
+        network_sender: NetworkSender,
+    ) -> (Arc<PayloadManager>, QuorumStoreClient, QuorumStoreBuilder) {
+        let (consensus_to_quorum_store_tx, consensus_to_quorum_store_rx) =
+            mpsc::channel(self.config.intra_consensus_channel_buffer_size);
+
+        let mut quorum_store_builder = if self.quorum_store_enabled {
+            info!("Building QuorumStore");
+            QuorumStoreBuilder::QuorumStore(InnerBuilder::new(
+                self.epoch(),
+                self.author,
+                epoch_state.verifier.len() as u64,
+                self.config.quorum_store.clone(),
+                consensus_to_quorum_store_rx,
+                self.quorum_store_to_mempool_sender.clone(),
+                self.config.mempool_txn_pull_timeout_ms,
+                self.storage.aptos_db().clone(),
+                network_sender,
+                epoch_state.verifier.clone(),
+                self.config.safety_rules.backend.clone(),
+                self.quorum_store_storage.clone(),
+            ))
+        } else {
+            info!("Building DirectMempool");
+            QuorumStoreBuilder::DirectMempool(DirectMempoolInnerBuilder::new(
+                consensus_to_quorum_store_rx,
+                self.quorum_store_to_mempool_sender.clone(),
+                self.config.mempool_txn_pull_timeout_ms,
+            ))
+        };
+
+        let (payload_manager, quorum_store_msg_tx) = quorum_store_builder.init_payload_manager();
+        self.quorum_store_msg_tx = quorum_store_msg_tx;
+
+        let payload_client = QuorumStoreClient::new(
+            consensus_to_quorum_store_tx,
+            self.config.quorum_store_pull_timeout_ms,
+            self.config.wait_for_full_blocks_above_recent_fill_threshold,
+            self.config.wait_for_full_blocks_above_pending_blocks,
+        );
+        (payload_manager, payload_client, quorum_store_builder)
+    }
+
+    fn init_commit_state_computer(
+        &self,
+        epoch_state: &EpochState,
+        payload_manager: Arc<PayloadManager>,
+        onchain_execution_config: &OnChainExecutionConfig,
+    ) {
+        let transaction_shuffler =
+            create_transaction_shuffler(onchain_execution_config.transaction_shuffler_type());
+        let block_gas_limit = onchain_execution_config.block_gas_limit();
+        let transaction_deduper =
+            create_transaction_deduper(onchain_execution_config.transaction_deduper_type());
+        self.commit_state_computer.new_epoch(
+            epoch_state,
+            payload_manager,
+            transaction_shuffler,
+            block_gas_limit,
+            transaction_deduper,
+        );
+    }
+
+    fn start_quorum_store(&mut self, quorum_store_builder: QuorumStoreBuilder) {
+        if let Some((quorum_store_coordinator_tx, batch_retrieval_rx)) =
+            quorum_store_builder.start()
+        {
+            self.quorum_store_coordinator_tx = Some(quorum_store_coordinator_tx);
+            self.batch_retrieval_tx = Some(batch_retrieval_rx);
+        }
+    }
+
     async fn start_round_manager(
         &mut self,
         recovery_data: RecoveryData,
@@ -649,60 +795,22 @@ impl EpochManager {
 
         let safety_rules_container = Arc::new(Mutex::new(safety_rules));
 
-        // Start QuorumStore
-        let (consensus_to_quorum_store_tx, consensus_to_quorum_store_rx) =
-            mpsc::channel(self.config.intra_consensus_channel_buffer_size);
-
-        let mut quorum_store_builder = if self.quorum_store_enabled {
-            info!("Building QuorumStore");
-            QuorumStoreBuilder::QuorumStore(InnerBuilder::new(
-                self.epoch(),
-                self.author,
-                epoch_state.verifier.len() as u64,
-                self.config.quorum_store.clone(),
-                consensus_to_quorum_store_rx,
-                self.quorum_store_to_mempool_sender.clone(),
-                self.config.mempool_txn_pull_timeout_ms,
-                self.storage.aptos_db().clone(),
-                network_sender.clone(),
-                epoch_state.verifier.clone(),
-                self.config.safety_rules.backend.clone(),
-                self.quorum_store_storage.clone(),
-            ))
-        } else {
-            info!("Building DirectMempool");
-            QuorumStoreBuilder::DirectMempool(DirectMempoolInnerBuilder::new(
-                consensus_to_quorum_store_rx,
-                self.quorum_store_to_mempool_sender.clone(),
-                self.config.mempool_txn_pull_timeout_ms,
-            ))
-        };
-
-        let (payload_manager, quorum_store_msg_tx) = quorum_store_builder.init_payload_manager();
-        let transaction_shuffler =
-            create_transaction_shuffler(onchain_execution_config.transaction_shuffler_type());
-        let block_gas_limit = onchain_execution_config.block_gas_limit();
-        let transaction_deduper =
-            create_transaction_deduper(onchain_execution_config.transaction_deduper_type());
-        self.quorum_store_msg_tx = quorum_store_msg_tx;
-
-        let payload_client = QuorumStoreClient::new(
-            consensus_to_quorum_store_tx,
-            self.config.quorum_store_pull_timeout_ms,
-            self.config.wait_for_full_blocks_above_recent_fill_threshold,
-            self.config.wait_for_full_blocks_above_pending_blocks,
-        );
-        self.commit_state_computer.new_epoch(
+        let (payload_manager, payload_client, quorum_store_builder) =
+            self.init_payload_provider(&epoch_state, network_sender.clone());
+        self.init_commit_state_computer(
             &epoch_state,
             payload_manager.clone(),
-            transaction_shuffler,
-            block_gas_limit,
-            transaction_deduper,
+            &onchain_execution_config,
         );
         let state_computer = if onchain_consensus_config.decoupled_execution() {
-            Arc::new(self.spawn_decoupled_execution(
+            let (block_tx, reset_tx) = self.spawn_decoupled_execution(
                 safety_rules_container.clone(),
                 epoch_state.verifier.clone(),
+            );
+            Arc::new(OrderingStateComputer::new(
+                block_tx,
+                self.commit_state_computer.clone(),
+                reset_tx,
             ))
         } else {
             self.commit_state_computer.clone()
@@ -719,12 +827,7 @@ impl EpochManager {
             payload_manager.clone(),
         ));
 
-        if let Some((quorum_store_coordinator_tx, batch_retrieval_rx)) =
-            quorum_store_builder.start()
-        {
-            self.quorum_store_coordinator_tx = Some(quorum_store_coordinator_tx);
-            self.batch_retrieval_tx = Some(batch_retrieval_rx);
-        }
+        self.start_quorum_store(quorum_store_builder);
 
         info!(epoch = epoch, "Create ProposalGenerator");
         // txn manager is required both by proposal generator (to pull the proposers)
@@ -792,6 +895,138 @@ impl EpochManager {
         self.spawn_block_retrieval_task(epoch, block_store);
     }
 
+    /// The DAG blocks aren't stored in ConsensusDB, DAG consensus starts from the latest commit
+    /// in AptosDB instead.
+    async fn start_dag_consensus(
+        &mut self,
+        epoch_state: EpochState,
+        onchain_consensus_config: OnChainConsensusConfig,
+        onchain_execution_config: OnChainExecutionConfig,
+    ) {
+        let epoch = epoch_state.epoch;
+        counters::EPOCH.set(epoch_state.epoch as i64);
+        counters::CURRENT_EPOCH_VALIDATORS.set(epoch_state.verifier.len() as i64);
+        let aptos_db = self.storage.aptos_db();
+        let ledger_info = match aptos_db.get_latest_ledger_info() {
+            Ok(ledger_info) => ledger_info,
+            Err(error) => {
+                error!(
+                    epoch = epoch,
+                    error = ?error,
+                    "Unable to read the latest ledger info, DAG consensus is not started",
+                );
+                return;
+            },
+        };
+        info!(
+            epoch = epoch_state.epoch,
+            validators = epoch_state.verifier.to_string(),
+            ledger_info = %ledger_info,
+            "Starting new epoch with DAG consensus",
+        );
+
+        let key_storage = self
+            .key_storage
+            .as_ref()
+            .expect("Key storage is created when DAG consensus is enabled");
+        let signer = match epoch_state.verifier.get_public_key(&self.author) {
+            Some(public_key) => match key_storage.consensus_key_for_version(public_key) {
+                Ok(private_key) => Arc::new(ValidatorSigner::new(self.author, private_key)),
+                Err(error) => {
+                    error!(
+                        epoch = epoch,
+                        error = ?error,
+                        "Unable to load the consensus key, DAG consensus is not started",
+                    );
+                    return;
+                },
+            },
+            None => {
+                info!(
+                    epoch = epoch,
+                    "Not a validator of the epoch, DAG consensus is not started"
+                );
+                return;
+            },
+        };
+
+        // The votes sent before a restart keep binding the validator within the epoch
+        let dag_storage: Arc<dyn DAGStorage> = self.storage.consensus_db();
+        let recovered_votes = match recover_votes(dag_storage.as_ref(), epoch) {
+            Ok(recovered_votes) => recovered_votes,
+            Err(error) => {
+                error!(
+                    epoch = epoch,
+                    error = ?error,
+                    "Unable to recover the DAG votes, DAG consensus is not started",
+                );
+                return;
+            },
+        };
+
+        let network_sender = NetworkSender::new(
+            self.author,
+            self.network_sender.clone(),
+            self.self_sender.clone(),
+            epoch_state.verifier.clone(),
+        );
+        let (payload_manager, payload_client, quorum_store_builder) =
+            self.init_payload_provider(&epoch_state, network_sender.clone());
+        self.init_commit_state_computer(
+            &epoch_state,
+            payload_manager.clone(),
+            &onchain_execution_config,
+        );
+        let (block_tx, reset_tx) = self.spawn_decoupled_execution(
+            Arc::new(DagCommitSigner::new(signer.clone())),
+            epoch_state.verifier.clone(),
+        );
+        // resets the buffer manager before state syncing
+        let state_computer = Arc::new(OrderingStateComputer::new(
+            block_tx.clone(),
+            self.commit_state_computer.clone(),
+            reset_tx,
+        ));
+        self.start_quorum_store(quorum_store_builder);
+
+        info!(epoch = epoch, "Create DagDriver");
+        let (committed_tx, committed_rx) = unbounded();
+        let notifier = OrderedNotifier::new(
+            committed_block_info(ledger_info.ledger_info()),
+            self.quorum_store_enabled,
+            payload_manager,
+            block_tx,
+            committed_tx,
+        );
+        let anchor_election = self.create_anchor_election(&epoch_state, &onchain_consensus_config);
+        let dag_driver = DagDriver::new(
+            self.author,
+            Arc::new(epoch_state),
+            signer,
+            anchor_election,
+            onchain_consensus_config.max_failed_authors_to_store(),
+            notifier,
+            Arc::new(network_sender),
+            Arc::new(payload_client),
+            dag_storage,
+            recovered_votes,
+            ledger_info.ledger_info(),
+            committed_rx,
+            aptos_db,
+            state_computer,
+            self.time_service.clone(),
+            self.config.dag_consensus.clone(),
+            self.quorum_store_enabled,
+        );
+
+        let (dag_rpc_tx, dag_rpc_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 100, Some(&counters::DAG_DRIVER_TASK_MSGS));
+        self.dag_rpc_tx = Some(dag_rpc_tx);
+        let (close_tx, close_rx) = oneshot::channel();
+        self.dag_close_tx = Some(close_tx);
+        tokio::spawn(dag_driver.start(dag_rpc_rx, close_rx));
+    }
+
     async fn start_new_epoch(&mut self, payload: OnChainConfigPayload) {
         let validator_set: ValidatorSet = payload
             .get()
@@ -809,6 +1044,16 @@ impl EpochManager {
 
         self.epoch_state = Some(Arc::new(epoch_state.clone()));
 
+        if self.config.dag_consensus.enabled {
+            let consensus_config = onchain_consensus_config.unwrap_or_default();
+            let execution_config = onchain_execution_config.unwrap_or_default();
+            self.quorum_store_enabled = self.enable_quorum_store(&consensus_config);
+            self.recovery_mode = false;
+            self.start_dag_consensus(epoch_state, consensus_config, execution_config)
+                .await;
+            return;
+        }
+
         match self.storage.start() {
             LivenessStorageData::FullRecoveryData(initial_data) => {
                 let consensus_config = onchain_consensus_config.unwrap_or_default();
@@ -1064,6 +1309,33 @@ impl EpochManager {
         }
     }
 
+    fn process_dag_rpc_request(
+        &mut self,
+        peer_id: Author,
+        request: IncomingDAGRequest,
+    ) -> anyhow::Result<()> {
+        fail_point!("consensus::process::any", |_| {
+            Err(anyhow::anyhow!("Injected error in process_dag_rpc_request"))
+        });
+        let epoch = dag_request_epoch(&request.req).ok_or_else(|| {
+            anyhow::anyhow!(
+                "[EpochManager] Unexpected DAG request {}",
+                request.req.name()
+            )
+        })?;
+        if epoch != self.epoch() {
+            return monitor!(
+                "process_different_epoch_dag_request",
+                self.process_different_epoch(epoch, peer_id)
+            );
+        }
+        if let Some(tx) = &self.dag_rpc_tx {
+            tx.push(peer_id, request)
+        } else {
+            Err(anyhow::anyhow!("DAG driver not started"))
+        }
+    }
+
     fn process_local_timeout(&mut self, round: u64) {
         let peer_id = self.author;
         let event = VerifiedEvent::LocalTimeout(round);
@@ -1119,6 +1391,12 @@ impl EpochManager {
                         error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
                     });
                 },
+                (peer, request) = network_receivers.dag_rpc_rx.select_next_some() => {
+                    monitor!("epoch_manager_process_dag_rpc",
+                    if let Err(e) = self.process_dag_rpc_request(peer, request) {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    });
+                },
                 round = round_timeout_sender_rx.select_next_some() => {
                     monitor!("epoch_manager_process_round_timeout",
                     self.process_local_timeout(round));
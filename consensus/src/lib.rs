@@ -48,6 +48,8 @@ pub mod consensus_provider;
 pub mod counters;
 /// AptosNet interface.
 pub mod network_interface;
+/// Replays leader reputation elections, used by the CLI
+pub use liveness::leader_reputation_explorer;
 mod payload_manager;
 mod sender_aware_shuffler;
 mod transaction_deduper;
@@ -24,7 +24,9 @@ use aptos_types::{
     account_config::{new_block_event_key, NewBlockEvent},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
+    on_chain_config::LeaderReputationType,
 };
+use serde::Serialize;
 use std::{
     cmp::max,
     collections::{HashMap, HashSet},
@@ -43,6 +45,7 @@ pub trait MetadataBackend: Send + Sync {
     ) -> (Vec<NewBlockEvent>, HashValue);
 }
 
+/// NewBlockEvent together with the version of the transaction that emitted it.
 #[derive(Debug, Clone)]
 pub struct VersionedNewBlockEvent {
     /// event
@@ -119,7 +122,7 @@ impl AptosDBBackend {
         &self,
         target_epoch: u64,
         target_round: Round,
-        events: &Vec<VersionedNewBlockEvent>,
+        events: &[VersionedNewBlockEvent],
         hit_end: bool,
     ) -> (Vec<NewBlockEvent>, HashValue) {
         // Do not warn when round==0, because check will always be unsure of whether we have
@@ -137,16 +140,8 @@ impl AptosDBBackend {
             }
         }
 
-        let mut max_version = 0;
-        let mut result = vec![];
-        for event in events {
-            if (event.event.epoch(), event.event.round()) <= (target_epoch, target_round)
-                && result.len() < self.window_size
-            {
-                max_version = std::cmp::max(max_version, event.version);
-                result.push(event.event.clone());
-            }
-        }
+        let (result, max_version) =
+            select_window(events, target_epoch, target_round, self.window_size);
 
         if result.len() < self.window_size && !hit_end {
             error!(
@@ -170,6 +165,27 @@ impl AptosDBBackend {
     }
 }
 
+/// Selects the window used for the election at target_round out of events ordered from the
+/// newest, returns it together with the highest version in it.
+pub fn select_window(
+    events: &[VersionedNewBlockEvent],
+    target_epoch: u64,
+    target_round: Round,
+    window_size: usize,
+) -> (Vec<NewBlockEvent>, u64) {
+    let mut max_version = 0;
+    let mut result = vec![];
+    for event in events {
+        if (event.event.epoch(), event.event.round()) <= (target_epoch, target_round)
+            && result.len() < window_size
+        {
+            max_version = std::cmp::max(max_version, event.version);
+            result.push(event.event.clone());
+        }
+    }
+    (result, max_version)
+}
+
 impl MetadataBackend for AptosDBBackend {
     // assume the target_round only increases
     fn get_block_metadata(
@@ -514,15 +530,47 @@ impl ProposerAndVoterHeuristic {
             ),
         }
     }
-}
 
-impl ReputationHeuristic for ProposerAndVoterHeuristic {
-    fn get_weights(
+    /// Creates the heuristic described by the on-chain config, returns it together with the
+    /// number of NewBlockEvents it looks at.
+    pub fn from_config(
+        author: Author,
+        num_proposers: usize,
+        leader_reputation_type: &LeaderReputationType,
+    ) -> (Self, usize) {
+        let proposer_and_voter_config = match leader_reputation_type {
+            LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
+            | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => {
+                proposer_and_voter_config
+            },
+        };
+        let proposer_window_size =
+            num_proposers * proposer_and_voter_config.proposer_window_num_validators_multiplier;
+        let voter_window_size =
+            num_proposers * proposer_and_voter_config.voter_window_num_validators_multiplier;
+        (
+            Self::new(
+                author,
+                proposer_and_voter_config.active_weight,
+                proposer_and_voter_config.inactive_weight,
+                proposer_and_voter_config.failed_weight,
+                proposer_and_voter_config.failure_threshold_percent,
+                voter_window_size,
+                proposer_window_size,
+                leader_reputation_type.use_reputation_window_from_stale_end(),
+            ),
+            max(proposer_window_size, voter_window_size),
+        )
+    }
+
+    /// Return the reputation of all candidates based on the history, together with the
+    /// metrics it was derived from.
+    pub fn get_candidate_reputations(
         &self,
         epoch: u64,
         epoch_to_candidates: &HashMap<u64, Vec<Author>>,
         history: &[NewBlockEvent],
-    ) -> Vec<u64> {
+    ) -> Vec<CandidateReputation> {
         assert!(epoch_to_candidates.contains_key(&epoch));
 
         let (votes, proposals, failed_proposals) =
@@ -536,20 +584,96 @@ impl ReputationHeuristic for ProposerAndVoterHeuristic {
                 let cur_proposals = *proposals.get(author).unwrap_or(&0);
                 let cur_failed_proposals = *failed_proposals.get(author).unwrap_or(&0);
 
-                if cur_failed_proposals * 100
+                let status = if cur_failed_proposals * 100
                     > (cur_proposals + cur_failed_proposals) * self.failure_threshold_percent
                 {
-                    self.failed_weight
+                    ReputationStatus::Failed
                 } else if cur_proposals > 0 || cur_votes > 0 {
-                    self.active_weight
+                    ReputationStatus::Active
                 } else {
-                    self.inactive_weight
+                    ReputationStatus::Inactive
+                };
+                CandidateReputation {
+                    author: *author,
+                    votes: cur_votes,
+                    proposals: cur_proposals,
+                    failed_proposals: cur_failed_proposals,
+                    status,
+                    weight: match status {
+                        ReputationStatus::Failed => self.failed_weight,
+                        ReputationStatus::Active => self.active_weight,
+                        ReputationStatus::Inactive => self.inactive_weight,
+                    },
                 }
             })
             .collect()
     }
 }
 
+impl ReputationHeuristic for ProposerAndVoterHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        self.get_candidate_reputations(epoch, epoch_to_candidates, history)
+            .into_iter()
+            .map(|reputation| reputation.weight)
+            .collect()
+    }
+}
+
+/// Which of the ProposerAndVoterHeuristic weights a candidate got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum ReputationStatus {
+    /// Failure rate of its proposals is above the threshold
+    Failed,
+    /// Proposed or voted within the windows
+    Active,
+    /// Neither proposed nor voted within the windows
+    Inactive,
+}
+
+/// Reputation of a single candidate, and the metrics within the windows it is based on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CandidateReputation {
+    pub author: Author,
+    pub votes: u32,
+    pub proposals: u32,
+    pub failed_proposals: u32,
+    pub status: ReputationStatus,
+    pub weight: u64,
+}
+
+/// Multiplies reputation weights by voting power, the result is what the proposer is chosen by.
+pub fn stake_weights(weights: &[u64], voting_powers: &[u64]) -> Vec<u128> {
+    weights
+        .iter()
+        .zip(voting_powers.iter())
+        .map(|(w, vp)| *w as u128 * *vp as u128)
+        .collect()
+}
+
+/// Seed of the weighted random proposer choice for the given round.
+pub fn election_seed(
+    use_root_hash: bool,
+    root_hash: HashValue,
+    epoch: u64,
+    round: Round,
+) -> Vec<u8> {
+    if use_root_hash {
+        [
+            root_hash.to_vec(),
+            epoch.to_le_bytes().to_vec(),
+            round.to_le_bytes().to_vec(),
+        ]
+        .concat()
+    } else {
+        [epoch.to_le_bytes().to_vec(), round.to_le_bytes().to_vec()].concat()
+    }
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
 pub struct LeaderReputation {
@@ -692,35 +816,14 @@ impl ProposerElection for LeaderReputation {
         let (sliding_window, root_hash) = self.backend.get_block_metadata(self.epoch, target_round);
         let voting_power_participation_ratio =
             self.compute_chain_health_and_add_metrics(&sliding_window, round);
-        let mut weights =
+        let weights =
             self.heuristic
                 .get_weights(self.epoch, &self.epoch_to_proposers, &sliding_window);
         let proposers = &self.epoch_to_proposers[&self.epoch];
         assert_eq!(weights.len(), proposers.len());
 
-        // Multiply weights by voting power:
-        let stake_weights: Vec<u128> = weights
-            .iter_mut()
-            .enumerate()
-            .map(|(i, w)| *w as u128 * self.voting_powers[i] as u128)
-            .collect();
-
-        let state = if self.use_root_hash {
-            [
-                root_hash.to_vec(),
-                self.epoch.to_le_bytes().to_vec(),
-                round.to_le_bytes().to_vec(),
-            ]
-            .concat()
-        } else {
-            [
-                self.epoch.to_le_bytes().to_vec(),
-                round.to_le_bytes().to_vec(),
-            ]
-            .concat()
-        };
-
-        let chosen_index = choose_index(stake_weights, state);
+        let state = election_seed(self.use_root_hash, root_hash, self.epoch, round);
+        let chosen_index = choose_index(stake_weights(&weights, &self.voting_powers), state);
         (proposers[chosen_index], voting_power_participation_ratio)
     }
 
@@ -735,6 +838,18 @@ impl ProposerElection for LeaderReputation {
     }
 }
 
+/// First epoch whose NewBlockEvents are part of the history used for elections in the given epoch.
+pub fn first_epoch_to_consider(epoch: u64, use_history_from_previous_epoch_max_count: u32) -> u64 {
+    // Genesis is epoch=0
+    // First block (after genesis) is epoch=1, and is the only block in that epoch.
+    // It has no votes, so we skip it unless we are in epoch 1, as otherwise it will
+    // skew leader elections for exclude_round number of rounds.
+    max(
+        if epoch == 1 { 1 } else { 2 },
+        epoch.saturating_sub(use_history_from_previous_epoch_max_count as u64),
+    )
+}
+
 pub(crate) fn extract_epoch_to_proposers_impl(
     next_epoch_states_and_cur_epoch_rounds: &[(&EpochState, u64)],
     epoch: u64,
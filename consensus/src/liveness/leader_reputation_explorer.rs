@@ -0,0 +1,307 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays the LeaderReputation proposer election of an epoch from its committed
+//! NewBlockEvents, and explains the weights every candidate had in each round.
+//!
+//! Elections are recomputed with the same heuristic, history window, stake weighting and seed
+//! as `LeaderReputation`, so the elected proposers match the ones validators computed, as long
+//! as the given on-chain config is the one the epoch ran with.
+
+pub use crate::liveness::leader_reputation::{ReputationStatus, VersionedNewBlockEvent};
+use crate::liveness::{
+    leader_reputation::{
+        election_seed, first_epoch_to_consider, select_window, stake_weights,
+        ProposerAndVoterHeuristic,
+    },
+    proposer_election::choose_index,
+};
+use anyhow::{bail, ensure, Result};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    on_chain_config::{LeaderReputationType, OnChainConsensusConfig, ProposerElectionType},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What happened in a round, as recorded by the NewBlockEvents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum RoundOutcome {
+    /// A block proposed in the round was committed
+    Committed,
+    /// The proposer of the round failed, a NIL block or a later block recorded it
+    Failed,
+    /// The round failed, but more rounds failed in a row than are recorded on chain
+    Unknown,
+}
+
+/// Weight of a single candidate in a round, and the metrics within the window it comes from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CandidateElection {
+    /// Candidate
+    pub author: Author,
+    /// Voting power, 1 if the election isn't weighted by voting power
+    pub voting_power: u64,
+    /// Votes within the voter window
+    pub votes: u32,
+    /// Committed proposals within the proposer window
+    pub proposals: u32,
+    /// Failed proposals within the proposer window
+    pub failed_proposals: u32,
+    /// Which of the reputation weights the candidate got
+    pub status: ReputationStatus,
+    /// Reputation weight
+    pub reputation_weight: u64,
+    /// Reputation weight multiplied by voting power
+    pub stake_weight: u128,
+    /// Probability of being elected in the round
+    pub probability: f64,
+}
+
+/// Recomputed election of a single round.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RoundElection {
+    /// Epoch
+    pub epoch: u64,
+    /// Round
+    pub round: Round,
+    /// Number of NewBlockEvents in the history window
+    pub window_len: usize,
+    /// Oldest (epoch, round) in the history window
+    pub window_start: Option<(u64, Round)>,
+    /// Newest (epoch, round) in the history window
+    pub window_end: Option<(u64, Round)>,
+    /// Proposer elected by leader reputation
+    pub elected: Author,
+    /// Proposer recorded on chain, if known
+    pub recorded_proposer: Option<Author>,
+    /// What happened in the round
+    pub outcome: RoundOutcome,
+    /// All candidates, in validator index order
+    pub candidates: Vec<CandidateElection>,
+}
+
+impl RoundElection {
+    /// Whether the recorded proposer differs from the recomputed one, which means the replay
+    /// doesn't match what validators computed.
+    pub fn is_mismatch(&self) -> bool {
+        self.recorded_proposer
+            .map_or(false, |proposer| proposer != self.elected)
+    }
+}
+
+/// Recomputes LeaderReputation elections of a single epoch.
+pub struct LeaderReputationExplorer {
+    epoch: u64,
+    epoch_to_proposers: HashMap<u64, Vec<Author>>,
+    voting_powers: Vec<u64>,
+    heuristic: ProposerAndVoterHeuristic,
+    window_size: usize,
+    exclude_round: u64,
+    use_root_hash: bool,
+}
+
+impl LeaderReputationExplorer {
+    /// Creates the explorer for the epoch, the same way validators create their proposer
+    /// election. validators holds the validators of the epoch with their voting power, in
+    /// validator index order. previous_epoch_proposers holds the ordered validators of earlier
+    /// epochs, only the ones the config looks back to are used.
+    pub fn new(
+        epoch: u64,
+        validators: &[(Author, u64)],
+        onchain_config: &OnChainConsensusConfig,
+        previous_epoch_proposers: &HashMap<u64, Vec<Author>>,
+    ) -> Result<Self> {
+        let leader_reputation_type = match onchain_config.proposer_election_type() {
+            ProposerElectionType::LeaderReputation(leader_reputation_type) => {
+                leader_reputation_type
+            },
+            other => bail!("Proposer election is not leader reputation: {:?}", other),
+        };
+        let (weight_by_voting_power, use_history_from_previous_epoch_max_count) =
+            match leader_reputation_type {
+                LeaderReputationType::ProposerAndVoter(proposer_and_voter_config)
+                | LeaderReputationType::ProposerAndVoterV2(proposer_and_voter_config) => (
+                    proposer_and_voter_config.weight_by_voting_power,
+                    proposer_and_voter_config.use_history_from_previous_epoch_max_count,
+                ),
+            };
+
+        let proposers: Vec<_> = validators.iter().map(|(author, _)| *author).collect();
+        // The author is only used for metrics
+        let (heuristic, window_size) = ProposerAndVoterHeuristic::from_config(
+            AccountAddress::ZERO,
+            proposers.len(),
+            leader_reputation_type,
+        );
+        let voting_powers = if weight_by_voting_power {
+            validators
+                .iter()
+                .map(|(_, voting_power)| *voting_power)
+                .collect()
+        } else {
+            vec![1; proposers.len()]
+        };
+
+        let first_epoch = first_epoch_to_consider(epoch, use_history_from_previous_epoch_max_count);
+        let mut epoch_to_proposers: HashMap<_, _> = previous_epoch_proposers
+            .iter()
+            .filter(|(previous, _)| (first_epoch..epoch).contains(*previous))
+            .map(|(previous, proposers)| (*previous, proposers.clone()))
+            .collect();
+        epoch_to_proposers.insert(epoch, proposers);
+
+        Ok(Self {
+            epoch,
+            epoch_to_proposers,
+            voting_powers,
+            heuristic,
+            window_size,
+            exclude_round: onchain_config.leader_reputation_exclude_round(),
+            use_root_hash: leader_reputation_type.use_root_hash_for_seed(),
+        })
+    }
+
+    /// Whether elections are seeded with the accumulator root hash, in which case replaying
+    /// needs the root hash at the version of every NewBlockEvent.
+    pub fn uses_root_hash(&self) -> bool {
+        self.use_root_hash
+    }
+
+    /// Recomputes the election of a round. history holds the NewBlockEvents ordered from the
+    /// newest, and needs to go back at least to the window of the round.
+    pub fn explain_round(
+        &self,
+        round: Round,
+        history: &[VersionedNewBlockEvent],
+        root_hashes: &HashMap<u64, HashValue>,
+    ) -> Result<RoundElection> {
+        let target_round = round.saturating_sub(self.exclude_round);
+        // skip the newer events up front, instead of filtering them out of the whole history
+        let start = history
+            .partition_point(|e| (e.event.epoch(), e.event.round()) > (self.epoch, target_round));
+        let end = std::cmp::min(history.len(), start + self.window_size);
+        let (window, max_version) = select_window(
+            &history[start..end],
+            self.epoch,
+            target_round,
+            self.window_size,
+        );
+        let root_hash = if self.use_root_hash {
+            match root_hashes.get(&max_version) {
+                Some(root_hash) => *root_hash,
+                None => bail!("Missing accumulator root hash for version {}", max_version),
+            }
+        } else {
+            HashValue::zero()
+        };
+
+        let reputations =
+            self.heuristic
+                .get_candidate_reputations(self.epoch, &self.epoch_to_proposers, &window);
+        let weights: Vec<_> = reputations.iter().map(|r| r.weight).collect();
+        let stake_weights = stake_weights(&weights, &self.voting_powers);
+        let total_stake_weight: u128 = stake_weights.iter().sum();
+        let chosen_index = choose_index(
+            stake_weights.clone(),
+            election_seed(self.use_root_hash, root_hash, self.epoch, round),
+        );
+
+        let candidates = reputations
+            .into_iter()
+            .zip(self.voting_powers.iter())
+            .zip(stake_weights)
+            .map(
+                |((reputation, voting_power), stake_weight)| CandidateElection {
+                    author: reputation.author,
+                    voting_power: *voting_power,
+                    votes: reputation.votes,
+                    proposals: reputation.proposals,
+                    failed_proposals: reputation.failed_proposals,
+                    status: reputation.status,
+                    reputation_weight: reputation.weight,
+                    stake_weight,
+                    probability: stake_weight as f64 / total_stake_weight as f64,
+                },
+            )
+            .collect();
+
+        Ok(RoundElection {
+            epoch: self.epoch,
+            round,
+            window_len: window.len(),
+            window_start: window.last().map(|e| (e.epoch(), e.round())),
+            window_end: window.first().map(|e| (e.epoch(), e.round())),
+            elected: self.epoch_to_proposers[&self.epoch][chosen_index],
+            recorded_proposer: None,
+            outcome: RoundOutcome::Unknown,
+            candidates,
+        })
+    }
+
+    /// Recomputes the elections of every round of the epoch up to its last committed block.
+    /// blocks holds the NewBlockEvents in commit order, including the ones of earlier epochs
+    /// the history window reaches into.
+    pub fn explain_epoch(
+        &self,
+        blocks: &[VersionedNewBlockEvent],
+        root_hashes: &HashMap<u64, HashValue>,
+    ) -> Result<Vec<RoundElection>> {
+        let history: Vec<_> = blocks.iter().rev().cloned().collect();
+        let proposers = &self.epoch_to_proposers[&self.epoch];
+
+        let mut result = vec![];
+        let mut previous_round = 0;
+        for block in blocks.iter().filter(|b| b.event.epoch() == self.epoch) {
+            let event = &block.event;
+            ensure!(
+                event.round() > previous_round,
+                "NewBlockEvents of epoch {} are not ordered by round",
+                self.epoch
+            );
+            for round in previous_round + 1..=event.round() {
+                let mut election = self.explain_round(round, &history, root_hashes)?;
+                let (outcome, recorded_proposer) = Self::recorded_outcome(event, round, proposers);
+                election.outcome = outcome;
+                election.recorded_proposer = recorded_proposer;
+                result.push(election);
+            }
+            previous_round = event.round();
+        }
+        Ok(result)
+    }
+
+    fn recorded_outcome(
+        event: &NewBlockEvent,
+        round: Round,
+        proposers: &[Author],
+    ) -> (RoundOutcome, Option<Author>) {
+        let is_nil = event.proposer() == AccountAddress::ZERO;
+        if round == event.round() && !is_nil {
+            return (RoundOutcome::Committed, Some(event.proposer()));
+        }
+        // failed proposers are recorded for the rounds right before the block, including
+        // the round of a NIL block
+        let last_failed_round = if is_nil {
+            event.round()
+        } else {
+            event.round() - 1
+        };
+        let failed = event.failed_proposer_indices();
+        match usize::try_from(last_failed_round - round)
+            .ok()
+            .filter(|offset| *offset < failed.len())
+        {
+            Some(offset) => (
+                RoundOutcome::Failed,
+                proposers
+                    .get(failed[failed.len() - 1 - offset] as usize)
+                    .cloned(),
+            ),
+            None => (RoundOutcome::Unknown, None),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    leader_reputation::{
+        select_window, LeaderReputation, MetadataBackend, ProposerAndVoterHeuristic,
+        VersionedNewBlockEvent,
+    },
+    leader_reputation_explorer::{LeaderReputationExplorer, RoundOutcome},
+    proposer_election::ProposerElection,
+};
+use aptos_bitvec::BitVec;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    on_chain_config::{OnChainConsensusConfig, ProposerElectionType},
+};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+const EPOCH: u64 = 2;
+
+struct MockBackend {
+    history: Vec<VersionedNewBlockEvent>,
+    root_hashes: HashMap<u64, HashValue>,
+    window_size: usize,
+}
+
+impl MetadataBackend for MockBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let (window, max_version) =
+            select_window(&self.history, target_epoch, target_round, self.window_size);
+        (window, self.root_hashes[&max_version])
+    }
+}
+
+fn validators(authors: &[Author]) -> Vec<(Author, u64)> {
+    authors
+        .iter()
+        .enumerate()
+        .map(|(i, author)| (*author, i as u64 + 1))
+        .collect()
+}
+
+fn block(
+    version: u64,
+    round: Round,
+    proposer: AccountAddress,
+    voters: &[usize],
+    failed_proposer_indices: Vec<u64>,
+) -> VersionedNewBlockEvent {
+    let mut votes = BitVec::with_num_bits(4);
+    for voter in voters {
+        votes.set(*voter as u16);
+    }
+    VersionedNewBlockEvent {
+        event: NewBlockEvent::new(
+            AccountAddress::random(),
+            EPOCH,
+            round,
+            round,
+            votes.into(),
+            proposer,
+            failed_proposer_indices,
+            round,
+        ),
+        version,
+    }
+}
+
+#[test]
+fn test_explorer_matches_leader_reputation() {
+    let authors: Vec<Author> = (0..4).map(|_| AccountAddress::random()).sorted().collect();
+    let config = OnChainConsensusConfig::default();
+
+    // validator 3 never votes, and fails every round it would propose in
+    let mut blocks = vec![];
+    let mut failed = vec![];
+    for round in 1..=300 {
+        if round % 5 == 0 {
+            failed.push(3);
+            continue;
+        }
+        blocks.push(block(
+            round * 10,
+            round,
+            authors[(round % 3) as usize],
+            &[0, 1, 2],
+            std::mem::take(&mut failed),
+        ));
+    }
+    let root_hashes: HashMap<_, _> = (0..=300)
+        .map(|version: u64| (version * 10, HashValue::sha3_256_of(&version.to_le_bytes())))
+        .collect();
+
+    let leader_reputation_type = match config.proposer_election_type() {
+        ProposerElectionType::LeaderReputation(leader_reputation_type) => leader_reputation_type,
+        _ => unreachable!(),
+    };
+    let (heuristic, window_size) =
+        ProposerAndVoterHeuristic::from_config(authors[0], authors.len(), leader_reputation_type);
+    let leader_reputation = LeaderReputation::new(
+        EPOCH,
+        HashMap::from([(EPOCH, authors.clone())]),
+        (1..=4).collect(),
+        Box::new(MockBackend {
+            history: blocks.iter().rev().cloned().collect(),
+            root_hashes: root_hashes.clone(),
+            window_size,
+        }),
+        Box::new(heuristic),
+        config.leader_reputation_exclude_round(),
+        leader_reputation_type.use_root_hash_for_seed(),
+        30,
+    );
+
+    let explorer =
+        LeaderReputationExplorer::new(EPOCH, &validators(&authors), &config, &HashMap::new())
+            .unwrap();
+    let elections = explorer.explain_epoch(&blocks, &root_hashes).unwrap();
+    assert_eq!(elections.len(), 299);
+    for election in &elections {
+        assert_eq!(
+            election.elected,
+            leader_reputation.get_valid_proposer(election.round),
+            "round {}",
+            election.round
+        );
+        let probability: f64 = election.candidates.iter().map(|c| c.probability).sum();
+        assert!((probability - 1.0).abs() < 1e-9);
+    }
+
+    let last = elections.last().unwrap();
+    assert_eq!(last.window_len, window_size);
+    assert_eq!(last.window_end, Some((EPOCH, 299 - 40)));
+    // the window spans rounds 210 to 259, validator 3 failed in all 10 rounds it was elected
+    assert_eq!(last.candidates[3].proposals, 0);
+    assert_eq!(last.candidates[3].failed_proposals, 10);
+    assert_eq!(last.candidates[3].reputation_weight, 1);
+    assert_eq!(last.candidates[0].reputation_weight, 1000);
+
+    assert_eq!(elections[4].outcome, RoundOutcome::Failed);
+    assert_eq!(elections[4].recorded_proposer, Some(authors[3]));
+    assert_eq!(elections[5].outcome, RoundOutcome::Committed);
+    assert_eq!(elections[5].recorded_proposer, Some(authors[0]));
+}
+
+#[test]
+fn test_explorer_round_outcomes() {
+    let authors: Vec<Author> = (0..4).map(|_| AccountAddress::random()).sorted().collect();
+    let config = OnChainConsensusConfig::default();
+
+    let blocks = vec![
+        block(10, 1, authors[0], &[], vec![]),
+        // NIL block records the failure of its own round
+        block(20, 2, AccountAddress::ZERO, &[0, 1], vec![1]),
+        // only the last failure out of rounds 3 and 4 is recorded
+        block(30, 5, authors[2], &[0, 1], vec![3]),
+    ];
+    let root_hashes = HashMap::from([
+        (0, HashValue::zero()),
+        (10, HashValue::random()),
+        (20, HashValue::random()),
+        (30, HashValue::random()),
+    ]);
+
+    let explorer =
+        LeaderReputationExplorer::new(EPOCH, &validators(&authors), &config, &HashMap::new())
+            .unwrap();
+    let outcomes: Vec<_> = explorer
+        .explain_epoch(&blocks, &root_hashes)
+        .unwrap()
+        .into_iter()
+        .map(|election| (election.round, election.outcome, election.recorded_proposer))
+        .collect();
+    assert_eq!(outcomes, vec![
+        (1, RoundOutcome::Committed, Some(authors[0])),
+        (2, RoundOutcome::Failed, Some(authors[1])),
+        (3, RoundOutcome::Unknown, None),
+        (4, RoundOutcome::Failed, Some(authors[3])),
+        (5, RoundOutcome::Committed, Some(authors[2])),
+    ]);
+}
@@ -5,9 +5,10 @@
 use crate::{
     block_storage::tracing::{observe_block, BlockStage},
     counters,
+    dag::DAGNetworkSender,
     logging::LogEvent,
     monitor,
-    network_interface::{ConsensusMsg, ConsensusNetworkClient},
+    network_interface::{ConsensusMsg, ConsensusNetworkClient, RPC},
     quorum_store::types::{Batch, BatchMsg, BatchRequest},
 };
 use anyhow::{anyhow, ensure};
@@ -60,6 +61,16 @@ pub struct IncomingBatchRetrievalRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+/// DAG rpc requests (nodes to vote on, certified nodes to add and fetch requests), the response
+/// is the serialized ack.
+#[derive(Debug)]
+pub struct IncomingDAGRequest {
+    pub req: ConsensusMsg,
+    pub sender: Author,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 #[derive(Debug)]
 pub enum IncomingRpcRequest {
     BlockRetrieval(IncomingBlockRetrievalRequest),
@@ -83,6 +94,7 @@ pub struct NetworkReceivers {
         (AccountAddress, ConsensusMsg),
     >,
     pub rpc_rx: aptos_channel::Receiver<AccountAddress, (AccountAddress, IncomingRpcRequest)>,
+    pub dag_rpc_rx: aptos_channel::Receiver<AccountAddress, (AccountAddress, IncomingDAGRequest)>,
 }
 
 #[async_trait::async_trait]
@@ -116,7 +128,8 @@ pub struct NetworkSender {
     consensus_network_client: ConsensusNetworkClient<NetworkClient<ConsensusMsg>>,
     // Self sender and self receivers provide a shortcut for sending the messages to itself.
     // (self sending is not supported by the networking API).
-    // Note that we do not support self rpc requests as it might cause infinite recursive calls.
+    // Self rpc requests are only supported for DAG messages, other rpcs might cause infinite
+    // recursive calls.
     self_sender: aptos_channels::Sender<Event<ConsensusMsg>>,
     validators: ValidatorVerifier,
 }
@@ -380,6 +393,39 @@ impl QuorumStoreSender for NetworkSender {
     }
 }
 
+#[async_trait::async_trait]
+impl DAGNetworkSender for NetworkSender {
+    async fn send_rpc(
+        &self,
+        receiver: Author,
+        message: ConsensusMsg,
+        timeout: Duration,
+    ) -> anyhow::Result<ConsensusMsg> {
+        fail_point!("consensus::send::any", |_| {
+            Err(anyhow::anyhow!("Injected error in send_rpc"))
+        });
+        counters::CONSENSUS_SENT_MSGS
+            .with_label_values(&[message.name()])
+            .inc();
+        if receiver != self.author {
+            return Ok(self
+                .consensus_network_client
+                .send_rpc(receiver, message, timeout)
+                .await?);
+        }
+        // Directly send the request to ourself without going through network, the protocol
+        // only matters for encoding the response.
+        let protocol = RPC[0];
+        let (tx, rx) = oneshot::channel();
+        let mut self_sender = self.self_sender.clone();
+        self_sender
+            .send(Event::RpcRequest(self.author, message, protocol, tx))
+            .await?;
+        let response_bytes = tokio::time::timeout(timeout, rx).await???;
+        protocol.from_bytes(&response_bytes)
+    }
+}
+
 pub struct NetworkTask {
     consensus_messages_tx: aptos_channel::Sender<
         (AccountAddress, Discriminant<ConsensusMsg>),
@@ -394,6 +440,7 @@ pub struct NetworkTask {
         (AccountAddress, ConsensusMsg),
     >,
     rpc_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, IncomingRpcRequest)>,
+    dag_rpc_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, IncomingDAGRequest)>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
 }
 
@@ -421,6 +468,9 @@ impl NetworkTask {
         );
         let (rpc_tx, rpc_rx) =
             aptos_channel::new(QueueStyle::LIFO, 1, Some(&counters::RPC_CHANNEL_MSGS));
+        // DAG rpcs are retried by the reliable broadcast, a peer has several of them in flight
+        let (dag_rpc_tx, dag_rpc_rx) =
+            aptos_channel::new(QueueStyle::FIFO, 100, Some(&counters::DAG_RPC_CHANNEL_MSGS));
 
         // Verify the network events have been constructed correctly
         let network_and_events = network_service_events.into_network_and_events();
@@ -441,6 +491,7 @@ impl NetworkTask {
                 buffer_manager_messages_tx,
                 quorum_store_messages_tx,
                 rpc_tx,
+                dag_rpc_tx,
                 all_events,
             },
             NetworkReceivers {
@@ -448,6 +499,7 @@ impl NetworkTask {
                 buffer_manager_messages,
                 quorum_store_messages,
                 rpc_rx,
+                dag_rpc_rx,
             },
         )
     }
@@ -556,6 +608,24 @@ impl NetworkTask {
                             warn!(error = ?e, "aptos channel closed");
                         }
                     },
+                    dag_msg @ (ConsensusMsg::DAGNodeMsg(_)
+                    | ConsensusMsg::DAGCertifiedNodeMsg(_)
+                    | ConsensusMsg::DAGFetchRequestMsg(_)
+                    | ConsensusMsg::DAGSyncRequestMsg(_)) => {
+                        counters::CONSENSUS_RECEIVED_MSGS
+                            .with_label_values(&[dag_msg.name()])
+                            .inc();
+                        let req_with_callback = IncomingDAGRequest {
+                            req: dag_msg,
+                            sender: peer_id,
+                            protocol,
+                            response_sender: callback,
+                        };
+                        if let Err(e) = self.dag_rpc_tx.push(peer_id, (peer_id, req_with_callback))
+                        {
+                            warn!(error = ?e, "aptos channel closed");
+                        }
+                    },
                     _ => {
                         warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                         continue;
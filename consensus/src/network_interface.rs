@@ -4,7 +4,13 @@
 
 //! Interface between Consensus and Network layers.
 
-use crate::quorum_store::types::{Batch, BatchMsg, BatchRequest};
+use crate::{
+    dag::{
+        CertifiedAck, CertifiedNode, FetchRequest, FetchResponse, Node, SyncRequest, SyncResponse,
+        Vote,
+    },
+    quorum_store::types::{Batch, BatchMsg, BatchRequest},
+};
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
@@ -61,6 +67,23 @@ pub enum ConsensusMsg {
     SignedBatchInfo(Box<SignedBatchInfoMsg>),
     /// Quorum Store: Broadcast a certified proof of store (a digest that received 2f+1 votes).
     ProofOfStoreMsg(Box<ProofOfStoreMsg>),
+    /// DAG: RPC carrying a node of the sender, the receiver responds with its vote.
+    DAGNodeMsg(Box<Node>),
+    /// DAG: Vote on a node, response to DAGNodeMsg.
+    DAGVoteMsg(Box<Vote>),
+    /// DAG: RPC carrying a node with 2f+1 votes, to be added to the receiver's DAG.
+    DAGCertifiedNodeMsg(Box<CertifiedNode>),
+    /// DAG: Response to DAGCertifiedNodeMsg once the node is added.
+    DAGCertifiedAckMsg(Box<CertifiedAck>),
+    /// DAG: RPC to get certified nodes that are missing locally.
+    DAGFetchRequestMsg(Box<FetchRequest>),
+    /// DAG: Response to DAGFetchRequestMsg.
+    DAGFetchResponseMsg(Box<FetchResponse>),
+    /// DAG: RPC to get the latest commit of a validator, to state sync to it when the DAG is
+    /// too far behind to fetch the missing nodes.
+    DAGSyncRequestMsg(Box<SyncRequest>),
+    /// DAG: Response to DAGSyncRequestMsg.
+    DAGSyncResponseMsg(Box<SyncResponse>),
     #[cfg(test)]
     DAGTestMessage(Vec<u8>),
 }
@@ -85,6 +108,14 @@ impl ConsensusMsg {
             ConsensusMsg::BatchResponse(_) => "BatchResponse",
             ConsensusMsg::SignedBatchInfo(_) => "SignedBatchInfo",
             ConsensusMsg::ProofOfStoreMsg(_) => "ProofOfStoreMsg",
+            ConsensusMsg::DAGNodeMsg(_) => "DAGNodeMsg",
+            ConsensusMsg::DAGVoteMsg(_) => "DAGVoteMsg",
+            ConsensusMsg::DAGCertifiedNodeMsg(_) => "DAGCertifiedNodeMsg",
+            ConsensusMsg::DAGCertifiedAckMsg(_) => "DAGCertifiedAckMsg",
+            ConsensusMsg::DAGFetchRequestMsg(_) => "DAGFetchRequestMsg",
+            ConsensusMsg::DAGFetchResponseMsg(_) => "DAGFetchResponseMsg",
+            ConsensusMsg::DAGSyncRequestMsg(_) => "DAGSyncRequestMsg",
+            ConsensusMsg::DAGSyncResponseMsg(_) => "DAGSyncResponseMsg",
             #[cfg(test)]
             ConsensusMsg::DAGTestMessage(_) => "DAGTestMessage",
         }
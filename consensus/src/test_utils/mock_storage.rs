@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    consensusdb::ConsensusDB,
     epoch_manager::LivenessStorageData,
     persistent_liveness_storage::{
         LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata,
@@ -233,6 +234,10 @@ impl PersistentLivenessStorage for MockStorage {
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn consensus_db(&self) -> Arc<ConsensusDB> {
+        unimplemented!()
+    }
 }
 
 /// A storage that ignores any requests, used in the tests that don't care about the storage.
@@ -301,4 +306,8 @@ impl PersistentLivenessStorage for EmptyStorage {
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn consensus_db(&self) -> Arc<ConsensusDB> {
+        unimplemented!()
+    }
 }
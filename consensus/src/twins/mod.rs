@@ -3,4 +3,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
+mod simulator;
+mod simulator_test;
 mod twins_node;
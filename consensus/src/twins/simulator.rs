@@ -0,0 +1,926 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deterministic simulation of a set of `RoundManager`s over a simulated network.
+//!
+//! A run is a function of its `SimulationConfig` and of its faults only: events are processed one
+//! at a time in simulated time, latencies and random faults are drawn from the seed, and block
+//! retrieval is served from the blocks known to the other nodes so that the random peer selection
+//! of the block retriever doesn't leak into the run. Safety and liveness are checked after every
+//! event, and the faults of a failing run can be shrunk to a minimal list that still fails.
+//!
+//! Only the `RoundManager`s are simulated, within a single epoch: the `EpochManager`s (i.e.,
+//! epoch changes, message verification and routing, and the epoch retrieval between nodes) are
+//! not covered.
+
+use crate::{
+    block_storage::{BlockReader, BlockStore},
+    error::QuorumStoreError,
+    experimental::buffer_manager::OrderedBlocks,
+    liveness::{
+        proposal_generator::{
+            ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
+        },
+        rotating_proposer_election::RotatingProposer,
+        round_state::{ExponentialTimeInterval, RoundState},
+    },
+    metrics_safety_rules::MetricsSafetyRules,
+    network::{IncomingBlockRetrievalRequest, NetworkSender},
+    network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
+    payload_manager::PayloadManager,
+    round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
+    state_replication::PayloadClient,
+    test_utils::{MockStateComputer, MockStorage},
+    util::{mock_time_service::SimulatedTimeService, time_service::TimeService},
+};
+use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
+use aptos_config::{
+    config::ConsensusConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_consensus_types::{
+    block::Block,
+    common::{Author, Payload, PayloadFilter, Round},
+    proposal_msg::ProposalMsg,
+};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_network::{
+    application::{interface::NetworkClient, storage::PeersAndMetadata},
+    peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
+    protocols::{
+        network,
+        network::{Event, NewNetworkSender, SerializedRequest},
+        rpc::OutboundRpcRequest,
+        wire::handshake::v1::ProtocolIdSet,
+    },
+    transport::ConnectionMetadata,
+    ProtocolId,
+};
+use aptos_safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use aptos_secure_storage::{InMemoryStorage, Storage};
+use aptos_types::{
+    account_address::AccountAddress,
+    epoch_state::EpochState,
+    ledger_info::LedgerInfo,
+    on_chain_config::{OnChainConsensusConfig, ValidatorSet},
+    transaction::SignedTransaction,
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+    waypoint::Waypoint,
+    PeerId,
+};
+use futures::{channel::mpsc, future::BoxFuture, Future, FutureExt, StreamExt};
+use maplit::hashmap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    iter::FromIterator,
+    sync::Arc,
+    time::Duration,
+};
+
+/// The genesis block has timestamp 0, the first proposals need a later one
+const START_TIME: Duration = Duration::from_secs(1);
+
+/// Misbehaviors of a Byzantine node, applied to the messages it sends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByzantineBehavior {
+    /// Sends a conflicting proposal to half of the validators in the rounds it leads
+    Equivocate,
+    /// Never sends its votes to the other validators
+    WithholdVotes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The n-th message sent over the network is dropped
+    Drop(u64),
+    /// The n-th message sent over the network is delivered later by the given duration
+    Delay(u64, Duration),
+    /// The nodes of the group can't communicate with the other nodes during [from, to)
+    Partition {
+        group: Vec<usize>,
+        from: Duration,
+        to: Duration,
+    },
+    /// The node misbehaves during the whole run
+    Byzantine(usize, ByzantineBehavior),
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub num_nodes: usize,
+    pub seed: u64,
+    /// Simulated time after which the run stops
+    pub duration: Duration,
+    /// Random faults are only injected before the global stabilization time, after which the
+    /// honest nodes are expected to keep ordering blocks
+    pub gst: Duration,
+    pub min_latency: Duration,
+    pub max_latency: Duration,
+    /// Percentage of the messages dropped before gst
+    pub drop_percent: u32,
+    /// Percentage of the messages delayed by up to `max_delay` before gst, which reorders them
+    pub delay_percent: u32,
+    pub max_delay: Duration,
+    pub round_timeout: Duration,
+    /// Longest time after gst without the honest nodes ordering a new block
+    pub liveness_timeout: Duration,
+    /// Faults injected on top of the random ones, e.g. partitions and Byzantine nodes
+    pub faults: Vec<Fault>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            num_nodes: 4,
+            seed: 0,
+            duration: Duration::from_secs(20),
+            gst: Duration::from_secs(5),
+            min_latency: Duration::from_millis(10),
+            max_latency: Duration::from_millis(100),
+            drop_percent: 0,
+            delay_percent: 0,
+            max_delay: Duration::from_millis(500),
+            round_timeout: Duration::from_secs(1),
+            liveness_timeout: Duration::from_secs(5),
+            faults: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// Two honest nodes ordered different blocks in the same round
+    ConflictingBlocks {
+        round: Round,
+        first: (usize, HashValue),
+        second: (usize, HashValue),
+    },
+    /// An honest node ordered a block with a round not higher than the one it ordered before
+    NonIncreasingRound {
+        node: usize,
+        round: Round,
+        previous: Round,
+    },
+    /// The honest nodes didn't order any new block for too long after gst
+    NoProgress { since: Duration, at: Duration },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::ConflictingBlocks {
+                round,
+                first,
+                second,
+            } => write!(
+                f,
+                "nodes {} and {} ordered {} and {} in round {}",
+                first.0, second.0, first.1, second.1, round
+            ),
+            Violation::NonIncreasingRound {
+                node,
+                round,
+                previous,
+            } => write!(
+                f,
+                "node {} ordered round {} after round {}",
+                node, round, previous
+            ),
+            Violation::NoProgress { since, at } => {
+                write!(f, "no block ordered between {:?} and {:?}", since, at)
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SimulationResult {
+    /// Every fault injected during the run, replaying them with the same config reproduces it
+    pub faults: Vec<Fault>,
+    pub violation: Option<Violation>,
+    pub steps: usize,
+    /// Blocks ordered by the honest nodes, by round
+    pub ordered: BTreeMap<Round, HashValue>,
+}
+
+enum SimEvent {
+    Deliver {
+        from: usize,
+        to: usize,
+        msg: ConsensusMsg,
+    },
+    LocalTimeout {
+        node: usize,
+        round: Round,
+    },
+}
+
+/// Always proposes empty blocks, `MockPayloadManager` generates random transactions.
+struct EmptyPayloadClient;
+
+#[async_trait::async_trait]
+impl PayloadClient for EmptyPayloadClient {
+    async fn pull_payload(
+        &self,
+        _max_poll_time: Duration,
+        _max_items: u64,
+        _max_bytes: u64,
+        _exclude: PayloadFilter,
+        _wait_callback: BoxFuture<'static, ()>,
+        _pending_ordering: bool,
+        _pending_uncommitted_blocks: usize,
+        _recent_max_fill_fraction: f32,
+    ) -> Result<Payload, QuorumStoreError> {
+        Ok(Payload::empty(false))
+    }
+}
+
+struct SimNode {
+    signer: ValidatorSigner,
+    round_manager: RoundManager,
+    /// Round of the last scheduled local timeout
+    timeout_round: Round,
+    /// Round of the last ordered block
+    ordered_round: Round,
+}
+
+/// Everything a node sends out, drained by the simulator after every event.
+struct Outbox {
+    self_rx: aptos_channels::Receiver<Event<ConsensusMsg>>,
+    network_reqs_rx: aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+    ordered_blocks_rx: mpsc::UnboundedReceiver<OrderedBlocks>,
+    state_computer: Arc<MockStateComputer>,
+    /// Direct sends drained while serving block retrieval, in order
+    stashed: VecDeque<PeerManagerRequest>,
+    _state_sync_rx: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
+}
+
+impl Outbox {
+    async fn serve_request(
+        &mut self,
+        request: PeerManagerRequest,
+        requester: usize,
+        block_stores: &[Arc<BlockStore>],
+    ) {
+        match request {
+            PeerManagerRequest::SendRpc(_, rpc) => {
+                serve_block_retrieval(rpc, requester, block_stores).await
+            },
+            request => self.stashed.push_back(request),
+        }
+    }
+}
+
+/// Serves the request from the first other node that has the block, the retrieved blocks are
+/// the same whichever node serves them. The rpc fails if no node has it.
+async fn serve_block_retrieval(
+    rpc: OutboundRpcRequest,
+    requester: usize,
+    block_stores: &[Arc<BlockStore>],
+) {
+    let OutboundRpcRequest {
+        protocol_id,
+        data,
+        res_tx,
+        ..
+    } = rpc;
+    if let Ok(ConsensusMsg::BlockRetrievalRequest(req)) = protocol_id.from_bytes(&data) {
+        let block_store = block_stores
+            .iter()
+            .enumerate()
+            .find(|(index, store)| *index != requester && store.get_block(req.block_id()).is_some())
+            .map(|(_, store)| store);
+        if let Some(block_store) = block_store {
+            if let Err(e) = block_store
+                .process_block_retrieval(IncomingBlockRetrievalRequest {
+                    req: *req,
+                    protocol: protocol_id,
+                    response_sender: res_tx,
+                })
+                .await
+            {
+                debug!(error = ?e, "[Simulator] Failed to serve block retrieval");
+            }
+        }
+    }
+}
+
+/// Drives `fut` to completion, serving the block retrieval rpcs it sends in the meantime. Rpcs
+/// are served as soon as they're sent, so that no wall clock timer is involved in the run.
+async fn with_block_retrieval<F: Future>(
+    fut: F,
+    outbox: &mut Outbox,
+    requester: usize,
+    block_stores: &[Arc<BlockStore>],
+) -> F::Output {
+    futures::pin_mut!(fut);
+    loop {
+        tokio::select! {
+            biased;
+            output = &mut fut => return output,
+            Some(request) = outbox.network_reqs_rx.next() => {
+                outbox.serve_request(request, requester, block_stores).await
+            },
+        }
+    }
+}
+
+pub struct Simulator {
+    config: SimulationConfig,
+    nodes: Vec<SimNode>,
+    outboxes: Vec<Outbox>,
+    block_stores: Vec<Arc<BlockStore>>,
+    author_to_index: HashMap<Author, usize>,
+    verifier: ValidatorVerifier,
+    time_service: SimulatedTimeService,
+    events: BTreeMap<(Duration, u64), SimEvent>,
+    next_event_id: u64,
+    latency_rng: StdRng,
+    /// Only set when random faults are injected, not when replaying
+    fault_rng: Option<StdRng>,
+    faults: Vec<Fault>,
+    drops: HashSet<u64>,
+    delays: HashMap<u64, Duration>,
+    byzantine: HashSet<(usize, ByzantineBehavior)>,
+    equivocations: HashMap<(usize, Round), ProposalMsg>,
+    sent_messages: u64,
+    ordered: BTreeMap<Round, (usize, HashValue)>,
+    last_progress: Duration,
+    steps: usize,
+}
+
+impl Simulator {
+    /// Runs the config with its faults plus random ones drawn from the seed.
+    pub fn run(config: &SimulationConfig) -> SimulationResult {
+        Self::new(config.clone(), config.faults.clone(), true).execute()
+    }
+
+    /// Runs the config with exactly the given faults.
+    pub fn replay(config: &SimulationConfig, faults: Vec<Fault>) -> SimulationResult {
+        Self::new(config.clone(), faults, false).execute()
+    }
+
+    /// Shrinks the faults of a failing run ddmin-style: removes chunks of faults as long as the
+    /// replay still violates an invariant, and halves the chunk size when none can be removed.
+    pub fn shrink(config: &SimulationConfig, mut faults: Vec<Fault>) -> Vec<Fault> {
+        let fails = |faults: &[Fault]| Self::replay(config, faults.to_vec()).violation.is_some();
+        let mut chunk_size = (faults.len() + 1) / 2;
+        while chunk_size > 0 {
+            let mut start = 0;
+            let mut removed = false;
+            while start < faults.len() {
+                let end = (start + chunk_size).min(faults.len());
+                let candidate = [&faults[..start], &faults[end..]].concat();
+                if fails(&candidate) {
+                    faults = candidate;
+                    removed = true;
+                } else {
+                    start = end;
+                }
+            }
+            if !removed {
+                chunk_size /= 2;
+            }
+        }
+        faults
+    }
+
+    fn new(config: SimulationConfig, faults: Vec<Fault>, inject_random_faults: bool) -> Self {
+        // fixed keys and addresses, so that block ids are the same across runs
+        let signers: Vec<_> = (0..config.num_nodes)
+            .map(|i| {
+                let mut address = [0; AccountAddress::LENGTH];
+                address[0] = i as u8 + 1;
+                ValidatorSigner::new(
+                    AccountAddress::new(address),
+                    ValidatorSigner::random([i as u8; 32]).private_key().clone(),
+                )
+            })
+            .collect();
+        let authors: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+        let verifier = ValidatorVerifier::new(
+            signers
+                .iter()
+                .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1))
+                .collect(),
+        );
+        let validator_set: ValidatorSet = (&verifier).into();
+        let waypoint =
+            Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some(validator_set.clone())))
+                .unwrap();
+
+        let peers_and_metadata = PeersAndMetadata::new(&[NetworkId::Validator]);
+        for author in &authors {
+            let mut conn_meta = ConnectionMetadata::mock(*author);
+            conn_meta.application_protocols = ProtocolIdSet::from_iter([
+                ProtocolId::ConsensusDirectSendBcs,
+                ProtocolId::ConsensusRpcBcs,
+            ]);
+            peers_and_metadata
+                .insert_connection_metadata(
+                    PeerNetworkId::new(NetworkId::Validator, *author),
+                    conn_meta,
+                )
+                .unwrap();
+        }
+
+        let time_service = SimulatedTimeService::new();
+        let mut nodes = vec![];
+        let mut outboxes = vec![];
+        let mut block_stores = vec![];
+        for signer in signers {
+            let author = signer.author();
+            let (initial_data, storage) = MockStorage::start_for_testing(validator_set.clone());
+            let safety_storage = PersistentSafetyStorage::initialize(
+                Storage::from(InMemoryStorage::new()),
+                author,
+                signer.private_key().clone(),
+                waypoint,
+                true,
+            );
+            let safety_rules_manager = SafetyRulesManager::new_local(safety_storage);
+
+            let (network_reqs_tx, network_reqs_rx) =
+                aptos_channel::new(QueueStyle::FIFO, 1_024, None);
+            let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let network_client = NetworkClient::new(
+                DIRECT_SEND.into(),
+                RPC.into(),
+                hashmap! {NetworkId::Validator => network::NetworkSender::new(
+                    PeerManagerRequestSender::new(network_reqs_tx),
+                    ConnectionRequestSender::new(connection_reqs_tx),
+                )},
+                peers_and_metadata.clone(),
+            );
+            let (self_sender, self_rx) = aptos_channels::new_test(1_024);
+            let network = NetworkSender::new(
+                author,
+                ConsensusNetworkClient::new(network_client),
+                self_sender,
+                verifier.clone(),
+            );
+
+            let (ordered_blocks_tx, ordered_blocks_rx) = mpsc::unbounded::<OrderedBlocks>();
+            let (state_sync_client, state_sync_rx) = mpsc::unbounded();
+            let state_computer = Arc::new(MockStateComputer::new(
+                state_sync_client,
+                ordered_blocks_tx,
+                storage.clone(),
+            ));
+            let node_time_service = Arc::new(time_service.clone());
+            let block_store = Arc::new(BlockStore::new(
+                storage.clone(),
+                initial_data,
+                state_computer.clone(),
+                10, // max pruned blocks in mem
+                node_time_service.clone(),
+                10,
+                Arc::from(PayloadManager::DirectMempool),
+            ));
+            let proposal_generator = ProposalGenerator::new(
+                author,
+                block_store.clone(),
+                Arc::new(EmptyPayloadClient),
+                node_time_service.clone(),
+                Duration::ZERO,
+                10,
+                1000,
+                10,
+                PipelineBackpressureConfig::new_no_backoff(),
+                ChainHealthBackoffConfig::new_no_backoff(),
+                false,
+            );
+            // local timeouts are scheduled by the simulator
+            let (round_timeout_sender, _) = aptos_channels::new_test(1_024);
+            let round_state = RoundState::new(
+                Box::new(ExponentialTimeInterval::fixed(config.round_timeout)),
+                node_time_service,
+                round_timeout_sender,
+            );
+            let mut safety_rules =
+                MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
+            safety_rules.perform_initialize().unwrap();
+            let (round_manager_tx, _) = aptos_channel::new(QueueStyle::LIFO, 1, None);
+
+            let round_manager = RoundManager::new(
+                EpochState {
+                    epoch: 1,
+                    verifier: verifier.clone(),
+                },
+                block_store.clone(),
+                round_state,
+                Box::new(RotatingProposer::new(authors.clone(), 1)),
+                proposal_generator,
+                Arc::new(Mutex::new(safety_rules)),
+                network,
+                storage,
+                OnChainConsensusConfig::default(),
+                round_manager_tx,
+                ConsensusConfig::default(),
+            );
+
+            nodes.push(SimNode {
+                signer,
+                round_manager,
+                timeout_round: 0,
+                ordered_round: 0,
+            });
+            outboxes.push(Outbox {
+                self_rx,
+                network_reqs_rx,
+                ordered_blocks_rx,
+                state_computer,
+                stashed: VecDeque::new(),
+                _state_sync_rx: state_sync_rx,
+            });
+            block_stores.push(block_store);
+        }
+
+        let mut drops = HashSet::new();
+        let mut delays = HashMap::new();
+        let mut byzantine = HashSet::new();
+        for fault in &faults {
+            match fault {
+                Fault::Drop(index) => {
+                    drops.insert(*index);
+                },
+                Fault::Delay(index, delay) => {
+                    delays.insert(*index, *delay);
+                },
+                Fault::Byzantine(node, behavior) => {
+                    byzantine.insert((*node, *behavior));
+                },
+                Fault::Partition { .. } => {},
+            }
+        }
+
+        Self {
+            nodes,
+            outboxes,
+            block_stores,
+            author_to_index: authors
+                .into_iter()
+                .enumerate()
+                .map(|(index, author)| (author, index))
+                .collect(),
+            verifier,
+            time_service,
+            events: BTreeMap::new(),
+            next_event_id: 0,
+            latency_rng: StdRng::seed_from_u64(config.seed),
+            fault_rng: inject_random_faults.then(|| StdRng::seed_from_u64(!config.seed)),
+            faults,
+            drops,
+            delays,
+            byzantine,
+            equivocations: HashMap::new(),
+            sent_messages: 0,
+            ordered: BTreeMap::new(),
+            last_progress: Duration::ZERO,
+            steps: 0,
+            config,
+        }
+    }
+
+    fn execute(self) -> SimulationResult {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(self.run_to_end())
+    }
+
+    async fn run_to_end(mut self) -> SimulationResult {
+        self.advance_to(START_TIME).await;
+        let mut violation = None;
+        for node in 0..self.nodes.len() {
+            let Self {
+                nodes,
+                outboxes,
+                block_stores,
+                ..
+            } = &mut self;
+            with_block_retrieval(
+                nodes[node].round_manager.init(None),
+                &mut outboxes[node],
+                node,
+                block_stores,
+            )
+            .await;
+            violation = violation.or(self.after_event(node).await);
+        }
+
+        while violation.is_none() {
+            let ((time, _), event) = match self.events.pop_first() {
+                Some(next) if next.0 .0 <= self.config.duration => next,
+                _ => break,
+            };
+            self.advance_to(time).await;
+            let node = self.process(event).await;
+            self.steps += 1;
+            violation = self.after_event(node).await;
+            if violation.is_none() {
+                violation = self.check_liveness();
+            }
+        }
+        if let Some(violation) = &violation {
+            info!(
+                "[Simulator] Invariant violated after {} steps: {}",
+                self.steps, violation
+            );
+        }
+
+        SimulationResult {
+            faults: self.faults,
+            violation,
+            steps: self.steps,
+            ordered: self
+                .ordered
+                .into_iter()
+                .map(|(round, (_, id))| (round, id))
+                .collect(),
+        }
+    }
+
+    fn now(&self) -> Duration {
+        self.time_service.get_current_timestamp()
+    }
+
+    async fn advance_to(&self, time: Duration) {
+        let now = self.now();
+        if time > now {
+            self.time_service.sleep(time - now).await;
+        }
+    }
+
+    fn schedule(&mut self, time: Duration, event: SimEvent) {
+        self.events.insert((time, self.next_event_id), event);
+        self.next_event_id += 1;
+    }
+
+    fn is_byzantine(&self, node: usize) -> bool {
+        self.byzantine
+            .iter()
+            .any(|(byzantine, _)| *byzantine == node)
+    }
+
+    /// Processes the event and returns the node that processed it.
+    async fn process(&mut self, event: SimEvent) -> usize {
+        let Self {
+            nodes,
+            outboxes,
+            block_stores,
+            verifier,
+            ..
+        } = &mut *self;
+        match event {
+            SimEvent::Deliver { from, to, msg } => {
+                let peer = nodes[from].signer.author();
+                let event = match msg {
+                    ConsensusMsg::ProposalMsg(_)
+                    | ConsensusMsg::VoteMsg(_)
+                    | ConsensusMsg::SyncInfo(_) => {
+                        UnverifiedEvent::from(msg).verify(peer, verifier, false, from == to, 10)
+                    },
+                    _ => return to,
+                };
+                let round_manager = &mut nodes[to].round_manager;
+                let result = with_block_retrieval(
+                    async {
+                        match event {
+                            Ok(VerifiedEvent::ProposalMsg(proposal_msg)) => {
+                                round_manager.process_proposal_msg(*proposal_msg).await
+                            },
+                            Ok(VerifiedEvent::VoteMsg(vote_msg)) => {
+                                round_manager.process_vote_msg(*vote_msg).await
+                            },
+                            Ok(VerifiedEvent::UnverifiedSyncInfo(sync_info)) => {
+                                round_manager.process_sync_info_msg(*sync_info, peer).await
+                            },
+                            Ok(_) => Ok(()),
+                            Err(e) => Err(e.into()),
+                        }
+                    },
+                    &mut outboxes[to],
+                    to,
+                    block_stores,
+                )
+                .await;
+                if let Err(e) = result {
+                    debug!(error = ?e, "[Simulator] Node {} failed to process a message", to);
+                }
+                to
+            },
+            SimEvent::LocalTimeout { node, round } => {
+                // it always fails, to stop the processing once the timeout vote is sent
+                let _ = with_block_retrieval(
+                    nodes[node].round_manager.process_local_timeout(round),
+                    &mut outboxes[node],
+                    node,
+                    block_stores,
+                )
+                .await;
+                let current_round = nodes[node].round_manager.round_state().current_round();
+                if current_round == round {
+                    // keep resending the timeout vote until the round changes
+                    let time = self.now() + self.config.round_timeout;
+                    self.schedule(time, SimEvent::LocalTimeout { node, round });
+                }
+                node
+            },
+        }
+    }
+
+    /// Sends out the messages of the node, records what it ordered and schedules its next local
+    /// timeout. Returns the safety violation found, if any.
+    async fn after_event(&mut self, node: usize) -> Option<Violation> {
+        while let Some(Some(event)) = self.outboxes[node].self_rx.next().now_or_never() {
+            if let Event::Message(_, msg) = event {
+                self.route(node, node, msg);
+            }
+        }
+        while let Some(Some(request)) = self.outboxes[node].network_reqs_rx.next().now_or_never() {
+            self.outboxes[node].stashed.push_back(request);
+        }
+        while let Some(request) = self.outboxes[node].stashed.pop_front() {
+            // block retrieval is only served while the requester waits for it
+            if let PeerManagerRequest::SendDirectSend(peer, message) = request {
+                match message.to_message::<ConsensusMsg>() {
+                    Ok(msg) => self.route(node, self.author_to_index[&peer], msg),
+                    Err(e) => debug!(error = ?e, "[Simulator] Undecodable message"),
+                }
+            }
+        }
+
+        let current_round = self.nodes[node].round_manager.round_state().current_round();
+        if current_round > self.nodes[node].timeout_round {
+            self.nodes[node].timeout_round = current_round;
+            let time = self.now() + self.config.round_timeout;
+            self.schedule(time, SimEvent::LocalTimeout {
+                node,
+                round: current_round,
+            });
+        }
+
+        while let Some(Some(ordered_blocks)) =
+            self.outboxes[node].ordered_blocks_rx.next().now_or_never()
+        {
+            let blocks: Vec<_> = ordered_blocks
+                .ordered_blocks
+                .iter()
+                .map(|block| (block.round(), block.id()))
+                .collect();
+            self.outboxes[node]
+                .state_computer
+                .commit_to_storage(ordered_blocks)
+                .await
+                .expect("Failed to commit ordered blocks");
+            for (round, id) in blocks {
+                if let Some(violation) = self.check_safety(node, round, id) {
+                    return Some(violation);
+                }
+            }
+        }
+        None
+    }
+
+    /// Applies the Byzantine behaviors of the sender.
+    fn route(&mut self, from: usize, to: usize, msg: ConsensusMsg) {
+        let msg = match msg {
+            ConsensusMsg::VoteMsg(_)
+                if from != to
+                    && self
+                        .byzantine
+                        .contains(&(from, ByzantineBehavior::WithholdVotes)) =>
+            {
+                return
+            },
+            ConsensusMsg::ProposalMsg(proposal_msg)
+                if to % 2 == 1
+                    && from != to
+                    && self
+                        .byzantine
+                        .contains(&(from, ByzantineBehavior::Equivocate)) =>
+            {
+                ConsensusMsg::ProposalMsg(Box::new(self.conflicting_proposal(from, &proposal_msg)))
+            },
+            msg => msg,
+        };
+        self.send(from, to, msg);
+    }
+
+    fn conflicting_proposal(&mut self, node: usize, proposal_msg: &ProposalMsg) -> ProposalMsg {
+        let proposal = proposal_msg.proposal();
+        let signer = &self.nodes[node].signer;
+        self.equivocations
+            .entry((node, proposal.round()))
+            .or_insert_with(|| {
+                let block = Block::new_proposal(
+                    Payload::empty(false),
+                    proposal.round(),
+                    proposal.timestamp_usecs() + 1,
+                    proposal.quorum_cert().clone(),
+                    signer,
+                    proposal
+                        .block_data()
+                        .failed_authors()
+                        .cloned()
+                        .unwrap_or_default(),
+                )
+                .expect("Failed to sign the conflicting proposal");
+                ProposalMsg::new(block, proposal_msg.sync_info().clone())
+            })
+            .clone()
+    }
+
+    /// Applies the network faults and schedules the delivery.
+    fn send(&mut self, from: usize, to: usize, msg: ConsensusMsg) {
+        let now = self.now();
+        if from == to {
+            self.schedule(now, SimEvent::Deliver { from, to, msg });
+            return;
+        }
+
+        let index = self.sent_messages;
+        self.sent_messages += 1;
+        let mut latency = Duration::from_millis(self.latency_rng.gen_range(
+            self.config.min_latency.as_millis() as u64,
+            self.config.max_latency.as_millis() as u64 + 1,
+        ));
+        if self.is_partitioned(from, to, now) {
+            return;
+        }
+        if let Some(rng) = self.fault_rng.as_mut().filter(|_| now < self.config.gst) {
+            if rng.gen_range(0, 100) < self.config.drop_percent {
+                self.drops.insert(index);
+                self.faults.push(Fault::Drop(index));
+            } else if rng.gen_range(0, 100) < self.config.delay_percent {
+                let delay = Duration::from_millis(
+                    rng.gen_range(0, self.config.max_delay.as_millis() as u64 + 1),
+                );
+                self.delays.insert(index, delay);
+                self.faults.push(Fault::Delay(index, delay));
+            }
+        }
+        if self.drops.contains(&index) {
+            return;
+        }
+        if let Some(delay) = self.delays.get(&index) {
+            latency += *delay;
+        }
+        self.schedule(now + latency, SimEvent::Deliver { from, to, msg });
+    }
+
+    fn is_partitioned(&self, from: usize, to: usize, now: Duration) -> bool {
+        self.faults.iter().any(|fault| match fault {
+            Fault::Partition {
+                group,
+                from: start,
+                to: end,
+            } => *start <= now && now < *end && group.contains(&from) != group.contains(&to),
+            _ => false,
+        })
+    }
+
+    fn check_safety(&mut self, node: usize, round: Round, id: HashValue) -> Option<Violation> {
+        if self.is_byzantine(node) {
+            return None;
+        }
+        let previous = self.nodes[node].ordered_round;
+        if round <= previous {
+            return Some(Violation::NonIncreasingRound {
+                node,
+                round,
+                previous,
+            });
+        }
+        self.nodes[node].ordered_round = round;
+        match self.ordered.entry(round) {
+            Entry::Occupied(entry) => {
+                let first = *entry.get();
+                (first.1 != id).then_some(Violation::ConflictingBlocks {
+                    round,
+                    first,
+                    second: (node, id),
+                })
+            },
+            Entry::Vacant(entry) => {
+                entry.insert((node, id));
+                self.last_progress = self.now();
+                None
+            },
+        }
+    }
+
+    fn check_liveness(&self) -> Option<Violation> {
+        let since = self.last_progress.max(self.config.gst);
+        let now = self.now();
+        (now > since + self.config.liveness_timeout)
+            .then_some(Violation::NoProgress { since, at: now })
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::simulator::{ByzantineBehavior, Fault, SimulationConfig, Simulator, Violation};
+use std::time::Duration;
+
+#[test]
+/// This test checks that a run without faults keeps ordering blocks, and
+/// that running the same config twice gives exactly the same run.
+///
+/// Run the test:
+/// cargo xtest -p consensus deterministic_simulation_test -- --nocapture
+fn deterministic_simulation_test() {
+    let config = SimulationConfig {
+        duration: Duration::from_secs(10),
+        ..SimulationConfig::default()
+    };
+    let first = Simulator::run(&config);
+    assert_eq!(first.violation, None);
+    assert!(first.ordered.len() > 10);
+
+    let second = Simulator::run(&config);
+    assert_eq!(first.steps, second.steps);
+    assert_eq!(first.ordered, second.ordered);
+}
+
+#[test]
+/// This test checks safety and liveness with messages dropped and
+/// reordered before gst, and a minority partition that heals.
+///
+/// Run the test:
+/// cargo xtest -p consensus random_faults_simulation_test -- --nocapture
+fn random_faults_simulation_test() {
+    for seed in 0..3 {
+        let config = SimulationConfig {
+            seed,
+            drop_percent: 10,
+            delay_percent: 10,
+            faults: vec![Fault::Partition {
+                group: vec![3],
+                from: Duration::from_secs(1),
+                to: Duration::from_secs(4),
+            }],
+            ..SimulationConfig::default()
+        };
+        let result = Simulator::run(&config);
+        assert_eq!(result.violation, None, "seed {}", seed);
+        assert!(result.faults.len() > 1);
+        assert!(!result.ordered.is_empty());
+    }
+}
+
+#[test]
+/// This test checks that a single Byzantine node out of 4 can't break
+/// safety or liveness, whether it equivocates or withholds its votes.
+///
+/// Run the test:
+/// cargo xtest -p consensus byzantine_simulation_test -- --nocapture
+fn byzantine_simulation_test() {
+    for behavior in [
+        ByzantineBehavior::Equivocate,
+        ByzantineBehavior::WithholdVotes,
+    ] {
+        let config = SimulationConfig {
+            faults: vec![Fault::Byzantine(0, behavior)],
+            ..SimulationConfig::default()
+        };
+        let result = Simulator::run(&config);
+        assert_eq!(result.violation, None, "{:?}", behavior);
+        assert!(!result.ordered.is_empty());
+    }
+}
+
+#[test]
+/// This test checks that a failing run is shrunk to the faults that
+/// cause it: a partition that never heals, without any quorum on either
+/// side, stops progress whatever messages are dropped before gst.
+///
+/// Run the test:
+/// cargo xtest -p consensus shrink_simulation_test -- --nocapture
+fn shrink_simulation_test() {
+    let partition = Fault::Partition {
+        group: vec![0, 1],
+        from: Duration::ZERO,
+        to: Duration::MAX,
+    };
+    let config = SimulationConfig {
+        duration: Duration::from_secs(10),
+        gst: Duration::from_secs(2),
+        liveness_timeout: Duration::from_secs(3),
+        drop_percent: 10,
+        faults: vec![
+            Fault::Drop(0),
+            partition.clone(),
+            Fault::Delay(1, Duration::from_millis(200)),
+        ],
+        ..SimulationConfig::default()
+    };
+    let result = Simulator::run(&config);
+    assert!(matches!(
+        result.violation,
+        Some(Violation::NoProgress { .. })
+    ));
+    assert!(result.faults.len() >= 3);
+
+    let faults = Simulator::shrink(&config, result.faults);
+    assert_eq!(faults, vec![partition]);
+}
@@ -141,12 +141,21 @@ pub fn silent_debug(source: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// The Serde name of a key type: its `#[key_name = "..."]` attribute if present, otherwise the
+/// name of the type. The attribute disambiguates types of different schemes sharing a name, e.g.
+/// `bls12381::PublicKey` and `secp256k1_ecdsa::PublicKey`.
+fn key_name(ast: &DeriveInput) -> String {
+    get_type_from_attrs(&ast.attrs, "key_name")
+        .map(|name| name.value())
+        .unwrap_or_else(|_| ast.ident.to_string())
+}
+
 /// Deserialize from a human readable format where applicable
-#[proc_macro_derive(DeserializeKey)]
+#[proc_macro_derive(DeserializeKey, attributes(key_name))]
 pub fn deserialize_key(source: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(source).expect("Incorrect macro input");
     let name = &ast.ident;
-    let name_string = name.to_string();
+    let name_string = key_name(&ast);
     let gen = quote! {
         impl<'de> ::serde::Deserialize<'de> for #name {
             fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
@@ -177,11 +186,11 @@ pub fn deserialize_key(source: TokenStream) -> TokenStream {
 }
 
 /// Serialize into a human readable format where applicable
-#[proc_macro_derive(SerializeKey)]
+#[proc_macro_derive(SerializeKey, attributes(key_name))]
 pub fn serialize_key(source: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(source).expect("Incorrect macro input");
     let name = &ast.ident;
-    let name_string = name.to_string();
+    let name_string = key_name(&ast);
     let gen = quote! {
         impl ::serde::Serialize for #name {
             fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
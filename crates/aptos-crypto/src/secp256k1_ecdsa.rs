@@ -0,0 +1,392 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for ECDSA signatures over the secp256k1 curve, the scheme used by
+//! Bitcoin and Ethereum keys, implemented on top of [libsecp256k1](https://docs.rs/libsecp256k1).
+//!
+//! Messages are hashed with SHA3-256 before signing. Public keys are serialized in the 65-byte
+//! uncompressed SEC1 format and signatures as the 64-byte `r || s` concatenation.
+//!
+//! Signature verification rejects signatures whose `s` is in the upper half of the curve order,
+//! since for every valid signature `(r, s)` the signature `(r, -s)` is valid as well.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use aptos_crypto::{
+//!     secp256k1_ecdsa::*,
+//!     traits::{Signature as _, SigningKey, Uniform},
+//!     test_utils::KeyPair
+//! };
+//! use rand_core::OsRng;
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng = OsRng;
+//! let kp = KeyPair::<PrivateKey, PublicKey>::generate(&mut rng);
+//!
+//! let signature = kp.private_key.sign(&message).unwrap();
+//! assert!(signature.verify(&message, &kp.public_key).is_ok());
+//! ```
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::test_utils::{self, KeyPair};
+use crate::{
+    hash::CryptoHash, traits, CryptoMaterialError, HashValue, Length, ValidCryptoMaterial,
+    ValidCryptoMaterialStringExt,
+};
+use anyhow::{anyhow, Result};
+use aptos_crypto_derive::{DeserializeKey, SerializeKey, SilentDebug, SilentDisplay};
+use core::convert::TryFrom;
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest::prelude::*;
+use serde::Serialize;
+use std::fmt;
+
+/// A secp256k1 ECDSA private key
+#[derive(DeserializeKey, SerializeKey, SilentDebug, SilentDisplay)]
+#[key_name = "Secp256k1EcdsaPrivateKey"]
+pub struct PrivateKey(pub(crate) libsecp256k1::SecretKey);
+
+#[cfg(feature = "assert-private-keys-not-cloneable")]
+static_assertions::assert_not_impl_any!(PrivateKey: Clone);
+
+#[cfg(any(test, feature = "cloneable-private-keys"))]
+impl Clone for PrivateKey {
+    fn clone(&self) -> Self {
+        let serialized: &[u8] = &(self.to_bytes());
+        PrivateKey::try_from(serialized).unwrap()
+    }
+}
+
+/// A secp256k1 ECDSA public key
+#[derive(DeserializeKey, Clone, SerializeKey)]
+#[key_name = "Secp256k1EcdsaPublicKey"]
+pub struct PublicKey(pub(crate) libsecp256k1::PublicKey);
+
+/// A secp256k1 ECDSA signature, always in the low-`s` form
+#[derive(DeserializeKey, Clone, SerializeKey)]
+#[key_name = "Secp256k1EcdsaSignature"]
+pub struct Signature(pub(crate) libsecp256k1::Signature);
+
+/// Hashes the message into the 32-byte digest that is actually signed.
+fn message_digest(message: &[u8]) -> libsecp256k1::Message {
+    let hash = HashValue::sha3_256_of(message);
+    let digest: &[u8; HashValue::LENGTH] = hash.as_ref();
+    libsecp256k1::Message::parse(digest)
+}
+
+impl PrivateKey {
+    /// The length of a serialized PrivateKey
+    pub const LENGTH: usize = 32;
+
+    /// Serialize a PrivateKey.
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        self.0.serialize()
+    }
+
+    /// Private function aimed at minimizing code duplication between sign
+    /// methods of the SigningKey implementation. This should remain private.
+    fn sign_arbitrary_message(&self, message: &[u8]) -> Signature {
+        // libsecp256k1 always produces signatures with a normalized, low `s`.
+        let (signature, _recovery_id) = libsecp256k1::sign(&message_digest(message), &self.0);
+        Signature(signature)
+    }
+}
+
+impl PublicKey {
+    /// The length of a serialized PublicKey, in the uncompressed SEC1 format
+    pub const LENGTH: usize = 65;
+
+    /// Serialize a PublicKey in the uncompressed SEC1 format.
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        self.0.serialize()
+    }
+}
+
+impl Signature {
+    /// The length of a serialized Signature
+    pub const LENGTH: usize = 64;
+
+    /// Serialize a Signature.
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        self.0.serialize()
+    }
+
+    /// Rejects signatures whose `s` is in the upper half of the curve order, to prevent
+    /// third parties from mauling a valid signature into another valid one.
+    pub fn check_s_malleability(&self) -> std::result::Result<(), CryptoMaterialError> {
+        if self.0.s.is_high() {
+            return Err(CryptoMaterialError::CanonicalRepresentationError);
+        }
+        Ok(())
+    }
+
+    /// return an all-zero signature (for test only)
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn dummy_signature() -> Self {
+        Signature(libsecp256k1::Signature::parse_overflowing(
+            &[0u8; Self::LENGTH],
+        ))
+    }
+}
+
+///////////////////////
+// PrivateKey Traits //
+///////////////////////
+
+impl traits::PrivateKey for PrivateKey {
+    type PublicKeyMaterial = PublicKey;
+}
+
+impl traits::SigningKey for PrivateKey {
+    type SignatureMaterial = Signature;
+    type VerifyingKeyMaterial = PublicKey;
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+    ) -> std::result::Result<Signature, CryptoMaterialError> {
+        Ok(PrivateKey::sign_arbitrary_message(
+            self,
+            traits::signing_message(message)?.as_ref(),
+        ))
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    fn sign_arbitrary_message(&self, message: &[u8]) -> Signature {
+        PrivateKey::sign_arbitrary_message(self, message)
+    }
+}
+
+impl traits::Uniform for PrivateKey {
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng,
+    {
+        // Out of range and zero scalars are rejected by libsecp256k1, which happens with
+        // negligible probability, so retry until we hit a valid key.
+        loop {
+            let mut bytes = [0u8; Self::LENGTH];
+            rng.fill_bytes(&mut bytes);
+            if let Ok(secret_key) = libsecp256k1::SecretKey::parse(&bytes) {
+                return PrivateKey(secret_key);
+            }
+        }
+    }
+}
+
+impl PartialEq<Self> for PrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for PrivateKey {}
+
+impl TryFrom<&[u8]> for PrivateKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a PrivateKey. This method will check for private key validity: i.e., correct
+    /// key length and a non-zero scalar smaller than the curve order.
+    fn try_from(bytes: &[u8]) -> std::result::Result<PrivateKey, CryptoMaterialError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        libsecp256k1::SecretKey::parse_slice(bytes)
+            .map(PrivateKey)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+}
+
+impl Length for PrivateKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for PrivateKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// PublicKey Traits //
+//////////////////////
+
+impl From<&PrivateKey> for PublicKey {
+    fn from(private_key: &PrivateKey) -> Self {
+        PublicKey(libsecp256k1::PublicKey::from_secret_key(&private_key.0))
+    }
+}
+
+impl traits::PublicKey for PublicKey {
+    type PrivateKeyMaterial = PrivateKey;
+}
+
+impl std::hash::Hash for PublicKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(&self.to_bytes());
+    }
+}
+
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &PublicKey) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl traits::VerifyingKey for PublicKey {
+    type SignatureMaterial = Signature;
+    type SigningKeyMaterial = PrivateKey;
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "secp256k1_ecdsa::PublicKey({})", self)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a PublicKey from its uncompressed SEC1 format. This checks that the point is
+    /// on the curve.
+    fn try_from(bytes: &[u8]) -> std::result::Result<PublicKey, CryptoMaterialError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        libsecp256k1::PublicKey::parse_slice(bytes, Some(libsecp256k1::PublicKeyFormat::Full))
+            .map(PublicKey)
+            .map_err(|_| CryptoMaterialError::PointNotOnCurveError)
+    }
+}
+
+impl Length for PublicKey {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for PublicKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+//////////////////////
+// Signature Traits //
+//////////////////////
+
+impl traits::Signature for Signature {
+    type SigningKeyMaterial = PrivateKey;
+    type VerifyingKeyMaterial = PublicKey;
+
+    /// Verifies that the provided signature is valid for the provided message, rejecting
+    /// signatures with a high `s` (see [Signature::check_s_malleability]).
+    fn verify<T: CryptoHash + Serialize>(&self, message: &T, public_key: &PublicKey) -> Result<()> {
+        Self::verify_arbitrary_msg(self, &traits::signing_message(message)?, public_key)
+    }
+
+    /// Checks that `self` is valid for an arbitrary &[u8] `message` using `public_key`.
+    fn verify_arbitrary_msg(&self, message: &[u8], public_key: &PublicKey) -> Result<()> {
+        self.check_s_malleability()?;
+        if libsecp256k1::verify(&message_digest(message), &self.0, &public_key.0) {
+            Ok(())
+        } else {
+            Err(anyhow!("Secp256k1 ECDSA signature verification failed"))
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Length for Signature {
+    fn length(&self) -> usize {
+        Self::LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for Signature {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for Signature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write(&self.to_bytes());
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = CryptoMaterialError;
+
+    /// Deserialize a Signature. This rejects out of range `r` and `s` as well as a high `s`.
+    fn try_from(bytes: &[u8]) -> std::result::Result<Signature, CryptoMaterialError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        let signature = libsecp256k1::Signature::parse_standard_slice(bytes)
+            .map(Signature)
+            .map_err(|_| CryptoMaterialError::DeserializationError)?;
+        signature.check_s_malleability()?;
+        Ok(signature)
+    }
+}
+
+impl PartialEq for Signature {
+    fn eq(&self, other: &Signature) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for Signature {}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "secp256k1_ecdsa::Signature({})", self)
+    }
+}
+
+/////////////
+// Fuzzing //
+/////////////
+
+/// Produces a uniformly random secp256k1 ECDSA keypair from a seed
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn keypair_strategy() -> impl Strategy<Value = KeyPair<PrivateKey, PublicKey>> {
+    test_utils::uniform_keypair_strategy::<PrivateKey, PublicKey>()
+}
+
+/// Produces a uniformly random secp256k1 ECDSA public key
+#[cfg(any(test, feature = "fuzzing"))]
+impl proptest::arbitrary::Arbitrary for PublicKey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        keypair_strategy().prop_map(|v| v.public_key).boxed()
+    }
+}
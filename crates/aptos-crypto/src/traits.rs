@@ -307,7 +307,7 @@ pub trait Genesis: PrivateKey {
 pub(crate) mod private {
     pub trait Sealed {}
 
-    // Implement for the ed25519, multi-ed25519 signatures
+    // Implement for the ed25519, multi-ed25519, bls12381 and secp256k1 ECDSA signatures
     impl Sealed for crate::ed25519::Ed25519PrivateKey {}
     impl Sealed for crate::ed25519::Ed25519PublicKey {}
     impl Sealed for crate::ed25519::Ed25519Signature {}
@@ -320,4 +320,8 @@ pub(crate) mod private {
     impl Sealed for crate::bls12381::PublicKey {}
     impl Sealed for crate::bls12381::Signature {}
     impl Sealed for crate::bls12381::ProofOfPossession {}
+
+    impl Sealed for crate::secp256k1_ecdsa::PrivateKey {}
+    impl Sealed for crate::secp256k1_ecdsa::PublicKey {}
+    impl Sealed for crate::secp256k1_ecdsa::Signature {}
 }
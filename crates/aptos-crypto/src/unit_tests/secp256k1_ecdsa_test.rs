@@ -0,0 +1,152 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate as aptos_crypto;
+use crate::{
+    secp256k1_ecdsa::{PrivateKey, PublicKey, Signature},
+    test_utils::{random_serializable_struct, uniform_keypair_strategy},
+    traits::*,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use core::convert::TryFrom;
+use proptest::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(CryptoHasher, BCSCryptoHash, Serialize, Deserialize)]
+struct CryptoHashable(pub usize);
+
+/// The order of the secp256k1 curve, big-endian.
+const N: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x41,
+];
+
+/// Computes `N - s` for a big-endian scalar `s`.
+fn negate_s(s: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut diff = N[i] as i16 - s[i] as i16 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 256;
+            borrow = 1;
+        }
+        out[i] = diff as u8;
+    }
+    out
+}
+
+proptest! {
+    #[test]
+    fn test_keys_encode(keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()) {
+        {
+            let encoded = keypair.private_key.to_encoded_string().unwrap();
+            // Hex encoding of a 32-bytes key is 64 (2 x 32) characters.
+            prop_assert_eq!(2 + 2 * PrivateKey::LENGTH, encoded.len());
+            let decoded = PrivateKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.private_key), decoded.ok());
+        }
+        {
+            let encoded = keypair.public_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 + 2 * PublicKey::LENGTH, encoded.len());
+            let decoded = PublicKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.public_key), decoded.ok());
+        }
+    }
+
+    #[test]
+    fn test_keys_custom_serialisation(
+        keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()
+    ) {
+        {
+            let serialized: &[u8] = &(keypair.private_key.to_bytes());
+            prop_assert_eq!(PrivateKey::LENGTH, serialized.len());
+            let deserialized = PrivateKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.private_key), deserialized.ok());
+        }
+        {
+            let serialized: &[u8] = &(keypair.public_key.to_bytes());
+            prop_assert_eq!(PublicKey::LENGTH, serialized.len());
+            let deserialized = PublicKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
+        }
+    }
+
+    #[test]
+    fn test_signature_verification_custom_serialisation(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        let serialized: &[u8] = &(signature.to_bytes());
+        prop_assert_eq!(Signature::LENGTH, serialized.len());
+        let deserialized = Signature::try_from(serialized).unwrap();
+        prop_assert!(deserialized.verify(&message, &keypair.public_key).is_ok());
+    }
+
+    #[test]
+    fn test_signature_verification_from_arbitrary(
+        // this should be > 64 bits to go over the hash size (8 bits) of the signing message
+        x in any::<u64>(),
+        keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()
+    ) {
+        let hashable = CryptoHashable(x as usize);
+        let signature = keypair.private_key.sign(&hashable).unwrap();
+        prop_assert!(signature.verify(&hashable, &keypair.public_key).is_ok());
+        prop_assert!(signature.verify(&CryptoHashable(x as usize ^ 1), &keypair.public_key).is_err());
+    }
+
+    #[test]
+    fn test_signature_verification_wrong_key(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>(),
+        other_keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()
+    ) {
+        prop_assume!(keypair.public_key != other_keypair.public_key);
+        let signature = keypair.private_key.sign(&message).unwrap();
+        prop_assert!(signature.verify(&message, &other_keypair.public_key).is_err());
+    }
+
+    #[test]
+    fn test_signature_malleability(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<PrivateKey, PublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        prop_assert!(signature.check_s_malleability().is_ok());
+
+        // (r, N - s) is an equally valid ECDSA signature, which we must reject.
+        let mut mauled = signature.to_bytes();
+        let high_s = negate_s(&mauled[32..]);
+        mauled[32..].copy_from_slice(&high_s);
+        prop_assert_eq!(
+            Signature::try_from(&mauled[..]),
+            Err(CryptoMaterialError::CanonicalRepresentationError)
+        );
+
+        let mauled_signature = Signature(libsecp256k1::Signature::parse_standard_slice(&mauled).unwrap());
+        prop_assert!(mauled_signature.verify(&message, &keypair.public_key).is_err());
+    }
+}
+
+#[test]
+fn test_deserialization_errors() {
+    assert_eq!(
+        PrivateKey::try_from(&[1u8; PrivateKey::LENGTH - 1][..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+    // Zero is not a valid secret scalar.
+    assert_eq!(
+        PrivateKey::try_from(&[0u8; PrivateKey::LENGTH][..]),
+        Err(CryptoMaterialError::DeserializationError)
+    );
+    assert_eq!(
+        PublicKey::try_from(&[4u8; PublicKey::LENGTH][..]),
+        Err(CryptoMaterialError::PointNotOnCurveError)
+    );
+    assert_eq!(
+        Signature::try_from(&[1u8; Signature::LENGTH + 1][..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+}
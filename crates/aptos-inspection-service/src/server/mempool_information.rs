@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{CONTENT_TYPE_JSON, CONTENT_TYPE_TEXT};
+use aptos_config::config::NodeConfig;
+use aptos_mempool::{MempoolClientRequest, MempoolClientSender};
+use aptos_types::account_address::AccountAddress;
+use futures::{channel::oneshot, SinkExt};
+use hyper::{Body, StatusCode};
+use std::{str::FromStr, time::Duration};
+
+// The message to display when the mempool information endpoint is disabled
+pub const MEMPOOL_INFO_DISABLED_MESSAGE: &str =
+    "This endpoint is disabled! Enable it in the node config at inspection_service.expose_mempool_information: true";
+
+// The message to display when mempool could not be reached
+pub const MEMPOOL_UNAVAILABLE_MESSAGE: &str = "Mempool is not available!";
+
+// The query parameter used to filter the transactions by sender
+const ACCOUNT_QUERY_PARAMETER: &str = "account";
+
+// How long to wait for mempool to respond
+const MEMPOOL_RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Handles a new mempool information request. The request can be
+/// filtered to a single sender using the `account` query parameter.
+pub async fn handle_mempool_information_request(
+    node_config: &NodeConfig,
+    query: Option<&str>,
+    mempool_client_sender: MempoolClientSender,
+) -> (StatusCode, Body, String) {
+    // Only return mempool information if the endpoint is enabled
+    if !node_config.inspection_service.expose_mempool_information {
+        return (
+            StatusCode::FORBIDDEN,
+            Body::from(MEMPOOL_INFO_DISABLED_MESSAGE),
+            CONTENT_TYPE_TEXT.into(),
+        );
+    }
+
+    // Parse the account filter (if one was given)
+    let account = match parse_account(query) {
+        Ok(account) => account,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Body::from(error),
+                CONTENT_TYPE_TEXT.into(),
+            )
+        },
+    };
+
+    // Fetch the mempool information
+    match get_mempool_information(account, mempool_client_sender).await {
+        Some(mempool_information) => (
+            StatusCode::OK,
+            Body::from(mempool_information),
+            CONTENT_TYPE_JSON.into(),
+        ),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Body::from(MEMPOOL_UNAVAILABLE_MESSAGE),
+            CONTENT_TYPE_TEXT.into(),
+        ),
+    }
+}
+
+/// Returns the account given in the query string (if any)
+fn parse_account(query: Option<&str>) -> Result<Option<AccountAddress>, String> {
+    let query = match query {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+
+    for parameter in query.split('&') {
+        if let Some((ACCOUNT_QUERY_PARAMETER, value)) = parameter.split_once('=') {
+            return AccountAddress::from_str(value)
+                .map(Some)
+                .map_err(|error| format!("Invalid account {}: {}", value, error));
+        }
+    }
+    Ok(None)
+}
+
+/// Requests an inspection from mempool and returns it as a JSON string.
+/// Returns None if mempool did not respond.
+async fn get_mempool_information(
+    account: Option<AccountAddress>,
+    mut mempool_client_sender: MempoolClientSender,
+) -> Option<String> {
+    let (callback, receiver) = oneshot::channel();
+    mempool_client_sender
+        .send(MempoolClientRequest::InspectMempool(account, callback))
+        .await
+        .ok()?;
+
+    let inspection = tokio::time::timeout(MEMPOOL_RESPONSE_TIMEOUT, receiver)
+        .await
+        .ok()?
+        .ok()?;
+    serde_json::to_string_pretty(&inspection).ok()
+}
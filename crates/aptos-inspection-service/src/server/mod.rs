@@ -4,6 +4,7 @@
 use crate::server::utils::CONTENT_TYPE_TEXT;
 use aptos_config::config::NodeConfig;
 use aptos_logger::debug;
+use aptos_mempool::MempoolClientSender;
 use aptos_network::application::storage::PeersAndMetadata;
 use hyper::{
     service::{make_service_fn, service_fn},
@@ -19,6 +20,7 @@ use std::{
 mod configuration;
 mod index;
 mod json_encoder;
+mod mempool_information;
 mod metrics;
 mod peer_information;
 mod system_information;
@@ -32,6 +34,7 @@ pub const CONFIGURATION_PATH: &str = "/configuration";
 pub const FORGE_METRICS_PATH: &str = "/forge_metrics";
 pub const INDEX_PATH: &str = "/";
 pub const JSON_METRICS_PATH: &str = "/json_metrics";
+pub const MEMPOOL_INFORMATION_PATH: &str = "/mempool_information";
 pub const METRICS_PATH: &str = "/metrics";
 pub const PEER_INFORMATION_PATH: &str = "/peer_information";
 pub const SYSTEM_INFORMATION_PATH: &str = "/system_information";
@@ -46,6 +49,7 @@ pub const UNEXPECTED_ERROR_MESSAGE: &str = "An unexpected error was encountered!
 pub fn start_inspection_service(
     node_config: NodeConfig,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    mempool_client_sender: MempoolClientSender,
 ) {
     // Fetch the service port and address
     let service_port = node_config.inspection_service.port;
@@ -72,9 +76,15 @@ pub fn start_inspection_service(
         let make_service = make_service_fn(move |_conn| {
             let node_config = node_config.clone();
             let peers_and_metadata = peers_and_metadata.clone();
+            let mempool_client_sender = mempool_client_sender.clone();
             async move {
                 Ok::<_, Infallible>(service_fn(move |request| {
-                    serve_requests(request, node_config.clone(), peers_and_metadata.clone())
+                    serve_requests(
+                        request,
+                        node_config.clone(),
+                        peers_and_metadata.clone(),
+                        mempool_client_sender.clone(),
+                    )
                 }))
             }
         });
@@ -94,6 +104,7 @@ async fn serve_requests(
     req: Request<Body>,
     node_config: NodeConfig,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    mempool_client_sender: MempoolClientSender,
 ) -> Result<Response<Body>, hyper::Error> {
     // Process the request and get the response components
     let (status_code, body, content_type) = match req.uri().path() {
@@ -117,6 +128,16 @@ async fn serve_requests(
             // Exposes JSON encoded metrics
             metrics::handle_json_metrics_request()
         },
+        MEMPOOL_INFORMATION_PATH => {
+            // /mempool_information
+            // Exposes the pending transactions in mempool
+            mempool_information::handle_mempool_information_request(
+                &node_config,
+                req.uri().query(),
+                mempool_client_sender,
+            )
+            .await
+        },
         METRICS_PATH => {
             // /metrics
             // Exposes text encoded metrics
@@ -4,16 +4,24 @@
 use crate::{
     server::{
         configuration::CONFIGURATION_DISABLED_MESSAGE,
-        peer_information::PEER_INFO_DISABLED_MESSAGE, serve_requests,
-        system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
+        mempool_information::{MEMPOOL_INFO_DISABLED_MESSAGE, MEMPOOL_UNAVAILABLE_MESSAGE},
+        peer_information::PEER_INFO_DISABLED_MESSAGE,
+        serve_requests,
+        system_information::SYS_INFO_DISABLED_MESSAGE,
+        utils::get_all_metrics,
     },
-    CONFIGURATION_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH, METRICS_PATH,
-    PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    CONFIGURATION_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH,
+    MEMPOOL_INFORMATION_PATH, METRICS_PATH, PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::NodeConfig;
+use aptos_mempool::{
+    inspection::{AccountInspection, MempoolInspection},
+    MempoolClientRequest, MempoolClientSender,
+};
 use aptos_network::application::storage::PeersAndMetadata;
+use aptos_types::account_address::AccountAddress;
 use assert_approx_eq::assert_approx_eq;
-use futures::executor::block_on;
+use futures::{channel::mpsc, executor::block_on, StreamExt};
 use hyper::{body, Body, Method, Request, Response, StatusCode};
 use once_cell::sync::Lazy;
 use prometheus::{proto::MetricFamily, register_int_counter, Counter, IntCounter, Opts, Registry};
@@ -84,6 +92,7 @@ async fn test_inspect_index() {
     assert!(response_body_string.contains(CONFIGURATION_PATH));
     assert!(response_body_string.contains(FORGE_METRICS_PATH));
     assert!(response_body_string.contains(JSON_METRICS_PATH));
+    assert!(response_body_string.contains(MEMPOOL_INFORMATION_PATH));
     assert!(response_body_string.contains(METRICS_PATH));
     assert!(response_body_string.contains(PEER_INFORMATION_PATH));
     assert!(response_body_string.contains(SYSTEM_INFORMATION_PATH));
@@ -105,6 +114,75 @@ async fn test_inspect_json_metrics() {
     assert!(response_body_string.contains(INT_COUNTER_NAME));
 }
 
+#[tokio::test]
+async fn test_inspect_mempool_information() {
+    // Create a validator node config
+    let mut config = NodeConfig::get_default_validator_config();
+
+    // Disable the mempool information endpoint and ping it
+    config.inspection_service.expose_mempool_information = false;
+    let mut response = send_get_request_to_path(&config, MEMPOOL_INFORMATION_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+
+    // Verify that the response contains an error
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(response_body, MEMPOOL_INFO_DISABLED_MESSAGE);
+
+    // Enable the mempool information endpoint and ping it with an invalid account
+    config.inspection_service.expose_mempool_information = true;
+    let invalid_account_path = format!("{}?account=invalid", MEMPOOL_INFORMATION_PATH);
+    let response = send_get_request_to_path(&config, &invalid_account_path).await;
+
+    // Verify that the request is rejected
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Ping the endpoint without a running mempool
+    let mut response = send_get_request_to_path(&config, MEMPOOL_INFORMATION_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+
+    // Verify that the response contains an error
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response_body, MEMPOOL_UNAVAILABLE_MESSAGE);
+
+    // Spawn a mock mempool that returns an inspection of the requested account
+    let (mempool_client_sender, mut mempool_client_receiver) = mpsc::channel(1);
+    tokio::spawn(async move {
+        while let Some(request) = mempool_client_receiver.next().await {
+            if let MempoolClientRequest::InspectMempool(account, callback) = request {
+                let accounts = account
+                    .map(|address| AccountInspection {
+                        address,
+                        account_sequence_number: 10,
+                        transactions: vec![],
+                    })
+                    .into_iter()
+                    .collect();
+                let _ = callback.send(MempoolInspection {
+                    accounts,
+                    ..Default::default()
+                });
+            }
+        }
+    });
+
+    // Ping the endpoint for a single account
+    let account_path = format!(
+        "{}?account={}",
+        MEMPOOL_INFORMATION_PATH,
+        AccountAddress::ONE.to_hex_literal()
+    );
+    let mut response =
+        send_get_request_to_path_with_mempool(&config, &account_path, mempool_client_sender).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let inspection: MempoolInspection = serde_json::from_slice(&response_body).unwrap();
+
+    // Verify that the response contains the expected information
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(inspection.accounts.len(), 1);
+    assert_eq!(inspection.accounts[0].address, AccountAddress::ONE);
+    assert_eq!(inspection.accounts[0].account_sequence_number, 10);
+}
+
 #[tokio::test]
 async fn test_inspect_metrics() {
     // Create a validator config
@@ -243,6 +321,18 @@ fn test_publish_metrics() {
 
 // Exercise the serve_requests() handler with a GET request to the given path
 async fn send_get_request_to_path(config: &NodeConfig, endpoint: &str) -> Response<Body> {
+    // Create a mempool client sender without a running mempool
+    let (mempool_client_sender, _) = mpsc::channel(1);
+    send_get_request_to_path_with_mempool(config, endpoint, mempool_client_sender).await
+}
+
+// Exercise the serve_requests() handler with a GET request to the given path,
+// sending any mempool requests to the given mempool client sender.
+async fn send_get_request_to_path_with_mempool(
+    config: &NodeConfig,
+    endpoint: &str,
+    mempool_client_sender: MempoolClientSender,
+) -> Response<Body> {
     // Build the URI
     let uri = format!("http://127.0.0.1:9201{}", endpoint);
 
@@ -255,6 +345,7 @@ async fn send_get_request_to_path(config: &NodeConfig, endpoint: &str) -> Respon
             .unwrap(),
         config.clone(),
         PeersAndMetadata::new(&[]),
+        mempool_client_sender,
     )
     .await
     .unwrap()
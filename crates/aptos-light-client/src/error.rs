@@ -0,0 +1,20 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_rest_client::error::RestError;
+use thiserror::Error;
+
+/// Different errors returned by the light client
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to deserialize the verified value: {0}")]
+    DeserializationError(String),
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("Invalid waypoint: {0}")]
+    InvalidWaypoint(String),
+    #[error("The request to the node failed: {0}")]
+    RestError(#[from] RestError),
+    #[error("Failed to verify the response of the node: {0}")]
+    VerificationError(String),
+}
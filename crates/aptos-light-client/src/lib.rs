@@ -0,0 +1,361 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A light client that doesn't trust the fullnode it talks to: starting from a waypoint, it
+//! follows the epoch changes of the chain to track a `TrustedState`, and verifies every value
+//! the node returns against the ledger infos signed by the validators of the trusted epoch.
+
+mod error;
+
+pub use crate::error::Error;
+use aptos_rest_client::Client;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    epoch_change::Verifier,
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use move_core_types::{language_storage::StructTag, move_resource::MoveStructType};
+use serde::de::DeserializeOwned;
+use std::{fs, path::Path, str::FromStr};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A client of the REST API of a fullnode that verifies all the data it returns
+pub struct LightClient {
+    client: Client,
+    trusted_state: TrustedState,
+}
+
+impl LightClient {
+    /// Creates a light client that trusts the given epoch waypoint, e.g., the genesis waypoint
+    pub fn new(client: Client, waypoint: Waypoint) -> Self {
+        Self {
+            client,
+            trusted_state: TrustedState::from_epoch_waypoint(waypoint),
+        }
+    }
+
+    /// Creates a light client that trusts the epoch waypoint in the given file, e.g.,
+    /// `waypoint.txt`
+    pub fn new_with_waypoint_file(client: Client, waypoint_file: &Path) -> Result<Self> {
+        let content = fs::read_to_string(waypoint_file).map_err(|error| {
+            Error::InvalidWaypoint(format!(
+                "Failed to read waypoint file {:?}: {}",
+                waypoint_file.display(),
+                error
+            ))
+        })?;
+        let waypoint = Waypoint::from_str(content.trim()).map_err(|error| {
+            Error::InvalidWaypoint(format!(
+                "Failed to parse waypoint {:?}: {}",
+                content.trim(),
+                error
+            ))
+        })?;
+
+        Ok(Self::new(client, waypoint))
+    }
+
+    pub fn trusted_state(&self) -> &TrustedState {
+        &self.trusted_state
+    }
+
+    /// Ratchets the trusted state forward to the latest ledger info of the node, verifying the
+    /// epoch changes in between.
+    pub async fn sync(&mut self) -> Result<()> {
+        loop {
+            let state_proof = self
+                .client
+                .get_state_proof_bcs(self.trusted_state.version())
+                .await?
+                .into_inner();
+            let new_state = self
+                .trusted_state
+                .verify_and_ratchet(&state_proof)
+                .map_err(|error| Error::VerificationError(error.to_string()))?
+                .new_state();
+            match new_state {
+                Some(new_state) => self.trusted_state = new_state,
+                None => return Ok(()),
+            }
+
+            // The node caps the number of epoch changes in a proof, in which case the trusted
+            // state is only ratcheted to the last of them and we have to ask for the rest.
+            if !state_proof.epoch_changes().more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns the resource of the given type under the given account, verified against the
+    /// latest ledger info of the node.
+    pub async fn get_resource<T: MoveStructType + DeserializeOwned>(
+        &mut self,
+        address: AccountAddress,
+    ) -> Result<Option<T>> {
+        self.get_resource_bytes(address, &T::struct_tag())
+            .await?
+            .map(|bytes| bcs::from_bytes(&bytes))
+            .transpose()
+            .map_err(|error| Error::DeserializationError(error.to_string()))
+    }
+
+    /// Returns the BCS encoded resource of the given type under the given account, verified
+    /// against the latest ledger info of the node.
+    pub async fn get_resource_bytes(
+        &mut self,
+        address: AccountAddress,
+        resource_type: &StructTag,
+    ) -> Result<Option<Vec<u8>>> {
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(address, resource_type.clone())
+                .map_err(|error| Error::InvalidRequest(error.to_string()))?,
+        );
+        let state_value_with_proof = self
+            .client
+            .get_account_resource_with_proof_bcs(address, &resource_type.to_string())
+            .await?
+            .into_inner();
+
+        let ledger_info_with_signatures = &state_value_with_proof.ledger_info_with_signatures;
+        self.verify_ledger_info(ledger_info_with_signatures).await?;
+        state_value_with_proof
+            .verify(ledger_info_with_signatures.ledger_info(), &state_key)
+            .map_err(|error| Error::VerificationError(error.to_string()))?;
+
+        Ok(state_value_with_proof
+            .state_value
+            .map(StateValue::into_bytes))
+    }
+
+    /// Verifies the signatures of the given ledger info with the validator set of the trusted
+    /// epoch, syncing first if the ledger info is in a newer epoch.
+    async fn verify_ledger_info(
+        &mut self,
+        ledger_info_with_signatures: &LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        let epoch = ledger_info_with_signatures.ledger_info().epoch();
+        if self
+            .trusted_epoch_state()
+            .map_or(true, |epoch_state| epoch_state.epoch < epoch)
+        {
+            self.sync().await?;
+        }
+
+        match self.trusted_epoch_state() {
+            Some(epoch_state) if epoch_state.epoch == epoch => epoch_state
+                .verify(ledger_info_with_signatures)
+                .map_err(|error| Error::VerificationError(error.to_string())),
+            epoch_state => Err(Error::VerificationError(format!(
+                "The ledger info is in epoch {}, but the trusted epoch is {:?}",
+                epoch,
+                epoch_state.map(|epoch_state| epoch_state.epoch)
+            ))),
+        }
+    }
+
+    fn trusted_epoch_state(&self) -> Option<&EpochState> {
+        match &self.trusted_state {
+            TrustedState::EpochWaypoint(_) => None,
+            TrustedState::EpochState { epoch_state, .. } => Some(epoch_state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, LightClient};
+    use aptos_api::{attach_poem_to_runtime, Context};
+    use aptos_api_types::{
+        mime_types::BCS, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH,
+        X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+        X_APTOS_OLDEST_BLOCK_HEIGHT,
+    };
+    use aptos_config::config::NodeConfig;
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_rest_client::{Client, State};
+    use aptos_storage_interface::DbReader;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        access_path::AccessPath,
+        account_address::AccountAddress,
+        account_config::{aptos_test_root_address, AccountResource},
+        chain_id::ChainId,
+        state_store::{state_key::StateKey, state_value::StateValue},
+        trusted_state::TrustedState,
+        waypoint::Waypoint,
+    };
+    use futures::channel::mpsc;
+    use httpmock::{prelude::*, Then};
+    use move_core_types::move_resource::MoveStructType;
+    use std::{fs, str::FromStr, sync::Arc};
+    use tokio::runtime::Handle;
+    use url::Url;
+
+    #[test]
+    fn test_new_with_waypoint_file() {
+        let client = Client::new(Url::parse("http://localhost:8080").unwrap());
+        let waypoint = Waypoint::from_str(
+            "0:6072b68a942aace147e0655c5704beaa255c84a7829baa4e72a500f1516584c4",
+        )
+        .unwrap();
+
+        // The waypoint file may end with a new line
+        let waypoint_file = TempPath::new();
+        fs::write(waypoint_file.path(), format!("{}\n", waypoint)).unwrap();
+        let light_client =
+            LightClient::new_with_waypoint_file(client.clone(), waypoint_file.path()).unwrap();
+        assert_eq!(
+            light_client.trusted_state(),
+            &TrustedState::EpochWaypoint(waypoint)
+        );
+
+        // Invalid waypoints are rejected
+        fs::write(waypoint_file.path(), "invalid waypoint").unwrap();
+        assert!(matches!(
+            LightClient::new_with_waypoint_file(client, waypoint_file.path()),
+            Err(Error::InvalidWaypoint(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_sync_and_get_resource() {
+        let (db, client, waypoint) = start_test_api();
+        let latest_ledger_info = db.get_latest_ledger_info().unwrap();
+
+        // Syncing verifies the epoch changes up to the latest ledger info
+        let mut light_client = LightClient::new(client, waypoint);
+        light_client.sync().await.unwrap();
+        assert_eq!(
+            light_client.trusted_state().version(),
+            latest_ledger_info.ledger_info().version()
+        );
+
+        // The resource is verified against the latest ledger info
+        let address = aptos_test_root_address();
+        let bytes = light_client
+            .get_resource_bytes(address, &AccountResource::struct_tag())
+            .await
+            .unwrap()
+            .unwrap();
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(address, AccountResource::struct_tag()).unwrap(),
+        );
+        let (snapshot_version, _) = db
+            .get_state_snapshot_before(latest_ledger_info.ledger_info().version() + 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(bytes),
+            db.get_state_value_by_version(&state_key, snapshot_version)
+                .unwrap()
+                .map(StateValue::into_bytes)
+        );
+        let account_resource: AccountResource =
+            light_client.get_resource(address).await.unwrap().unwrap();
+        assert!(account_resource.sequence_number() > 0);
+
+        // So is the absence of a resource
+        assert!(light_client
+            .get_resource::<AccountResource>(AccountAddress::random())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_reject_tampered_proof() {
+        let (_db, client, waypoint) = start_test_api();
+        let address = aptos_test_root_address();
+        let resource_type = AccountResource::struct_tag().to_string();
+
+        // Serve the genuine state proof, but tamper with the value of the resource
+        let state_proof = client.get_state_proof_bcs(0).await.unwrap();
+        let (mut state_value_with_proof, state) = client
+            .get_account_resource_with_proof_bcs(address, &resource_type)
+            .await
+            .unwrap()
+            .into_parts();
+        state_value_with_proof.state_value = Some(StateValue::new_legacy(vec![0; 32]));
+
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/v1/state_proof");
+                with_state_headers(then, state_proof.state())
+                    .header("Content-Type", BCS)
+                    .body(bcs::to_bytes(state_proof.inner()).unwrap());
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path_contains("/proof");
+                with_state_headers(then, &state)
+                    .header("Content-Type", BCS)
+                    .body(bcs::to_bytes(&state_value_with_proof).unwrap());
+            })
+            .await;
+
+        let mut light_client = LightClient::new(
+            Client::new(Url::parse(&server.base_url()).unwrap()),
+            waypoint,
+        );
+        assert!(matches!(
+            light_client
+                .get_resource_bytes(address, &AccountResource::struct_tag())
+                .await,
+            Err(Error::VerificationError(_))
+        ));
+    }
+
+    /// Serves the REST API of a DB whose ledger infos are signed by a test validator, returning
+    /// the DB, a client of the API and the genesis waypoint.
+    fn start_test_api() -> (Arc<dyn DbReader>, Client, Waypoint) {
+        let db: Arc<dyn DbReader> = test_execution_with_storage_impl();
+        let genesis_ledger_info = db
+            .get_epoch_ending_ledger_infos(0, 1)
+            .unwrap()
+            .ledger_info_with_sigs
+            .remove(0);
+        let waypoint = Waypoint::new_epoch_boundary(genesis_ledger_info.ledger_info()).unwrap();
+
+        // The light client doesn't submit transactions, so mempool is never reached
+        let (mempool_sender, _) = mpsc::channel(1);
+        let node_config = NodeConfig::default();
+        let context = Context::new(
+            ChainId::test(),
+            db.clone(),
+            mempool_sender,
+            node_config.clone(),
+        );
+        let address = attach_poem_to_runtime(&Handle::current(), context, &node_config, true)
+            .expect("Failed to attach poem to runtime");
+        let client = Client::new(Url::parse(&format!("http://{}", address)).unwrap());
+
+        (db, client, waypoint)
+    }
+
+    /// Adds the ledger state headers of a genuine response
+    fn with_state_headers(then: Then, state: &State) -> Then {
+        then.header(X_APTOS_CHAIN_ID, state.chain_id.to_string())
+            .header(X_APTOS_EPOCH, state.epoch.to_string())
+            .header(X_APTOS_LEDGER_VERSION, state.version.to_string())
+            .header(
+                X_APTOS_LEDGER_OLDEST_VERSION,
+                state.oldest_ledger_version.to_string(),
+            )
+            .header(X_APTOS_LEDGER_TIMESTAMP, state.timestamp_usecs.to_string())
+            .header(X_APTOS_BLOCK_HEIGHT, state.block_height.to_string())
+            .header(
+                X_APTOS_OLDEST_BLOCK_HEIGHT,
+                state.oldest_block_height.to_string(),
+            )
+    }
+}
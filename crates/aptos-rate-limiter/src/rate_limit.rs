@@ -5,6 +5,7 @@
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::debug;
 use aptos_metrics_core::HistogramVec;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use std::{cmp::min, collections::HashMap, fmt::Debug, hash::Hash, sync::Arc, time::Instant};
 use tokio::time::Duration;
 
@@ -202,6 +203,8 @@ pub struct Bucket {
     /// Number of requests throttled prior to next fill
     throttled_in_period: usize,
     metrics: Option<HistogramVec>,
+    /// The clock the bucket is refilled with
+    time_service: TimeService,
 }
 
 impl Bucket {
@@ -219,6 +222,7 @@ impl Bucket {
             "Bucket size must be greater than or equal to fill rate"
         );
         // Store the stringified version of the key for logging
+        let time_service = TimeService::real();
         Self {
             label,
             log_info,
@@ -226,16 +230,26 @@ impl Bucket {
             tokens: initial,
             size,
             rate,
-            last_refresh_time: Instant::now(),
+            last_refresh_time: time_service.now(),
             enabled: true,
             allowed_in_period: 0,
             throttled_in_period: 0,
             metrics,
+            time_service,
         }
     }
 
+    /// Refills the bucket based on the given clock rather than the system clock, e.g., a mock
+    /// time service in tests
+    pub fn with_time_service(mut self, time_service: TimeService) -> Self {
+        self.last_refresh_time = time_service.now();
+        self.time_service = time_service;
+        self
+    }
+
     /// A fully open rate limiter, to allow for ignoring rate limiting for tests
     pub fn open(label: String) -> Self {
+        let time_service = TimeService::real();
         Self {
             label,
             log_info: String::new(),
@@ -243,17 +257,22 @@ impl Bucket {
             tokens: std::usize::MAX,
             size: std::usize::MAX,
             rate: std::usize::MAX,
-            last_refresh_time: Instant::now(),
+            last_refresh_time: time_service.now(),
             enabled: false,
             allowed_in_period: 0,
             throttled_in_period: 0,
             metrics: None,
+            time_service,
         }
     }
 
     /// Refill tokens based on how many seconds have passed since last refresh
     pub(crate) fn refill(&mut self) {
-        let num_intervals = self.last_refresh_time.elapsed().as_secs();
+        let num_intervals = self
+            .time_service
+            .now()
+            .saturating_duration_since(self.last_refresh_time)
+            .as_secs();
         if num_intervals > 0 {
             // Log how many were throttled in the period before refill
             if self.allowed_in_period > 0 || self.throttled_in_period > 0 {
@@ -355,7 +374,7 @@ impl Bucket {
     /// all tokens are ready.  Returns `None` if it is never possible.
     pub fn time_of_tokens_needed(&self, requested: usize) -> Option<Instant> {
         if !self.enabled {
-            Some(Instant::now())
+            Some(self.time_service.now())
         } else if self.size < requested {
             // This means the batch can never succeed
             None
@@ -12,12 +12,17 @@ pub mod response;
 pub use response::Response;
 pub mod client_builder;
 pub mod state;
+pub mod stream;
 pub mod types;
 
+#[cfg(test)]
+mod tests;
+
 pub use crate::client_builder::{AptosBaseUrl, ClientBuilder};
 use crate::{
     aptos::{AptosVersion, Balance},
     error::RestError,
+    stream::{EventHandleId, ResourceUpdate, StreamConfig, StreamedEvent},
 };
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
@@ -30,15 +35,23 @@ use aptos_api_types::{
     TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
     VersionedEvent, ViewRequest,
 };
-use aptos_crypto::HashValue;
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature, ED25519_SIGNATURE_LENGTH},
+    HashValue,
+};
 use aptos_logger::{debug, info, sample, sample::SampleRate};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
     contract_event::EventWithVersion,
-    state_store::state_key::StateKey,
-    transaction::SignedTransaction,
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValueWithProof},
+    transaction::{
+        authenticator::AccountAuthenticator, RawTransaction, SignedTransaction,
+        TransactionWithLedgerInfoProof,
+    },
 };
+use futures::Stream;
 use move_core_types::language_storage::StructTag;
 use reqwest::{
     header::{ACCEPT, CONTENT_TYPE},
@@ -265,6 +278,15 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    pub async fn get_state_proof_bcs(
+        &self,
+        known_version: u64,
+    ) -> AptosResult<Response<StateProof>> {
+        let url = self.build_path(&format!("state_proof?known_version={}", known_version))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     // TODO: Remove this, just use `get_index`: https://github.com/aptos-labs/aptos-core/issues/5597.
     pub async fn get_ledger_information(&self) -> AptosResult<Response<State>> {
         let response = self.get_index_bcs().await?.map(|r| State {
@@ -394,6 +416,37 @@ impl Client {
         Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
     }
 
+    /// Simulates a transaction whose gas is paid by `fee_payer_address` instead of the sender.
+    /// Simulated transactions must not be validly signed, so every signer is only given by its
+    /// public key and signs with an all-zero signature. The prologue still checks the public keys
+    /// against the authentication keys on chain.
+    pub async fn simulate_fee_payer(
+        &self,
+        raw_txn: RawTransaction,
+        sender_public_key: Ed25519PublicKey,
+        secondary_signers: Vec<(AccountAddress, Ed25519PublicKey)>,
+        fee_payer_address: AccountAddress,
+        fee_payer_public_key: Ed25519PublicKey,
+    ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        let signature = Ed25519Signature::try_from([0u8; ED25519_SIGNATURE_LENGTH].as_ref())
+            .map_err(|err| anyhow!("Failed to create a simulation signature: {}", err))?;
+        let authenticator =
+            |public_key| AccountAuthenticator::ed25519(public_key, signature.clone());
+        let (secondary_signer_addresses, secondary_signers) = secondary_signers
+            .into_iter()
+            .map(|(address, public_key)| (address, authenticator(public_key)))
+            .unzip();
+        let txn = SignedTransaction::new_fee_payer(
+            raw_txn,
+            authenticator(sender_public_key),
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            authenticator(fee_payer_public_key),
+        );
+        self.simulate(&txn).await
+    }
+
     pub async fn submit(
         &self,
         txn: &SignedTransaction,
@@ -864,6 +917,15 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    pub async fn get_transaction_by_version_with_proof_bcs(
+        &self,
+        version: u64,
+    ) -> AptosResult<Response<TransactionWithLedgerInfoProof>> {
+        let url = self.build_path(&format!("transactions/by_version/{}/proof", version))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         version: u64,
@@ -1002,6 +1064,19 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    pub async fn get_account_resource_with_proof_bcs(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/resource/{}/proof",
+            address, resource_type
+        ))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account_resource_at_version_bcs<T: DeserializeOwned>(
         &self,
         address: AccountAddress,
@@ -1140,6 +1215,46 @@ impl Client {
         self.json(response).await
     }
 
+    /// Returns a stream following the given event handle, starting at `start_sequence`.
+    /// Once caught up the stream keeps polling for new events. Transient errors are
+    /// retried, other errors are yielded and the next polls are backed off, and a
+    /// skipped sequence number is reported as an error before the stream resumes
+    /// from the next available event. The stream only ends after yielding an error
+    /// for a request the node rejects as invalid, e.g. an unknown struct tag.
+    pub fn stream_events<T: DeserializeOwned>(
+        &self,
+        event_handle: EventHandleId,
+        start_sequence: u64,
+    ) -> impl Stream<Item = AptosResult<StreamedEvent<T>>> {
+        self.stream_events_with_config(event_handle, start_sequence, StreamConfig::default())
+    }
+
+    pub fn stream_events_with_config<T: DeserializeOwned>(
+        &self,
+        event_handle: EventHandleId,
+        start_sequence: u64,
+        config: StreamConfig,
+    ) -> impl Stream<Item = AptosResult<StreamedEvent<T>>> {
+        stream::event_stream(self.clone(), event_handle, start_sequence, config)
+    }
+
+    /// Returns a stream that polls a resource every `poll_interval` and yields its
+    /// current value first and then every time it changes. Reads served by a node
+    /// behind an already observed ledger version are ignored. Errors are handled as
+    /// in [`Client::stream_events`].
+    pub fn watch_resource<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = AptosResult<ResourceUpdate<T>>> {
+        let config = StreamConfig {
+            poll_interval,
+            ..StreamConfig::default()
+        };
+        stream::resource_watch(self.clone(), address, resource_type.to_string(), config)
+    }
+
     pub async fn get_account_events_bcs(
         &self,
         address: AccountAddress,
@@ -1263,6 +1378,20 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    pub async fn get_raw_table_item_with_proof_bcs(
+        &self,
+        table_handle: AccountAddress,
+        key: &[u8],
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let url = self.build_path(&format!("tables/{}/raw_item/proof", table_handle))?;
+        let data = json!({
+            "key": hex::encode(key),
+        });
+
+        let response = self.post_bcs(url, data).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_raw_state_value(
         &self,
         state_key: &StateKey,
@@ -0,0 +1,367 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Polling-based streams over the REST API.
+//!
+//! These helpers turn the request/response endpoints of the node into
+//! `futures::Stream`s, so consumers no longer need to write their own polling
+//! loops to follow an event handle or to detect changes to a resource. Pages
+//! are only fetched once the previously fetched items have been consumed, so a
+//! slow consumer naturally applies back-pressure to the stream.
+//!
+//! Errors are yielded on the stream rather than ending it, and the polls
+//! following an error are spaced out with an exponential backoff. Only errors
+//! for requests the node will never serve, such as a malformed struct tag,
+//! end the stream.
+
+use crate::{error::RestError, retriable, AptosResult, Client, DEFAULT_INTERVAL_DURATION};
+use anyhow::anyhow;
+use aptos_api_types::{AptosErrorCode, EventGuid, VersionedEvent};
+use aptos_logger::{debug, sample, sample::SampleRate};
+use aptos_types::account_address::AccountAddress;
+use futures::{stream, Stream};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{collections::VecDeque, marker::PhantomData, time::Duration};
+
+const DEFAULT_EVENT_PAGE_SIZE: u16 = 100;
+const DEFAULT_RETRY_WAIT_DURATION: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_ERROR_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Identifies an event handle stored in a resource: the account holding the
+/// resource, the resource's struct tag and the name of the `EventHandle` field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventHandleId {
+    pub address: AccountAddress,
+    pub struct_tag: String,
+    pub field_name: String,
+}
+
+impl EventHandleId {
+    pub fn new(
+        address: AccountAddress,
+        struct_tag: impl Into<String>,
+        field_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            address,
+            struct_tag: struct_tag.into(),
+            field_name: field_name.into(),
+        }
+    }
+}
+
+/// Tuning knobs shared by [`Client::stream_events`] and [`Client::watch_resource`].
+#[derive(Clone, Debug)]
+pub struct StreamConfig {
+    /// How long to wait before polling again when there is nothing new.
+    pub poll_interval: Duration,
+    /// Maximum number of events requested per page.
+    pub page_size: u16,
+    /// How long a single poll keeps retrying transient errors before the error
+    /// is surfaced on the stream.
+    pub retry_wait: Duration,
+    /// Upper bound of the wait before polling again after consecutive errors.
+    /// The wait starts at `poll_interval` and doubles with every error.
+    pub max_error_backoff: Duration,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_INTERVAL_DURATION,
+            page_size: DEFAULT_EVENT_PAGE_SIZE,
+            retry_wait: DEFAULT_RETRY_WAIT_DURATION,
+            max_error_backoff: DEFAULT_MAX_ERROR_BACKOFF,
+        }
+    }
+}
+
+/// An event yielded by [`Client::stream_events`], with its data deserialized.
+#[derive(Clone, Debug)]
+pub struct StreamedEvent<T> {
+    /// Version of the transaction that emitted the event.
+    pub version: u64,
+    pub guid: EventGuid,
+    pub sequence_number: u64,
+    pub data: T,
+}
+
+/// A change observed by [`Client::watch_resource`]. `value` is `None` while the
+/// resource does not exist under the account.
+#[derive(Clone, Debug)]
+pub struct ResourceUpdate<T> {
+    /// Ledger version at which the new value was read.
+    pub ledger_version: u64,
+    pub value: Option<T>,
+}
+
+pub(crate) fn event_stream<T: DeserializeOwned>(
+    client: Client,
+    handle: EventHandleId,
+    start_sequence: u64,
+    config: StreamConfig,
+) -> impl Stream<Item = AptosResult<StreamedEvent<T>>> {
+    let state = EventStreamState {
+        client,
+        handle,
+        backoff: ErrorBackoff::new(&config),
+        config,
+        next_sequence: start_sequence,
+        ledger_version: 0,
+        buffer: VecDeque::new(),
+        phantom: PhantomData,
+    };
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        let item = state.next().await;
+        let state = (!matches!(&item, Err(err) if is_fatal(err))).then_some(state);
+        Some((item, state))
+    })
+}
+
+pub(crate) fn resource_watch<T: DeserializeOwned>(
+    client: Client,
+    address: AccountAddress,
+    resource_type: String,
+    config: StreamConfig,
+) -> impl Stream<Item = AptosResult<ResourceUpdate<T>>> {
+    let state = ResourceWatchState {
+        client,
+        address,
+        resource_type,
+        backoff: ErrorBackoff::new(&config),
+        config,
+        ledger_version: 0,
+        last_value: None,
+        polled: false,
+        phantom: PhantomData,
+    };
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        let item = state.next().await;
+        let state = (!matches!(&item, Err(err) if is_fatal(err))).then_some(state);
+        Some((item, state))
+    })
+}
+
+/// Whether `err` is for a request the node will never serve, so polling again
+/// is pointless and the stream ends after yielding it.
+fn is_fatal(err: &RestError) -> bool {
+    match err {
+        RestError::Api(err) => err.status_code == StatusCode::BAD_REQUEST,
+        RestError::UrlParse(_) => true,
+        _ => false,
+    }
+}
+
+/// Spaces out the polls following failed fetches, so a consumer that keeps
+/// polling a failing stream doesn't hammer the node.
+struct ErrorBackoff {
+    initial: Duration,
+    max: Duration,
+    /// How long to wait before the next fetch, `None` if the last one succeeded.
+    wait: Option<Duration>,
+}
+
+impl ErrorBackoff {
+    fn new(config: &StreamConfig) -> Self {
+        Self {
+            initial: config.poll_interval,
+            max: config.max_error_backoff,
+            wait: None,
+        }
+    }
+
+    /// Waits out the backoff if the last fetch failed, returning whether it did.
+    async fn wait(&self) -> bool {
+        match self.wait {
+            Some(wait) => {
+                tokio::time::sleep(wait).await;
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn record<T>(&mut self, result: &AptosResult<T>) {
+        self.wait = match result {
+            Ok(_) => None,
+            Err(_) => Some(
+                self.wait
+                    .map_or(self.initial, |wait| wait.saturating_mul(2))
+                    .min(self.max),
+            ),
+        };
+    }
+}
+
+struct EventStreamState<T> {
+    client: Client,
+    handle: EventHandleId,
+    config: StreamConfig,
+    backoff: ErrorBackoff,
+    /// Sequence number of the next event to yield.
+    next_sequence: u64,
+    /// Highest ledger version observed so far, used to discard responses from
+    /// nodes that are behind the ones we already read from.
+    ledger_version: u64,
+    /// Events fetched but not yet consumed.
+    buffer: VecDeque<VersionedEvent>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> EventStreamState<T> {
+    async fn next(&mut self) -> AptosResult<StreamedEvent<T>> {
+        loop {
+            if let Some(event) = self.buffer.pop_front() {
+                let sequence_number = event.sequence_number.0;
+                if sequence_number < self.next_sequence {
+                    // Already yielded, e.g. re-fetched after a gap was reported.
+                    continue;
+                }
+                if sequence_number > self.next_sequence {
+                    // Report the gap once, then resume from the first event the
+                    // node actually returned.
+                    let err = anyhow!(
+                        "Gap in event stream {}::{}::{}: expected sequence number {}, got {}",
+                        self.handle.address,
+                        self.handle.struct_tag,
+                        self.handle.field_name,
+                        self.next_sequence,
+                        sequence_number
+                    );
+                    self.next_sequence = sequence_number;
+                    self.buffer.push_front(event);
+                    return Err(err.into());
+                }
+
+                self.next_sequence = sequence_number + 1;
+                let data = serde_json::from_value(event.data)?;
+                return Ok(StreamedEvent {
+                    version: event.version.0,
+                    guid: event.guid,
+                    sequence_number,
+                    data,
+                });
+            }
+
+            self.backoff.wait().await;
+            let page = self.fetch_page().await;
+            self.backoff.record(&page);
+            let (events, state) = page?.into_parts();
+            if state.version < self.ledger_version {
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(60)),
+                    debug!(
+                        "Ignoring stale response at version {}, already observed version {}",
+                        state.version, self.ledger_version
+                    )
+                );
+                tokio::time::sleep(self.config.poll_interval).await;
+                continue;
+            }
+            self.ledger_version = state.version;
+
+            if events.is_empty() {
+                tokio::time::sleep(self.config.poll_interval).await;
+            } else {
+                self.buffer.extend(events);
+            }
+        }
+    }
+
+    async fn fetch_page(&self) -> AptosResult<crate::Response<Vec<VersionedEvent>>> {
+        Client::try_until_ok(
+            Some(self.config.retry_wait),
+            Some(self.config.poll_interval),
+            retriable,
+            || {
+                self.client.get_account_events(
+                    self.handle.address,
+                    &self.handle.struct_tag,
+                    &self.handle.field_name,
+                    Some(self.next_sequence),
+                    Some(self.config.page_size),
+                )
+            },
+        )
+        .await
+    }
+}
+
+struct ResourceWatchState<T> {
+    client: Client,
+    address: AccountAddress,
+    resource_type: String,
+    config: StreamConfig,
+    backoff: ErrorBackoff,
+    /// Highest ledger version observed so far. Reads from nodes behind this
+    /// version are discarded so updates are never reported out of order.
+    ledger_version: u64,
+    /// Raw value of the last update yielded.
+    last_value: Option<Value>,
+    polled: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> ResourceWatchState<T> {
+    async fn next(&mut self) -> AptosResult<ResourceUpdate<T>> {
+        loop {
+            if !self.backoff.wait().await && self.polled {
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+
+            let fetched = Client::try_until_ok(
+                Some(self.config.retry_wait),
+                Some(self.config.poll_interval),
+                retriable,
+                || self.fetch(),
+            )
+            .await;
+            self.backoff.record(&fetched);
+            let (value, version) = fetched?;
+
+            if version < self.ledger_version {
+                continue;
+            }
+            self.ledger_version = version;
+
+            if self.polled && value == self.last_value {
+                continue;
+            }
+            self.polled = true;
+
+            // Recorded before deserializing, so a value that fails to
+            // deserialize is reported once rather than on every poll.
+            self.last_value = value.clone();
+            return Ok(ResourceUpdate {
+                ledger_version: version,
+                value: value.map(serde_json::from_value).transpose()?,
+            });
+        }
+    }
+
+    async fn fetch(&self) -> AptosResult<(Option<Value>, u64)> {
+        match self
+            .client
+            .get_account_resource(self.address, &self.resource_type)
+            .await
+        {
+            Ok(response) => {
+                let (resource, state) = response.into_parts();
+                Ok((resource.map(|resource| resource.data), state.version))
+            },
+            Err(RestError::Api(err))
+                if matches!(err.error.error_code, AptosErrorCode::ResourceNotFound) =>
+            {
+                match err.state {
+                    Some(state) => Ok((None, state.version)),
+                    None => Err(RestError::Api(err)),
+                }
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
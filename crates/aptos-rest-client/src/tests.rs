@@ -0,0 +1,253 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    stream::{EventHandleId, StreamConfig},
+    Client,
+};
+use aptos_api_types::{
+    mime_types::BCS_SIGNED_TRANSACTION, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH,
+    X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        authenticator::TransactionAuthenticator, EntryFunction, RawTransaction, SignedTransaction,
+        TransactionPayload,
+    },
+};
+use futures::StreamExt;
+use httpmock::{prelude::*, Then};
+use move_core_types::{ident_str, language_storage::ModuleId};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Adds the ledger state headers every successful API response carries.
+pub(crate) fn with_state_headers(then: Then) -> Then {
+    then.header(X_APTOS_CHAIN_ID, "4")
+        .header(X_APTOS_EPOCH, "1")
+        .header(X_APTOS_LEDGER_VERSION, "100")
+        .header(X_APTOS_LEDGER_OLDEST_VERSION, "0")
+        .header(X_APTOS_LEDGER_TIMESTAMP, "1000000")
+        .header(X_APTOS_BLOCK_HEIGHT, "10")
+        .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "0")
+}
+
+pub(crate) fn client(server: &MockServer) -> Client {
+    Client::new(Url::parse(&server.base_url()).unwrap())
+}
+
+const FEE_PAYER: AccountAddress = AccountAddress::new([0xFE; AccountAddress::LENGTH]);
+
+/// Matches a fee payer transaction paid by `FEE_PAYER` whose signatures are all invalid.
+fn is_fee_payer_simulation(req: &HttpMockRequest) -> bool {
+    let txn: SignedTransaction = match req.body.as_ref().map(|body| bcs::from_bytes(body)) {
+        Some(Ok(txn)) => txn,
+        _ => return false,
+    };
+    matches!(
+        txn.authenticator_ref(),
+        TransactionAuthenticator::FeePayer {
+            fee_payer_address,
+            secondary_signer_addresses,
+            ..
+        } if *fee_payer_address == FEE_PAYER && secondary_signer_addresses.len() == 1
+    ) && !txn.signature_is_valid()
+}
+
+#[tokio::test]
+async fn test_simulate_fee_payer() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/v1/transactions/simulate")
+                .header("content-type", BCS_SIGNED_TRANSACTION)
+                .matches(is_fee_payer_simulation);
+            with_state_headers(then.status(200)).json_body(json!([]));
+        })
+        .await;
+
+    let key = |seed: u8| {
+        Ed25519PrivateKey::try_from(&[seed; 32][..])
+            .unwrap()
+            .public_key()
+    };
+    let raw_txn = RawTransaction::new(
+        AccountAddress::ONE,
+        0,
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned()),
+            ident_str!("transfer").to_owned(),
+            vec![],
+            vec![],
+        )),
+        1000,
+        100,
+        u64::MAX,
+        ChainId::test(),
+    );
+    let response = client(&server)
+        .simulate_fee_payer(
+            raw_txn,
+            key(1),
+            vec![(AccountAddress::TWO, key(2))],
+            FEE_PAYER,
+            key(3),
+        )
+        .await
+        .unwrap();
+    assert!(response.inner().is_empty());
+    assert_eq!(response.state().chain_id, 4);
+    mock.assert_async().await;
+}
+
+fn deposit_events() -> EventHandleId {
+    EventHandleId::new(AccountAddress::ONE, "0x1::m::Events", "deposits")
+}
+
+fn stream_config() -> StreamConfig {
+    StreamConfig {
+        poll_interval: Duration::from_millis(50),
+        max_error_backoff: Duration::from_millis(100),
+        ..StreamConfig::default()
+    }
+}
+
+fn error_body(error_code: &str) -> Value {
+    json!({"message": "error", "error_code": error_code, "vm_error_code": null})
+}
+
+fn deposit(sequence_number: u64) -> Value {
+    json!({
+        "version": (10 + sequence_number).to_string(),
+        "guid": {"creation_number": "0", "account_address": "0x1"},
+        "sequence_number": sequence_number.to_string(),
+        "type": "0x1::m::Deposit",
+        "data": {"amount": sequence_number.to_string()},
+    })
+}
+
+#[tokio::test]
+async fn test_event_stream_pages() {
+    let server = MockServer::start_async().await;
+    let first_page = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path_contains("/events/")
+                .query_param("start", "0");
+            with_state_headers(then.status(200)).json_body(json!([deposit(0), deposit(1)]));
+        })
+        .await;
+    let second_page = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path_contains("/events/")
+                .query_param("start", "2");
+            with_state_headers(then.status(200)).json_body(json!([deposit(2)]));
+        })
+        .await;
+
+    let events: Vec<_> = client(&server)
+        .stream_events_with_config::<Value>(deposit_events(), 0, stream_config())
+        .take(3)
+        .collect()
+        .await;
+    for (sequence_number, event) in events.into_iter().enumerate() {
+        let event = event.unwrap();
+        assert_eq!(event.sequence_number, sequence_number as u64);
+        assert_eq!(event.version, 10 + sequence_number as u64);
+        assert_eq!(event.data, json!({"amount": sequence_number.to_string()}));
+    }
+    first_page.assert_async().await;
+    second_page.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_event_stream_backs_off_after_errors() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/events/");
+            then.status(404).json_body(error_body("account_not_found"));
+        })
+        .await;
+
+    let start = Instant::now();
+    let events: Vec<_> = client(&server)
+        .stream_events_with_config::<Value>(deposit_events(), 0, stream_config())
+        .take(4)
+        .collect()
+        .await;
+    assert!(events.iter().all(|event| event.is_err()));
+    // Waits of 50ms, 100ms and, capped, 100ms again between the four polls.
+    assert!(start.elapsed() >= Duration::from_millis(250));
+    mock.assert_hits_async(4).await;
+}
+
+#[tokio::test]
+async fn test_event_stream_ends_on_bad_request() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/events/");
+            then.status(400).json_body(error_body("invalid_input"));
+        })
+        .await;
+
+    let events: Vec<_> = client(&server)
+        .stream_events_with_config::<Value>(deposit_events(), 0, stream_config())
+        .collect()
+        .await;
+    assert_eq!(events.len(), 1);
+    assert!(events[0].is_err());
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_resource_watch_yields_changes() {
+    let server = MockServer::start_async().await;
+    let counter = |value: &str| json!({"type": "0x1::m::Counter", "data": {"value": value}});
+    let mut mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            with_state_headers(then.status(200)).json_body(counter("1"));
+        })
+        .await;
+
+    let mut updates = Box::pin(client(&server).watch_resource::<Value>(
+        AccountAddress::ONE,
+        "0x1::m::Counter",
+        Duration::from_millis(50),
+    ));
+    let update = updates.next().await.unwrap().unwrap();
+    assert_eq!(update.value, Some(json!({"value": "1"})));
+
+    // Polls returning the same value yield nothing.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), updates.next())
+            .await
+            .is_err()
+    );
+    assert!(mock.hits_async().await > 1);
+
+    mock.delete_async().await;
+    mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            with_state_headers(then.status(200)).json_body(counter("2"));
+        })
+        .await;
+    // Polls made while no mock was registered fail, and are skipped.
+    let update = loop {
+        if let Ok(update) = updates.next().await.unwrap() {
+            break update;
+        }
+    };
+    assert_eq!(update.value, Some(json!({"value": "2"})));
+    mock.assert_async().await;
+}
@@ -282,6 +282,9 @@ impl From<RestError> for ApiError {
                 },
                 AptosErrorCode::VersionPruned => ApiError::VersionPruned(Some(err.error.message)),
                 AptosErrorCode::BlockPruned => ApiError::BlockPruned(Some(err.error.message)),
+                AptosErrorCode::StateValueNotReplicated => {
+                    ApiError::InternalError(Some(err.error.message))
+                },
                 AptosErrorCode::InvalidInput => ApiError::InvalidInput(Some(err.error.message)),
                 AptosErrorCode::InvalidTransactionUpdate => {
                     ApiError::InvalidInput(Some(err.error.message))
@@ -22,7 +22,7 @@ use aptos_crypto::{
     x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
 use aptos_debugger::AptosDebugger;
-use aptos_gas_profiling::FrameName;
+use aptos_gas_profiling::{FrameName, GasReport};
 use aptos_global_constants::adjust_gas_headroom;
 use aptos_keygen::KeyGen;
 use aptos_logger::Level;
@@ -1349,6 +1349,13 @@ pub struct TransactionOptions {
     /// flamegraphs that reflect the gas usage.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// JSON gas report from an earlier `--profile-gas` run to compare against, e.g. from before
+    /// a contract change. A diff report is generated next to the new report.
+    ///
+    /// Only used together with `--profile-gas`.
+    #[clap(long, parse(from_os_str))]
+    pub(crate) profile_gas_baseline: Option<PathBuf>,
 }
 
 impl TransactionOptions {
@@ -1643,6 +1650,52 @@ impl TransactionOptions {
             },
         }
 
+        // Generate the gas reports.
+        let report = gas_log.to_report();
+        create_dir!();
+        let write_report = |extension: &str, contents: String| -> CliTypedResult<PathBuf> {
+            let report_file_path = Path::join(dir, format!("{}.{}", raw_file_name, extension));
+            std::fs::write(&report_file_path, contents).map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Failed to write gas report to file {} : {:?}",
+                    report_file_path.display(),
+                    err
+                ))
+            })?;
+            Ok(report_file_path)
+        };
+        write_report("report.md", report.to_markdown())?;
+        write_report("report.html", report.to_html())?;
+        let json_path = write_report("report.json", report.to_json()?)?;
+        println!(
+            "Gas reports saved to {}",
+            Path::join(dir, format!("{}.report.{{md,html,json}}", raw_file_name)).display()
+        );
+
+        // Compare against the baseline report, if any.
+        if let Some(baseline_path) = &self.profile_gas_baseline {
+            let baseline: GasReport = serde_json::from_slice(&read_from_file(baseline_path)?)
+                .map_err(|err| {
+                    CliError::UnableToParse(
+                        "gas report",
+                        format!("{}: {}", baseline_path.display(), err),
+                    )
+                })?;
+            let diff = baseline.diff(&report);
+            write_report("diff.md", diff.to_markdown())?;
+            write_report("diff.html", diff.to_html())?;
+            write_report("diff.json", diff.to_json()?)?;
+            println!(
+                "Gas report diff against {} saved to {}",
+                baseline_path.display(),
+                Path::join(dir, format!("{}.diff.{{md,html,json}}", raw_file_name)).display()
+            );
+        }
+        println!(
+            "Pass --profile-gas-baseline {} to compare later runs against this one",
+            json_path.display()
+        );
+
         println!();
 
         // Generate the transaction summary
@@ -88,6 +88,7 @@ pub enum MoveTool {
     Disassemble(Disassemble),
     Document(DocumentPackage),
     Download(DownloadPackage),
+    GenerateBindings(GenerateBindings),
     Init(InitPackage),
     List(ListPackage),
     Prove(ProvePackage),
@@ -116,6 +117,7 @@ impl MoveTool {
             MoveTool::Disassemble(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
+            MoveTool::GenerateBindings(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
@@ -602,6 +604,57 @@ impl CliCommand<&'static str> for DocumentPackage {
     }
 }
 
+/// Generates typed Rust bindings for a Move package
+///
+/// The bindings contain builders for the entry functions, callers for the view functions, and
+/// serde structs for the resources and events of the package. They depend on the `aptos-sdk`,
+/// `serde` and `serde_json` crates.
+#[derive(Parser)]
+pub struct GenerateBindings {
+    #[clap(flatten)]
+    move_options: MovePackageDir,
+
+    /// Path of the Rust file to write the bindings to
+    #[clap(long, parse(from_os_str))]
+    output_file: PathBuf,
+
+    #[clap(flatten)]
+    prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for GenerateBindings {
+    fn command_name(&self) -> &'static str {
+        "GenerateBindings"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let GenerateBindings {
+            move_options,
+            output_file,
+            prompt_options,
+        } = self;
+        check_if_file_exists(output_file.as_path(), prompt_options)?;
+        let build_options = BuildOptions {
+            with_srcs: false,
+            with_abis: true,
+            with_source_maps: false,
+            with_error_map: false,
+            with_docs: false,
+            install_dir: move_options.output_dir.clone(),
+            named_addresses: move_options.named_addresses(),
+            docgen_options: None,
+            skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+            bytecode_version: move_options.bytecode_version,
+        };
+        let package = BuiltPackage::build(move_options.get_package_path()?, build_options)?;
+        let mut bindings = vec![];
+        package.generate_rust_bindings(&mut bindings)?;
+        write_to_file(output_file.as_path(), "Rust bindings", &bindings)?;
+        Ok(output_file.display().to_string())
+    }
+}
+
 #[derive(Parser)]
 pub struct IncludedArtifactsArgs {
     /// Artifacts to be generated when building the package
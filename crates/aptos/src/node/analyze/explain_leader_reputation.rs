@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::fetch_metadata::EpochInfo;
+use anyhow::{anyhow, Result};
+use aptos_consensus::leader_reputation_explorer::{
+    LeaderReputationExplorer, ReputationStatus, RoundElection, RoundOutcome, VersionedNewBlockEvent,
+};
+use aptos_crypto::HashValue;
+use aptos_rest_client::Client as RestClient;
+use aptos_types::{
+    account_address::AccountAddress, account_config::CORE_CODE_ADDRESS,
+    on_chain_config::OnChainConsensusConfig,
+};
+use serde::Serialize;
+use std::{collections::HashMap, io::Write};
+
+const MAX_CONCURRENT_FETCHES: usize = 32;
+
+/// Single row of the CSV export, one per round and candidate
+#[derive(Serialize)]
+struct CandidateRow {
+    epoch: u64,
+    round: u64,
+    outcome: RoundOutcome,
+    recorded_proposer: Option<AccountAddress>,
+    elected: AccountAddress,
+    mismatch: bool,
+    window_len: usize,
+    window_start_epoch: Option<u64>,
+    window_start_round: Option<u64>,
+    window_end_round: Option<u64>,
+    candidate: AccountAddress,
+    voting_power: u64,
+    votes: u32,
+    proposals: u32,
+    failed_proposals: u32,
+    status: ReputationStatus,
+    reputation_weight: u64,
+    stake_weight: u128,
+    probability: f64,
+}
+
+pub struct ExplainLeaderReputation {}
+
+impl ExplainLeaderReputation {
+    /// Consensus config the epoch starting at the given version ran with. Falls back to the
+    /// latest one if state at that version is pruned.
+    pub async fn fetch_consensus_config(
+        client: &RestClient,
+        version: u64,
+    ) -> Result<OnChainConsensusConfig> {
+        const RESOURCE: &str = "0x1::consensus_config::ConsensusConfig";
+        let config_bytes = match client
+            .get_account_resource_at_version_bcs::<Vec<u8>>(CORE_CODE_ADDRESS, RESOURCE, version)
+            .await
+        {
+            Ok(response) => response.into_inner(),
+            Err(e) => {
+                println!(
+                    "Couldn't fetch consensus config at version {}, using the latest one: {:?}",
+                    version, e
+                );
+                client
+                    .get_account_resource_bcs::<Vec<u8>>(CORE_CODE_ADDRESS, RESOURCE)
+                    .await?
+                    .into_inner()
+            },
+        };
+        Ok(bcs::from_bytes(&config_bytes)?)
+    }
+
+    /// Accumulator root hashes at the given versions, elections are seeded with them.
+    pub async fn fetch_root_hashes(
+        client: &RestClient,
+        versions: &[u64],
+    ) -> Result<HashMap<u64, HashValue>> {
+        let mut result = HashMap::new();
+        for chunk in versions.chunks(MAX_CONCURRENT_FETCHES) {
+            let hashes = futures::future::try_join_all(chunk.iter().map(|version| async move {
+                let transaction = client
+                    .get_transaction_by_version(*version)
+                    .await?
+                    .into_inner();
+                let root_hash: HashValue = transaction
+                    .transaction_info()
+                    .map_err(|e| anyhow!("No transaction info at version {}: {}", version, e))?
+                    .accumulator_root_hash
+                    .into();
+                Ok::<_, anyhow::Error>((*version, root_hash))
+            }))
+            .await?;
+            result.extend(hashes);
+        }
+        Ok(result)
+    }
+
+    /// Recomputes the proposer elections of every round of the epoch. previous_epochs are
+    /// the epochs fetched before it, their blocks are used as the history of the first rounds.
+    pub async fn explain_epoch(
+        client: &RestClient,
+        epoch_info: &EpochInfo,
+        previous_epochs: &[EpochInfo],
+        onchain_config: &OnChainConsensusConfig,
+    ) -> Result<Vec<RoundElection>> {
+        let explorer = LeaderReputationExplorer::new(
+            epoch_info.epoch,
+            &epoch_info
+                .validators
+                .iter()
+                .map(|v| (v.address, v.voting_power))
+                .collect::<Vec<_>>(),
+            onchain_config,
+            &previous_epochs
+                .iter()
+                .map(|e| (e.epoch, e.validators.iter().map(|v| v.address).collect()))
+                .collect(),
+        )?;
+        let blocks: Vec<_> = previous_epochs
+            .iter()
+            .chain(std::iter::once(epoch_info))
+            .flat_map(|e| e.blocks.iter())
+            .map(|block| VersionedNewBlockEvent {
+                event: block.event.clone(),
+                version: block.version,
+            })
+            .collect();
+
+        let root_hashes = if explorer.uses_root_hash() {
+            // Each round is seeded with the root hash at the newest block of its window, which
+            // is the last block before the epoch for the first rounds, or genesis if there's none
+            let mut versions: Vec<_> = epoch_info.blocks.iter().map(|b| b.version).collect();
+            versions.push(
+                previous_epochs
+                    .iter()
+                    .rev()
+                    .find_map(|e| e.blocks.last())
+                    .map_or(0, |b| b.version),
+            );
+            Self::fetch_root_hashes(client, &versions).await?
+        } else {
+            HashMap::new()
+        };
+        explorer.explain_epoch(&blocks, &root_hashes)
+    }
+
+    /// Keeps only the candidates in pool_addresses, keeps all of them if it is empty.
+    pub fn filter_candidates(elections: &mut [RoundElection], pool_addresses: &[AccountAddress]) {
+        if pool_addresses.is_empty() {
+            return;
+        }
+        for election in elections {
+            election
+                .candidates
+                .retain(|candidate| pool_addresses.contains(&candidate.author));
+        }
+    }
+
+    pub fn write_csv<W: Write>(elections: &[RoundElection], writer: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for election in elections {
+            for candidate in &election.candidates {
+                writer.serialize(CandidateRow {
+                    epoch: election.epoch,
+                    round: election.round,
+                    outcome: election.outcome,
+                    recorded_proposer: election.recorded_proposer,
+                    elected: election.elected,
+                    mismatch: election.is_mismatch(),
+                    window_len: election.window_len,
+                    window_start_epoch: election.window_start.map(|(epoch, _)| epoch),
+                    window_start_round: election.window_start.map(|(_, round)| round),
+                    window_end_round: election.window_end.map(|(_, round)| round),
+                    candidate: candidate.author,
+                    voting_power: candidate.voting_power,
+                    votes: candidate.votes,
+                    proposals: candidate.proposals,
+                    failed_proposals: candidate.failed_proposals,
+                    status: candidate.status,
+                    reputation_weight: candidate.reputation_weight,
+                    stake_weight: candidate.stake_weight,
+                    probability: candidate.probability,
+                })?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_json<W: Write>(elections: &[RoundElection], writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, elections)?;
+        Ok(())
+    }
+
+    /// Prints how rounds went in the epoch, and for every exported candidate how often it was
+    /// elected compared to its expected share, and why its weight was reduced.
+    pub fn print_summary(epoch: u64, elections: &[RoundElection]) {
+        let count = |outcome| elections.iter().filter(|e| e.outcome == outcome).count();
+        println!(
+            "Epoch {}: {} rounds, {} committed, {} failed, {} unknown, {} not matching the recorded proposer",
+            epoch,
+            elections.len(),
+            count(RoundOutcome::Committed),
+            count(RoundOutcome::Failed),
+            count(RoundOutcome::Unknown),
+            elections.iter().filter(|e| e.is_mismatch()).count(),
+        );
+
+        let mut per_candidate: HashMap<AccountAddress, (usize, f64, usize, usize)> = HashMap::new();
+        for election in elections {
+            for candidate in &election.candidates {
+                let entry = per_candidate.entry(candidate.author).or_default();
+                entry.0 += usize::from(election.elected == candidate.author);
+                entry.1 += candidate.probability;
+                entry.2 += usize::from(candidate.status == ReputationStatus::Failed);
+                entry.3 += usize::from(candidate.status == ReputationStatus::Inactive);
+            }
+        }
+        let mut per_candidate: Vec<_> = per_candidate.into_iter().collect();
+        per_candidate.sort_by_key(|(address, _)| *address);
+        for (address, (elected, expected, failed, inactive)) in per_candidate {
+            println!(
+                "  {}: elected {} times (expected {:.1}), weighted as failed in {} and as inactive in {} rounds",
+                address, elected, expected, failed, inactive
+            );
+        }
+    }
+}
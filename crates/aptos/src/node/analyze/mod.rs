@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod analyze_validators;
+pub mod explain_leader_reputation;
 pub mod fetch_metadata;
@@ -16,6 +16,7 @@ use crate::{
     genesis::git::from_yaml,
     node::analyze::{
         analyze_validators::{AnalyzeValidators, ValidatorStats},
+        explain_leader_reputation::ExplainLeaderReputation,
         fetch_metadata::FetchMetadata,
     },
 };
@@ -73,6 +74,7 @@ const SECS_TO_MICROSECS: u64 = 1_000_000;
 /// identify issues with nodes, and show related information.
 #[derive(Parser)]
 pub enum NodeTool {
+    AnalyzeLeaderReputation(AnalyzeLeaderReputation),
     AnalyzeValidatorPerformance(AnalyzeValidatorPerformance),
     BootstrapDb(BootstrapDb),
     CheckNetworkConnectivity(CheckNetworkConnectivity),
@@ -94,6 +96,7 @@ impl NodeTool {
     pub async fn execute(self) -> CliResult {
         use NodeTool::*;
         match self {
+            AnalyzeLeaderReputation(tool) => tool.execute_serialized().await,
             AnalyzeValidatorPerformance(tool) => tool.execute_serialized().await,
             BootstrapDb(tool) => {
                 tool.execute_serialized_with_logging_level(Level::Info)
@@ -1484,6 +1487,117 @@ impl CliCommand<()> for AnalyzeValidatorPerformance {
     }
 }
 
+/// Explain leader reputation proposer elections
+///
+/// Replays the NewBlockEvents of each epoch and recomputes the proposer election of every
+/// round the same way consensus does. Exports, for every round and candidate, the reputation
+/// weight, the failed proposals, proposals and votes within the window it comes from, the
+/// voting power and the resulting probability of being elected.
+#[derive(Parser)]
+pub struct AnalyzeLeaderReputation {
+    /// First epoch to analyze
+    ///
+    /// Defaults to the last full epoch. The epoch before it is fetched as well,
+    /// as the history of its first rounds.
+    #[clap(long, default_value = "-2")]
+    pub start_epoch: i64,
+
+    /// Last epoch to analyze
+    ///
+    /// Defaults to the latest epoch
+    #[clap(long)]
+    pub end_epoch: Option<i64>,
+
+    /// Filter of stake pool addresses to export
+    ///
+    /// Defaults to all stake pool addresses
+    #[clap(long, multiple_values = true, parse(try_from_str=crate::common::types::load_account_arg))]
+    pub pool_addresses: Vec<AccountAddress>,
+
+    /// File to export the elections to
+    #[clap(long, parse(from_os_str))]
+    pub output_file: PathBuf,
+
+    /// Format of the export: [Csv, Json]
+    #[clap(arg_enum, long, default_value = "csv")]
+    pub(crate) output_format: ExportFormat,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[derive(PartialEq, Eq, clap::ArgEnum, Clone, Copy)]
+pub enum ExportFormat {
+    /// One row per round and candidate
+    Csv,
+    /// One object per round, with all candidates nested in it
+    Json,
+}
+
+#[async_trait]
+impl CliCommand<()> for AnalyzeLeaderReputation {
+    fn command_name(&self) -> &'static str {
+        "AnalyzeLeaderReputation"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let client = self.rest_options.client(&self.profile_options)?;
+
+        let current_epoch = client.get_ledger_information().await?.into_inner().epoch;
+        let start_epoch = if self.start_epoch < 0 {
+            current_epoch as i64 + self.start_epoch + 1
+        } else {
+            self.start_epoch
+        };
+        let epochs = FetchMetadata::fetch_new_block_events(
+            &client,
+            Some(std::cmp::max(start_epoch - 1, 2)),
+            self.end_epoch,
+        )
+        .await?;
+
+        let mut elections = vec![];
+        for (index, epoch_info) in epochs.iter().enumerate() {
+            let first_block = match epoch_info.blocks.first() {
+                Some(first_block) if epoch_info.epoch as i64 >= start_epoch => first_block,
+                _ => continue,
+            };
+            let onchain_config =
+                ExplainLeaderReputation::fetch_consensus_config(&client, first_block.version)
+                    .await?;
+            let mut epoch_elections = ExplainLeaderReputation::explain_epoch(
+                &client,
+                epoch_info,
+                &epochs[..index],
+                &onchain_config,
+            )
+            .await?;
+            ExplainLeaderReputation::filter_candidates(&mut epoch_elections, &self.pool_addresses);
+            ExplainLeaderReputation::print_summary(epoch_info.epoch, &epoch_elections);
+            elections.append(&mut epoch_elections);
+        }
+
+        if elections.is_empty() {
+            println!("No data found for given input");
+            return Ok(());
+        }
+        let file = std::fs::File::create(&self.output_file)
+            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
+        match self.output_format {
+            ExportFormat::Csv => ExplainLeaderReputation::write_csv(&elections, file)?,
+            ExportFormat::Json => ExplainLeaderReputation::write_json(&elections, file)?,
+        }
+        println!(
+            "Exported {} rounds to {}",
+            elections.len(),
+            self.output_file.display()
+        );
+        Ok(())
+    }
+}
+
 /// Bootstrap AptosDB from a backup
 ///
 /// Enables users to load from a backup to catch their node's DB up to a known state.
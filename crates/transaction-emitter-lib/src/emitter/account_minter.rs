@@ -304,7 +304,7 @@ impl<'t> AccountMinter<'t> {
                             self.source_account
                         },
                         coins_per_seed_account,
-                        account.public_key(),
+                        account.public_key().unwrap(),
                         txn_factory,
                     )
                 })
@@ -358,7 +358,7 @@ impl<'t> AccountMinter<'t> {
             let txn = create_and_fund_account_request(
                 self.source_account,
                 coins_for_source,
-                new_source_account.public_key(),
+                new_source_account.public_key()?,
                 &self.txn_factory,
             );
             if let Err(e) = txn_executor.execute_transactions(&[txn]).await {
@@ -432,7 +432,7 @@ where
                     create_and_fund_account_request(
                         &mut source_account,
                         coins_per_new_account,
-                        account.public_key(),
+                        account.public_key().unwrap(),
                         txn_factory,
                     )
                 })
@@ -564,20 +564,25 @@ pub fn convert_account_signature(
         AccountSignature::MultiEd25519Signature(_) => {
             transaction::account_signature::Type::MultiEd25519
         },
+        // Not in the protobuf schema yet.
+        AccountSignature::Secp256k1EcdsaSignature(_) => {
+            transaction::account_signature::Type::Unspecified
+        },
     };
     let signature = match account_signature {
-        AccountSignature::Ed25519Signature(s) => {
-            transaction::account_signature::Signature::Ed25519(convert_ed25519_signature(s))
-        },
+        AccountSignature::Ed25519Signature(s) => Some(
+            transaction::account_signature::Signature::Ed25519(convert_ed25519_signature(s)),
+        ),
         AccountSignature::MultiEd25519Signature(s) => {
-            transaction::account_signature::Signature::MultiEd25519(
+            Some(transaction::account_signature::Signature::MultiEd25519(
                 convert_multi_ed25519_signature(s),
-            )
+            ))
         },
+        AccountSignature::Secp256k1EcdsaSignature(_) => None,
     };
     transaction::AccountSignature {
         r#type: r#type as i32,
-        signature: Some(signature),
+        signature,
     }
 }
 
@@ -594,16 +599,19 @@ pub fn convert_transaction_signature(
             transaction::signature::Type::MultiEd25519
         },
         TransactionSignature::MultiAgentSignature(_) => transaction::signature::Type::MultiAgent,
+        // Not in the protobuf schema yet.
+        TransactionSignature::Secp256k1EcdsaSignature(_)
+        | TransactionSignature::FeePayerSignature(_) => transaction::signature::Type::Unspecified,
     };
 
     let signature = match signature {
-        TransactionSignature::Ed25519Signature(s) => {
-            transaction::signature::Signature::Ed25519(convert_ed25519_signature(s))
-        },
-        TransactionSignature::MultiEd25519Signature(s) => {
-            transaction::signature::Signature::MultiEd25519(convert_multi_ed25519_signature(s))
-        },
-        TransactionSignature::MultiAgentSignature(s) => {
+        TransactionSignature::Ed25519Signature(s) => Some(
+            transaction::signature::Signature::Ed25519(convert_ed25519_signature(s)),
+        ),
+        TransactionSignature::MultiEd25519Signature(s) => Some(
+            transaction::signature::Signature::MultiEd25519(convert_multi_ed25519_signature(s)),
+        ),
+        TransactionSignature::MultiAgentSignature(s) => Some(
             transaction::signature::Signature::MultiAgent(transaction::MultiAgentSignature {
                 sender: Some(convert_account_signature(&s.sender)),
                 secondary_signer_addresses: s
@@ -616,13 +624,15 @@ pub fn convert_transaction_signature(
                     .iter()
                     .map(convert_account_signature)
                     .collect(),
-            })
-        },
+            }),
+        ),
+        TransactionSignature::Secp256k1EcdsaSignature(_)
+        | TransactionSignature::FeePayerSignature(_) => None,
     };
 
     Some(transaction::Signature {
         r#type: r#type as i32,
-        signature: Some(signature),
+        signature,
     })
 }
 
@@ -18,6 +18,7 @@ export enum AptosErrorCode {
     STATE_VALUE_NOT_FOUND = 'state_value_not_found',
     VERSION_PRUNED = 'version_pruned',
     BLOCK_PRUNED = 'block_pruned',
+    STATE_VALUE_NOT_REPLICATED = 'state_value_not_replicated',
     INVALID_INPUT = 'invalid_input',
     INVALID_TRANSACTION_UPDATE = 'invalid_transaction_update',
     SEQUENCE_NUMBER_TOO_OLD = 'sequence_number_too_old',
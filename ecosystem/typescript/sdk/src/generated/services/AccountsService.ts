@@ -7,6 +7,7 @@ import type { IdentifierWrapper } from '../models/IdentifierWrapper';
 import type { MoveModuleBytecode } from '../models/MoveModuleBytecode';
 import type { MoveResource } from '../models/MoveResource';
 import type { MoveStructTag } from '../models/MoveStructTag';
+import type { MoveValue } from '../models/MoveValue';
 import type { StateKeyWrapper } from '../models/StateKeyWrapper';
 import type { U64 } from '../models/U64';
 
@@ -164,6 +165,33 @@ export class AccountsService {
         });
     }
 
+    /**
+     * Get account resource with proof
+     * Retrieves an individual resource from a given account at the latest ledger version,
+     * together with the proof of its value (or of its absence) against the latest ledger info
+     * with signatures. The response is a BCS encoded `StateValueWithProof`, which can be verified
+     * by clients that don't trust the node, e.g., light clients.
+     *
+     * Only BCS is supported as an AcceptType.
+     * @param address Address of account with or without a `0x` prefix
+     * @param resourceType Name of struct to retrieve e.g. `0x1::account::Account`
+     * @returns MoveValue
+     * @throws ApiError
+     */
+    public getAccountResourceWithProof(
+        address: Address,
+        resourceType: MoveStructTag,
+    ): CancelablePromise<MoveValue> {
+        return this.httpRequest.request({
+            method: 'GET',
+            url: '/accounts/{address}/resource/{resource_type}/proof',
+            path: {
+                'address': address,
+                'resource_type': resourceType,
+            },
+        });
+    }
+
     /**
      * Get account module
      * Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -3,6 +3,8 @@
 /* eslint-disable */
 import type { HealthCheckSuccess } from '../models/HealthCheckSuccess';
 import type { IndexResponse } from '../models/IndexResponse';
+import type { MoveValue } from '../models/MoveValue';
+import type { U64 } from '../models/U64';
 
 import type { CancelablePromise } from '../core/CancelablePromise';
 import type { BaseHttpRequest } from '../core/BaseHttpRequest';
@@ -66,4 +68,28 @@ export class GeneralService {
         });
     }
 
+    /**
+     * Get state proof
+     * Get the proof of the latest ledger info with signatures from the given known version: the
+     * epoch change proof from the epoch of the known version to the latest epoch, and the
+     * consistency proof of the transaction accumulator. Light clients use it to move their
+     * trusted state forward without trusting the node.
+     *
+     * Only BCS is supported as an AcceptType.
+     * @param knownVersion Ledger version already trusted by the client
+     * @returns MoveValue
+     * @throws ApiError
+     */
+    public getStateProof(
+        knownVersion: U64,
+    ): CancelablePromise<MoveValue> {
+        return this.httpRequest.request({
+            method: 'GET',
+            url: '/state_proof',
+            query: {
+                'known_version': knownVersion,
+            },
+        });
+    }
+
 }
@@ -90,4 +90,33 @@ export class TablesService {
         });
     }
 
+    /**
+     * Get raw table item with proof
+     * Get a table item from the table identified by {table_handle} in the path and the "key"
+     * (RawTableItemRequest) provided in the request body, at the latest ledger version, together
+     * with the proof of its value (or of its absence) against the latest ledger info with
+     * signatures. The response is a BCS encoded `StateValueWithProof`, which can be verified by
+     * clients that don't trust the node, e.g., light clients.
+     *
+     * Only BCS is supported as an AcceptType.
+     * @param tableHandle Table handle hex encoded 32-byte string
+     * @param requestBody
+     * @returns MoveValue
+     * @throws ApiError
+     */
+    public getRawTableItemWithProof(
+        tableHandle: Address,
+        requestBody: RawTableItemRequest,
+    ): CancelablePromise<MoveValue> {
+        return this.httpRequest.request({
+            method: 'POST',
+            url: '/tables/{table_handle}/raw_item/proof',
+            path: {
+                'table_handle': tableHandle,
+            },
+            body: requestBody,
+            mediaType: 'application/json',
+        });
+    }
+
 }
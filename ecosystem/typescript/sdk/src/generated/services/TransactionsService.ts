@@ -6,6 +6,7 @@ import type { EncodeSubmissionRequest } from '../models/EncodeSubmissionRequest'
 import type { GasEstimation } from '../models/GasEstimation';
 import type { HashValue } from '../models/HashValue';
 import type { HexEncodedBytes } from '../models/HexEncodedBytes';
+import type { MoveValue } from '../models/MoveValue';
 import type { PendingTransaction } from '../models/PendingTransaction';
 import type { SubmitTransactionRequest } from '../models/SubmitTransactionRequest';
 import type { Transaction } from '../models/Transaction';
@@ -133,6 +134,30 @@ export class TransactionsService {
         });
     }
 
+    /**
+     * Get transaction by version with proof
+     * Retrieves a transaction by a given version, with its events and the proof of both against
+     * the latest ledger info with signatures. The response is a BCS encoded
+     * `TransactionWithLedgerInfoProof`, which can be verified by clients that don't trust the
+     * node, e.g., light clients. If the version has been pruned, a 410 will be returned.
+     *
+     * Only BCS is supported as an AcceptType.
+     * @param txnVersion Version of transaction to retrieve
+     * @returns MoveValue
+     * @throws ApiError
+     */
+    public getTransactionByVersionWithProof(
+        txnVersion: U64,
+    ): CancelablePromise<MoveValue> {
+        return this.httpRequest.request({
+            method: 'GET',
+            url: '/transactions/by_version/{txn_version}/proof',
+            path: {
+                'txn_version': txnVersion,
+            },
+        });
+    }
+
     /**
      * Get account transactions
      * Retrieves on-chain committed transactions from an account. If the start
@@ -0,0 +1,146 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::{
+    block_executor::partitioner::{ShardId, TxnIndex},
+    transaction::analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+};
+use std::collections::HashMap;
+
+/// Identifies a storage location that is accessed by more than one transaction, and written by
+/// at least one of them, i.e. a location that can cause a conflict.
+pub(crate) type LocationId = usize;
+
+/// The transactions of a block, indexed by the storage locations they conflict on. Two
+/// transactions conflict if one of them writes a location the other one reads or writes.
+/// Instead of materializing an edge for every pair of conflicting transactions (which is
+/// quadratic for hot locations), the graph is kept as a bipartite graph between transactions
+/// and locations.
+pub(crate) struct ConflictGraph {
+    /// The transactions accessing each location, in block order, and whether they write it.
+    location_accesses: Vec<Vec<(TxnIndex, bool)>>,
+    /// The conflicting locations accessed by each transaction.
+    txn_locations: Vec<Vec<LocationId>>,
+}
+
+impl ConflictGraph {
+    pub(crate) fn new(txns: &[AnalyzedTransaction]) -> Self {
+        let mut accesses_by_location: HashMap<&StorageLocation, Vec<(TxnIndex, bool)>> =
+            HashMap::new();
+        for (txn_idx, txn) in txns.iter().enumerate() {
+            for location in txn.read_hints() {
+                accesses_by_location
+                    .entry(location)
+                    .or_default()
+                    .push((txn_idx, false));
+            }
+            for location in txn.write_hints() {
+                accesses_by_location
+                    .entry(location)
+                    .or_default()
+                    .push((txn_idx, true));
+            }
+        }
+
+        let mut location_accesses = vec![];
+        let mut txn_locations = vec![vec![]; txns.len()];
+        for (_, mut accesses) in accesses_by_location {
+            let has_writer = accesses.iter().any(|(_, is_write)| *is_write);
+            if !has_writer || accesses.len() < 2 {
+                continue;
+            }
+            accesses.sort_unstable();
+            let location_id = location_accesses.len();
+            for (txn_idx, _) in &accesses {
+                txn_locations[*txn_idx].push(location_id);
+            }
+            location_accesses.push(accesses);
+        }
+
+        Self {
+            location_accesses,
+            txn_locations,
+        }
+    }
+
+    pub(crate) fn num_txns(&self) -> usize {
+        self.txn_locations.len()
+    }
+
+    pub(crate) fn txn_locations(&self, txn_idx: TxnIndex) -> &[LocationId] {
+        &self.txn_locations[txn_idx]
+    }
+
+    /// Returns the sets of transactions that transitively conflict with each other. Each
+    /// component is in block order, and components are ordered by their first transaction.
+    pub(crate) fn connected_components(&self) -> Vec<Vec<TxnIndex>> {
+        let mut parents: Vec<TxnIndex> = (0..self.num_txns()).collect();
+        for accesses in &self.location_accesses {
+            let (first, _) = accesses[0];
+            for (txn_idx, _) in &accesses[1..] {
+                let root = find_root(&mut parents, *txn_idx);
+                let first_root = find_root(&mut parents, first);
+                parents[root] = first_root;
+            }
+        }
+
+        let mut component_by_root = HashMap::new();
+        let mut components: Vec<Vec<TxnIndex>> = vec![];
+        for txn_idx in 0..self.num_txns() {
+            let root = find_root(&mut parents, txn_idx);
+            let component = *component_by_root.entry(root).or_insert_with(|| {
+                components.push(vec![]);
+                components.len() - 1
+            });
+            components[component].push(txn_idx);
+        }
+        components
+    }
+
+    /// Returns the number of transactions that conflict with a transaction assigned to a
+    /// different shard, i.e. the transactions that have a cross-shard dependency.
+    pub(crate) fn num_txns_with_cross_shard_conflicts(&self, shard_ids: &[ShardId]) -> usize {
+        let num_shards = shard_ids.iter().max().map_or(0, |max| max + 1);
+        let mut has_conflict = vec![false; self.num_txns()];
+        for accesses in &self.location_accesses {
+            let mut writers_per_shard = vec![0; num_shards];
+            let mut accesses_per_shard = vec![0; num_shards];
+            for (txn_idx, is_write) in accesses {
+                let shard_id = shard_ids[*txn_idx];
+                accesses_per_shard[shard_id] += 1;
+                if *is_write {
+                    writers_per_shard[shard_id] += 1;
+                }
+            }
+            let num_writers: usize = writers_per_shard.iter().sum();
+
+            for (txn_idx, is_write) in accesses {
+                let shard_id = shard_ids[*txn_idx];
+                let writers_in_other_shards = num_writers - writers_per_shard[shard_id];
+                let accesses_in_other_shards = accesses.len() - accesses_per_shard[shard_id];
+                if writers_in_other_shards > 0 || (*is_write && accesses_in_other_shards > 0) {
+                    has_conflict[*txn_idx] = true;
+                }
+            }
+        }
+        has_conflict
+            .into_iter()
+            .filter(|conflict| *conflict)
+            .count()
+    }
+}
+
+fn find_root(parents: &mut [TxnIndex], txn_idx: TxnIndex) -> TxnIndex {
+    let mut root = txn_idx;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    // Compress the path, so later lookups are cheap
+    let mut current = txn_idx;
+    while parents[current] != root {
+        let next = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    root
+}
@@ -0,0 +1,190 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::{
+    access_path::AccessPath,
+    state_store::state_key::StateKey,
+    transaction::{
+        analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+        EntryFunction, SignedTransaction, Transaction, TransactionPayload,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+};
+use std::collections::{HashMap, HashSet};
+
+/// The account under which a resource accessed by an entry function is stored.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FootprintAddress {
+    /// The sender of the transaction.
+    Sender,
+    /// The address passed as the argument at the given index.
+    Argument(usize),
+    /// A fixed address, e.g. the resource account holding a protocol's global state.
+    Fixed(AccountAddress),
+}
+
+/// A resource read or written by an entry function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResourceFootprint {
+    pub address: FootprintAddress,
+    pub struct_tag: StructTag,
+    pub is_write: bool,
+}
+
+impl ResourceFootprint {
+    pub fn read(address: FootprintAddress, struct_tag: StructTag) -> Self {
+        Self {
+            address,
+            struct_tag,
+            is_write: false,
+        }
+    }
+
+    pub fn write(address: FootprintAddress, struct_tag: StructTag) -> Self {
+        Self {
+            address,
+            struct_tag,
+            is_write: true,
+        }
+    }
+}
+
+/// Statically known resource footprints of entry functions, used to infer the read and write
+/// hints of transactions. Entry functions without a registered footprint are assumed to only
+/// access the resources of the sender, so the hints of such transactions may be incomplete.
+#[derive(Clone, Debug, Default)]
+pub struct EntryFunctionFootprints {
+    footprints: HashMap<(ModuleId, Identifier), Vec<ResourceFootprint>>,
+}
+
+impl EntryFunctionFootprints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a resource accessed by the given entry function.
+    pub fn add(&mut self, module: ModuleId, function: Identifier, footprint: ResourceFootprint) {
+        self.footprints
+            .entry((module, function))
+            .or_default()
+            .push(footprint);
+    }
+
+    pub fn get(&self, module: &ModuleId, function: &Identifier) -> Option<&[ResourceFootprint]> {
+        self.footprints
+            .get(&(module.clone(), function.clone()))
+            .map(|footprints| footprints.as_slice())
+    }
+
+    /// Infers the read and write hints of a transaction from its sender, the framework coin
+    /// transfer functions and the registered entry function footprints.
+    pub fn analyze(&self, txn: Transaction) -> AnalyzedTransaction {
+        let signed_txn = match &txn {
+            Transaction::UserTransaction(signed_txn) => signed_txn,
+            _ => return AnalyzedTransaction::new_with_no_hints(txn),
+        };
+
+        let mut read_hints = vec![];
+        let mut write_hints = gas_payer_write_hints(signed_txn);
+        if let TransactionPayload::EntryFunction(func) = signed_txn.payload() {
+            for footprint in self.entry_function_footprints(signed_txn.sender(), func) {
+                if footprint.is_write {
+                    write_hints.push(footprint.location);
+                } else {
+                    read_hints.push(footprint.location);
+                }
+            }
+        }
+
+        // Locations that are written are not repeated in the read hints
+        let mut seen = HashSet::new();
+        write_hints.retain(|location| seen.insert(location.clone()));
+        read_hints.retain(|location| seen.insert(location.clone()));
+        AnalyzedTransaction::new(txn, read_hints, write_hints)
+    }
+
+    fn entry_function_footprints(
+        &self,
+        sender: AccountAddress,
+        func: &EntryFunction,
+    ) -> Vec<LocatedFootprint> {
+        let argument_address = |index: usize| {
+            func.args()
+                .get(index)
+                .and_then(|arg| bcs::from_bytes::<AccountAddress>(arg).ok())
+        };
+
+        let module = func.module();
+        if *module.address() == AccountAddress::ONE {
+            let receiver = argument_address(0);
+            match (module.name().as_str(), func.function().as_str(), receiver) {
+                ("coin", "transfer", Some(receiver)) => {
+                    return vec![LocatedFootprint::write(
+                        AnalyzedTransaction::coin_store_location(receiver),
+                    )];
+                },
+                ("aptos_account", "transfer" | "create_account", Some(receiver)) => {
+                    return vec![
+                        LocatedFootprint::write(AnalyzedTransaction::account_resource_location(
+                            receiver,
+                        )),
+                        LocatedFootprint::write(AnalyzedTransaction::coin_store_location(receiver)),
+                    ];
+                },
+                _ => {},
+            }
+        }
+
+        self.get(module, &func.function().to_owned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|footprint| {
+                let address = match &footprint.address {
+                    FootprintAddress::Sender => sender,
+                    FootprintAddress::Argument(index) => argument_address(*index)?,
+                    FootprintAddress::Fixed(address) => *address,
+                };
+                Some(LocatedFootprint {
+                    location: StorageLocation::Specific(StateKey::access_path(AccessPath::new(
+                        address,
+                        footprint.struct_tag.access_vector(),
+                    ))),
+                    is_write: footprint.is_write,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Every user transaction bumps the sequence number of the sender and charges gas, either to the
+/// sender or to the fee payer.
+fn gas_payer_write_hints(signed_txn: &SignedTransaction) -> Vec<StorageLocation> {
+    let sender = signed_txn.sender();
+    let gas_payer = signed_txn
+        .authenticator_ref()
+        .fee_payer_address()
+        .unwrap_or(sender);
+    vec![
+        AnalyzedTransaction::account_resource_location(sender),
+        AnalyzedTransaction::coin_store_location(gas_payer),
+    ]
+}
+
+/// A footprint resolved to a storage location for a specific transaction.
+struct LocatedFootprint {
+    location: StorageLocation,
+    is_write: bool,
+}
+
+impl LocatedFootprint {
+    fn write(location: StorageLocation) -> Self {
+        Self {
+            location,
+            is_write: true,
+        }
+    }
+}
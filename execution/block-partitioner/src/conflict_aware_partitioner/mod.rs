@@ -0,0 +1,357 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    conflict_aware_partitioner::conflict_graph::{ConflictGraph, LocationId},
+    BlockPartitioner,
+};
+use aptos_types::{
+    block_executor::partitioner::ShardId,
+    transaction::{analyzed_transaction::AnalyzedTransaction, Transaction},
+};
+use std::{cmp::Reverse, collections::HashMap};
+
+mod conflict_graph;
+mod hints;
+
+pub use hints::{EntryFunctionFootprints, FootprintAddress, ResourceFootprint};
+
+const DEFAULT_MAX_IMBALANCE: f64 = 0.1;
+
+/// A partitioner that uses the read/write hints of transactions to place conflicting transactions
+/// on the same shard, minimizing the number of cross-shard dependencies. The partitioning works
+/// as follows:
+/// ```plaintext
+/// 1. Build a conflict graph: two transactions conflict if one of them writes a storage location
+///    the other one reads or writes. Transactions of the same sender always conflict, as they all
+///    write the sender's account resource.
+/// 2. Split the block into connected components of the conflict graph. Components can be placed
+///    on any shard without introducing cross-shard dependencies.
+/// 3. Place the components on shards, largest first, each on the least loaded shard, as long as
+///    the shard stays within its capacity (an even share of the block plus `max_imbalance`).
+/// 4. Components that don't fit (e.g. all the transactions touching a hot account) are split
+///    transaction by transaction, in block order: each transaction goes to the shard holding most
+///    of the transactions it conflicts with, discounted by how full the shard is.
+/// ```
+/// Within a shard, transactions keep their relative order from the block.
+pub struct ConflictAwarePartitioner {
+    footprints: EntryFunctionFootprints,
+    max_imbalance: f64,
+}
+
+impl ConflictAwarePartitioner {
+    /// `max_imbalance` is how much larger than an even share of the block a shard may grow, as
+    /// a fraction of the even share. Larger values avoid more cross-shard dependencies, at the
+    /// cost of less evenly sized shards.
+    pub fn new(footprints: EntryFunctionFootprints, max_imbalance: f64) -> Self {
+        assert!(max_imbalance >= 0.0, "max_imbalance must be >= 0");
+        Self {
+            footprints,
+            max_imbalance,
+        }
+    }
+
+    pub fn footprints(&self) -> &EntryFunctionFootprints {
+        &self.footprints
+    }
+
+    /// Returns the shard each transaction is assigned to.
+    pub fn assign_shards(&self, txns: &[AnalyzedTransaction], num_shards: usize) -> Vec<ShardId> {
+        assert!(num_shards > 0, "num_shards must be > 0");
+        let graph = ConflictGraph::new(txns);
+        let even_share = (txns.len() as f64 / num_shards as f64).ceil();
+        let capacity = ((even_share * (1.0 + self.max_imbalance)).ceil() as usize).max(1);
+
+        let mut shard_ids = vec![0; txns.len()];
+        let mut loads = vec![0; num_shards];
+        let mut location_loads: HashMap<LocationId, Vec<usize>> = HashMap::new();
+
+        let mut components = graph.connected_components();
+        // Place the largest components first, so the small ones can fill up the remaining space.
+        components.sort_by_key(|component| Reverse(component.len()));
+        for component in components {
+            let shard_id = least_loaded_shard(&loads);
+            if loads[shard_id] + component.len() <= capacity {
+                for txn_idx in component {
+                    shard_ids[txn_idx] = shard_id;
+                }
+                loads[shard_id] += component.len();
+                continue;
+            }
+
+            for txn_idx in component {
+                let locations = graph.txn_locations(txn_idx);
+                let shard_id = (0..num_shards)
+                    .filter(|shard_id| loads[*shard_id] < capacity)
+                    .max_by(|a, b| {
+                        let score = |shard_id: ShardId| {
+                            let num_conflicts: usize = locations
+                                .iter()
+                                .filter_map(|location| location_loads.get(location))
+                                .map(|location_loads| location_loads[shard_id])
+                                .sum();
+                            num_conflicts as f64 * (1.0 - loads[shard_id] as f64 / capacity as f64)
+                        };
+                        score(*a)
+                            .total_cmp(&score(*b))
+                            // Break ties in favor of the least loaded shard
+                            .then(loads[*b].cmp(&loads[*a]))
+                    })
+                    .expect("the shards can always hold the whole block");
+
+                shard_ids[txn_idx] = shard_id;
+                loads[shard_id] += 1;
+                for location in locations {
+                    location_loads
+                        .entry(*location)
+                        .or_insert_with(|| vec![0; num_shards])[shard_id] += 1;
+                }
+            }
+        }
+        shard_ids
+    }
+
+    /// Partitions the transactions into `num_shards` shards, keeping the block order within each
+    /// shard.
+    pub fn partition_transactions(
+        &self,
+        txns: Vec<AnalyzedTransaction>,
+        num_shards: usize,
+    ) -> Vec<Vec<AnalyzedTransaction>> {
+        let shard_ids = self.assign_shards(&txns, num_shards);
+        let mut shards = vec![vec![]; num_shards];
+        for (txn, shard_id) in txns.into_iter().zip(shard_ids) {
+            shards[shard_id].push(txn);
+        }
+        shards
+    }
+}
+
+impl Default for ConflictAwarePartitioner {
+    fn default() -> Self {
+        Self::new(EntryFunctionFootprints::new(), DEFAULT_MAX_IMBALANCE)
+    }
+}
+
+impl BlockPartitioner for ConflictAwarePartitioner {
+    fn partition(
+        &self,
+        transactions: Vec<Transaction>,
+        num_shards: usize,
+    ) -> Vec<Vec<Transaction>> {
+        if transactions.is_empty() {
+            return vec![];
+        }
+        let txns = transactions
+            .into_iter()
+            .map(|txn| self.footprints.analyze(txn))
+            .collect();
+        self.partition_transactions(txns, num_shards)
+            .into_iter()
+            .map(|shard| shard.into_iter().map(|txn| txn.into_txn()).collect())
+            .collect()
+    }
+}
+
+/// Returns the number of transactions that conflict with a transaction in a different shard,
+/// i.e. the number of cross-shard dependencies a partitioning introduces.
+pub fn num_txns_with_cross_shard_conflicts(shards: &[Vec<AnalyzedTransaction>]) -> usize {
+    let txns: Vec<_> = shards.iter().flatten().cloned().collect();
+    let shard_ids: Vec<_> = shards
+        .iter()
+        .enumerate()
+        .flat_map(|(shard_id, txns)| std::iter::repeat(shard_id).take(txns.len()))
+        .collect();
+    ConflictGraph::new(&txns).num_txns_with_cross_shard_conflicts(&shard_ids)
+}
+
+fn least_loaded_shard(loads: &[usize]) -> ShardId {
+    loads
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, load)| **load)
+        .map(|(shard_id, _)| shard_id)
+        .expect("there is at least one shard")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conflict_aware_partitioner::{
+            num_txns_with_cross_shard_conflicts, ConflictAwarePartitioner, EntryFunctionFootprints,
+            FootprintAddress, ResourceFootprint,
+        },
+        test_utils::{create_signed_p2p_transaction, generate_test_account, TestAccount},
+        BlockPartitioner, UniformPartitioner,
+    };
+    use aptos_crypto::{PrivateKey, SigningKey};
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{
+            analyzed_transaction::AnalyzedTransaction, EntryFunction, RawTransaction,
+            SignedTransaction, Transaction, TransactionPayload,
+        },
+    };
+    use move_core_types::{
+        account_address::AccountAddress,
+        identifier::Identifier,
+        language_storage::{ModuleId, StructTag},
+    };
+
+    fn analyze(
+        partitioner: &ConflictAwarePartitioner,
+        txns: &[AnalyzedTransaction],
+    ) -> Vec<AnalyzedTransaction> {
+        txns.iter()
+            .map(|txn| partitioner.footprints().analyze(txn.transaction().clone()))
+            .collect()
+    }
+
+    fn create_entry_function_transaction(
+        sender: &mut TestAccount,
+        entry_function: EntryFunction,
+    ) -> Transaction {
+        let raw_transaction = RawTransaction::new(
+            sender.account_address,
+            sender.sequence_number,
+            TransactionPayload::EntryFunction(entry_function),
+            0,
+            0,
+            0,
+            ChainId::new(10),
+        );
+        sender.sequence_number += 1;
+        Transaction::UserTransaction(SignedTransaction::new(
+            raw_transaction.clone(),
+            sender.private_key.public_key(),
+            sender.private_key.sign(&raw_transaction).unwrap(),
+        ))
+    }
+
+    #[test]
+    // Transfers between disjoint pairs of accounts never conflict, so the shards are evenly sized
+    // and there are no cross-shard dependencies.
+    fn test_non_conflicting_txns() {
+        let partitioner = ConflictAwarePartitioner::default();
+        let txns: Vec<_> = (0..12)
+            .flat_map(|_| {
+                let mut sender = generate_test_account();
+                let receiver = generate_test_account();
+                create_signed_p2p_transaction(&mut sender, vec![&receiver])
+            })
+            .collect();
+
+        let shards = partitioner.partition_transactions(analyze(&partitioner, &txns), 4);
+        assert_eq!(shards.len(), 4);
+        for shard in &shards {
+            assert_eq!(shard.len(), 3);
+        }
+        assert_eq!(num_txns_with_cross_shard_conflicts(&shards), 0);
+    }
+
+    #[test]
+    // Transfers within the same group of accounts end up on the same shard, while the uniform
+    // partitioner spreads them over all shards.
+    fn test_conflicting_txns_are_colocated() {
+        let partitioner = ConflictAwarePartitioner::default();
+        let num_groups = 4;
+        let mut groups: Vec<Vec<TestAccount>> = (0..num_groups)
+            .map(|_| (0..3).map(|_| generate_test_account()).collect())
+            .collect();
+
+        // Interleave the transactions of the groups in the block, so that each group is spread
+        // over the whole block
+        let mut txns = vec![];
+        for round in 0..3 {
+            for group in groups.iter_mut() {
+                let (senders, receivers) = group.split_at_mut(round % 2 + 1);
+                let sender = senders.last_mut().unwrap();
+                txns.extend(create_signed_p2p_transaction(sender, vec![&receivers[0]]));
+            }
+        }
+        let txns = analyze(&partitioner, &txns);
+
+        let shards = partitioner.partition_transactions(txns.clone(), num_groups);
+        assert_eq!(num_txns_with_cross_shard_conflicts(&shards), 0);
+        for shard in &shards {
+            assert_eq!(shard.len(), 3);
+            // The relative order of the block is kept within a shard
+            let positions: Vec<_> = shard
+                .iter()
+                .map(|txn| txns.iter().position(|other| other == txn).unwrap())
+                .collect();
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        let uniform_shards: Vec<Vec<AnalyzedTransaction>> = UniformPartitioner {}
+            .partition(
+                txns.into_iter().map(|txn| txn.into_txn()).collect(),
+                num_groups,
+            )
+            .into_iter()
+            .map(|shard| {
+                shard
+                    .into_iter()
+                    .map(|txn| partitioner.footprints().analyze(txn))
+                    .collect()
+            })
+            .collect();
+        assert!(num_txns_with_cross_shard_conflicts(&uniform_shards) > 0);
+    }
+
+    #[test]
+    // A hot receiver connects the whole block into a single component, which has to be split up
+    // to keep the shards within their capacity.
+    fn test_hot_account_is_split_within_capacity() {
+        let partitioner = ConflictAwarePartitioner::new(EntryFunctionFootprints::new(), 0.0);
+        let receiver = generate_test_account();
+        let txns: Vec<_> = (0..16)
+            .flat_map(|_| {
+                let mut sender = generate_test_account();
+                create_signed_p2p_transaction(&mut sender, vec![&receiver])
+            })
+            .collect();
+
+        let shards = partitioner.partition_transactions(analyze(&partitioner, &txns), 4);
+        for shard in &shards {
+            assert_eq!(shard.len(), 4);
+        }
+    }
+
+    #[test]
+    // Registered entry function footprints make transactions touching the same global resource
+    // conflict, even if they are sent by unrelated accounts.
+    fn test_entry_function_footprints() {
+        let module = ModuleId::new(AccountAddress::random(), Identifier::new("dex").unwrap());
+        let function = Identifier::new("swap").unwrap();
+        let pool = StructTag {
+            address: *module.address(),
+            module: module.name().to_owned(),
+            name: Identifier::new("Pool").unwrap(),
+            type_params: vec![],
+        };
+        let mut footprints = EntryFunctionFootprints::new();
+        footprints.add(
+            module.clone(),
+            function.clone(),
+            ResourceFootprint::write(FootprintAddress::Fixed(*module.address()), pool),
+        );
+        let partitioner = ConflictAwarePartitioner::new(footprints, 1.0);
+
+        let txns: Vec<_> = (0..4)
+            .map(|_| {
+                let txn = create_entry_function_transaction(
+                    &mut generate_test_account(),
+                    EntryFunction::new(module.clone(), function.clone(), vec![], vec![]),
+                );
+                partitioner.footprints().analyze(txn)
+            })
+            .collect();
+        // The sender's account resource, the sender's coin store and the pool
+        assert_eq!(txns[0].write_hints().len(), 3);
+
+        let shards = partitioner.partition_transactions(txns, 2);
+        assert_eq!(num_txns_with_cross_shard_conflicts(&shards), 0);
+        assert!(shards.iter().any(|shard| shard.len() == 4));
+    }
+}
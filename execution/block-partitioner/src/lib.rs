@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod conflict_aware_partitioner;
 pub mod sharded_block_partitioner;
 pub mod test_utils;
 
@@ -8,6 +8,7 @@ pub mod db_generator;
 mod db_reliable_submitter;
 mod metrics;
 pub mod native_executor;
+pub mod partitioner_benchmark;
 pub mod pipeline;
 pub mod transaction_committer;
 pub mod transaction_executor;
@@ -210,6 +210,30 @@ enum Command {
         #[clap(long, default_value = "1000000")]
         init_account_balance: u64,
     },
+    /// Compares the uniform and the conflict aware block partitioners on
+    /// generated p2p transfers, without executing them
+    BenchmarkPartitioners {
+        #[clap(long, default_value = "100000")]
+        num_accounts: usize,
+
+        #[clap(long, default_value = "10")]
+        num_blocks: usize,
+
+        #[clap(long, default_value = "4")]
+        num_shards: usize,
+
+        /// Number of popular receivers
+        #[clap(long, default_value = "10")]
+        num_hotspot_accounts: usize,
+
+        /// Fraction of the transfers sent to the popular receivers
+        #[clap(long, default_value = "0.2")]
+        hotspot_ratio: f64,
+
+        /// How much larger than an even share of the block a shard may grow
+        #[clap(long, default_value = "0.1")]
+        max_imbalance: f64,
+    },
 }
 
 fn run<E>(opt: Opt)
@@ -278,6 +302,24 @@ where
                 opt.pipeline_opt.pipeline_config(),
             );
         },
+        Command::BenchmarkPartitioners {
+            num_accounts,
+            num_blocks,
+            num_shards,
+            num_hotspot_accounts,
+            hotspot_ratio,
+            max_imbalance,
+        } => {
+            aptos_executor_benchmark::partitioner_benchmark::run_partitioner_benchmark(
+                num_accounts,
+                opt.block_size,
+                num_blocks,
+                num_shards,
+                num_hotspot_accounts,
+                hotspot_ratio,
+                max_imbalance,
+            );
+        },
     }
 }
 
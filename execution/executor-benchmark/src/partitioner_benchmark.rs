@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_block_partitioner::{
+    conflict_aware_partitioner::{num_txns_with_cross_shard_conflicts, ConflictAwarePartitioner},
+    test_utils::{create_signed_p2p_transaction, generate_test_account, TestAccount},
+    BlockPartitioner, UniformPartitioner,
+};
+use aptos_types::transaction::analyzed_transaction::AnalyzedTransaction;
+use rand::{rngs::OsRng, Rng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Compares the uniform and the conflict aware block partitioners on blocks of p2p transfers.
+/// A `hotspot_ratio` fraction of the transfers go to one of `num_hotspot_accounts` receivers,
+/// which mimics popular accounts (e.g. exchanges) and makes the blocks more conflicting.
+pub fn run_partitioner_benchmark(
+    num_accounts: usize,
+    block_size: usize,
+    num_blocks: usize,
+    num_shards: usize,
+    num_hotspot_accounts: usize,
+    hotspot_ratio: f64,
+    max_imbalance: f64,
+) {
+    assert!(num_accounts >= 2, "Need at least 2 accounts");
+    assert!(
+        num_hotspot_accounts < num_accounts,
+        "Need more accounts than hotspot accounts"
+    );
+    assert!(
+        (0.0..=1.0).contains(&hotspot_ratio),
+        "hotspot_ratio must be within [0, 1]"
+    );
+
+    println!("Creating {} accounts", num_accounts);
+    let accounts: Vec<Mutex<TestAccount>> = (0..num_accounts)
+        .into_par_iter()
+        .map(|_| Mutex::new(generate_test_account()))
+        .collect();
+
+    let conflict_aware = ConflictAwarePartitioner::new(Default::default(), max_imbalance);
+    let mut uniform_stats = PartitionerStats::default();
+    let mut conflict_aware_stats = PartitionerStats::default();
+    for _ in 0..num_blocks {
+        let block = create_block(
+            &accounts,
+            block_size,
+            num_hotspot_accounts,
+            hotspot_ratio,
+            &conflict_aware,
+        );
+
+        let now = Instant::now();
+        let shards = UniformPartitioner {}.partition(
+            block.iter().map(|txn| txn.transaction().clone()).collect(),
+            num_shards,
+        );
+        uniform_stats.time += now.elapsed();
+        let shards: Vec<Vec<AnalyzedTransaction>> = shards
+            .into_iter()
+            .map(|shard| {
+                shard
+                    .into_iter()
+                    .map(|txn| conflict_aware.footprints().analyze(txn))
+                    .collect()
+            })
+            .collect();
+        uniform_stats.record(&shards);
+
+        let now = Instant::now();
+        let shards = conflict_aware.partition_transactions(block, num_shards);
+        conflict_aware_stats.time += now.elapsed();
+        conflict_aware_stats.record(&shards);
+    }
+
+    println!(
+        "Partitioned {} blocks of {} transactions into {} shards",
+        num_blocks, block_size, num_shards
+    );
+    uniform_stats.print("uniform", num_blocks, block_size);
+    conflict_aware_stats.print("conflict aware", num_blocks, block_size);
+}
+
+/// Creates a block of p2p transfers between random accounts. The first `num_hotspot_accounts`
+/// accounts are the hotspots.
+fn create_block(
+    accounts: &[Mutex<TestAccount>],
+    block_size: usize,
+    num_hotspot_accounts: usize,
+    hotspot_ratio: f64,
+    partitioner: &ConflictAwarePartitioner,
+) -> Vec<AnalyzedTransaction> {
+    let mut rng = OsRng;
+    (0..block_size)
+        .map(|_| {
+            let sender_idx = rng.gen_range(num_hotspot_accounts, accounts.len());
+            let receiver_idx = if num_hotspot_accounts > 0 && rng.gen_bool(hotspot_ratio) {
+                rng.gen_range(0, num_hotspot_accounts)
+            } else {
+                loop {
+                    let receiver_idx = rng.gen_range(0, accounts.len());
+                    if receiver_idx != sender_idx {
+                        break receiver_idx;
+                    }
+                }
+            };
+            let receiver = accounts[receiver_idx].lock().unwrap();
+            let mut sender = accounts[sender_idx].lock().unwrap();
+            let txn = create_signed_p2p_transaction(&mut sender, vec![&receiver])
+                .remove(0)
+                .into_txn();
+            partitioner.footprints().analyze(txn)
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct PartitionerStats {
+    time: Duration,
+    num_conflicting_txns: usize,
+    max_shard_size: usize,
+}
+
+impl PartitionerStats {
+    fn record(&mut self, shards: &[Vec<AnalyzedTransaction>]) {
+        self.num_conflicting_txns += num_txns_with_cross_shard_conflicts(shards);
+        self.max_shard_size = shards
+            .iter()
+            .map(|shard| shard.len())
+            .max()
+            .unwrap_or_default()
+            .max(self.max_shard_size);
+    }
+
+    fn print(&self, name: &str, num_blocks: usize, block_size: usize) {
+        let num_txns = (num_blocks * block_size).max(1);
+        println!(
+            "{:>16}: {:?} per block, {:.2}% of transactions with cross-shard conflicts, max shard size {}",
+            name,
+            self.time / num_blocks.max(1) as u32,
+            self.num_conflicting_txns as f64 * 100.0 / num_txns as f64,
+            self.max_shard_size,
+        );
+    }
+}
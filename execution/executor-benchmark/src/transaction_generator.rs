@@ -339,7 +339,7 @@ impl TransactionGenerator {
                     let txn = self.root_account.sign_with_transaction_builder(
                         self.transaction_factory
                             .implicitly_create_user_account_and_transfer(
-                                new_account.public_key(),
+                                new_account.public_key().unwrap(),
                                 seed_account_balance,
                             ),
                     );
@@ -384,7 +384,7 @@ impl TransactionGenerator {
                     let txn = sender.sign_with_transaction_builder(
                         self.transaction_factory
                             .implicitly_create_user_account_and_transfer(
-                                new_account.public_key(),
+                                new_account.public_key().unwrap(),
                                 init_account_balance,
                             ),
                     );
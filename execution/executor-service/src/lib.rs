@@ -1,8 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use aptos_state_view::in_memory_state_view::InMemoryStateView;
 use aptos_types::{
     block_executor::partitioner::SubBlocksForShard,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
     transaction::{Transaction, TransactionOutput},
     vm_status::VMStatus,
 };
@@ -12,6 +14,7 @@ mod error;
 pub mod process_executor_service;
 pub mod remote_executor_client;
 pub mod remote_executor_service;
+mod remote_state_view;
 #[cfg(test)]
 mod thread_executor_service;
 
@@ -20,20 +23,47 @@ pub struct BlockExecutionResult {
     pub inner: Result<Vec<TransactionOutput>, VMStatus>,
 }
 
+/// A message sent from the coordinator (the remote executor client) to the executor service.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum BlockExecutionRequest {
     ExecuteBlock(ExecuteBlockCommand),
+    /// The state values the executor service asked for with
+    /// [`BlockExecutionResponse::GetStateValues`].
+    StateValues(RemoteKVResponse),
+}
+
+/// A message sent from the executor service to the coordinator, while executing a block.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum BlockExecutionResponse {
+    /// The executor service needs the values of these keys to continue executing the block. The
+    /// coordinator reads them from its state view and answers with
+    /// [`BlockExecutionRequest::StateValues`].
+    GetStateValues(RemoteKVRequest),
+    /// The block is executed, this is the last message for the block.
+    ExecutionResult(BlockExecutionResult),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteBlockCommand {
     pub(crate) sub_blocks: SubBlocksForShard<Transaction>,
-    // Currently we only support the state view backed by in-memory hashmap, which means that
-    // the controller needs to pre-read all the KV pairs from the storage and pass them to the
-    // executor service. In the future, we will support other types of state view, e.g., the
-    // state view backed by remote storage service, which will allow the executor service to read the KV pairs
-    // directly from the storage.
-    pub(crate) state_view: InMemoryStateView,
+    // The executor service doesn't hold the state. The controller sends along the values of the
+    // keys the block is likely to read, and the executor service fetches the rest of the state
+    // from the controller (in batches) as it executes the block.
+    pub(crate) prefetched_state_values: Vec<(StateKey, Option<StateValue>)>,
+    pub(crate) is_genesis: bool,
+    pub(crate) state_storage_usage: Result<StateStorageUsage, String>,
     pub(crate) concurrency_level: usize,
     pub(crate) maybe_block_gas_limit: Option<u64>,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteKVRequest {
+    pub(crate) keys: Vec<StateKey>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteKVResponse {
+    /// The values of the requested keys, in the order of the request, or the error returned by
+    /// the controller's state view.
+    pub(crate) inner: Result<Vec<Option<StateValue>>, String>,
+}
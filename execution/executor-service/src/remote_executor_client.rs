@@ -1,59 +1,182 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::Error, BlockExecutionRequest, BlockExecutionResult, ExecuteBlockCommand};
-use aptos_logger::error;
+use crate::{
+    error::Error, remote_state_view::keys_to_prefetch, BlockExecutionRequest,
+    BlockExecutionResponse, BlockExecutionResult, ExecuteBlockCommand, RemoteKVResponse,
+};
+use aptos_logger::{error, warn};
 use aptos_retrier::{fixed_retry_strategy, retry};
 use aptos_secure_net::NetworkClient;
 use aptos_state_view::StateView;
 use aptos_types::{
     block_executor::partitioner::SubBlocksForShard,
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{Transaction, TransactionOutput},
-    vm_status::VMStatus,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_vm::sharded_block_executor::block_executor_client::BlockExecutorClient;
-use std::{net::SocketAddr, sync::Mutex};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Number of times to try connecting to an executor service before considering it failed.
+const MAX_CONNECTION_ATTEMPTS: usize = 5;
+/// Upper bound on the number of keys remembered for prefetching.
+const MAX_KEYS_TO_REMEMBER: usize = 100_000;
 
 /// An implementation of [`BlockExecutorClient`] that supports executing blocks remotely.
+///
+/// The client doesn't send the whole state to the executor service. It sends the block along
+/// with the values of the keys the block is likely to read, and serves the values of the other
+/// keys on demand while the block is executed. Keys that had to be served on demand are
+/// remembered and prefetched for the next blocks, as consecutive blocks tend to read the same
+/// framework modules and configs.
+///
+/// If the executor service fails (it can't be reached, or the connection breaks), the block is
+/// retried on the next executor service the client knows about.
 pub struct RemoteExecutorClient {
-    network_client: Mutex<NetworkClient>,
+    /// The executor services this client can execute blocks on, in order of preference.
+    server_addresses: Vec<SocketAddr>,
+    /// Index of the executor service currently in use.
+    current_server: AtomicUsize,
+    network_timeout_ms: u64,
+    /// Keys fetched on demand by the executor services while executing previous blocks.
+    remembered_keys: Mutex<HashSet<StateKey>>,
 }
 
 impl RemoteExecutorClient {
     pub fn new(server_address: SocketAddr, network_timeout_ms: u64) -> Self {
-        let network_client = NetworkClient::new(
-            "remote-executor-service",
-            server_address,
-            network_timeout_ms,
+        Self::new_with_fallbacks(vec![server_address], network_timeout_ms)
+    }
+
+    /// Creates a client that executes blocks on the first executor service, and falls back to
+    /// the next ones (in order) when it fails.
+    pub fn new_with_fallbacks(server_addresses: Vec<SocketAddr>, network_timeout_ms: u64) -> Self {
+        assert!(
+            !server_addresses.is_empty(),
+            "At least one executor service address is required"
         );
         Self {
-            network_client: Mutex::new(network_client),
+            server_addresses,
+            current_server: AtomicUsize::new(0),
+            network_timeout_ms,
+            remembered_keys: Mutex::new(HashSet::new()),
         }
     }
 
-    fn execute_block_inner(
+    /// Creates one client per executor service, to be used as the shards of a
+    /// `ShardedBlockExecutor`. Each client executes its blocks on its own executor service, and
+    /// falls back to the executor services of the other shards when it fails.
+    pub fn create_remote_clients(
+        server_addresses: Vec<SocketAddr>,
+        network_timeout_ms: u64,
+    ) -> Vec<Self> {
+        (0..server_addresses.len())
+            .map(|shard_id| {
+                let mut addresses = server_addresses.clone();
+                addresses.rotate_left(shard_id);
+                Self::new_with_fallbacks(addresses, network_timeout_ms)
+            })
+            .collect()
+    }
+
+    fn execute_block_inner<S: StateView + Sync>(
         &self,
-        execution_request: BlockExecutionRequest,
+        server_address: SocketAddr,
+        command: ExecuteBlockCommand,
+        state_view: &S,
     ) -> Result<BlockExecutionResult, Error> {
-        let input_message = bcs::to_bytes(&execution_request)?;
-        let mut network_client = self.network_client.lock().unwrap();
-        network_client.write(&input_message)?;
-        let bytes = network_client.read()?;
-        Ok(bcs::from_bytes(&bytes)?)
+        let mut network_client = NetworkClient::new(
+            "remote-executor-service",
+            server_address,
+            self.network_timeout_ms,
+        )
+        .with_max_connection_attempts(MAX_CONNECTION_ATTEMPTS);
+        network_client.write(&bcs::to_bytes(&BlockExecutionRequest::ExecuteBlock(
+            command,
+        ))?)?;
+
+        loop {
+            let bytes = network_client.read()?;
+            match bcs::from_bytes(&bytes)? {
+                BlockExecutionResponse::GetStateValues(request) => {
+                    let values = request
+                        .keys
+                        .iter()
+                        .map(|key| state_view.get_state_value(key))
+                        .collect::<anyhow::Result<Vec<_>>>()
+                        .map_err(|e| e.to_string());
+                    self.remember_keys(request.keys);
+                    let response =
+                        BlockExecutionRequest::StateValues(RemoteKVResponse { inner: values });
+                    network_client.write(&bcs::to_bytes(&response)?)?;
+                },
+                BlockExecutionResponse::ExecutionResult(result) => {
+                    // Close the connection, so the executor service can serve other clients
+                    if let Err(e) = network_client.shutdown() {
+                        warn!(
+                            "Failed to close the connection to {}: {}",
+                            server_address, e
+                        );
+                    }
+                    return Ok(result);
+                },
+            }
+        }
     }
 
-    fn execute_block_with_retry(
+    fn execute_block_with_retry<S: StateView + Sync>(
         &self,
-        execution_request: BlockExecutionRequest,
-    ) -> BlockExecutionResult {
+        command: ExecuteBlockCommand,
+        state_view: &S,
+    ) -> Result<BlockExecutionResult, Error> {
         retry(fixed_retry_strategy(5, 20), || {
-            let res = self.execute_block_inner(execution_request.clone());
+            let server_idx = self.current_server.load(Ordering::Relaxed);
+            let server_address = self.server_addresses[server_idx];
+            let res = self.execute_block_inner(server_address, command.clone(), state_view);
             if let Err(e) = &res {
-                error!("Failed to execute block: {:?}", e);
+                error!(
+                    "Failed to execute block on executor service {}: {:?}",
+                    server_address, e
+                );
+                // Retry on the next executor service
+                let next_server_idx = (server_idx + 1) % self.server_addresses.len();
+                self.current_server
+                    .store(next_server_idx, Ordering::Relaxed);
             }
             res
         })
-        .unwrap()
+    }
+
+    fn prefetch_state_values<S: StateView + Sync>(
+        &self,
+        sub_blocks: &SubBlocksForShard<Transaction>,
+        state_view: &S,
+    ) -> Vec<(StateKey, Option<StateValue>)> {
+        let mut keys = keys_to_prefetch(sub_blocks);
+        keys.extend(self.remembered_keys.lock().unwrap().iter().cloned());
+        keys.into_iter()
+            // Keys that can't be read are left to the executor service, which fails the block
+            // if it actually needs them
+            .filter_map(|key| {
+                let value = state_view.get_state_value(&key).ok()?;
+                Some((key, value))
+            })
+            .collect()
+    }
+
+    fn remember_keys(&self, keys: Vec<StateKey>) {
+        let mut remembered_keys = self.remembered_keys.lock().unwrap();
+        if remembered_keys.len() + keys.len() > MAX_KEYS_TO_REMEMBER {
+            remembered_keys.clear();
+        }
+        remembered_keys.extend(keys);
     }
 }
 
@@ -65,12 +188,23 @@ impl BlockExecutorClient for RemoteExecutorClient {
         concurrency_level: usize,
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
-        let input = BlockExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+        let input = ExecuteBlockCommand {
+            prefetched_state_values: self.prefetch_state_values(&sub_blocks, state_view),
             sub_blocks,
-            state_view: S::as_in_memory_state_view(state_view),
+            is_genesis: state_view.is_genesis(),
+            state_storage_usage: state_view.get_usage().map_err(|e| e.to_string()),
             concurrency_level,
             maybe_block_gas_limit,
-        });
-        self.execute_block_with_retry(input).inner
+        };
+        match self.execute_block_with_retry(input, state_view) {
+            Ok(result) => result.inner,
+            Err(e) => Err(VMStatus::Error(
+                StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+                Some(format!(
+                    "No executor service could execute the block: {}",
+                    e
+                )),
+            )),
+        }
     }
 }
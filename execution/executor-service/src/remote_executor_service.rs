@@ -2,18 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    error::Error, remote_executor_client::RemoteExecutorClient, BlockExecutionRequest,
-    BlockExecutionResult,
+    error::Error, remote_executor_client::RemoteExecutorClient, remote_state_view::RemoteStateView,
+    BlockExecutionRequest, BlockExecutionResponse, BlockExecutionResult, ExecuteBlockCommand,
 };
 use aptos_logger::{error, info};
 use aptos_secure_net::NetworkServer;
 use aptos_vm::sharded_block_executor::block_executor_client::{
     BlockExecutorClient, LocalExecutorClient,
 };
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Mutex};
 
 /// A service that provides support for remote execution. Essentially, it reads a request from
-/// the remote executor client and executes the block locally and returns the result.
+/// the remote executor client and executes the block locally and returns the result. The state
+/// read by the block is fetched from the remote executor client.
 pub struct ExecutorService {
     client: LocalExecutorClient,
 }
@@ -25,25 +26,27 @@ impl ExecutorService {
         }
     }
 
-    pub fn handle_message(&self, execution_message: Vec<u8>) -> Result<Vec<u8>, Error> {
-        let input = bcs::from_bytes(&execution_message)?;
-        let result = self.handle_execution_request(input)?;
-        Ok(bcs::to_bytes(&result)?)
-    }
-
-    pub fn handle_execution_request(
+    /// Executes the block of the command. State values that were not sent along with the block
+    /// are requested from the remote executor client over the network server the command was
+    /// received on.
+    pub fn execute_block(
         &self,
-        execution_request: BlockExecutionRequest,
-    ) -> Result<BlockExecutionResult, Error> {
-        let result = match execution_request {
-            BlockExecutionRequest::ExecuteBlock(command) => self.client.execute_block(
-                command.sub_blocks,
-                &command.state_view,
-                command.concurrency_level,
-                command.maybe_block_gas_limit,
-            ),
-        };
-        Ok(BlockExecutionResult { inner: result })
+        command: ExecuteBlockCommand,
+        network_server: &Mutex<NetworkServer>,
+    ) -> BlockExecutionResult {
+        let state_view = RemoteStateView::new(
+            command.prefetched_state_values,
+            command.is_genesis,
+            command.state_storage_usage,
+            network_server,
+        );
+        let result = self.client.execute_block(
+            command.sub_blocks,
+            &state_view,
+            command.concurrency_level,
+            command.maybe_block_gas_limit,
+        );
+        BlockExecutionResult { inner: result }
     }
 }
 
@@ -60,22 +63,37 @@ pub trait RemoteExecutorService {
     fn executor_threads(&self) -> usize;
 }
 
-pub fn execute(mut network_server: NetworkServer, executor_service: ExecutorService) {
+pub fn execute(network_server: NetworkServer, executor_service: ExecutorService) {
+    let network_server = Mutex::new(network_server);
     loop {
-        if let Err(e) = process_one_message(&mut network_server, &executor_service) {
+        if let Err(e) = process_one_message(&network_server, &executor_service) {
             error!("Failed to process message: {}", e);
         }
     }
 }
 
 fn process_one_message(
-    network_server: &mut NetworkServer,
+    network_server: &Mutex<NetworkServer>,
     executor_service: &ExecutorService,
 ) -> Result<(), Error> {
-    let request = network_server.read()?;
-    let response = executor_service.handle_message(request)?;
+    let request = match network_server.lock().unwrap().read() {
+        Ok(request) => request,
+        // The remote executor client closes the connection after each block
+        Err(aptos_secure_net::Error::RemoteStreamClosed) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let command = match bcs::from_bytes(&request)? {
+        BlockExecutionRequest::ExecuteBlock(command) => command,
+        BlockExecutionRequest::StateValues(_) => {
+            return Err(Error::InternalError(
+                "Received state values while not executing a block".to_string(),
+            ))
+        },
+    };
+    let result = executor_service.execute_block(command, network_server);
+    let response = bcs::to_bytes(&BlockExecutionResponse::ExecutionResult(result))?;
     info!("server sending response");
-    network_server.write(&response)?;
+    network_server.lock().unwrap().write(&response)?;
     Ok(())
 }
 
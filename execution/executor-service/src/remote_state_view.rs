@@ -0,0 +1,174 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{BlockExecutionRequest, BlockExecutionResponse, RemoteKVRequest};
+use anyhow::{anyhow, bail, Result};
+use aptos_secure_net::NetworkServer;
+use aptos_state_view::TStateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    block_executor::partitioner::SubBlocksForShard,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{
+        analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+        Transaction, TransactionPayload,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, RwLock},
+};
+
+/// A state view for the executor service, which doesn't hold the state of the block it executes.
+/// It starts with the values prefetched by the controller, and fetches the missing ones from the
+/// controller over the connection the block was received on.
+///
+/// Only one request can be in flight on the connection at a time. The keys missed by the
+/// execution threads while a request is in flight are collected, and fetched together in the
+/// next request.
+pub(crate) struct RemoteStateView<'a> {
+    state_values: RwLock<HashMap<StateKey, Option<StateValue>>>,
+    /// Keys missed by the execution threads that are not requested yet.
+    pending_keys: Mutex<HashSet<StateKey>>,
+    is_genesis: bool,
+    state_storage_usage: Result<StateStorageUsage, String>,
+    network_server: &'a Mutex<NetworkServer>,
+}
+
+impl<'a> RemoteStateView<'a> {
+    pub(crate) fn new(
+        prefetched_state_values: Vec<(StateKey, Option<StateValue>)>,
+        is_genesis: bool,
+        state_storage_usage: Result<StateStorageUsage, String>,
+        network_server: &'a Mutex<NetworkServer>,
+    ) -> Self {
+        Self {
+            state_values: RwLock::new(prefetched_state_values.into_iter().collect()),
+            pending_keys: Mutex::new(HashSet::new()),
+            is_genesis,
+            state_storage_usage,
+            network_server,
+        }
+    }
+
+    fn fetch_state_values(
+        network_server: &mut NetworkServer,
+        keys: Vec<StateKey>,
+    ) -> Result<Vec<Option<StateValue>>> {
+        let num_keys = keys.len();
+        let request = BlockExecutionResponse::GetStateValues(RemoteKVRequest { keys });
+        network_server.write(&bcs::to_bytes(&request)?)?;
+        let response = network_server.read()?;
+        let values = match bcs::from_bytes(&response)? {
+            BlockExecutionRequest::StateValues(response) => {
+                response.inner.map_err(|e| anyhow!(e))?
+            },
+            BlockExecutionRequest::ExecuteBlock(_) => {
+                bail!("Received a new block while waiting for state values")
+            },
+        };
+        if values.len() != num_keys {
+            bail!(
+                "Requested {} state values, but received {}",
+                num_keys,
+                values.len()
+            );
+        }
+        Ok(values)
+    }
+}
+
+impl<'a> TStateView for RemoteStateView<'a> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        if let Some(value) = self.state_values.read().unwrap().get(state_key) {
+            return Ok(value.clone());
+        }
+
+        self.pending_keys.lock().unwrap().insert(state_key.clone());
+        let mut network_server = self.network_server.lock().unwrap();
+        // The value may have been fetched by another thread while waiting for the connection
+        if let Some(value) = self.state_values.read().unwrap().get(state_key) {
+            return Ok(value.clone());
+        }
+        // Fetch the keys missed by all threads so far. The key may have been taken by a request
+        // that failed, so it is added again.
+        let mut keys = std::mem::take(&mut *self.pending_keys.lock().unwrap());
+        keys.insert(state_key.clone());
+        let keys: Vec<_> = keys.into_iter().collect();
+        let values = Self::fetch_state_values(&mut network_server, keys.clone())?;
+
+        let mut state_values = self.state_values.write().unwrap();
+        state_values.extend(keys.into_iter().zip(values));
+        Ok(state_values
+            .get(state_key)
+            .cloned()
+            .expect("The key was just fetched"))
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.is_genesis
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.state_storage_usage.clone().map_err(|e| anyhow!(e))
+    }
+}
+
+/// Returns the keys that the transactions of the block are likely to read: the account and the
+/// coin store of the senders, the gas payers and the receivers of coin transfers, and the modules
+/// of the entry functions called.
+pub(crate) fn keys_to_prefetch(sub_blocks: &SubBlocksForShard<Transaction>) -> HashSet<StateKey> {
+    let mut addresses = HashSet::new();
+    let mut keys = HashSet::new();
+    for txn in sub_blocks.iter() {
+        let signed_txn = match txn.txn() {
+            Transaction::UserTransaction(signed_txn) => signed_txn,
+            _ => continue,
+        };
+        addresses.insert(signed_txn.sender());
+        if let Some(fee_payer) = signed_txn.authenticator_ref().fee_payer_address() {
+            addresses.insert(fee_payer);
+        }
+        if let TransactionPayload::EntryFunction(func) = signed_txn.payload() {
+            keys.insert(StateKey::access_path(AccessPath::code_access_path(
+                func.module().clone(),
+            )));
+            let is_transfer = *func.module().address() == AccountAddress::ONE
+                && matches!(
+                    (func.module().name().as_str(), func.function().as_str()),
+                    ("coin", "transfer")
+                        | ("aptos_account", "transfer")
+                        | ("aptos_account", "create_account")
+                );
+            if let Some(receiver) = func
+                .args()
+                .first()
+                .filter(|_| is_transfer)
+                .and_then(|arg| bcs::from_bytes::<AccountAddress>(arg).ok())
+            {
+                addresses.insert(receiver);
+            }
+        }
+    }
+
+    keys.extend(
+        addresses
+            .into_iter()
+            .flat_map(|address| {
+                [
+                    AnalyzedTransaction::account_resource_location(address),
+                    AnalyzedTransaction::coin_store_location(address),
+                ]
+            })
+            .filter_map(|location| match location {
+                StorageLocation::Specific(state_key) => Some(state_key),
+                _ => None,
+            }),
+    );
+    keys
+}
@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::utils;
+use aptos_executor_service::remote_executor_client::RemoteExecutorClient;
+use aptos_language_e2e_tests::{
+    common_transactions::peer_to_peer_txn, data_store::FakeDataStore, executor::FakeExecutor,
+};
+use aptos_types::{
+    block_executor::partitioner::{
+        BlockExecutorTransactions, CrossShardDependencies, SubBlock, SubBlocksForShard,
+        TransactionWithDependencies,
+    },
+    transaction::{Transaction, TransactionOutput},
+};
+use aptos_vm::{block_executor::BlockAptosVM, sharded_block_executor::ShardedBlockExecutor};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
+    process::{Child, Command},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+const NUM_SHARDS: usize = 3;
+const TXNS_PER_SHARD: usize = 4;
+const NETWORK_TIMEOUT_MS: u64 = 10_000;
+
+/// An executor service running in its own process, killed when dropped.
+struct ExecutorServiceProcess {
+    process: Child,
+    address: SocketAddr,
+}
+
+impl ExecutorServiceProcess {
+    fn spawn() -> Self {
+        let port = utils::get_available_port();
+        let process = Command::new(env!("CARGO_BIN_EXE_aptos-executor-service"))
+            .arg("--server-port")
+            .arg(port.to_string())
+            .arg("--num-executor-threads")
+            .arg("2")
+            .spawn()
+            .expect("Failed to start the executor service");
+        let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+
+        // Wait for the executor service to listen
+        for _ in 0..100 {
+            if TcpStream::connect(address).is_ok() {
+                return Self { process, address };
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("Executor service didn't start listening on {}", address);
+    }
+
+    fn kill(&mut self) {
+        self.process.kill().unwrap();
+        self.process.wait().unwrap();
+    }
+}
+
+impl Drop for ExecutorServiceProcess {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+    }
+}
+
+/// Creates a block of transfers, where each shard transfers between its own accounts, so the
+/// shards don't depend on each other. The gas price is zero, so no fees are burnt: burning
+/// updates the coin supply aggregator, which is materialized per shard.
+fn generate_block(executor: &mut FakeExecutor) -> Vec<SubBlocksForShard<Transaction>> {
+    (0..NUM_SHARDS)
+        .map(|shard_id| {
+            let sender = executor.create_raw_account_data(3_000_000_000, 0);
+            let receiver = executor.create_raw_account_data(3_000_000_000, 0);
+            executor.add_account_data(&sender);
+            executor.add_account_data(&receiver);
+
+            let txns = (0..TXNS_PER_SHARD)
+                .map(|seq_num| {
+                    let txn = Transaction::UserTransaction(peer_to_peer_txn(
+                        sender.account(),
+                        receiver.account(),
+                        seq_num as u64,
+                        1_000,
+                        0,
+                    ));
+                    TransactionWithDependencies::new(txn, CrossShardDependencies::default())
+                })
+                .collect();
+            let sub_block = SubBlock::new(shard_id * TXNS_PER_SHARD, txns);
+            SubBlocksForShard::new(shard_id, vec![sub_block])
+        })
+        .collect()
+}
+
+fn execute_unsharded(
+    executor: &FakeExecutor,
+    block: Vec<SubBlocksForShard<Transaction>>,
+) -> Vec<TransactionOutput> {
+    let thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap(),
+    );
+    BlockAptosVM::execute_block(
+        thread_pool,
+        BlockExecutorTransactions::Unsharded(SubBlocksForShard::flatten(block)),
+        executor.data_store(),
+        2,
+        None,
+    )
+    .unwrap()
+}
+
+fn execute_and_compare(
+    sharded_block_executor: &ShardedBlockExecutor<FakeDataStore>,
+    executor: &mut FakeExecutor,
+) {
+    let block = generate_block(executor);
+    let expected_output = execute_unsharded(executor, block.clone());
+
+    let output = sharded_block_executor
+        .execute_block(Arc::new(executor.data_store().clone()), block, 2, None)
+        .unwrap();
+    assert_eq!(output, expected_output);
+
+    for txn_output in output {
+        executor.apply_write_set(txn_output.write_set());
+    }
+}
+
+#[test]
+fn test_remote_sharded_execution_matches_block_executor() {
+    let mut executor_services: Vec<_> = (0..NUM_SHARDS)
+        .map(|_| ExecutorServiceProcess::spawn())
+        .collect();
+    let addresses = executor_services
+        .iter()
+        .map(|service| service.address)
+        .collect();
+    let sharded_block_executor = ShardedBlockExecutor::new(
+        RemoteExecutorClient::create_remote_clients(addresses, NETWORK_TIMEOUT_MS),
+    );
+
+    let mut executor = FakeExecutor::from_head_genesis();
+    for _ in 0..3 {
+        execute_and_compare(&sharded_block_executor, &mut executor);
+    }
+
+    // The blocks of the failed shard are retried on the other executor services
+    executor_services[0].kill();
+    for _ in 0..3 {
+        execute_and_compare(&sharded_block_executor, &mut executor);
+    }
+}
@@ -80,12 +80,15 @@ pub fn test_execution_with_storage_impl() -> Arc<AptosDB> {
         vec![],
         1,
     ));
-    let tx1 = core_resources_account
-        .sign_with_transaction_builder(txn_factory.create_user_account(account1.public_key()));
-    let tx2 = core_resources_account
-        .sign_with_transaction_builder(txn_factory.create_user_account(account2.public_key()));
-    let tx3 = core_resources_account
-        .sign_with_transaction_builder(txn_factory.create_user_account(account3.public_key()));
+    let tx1 = core_resources_account.sign_with_transaction_builder(
+        txn_factory.create_user_account(account1.public_key().unwrap()),
+    );
+    let tx2 = core_resources_account.sign_with_transaction_builder(
+        txn_factory.create_user_account(account2.public_key().unwrap()),
+    );
+    let tx3 = core_resources_account.sign_with_transaction_builder(
+        txn_factory.create_user_account(account3.public_key().unwrap()),
+    );
 
     // Create account1 with 2T coins.
     let txn1 = core_resources_account
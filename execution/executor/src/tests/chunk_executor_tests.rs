@@ -11,14 +11,19 @@ use crate::{
     mock_vm::{encode_mint_transaction, MockVM},
     tests,
 };
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
 use aptos_crypto::HashValue;
 use aptos_db::AptosDB;
 use aptos_executor_types::{BlockExecutorTrait, ChunkExecutorTrait};
 use aptos_storage_interface::DbReaderWriter;
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
+    state_store::state_key_filter::StateKeyFilter,
     test_helpers::transaction_test_helpers::{block, BLOCK_GAS_LIMIT},
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof},
+    transaction::{Transaction, TransactionListWithProof, TransactionOutputListWithProof},
 };
 use rand::Rng;
 
@@ -33,6 +38,30 @@ impl TestExecutor {
         let path = aptos_temppath::TempPath::new();
         path.create_as_dir().unwrap();
         let db = DbReaderWriter::new(AptosDB::new_for_test(path.path()));
+        Self::bootstrap(path, db)
+    }
+
+    /// Creates an executor on top of a DB that only persists the values of the selected keys.
+    pub fn new_partially_replicated(replicated_state_keys: StateKeyFilter) -> TestExecutor {
+        let path = aptos_temppath::TempPath::new();
+        path.create_as_dir().unwrap();
+        let db = DbReaderWriter::new(
+            AptosDB::open_partially_replicated(
+                path.path(),
+                false, /* readonly */
+                NO_OP_STORAGE_PRUNER_CONFIG,
+                RocksdbConfigs::default(),
+                false, /* enable_indexer */
+                BUFFERED_STATE_TARGET_ITEMS,
+                DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+                replicated_state_keys,
+            )
+            .unwrap(),
+        );
+        Self::bootstrap(path, db)
+    }
+
+    fn bootstrap(path: aptos_temppath::TempPath, db: DbReaderWriter) -> TestExecutor {
         let genesis = aptos_vm_genesis::test_genesis_transaction();
         let waypoint = generate_waypoint::<MockVM>(&db, &genesis).unwrap();
         maybe_bootstrap::<MockVM>(&db, &genesis, waypoint).unwrap();
@@ -197,6 +226,77 @@ fn test_executor_execute_or_apply_and_commit_chunk() {
     assert_eq!(li, ledger_info);
 }
 
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_apply_chunk_partially_replicated() {
+    let replicated_account = tests::gen_address(1);
+    let unreplicated_account = tests::gen_address(2);
+
+    // Mint to both accounts repeatedly, so that the later chunk updates the existing (and on the
+    // partially replicated DB, unpersisted) balance of the unreplicated account.
+    let (chunks, ledger_info) = {
+        let TestExecutor { executor, .. } = TestExecutor::new();
+        let mut txns = vec![];
+        for i in 1..=3 {
+            txns.push(encode_mint_transaction(replicated_account, i));
+            txns.push(encode_mint_transaction(unreplicated_account, i));
+        }
+        if BLOCK_GAS_LIMIT.is_none() {
+            txns.push(Transaction::StateCheckpoint(HashValue::random()));
+        }
+        let id = tests::gen_block_id(1);
+        let output = executor
+            .execute_block(
+                (id, txns.clone()).into(),
+                executor.committed_block_id(),
+                BLOCK_GAS_LIMIT,
+            )
+            .unwrap();
+        let ledger_version =
+            tests::ledger_version_from_block_size(txns.len(), BLOCK_GAS_LIMIT) as u64;
+        let ledger_info = tests::gen_ledger_info(ledger_version, output.root_hash(), id, 1);
+        executor
+            .commit_blocks(vec![id], ledger_info.clone())
+            .unwrap();
+
+        let first_chunk = executor
+            .db
+            .reader
+            .get_transaction_outputs(1, 2, ledger_version)
+            .unwrap();
+        let second_chunk = executor
+            .db
+            .reader
+            .get_transaction_outputs(3, ledger_version - 2, ledger_version)
+            .unwrap();
+        (vec![first_chunk, second_chunk], ledger_info)
+    };
+
+    let TestExecutor {
+        _path,
+        db,
+        executor,
+    } = TestExecutor::new_partially_replicated(StateKeyFilter::new(
+        vec![replicated_account],
+        vec![],
+    ));
+    assert!(db.reader.is_state_partially_replicated().unwrap());
+
+    executor
+        .apply_chunk(chunks[0].clone(), &ledger_info, None)
+        .unwrap();
+    executor.commit_chunk().unwrap();
+    let li = db.reader.get_latest_ledger_info().unwrap();
+    assert_eq!(li.ledger_info().version(), 0);
+
+    executor
+        .apply_chunk(chunks[1].clone(), &ledger_info, None)
+        .unwrap();
+    executor.commit_chunk().unwrap();
+    let li = db.reader.get_latest_ledger_info().unwrap();
+    assert_eq!(li, ledger_info);
+}
+
 #[test]
 fn test_executor_execute_and_commit_chunk_restart() {
     let first_batch_size = 30;
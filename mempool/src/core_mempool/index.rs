@@ -9,13 +9,17 @@ use crate::{
     logging::{LogEntry, LogSchema},
     shared_mempool::types::MultiBucketTimelineIndexIds,
 };
+use aptos_config::config::MempoolLaneConfig;
 use aptos_consensus_types::common::TransactionSummary;
 use aptos_logger::prelude::*;
-use aptos_types::account_address::AccountAddress;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{SignedTransaction, TransactionPayload},
+};
 use rand::seq::SliceRandom;
 use std::{
     cmp::Ordering,
-    collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap},
+    collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap, HashSet},
     iter::Rev,
     ops::Bound,
     time::Duration,
@@ -107,6 +111,126 @@ impl Ord for OrderedQueueKey {
     }
 }
 
+/// LaneIndex tracks the priority lanes configured in `MempoolConfig::lanes`.
+/// Every transaction is assigned to at most one lane on insertion. Each lane keeps its own
+/// priority queue of ready transactions, so `get_batch` can fill the lane's reserved share of
+/// the batch before falling back to the main `PriorityIndex`, and counts all of its
+/// transactions to enforce the lane's capacity.
+pub struct LaneIndex {
+    lanes: Vec<Lane>,
+}
+
+struct Lane {
+    config: MempoolLaneConfig,
+    senders: HashSet<AccountAddress>,
+    entry_function_modules: HashSet<(AccountAddress, String)>,
+    priority_index: PriorityIndex,
+    size: usize,
+}
+
+impl Lane {
+    fn matches(&self, txn: &SignedTransaction) -> bool {
+        if self.senders.contains(&txn.sender()) {
+            return true;
+        }
+        if let TransactionPayload::EntryFunction(entry_function) = txn.payload() {
+            let module = entry_function.module();
+            if self
+                .entry_function_modules
+                .contains(&(*module.address(), module.name().to_string()))
+            {
+                return true;
+            }
+        }
+        matches!(self.config.min_gas_unit_price, Some(min_gas_unit_price) if txn.gas_unit_price() >= min_gas_unit_price)
+    }
+}
+
+impl LaneIndex {
+    pub(crate) fn new(lanes: &[MempoolLaneConfig]) -> Self {
+        Self {
+            lanes: lanes
+                .iter()
+                .map(|config| Lane {
+                    senders: config.senders.iter().cloned().collect(),
+                    // The config sanitizer rejects malformed modules
+                    entry_function_modules: config
+                        .parse_entry_function_modules()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect(),
+                    config: config.clone(),
+                    priority_index: PriorityIndex::new(),
+                    size: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the first lane matching the transaction, if any.
+    pub(crate) fn assign(&self, txn: &SignedTransaction) -> Option<usize> {
+        self.lanes.iter().position(|lane| lane.matches(txn))
+    }
+
+    /// Returns the name and capacity of the lane if it can't take any more transactions.
+    pub(crate) fn check_capacity(&self, lane: Option<usize>) -> Option<(&str, usize)> {
+        let lane = &self.lanes[lane?];
+        if lane.size >= lane.config.capacity {
+            Some((&lane.config.name, lane.config.capacity))
+        } else {
+            None
+        }
+    }
+
+    /// Counts a transaction stored in mempool against its lane's capacity.
+    pub(crate) fn insert(&mut self, txn: &MempoolTransaction) {
+        if let Some(lane) = txn.lane {
+            self.lanes[lane].size += 1;
+        }
+    }
+
+    /// Adds a transaction that became ready for consensus to its lane's priority queue.
+    pub(crate) fn insert_ready(&mut self, txn: &MempoolTransaction) {
+        if let Some(lane) = txn.lane {
+            self.lanes[lane].priority_index.insert(txn);
+        }
+    }
+
+    /// Removes a transaction that is no longer ready from its lane's priority queue.
+    pub(crate) fn remove_ready(&mut self, txn: &MempoolTransaction) {
+        if let Some(lane) = txn.lane {
+            self.lanes[lane].priority_index.remove(txn);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, txn: &MempoolTransaction) {
+        if let Some(lane) = txn.lane {
+            let lane = &mut self.lanes[lane];
+            lane.priority_index.remove(txn);
+            lane.size = lane.size.saturating_sub(1);
+        }
+    }
+
+    /// Iterates over the lanes as (name, reserved batch percentage, priority queue) triples.
+    pub(crate) fn iter_queues(&self) -> impl Iterator<Item = (&str, u64, PriorityQueueIter)> {
+        self.lanes.iter().map(|lane| {
+            (
+                lane.config.name.as_str(),
+                lane.config.reserved_batch_percentage,
+                lane.priority_index.iter(),
+            )
+        })
+    }
+
+    /// Returns (lane name, number of transactions) pairs.
+    pub(crate) fn get_sizes(&self) -> Vec<(&str, usize)> {
+        self.lanes
+            .iter()
+            .map(|lane| (lane.config.name.as_str(), lane.size))
+            .collect()
+    }
+}
+
 /// TTLIndex is used to perform garbage collection of old transactions in Mempool.
 /// Periodically separate GC-like job queries this index to find out transactions that have to be
 /// removed. Index is represented as `BTreeSet<TTLOrderingKey>`, where `TTLOrderingKey`
@@ -260,6 +384,11 @@ impl TimelineIndex {
     pub(crate) fn size(&self) -> usize {
         self.timeline.len()
     }
+
+    /// Iterate over the timeline in broadcast order, as (timeline_id, (Address, sequence_number)).
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&u64, &(AccountAddress, u64))> {
+        self.timeline.iter()
+    }
 }
 
 pub struct MultiBucketTimelineIndex {
@@ -342,14 +471,23 @@ impl MultiBucketTimelineIndex {
     }
 
     #[inline]
-    fn get_timeline(&mut self, ranking_score: u64) -> &mut TimelineIndex {
-        let index = self
-            .bucket_mins
+    pub(crate) fn get_bucket_index(&self, ranking_score: u64) -> usize {
+        self.bucket_mins
             .binary_search(&ranking_score)
-            .unwrap_or_else(|i| i - 1);
+            .unwrap_or_else(|i| i - 1)
+    }
+
+    #[inline]
+    fn get_timeline(&mut self, ranking_score: u64) -> &mut TimelineIndex {
+        let index = self.get_bucket_index(ranking_score);
         self.timelines.get_mut(index).unwrap()
     }
 
+    /// Iterate over the timeline of each bucket, together with the bucket's minimum ranking score.
+    pub(crate) fn iter_buckets(&self) -> impl Iterator<Item = (u64, &TimelineIndex)> {
+        self.bucket_mins.iter().cloned().zip(self.timelines.iter())
+    }
+
     pub(crate) fn insert(&mut self, txn: &mut MempoolTransaction) {
         self.get_timeline(txn.ranking_score).insert(txn);
     }
@@ -376,10 +514,7 @@ impl MultiBucketTimelineIndex {
 
     #[inline]
     pub(crate) fn get_bucket(&self, ranking_score: u64) -> &str {
-        let index = self
-            .bucket_mins
-            .binary_search(&ranking_score)
-            .unwrap_or_else(|i| i - 1);
+        let index = self.get_bucket_index(ranking_score);
         self.bucket_mins_to_string[index].as_str()
     }
 }
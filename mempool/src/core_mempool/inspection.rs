@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Operator-facing view of the contents of mempool, used to answer questions
+//! such as "why is my transaction not included in a block?".
+
+use aptos_crypto::HashValue;
+use aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of the pending transactions in mempool and their broadcast state.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct MempoolInspection {
+    /// Pending transactions, grouped by sender and ordered by address.
+    pub accounts: Vec<AccountInspection>,
+    /// The broadcast timeline of each broadcast bucket.
+    pub timeline: Vec<TimelineBucketInspection>,
+    /// True if the snapshot was cut short because mempool holds more
+    /// transactions than a single inspection returns.
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct AccountInspection {
+    pub address: AccountAddress,
+    /// The account's sequence number as last known by mempool.
+    pub account_sequence_number: u64,
+    /// Pending transactions of the account, ordered by sequence number.
+    pub transactions: Vec<TransactionInspection>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TransactionInspection {
+    pub hash: HashValue,
+    pub sequence_number: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+    pub insertion_timestamp_usecs: u64,
+    /// Where the transaction came from: a client, a downstream peer or a peer validator.
+    pub submitted_by: String,
+    pub state: TransactionState,
+    /// Position in the broadcast timeline. `None` if the transaction is not
+    /// broadcast, either because it is parked or because it was received from
+    /// a peer validator.
+    pub timeline_position: Option<TimelinePosition>,
+    /// Peers the transaction has been broadcast to.
+    pub broadcast_to: Vec<PeerBroadcast>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TransactionState {
+    /// The transaction can be included in the next block.
+    Ready,
+    /// The transaction is held in the parking lot and can't be included until
+    /// the reason is resolved.
+    Parked { reason: ParkedReason },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParkedReason {
+    /// A transaction with a lower sequence number from the same sender is
+    /// missing from mempool, so this one can't be executed yet.
+    SequenceNumberGap { missing_sequence_number: u64 },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TimelinePosition {
+    /// Index of the broadcast bucket, see `MempoolConfig::broadcast_buckets`.
+    pub bucket: usize,
+    pub timeline_id: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct PeerBroadcast {
+    pub peer: String,
+    /// False while the batch containing the transaction is waiting for an ack,
+    /// or was asked to be retried by the peer.
+    pub acknowledged: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TimelineBucketInspection {
+    /// Minimum ranking score (gas unit price) of transactions in the bucket.
+    pub bucket_min: u64,
+    /// Number of transactions in the bucket's timeline.
+    pub size: usize,
+    /// Transactions in the bucket, in broadcast order.
+    pub entries: Vec<TimelineEntry>,
+    /// The last timeline ID broadcast to each peer.
+    pub peer_timeline_ids: Vec<PeerTimelineId>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TimelineEntry {
+    pub timeline_id: u64,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct PeerTimelineId {
+    pub peer: String,
+    pub timeline_id: u64,
+}
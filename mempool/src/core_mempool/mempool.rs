@@ -6,7 +6,8 @@
 //! agreed upon.
 use crate::{
     core_mempool::{
-        index::TxnPointer,
+        index::{OrderedQueueKey, TxnPointer},
+        inspection::MempoolInspection,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
     },
@@ -195,7 +196,6 @@ impl Mempool {
     ///                          exclude_transactions. Should only be true for Quorum Store.
     /// `exclude_transactions` - transactions that were sent to Consensus but were not committed yet
     ///  mempool should filter out such transactions.
-    #[allow(clippy::explicit_counter_loop)]
     pub(crate) fn get_batch(
         &self,
         max_txns: u64,
@@ -238,40 +238,32 @@ impl Mempool {
         let mut total_bytes = 0;
         let seen_size = seen.len();
         let mut txn_walked = 0usize;
-        // iterate over the queue of transactions based on gas price
-        'main: for txn in self.transactions.iter_queue() {
-            txn_walked += 1;
-            if seen.contains_key(&TxnPointer::from(txn)) {
+        // fill the share of the batch reserved for each priority lane first, so lane
+        // transactions are not crowded out by higher paying transactions
+        for (lane, reserved_batch_percentage, queue) in self.transactions.iter_lane_queues() {
+            let reserved_txns = max_txns * reserved_batch_percentage / 100;
+            if reserved_txns == 0 {
                 continue;
             }
-            let tx_seq = txn.sequence_number.transaction_sequence_number;
-            let account_sequence_number = self.transactions.get_sequence_number(&txn.address);
-            let seen_previous =
-                tx_seq > 0 && seen.contains_key(&TxnPointer::new(txn.address, tx_seq - 1));
-            // include transaction if it's "next" for given account or
-            // we've already sent its ancestor to Consensus.
-            if seen_previous || account_sequence_number == Some(&tx_seq) {
-                let ptr = TxnPointer::from(txn);
-                seen.insert(ptr, txn.gas_ranking_score);
-                result.push(ptr);
-                if (result.len() as u64) == max_txns {
-                    break;
-                }
-
-                // check if we can now include some transactions
-                // that were skipped before for given account
-                let mut skipped_txn = TxnPointer::new(txn.address, tx_seq + 1);
-                while skipped.contains(&skipped_txn) {
-                    seen.insert(skipped_txn, txn.gas_ranking_score);
-                    result.push(skipped_txn);
-                    if (result.len() as u64) == max_txns {
-                        break 'main;
-                    }
-                    skipped_txn = TxnPointer::new(txn.address, skipped_txn.sequence_number + 1);
-                }
-            } else {
-                skipped.insert(TxnPointer::from(txn));
-            }
+            let lane_start = result.len();
+            txn_walked += self.collect_ready_txns(
+                queue,
+                lane_start as u64 + reserved_txns,
+                &mut seen,
+                &mut skipped,
+                &mut result,
+            );
+            counters::core_mempool_lane_batch_txns(lane, result.len() - lane_start);
+        }
+        // iterate over the queue of transactions based on gas price
+        if (result.len() as u64) < max_txns {
+            txn_walked += self.collect_ready_txns(
+                self.transactions.iter_queue(),
+                max_txns,
+                &mut seen,
+                &mut skipped,
+                &mut result,
+            );
         }
         let result_size = result.len();
         let mut block = Vec::with_capacity(result_size);
@@ -341,6 +333,56 @@ impl Mempool {
         block
     }
 
+    /// Walks `queue` in priority order and appends the transactions that can be proposed to
+    /// `result`, until `result` holds `max_txns` transactions or the queue is exhausted.
+    /// Returns the number of transactions walked.
+    #[allow(clippy::explicit_counter_loop)]
+    fn collect_ready_txns<'a>(
+        &self,
+        queue: impl Iterator<Item = &'a OrderedQueueKey>,
+        max_txns: u64,
+        seen: &mut HashMap<TxnPointer, u64>,
+        skipped: &mut HashSet<TxnPointer>,
+        result: &mut Vec<TxnPointer>,
+    ) -> usize {
+        let mut txn_walked = 0usize;
+        'main: for txn in queue {
+            txn_walked += 1;
+            if seen.contains_key(&TxnPointer::from(txn)) {
+                continue;
+            }
+            let tx_seq = txn.sequence_number.transaction_sequence_number;
+            let account_sequence_number = self.transactions.get_sequence_number(&txn.address);
+            let seen_previous =
+                tx_seq > 0 && seen.contains_key(&TxnPointer::new(txn.address, tx_seq - 1));
+            // include transaction if it's "next" for given account or
+            // we've already sent its ancestor to Consensus.
+            if seen_previous || account_sequence_number == Some(&tx_seq) {
+                let ptr = TxnPointer::from(txn);
+                seen.insert(ptr, txn.gas_ranking_score);
+                result.push(ptr);
+                if (result.len() as u64) == max_txns {
+                    break;
+                }
+
+                // check if we can now include some transactions
+                // that were skipped before for given account
+                let mut skipped_txn = TxnPointer::new(txn.address, tx_seq + 1);
+                while skipped.contains(&skipped_txn) {
+                    seen.insert(skipped_txn, txn.gas_ranking_score);
+                    result.push(skipped_txn);
+                    if (result.len() as u64) == max_txns {
+                        break 'main;
+                    }
+                    skipped_txn = TxnPointer::new(txn.address, skipped_txn.sequence_number + 1);
+                }
+            } else {
+                skipped.insert(TxnPointer::from(txn));
+            }
+        }
+        txn_walked
+    }
+
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
@@ -375,6 +417,15 @@ impl Mempool {
         self.transactions.gen_snapshot()
     }
 
+    /// Returns the pending transactions of `sender` (or of all senders) with their state.
+    pub(crate) fn inspect(
+        &self,
+        sender: Option<AccountAddress>,
+        max_txns: usize,
+    ) -> MempoolInspection {
+        self.transactions.inspect(sender, max_txns)
+    }
+
     #[cfg(test)]
     pub fn get_parking_lot_size(&self) -> usize {
         self.transactions.get_parking_lot_size()
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod index;
+pub mod inspection;
 mod mempool;
 mod transaction;
 mod transaction_store;
@@ -24,6 +24,8 @@ pub struct MempoolTransaction {
     pub sequence_info: SequenceInfo,
     pub insertion_info: InsertionInfo,
     pub was_parked: bool,
+    // Index of the priority lane the transaction is assigned to, see `LaneIndex`.
+    pub lane: Option<usize>,
 }
 
 impl MempoolTransaction {
@@ -47,6 +49,7 @@ impl MempoolTransaction {
             timeline_state,
             insertion_info: InsertionInfo::new(insertion_time, client_submitted, timeline_state),
             was_parked: false,
+            lane: None,
         }
     }
 
@@ -5,8 +5,12 @@
 use crate::{
     core_mempool::{
         index::{
-            AccountTransactions, MultiBucketTimelineIndex, ParkingLotIndex, PriorityIndex,
-            PriorityQueueIter, TTLIndex,
+            AccountTransactions, LaneIndex, MultiBucketTimelineIndex, ParkingLotIndex,
+            PriorityIndex, PriorityQueueIter, TTLIndex,
+        },
+        inspection::{
+            AccountInspection, MempoolInspection, ParkedReason, TimelineBucketInspection,
+            TimelineEntry, TimelinePosition, TransactionInspection, TransactionState,
         },
         mempool::Mempool,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
@@ -39,6 +43,14 @@ pub const TXN_INDEX_ESTIMATED_BYTES: usize = size_of::<crate::core_mempool::inde
     + (size_of::<u64>() * 3 + size_of::<AccountAddress>()) // timeline_index
     + (size_of::<HashValue>() + size_of::<u64>() + size_of::<AccountAddress>()); // hash_index
 
+/// Returns the lowest gas unit price a transaction may replace a transaction with
+/// `current_gas_price` with, i.e. `current_gas_price` raised by `bump_percentage` percent
+/// (rounded up), and at least by one.
+fn min_replacement_gas_price(current_gas_price: u64, bump_percentage: u64) -> u64 {
+    let bumped = (current_gas_price as u128 * (100 + bump_percentage as u128) + 99) / 100;
+    max(bumped, current_gas_price as u128 + 1).min(u64::MAX as u128) as u64
+}
+
 /// TransactionStore is in-memory storage for all transactions in mempool.
 pub struct TransactionStore {
     // main DS
@@ -69,12 +81,15 @@ pub struct TransactionStore {
     size_bytes: usize,
     // keeps track of txns that were resubmitted with higher gas
     gas_upgraded_index: HashMap<TxnPointer, u64>,
+    // priority lanes with reserved space in consensus batches
+    lane_index: LaneIndex,
 
     // configuration
     capacity: usize,
     capacity_bytes: usize,
     capacity_per_user: usize,
     max_batch_bytes: u64,
+    replace_by_fee_min_gas_price_bump_percentage: u64,
 
     // eager expiration
     eager_expire_threshold: Option<Duration>,
@@ -101,12 +116,15 @@ impl TransactionStore {
             // estimated size in bytes
             size_bytes: 0,
             gas_upgraded_index: HashMap::new(),
+            lane_index: LaneIndex::new(&config.lanes),
 
             // configuration
             capacity: config.capacity,
             capacity_bytes: config.capacity_bytes,
             capacity_per_user: config.capacity_per_user,
             max_batch_bytes: config.shared_mempool_max_batch_bytes,
+            replace_by_fee_min_gas_price_bump_percentage: config
+                .replace_by_fee_min_gas_price_bump_percentage,
 
             // eager expiration
             eager_expire_threshold: config.eager_expire_threshold_ms.map(Duration::from_millis),
@@ -192,55 +210,27 @@ impl TransactionStore {
     }
 
     /// Insert transaction into TransactionStore. Performs validation checks and updates indexes.
-    pub(crate) fn insert(&mut self, txn: MempoolTransaction) -> MempoolStatus {
+    pub(crate) fn insert(&mut self, mut txn: MempoolTransaction) -> MempoolStatus {
         let address = txn.get_sender();
         let txn_seq_num = txn.sequence_info.transaction_sequence_number;
         let acc_seq_num = txn.sequence_info.account_sequence_number;
-        let mut gas_upgraded = false;
 
-        // If the transaction is already in Mempool, we only allow the user to
-        // increase the gas unit price to speed up a transaction, but not the max gas.
-        //
-        // Transactions with all the same inputs (but possibly signed differently) are idempotent
-        // since the raw transaction is the same
-        if let Some(txns) = self.transactions.get_mut(&address) {
-            if let Some(current_version) = txns.get_mut(&txn_seq_num) {
-                if current_version.txn.payload() != txn.txn.payload() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different payload".to_string(),
-                    );
-                } else if current_version.txn.expiration_timestamp_secs()
-                    != txn.txn.expiration_timestamp_secs()
-                {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different expiration timestamp"
-                            .to_string(),
-                    );
-                } else if current_version.txn.max_gas_amount() != txn.txn.max_gas_amount() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different max gas amount"
-                            .to_string(),
-                    );
-                } else if current_version.get_gas_price() < txn.get_gas_price() {
-                    // Update txn if gas unit price is a larger value than before
-                    if let Some(txn) = txns.remove(&txn_seq_num) {
-                        self.index_remove(&txn);
-                    };
-                    gas_upgraded = true;
-                } else if current_version.get_gas_price() > txn.get_gas_price() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a higher gas price".to_string(),
-                    );
-                } else {
-                    // If the transaction is the same, it's an idempotent call
-                    // Updating signers is not supported, the previous submission must fail
-                    counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
-                    return MempoolStatus::new(MempoolStatusCode::Accepted);
+        // If a transaction with the same sequence number is already in Mempool, the new one may
+        // only replace it by fee, see `check_replacement`. The replaced transaction is only
+        // removed once the replacement is known to be accepted.
+        let replaced_lane = match self.get_mempool_txn(&address, txn_seq_num) {
+            Some(current_version) => {
+                if let Err(status) = self.check_replacement(current_version, &txn) {
+                    return status;
                 }
-            }
-        }
+                Some(current_version.lane)
+            },
+            None => None,
+        };
+        let replacing = replaced_lane.is_some();
 
-        if self.check_is_full_after_eviction(&txn, acc_seq_num) {
+        // A replacement doesn't increase the number of transactions in Mempool
+        if !replacing && self.check_is_full_after_eviction(&txn, acc_seq_num) {
             return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
                 "Mempool is full. Mempool size: {}, Capacity: {}",
                 self.system_ttl_index.size(),
@@ -248,6 +238,30 @@ impl TransactionStore {
             ));
         }
 
+        txn.lane = self.lane_index.assign(&txn.txn);
+        if replaced_lane != Some(txn.lane) {
+            if let Some((lane_name, lane_capacity)) = self.lane_index.check_capacity(txn.lane) {
+                counters::core_mempool_lane_rejected_txns(lane_name);
+                return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+                    "Mempool lane {} is full. Capacity: {}",
+                    lane_name, lane_capacity,
+                ));
+            }
+        }
+
+        if replacing {
+            if let Some(replaced) = self
+                .transactions
+                .get_mut(&address)
+                .and_then(|txns| txns.remove(&txn_seq_num))
+            {
+                // Removing the replaced transaction from the timeline index invalidates it for
+                // broadcast, the replacement gets a new timeline id once it's ready.
+                self.index_remove(&replaced);
+            }
+            counters::CORE_MEMPOOL_REPLACED_TXNS.inc();
+        }
+
         self.clean_committed_transactions(&address, acc_seq_num);
 
         self.transactions
@@ -273,7 +287,8 @@ impl TransactionStore {
                 .insert(txn.get_committed_hash(), (txn.get_sender(), txn_seq_num));
             self.sequence_numbers.insert(txn.get_sender(), acc_seq_num);
             self.size_bytes += txn.get_estimated_bytes();
-            if gas_upgraded {
+            self.lane_index.insert(&txn);
+            if replacing {
                 self.gas_upgraded_index
                     .insert(TxnPointer::from(&txn), txn.get_gas_price());
             }
@@ -281,7 +296,81 @@ impl TransactionStore {
             self.track_indices();
         }
         self.process_ready_transactions(&address, acc_seq_num);
-        MempoolStatus::new(MempoolStatusCode::Accepted)
+        if replacing {
+            MempoolStatus::new(MempoolStatusCode::Replaced)
+        } else {
+            MempoolStatus::new(MempoolStatusCode::Accepted)
+        }
+    }
+
+    /// Checks whether `replacement` may replace `current`, a transaction in Mempool with the same
+    /// sender and sequence number. Replace-by-fee requires the same payload and max gas amount, an
+    /// expiration timestamp no earlier than the current one, and a gas unit price raised by at
+    /// least `replace_by_fee_min_gas_price_bump_percentage` percent.
+    ///
+    /// Returns the status to respond with when `replacement` must not be inserted. Resubmitting
+    /// the same raw transaction (but possibly signed differently) is idempotent and accepted.
+    fn check_replacement(
+        &self,
+        current: &MempoolTransaction,
+        replacement: &MempoolTransaction,
+    ) -> Result<(), MempoolStatus> {
+        let invalid_update = |message: String| {
+            Err(MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(message))
+        };
+
+        if current.txn.payload() != replacement.txn.payload() {
+            return invalid_update(
+                "Transaction already in mempool with a different payload".to_string(),
+            );
+        }
+        if current.txn.max_gas_amount() != replacement.txn.max_gas_amount() {
+            return invalid_update(
+                "Transaction already in mempool with a different max gas amount".to_string(),
+            );
+        }
+        let current_expiration = current.txn.expiration_timestamp_secs();
+        let expiration = replacement.txn.expiration_timestamp_secs();
+        if expiration < current_expiration {
+            return invalid_update(
+                "Transaction already in mempool with a later expiration timestamp".to_string(),
+            );
+        }
+
+        let current_gas_price = current.get_gas_price();
+        let gas_price = replacement.get_gas_price();
+        if gas_price == current_gas_price {
+            if expiration == current_expiration {
+                // If the transaction is the same, it's an idempotent call
+                // Updating signers is not supported, the previous submission must fail
+                counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
+                return Err(MempoolStatus::new(MempoolStatusCode::Accepted));
+            }
+            return invalid_update(
+                "Transaction already in mempool with a different expiration timestamp, replacing it requires a higher gas price"
+                    .to_string(),
+            );
+        }
+        if gas_price < current_gas_price {
+            return invalid_update(
+                "Transaction already in mempool with a higher gas price".to_string(),
+            );
+        }
+
+        let min_gas_price = min_replacement_gas_price(
+            current_gas_price,
+            self.replace_by_fee_min_gas_price_bump_percentage,
+        );
+        if gas_price < min_gas_price {
+            return invalid_update(format!(
+                "Replacement gas unit price {} is too low, it must be at least {} ({}% above the gas unit price {} of the transaction in mempool)",
+                gas_price,
+                min_gas_price,
+                self.replace_by_fee_min_gas_price_bump_percentage,
+                current_gas_price,
+            ));
+        }
+        Ok(())
     }
 
     fn track_indices(&self) {
@@ -311,6 +400,7 @@ impl TransactionStore {
             self.hash_index.len(),
         );
         counters::core_mempool_index_size(counters::SIZE_BYTES_LABEL, self.size_bytes);
+        counters::core_mempool_lane_size(&self.lane_index.get_sizes());
     }
 
     /// Checks if Mempool is full.
@@ -431,6 +521,7 @@ impl TransactionStore {
             while let Some(txn) = txns.get_mut(&min_seq) {
                 let process_ready = !self.priority_index.contains(txn);
                 self.priority_index.insert(txn);
+                self.lane_index.insert_ready(txn);
 
                 let process_broadcast_ready = txn.timeline_state == TimelineState::NotReady;
                 if process_broadcast_ready {
@@ -551,6 +642,7 @@ impl TransactionStore {
         self.system_ttl_index.remove(txn);
         self.expiration_time_index.remove(txn);
         self.priority_index.remove(txn);
+        self.lane_index.remove(txn);
         self.timeline_index.remove(txn);
         self.parking_lot_index.remove(txn);
         self.hash_index.remove(&txn.get_committed_hash());
@@ -718,6 +810,7 @@ impl TransactionStore {
                     self.parking_lot_index.insert(t);
                     t.was_parked = true;
                     self.priority_index.remove(t);
+                    self.lane_index.remove_ready(t);
                     self.timeline_index.remove(t);
                     if let TimelineState::Ready(_) = t.timeline_state {
                         t.timeline_state = TimelineState::NotReady;
@@ -759,6 +852,10 @@ impl TransactionStore {
         self.priority_index.iter()
     }
 
+    pub(crate) fn iter_lane_queues(&self) -> impl Iterator<Item = (&str, u64, PriorityQueueIter)> {
+        self.lane_index.iter_queues()
+    }
+
     pub(crate) fn gen_snapshot(&self) -> TxnsLog {
         let mut txns_log = TxnsLog::new();
         for (account, txns) in self.transactions.iter() {
@@ -779,6 +876,117 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Generates an inspection of the transactions of `sender`, or of all accounts if `sender` is
+    /// `None`. At most `max_txns` transactions, and `max_txns` timeline entries per broadcast
+    /// bucket, are included. Broadcast information is left for the shared mempool to fill in.
+    pub(crate) fn inspect(
+        &self,
+        sender: Option<AccountAddress>,
+        max_txns: usize,
+    ) -> MempoolInspection {
+        let mut addresses: Vec<_> = match sender {
+            Some(sender) => self
+                .transactions
+                .contains_key(&sender)
+                .then_some(sender)
+                .into_iter()
+                .collect(),
+            None => self.transactions.keys().cloned().collect(),
+        };
+        addresses.sort();
+
+        let mut inspection = MempoolInspection::default();
+        let mut num_txns = 0;
+        for address in addresses {
+            let txns = match self.transactions.get(&address) {
+                Some(txns) => txns,
+                None => continue,
+            };
+            if num_txns + txns.len() > max_txns {
+                inspection.truncated = true;
+                break;
+            }
+            num_txns += txns.len();
+            inspection
+                .accounts
+                .push(self.inspect_account(&address, txns));
+        }
+
+        inspection.timeline = self
+            .timeline_index
+            .iter_buckets()
+            .map(|(bucket_min, timeline)| TimelineBucketInspection {
+                bucket_min,
+                size: timeline.size(),
+                entries: timeline
+                    .iter()
+                    .filter(|(_, (address, _))| sender.map_or(true, |sender| sender == *address))
+                    .take(max_txns)
+                    .map(|(&timeline_id, &(sender, sequence_number))| TimelineEntry {
+                        timeline_id,
+                        sender,
+                        sequence_number,
+                    })
+                    .collect(),
+                peer_timeline_ids: vec![],
+            })
+            .collect();
+        inspection
+    }
+
+    fn inspect_account(
+        &self,
+        address: &AccountAddress,
+        txns: &AccountTransactions,
+    ) -> AccountInspection {
+        let account_sequence_number = self.get_sequence_number(address).cloned().unwrap_or(0);
+        let transactions = txns
+            .iter()
+            .map(|(&sequence_number, txn)| {
+                let state = if self.parking_lot_index.contains(address, &sequence_number) {
+                    // A transaction is parked when it can't be reached from the account's
+                    // sequence number through consecutive transactions in mempool.
+                    let missing_sequence_number = (account_sequence_number..sequence_number)
+                        .find(|seq| !txns.contains_key(seq))
+                        .unwrap_or(account_sequence_number);
+                    TransactionState::Parked {
+                        reason: ParkedReason::SequenceNumberGap {
+                            missing_sequence_number,
+                        },
+                    }
+                } else {
+                    TransactionState::Ready
+                };
+                let timeline_position = match txn.timeline_state {
+                    TimelineState::Ready(timeline_id) => Some(TimelinePosition {
+                        bucket: self.timeline_index.get_bucket_index(txn.ranking_score),
+                        timeline_id,
+                    }),
+                    TimelineState::NotReady | TimelineState::NonQualified => None,
+                };
+                TransactionInspection {
+                    hash: txn.get_committed_hash(),
+                    sequence_number,
+                    gas_unit_price: txn.get_gas_price(),
+                    expiration_timestamp_secs: txn.txn.expiration_timestamp_secs(),
+                    insertion_timestamp_usecs: aptos_infallible::duration_since_epoch_at(
+                        &txn.insertion_info.insertion_time,
+                    )
+                    .as_micros() as u64,
+                    submitted_by: txn.insertion_info.submitted_by_label().to_string(),
+                    state,
+                    timeline_position,
+                    broadcast_to: vec![],
+                }
+            })
+            .collect();
+        AccountInspection {
+            address: *address,
+            account_sequence_number,
+            transactions,
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
@@ -62,6 +62,7 @@ pub const SUCCESS_LABEL: &str = "success";
 // Bounded executor task labels
 pub const CLIENT_EVENT_LABEL: &str = "client_event";
 pub const CLIENT_EVENT_GET_TXN_LABEL: &str = "client_event_get_txn";
+pub const CLIENT_EVENT_INSPECT_LABEL: &str = "client_event_inspect";
 pub const RECONFIG_EVENT_LABEL: &str = "reconfig";
 pub const PEER_BROADCAST_EVENT_LABEL: &str = "peer_broadcast";
 
@@ -160,6 +161,56 @@ pub fn core_mempool_timeline_index_size(bucket_min_size_pairs: &Vec<(&str, usize
     }
 }
 
+/// Counter tracking number of txns in each priority lane
+static CORE_MEMPOOL_LANE_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_core_mempool_lane_size",
+        "Number of txns in each core mempool priority lane",
+        &["lane"]
+    )
+    .unwrap()
+});
+
+pub fn core_mempool_lane_size(lane_size_pairs: &Vec<(&str, usize)>) {
+    for &(lane, size) in lane_size_pairs {
+        CORE_MEMPOOL_LANE_SIZE
+            .with_label_values(&[lane])
+            .set(size as i64)
+    }
+}
+
+/// Counter tracking number of txns rejected because their priority lane is full
+static CORE_MEMPOOL_LANE_REJECTED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_core_mempool_lane_rejected_txns_count",
+        "Number of txns rejected because their core mempool priority lane is full",
+        &["lane"]
+    )
+    .unwrap()
+});
+
+pub fn core_mempool_lane_rejected_txns(lane: &str) {
+    CORE_MEMPOOL_LANE_REJECTED_TXNS
+        .with_label_values(&[lane])
+        .inc();
+}
+
+/// Counter tracking number of txns pulled into batches from the reserved share of each priority lane
+static CORE_MEMPOOL_LANE_BATCH_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_core_mempool_lane_batch_txns_count",
+        "Number of txns pulled into batches from the reserved share of each core mempool priority lane",
+        &["lane"]
+    )
+    .unwrap()
+});
+
+pub fn core_mempool_lane_batch_txns(lane: &str, num_txns: usize) {
+    CORE_MEMPOOL_LANE_BATCH_TXNS
+        .with_label_values(&[lane])
+        .inc_by(num_txns as u64);
+}
+
 /// Counter tracking number of txns removed from core mempool
 pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -178,6 +229,15 @@ pub static CORE_MEMPOOL_IDEMPOTENT_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of txns that replaced a txn with the same sequence number by fee
+pub static CORE_MEMPOOL_REPLACED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_core_mempool_replaced_txns_count",
+        "Number of txns received that replaced a txn with the same sequence number by fee"
+    )
+    .unwrap()
+});
+
 pub fn core_mempool_txn_commit_latency(
     stage: &'static str,
     submitted_by: &'static str,
@@ -58,6 +58,7 @@
 
 #[cfg(any(test, feature = "fuzzing"))]
 mod tests;
+pub use core_mempool::inspection;
 pub use shared_mempool::{
     bootstrap, network,
     network::MempoolSyncMsg,
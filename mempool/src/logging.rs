@@ -157,6 +157,7 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    InspectMempool,
     GetBlock,
     QuorumStore,
     StateSyncCommit,
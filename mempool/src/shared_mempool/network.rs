@@ -5,6 +5,9 @@
 //! Interface between Mempool and Network layers.
 
 use crate::{
+    core_mempool::inspection::{
+        MempoolInspection, PeerBroadcast, PeerTimelineId, TimelinePosition,
+    },
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     shared_mempool::{
@@ -507,6 +510,73 @@ impl<NetworkClient: NetworkClientInterface<MempoolSyncMsg>> MempoolNetworkInterf
     pub fn sync_states_exists(&self, peer: &PeerNetworkId) -> bool {
         self.sync_states.read().get(peer).is_some()
     }
+
+    /// Fills in the peers each inspected transaction was broadcast to, and how far the
+    /// broadcasts to each peer have progressed through the timeline.
+    pub(crate) fn add_broadcast_info(&self, inspection: &mut MempoolInspection) {
+        let sync_states = self.sync_states.read();
+        let mut peers: Vec<_> = sync_states.iter().collect();
+        peers.sort_by_key(|(peer, _)| **peer);
+
+        for (bucket, timeline) in inspection.timeline.iter_mut().enumerate() {
+            timeline.peer_timeline_ids = peers
+                .iter()
+                .filter_map(|(peer, state)| {
+                    state
+                        .timeline_id
+                        .id_per_bucket
+                        .get(bucket)
+                        .map(|&timeline_id| PeerTimelineId {
+                            peer: peer.to_string(),
+                            timeline_id,
+                        })
+                })
+                .collect();
+        }
+
+        for txn in inspection
+            .accounts
+            .iter_mut()
+            .flat_map(|account| account.transactions.iter_mut())
+        {
+            let position = match txn.timeline_position {
+                Some(position) => position,
+                None => continue,
+            };
+            txn.broadcast_to = peers
+                .iter()
+                .filter(|(_, state)| {
+                    state
+                        .timeline_id
+                        .id_per_bucket
+                        .get(position.bucket)
+                        .map_or(false, |&timeline_id| timeline_id >= position.timeline_id)
+                })
+                .map(|(peer, state)| {
+                    let broadcast_info = &state.broadcast_info;
+                    let acknowledged = !broadcast_info
+                        .sent_batches
+                        .keys()
+                        .chain(broadcast_info.retry_batches.iter())
+                        .any(|batch_id| batch_contains(batch_id, &position));
+                    PeerBroadcast {
+                        peer: peer.to_string(),
+                        acknowledged,
+                    }
+                })
+                .collect();
+        }
+    }
+}
+
+/// Returns true if the timeline range of `batch_id` covers `position`.
+fn batch_contains(batch_id: &MultiBatchId, position: &TimelinePosition) -> bool {
+    batch_id
+        .0
+        .get(position.bucket)
+        .map_or(false, |&(start_id, end_id)| {
+            start_id < position.timeline_id && position.timeline_id <= end_id
+        })
 }
 
 #[derive(Clone, Debug)]
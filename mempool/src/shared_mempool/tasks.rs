@@ -4,7 +4,7 @@
 
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 use crate::{
-    core_mempool::{CoreMempool, TimelineState},
+    core_mempool::{inspection::MempoolInspection, CoreMempool, TimelineState},
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
@@ -25,6 +25,7 @@ use aptos_metrics_core::HistogramTimer;
 use aptos_network::application::interface::NetworkClientInterface;
 use aptos_storage_interface::state_view::LatestDbStateCheckpointView;
 use aptos_types::{
+    account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::{OnChainConfigPayload, OnChainConsensusConfig},
     transaction::SignedTransaction,
@@ -154,6 +155,35 @@ pub(crate) async fn process_client_get_transaction<NetworkClient, TransactionVal
     }
 }
 
+/// Maximum number of transactions returned by a single mempool inspection.
+const MAX_INSPECTED_TRANSACTIONS: usize = 10_000;
+
+/// Processes mempool inspection request by client.
+pub(crate) async fn process_client_inspect_mempool<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    sender: Option<AccountAddress>,
+    callback: oneshot::Sender<MempoolInspection>,
+    timer: HistogramTimer,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    timer.stop_and_record();
+    let mut inspection = smp
+        .mempool
+        .lock()
+        .inspect(sender, MAX_INSPECTED_TRANSACTIONS);
+    smp.network_interface.add_broadcast_info(&mut inspection);
+
+    if callback.send(inspection).is_err() {
+        warn!(LogSchema::event_log(
+            LogEntry::InspectMempool,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionValidator>(
     smp: SharedMempool<NetworkClient, TransactionValidator>,
@@ -432,10 +462,12 @@ fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<Pe
             continue;
         }
         match mempool_status.code {
-            MempoolStatusCode::Accepted => counters::shared_mempool_transactions_processed_inc(
-                counters::SUCCESS_LABEL,
-                &network,
-            ),
+            MempoolStatusCode::Accepted | MempoolStatusCode::Replaced => {
+                counters::shared_mempool_transactions_processed_inc(
+                    counters::SUCCESS_LABEL,
+                    &network,
+                )
+            },
             _ => counters::shared_mempool_transactions_processed_inc(
                 &mempool_status.code.to_string(),
                 &network,
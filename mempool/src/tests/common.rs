@@ -144,7 +144,7 @@ pub(crate) fn add_signed_txn(pool: &mut CoreMempool, transaction: SignedTransact
         )
         .code
     {
-        MempoolStatusCode::Accepted => Ok(()),
+        MempoolStatusCode::Accepted | MempoolStatusCode::Replaced => Ok(()),
         _ => Err(format_err!("insertion failure")),
     }
 }
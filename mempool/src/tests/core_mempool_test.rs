@@ -3,13 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, MempoolTransaction, SubmittedBy, TimelineState},
+    core_mempool::{
+        inspection::{ParkedReason, TimelinePosition, TransactionState},
+        CoreMempool, MempoolTransaction, SubmittedBy, TimelineState,
+    },
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, setup_mempool,
         setup_mempool_with_broadcast_buckets, TestTransaction,
     },
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{MempoolLaneConfig, NodeConfig};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
 use aptos_crypto::HashValue;
 use aptos_types::{
@@ -149,6 +152,144 @@ fn test_fail_for_same_gas_amount_and_not_same_expiration_time() {
     assert!(ret.is_err())
 }
 
+#[test]
+fn test_replace_by_fee() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.broadcast_buckets = vec![0];
+    config.mempool.replace_by_fee_min_gas_price_bump_percentage = 10;
+    let mut pool = CoreMempool::new(&config);
+    let original =
+        TestTransaction::new(0, 0, 100).make_signed_transaction_with_expiration_time(1_000);
+    assert_eq!(
+        pool.add_txn(original.clone(), 100, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::Accepted
+    );
+
+    // The gas price bump is too small
+    let txn = TestTransaction::new(0, 0, 109).make_signed_transaction_with_expiration_time(1_000);
+    assert_eq!(
+        pool.add_txn(txn, 109, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::InvalidUpdate
+    );
+
+    // The replacement can't expire earlier
+    let txn = TestTransaction::new(0, 0, 200).make_signed_transaction_with_expiration_time(999);
+    assert_eq!(
+        pool.add_txn(txn, 200, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::InvalidUpdate
+    );
+
+    // Extending the expiration requires a gas price bump
+    let txn = TestTransaction::new(0, 0, 100).make_signed_transaction_with_expiration_time(2_000);
+    assert_eq!(
+        pool.add_txn(txn, 100, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::InvalidUpdate
+    );
+
+    // A large enough gas price bump replaces the transaction, and may extend the expiration
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline.len(), 1);
+    let replacement =
+        TestTransaction::new(0, 0, 110).make_signed_transaction_with_expiration_time(2_000);
+    assert_eq!(
+        pool.add_txn(replacement.clone(), 110, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::Replaced
+    );
+    assert!(pool.get_by_hash(original.committed_hash()).is_none());
+    assert_eq!(
+        pool.get_by_hash(replacement.clone().committed_hash()),
+        Some(replacement.clone())
+    );
+
+    // The replaced transaction is no longer broadcast, only the replacement is
+    let (timeline, _) = pool.read_timeline(&vec![1].into(), 10);
+    assert_eq!(timeline, vec![replacement.clone()]);
+    assert!(pool.timeline_range(&vec![(0, 1)]).is_empty());
+
+    // Resubmitting the replacement is idempotent
+    assert_eq!(
+        pool.add_txn(replacement.clone(), 110, 0, TimelineState::NotReady, false)
+            .code,
+        MempoolStatusCode::Accepted
+    );
+}
+
+#[test]
+fn test_inspect() {
+    let (mut pool, _) = setup_mempool_with_broadcast_buckets(vec![0, 100]);
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 2, 200),
+        TestTransaction::new(1, 0, 150),
+    ]);
+    let address_0 = TestTransaction::get_address(0);
+    let address_1 = TestTransaction::get_address(1);
+
+    let inspection = pool.inspect(None, 100);
+    assert!(!inspection.truncated);
+    assert_eq!(inspection.accounts.len(), 2);
+
+    // The first transaction of account 0 is ready, the second one is waiting for sequence number 1
+    let account = inspection
+        .accounts
+        .iter()
+        .find(|account| account.address == address_0)
+        .unwrap();
+    assert_eq!(account.transactions.len(), 2);
+    assert_eq!(account.transactions[0].state, TransactionState::Ready);
+    assert_eq!(
+        account.transactions[0].timeline_position,
+        Some(TimelinePosition {
+            bucket: 0,
+            timeline_id: 1,
+        })
+    );
+    assert_eq!(account.transactions[1].state, TransactionState::Parked {
+        reason: ParkedReason::SequenceNumberGap {
+            missing_sequence_number: 1,
+        },
+    });
+    assert_eq!(account.transactions[1].timeline_position, None);
+
+    // Account 1 is broadcast in the higher bucket
+    let account = inspection
+        .accounts
+        .iter()
+        .find(|account| account.address == address_1)
+        .unwrap();
+    assert_eq!(
+        account.transactions[0].timeline_position,
+        Some(TimelinePosition {
+            bucket: 1,
+            timeline_id: 1,
+        })
+    );
+
+    // The timeline view lists the ready transactions of each bucket
+    assert_eq!(inspection.timeline.len(), 2);
+    assert_eq!(inspection.timeline[0].bucket_min, 0);
+    assert_eq!(inspection.timeline[0].size, 1);
+    assert_eq!(inspection.timeline[0].entries[0].sender, address_0);
+    assert_eq!(inspection.timeline[1].bucket_min, 100);
+    assert_eq!(inspection.timeline[1].entries[0].sender, address_1);
+
+    // Inspect a single account
+    let inspection = pool.inspect(Some(address_1), 100);
+    assert_eq!(inspection.accounts.len(), 1);
+    assert_eq!(inspection.accounts[0].address, address_1);
+    assert!(inspection.timeline[0].entries.is_empty());
+    assert_eq!(inspection.timeline[1].entries.len(), 1);
+
+    // The inspection is truncated once it exceeds the limit
+    let inspection = pool.inspect(None, 1);
+    assert!(inspection.truncated);
+}
+
 #[test]
 fn test_update_invalid_transaction_in_mempool() {
     let (mut mempool, mut consensus) = setup_mempool();
@@ -522,6 +663,48 @@ fn test_capacity() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_ok());
 }
 
+#[test]
+fn test_lane_reserved_batch_share() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.lanes = vec![MempoolLaneConfig {
+        name: "governance".into(),
+        senders: vec![TestTransaction::get_address(0)],
+        entry_function_modules: vec![],
+        min_gas_unit_price: None,
+        reserved_batch_percentage: 50,
+        capacity: 2,
+    }];
+    let mut pool = CoreMempool::new(&config);
+
+    // Lane transactions pay less than everything else in mempool
+    let lane_txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+    ]);
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 0, 10),
+        TestTransaction::new(1, 1, 10),
+        TestTransaction::new(1, 2, 10),
+        TestTransaction::new(1, 3, 10),
+    ]);
+
+    // The lane still gets its reserved half of the batch
+    let batch = pool.get_batch(4, 1024 * 1024, true, false, vec![]);
+    assert_eq!(batch.len(), 4);
+    for txn in &lane_txns {
+        assert!(batch.contains(txn));
+    }
+
+    // The lane is full
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 2, 1)).is_err());
+    // Transactions outside of the lane are unaffected
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 0, 1)).is_ok());
+
+    // Committing a lane transaction frees up space in the lane
+    pool.commit_transaction(&TestTransaction::get_address(0), 0);
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 2, 1)).is_ok());
+}
+
 #[test]
 fn test_capacity_bytes() {
     let capacity_bytes = 2_048;
@@ -150,7 +150,10 @@ impl MempoolNode {
                 .await
                 .unwrap();
             let status = receiver.await.unwrap().unwrap();
-            assert_eq!(status.0.code, MempoolStatusCode::Accepted)
+            assert!(matches!(
+                status.0.code,
+                MempoolStatusCode::Accepted | MempoolStatusCode::Replaced
+            ))
         }
     }
 
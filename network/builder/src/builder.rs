@@ -12,10 +12,10 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE,
-        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
-        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
-        NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, NetworkConfig, OutboundTrafficShapingConfig, Peer, PeerRole, PeerSet,
+        RoleType, TransportProtocol, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
+        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -40,7 +40,7 @@ use aptos_network::{
         },
     },
 };
-use aptos_network_discovery::DiscoveryChangeListener;
+use aptos_network_discovery::{DiscoveryChangeListener, SystemDnsResolver};
 use aptos_time_service::TimeService;
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress};
 use std::{clone::Clone, collections::HashSet, sync::Arc, time::Duration};
@@ -80,6 +80,7 @@ impl NetworkBuilder {
         network_context: NetworkContext,
         time_service: TimeService,
         listen_address: NetworkAddress,
+        transport_protocol: TransportProtocol,
         authentication_mode: AuthenticationMode,
         max_frame_size: usize,
         max_message_size: usize,
@@ -88,6 +89,7 @@ impl NetworkBuilder {
         max_concurrent_network_reqs: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_traffic_shaping: OutboundTrafficShapingConfig,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -96,6 +98,7 @@ impl NetworkBuilder {
             network_context,
             time_service.clone(),
             listen_address,
+            transport_protocol,
             peers_and_metadata.clone(),
             authentication_mode,
             network_channel_size,
@@ -105,6 +108,7 @@ impl NetworkBuilder {
             enable_proxy_protocol,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            outbound_traffic_shaping,
         );
 
         NetworkBuilder {
@@ -126,6 +130,7 @@ impl NetworkBuilder {
         network_context: NetworkContext,
         time_service: TimeService,
         listen_address: NetworkAddress,
+        transport_protocol: TransportProtocol,
         authentication_mode: AuthenticationMode,
         peers_and_metadata: Arc<PeersAndMetadata>,
     ) -> NetworkBuilder {
@@ -137,6 +142,7 @@ impl NetworkBuilder {
             network_context,
             time_service,
             listen_address,
+            transport_protocol,
             authentication_mode,
             MAX_FRAME_SIZE,
             MAX_MESSAGE_SIZE,
@@ -145,6 +151,7 @@ impl NetworkBuilder {
             MAX_CONCURRENT_NETWORK_REQS,
             MAX_INBOUND_CONNECTIONS,
             TCPBufferCfg::default(),
+            OutboundTrafficShapingConfig::default(),
         );
 
         builder.add_connectivity_manager(
@@ -188,6 +195,7 @@ impl NetworkBuilder {
             network_context,
             time_service,
             config.listen_address.clone(),
+            config.transport_protocol,
             authentication_mode,
             config.max_frame_size,
             config.max_message_size,
@@ -201,6 +209,7 @@ impl NetworkBuilder {
                 config.outbound_rx_buffer_size_bytes,
                 config.outbound_tx_buffer_size_bytes,
             ),
+            config.outbound_traffic_shaping.clone(),
         );
 
         network_builder.add_connection_monitoring(
@@ -239,6 +248,7 @@ impl NetworkBuilder {
 
             network_builder.add_discovery_change_listener(
                 discovery_method,
+                config.transport_protocol,
                 pubkey,
                 reconfig_listener,
             );
@@ -368,6 +378,7 @@ impl NetworkBuilder {
     fn add_discovery_change_listener(
         &mut self,
         discovery_method: &DiscoveryMethod,
+        transport_protocol: TransportProtocol,
         pubkey: PublicKey,
         reconfig_events: Option<ReconfigNotificationListener>,
     ) {
@@ -400,6 +411,17 @@ impl NetworkBuilder {
                 Duration::from_secs(rest_discovery.interval_secs),
                 self.time_service.clone(),
             ),
+            DiscoveryMethod::Dns(dns_discovery) => DiscoveryChangeListener::dns(
+                self.network_context,
+                conn_mgr_reqs_tx,
+                dns_discovery.clone(),
+                transport_protocol,
+                Arc::new(
+                    SystemDnsResolver::new(dns_discovery.validate_dnssec)
+                        .expect("Failed to create the DNS resolver"),
+                ),
+                self.time_service.clone(),
+            ),
             DiscoveryMethod::None => return,
         };
 
@@ -7,7 +7,7 @@
 use crate::builder::NetworkBuilder;
 use aptos_channels::aptos_channel;
 use aptos_config::{
-    config::{Peer, PeerRole, PeerSet, RoleType, NETWORK_CHANNEL_SIZE},
+    config::{Peer, PeerRole, PeerSet, RoleType, TransportProtocol, NETWORK_CHANNEL_SIZE},
     network_id::{NetworkContext, NetworkId, PeerNetworkId},
 };
 use aptos_crypto::{test_utils::TEST_SEED, x25519, Uniform};
@@ -65,6 +65,12 @@ pub struct DummyNetwork {
 
 /// The following sets up a 2 peer network and verifies connectivity.
 pub fn setup_network() -> DummyNetwork {
+    setup_network_with_transport(TransportProtocol::Tcp)
+}
+
+/// The following sets up a 2 peer network over the given transport protocol
+/// and verifies connectivity.
+pub fn setup_network_with_transport(transport_protocol: TransportProtocol) -> DummyNetwork {
     let runtime = Runtime::new().unwrap();
     let role = RoleType::Validator;
     let network_id = NetworkId::Validator;
@@ -82,8 +88,12 @@ pub fn setup_network() -> DummyNetwork {
     let listener_identity_private_key = x25519::PrivateKey::generate(&mut rng);
 
     // Setup listen addresses
-    let dialer_addr: NetworkAddress = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
-    let listener_addr: NetworkAddress = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+    let local_addr = match transport_protocol {
+        TransportProtocol::Tcp => "/ip4/127.0.0.1/tcp/0",
+        TransportProtocol::Quic => "/ip4/127.0.0.1/udp/0",
+    };
+    let dialer_addr: NetworkAddress = local_addr.parse().unwrap();
+    let listener_addr: NetworkAddress = local_addr.parse().unwrap();
 
     // Setup seed peers
     let mut seeds = PeerSet::new();
@@ -102,6 +112,7 @@ pub fn setup_network() -> DummyNetwork {
         network_context,
         TimeService::real(),
         listener_addr,
+        transport_protocol,
         authentication_mode,
         peers_and_metadata.clone(),
     );
@@ -135,6 +146,7 @@ pub fn setup_network() -> DummyNetwork {
         network_context,
         TimeService::real(),
         dialer_addr,
+        transport_protocol,
         authentication_mode,
         peers_and_metadata.clone(),
     );
@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Integration tests for validator_network.
-use crate::dummy::{setup_network, DummyMsg};
+use crate::dummy::{setup_network, setup_network_with_transport, DummyMsg, DummyNetwork};
+use aptos_config::config::TransportProtocol;
 use aptos_network::{application::interface::NetworkClientInterface, protocols::network::Event};
 use futures::{future::join, StreamExt};
 use std::time::Duration;
@@ -13,10 +14,36 @@ fn test_network_builder() {
     setup_network();
 }
 
+#[test]
+fn test_quic_network_builder() {
+    setup_network_with_transport(TransportProtocol::Quic);
+}
+
 #[test]
 fn test_direct_send() {
     ::aptos_logger::Logger::init_for_testing();
-    let tn = setup_network();
+    check_direct_send(setup_network());
+}
+
+#[test]
+fn test_quic_direct_send() {
+    ::aptos_logger::Logger::init_for_testing();
+    check_direct_send(setup_network_with_transport(TransportProtocol::Quic));
+}
+
+#[test]
+fn test_rpc() {
+    ::aptos_logger::Logger::init_for_testing();
+    check_rpc(setup_network());
+}
+
+#[test]
+fn test_quic_rpc() {
+    ::aptos_logger::Logger::init_for_testing();
+    check_rpc(setup_network_with_transport(TransportProtocol::Quic));
+}
+
+fn check_direct_send(tn: DummyNetwork) {
     let dialer_peer = tn.dialer_peer;
     let mut dialer_events = tn.dialer_events;
     let dialer_network_client = tn.dialer_network_client;
@@ -58,10 +85,7 @@ fn test_direct_send() {
     tn.runtime.block_on(join(f_dialer, f_listener));
 }
 
-#[test]
-fn test_rpc() {
-    ::aptos_logger::Logger::init_for_testing();
-    let tn = setup_network();
+fn check_rpc(tn: DummyNetwork) {
     let dialer_peer = tn.dialer_peer;
     let mut dialer_events = tn.dialer_events;
     let dialer_sender = tn.dialer_network_client;
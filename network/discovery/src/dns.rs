@@ -0,0 +1,730 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters::DISCOVERY_COUNTS, DiscoveryError};
+use aptos_config::{
+    config::{DnsDiscovery, Peer, PeerRole, PeerSet, TransportProtocol, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::prelude::*;
+use aptos_network::{counters::inc_by_with_context, logging::NetworkSchema};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{
+    network_address::{DnsName, NetworkAddress, Protocol},
+    PeerId,
+};
+use async_trait::async_trait;
+use futures::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use std::{
+    cmp::{max, min},
+    collections::HashSet,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use trust_dns_resolver::{system_conf::read_system_conf, TokioAsyncResolver};
+
+const PEER_ID_KEY: &str = "peer_id";
+const ROLE_KEY: &str = "role";
+const X25519_KEY: &str = "x25519";
+
+/// A single target of an SRV record
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SrvRecord {
+    pub target: String,
+    pub port: u16,
+}
+
+/// The records of a DNS lookup, along with the time they may be cached for
+#[derive(Clone, Debug)]
+pub struct DnsAnswer<T> {
+    pub records: Vec<T>,
+    pub ttl: Duration,
+}
+
+/// Resolves the DNS records used for peer discovery. TXT records are returned as the list of
+/// character strings they hold.
+#[async_trait]
+pub trait DnsResolver: Send + Sync {
+    async fn lookup_srv(&self, name: &str) -> Result<DnsAnswer<SrvRecord>, DiscoveryError>;
+
+    async fn lookup_txt(&self, name: &str) -> Result<DnsAnswer<Vec<String>>, DiscoveryError>;
+}
+
+/// A `DnsResolver` that uses the system's resolver configuration (e.g., `/etc/resolv.conf`).
+/// If required, the answers are DNSSEC validated, and unsigned answers are rejected.
+pub struct SystemDnsResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SystemDnsResolver {
+    pub fn new(validate_dnssec: bool) -> Result<Self, DiscoveryError> {
+        let (config, mut options) =
+            read_system_conf().map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        options.validate = validate_dnssec;
+        let resolver = TokioAsyncResolver::tokio(config, options)
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        Ok(SystemDnsResolver { resolver })
+    }
+}
+
+#[async_trait]
+impl DnsResolver for SystemDnsResolver {
+    async fn lookup_srv(&self, name: &str) -> Result<DnsAnswer<SrvRecord>, DiscoveryError> {
+        let lookup = self
+            .resolver
+            .srv_lookup(name)
+            .await
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        let records = lookup
+            .iter()
+            .map(|srv| SrvRecord {
+                target: srv.target().to_utf8(),
+                port: srv.port(),
+            })
+            .collect();
+        Ok(DnsAnswer {
+            records,
+            ttl: lookup
+                .as_lookup()
+                .valid_until()
+                .saturating_duration_since(Instant::now()),
+        })
+    }
+
+    async fn lookup_txt(&self, name: &str) -> Result<DnsAnswer<Vec<String>>, DiscoveryError> {
+        let lookup = self
+            .resolver
+            .txt_lookup(name)
+            .await
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        let records = lookup
+            .iter()
+            .map(|txt| {
+                txt.txt_data()
+                    .iter()
+                    .map(|data| String::from_utf8_lossy(data).into_owned())
+                    .collect()
+            })
+            .collect();
+        Ok(DnsAnswer {
+            records,
+            ttl: lookup
+                .as_lookup()
+                .valid_until()
+                .saturating_duration_since(Instant::now()),
+        })
+    }
+}
+
+/// A discovery stream that resolves the peers advertised in DNS. The records are resolved
+/// immediately, and then again whenever the shortest TTL of the previous answers expires.
+pub struct DnsStream {
+    inner: BoxStream<'static, Result<PeerSet, DiscoveryError>>,
+}
+
+struct DnsStreamState {
+    network_context: NetworkContext,
+    dns_discovery: DnsDiscovery,
+    transport_protocol: TransportProtocol,
+    resolver: Arc<dyn DnsResolver>,
+    time_service: TimeService,
+    next_refresh: Option<Duration>,
+    resolved_once: bool,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        dns_discovery: DnsDiscovery,
+        transport_protocol: TransportProtocol,
+        resolver: Arc<dyn DnsResolver>,
+        time_service: TimeService,
+    ) -> Self {
+        let state = DnsStreamState {
+            network_context,
+            dns_discovery,
+            transport_protocol,
+            resolver,
+            time_service,
+            next_refresh: None,
+            resolved_once: false,
+        };
+        let inner = stream::unfold(state, |mut state| async move {
+            if let Some(next_refresh) = state.next_refresh {
+                state.time_service.sleep(next_refresh).await;
+            }
+
+            let min_interval = Duration::from_secs(state.dns_discovery.min_interval_secs);
+            let max_interval = Duration::from_secs(state.dns_discovery.max_interval_secs);
+            let update = match resolve_peers(
+                state.network_context,
+                &state.dns_discovery,
+                state.transport_protocol,
+                state.resolver.as_ref(),
+            )
+            .await
+            {
+                Ok((peers, ttl)) => {
+                    state.resolved_once = true;
+                    state.next_refresh = Some(min(max(ttl, min_interval), max_interval));
+                    Ok(peers)
+                },
+                Err(error) => {
+                    state.next_refresh = Some(min_interval);
+                    if state.resolved_once || state.dns_discovery.bootstrap_peers.is_empty() {
+                        Err(error)
+                    } else {
+                        // Until DNS has been resolved once, fall back to the bootstrap peers
+                        Ok(state.dns_discovery.bootstrap_peers.clone())
+                    }
+                },
+            };
+            Some((update, state))
+        })
+        .boxed();
+        DnsStream { inner }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Resolves the SRV record and the TXT records of its targets into a `PeerSet` (along with the
+/// bootstrap peers), returning it with the shortest TTL of the answers. Targets that can't be
+/// resolved, or that publish invalid records, are skipped. If none of the targets can be
+/// resolved, an error is returned instead, so that a transient DNS failure doesn't drop every
+/// previously discovered peer.
+async fn resolve_peers(
+    network_context: NetworkContext,
+    dns_discovery: &DnsDiscovery,
+    transport_protocol: TransportProtocol,
+    resolver: &dyn DnsResolver,
+) -> Result<(PeerSet, Duration), DiscoveryError> {
+    let srv_answer = resolver.lookup_srv(&dns_discovery.srv_name).await?;
+
+    let mut ttl = srv_answer.ttl;
+    let mut peers = dns_discovery.bootstrap_peers.clone();
+    let mut last_lookup_error = None;
+    let mut resolved_targets = 0;
+    for srv_record in srv_answer.records {
+        let txt_answer = match resolver.lookup_txt(&srv_record.target).await {
+            Ok(txt_answer) => txt_answer,
+            Err(error) => {
+                inc_by_with_context(&DISCOVERY_COUNTS, &network_context, "dns_lookup_failure", 1);
+                warn!(
+                    NetworkSchema::new(&network_context),
+                    "{} Failed to resolve the TXT records of {}: {:?}",
+                    network_context,
+                    srv_record.target,
+                    error
+                );
+                last_lookup_error = Some(error);
+                continue;
+            },
+        };
+        resolved_targets += 1;
+        ttl = min(ttl, txt_answer.ttl);
+
+        for txt_record in txt_answer.records {
+            match parse_peer(
+                &srv_record,
+                &txt_record,
+                transport_protocol,
+                dns_discovery.max_peer_role(),
+            ) {
+                Ok(Some((peer_id, peer))) => merge_peer(&mut peers, peer_id, peer),
+                Ok(None) => (),
+                Err(error) => {
+                    inc_by_with_context(
+                        &DISCOVERY_COUNTS,
+                        &network_context,
+                        "dns_invalid_record",
+                        1,
+                    );
+                    warn!(
+                        NetworkSchema::new(&network_context),
+                        "{} Invalid TXT record {:?} for {}: {:?}",
+                        network_context,
+                        txt_record,
+                        srv_record.target,
+                        error
+                    );
+                },
+            }
+        }
+    }
+
+    if resolved_targets == 0 {
+        if let Some(error) = last_lookup_error {
+            return Err(error);
+        }
+    }
+    Ok((peers, ttl))
+}
+
+/// Parses a peer from the TXT record of an SRV target. Returns `None` if the TXT record
+/// doesn't describe a peer (i.e., it has no peer id).
+fn parse_peer(
+    srv_record: &SrvRecord,
+    txt_record: &[String],
+    transport_protocol: TransportProtocol,
+    max_peer_role: PeerRole,
+) -> Result<Option<(PeerId, Peer)>, DiscoveryError> {
+    let mut peer_id = None;
+    let mut keys = HashSet::new();
+    let mut role = max_peer_role;
+    for entry in txt_record.iter().flat_map(|data| data.split_whitespace()) {
+        let (key, value) = entry.split_once('=').ok_or_else(|| {
+            DiscoveryError::Parsing(format!("TXT entry {} is not a key=value pair", entry))
+        })?;
+        match key {
+            PEER_ID_KEY => {
+                peer_id = Some(
+                    PeerId::from_str(value)
+                        .map_err(|error| DiscoveryError::Parsing(error.to_string()))?,
+                )
+            },
+            X25519_KEY => {
+                keys.insert(
+                    x25519::PublicKey::from_encoded_string(value)
+                        .map_err(|error| DiscoveryError::Parsing(error.to_string()))?,
+                );
+            },
+            // A record can only lower the trust of its peer
+            ROLE_KEY => role = max(role, parse_peer_role(value)?),
+            _ => (),
+        }
+    }
+
+    let peer_id = match peer_id {
+        Some(peer_id) => peer_id,
+        None => return Ok(None),
+    };
+    if keys.is_empty() {
+        return Err(DiscoveryError::Parsing(format!(
+            "TXT record of peer {} has no {} key",
+            peer_id, X25519_KEY
+        )));
+    }
+
+    // Dial the target with each of the peer's keys
+    let dns_name = DnsName::from_str(srv_record.target.trim_end_matches('.'))
+        .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+    let transport = match transport_protocol {
+        TransportProtocol::Tcp => Protocol::Tcp(srv_record.port),
+        TransportProtocol::Quic => Protocol::Udp(srv_record.port),
+    };
+    let addresses = keys
+        .iter()
+        .map(|key| {
+            NetworkAddress::from_protocols(vec![Protocol::Dns(dns_name.clone()), transport.clone()])
+                .map(|address| address.append_prod_protos(*key, HANDSHAKE_VERSION))
+                .map_err(|error| DiscoveryError::Parsing(error.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some((peer_id, Peer::new(addresses, keys, role))))
+}
+
+fn parse_peer_role(value: &str) -> Result<PeerRole, DiscoveryError> {
+    [
+        PeerRole::Validator,
+        PeerRole::PreferredUpstream,
+        PeerRole::Upstream,
+        PeerRole::ValidatorFullNode,
+        PeerRole::Downstream,
+        PeerRole::Known,
+        PeerRole::Unknown,
+    ]
+    .into_iter()
+    .find(|role| role.as_str() == value)
+    .ok_or_else(|| DiscoveryError::Parsing(format!("Unknown peer role {}", value)))
+}
+
+/// Merges a peer into the peer set. A peer advertised by several targets keeps the addresses and
+/// keys of all of them, and the least trusted of their roles.
+fn merge_peer(peers: &mut PeerSet, peer_id: PeerId, peer: Peer) {
+    match peers.get_mut(&peer_id) {
+        Some(existing_peer) => {
+            existing_peer.addresses.extend(peer.addresses);
+            existing_peer.keys.extend(peer.keys);
+            existing_peer.role = max(existing_peer.role, peer.role);
+        },
+        None => {
+            peers.insert(peer_id, peer);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{PrivateKey, Uniform};
+    use futures::FutureExt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{collections::HashMap, sync::Mutex};
+
+    const SRV_NAME: &str = "_aptos._tcp.peers.example.com";
+
+    /// A `DnsResolver` that serves records from memory
+    #[derive(Default)]
+    struct StubDnsResolver {
+        srv_records: Mutex<HashMap<String, DnsAnswer<SrvRecord>>>,
+        txt_records: Mutex<HashMap<String, DnsAnswer<Vec<String>>>>,
+    }
+
+    impl StubDnsResolver {
+        fn set_srv(&self, name: &str, records: Vec<SrvRecord>, ttl: Duration) {
+            self.srv_records
+                .lock()
+                .unwrap()
+                .insert(name.into(), DnsAnswer { records, ttl });
+        }
+
+        fn set_txt(&self, name: &str, records: Vec<Vec<String>>, ttl: Duration) {
+            self.txt_records
+                .lock()
+                .unwrap()
+                .insert(name.into(), DnsAnswer { records, ttl });
+        }
+    }
+
+    #[async_trait]
+    impl DnsResolver for StubDnsResolver {
+        async fn lookup_srv(&self, name: &str) -> Result<DnsAnswer<SrvRecord>, DiscoveryError> {
+            self.srv_records
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| DiscoveryError::Dns(format!("No SRV record for {}", name)))
+        }
+
+        async fn lookup_txt(&self, name: &str) -> Result<DnsAnswer<Vec<String>>, DiscoveryError> {
+            self.txt_records
+                .lock()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| DiscoveryError::Dns(format!("No TXT record for {}", name)))
+        }
+    }
+
+    fn create_dns_discovery(bootstrap_peers: PeerSet) -> DnsDiscovery {
+        DnsDiscovery {
+            srv_name: SRV_NAME.into(),
+            peer_role: PeerRole::Upstream,
+            bootstrap_peers,
+            min_interval_secs: 10,
+            max_interval_secs: 600,
+            validate_dnssec: false,
+        }
+    }
+
+    fn create_peer_record(
+        rng: &mut StdRng,
+        target: &str,
+        role: Option<PeerRole>,
+    ) -> (PeerId, x25519::PublicKey, Vec<String>) {
+        let peer_id = PeerId::random();
+        let key = x25519::PrivateKey::generate(rng).public_key();
+        let mut txt_record = vec![
+            format!("{}={}", PEER_ID_KEY, peer_id),
+            format!("{}={}", X25519_KEY, key),
+        ];
+        if let Some(role) = role {
+            txt_record.push(format!("{}={}", ROLE_KEY, role.as_str()));
+        }
+        let expected_address = NetworkAddress::from_str(&format!(
+            "/dns/{}/tcp/6180/noise-ik/{}/handshake/{}",
+            target, key, HANDSHAKE_VERSION
+        ))
+        .unwrap();
+        assert_eq!(expected_address.find_noise_proto(), Some(key));
+        (peer_id, key, txt_record)
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let resolver = Arc::new(StubDnsResolver::default());
+        let time_service = TimeService::mock();
+
+        // Advertise two peers, one of which claims to be a validator and one which lowers its trust
+        let (peer_id_1, key_1, txt_record_1) =
+            create_peer_record(&mut rng, "node1.example.com", Some(PeerRole::Validator));
+        let (peer_id_2, key_2, txt_record_2) =
+            create_peer_record(&mut rng, "node2.example.com", Some(PeerRole::Known));
+        resolver.set_srv(
+            SRV_NAME,
+            vec![
+                SrvRecord {
+                    target: "node1.example.com.".into(),
+                    port: 6180,
+                },
+                SrvRecord {
+                    target: "node2.example.com.".into(),
+                    port: 6180,
+                },
+            ],
+            Duration::from_secs(300),
+        );
+        resolver.set_txt(
+            "node1.example.com.",
+            vec![txt_record_1, vec!["v=spf1 -all".into()]],
+            Duration::from_secs(60),
+        );
+        resolver.set_txt(
+            "node2.example.com.",
+            vec![txt_record_2],
+            Duration::from_secs(120),
+        );
+
+        // Verify that the peers are resolved immediately, and tagged with the right roles
+        let mut stream = DnsStream::new(
+            NetworkContext::mock(),
+            create_dns_discovery(PeerSet::new()),
+            TransportProtocol::Tcp,
+            resolver.clone(),
+            time_service.clone(),
+        );
+        let peers = stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 2);
+        let peer_1 = peers.get(&peer_id_1).unwrap();
+        assert_eq!(peer_1.role, PeerRole::Upstream);
+        assert_eq!(peer_1.keys, [key_1].into_iter().collect());
+        assert_eq!(peer_1.addresses, vec![NetworkAddress::from_str(&format!(
+            "/dns/node1.example.com/tcp/6180/noise-ik/{}/handshake/{}",
+            key_1, HANDSHAKE_VERSION
+        ))
+        .unwrap()]);
+        let peer_2 = peers.get(&peer_id_2).unwrap();
+        assert_eq!(peer_2.role, PeerRole::Known);
+        assert_eq!(peer_2.keys, [key_2].into_iter().collect());
+
+        // Remove a peer and verify that nothing is resolved before the shortest TTL expires
+        resolver.set_srv(
+            SRV_NAME,
+            vec![SrvRecord {
+                target: "node1.example.com.".into(),
+                port: 6180,
+            }],
+            Duration::from_secs(300),
+        );
+        assert!(stream.next().now_or_never().is_none());
+        let mock_time_service = time_service.into_mock();
+        mock_time_service
+            .advance_async(Duration::from_secs(59))
+            .await;
+        assert!(stream.next().now_or_never().is_none());
+
+        // Verify that the records are resolved again once the TTL expires
+        mock_time_service
+            .advance_async(Duration::from_secs(1))
+            .await;
+        let peers = stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 1);
+        assert!(peers.contains_key(&peer_id_1));
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_bootstrap_peers() {
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let resolver = Arc::new(StubDnsResolver::default());
+        let time_service = TimeService::mock();
+
+        // Create a bootstrap peer
+        let bootstrap_peer_id = PeerId::random();
+        let bootstrap_key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let bootstrap_address = NetworkAddress::from_str("/ip4/127.0.0.1/tcp/6180")
+            .unwrap()
+            .append_prod_protos(bootstrap_key, HANDSHAKE_VERSION);
+        let mut bootstrap_peers = PeerSet::new();
+        bootstrap_peers.insert(
+            bootstrap_peer_id,
+            Peer::new(vec![bootstrap_address], HashSet::new(), PeerRole::Upstream),
+        );
+
+        // Verify that the bootstrap peers are used while DNS can't be resolved
+        let mut stream = DnsStream::new(
+            NetworkContext::mock(),
+            create_dns_discovery(bootstrap_peers.clone()),
+            TransportProtocol::Tcp,
+            resolver.clone(),
+            time_service.clone(),
+        );
+        assert_eq!(stream.next().await.unwrap().unwrap(), bootstrap_peers);
+
+        // Advertise a peer and verify that it's merged with the bootstrap peers after the retry
+        let (peer_id, _, txt_record) = create_peer_record(&mut rng, "node1.example.com", None);
+        resolver.set_srv(
+            SRV_NAME,
+            vec![SrvRecord {
+                target: "node1.example.com.".into(),
+                port: 6180,
+            }],
+            Duration::from_secs(1),
+        );
+        resolver.set_txt(
+            "node1.example.com.",
+            vec![txt_record],
+            Duration::from_secs(1),
+        );
+        assert!(stream.next().now_or_never().is_none());
+        let mock_time_service = time_service.into_mock();
+        mock_time_service
+            .advance_async(Duration::from_secs(10))
+            .await;
+        let peers = stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 2);
+        assert!(peers.contains_key(&bootstrap_peer_id));
+        assert_eq!(peers.get(&peer_id).unwrap().role, PeerRole::Upstream);
+
+        // Verify that later DNS failures are reported, instead of falling back to the bootstrap
+        // peers. The short TTL is bounded by the min interval.
+        resolver.srv_records.lock().unwrap().clear();
+        assert!(stream.next().now_or_never().is_none());
+        mock_time_service
+            .advance_async(Duration::from_secs(10))
+            .await;
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Dns(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_txt_failures() {
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let resolver = Arc::new(StubDnsResolver::default());
+        let time_service = TimeService::mock();
+
+        // Advertise two peers and verify that both are resolved
+        let (peer_id_1, _, txt_record_1) = create_peer_record(&mut rng, "node1.example.com", None);
+        let (peer_id_2, _, txt_record_2) = create_peer_record(&mut rng, "node2.example.com", None);
+        resolver.set_srv(
+            SRV_NAME,
+            vec![
+                SrvRecord {
+                    target: "node1.example.com.".into(),
+                    port: 6180,
+                },
+                SrvRecord {
+                    target: "node2.example.com.".into(),
+                    port: 6180,
+                },
+            ],
+            Duration::from_secs(300),
+        );
+        resolver.set_txt(
+            "node1.example.com.",
+            vec![txt_record_1],
+            Duration::from_secs(10),
+        );
+        resolver.set_txt(
+            "node2.example.com.",
+            vec![txt_record_2],
+            Duration::from_secs(10),
+        );
+        let mut stream = DnsStream::new(
+            NetworkContext::mock(),
+            create_dns_discovery(PeerSet::new()),
+            TransportProtocol::Tcp,
+            resolver.clone(),
+            time_service.clone(),
+        );
+        assert_eq!(stream.next().await.unwrap().unwrap().len(), 2);
+
+        // Verify that the targets that still resolve are kept when one of them fails
+        resolver
+            .txt_records
+            .lock()
+            .unwrap()
+            .remove("node2.example.com.");
+        let mock_time_service = time_service.into_mock();
+        mock_time_service
+            .advance_async(Duration::from_secs(10))
+            .await;
+        let peers = stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 1);
+        assert!(peers.contains_key(&peer_id_1));
+        assert!(!peers.contains_key(&peer_id_2));
+
+        // Verify that an error is reported (instead of an empty peer set) once no target resolves
+        resolver.txt_records.lock().unwrap().clear();
+        mock_time_service
+            .advance_async(Duration::from_secs(10))
+            .await;
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Dns(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_transport_and_dnssec() {
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let resolver = StubDnsResolver::default();
+
+        // Advertise a peer that claims to be a validator
+        let (peer_id, key, txt_record) =
+            create_peer_record(&mut rng, "node1.example.com", Some(PeerRole::Validator));
+        resolver.set_srv(
+            SRV_NAME,
+            vec![SrvRecord {
+                target: "node1.example.com.".into(),
+                port: 6180,
+            }],
+            Duration::from_secs(300),
+        );
+        resolver.set_txt(
+            "node1.example.com.",
+            vec![txt_record],
+            Duration::from_secs(300),
+        );
+
+        // Verify that the peer is dialed over QUIC, and that it isn't trusted as a validator
+        // without DNSSEC validation
+        let mut dns_discovery = create_dns_discovery(PeerSet::new());
+        dns_discovery.peer_role = PeerRole::Validator;
+        let (peers, _) = resolve_peers(
+            NetworkContext::mock(),
+            &dns_discovery,
+            TransportProtocol::Quic,
+            &resolver,
+        )
+        .await
+        .unwrap();
+        let peer = peers.get(&peer_id).unwrap();
+        assert_eq!(peer.role, PeerRole::Upstream);
+        assert_eq!(peer.addresses, vec![NetworkAddress::from_str(&format!(
+            "/dns/node1.example.com/udp/6180/noise-ik/{}/handshake/{}",
+            key, HANDSHAKE_VERSION
+        ))
+        .unwrap()]);
+
+        // Verify that the peer is trusted as a validator with DNSSEC validation
+        dns_discovery.validate_dnssec = true;
+        let (peers, _) = resolve_peers(
+            NetworkContext::mock(),
+            &dns_discovery,
+            TransportProtocol::Quic,
+            &resolver,
+        )
+        .await
+        .unwrap();
+        assert_eq!(peers.get(&peer_id).unwrap().role, PeerRole::Validator);
+    }
+}
@@ -264,6 +264,55 @@ pub fn inbound_rpc_handler_latency(
     ])
 }
 
+pub static APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_network_outbound_queueing_delay_seconds",
+        "Time outbound messages spend in the (shaped) protocol queues of the peer writer",
+        &["role_type", "network_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queueing_delay(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+) -> Histogram {
+    APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        protocol_id.as_str(),
+    ])
+}
+
+pub static APTOS_NETWORK_OUTBOUND_THROTTLED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_outbound_throttled_messages",
+        "Number of times an outbound message was held back by a byte-rate limit",
+        &["role_type", "network_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_throttled_messages(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+) -> IntCounter {
+    APTOS_NETWORK_OUTBOUND_THROTTLED_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        protocol_id.as_str(),
+    ])
+}
+
+/// Counter of pending outbound messages in the (shaped) protocol queues of the peer writers
+pub static PENDING_SHAPED_MESSAGES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_network_pending_shaped_messages",
+        "Number of pending outbound messages in the protocol queues"
+    )
+    .unwrap()
+});
+
 pub static APTOS_NETWORK_DIRECT_SEND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_network_direct_send_messages",
@@ -415,6 +464,16 @@ pub static PENDING_MULTIPLEX_STREAM: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter of messages read from the protocol streams of QUIC connections, pending in queue to
+/// be handled by the peer
+pub static PENDING_QUIC_STREAM_MESSAGES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_network_pending_quic_stream_messages",
+        "Number of pending messages read from QUIC protocol streams"
+    )
+    .unwrap()
+});
+
 /// Counter of pending requests in Direct Send
 pub static PENDING_DIRECT_SEND_REQUESTS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
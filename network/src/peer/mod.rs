@@ -13,6 +13,15 @@
 //! [`Peer`] owns the actual underlying connection socket and is reponsible for
 //! the socket's shutdown, graceful or otherwise.
 //!
+//! On QUIC connections, [`Peer`] opens a stream for each of the application
+//! protocols of the connection, so the (large) messages of one protocol don't
+//! delay the messages of the others. The socket then only carries the messages
+//! of unknown protocols and the error messages.
+//!
+//! When outbound traffic shaping is enabled, the messages of each application
+//! protocol are queued separately in the writer task, which serves the queues
+//! according to their weights and byte-rate limits (see the `shaping` module).
+//!
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
@@ -21,6 +30,7 @@ use crate::{
         RECEIVED_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
+    peer::shaping::{NextMessage, OutboundQueues, TrafficShaper},
     peer_manager::{PeerManagerError, TransportNotification},
     protocols::{
         direct_send::Message,
@@ -35,8 +45,9 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::aptos_channel;
-use aptos_config::network_id::NetworkContext;
+use aptos_config::{config::OutboundTrafficShapingConfig, network_id::NetworkContext};
 use aptos_logger::prelude::*;
+use aptos_netcore::transport::quic::QuicConnection;
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::PeerId;
@@ -44,13 +55,14 @@ use bytes::Bytes;
 use futures::{
     self,
     channel::oneshot,
+    future::{self, FutureExt},
     io::{AsyncRead, AsyncWrite},
-    stream::StreamExt,
+    stream::{BoxStream, StreamExt},
     SinkExt,
 };
 use futures_util::stream::select;
 use serde::Serialize;
-use std::{fmt, panic, time::Duration};
+use std::{collections::HashMap, fmt, panic, time::Duration};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -61,6 +73,16 @@ mod test;
 
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
+mod shaping;
+
+/// Messages are never fragmented on the protocol streams of QUIC connections, so
+/// the frames of those streams must fit the largest message and its header.
+const PROTOCOL_STREAM_FRAME_OVERHEAD: usize = 64;
+
+/// The number of multiplexed messages buffered ahead of the socket when the
+/// outbound traffic is shaped. It is kept small, so the order of the messages
+/// is decided by the protocol queues rather than by this buffer.
+const SHAPED_MULTIPLEX_QUEUE_SIZE: usize = 4;
 
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
@@ -107,6 +129,27 @@ enum State {
     ShuttingDown(DisconnectReason),
 }
 
+/// The queues of outbound messages to write on the wire, along with the handles
+/// to close their writer tasks.
+struct Writers {
+    /// Queue of the messages written to the socket.
+    socket_writer: aptos_channels::Sender<NetworkMessage>,
+    /// Queues of the messages written to the stream of their protocol, if the
+    /// connection has a stream per protocol.
+    protocol_writers: HashMap<ProtocolId, aptos_channels::Sender<NetworkMessage>>,
+    /// Handles to close the writer tasks.
+    close_txs: Vec<oneshot::Sender<()>>,
+}
+
+impl Writers {
+    /// Returns the queue of the messages of the given protocol.
+    fn writer(&mut self, protocol_id: ProtocolId) -> &mut aptos_channels::Sender<NetworkMessage> {
+        self.protocol_writers
+            .get_mut(&protocol_id)
+            .unwrap_or(&mut self.socket_writer)
+    }
+}
+
 /// The `Peer` actor manages a single connection to another remote peer after
 /// the initial connection establishment and handshake.
 pub struct Peer<TSocket> {
@@ -120,6 +163,8 @@ pub struct Peer<TSocket> {
     connection_metadata: ConnectionMetadata,
     /// Underlying connection.
     connection: Option<TSocket>,
+    /// The QUIC connection carrying the socket, if any.
+    quic_connection: Option<QuicConnection>,
     /// Channel to notify PeerManager that we've disconnected.
     connection_notifs_tx: aptos_channels::Sender<TransportNotification<TSocket>>,
     /// Channel to receive requests from PeerManager to send messages and rpcs.
@@ -138,6 +183,8 @@ pub struct Peer<TSocket> {
     max_message_size: usize,
     /// Inbound stream buffer
     inbound_stream: InboundStreamBuffer,
+    /// The weights and rate limits of the outbound traffic, if it is shaped
+    traffic_shaper: Option<TrafficShaper>,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -158,19 +205,28 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_traffic_shaping: &OutboundTrafficShapingConfig,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
             socket,
+            quic_connection,
         } = connection;
         let remote_peer_id = connection_metadata.remote_peer_id;
         let max_fragments = max_message_size / max_frame_size;
+        let traffic_shaper = TrafficShaper::new(
+            network_context,
+            time_service.clone(),
+            remote_peer_id,
+            outbound_traffic_shaping,
+        );
         Self {
             network_context,
             executor,
             time_service: time_service.clone(),
             connection_metadata,
             connection: Some(socket),
+            quic_connection,
             connection_notifs_tx,
             peer_reqs_rx,
             peer_notifs_tx,
@@ -191,6 +247,7 @@ where
             max_frame_size,
             max_message_size,
             inbound_stream: InboundStreamBuffer::new(max_fragments),
+            traffic_shaper,
         }
     }
 
@@ -212,15 +269,25 @@ where
         let (read_socket, write_socket) =
             tokio::io::split(self.connection.take().unwrap().compat());
 
-        let mut reader =
-            MultiplexMessageStream::new(read_socket.compat(), self.max_frame_size).fuse();
+        let reader = MultiplexMessageStream::new(read_socket.compat(), self.max_frame_size);
         let writer = MultiplexMessageSink::new(write_socket.compat_write(), self.max_frame_size);
 
+        // If the outbound traffic is shaped (and the connection has no stream per
+        // protocol), queue the messages of each protocol separately in the writer.
+        let (protocol_writers, shaped_queues) = match &self.traffic_shaper {
+            Some(traffic_shaper) if self.quic_connection.is_none() => {
+                let (protocol_writers, shaped_queues) = traffic_shaper
+                    .protocol_queues(self.connection_metadata.application_protocols.iter());
+                (protocol_writers, Some(shaped_queues))
+            },
+            _ => (HashMap::new(), None),
+        };
+
         // Start writer "process" as a separate task. We receive two handles to
         // communicate with the task:
         //   1. `write_reqs_tx`: Queue of pending NetworkMessages to write.
         //   2. `close_tx`: Handle to close the task and underlying connection.
-        let (mut write_reqs_tx, writer_close_tx) = Self::start_writer_task(
+        let (write_reqs_tx, writer_close_tx) = Self::start_writer_task(
             &self.executor,
             self.time_service.clone(),
             self.connection_metadata.clone(),
@@ -228,7 +295,27 @@ where
             writer,
             self.max_frame_size,
             self.max_message_size,
+            shaped_queues,
         );
+        let mut writers = Writers {
+            socket_writer: write_reqs_tx,
+            protocol_writers,
+            close_txs: vec![writer_close_tx],
+        };
+
+        // On QUIC connections, also read and write the messages of each protocol
+        // on its own stream.
+        let reader: BoxStream<'static, Result<MultiplexMessage, ReadError>> =
+            match self.quic_connection.clone() {
+                Some(quic_connection) => {
+                    self.open_protocol_streams(&quic_connection, &mut writers)
+                        .await;
+                    let protocol_streams_reader = self.accept_protocol_streams(quic_connection);
+                    select(reader, protocol_streams_reader).boxed()
+                },
+                None => reader.boxed(),
+            };
+        let mut reader = reader.fuse();
 
         // Start main Peer event loop.
         let reason = loop {
@@ -240,7 +327,7 @@ where
                 // Handle a new outbound request from the PeerManager.
                 maybe_request = self.peer_reqs_rx.next() => {
                     match maybe_request {
-                        Some(request) => self.handle_outbound_request(request, &mut write_reqs_tx).await,
+                        Some(request) => self.handle_outbound_request(request, &mut writers).await,
                         // The PeerManager is requesting this connection to close
                         // by dropping the corresponding peer_reqs_tx handle.
                         None => self.shutdown(DisconnectReason::Requested),
@@ -251,7 +338,7 @@ where
                 maybe_message = reader.next() => {
                     match maybe_message {
                         Some(message) =>  {
-                            if let Err(err) = self.handle_inbound_message(message, &mut write_reqs_tx).await {
+                            if let Err(err) = self.handle_inbound_message(message, &mut writers.socket_writer).await {
                                 warn!(
                                     NetworkSchema::new(&self.network_context)
                                         .connection_metadata(&self.connection_metadata),
@@ -269,8 +356,8 @@ where
                 },
                 // Drive the queue of pending inbound rpcs. When one is fulfilled
                 // by an upstream protocol, send the response to the remote peer.
-                maybe_response = self.inbound_rpcs.next_completed_response() => {
-                    if let Err(err) = self.inbound_rpcs.send_outbound_response(&mut write_reqs_tx, maybe_response).await {
+                (protocol_id, maybe_response) = self.inbound_rpcs.next_completed_response() => {
+                    if let Err(err) = self.inbound_rpcs.send_outbound_response(writers.writer(protocol_id), maybe_response).await {
                         warn!(
                             NetworkSchema::new(&self.network_context).connection_metadata(&self.connection_metadata),
                             error = %err,
@@ -286,9 +373,86 @@ where
             }
         };
 
-        // Finish shutting down the connection. Close the writer task and notify
+        // Finish shutting down the connection. Close the writer tasks and notify
         // PeerManager that this connection has shutdown.
-        self.do_shutdown(writer_close_tx, reason).await;
+        self.do_shutdown(writers, reason).await;
+    }
+
+    // Open a stream for each of the application protocols of the QUIC connection, and start a
+    // writer task for each stream. The messages of the protocols without a stream (e.g., if the
+    // stream couldn't be opened) are written to the socket.
+    async fn open_protocol_streams(&self, quic_connection: &QuicConnection, writers: &mut Writers) {
+        let frame_size = self.max_message_size + PROTOCOL_STREAM_FRAME_OVERHEAD;
+        for protocol_id in self.connection_metadata.application_protocols.iter() {
+            let stream = match quic_connection.open_stream().await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!(
+                        NetworkSchema::new(&self.network_context)
+                            .connection_metadata(&self.connection_metadata),
+                        error = %err,
+                        "{} Failed to open a stream for protocol {} to peer: {}, error: {}",
+                        self.network_context,
+                        protocol_id,
+                        self.remote_peer_id().short_str(),
+                        err
+                    );
+                    continue;
+                },
+            };
+            // If the outbound traffic is shaped, the stream is still subject to the
+            // rate limits of the protocol and the peer.
+            let (shaped_writers, shaped_queues) = match &self.traffic_shaper {
+                Some(traffic_shaper) => {
+                    let (shaped_writers, shaped_queues) =
+                        traffic_shaper.protocol_queues([protocol_id]);
+                    (shaped_writers, Some(shaped_queues))
+                },
+                None => (HashMap::new(), None),
+            };
+            let (write_reqs_tx, close_tx) = Self::start_writer_task(
+                &self.executor,
+                self.time_service.clone(),
+                self.connection_metadata.clone(),
+                self.network_context,
+                MultiplexMessageSink::new(stream, frame_size),
+                frame_size,
+                self.max_message_size,
+                shaped_queues,
+            );
+            writers.protocol_writers.insert(protocol_id, write_reqs_tx);
+            writers.protocol_writers.extend(shaped_writers);
+            writers.close_txs.push(close_tx);
+        }
+    }
+
+    // Start a new task on the given executor which accepts the protocol streams opened by the
+    // remote peer, and forwards the messages read from them to the returned channel. The task
+    // ends when the QUIC connection is closed.
+    fn accept_protocol_streams(
+        &self,
+        quic_connection: QuicConnection,
+    ) -> aptos_channels::Receiver<Result<MultiplexMessage, ReadError>> {
+        let frame_size = self.max_message_size + PROTOCOL_STREAM_FRAME_OVERHEAD;
+        let (messages_tx, messages_rx) =
+            aptos_channels::new(1024, &counters::PENDING_QUIC_STREAM_MESSAGES);
+
+        let executor = self.executor.clone();
+        let accept_task = async move {
+            while let Ok(stream) = quic_connection.accept_stream().await {
+                let mut messages_tx = messages_tx.clone();
+                let mut reader = MultiplexMessageStream::new(stream, frame_size);
+                executor.spawn(async move {
+                    while let Some(message) = reader.next().await {
+                        if messages_tx.send(message).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        };
+        self.executor.spawn(accept_task);
+        messages_rx
     }
 
     // Start a new task on the given executor which is responsible for writing outbound messages on
@@ -298,6 +462,9 @@ where
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    // If `shaped_queues` are given, the task also writes the messages received on their channels,
+    // in the order decided by their weights and rate limits.
+    #[allow(clippy::too_many_arguments)]
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
@@ -306,13 +473,21 @@ where
         mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
+        shaped_queues: Option<OutboundQueues>,
     ) -> (aptos_channels::Sender<NetworkMessage>, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
         let (write_reqs_tx, mut write_reqs_rx): (aptos_channels::Sender<NetworkMessage>, _) =
             aptos_channels::new(1024, &counters::PENDING_WIRE_MESSAGES);
         let (close_tx, mut close_rx) = oneshot::channel();
+        let shaping_time_service = time_service.clone();
 
-        let (mut msg_tx, msg_rx) = aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_MESSAGE);
+        let multiplex_queue_size = if shaped_queues.is_some() {
+            SHAPED_MULTIPLEX_QUEUE_SIZE
+        } else {
+            1024
+        };
+        let (mut msg_tx, msg_rx) =
+            aptos_channels::new(multiplex_queue_size, &counters::PENDING_MULTIPLEX_MESSAGE);
         let (stream_msg_tx, stream_msg_rx) =
             aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_STREAM);
 
@@ -378,24 +553,56 @@ where
         let multiplex_task = async move {
             let mut outbound_stream =
                 OutboundStream::new(max_frame_size, max_message_size, stream_msg_tx);
+            let mut outbound_queues = shaped_queues;
             loop {
+                // Write the next shaped message, if the queue weights and rate limits allow one
+                let mut throttle_delay = None;
+                if let Some(outbound_queues) = outbound_queues.as_mut() {
+                    // Queue the messages received so far, without waiting for more
+                    outbound_queues.receive().now_or_never();
+                    match outbound_queues.pop() {
+                        NextMessage::Ready(message) => {
+                            // Stop writing the queued messages once the task is instructed to close
+                            if !matches!(close_rx.try_recv(), Ok(None)) {
+                                break;
+                            }
+                            Self::multiplex_message(
+                                &mut outbound_stream,
+                                &mut msg_tx,
+                                message,
+                                network_context,
+                                remote_peer_id,
+                            )
+                            .await;
+                            continue;
+                        },
+                        NextMessage::Throttled(delay) => throttle_delay = Some(delay),
+                        NextMessage::Empty => (),
+                    }
+                }
+                let mut throttle = match throttle_delay {
+                    Some(delay) => shaping_time_service.sleep(delay).boxed(),
+                    None => future::pending().boxed(),
+                }
+                .fuse();
+                let mut shaped_messages = match outbound_queues.as_mut() {
+                    Some(outbound_queues) => outbound_queues.receive().boxed(),
+                    None => future::pending().boxed(),
+                }
+                .fuse();
+
                 futures::select! {
                     message = write_reqs_rx.select_next_some() => {
-                        // either channel full would block the other one
-                        let result = if outbound_stream.should_stream(&message) {
-                            outbound_stream.stream_message(message).await
-                        } else {
-                            msg_tx.send(MultiplexMessage::Message(message)).await.map_err(|_| anyhow::anyhow!("Writer task ended"))
-                        };
-                        if let Err(err) = result {
-                            warn!(
-                                error = %err,
-                                "{} Error in sending message to peer: {}",
-                                network_context,
-                                remote_peer_id.short_str(),
-                            );
-                        }
+                        Self::multiplex_message(
+                            &mut outbound_stream,
+                            &mut msg_tx,
+                            message,
+                            network_context,
+                            remote_peer_id,
+                        ).await;
                     },
+                    _ = shaped_messages => (),
+                    _ = throttle => (),
                     _ = close_rx => {
                         break;
                     }
@@ -407,6 +614,34 @@ where
         (write_reqs_tx, close_tx)
     }
 
+    // Send the given message to the writer task, as a stream of fragments if it doesn't fit in a
+    // single frame.
+    async fn multiplex_message(
+        outbound_stream: &mut OutboundStream,
+        msg_tx: &mut aptos_channels::Sender<MultiplexMessage>,
+        message: NetworkMessage,
+        network_context: NetworkContext,
+        remote_peer_id: PeerId,
+    ) {
+        // either channel full would block the other one
+        let result = if outbound_stream.should_stream(&message) {
+            outbound_stream.stream_message(message).await
+        } else {
+            msg_tx
+                .send(MultiplexMessage::Message(message))
+                .await
+                .map_err(|_| anyhow::anyhow!("Writer task ended"))
+        };
+        if let Err(err) = result {
+            warn!(
+                error = %err,
+                "{} Error in sending message to peer: {}",
+                network_context,
+                remote_peer_id.short_str(),
+            );
+        }
+    }
+
     async fn handle_inbound_network_message(
         &mut self,
         message: NetworkMessage,
@@ -544,11 +779,7 @@ where
         }
     }
 
-    async fn handle_outbound_request(
-        &mut self,
-        request: PeerRequest,
-        write_reqs_tx: &mut aptos_channels::Sender<NetworkMessage>,
-    ) {
+    async fn handle_outbound_request(&mut self, request: PeerRequest, writers: &mut Writers) {
         trace!(
             "Peer {} PeerRequest::{:?}",
             self.remote_peer_id().short_str(),
@@ -571,7 +802,7 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.send(message).await {
+                match writers.writer(protocol_id).send(message).await {
                     Ok(_) => {
                         counters::direct_send_messages(&self.network_context, SENT_LABEL).inc();
                         counters::direct_send_bytes(&self.network_context, SENT_LABEL)
@@ -599,7 +830,7 @@ where
                 );
                 if let Err(e) = self
                     .outbound_rpcs
-                    .handle_outbound_request(request, write_reqs_tx)
+                    .handle_outbound_request(request, writers.writer(protocol_id))
                     .await
                 {
                     warn!(
@@ -622,7 +853,7 @@ where
         self.state = State::ShuttingDown(reason);
     }
 
-    async fn do_shutdown(mut self, writer_close_tx: oneshot::Sender<()>, reason: DisconnectReason) {
+    async fn do_shutdown(mut self, writers: Writers, reason: DisconnectReason) {
         let remote_peer_id = self.remote_peer_id();
 
         // Send a PeerDisconnected event to PeerManager.
@@ -645,18 +876,26 @@ where
             );
         }
 
-        // Send a close instruction to the writer task. On receipt of this
-        // instruction, the writer task drops all pending outbound messages and
-        // closes the connection.
-        if let Err(e) = writer_close_tx.send(()) {
-            info!(
-                NetworkSchema::new(&self.network_context)
-                    .connection_metadata(&self.connection_metadata),
-                error = ?e,
-                "{} Failed to send close instruction to writer task. It must already be terminating/terminated. Error: {:?}",
-                self.network_context,
-                e
-            );
+        // Send a close instruction to the writer tasks. On receipt of this
+        // instruction, a writer task drops all pending outbound messages and
+        // closes its socket or stream.
+        for writer_close_tx in writers.close_txs {
+            if let Err(e) = writer_close_tx.send(()) {
+                info!(
+                    NetworkSchema::new(&self.network_context)
+                        .connection_metadata(&self.connection_metadata),
+                    error = ?e,
+                    "{} Failed to send close instruction to writer task. It must already be terminating/terminated. Error: {:?}",
+                    self.network_context,
+                    e
+                );
+            }
+        }
+
+        // Close the QUIC connection (if any), which also ends the tasks reading
+        // the protocol streams.
+        if let Some(quic_connection) = &self.quic_connection {
+            quic_connection.close();
         }
 
         trace!(
@@ -0,0 +1,577 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shaping of the outbound traffic of a [`Peer`](super::Peer).
+//!
+//! When shaping is enabled, the messages of each application protocol are sent to the writer
+//! task through their own channel and queued separately. The writer task serves the queues with
+//! deficit round robin: in each round, a queue may send up to `weight * QUANTUM_BYTES_PER_WEIGHT`
+//! bytes, so the protocols share the connection in proportion to their weights (e.g., consensus
+//! messages don't wait behind a backlog of state sync responses). A message is only sent once
+//! the byte-rate limits of its protocol and of the peer allow it.
+//!
+//! The queue of each protocol is bounded: once it is full (e.g., while the protocol is throttled),
+//! the writer task stops reading the channel of the protocol, so the backpressure reaches the
+//! application once the channel is full too.
+
+use crate::{
+    counters, logging::NetworkSchema, protocols::wire::messaging::v1::NetworkMessage, ProtocolId,
+};
+use aptos_config::{
+    config::{ByteRateLimitConfig, OutboundTrafficShapingConfig},
+    network_id::NetworkContext,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_rate_limiter::rate_limit::{Bucket, SharedBucket};
+use aptos_short_hex_str::AsShortHexStr;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use futures::{future, stream::StreamExt};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// The number of bytes a queue may send per unit of weight in each round
+const QUANTUM_BYTES_PER_WEIGHT: usize = 16 * 1024;
+
+/// The capacity of the channel of each protocol, and the number of messages queued for a
+/// protocol before its channel stops being read
+const PROTOCOL_QUEUE_CAPACITY: usize = 1024;
+
+/// The label of the outbound rate limiting metrics
+const OUTBOUND_LABEL: &str = "outbound";
+
+/// The weights and byte-rate limits of the outbound traffic to a peer. The rate limits are shared
+/// by all the writer tasks of the peer.
+#[derive(Clone)]
+pub struct TrafficShaper {
+    network_context: NetworkContext,
+    time_service: TimeService,
+    default_weight: u32,
+    weights: HashMap<ProtocolId, u32>,
+    peer_bucket: Option<SharedBucket>,
+    protocol_buckets: HashMap<ProtocolId, SharedBucket>,
+}
+
+impl TrafficShaper {
+    /// Creates the traffic shaper of a peer, if shaping is enabled in the given config
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        remote_peer_id: PeerId,
+        config: &OutboundTrafficShapingConfig,
+    ) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let weights = config
+            .protocol_weights
+            .iter()
+            .filter_map(|(protocol, weight)| {
+                parse_protocol_id(&network_context, protocol)
+                    .map(|protocol_id| (protocol_id, *weight))
+            })
+            .collect();
+        let new_bucket = |key: String, rate_limit: &ByteRateLimitConfig| {
+            Arc::new(Mutex::new(
+                Bucket::new(
+                    OUTBOUND_LABEL.into(),
+                    network_context.to_string(),
+                    key,
+                    rate_limit.byte_bucket_size,
+                    rate_limit.byte_bucket_size,
+                    rate_limit.byte_bucket_rate,
+                    Some(counters::NETWORK_RATE_LIMIT_METRICS.clone()),
+                )
+                .with_time_service(time_service.clone()),
+            ))
+        };
+        let peer_bucket = config
+            .peer_rate_limit
+            .as_ref()
+            .map(|rate_limit| new_bucket(remote_peer_id.short_str().to_string(), rate_limit));
+        let protocol_buckets = config
+            .protocol_rate_limits
+            .iter()
+            .filter_map(|(protocol, rate_limit)| {
+                parse_protocol_id(&network_context, protocol).map(|protocol_id| {
+                    let key = format!("{}-{}", remote_peer_id.short_str(), protocol);
+                    (protocol_id, new_bucket(key, rate_limit))
+                })
+            })
+            .collect();
+
+        Some(Self {
+            network_context,
+            time_service,
+            default_weight: config.default_protocol_weight,
+            weights,
+            peer_bucket,
+            protocol_buckets,
+        })
+    }
+
+    /// Creates a channel for each of the given protocols, and returns the senders along with the
+    /// (shaped) queues the writer task reads the messages from.
+    pub fn protocol_queues(
+        &self,
+        protocol_ids: impl IntoIterator<Item = ProtocolId>,
+    ) -> (
+        HashMap<ProtocolId, aptos_channels::Sender<NetworkMessage>>,
+        OutboundQueues,
+    ) {
+        let mut senders = HashMap::new();
+        let mut queues = OutboundQueues::new(self.clone());
+        for protocol_id in protocol_ids {
+            let (sender, receiver) =
+                aptos_channels::new(PROTOCOL_QUEUE_CAPACITY, &counters::PENDING_WIRE_MESSAGES);
+            senders.insert(protocol_id, sender);
+            queues.queues.insert(protocol_id, ProtocolQueue {
+                receiver: Some(receiver),
+                ..Default::default()
+            });
+        }
+        (senders, queues)
+    }
+
+    /// Returns the number of bytes a queue of the given protocol may send in each round
+    fn quantum(&self, protocol_id: ProtocolId) -> usize {
+        let weight = self
+            .weights
+            .get(&protocol_id)
+            .copied()
+            .unwrap_or(self.default_weight);
+        (weight as usize).saturating_mul(QUANTUM_BYTES_PER_WEIGHT)
+    }
+
+    /// Acquires the tokens to send a message of the given protocol and length. If the rate limits
+    /// don't allow the message yet, returns the time at which they will.
+    fn acquire(&self, protocol_id: ProtocolId, message_len: usize) -> Result<(), Instant> {
+        let protocol_bucket = self.protocol_buckets.get(&protocol_id);
+        if let Some(bucket) = protocol_bucket {
+            if let Err(Some(ready_time)) = bucket.lock().acquire_all_tokens(message_len) {
+                counters::outbound_throttled_messages(&self.network_context, protocol_id).inc();
+                return Err(ready_time);
+            }
+        }
+        if let Some(bucket) = &self.peer_bucket {
+            if let Err(Some(ready_time)) = bucket.lock().acquire_all_tokens(message_len) {
+                // Give the protocol tokens back, the message isn't sent yet
+                if let Some(bucket) = protocol_bucket {
+                    bucket.lock().return_tokens(message_len);
+                }
+                counters::outbound_throttled_messages(&self.network_context, protocol_id).inc();
+                return Err(ready_time);
+            }
+        }
+
+        // Note: messages larger than a bucket can never acquire all of its tokens. The
+        // config sanitizer prevents this, but such messages are sent unthrottled regardless.
+        Ok(())
+    }
+}
+
+/// Returns the protocol with the given name, or logs a warning if there is none
+fn parse_protocol_id(network_context: &NetworkContext, protocol: &str) -> Option<ProtocolId> {
+    let protocol_id = ProtocolId::all()
+        .iter()
+        .find(|protocol_id| protocol_id.as_str() == protocol)
+        .copied();
+    if protocol_id.is_none() {
+        warn!(
+            NetworkSchema::new(network_context),
+            "{} Ignoring the outbound traffic shaping of unknown protocol: {}",
+            network_context,
+            protocol
+        );
+    }
+    protocol_id
+}
+
+/// The result of [`OutboundQueues::pop`]
+#[derive(Debug)]
+pub enum NextMessage {
+    /// The message to send next
+    Ready(NetworkMessage),
+    /// The queued messages are held back by the rate limits for the given duration
+    Throttled(Duration),
+    /// There are no queued messages
+    Empty,
+}
+
+/// A queue of the messages of a protocol, along with the times they were queued at
+#[derive(Default)]
+struct ProtocolQueue {
+    /// The channel the messages of the protocol are received from, until it is closed
+    receiver: Option<aptos_channels::Receiver<NetworkMessage>>,
+    messages: VecDeque<(Instant, NetworkMessage)>,
+    /// The number of bytes the queue may still send in the current round
+    deficit: usize,
+}
+
+/// The per-protocol queues of a writer task, served with deficit round robin
+pub struct OutboundQueues {
+    shaper: TrafficShaper,
+    queues: HashMap<ProtocolId, ProtocolQueue>,
+    /// The protocols with queued messages, in the order they are served
+    active: VecDeque<ProtocolId>,
+}
+
+impl OutboundQueues {
+    fn new(shaper: TrafficShaper) -> Self {
+        Self {
+            shaper,
+            queues: HashMap::new(),
+            active: VecDeque::new(),
+        }
+    }
+
+    /// Waits for messages on the channels of the protocols whose queues aren't full, and queues
+    /// all the messages already received.
+    pub async fn receive(&mut self) {
+        future::poll_fn(|cx| self.poll_receive(cx)).await
+    }
+
+    fn poll_receive(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut received = false;
+        let protocol_ids: Vec<_> = self.queues.keys().copied().collect();
+        for protocol_id in protocol_ids {
+            loop {
+                let queue = self
+                    .queues
+                    .get_mut(&protocol_id)
+                    .expect("Protocols must have a queue!");
+                // Leave the messages of a full queue in its channel, so the channel fills up
+                // and pushes back on the application.
+                if queue.messages.len() >= PROTOCOL_QUEUE_CAPACITY {
+                    break;
+                }
+                let receiver = match queue.receiver.as_mut() {
+                    Some(receiver) => receiver,
+                    None => break,
+                };
+                match receiver.poll_next_unpin(cx) {
+                    Poll::Ready(Some(message)) => {
+                        self.push(protocol_id, message);
+                        received = true;
+                    },
+                    Poll::Ready(None) => {
+                        queue.receiver = None;
+                        break;
+                    },
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        if received {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Queues the given message of the given protocol
+    fn push(&mut self, protocol_id: ProtocolId, message: NetworkMessage) {
+        let queue = self.queues.entry(protocol_id).or_default();
+        if queue.messages.is_empty() {
+            self.active.push_back(protocol_id);
+        }
+        queue
+            .messages
+            .push_back((self.shaper.time_service.now(), message));
+        counters::PENDING_SHAPED_MESSAGES.inc();
+    }
+
+    /// Removes the next message to send from the queues
+    pub fn pop(&mut self) -> NextMessage {
+        let mut throttled_protocols = HashSet::new();
+        let mut ready_time: Option<Instant> = None;
+        while let Some(protocol_id) = self.active.front().copied() {
+            let queue = self
+                .queues
+                .get_mut(&protocol_id)
+                .expect("Active protocols must have a queue!");
+            let message_len = queue
+                .messages
+                .front()
+                .map(|(_, message)| message.data_len())
+                .expect("Active protocols must have queued messages!");
+
+            // Give the queue its share of the round, and move on to the next queue
+            // if the share doesn't cover the message yet.
+            if queue.deficit < message_len {
+                queue.deficit = queue
+                    .deficit
+                    .saturating_add(self.shaper.quantum(protocol_id));
+                if queue.deficit < message_len {
+                    self.active.rotate_left(1);
+                    continue;
+                }
+            }
+
+            // Send the message if the rate limits allow it
+            match self.shaper.acquire(protocol_id, message_len) {
+                Ok(()) => {
+                    let (queued_time, message) = queue
+                        .messages
+                        .pop_front()
+                        .expect("Active protocols must have queued messages!");
+                    queue.deficit -= message_len;
+                    if queue.messages.is_empty() {
+                        queue.deficit = 0;
+                        self.active.pop_front();
+                    }
+                    counters::PENDING_SHAPED_MESSAGES.dec();
+                    counters::outbound_queueing_delay(&self.shaper.network_context, protocol_id)
+                        .observe(
+                            self.shaper
+                                .time_service
+                                .now()
+                                .saturating_duration_since(queued_time)
+                                .as_secs_f64(),
+                        );
+                    return NextMessage::Ready(message);
+                },
+                Err(protocol_ready_time) => {
+                    ready_time = Some(match ready_time {
+                        Some(ready_time) => ready_time.min(protocol_ready_time),
+                        None => protocol_ready_time,
+                    });
+                    throttled_protocols.insert(protocol_id);
+                    if throttled_protocols.len() == self.active.len() {
+                        break;
+                    }
+                    self.active.rotate_left(1);
+                },
+            }
+        }
+
+        match ready_time {
+            Some(ready_time) => NextMessage::Throttled(
+                ready_time.saturating_duration_since(self.shaper.time_service.now()),
+            ),
+            None => NextMessage::Empty,
+        }
+    }
+}
+
+impl Drop for OutboundQueues {
+    fn drop(&mut self) {
+        let num_messages: usize = self.queues.values().map(|queue| queue.messages.len()).sum();
+        counters::PENDING_SHAPED_MESSAGES.sub(num_messages as i64);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::DirectSendMsg;
+    use aptos_config::{config::RoleType, network_id::NetworkId};
+    use futures::FutureExt;
+    use std::collections::BTreeMap;
+
+    fn direct_send(protocol_id: ProtocolId, len: usize) -> NetworkMessage {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: vec![0; len],
+        })
+    }
+
+    fn message_protocol(next_message: NextMessage) -> ProtocolId {
+        match next_message {
+            NextMessage::Ready(NetworkMessage::DirectSendMsg(message)) => message.protocol_id,
+            next_message => panic!("Unexpected next message: {:?}", next_message),
+        }
+    }
+
+    fn queued_messages(queues: &OutboundQueues, protocol_id: ProtocolId) -> usize {
+        queues
+            .queues
+            .get(&protocol_id)
+            .map_or(0, |queue| queue.messages.len())
+    }
+
+    fn state_sync_rate_limited_config() -> OutboundTrafficShapingConfig {
+        let mut protocol_rate_limits = BTreeMap::new();
+        protocol_rate_limits.insert(
+            ProtocolId::StorageServiceRpc.as_str().to_string(),
+            ByteRateLimitConfig {
+                byte_bucket_rate: 1024,
+                byte_bucket_size: 1024,
+            },
+        );
+        OutboundTrafficShapingConfig {
+            enabled: true,
+            protocol_rate_limits,
+            ..Default::default()
+        }
+    }
+
+    fn traffic_shaper(
+        config: OutboundTrafficShapingConfig,
+        time_service: TimeService,
+    ) -> TrafficShaper {
+        let network_context =
+            NetworkContext::new(RoleType::Validator, NetworkId::Validator, PeerId::random());
+        TrafficShaper::new(network_context, time_service, PeerId::random(), &config).unwrap()
+    }
+
+    fn outbound_queues(config: OutboundTrafficShapingConfig) -> OutboundQueues {
+        OutboundQueues::new(traffic_shaper(config, TimeService::mock()))
+    }
+
+    #[test]
+    fn test_weighted_queues() {
+        let mut queues = outbound_queues(OutboundTrafficShapingConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        assert!(matches!(queues.pop(), NextMessage::Empty));
+
+        // Queue a backlog of large state sync responses before a few consensus messages
+        let state_sync_len = QUANTUM_BYTES_PER_WEIGHT * 4;
+        for _ in 0..4 {
+            queues.push(
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, state_sync_len),
+            );
+        }
+        for _ in 0..3 {
+            queues.push(
+                ProtocolId::ConsensusDirectSendCompressed,
+                direct_send(ProtocolId::ConsensusDirectSendCompressed, 1024),
+            );
+        }
+
+        // The consensus messages are sent first, as the state sync queue needs several
+        // rounds to cover its first message.
+        for _ in 0..3 {
+            assert_eq!(
+                message_protocol(queues.pop()),
+                ProtocolId::ConsensusDirectSendCompressed
+            );
+        }
+        for _ in 0..4 {
+            assert_eq!(
+                message_protocol(queues.pop()),
+                ProtocolId::StorageServiceRpc
+            );
+        }
+        assert!(matches!(queues.pop(), NextMessage::Empty));
+    }
+
+    #[test]
+    fn test_rate_limited_queues() {
+        let mut queues = outbound_queues(state_sync_rate_limited_config());
+
+        // The first state sync message drains the bucket, so the second is throttled
+        for _ in 0..2 {
+            queues.push(
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, 1024),
+            );
+        }
+        assert_eq!(
+            message_protocol(queues.pop()),
+            ProtocolId::StorageServiceRpc
+        );
+        assert!(matches!(queues.pop(), NextMessage::Throttled(_)));
+
+        // The messages of other protocols aren't held back by the throttled queue
+        queues.push(
+            ProtocolId::MempoolDirectSend,
+            direct_send(ProtocolId::MempoolDirectSend, 1024),
+        );
+        assert_eq!(
+            message_protocol(queues.pop()),
+            ProtocolId::MempoolDirectSend
+        );
+        assert!(matches!(queues.pop(), NextMessage::Throttled(_)));
+    }
+
+    #[test]
+    fn test_throttled_queues_follow_time_service() {
+        let time_service = TimeService::mock();
+        let mut queues = OutboundQueues::new(traffic_shaper(
+            state_sync_rate_limited_config(),
+            time_service.clone(),
+        ));
+
+        // The second state sync message waits for the bucket to be refilled
+        for _ in 0..2 {
+            queues.push(
+                ProtocolId::StorageServiceRpc,
+                direct_send(ProtocolId::StorageServiceRpc, 1024),
+            );
+        }
+        assert_eq!(
+            message_protocol(queues.pop()),
+            ProtocolId::StorageServiceRpc
+        );
+        match queues.pop() {
+            NextMessage::Throttled(delay) => assert_eq!(delay, Duration::from_secs(1)),
+            next_message => panic!("Unexpected next message: {:?}", next_message),
+        }
+
+        // Once the mock time has passed, the message is sent
+        time_service.into_mock().advance(Duration::from_secs(1));
+        assert_eq!(
+            message_protocol(queues.pop()),
+            ProtocolId::StorageServiceRpc
+        );
+        assert!(matches!(queues.pop(), NextMessage::Empty));
+    }
+
+    #[test]
+    fn test_full_queues_push_back() {
+        let shaper = traffic_shaper(state_sync_rate_limited_config(), TimeService::mock());
+        let (mut senders, mut queues) = shaper.protocol_queues([ProtocolId::StorageServiceRpc]);
+        let sender = senders.get_mut(&ProtocolId::StorageServiceRpc).unwrap();
+        assert!(queues.receive().now_or_never().is_none());
+
+        // Fill the queue of the throttled protocol
+        for _ in 0..PROTOCOL_QUEUE_CAPACITY {
+            sender
+                .try_send(direct_send(ProtocolId::StorageServiceRpc, 1024))
+                .unwrap();
+        }
+        assert!(queues.receive().now_or_never().is_some());
+        assert_eq!(
+            queued_messages(&queues, ProtocolId::StorageServiceRpc),
+            PROTOCOL_QUEUE_CAPACITY
+        );
+
+        // The channel of the full queue isn't read, so it fills up and rejects new messages
+        while sender
+            .try_send(direct_send(ProtocolId::StorageServiceRpc, 1024))
+            .is_ok()
+        {}
+        assert!(queues.receive().now_or_never().is_none());
+        assert_eq!(
+            queued_messages(&queues, ProtocolId::StorageServiceRpc),
+            PROTOCOL_QUEUE_CAPACITY
+        );
+
+        // Sending a queued message makes room for a message of the channel, and in the channel
+        assert_eq!(
+            message_protocol(queues.pop()),
+            ProtocolId::StorageServiceRpc
+        );
+        assert!(matches!(queues.pop(), NextMessage::Throttled(_)));
+        assert!(queues.receive().now_or_never().is_some());
+        assert_eq!(
+            queued_messages(&queues, ProtocolId::StorageServiceRpc),
+            PROTOCOL_QUEUE_CAPACITY
+        );
+        sender
+            .try_send(direct_send(ProtocolId::StorageServiceRpc, 1024))
+            .unwrap();
+    }
+}
@@ -24,7 +24,10 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::PeerRole, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundTrafficShapingConfig, PeerRole},
+    network_id::NetworkContext,
+};
 use aptos_memsocket::MemorySocket;
 use aptos_netcore::transport::ConnectionOrigin;
 use aptos_time_service::{MockTimeService, TimeService};
@@ -55,6 +58,28 @@ fn build_test_peer(
     MemorySocket,
     aptos_channels::Receiver<TransportNotification<MemorySocket>>,
     aptos_channel::Receiver<ProtocolId, PeerNotification>,
+) {
+    build_test_peer_with_shaping(
+        executor,
+        time_service,
+        origin,
+        ProtocolIdSet::empty(),
+        &OutboundTrafficShapingConfig::default(),
+    )
+}
+
+fn build_test_peer_with_shaping(
+    executor: Handle,
+    time_service: TimeService,
+    origin: ConnectionOrigin,
+    application_protocols: ProtocolIdSet,
+    outbound_traffic_shaping: &OutboundTrafficShapingConfig,
+) -> (
+    Peer<MemorySocket>,
+    PeerHandle,
+    MemorySocket,
+    aptos_channels::Receiver<TransportNotification<MemorySocket>>,
+    aptos_channel::Receiver<ProtocolId, PeerNotification>,
 ) {
     let (a, b) = MemorySocket::new_pair();
     let peer_id = PeerId::random();
@@ -65,10 +90,11 @@ fn build_test_peer(
             NetworkAddress::from_str("/ip4/127.0.0.1/tcp/8081").unwrap(),
             origin,
             MessagingProtocolVersion::V1,
-            ProtocolIdSet::empty(),
+            application_protocols,
             PeerRole::Unknown,
         ),
         socket: a,
+        quic_connection: None,
     };
 
     let (connection_notifs_tx, connection_notifs_rx) = aptos_channels::new_test(1);
@@ -90,6 +116,7 @@ fn build_test_peer(
         MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE,
         MAX_MESSAGE_SIZE,
+        outbound_traffic_shaping,
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -237,6 +264,62 @@ fn peer_send_message() {
     rt.block_on(future::join3(peer.start(), server, client));
 }
 
+// Sending outbound DirectSends of several protocols through the shaped protocol
+// queues should write all of them to the wire, in order for each protocol.
+#[test]
+fn peer_send_message_shaped() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let protocols = [PROTOCOL, ProtocolId::ConsensusDirectSendBcs];
+    let (peer, mut peer_handle, mut connection, _connection_notifs_rx, _peer_notifs_rx) =
+        build_test_peer_with_shaping(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+            ProtocolIdSet::from_iter(protocols),
+            &OutboundTrafficShapingConfig {
+                enabled: true,
+                ..Default::default()
+            },
+        );
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let client = async {
+        // Client should receive the direct send messages of each protocol in order.
+        let mut next_indices = [0u8; 2];
+        for _ in 0..60 {
+            let msg = client_stream.next().await.unwrap().unwrap();
+            match msg {
+                MultiplexMessage::Message(NetworkMessage::DirectSendMsg(msg)) => {
+                    let protocol_index = protocols
+                        .iter()
+                        .position(|protocol_id| *protocol_id == msg.protocol_id)
+                        .unwrap();
+                    assert_eq!(msg.raw_msg, vec![next_indices[protocol_index]]);
+                    next_indices[protocol_index] += 1;
+                },
+                msg => panic!("Unexpected message: {:?}", msg),
+            }
+        }
+        assert_eq!(next_indices, [30, 30]);
+        // Client then closes the connection.
+        client_sink.close().await.unwrap();
+    };
+
+    let server = async {
+        // Server sends some direct send messages of both protocols.
+        for index in 0..30u8 {
+            for protocol_id in protocols {
+                peer_handle.send_direct_send(Message {
+                    protocol_id,
+                    mdata: Bytes::from(vec![index]),
+                });
+            }
+        }
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
 // Reading an inbound DirectSendMsg off the wire should notify the PeerManager of
 // an inbound DirectSend.
 #[test]
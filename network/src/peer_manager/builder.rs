@@ -18,12 +18,16 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::HANDSHAKE_VERSION, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundTrafficShapingConfig, TransportProtocol, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_logger::prelude::*;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 use aptos_netcore::transport::memory::MemoryTransport;
 use aptos_netcore::transport::{
+    quic::{QuicSocket, QuicTransport},
     tcp::{TCPBufferCfg, TcpSocket, TcpTransport},
     Transport,
 };
@@ -48,6 +52,7 @@ pub enum AuthenticationMode {
 
 struct TransportContext {
     chain_id: ChainId,
+    transport_protocol: TransportProtocol,
     supported_protocols: ProtocolIdSet,
     authentication_mode: AuthenticationMode,
     peers_and_metadata: Arc<PeersAndMetadata>,
@@ -79,6 +84,7 @@ struct PeerManagerContext {
     max_message_size: usize,
     inbound_connection_limit: usize,
     tcp_buffer_cfg: TCPBufferCfg,
+    outbound_traffic_shaping: OutboundTrafficShapingConfig,
 }
 
 impl PeerManagerContext {
@@ -102,6 +108,7 @@ impl PeerManagerContext {
         max_message_size: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_traffic_shaping: OutboundTrafficShapingConfig,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -119,6 +126,7 @@ impl PeerManagerContext {
             max_message_size,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            outbound_traffic_shaping,
         }
     }
 
@@ -142,11 +150,13 @@ impl PeerManagerContext {
 type MemoryPeerManager =
     PeerManager<AptosNetTransport<MemoryTransport>, NoiseStream<aptos_memsocket::MemorySocket>>;
 type TcpPeerManager = PeerManager<AptosNetTransport<TcpTransport>, NoiseStream<TcpSocket>>;
+type QuicPeerManager = PeerManager<AptosNetTransport<QuicTransport>, NoiseStream<QuicSocket>>;
 
 enum TransportPeerManager {
     #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
     Memory(MemoryPeerManager),
     Tcp(TcpPeerManager),
+    Quic(QuicPeerManager),
 }
 
 pub struct PeerManagerBuilder {
@@ -168,6 +178,7 @@ impl PeerManagerBuilder {
         time_service: TimeService,
         // TODO(philiphayes): better support multiple listening addrs
         listen_address: NetworkAddress,
+        transport_protocol: TransportProtocol,
         peers_and_metadata: Arc<PeersAndMetadata>,
         authentication_mode: AuthenticationMode,
         channel_size: usize,
@@ -177,6 +188,7 @@ impl PeerManagerBuilder {
         enable_proxy_protocol: bool,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_traffic_shaping: OutboundTrafficShapingConfig,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = aptos_channel::new(
@@ -193,6 +205,7 @@ impl PeerManagerBuilder {
             time_service,
             transport_context: Some(TransportContext {
                 chain_id,
+                transport_protocol,
                 supported_protocols: ProtocolIdSet::empty(),
                 authentication_mode,
                 peers_and_metadata: peers_and_metadata.clone(),
@@ -212,6 +225,7 @@ impl PeerManagerBuilder {
                 max_message_size,
                 inbound_connection_limit,
                 tcp_buffer_cfg,
+                outbound_traffic_shaping,
             )),
             peer_manager: None,
             listen_address,
@@ -255,6 +269,7 @@ impl PeerManagerBuilder {
         let protos = transport_context.supported_protocols;
         let chain_id = transport_context.chain_id;
         let enable_proxy_protocol = transport_context.enable_proxy_protocol;
+        let transport_protocol = transport_context.transport_protocol;
 
         let (key, auth_mode) = match transport_context.authentication_mode {
             AuthenticationMode::MaybeMutual(key) => (
@@ -271,8 +286,8 @@ impl PeerManagerBuilder {
         let tcp_cfg = self.get_tcp_buffers_cfg();
         aptos_tcp_transport.set_tcp_buffers(&tcp_cfg);
 
-        self.peer_manager = match self.listen_address.as_slice() {
-            [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => {
+        self.peer_manager = match (transport_protocol, self.listen_address.as_slice()) {
+            (TransportProtocol::Tcp, [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)]) => {
                 Some(TransportPeerManager::Tcp(self.build_with_transport(
                     AptosNetTransport::new(
                         aptos_tcp_transport,
@@ -288,25 +303,50 @@ impl PeerManagerBuilder {
                     executor,
                 )))
             },
+            (TransportProtocol::Quic, [Ip4(_), Udp(_)] | [Ip6(_), Udp(_)]) => {
+                let quic_transport = QuicTransport::new().unwrap_or_else(|error| {
+                    panic!(
+                        "{} Failed to create the QUIC transport: {}",
+                        self.network_context, error
+                    )
+                });
+                Some(TransportPeerManager::Quic(self.build_with_transport(
+                    AptosNetTransport::new(
+                        quic_transport,
+                        self.network_context,
+                        self.time_service.clone(),
+                        key,
+                        auth_mode,
+                        HANDSHAKE_VERSION,
+                        chain_id,
+                        protos,
+                        enable_proxy_protocol,
+                    ),
+                    executor,
+                )))
+            },
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
-            [Memory(_)] => Some(TransportPeerManager::Memory(self.build_with_transport(
-                AptosNetTransport::new(
-                    MemoryTransport,
-                    self.network_context,
-                    self.time_service.clone(),
-                    key,
-                    auth_mode,
-                    HANDSHAKE_VERSION,
-                    chain_id,
-                    protos,
-                    enable_proxy_protocol,
-                ),
-                executor,
-            ))),
+            (TransportProtocol::Tcp, [Memory(_)]) => {
+                Some(TransportPeerManager::Memory(self.build_with_transport(
+                    AptosNetTransport::new(
+                        MemoryTransport,
+                        self.network_context,
+                        self.time_service.clone(),
+                        key,
+                        auth_mode,
+                        HANDSHAKE_VERSION,
+                        chain_id,
+                        protos,
+                        enable_proxy_protocol,
+                    ),
+                    executor,
+                )))
+            },
             _ => panic!(
-                "{} Unsupported listen_address: '{}', expected '/memory/<port>', \
-                 '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>'.",
-                self.network_context, self.listen_address
+                "{} Unsupported listen_address: '{}' for transport protocol {:?}, expected \
+                 '/memory/<port>', '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>' for TCP, \
+                 and '/ip4/<addr>/udp/<port>' or '/ip6/<addr>/udp/<port>' for QUIC.",
+                self.network_context, self.listen_address, transport_protocol
             ),
         };
 
@@ -346,6 +386,7 @@ impl PeerManagerBuilder {
             pm_context.max_frame_size,
             pm_context.max_message_size,
             pm_context.inbound_connection_limit,
+            pm_context.outbound_traffic_shaping,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -376,6 +417,7 @@ impl PeerManagerBuilder {
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
             TransportPeerManager::Memory(pm) => self.start_peer_manager(pm, executor),
             TransportPeerManager::Tcp(pm) => self.start_peer_manager(pm, executor),
+            TransportPeerManager::Quic(pm) => self.start_peer_manager(pm, executor),
         }
     }
 
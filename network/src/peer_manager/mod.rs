@@ -23,7 +23,10 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_config::{
+    config::OutboundTrafficShapingConfig,
+    network_id::{NetworkContext, PeerNetworkId},
+};
 use aptos_logger::prelude::*;
 use aptos_netcore::transport::{ConnectionOrigin, Transport};
 use aptos_short_hex_str::AsShortHexStr;
@@ -119,6 +122,8 @@ where
     max_message_size: usize,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
+    /// Weighted priority and byte-rate limits for the outbound messages to each peer
+    outbound_traffic_shaping: OutboundTrafficShapingConfig,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -147,6 +152,7 @@ where
         max_frame_size: usize,
         max_message_size: usize,
         inbound_connection_limit: usize,
+        outbound_traffic_shaping: OutboundTrafficShapingConfig,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
             channel_size,
@@ -189,6 +195,7 @@ where
             max_frame_size,
             max_message_size,
             inbound_connection_limit,
+            outbound_traffic_shaping,
         }
     }
 
@@ -592,6 +599,9 @@ where
                     e
                 );
             };
+            if let Some(quic_connection) = &connection.quic_connection {
+                quic_connection.close();
+            }
         };
         self.executor.spawn(drop_fut);
     }
@@ -673,6 +683,7 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            &self.outbound_traffic_shaping,
         );
         self.executor.spawn(peer.start());
 
@@ -175,7 +175,10 @@ pub struct InboundRpcs {
     remote_peer_id: PeerId,
     /// The core async queue of pending inbound rpc tasks. The tasks are driven
     /// to completion by the `InboundRpcs::next_completed_response()` method.
-    inbound_rpc_tasks: FuturesUnordered<BoxFuture<'static, Result<RpcResponse, RpcError>>>,
+    /// Each task also yields the `ProtocolId` of its request, so the response
+    /// can be written on the stream of that protocol.
+    inbound_rpc_tasks:
+        FuturesUnordered<BoxFuture<'static, (ProtocolId, Result<RpcResponse, RpcError>)>>,
     /// A blanket timeout on all inbound rpc requests. If the application handler
     /// doesn't respond to the request before this timeout, the request will be
     /// dropped.
@@ -272,7 +275,7 @@ impl InboundRpcs {
                     Ok(_) => timer.stop_and_record(),
                     Err(_) => timer.stop_and_discard(),
                 };
-                maybe_response
+                (protocol_id, maybe_response)
             })
             .boxed();
 
@@ -288,7 +291,7 @@ impl InboundRpcs {
     /// `futures::select!`.
     pub fn next_completed_response(
         &mut self,
-    ) -> impl Future<Output = Result<RpcResponse, RpcError>> + FusedFuture + '_ {
+    ) -> impl Future<Output = (ProtocolId, Result<RpcResponse, RpcError>)> + FusedFuture + '_ {
         self.inbound_rpc_tasks.select_next_some()
     }
 
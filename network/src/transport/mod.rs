@@ -19,17 +19,27 @@ use aptos_id_generator::{IdGenerator, U32IdGenerator};
 use aptos_logger::prelude::*;
 // Re-exposed for aptos-network-checker
 pub use aptos_netcore::transport::tcp::{resolve_and_connect, TCPBufferCfg, TcpSocket};
-use aptos_netcore::transport::{proxy_protocol, tcp, ConnectionOrigin, Transport};
+use aptos_netcore::{
+    framing::{read_u16frame, write_u16frame},
+    transport::{
+        proxy_protocol,
+        quic::{QuicConnection, QuicSocketExt},
+        tcp, ConnectionOrigin, Transport,
+    },
+};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{timeout, TimeService, TimeServiceTrait};
 use aptos_types::{
     chain_id::ChainId,
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, NetworkAddress},
+    network_address::{
+        parse_dns_tcp, parse_dns_udp, parse_ip_tcp, parse_ip_udp, parse_memory, NetworkAddress,
+    },
     PeerId,
 };
+use bytes::BytesMut;
 use futures::{
     future::{Future, FutureExt},
-    io::{AsyncRead, AsyncWrite},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
     stream::{Stream, StreamExt, TryStreamExt},
 };
 use serde::{Deserialize, Serialize};
@@ -45,6 +55,9 @@ pub const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(30);
 /// TODO: Add ability to support more than one messaging protocol.
 pub const SUPPORTED_MESSAGING_PROTOCOL: MessagingProtocolVersion = MessagingProtocolVersion::V1;
 
+/// The label of the keying material that binds a QUIC connection to its Noise session.
+const QUIC_BINDING_LABEL: &[u8] = b"aptos-network-quic-noise-binding";
+
 /// Global connection-id generator.
 static CONNECTION_ID_GENERATOR: ConnectionIdGenerator = ConnectionIdGenerator::new();
 
@@ -184,6 +197,9 @@ impl fmt::Display for ConnectionMetadata {
 pub struct Connection<TSocket> {
     pub socket: TSocket,
     pub metadata: ConnectionMetadata,
+    /// The QUIC connection that carries the socket, if any. Messages are then sent over a
+    /// stream per protocol instead of being multiplexed over the socket.
+    pub quic_connection: Option<QuicConnection>,
 }
 
 /// Convenience function for adding a timeout to a Future that returns an `io::Result`.
@@ -225,6 +241,29 @@ impl UpgradeContext {
     }
 }
 
+/// Exchanges the keying material of the QUIC connection over the Noise session, and verifies
+/// that the remote peer derived the same material. This binds the QUIC connection, whose streams
+/// are only protected by an unauthenticated TLS session, to the authenticated Noise session, so
+/// a man in the middle can't relay the Noise handshake between two QUIC connections of its own.
+async fn bind_quic_connection<T: TSocket>(
+    socket: &mut NoiseStream<T>,
+    quic_connection: &QuicConnection,
+) -> io::Result<()> {
+    let binding = quic_connection.export_keying_material(QUIC_BINDING_LABEL)?;
+    write_u16frame(socket, &binding).await?;
+    socket.flush().await?;
+
+    let mut remote_binding = BytesMut::new();
+    read_u16frame(socket, &mut remote_binding).await?;
+    if remote_binding.as_ref() != binding.as_ref() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "QUIC connection isn't bound to the Noise session",
+        ));
+    }
+    Ok(())
+}
+
 /// If we have proxy protocol enabled, then prepend the un-proxied address to the error.
 fn add_pp_addr(proxy_protocol_enabled: bool, error: io::Error, addr: &NetworkAddress) -> io::Error {
     if proxy_protocol_enabled {
@@ -242,7 +281,7 @@ fn add_pp_addr(proxy_protocol_enabled: bool, error: io::Error, addr: &NetworkAdd
 /// `ctxt.noise.auth_mode` is `HandshakeAuthMode::Mutual( anti_replay_timestamps , trusted_peers )`,
 /// then we will only allow connections from peers with a pubkey in the `trusted_peers`
 /// set. Otherwise, we will allow inbound connections from any pubkey.
-async fn upgrade_inbound<T: TSocket>(
+async fn upgrade_inbound<T: TSocket + QuicSocketExt>(
     ctxt: Arc<UpgradeContext>,
     fut_socket: impl Future<Output = io::Result<T>>,
     addr: NetworkAddress,
@@ -250,6 +289,7 @@ async fn upgrade_inbound<T: TSocket>(
 ) -> io::Result<Connection<NoiseStream<T>>> {
     let origin = ConnectionOrigin::Inbound;
     let mut socket = fut_socket.await?;
+    let quic_connection = socket.quic_connection();
 
     // If we have proxy protocol enabled, process the event, otherwise skip it
     // TODO: This would make more sense to build this in at instantiation so we don't need to put the if statement here
@@ -290,6 +330,13 @@ async fn upgrade_inbound<T: TSocket>(
     let remote_pubkey = socket.get_remote_static();
     let addr = addr.append_prod_protos(remote_pubkey, HANDSHAKE_VERSION);
 
+    // bind the QUIC connection (if any) to the noise session
+    if let Some(quic_connection) = &quic_connection {
+        bind_quic_connection(&mut socket, quic_connection)
+            .await
+            .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
+    }
+
     // exchange HandshakeMsg
     let handshake_msg = HandshakeMsg {
         supported_protocols: ctxt.supported_protocols.clone(),
@@ -328,12 +375,13 @@ async fn upgrade_inbound<T: TSocket>(
             application_protocols,
             peer_role,
         ),
+        quic_connection,
     })
 }
 
 /// Upgrade an outbound connection. This means we run a Noise IK handshake for
 /// authentication and then negotiate common supported protocols.
-pub async fn upgrade_outbound<T: TSocket>(
+pub async fn upgrade_outbound<T: TSocket + QuicSocketExt>(
     ctxt: Arc<UpgradeContext>,
     fut_socket: impl Future<Output = io::Result<T>>,
     addr: NetworkAddress,
@@ -342,6 +390,7 @@ pub async fn upgrade_outbound<T: TSocket>(
 ) -> io::Result<Connection<NoiseStream<T>>> {
     let origin = ConnectionOrigin::Outbound;
     let socket = fut_socket.await?;
+    let quic_connection = socket.quic_connection();
 
     // noise handshake
     let (mut socket, peer_role) = ctxt
@@ -372,6 +421,11 @@ pub async fn upgrade_outbound<T: TSocket>(
     // sanity check: Noise IK should always guarantee this is true
     debug_assert_eq!(remote_pubkey, socket.get_remote_static());
 
+    // bind the QUIC connection (if any) to the noise session
+    if let Some(quic_connection) = &quic_connection {
+        bind_quic_connection(&mut socket, quic_connection).await?;
+    }
+
     // exchange HandshakeMsg
     let handshake_msg = HandshakeMsg {
         supported_protocols: ctxt.supported_protocols.clone(),
@@ -403,6 +457,7 @@ pub async fn upgrade_outbound<T: TSocket>(
             application_protocols,
             peer_role,
         ),
+        quic_connection,
     })
 }
 
@@ -410,7 +465,7 @@ pub async fn upgrade_outbound<T: TSocket>(
 ///
 /// The base transport layer is pluggable, so long as it provides a reliable,
 /// ordered, connection-oriented, byte-stream abstraction (e.g., TCP). We currently
-/// use either `MemoryTransport` or `TcpTransport` as this base layer.
+/// use either `MemoryTransport`, `TcpTransport` or `QuicTransport` as this base layer.
 ///
 /// Inbound and outbound connections are first established with the `base_transport`
 /// and then negotiate a secure, authenticated transport layer (currently Noise
@@ -429,7 +484,7 @@ pub struct AptosNetTransport<TTransport> {
 impl<TTransport> AptosNetTransport<TTransport>
 where
     TTransport: Transport<Error = io::Error>,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + QuicSocketExt,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -482,13 +537,15 @@ where
         let (base_transport_protos, base_transport_suffix) = parse_ip_tcp(protos)
             .map(|x| (&protos[..2], x.1))
             .or_else(|| parse_dns_tcp(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_ip_udp(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_dns_udp(protos).map(|x| (&protos[..2], x.1)))
             .or_else(|| parse_memory(protos).map(|x| (&protos[..1], x.1)))
             .ok_or_else(|| {
                 io::Error::new(
                     io::ErrorKind::InvalidInput,
                     format!(
                         "Unexpected dialing network address: '{}', expected: \
-                         memory, ip+tcp, or dns+tcp",
+                         memory, ip+tcp, dns+tcp, ip+udp or dns+udp",
                         addr
                     ),
                 )
@@ -534,6 +591,9 @@ where
     /// `/dns/<ipaddr>/tcp/<port>` or
     /// `/dns4/<ipaddr>/tcp/<port>` or
     /// `/dns6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then `/<base_transport>` is any
+    /// of the above, with `/udp/<port>` instead of `/tcp/<port>`.
     pub fn dial(
         &self,
         peer_id: PeerId,
@@ -580,6 +640,11 @@ where
     ///
     /// `/ip4/<ipaddr>/tcp/<port>` or
     /// `/ip6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then we expect:
+    ///
+    /// `/ip4/<ipaddr>/udp/<port>` or
+    /// `/ip6/<ipaddr>/udp/<port>`
     pub fn listen_on(
         &self,
         addr: NetworkAddress,
@@ -636,7 +701,7 @@ where
 impl<TTransport: Transport> Transport for AptosNetTransport<TTransport>
 where
     TTransport: Transport<Error = io::Error> + Send + 'static,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + QuicSocketExt,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
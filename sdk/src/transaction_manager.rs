@@ -0,0 +1,411 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    move_types::vm_status::StatusCode,
+    rest_client::{
+        aptos_api_types::AptosErrorCode, error::RestError, Client as ApiClient, Transaction,
+    },
+    transaction_builder::TransactionFactory,
+    types::{
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use anyhow::{bail, Context, Result};
+use futures::future::join_all;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, Semaphore};
+
+/// Mempool's default `capacity_per_user`: a node will not hold more than this many
+/// pending transactions from a single account. Nodes configured with a different
+/// capacity need a matching `TransactionManagerConfig::max_in_flight`.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 100;
+const DEFAULT_MAX_RESUBMISSIONS: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct TransactionManagerConfig {
+    /// Maximum number of transactions submitted but not yet committed or expired.
+    /// Should not exceed the `capacity_per_user` of the nodes being submitted to.
+    pub max_in_flight: usize,
+    /// How many times a transaction is re-signed and resubmitted after it expired
+    /// or was rejected for a stale sequence number.
+    pub max_resubmissions: usize,
+}
+
+impl Default for TransactionManagerConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            max_resubmissions: DEFAULT_MAX_RESUBMISSIONS,
+        }
+    }
+}
+
+/// Submits transactions from a single account concurrently. The manager owns the
+/// account and hands out sequence numbers to callers as they submit, caps the
+/// number of transactions in flight, and resyncs the sequence number from the
+/// chain when a submission is rejected for its sequence number, or when a failed
+/// or expired transaction leaves a gap and nothing else is in flight, so a single
+/// failure does not leave the account stuck behind a sequence number gap.
+pub struct AccountTransactionManager {
+    client: ApiClient,
+    account: Mutex<LocalAccount>,
+    transaction_factory: TransactionFactory,
+    in_flight: Semaphore,
+    config: TransactionManagerConfig,
+}
+
+impl AccountTransactionManager {
+    pub fn new(
+        client: ApiClient,
+        account: LocalAccount,
+        transaction_factory: TransactionFactory,
+        config: TransactionManagerConfig,
+    ) -> Self {
+        Self {
+            client,
+            account: Mutex::new(account),
+            transaction_factory,
+            in_flight: Semaphore::new(config.max_in_flight),
+            config,
+        }
+    }
+
+    /// Consumes the manager, returning the account with its latest known sequence number.
+    pub fn into_account(self) -> LocalAccount {
+        self.account.into_inner()
+    }
+
+    /// Returns the next sequence number that will be handed out.
+    pub async fn sequence_number(&self) -> u64 {
+        self.account.lock().await.sequence_number()
+    }
+
+    /// Resets the local sequence number to the one currently on chain.
+    pub async fn resync_sequence_number(&self) -> Result<u64> {
+        let mut account = self.account.lock().await;
+        let sequence_number = self
+            .client
+            .get_account(account.address())
+            .await
+            .context("Failed to get account")?
+            .into_inner()
+            .sequence_number;
+        *account.sequence_number_mut() = sequence_number;
+        Ok(sequence_number)
+    }
+
+    /// Signs, submits and waits for a transaction with the given payload, resubmitting
+    /// it with a new sequence number if it expires or its sequence number was stale.
+    pub async fn submit_and_wait(&self, payload: TransactionPayload) -> Result<Transaction> {
+        let _permit = self
+            .in_flight
+            .acquire()
+            .await
+            .expect("in-flight semaphore is never closed");
+
+        let mut attempt = 0;
+        loop {
+            let txn = self.submit(&payload, &mut attempt).await?;
+            match self.client.wait_for_signed_transaction(&txn).await {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(err) => {
+                    if !is_expired(&txn) {
+                        return Err(err).context("Failed to wait for transaction");
+                    }
+                    // The transaction will never be committed, most likely because an
+                    // earlier sequence number was lost. Resync and try again.
+                    self.resync_if_idle().await?;
+                    if attempt >= self.config.max_resubmissions {
+                        bail!(
+                            "Transaction {} expired after {} resubmissions",
+                            txn.clone().committed_hash(),
+                            attempt
+                        );
+                    }
+                    attempt += 1;
+                },
+            }
+        }
+    }
+
+    /// Submits all payloads concurrently, returning the results in the same order.
+    pub async fn submit_all_and_wait(
+        &self,
+        payloads: Vec<TransactionPayload>,
+    ) -> Vec<Result<Transaction>> {
+        join_all(
+            payloads
+                .into_iter()
+                .map(|payload| self.submit_and_wait(payload)),
+        )
+        .await
+    }
+
+    /// Signs and submits a transaction with the given payload, resyncing and resubmitting
+    /// it while its sequence number is rejected, up to the resubmission limit.
+    async fn submit(
+        &self,
+        payload: &TransactionPayload,
+        attempt: &mut usize,
+    ) -> Result<SignedTransaction> {
+        loop {
+            let txn = self.sign(payload.clone()).await;
+            let err = match self.client.submit_bcs(&txn).await {
+                Ok(_) => return Ok(txn),
+                Err(err) => err,
+            };
+            if is_sequence_number_rejected(&err) {
+                // The local sequence number is off, so every transaction signed after this
+                // one would be rejected as well.
+                self.resync_sequence_number().await?;
+                if *attempt < self.config.max_resubmissions {
+                    *attempt += 1;
+                    continue;
+                }
+            } else {
+                // The sequence number handed to this transaction was never used.
+                self.resync_if_idle().await?;
+            }
+            return Err(err).context("Failed to submit transaction");
+        }
+    }
+
+    /// Resyncs the sequence number unless other transactions are in flight: they hold
+    /// the sequence numbers a resync would hand out again. If they are stuck behind a
+    /// gap they expire, and the last of them to do so resyncs.
+    async fn resync_if_idle(&self) -> Result<()> {
+        // The caller holds one of the permits.
+        if self.in_flight.available_permits() + 1 >= self.config.max_in_flight {
+            self.resync_sequence_number().await?;
+        }
+        Ok(())
+    }
+
+    async fn sign(&self, payload: TransactionPayload) -> SignedTransaction {
+        let builder = self.transaction_factory.payload(payload);
+        self.account
+            .lock()
+            .await
+            .sign_with_transaction_builder(builder)
+    }
+}
+
+/// Whether the submission was rejected because the sequence number is behind the
+/// account's, or too far ahead of it.
+fn is_sequence_number_rejected(err: &RestError) -> bool {
+    match err {
+        RestError::Api(inner) => match inner.error.error_code {
+            AptosErrorCode::SequenceNumberTooOld => true,
+            AptosErrorCode::VmError => matches!(
+                inner.error.vm_error_code,
+                Some(code) if code == StatusCode::SEQUENCE_NUMBER_TOO_OLD as u64
+                    || code == StatusCode::SEQUENCE_NUMBER_TOO_NEW as u64
+            ),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_expired(txn: &SignedTransaction) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    now >= txn.expiration_timestamp_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rest_client::aptos_api_types::{
+            X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION,
+            X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_BLOCK_HEIGHT,
+        },
+        transaction_builder::aptos_stdlib,
+        types::{account_address::AccountAddress, chain_id::ChainId},
+    };
+    use aptos_config::config::MempoolConfig;
+    use httpmock::{prelude::*, Mock, Then};
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    fn test_manager(base_url: &str, config: TransactionManagerConfig) -> AccountTransactionManager {
+        let mut rng = rand::rngs::OsRng;
+        AccountTransactionManager::new(
+            ApiClient::new(url::Url::parse(base_url).unwrap()),
+            LocalAccount::generate(&mut rng),
+            // Transactions expire as soon as they are signed, so waiting for one fails as
+            // soon as the node reports it missing.
+            TransactionFactory::new(ChainId::test()).with_transaction_expiration_time(0),
+            config,
+        )
+    }
+
+    /// Adds the ledger state headers, with a ledger timestamp ahead of the expiration of
+    /// every transaction signed so far.
+    fn with_state_headers(then: Then) -> Then {
+        let timestamp_usecs = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+            + std::time::Duration::from_secs(60))
+        .as_micros();
+        then.header(X_APTOS_CHAIN_ID, ChainId::test().id().to_string())
+            .header(X_APTOS_EPOCH, "1")
+            .header(X_APTOS_LEDGER_VERSION, "100")
+            .header(X_APTOS_LEDGER_OLDEST_VERSION, "0")
+            .header(X_APTOS_LEDGER_TIMESTAMP, timestamp_usecs.to_string())
+            .header(X_APTOS_BLOCK_HEIGHT, "10")
+            .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "0")
+    }
+
+    fn error_body(error_code: &str, vm_error_code: Option<StatusCode>) -> serde_json::Value {
+        json!({
+            "message": "error",
+            "error_code": error_code,
+            "vm_error_code": vm_error_code.map(|code| code as u64),
+        })
+    }
+
+    fn has_sequence_number<const SEQUENCE_NUMBER: u64>(req: &HttpMockRequest) -> bool {
+        match req
+            .body
+            .as_ref()
+            .map(|body| bcs::from_bytes::<SignedTransaction>(body))
+        {
+            Some(Ok(txn)) => txn.sequence_number() == SEQUENCE_NUMBER,
+            _ => false,
+        }
+    }
+
+    async fn mock_account<'a>(
+        server: &'a MockServer,
+        manager: &AccountTransactionManager,
+        sequence_number: u64,
+    ) -> Mock<'a> {
+        let address = manager.account.lock().await.address();
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/v1/accounts/{}", address));
+                with_state_headers(then.status(200)).json_body(json!({
+                    "sequence_number": sequence_number.to_string(),
+                    "authentication_key": format!("0x{}", "00".repeat(32)),
+                }));
+            })
+            .await
+    }
+
+    async fn mock_accepted(server: &MockServer, matcher: fn(&HttpMockRequest) -> bool) -> Mock {
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/transactions").matches(matcher);
+                with_state_headers(then.status(202));
+            })
+            .await
+    }
+
+    #[test]
+    fn test_default_max_in_flight_matches_mempool_capacity() {
+        assert_eq!(
+            DEFAULT_MAX_IN_FLIGHT,
+            MempoolConfig::default().capacity_per_user
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_signers_get_unique_sequence_numbers() {
+        let manager = test_manager("http://localhost:8080", TransactionManagerConfig::default());
+
+        let payload = aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1);
+        let txns = join_all((0..10).map(|_| manager.sign(payload.clone()))).await;
+        let sequence_numbers: BTreeSet<_> = txns.iter().map(|txn| txn.sequence_number()).collect();
+
+        assert_eq!(sequence_numbers, (0..10).collect());
+        assert_eq!(manager.sequence_number().await, 10);
+    }
+
+    #[tokio::test]
+    async fn test_resubmits_after_sequence_number_too_old() {
+        let server = MockServer::start_async().await;
+        let manager = test_manager(&server.base_url(), TransactionManagerConfig::default());
+        let account = mock_account(&server, &manager, 5).await;
+        let too_old = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/v1/transactions")
+                    .matches(has_sequence_number::<0>);
+                with_state_headers(then.status(400))
+                    .json_body(error_body("sequence_number_too_old", None));
+            })
+            .await;
+        let accepted = mock_accepted(&server, has_sequence_number::<5>).await;
+
+        let payload = aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1);
+        let mut attempt = 0;
+        let txn = manager.submit(&payload, &mut attempt).await.unwrap();
+        assert_eq!(txn.sequence_number(), 5);
+        assert_eq!(attempt, 1);
+        assert_eq!(manager.sequence_number().await, 6);
+        account.assert_async().await;
+        too_old.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resubmits_expired_transactions() {
+        let server = MockServer::start_async().await;
+        let manager = test_manager(&server.base_url(), TransactionManagerConfig {
+            max_resubmissions: 1,
+            ..TransactionManagerConfig::default()
+        });
+        let account = mock_account(&server, &manager, 3).await;
+        let first = mock_accepted(&server, has_sequence_number::<0>).await;
+        let resubmitted = mock_accepted(&server, has_sequence_number::<3>).await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path_contains("/v1/transactions/by_hash/");
+                with_state_headers(then.status(404))
+                    .json_body(error_body("transaction_not_found", None));
+            })
+            .await;
+
+        let payload = aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1);
+        let err = manager.submit_and_wait(payload).await.unwrap_err();
+        assert!(err.to_string().contains("expired after 1 resubmissions"));
+        // Resynced after each expiration.
+        account.assert_hits_async(2).await;
+        first.assert_async().await;
+        resubmitted.assert_async().await;
+        assert_eq!(manager.sequence_number().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_submission_resyncs_only_when_idle() {
+        let server = MockServer::start_async().await;
+        let manager = test_manager(&server.base_url(), TransactionManagerConfig::default());
+        let account = mock_account(&server, &manager, 0).await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/transactions");
+                with_state_headers(then.status(400)).json_body(error_body(
+                    "vm_error",
+                    Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE),
+                ));
+            })
+            .await;
+        let payload = aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1);
+
+        // Another transaction is in flight and may hold the next sequence number, so the
+        // gap is left for it to resolve.
+        let permit = manager.in_flight.acquire().await.unwrap();
+        assert!(manager.submit_and_wait(payload.clone()).await.is_err());
+        account.assert_hits_async(0).await;
+        assert_eq!(manager.sequence_number().await, 1);
+
+        drop(permit);
+        assert!(manager.submit_and_wait(payload).await.is_err());
+        account.assert_async().await;
+        assert_eq!(manager.sequence_number().await, 0);
+    }
+}
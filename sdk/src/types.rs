@@ -5,15 +5,19 @@
 use crate::{
     crypto::{
         ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-        traits::Uniform,
+        secp256k1_ecdsa,
+        traits::{SigningKey, Uniform},
     },
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::{AccountAuthenticator, AuthenticationKey},
+            RawTransaction, RawTransactionWithData, SignedTransaction,
+        },
     },
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use aptos_types::event::EventKey;
 pub use aptos_types::*;
 use bip39::{Language, Mnemonic, Seed};
@@ -29,7 +33,7 @@ pub struct LocalAccount {
     /// Address of the account.
     address: AccountAddress,
     /// Authentication key of the account.
-    key: AccountKey,
+    key: LocalAccountKey,
     /// Latest known sequence number of the account, it can be different from validator.
     sequence_number: u64,
 }
@@ -38,7 +42,11 @@ impl LocalAccount {
     /// Create a new representation of an account locally. Note: This function
     /// does not actually create an account on the Aptos blockchain, just a
     /// local representation.
-    pub fn new<T: Into<AccountKey>>(address: AccountAddress, key: T, sequence_number: u64) -> Self {
+    pub fn new<T: Into<LocalAccountKey>>(
+        address: AccountAddress,
+        key: T,
+        sequence_number: u64,
+    ) -> Self {
         Self {
             address,
             key: key.into(),
@@ -59,7 +67,7 @@ impl LocalAccount {
         let key = ExtendedSecretKey::from_seed(seed.as_bytes())?
             .derive(&derive_path)?
             .secret_key;
-        let key = AccountKey::from(Ed25519PrivateKey::try_from(key.as_bytes().as_ref())?);
+        let key = LocalAccountKey::from(Ed25519PrivateKey::try_from(key.as_bytes().as_ref())?);
         let address = key.authentication_key().derived_address();
 
         Ok(Self {
@@ -82,10 +90,52 @@ impl LocalAccount {
         Self::new(address, key, 0)
     }
 
+    /// Generate a new account locally that is controlled by a secp256k1 ECDSA key. Note: This
+    /// function does not actually create an account on the Aptos blockchain, it just generates a
+    /// new account locally.
+    pub fn generate_secp256k1_ecdsa<R>(rng: &mut R) -> Self
+    where
+        R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+    {
+        let key = Secp256k1EcdsaAccountKey::generate(rng);
+        let address = key.authentication_key().derived_address();
+
+        Self::new(address, key, 0)
+    }
+
     pub fn sign_transaction(&self, txn: RawTransaction) -> SignedTransaction {
-        txn.sign(self.private_key(), self.public_key().clone())
-            .expect("Signing a txn can't fail")
-            .into_inner()
+        match &self.key {
+            LocalAccountKey::Ed25519(key) => txn
+                .sign(key.private_key(), key.public_key().clone())
+                .expect("Signing a txn can't fail")
+                .into_inner(),
+            LocalAccountKey::Secp256k1Ecdsa(key) => txn
+                .sign_secp256k1_ecdsa(key.private_key(), key.public_key().clone())
+                .expect("Signing a txn can't fail")
+                .into_inner(),
+        }
+    }
+
+    /// Signs the message with this account's key, for use as one of the signers of a
+    /// multi-agent transaction.
+    pub fn sign_as_account_authenticator(
+        &self,
+        message: &RawTransactionWithData,
+    ) -> AccountAuthenticator {
+        match &self.key {
+            LocalAccountKey::Ed25519(key) => AccountAuthenticator::ed25519(
+                key.public_key().clone(),
+                key.private_key()
+                    .sign(message)
+                    .expect("Signing a message can't fail"),
+            ),
+            LocalAccountKey::Secp256k1Ecdsa(key) => AccountAuthenticator::secp256k1_ecdsa(
+                key.public_key().clone(),
+                key.private_key()
+                    .sign(message)
+                    .expect("Signing a message can't fail"),
+            ),
+        }
     }
 
     pub fn sign_with_transaction_builder(
@@ -105,39 +155,100 @@ impl LocalAccount {
         secondary_signers: Vec<&Self>,
         builder: TransactionBuilder,
     ) -> SignedTransaction {
-        let secondary_signer_addresses = secondary_signers
+        let secondary_signer_addresses: Vec<_> = secondary_signers
             .iter()
             .map(|signer| signer.address())
             .collect();
-        let secondary_signer_privkeys = secondary_signers
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        *self.sequence_number_mut() += 1;
+
+        let message = RawTransactionWithData::new_multi_agent(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+        );
+        let sender_authenticator = self.sign_as_account_authenticator(&message);
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.sign_as_account_authenticator(&message))
+            .collect();
+        SignedTransaction::new_multi_agent(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+        )
+    }
+
+    /// Signs a transaction whose gas is paid by `fee_payer` instead of this account. The fee payer
+    /// co-signs the transaction; its sequence number is left untouched.
+    pub fn sign_fee_payer_with_transaction_builder(
+        &mut self,
+        secondary_signers: Vec<&Self>,
+        fee_payer: &Self,
+        builder: TransactionBuilder,
+    ) -> SignedTransaction {
+        let secondary_signer_addresses: Vec<_> = secondary_signers
             .iter()
-            .map(|signer| signer.private_key())
+            .map(|signer| signer.address())
             .collect();
         let raw_txn = builder
             .sender(self.address())
             .sequence_number(self.sequence_number())
             .build();
         *self.sequence_number_mut() += 1;
-        raw_txn
-            .sign_multi_agent(
-                self.private_key(),
-                secondary_signer_addresses,
-                secondary_signer_privkeys,
-            )
-            .expect("Signing multi agent txn failed")
-            .into_inner()
+
+        let message = RawTransactionWithData::new_multi_agent_with_fee_payer(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+            fee_payer.address(),
+        );
+        let sender_authenticator = self.sign_as_account_authenticator(&message);
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.sign_as_account_authenticator(&message))
+            .collect();
+        let fee_payer_authenticator = fee_payer.sign_as_account_authenticator(&message);
+        SignedTransaction::new_fee_payer(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+            fee_payer.address(),
+            fee_payer_authenticator,
+        )
     }
 
     pub fn address(&self) -> AccountAddress {
         self.address
     }
 
-    pub fn private_key(&self) -> &Ed25519PrivateKey {
-        self.key.private_key()
+    /// The Ed25519 private key of the account. Fails if the account is controlled by a key of
+    /// another scheme, see `key()`.
+    pub fn private_key(&self) -> Result<&Ed25519PrivateKey> {
+        Ok(self.ed25519_key()?.private_key())
     }
 
-    pub fn public_key(&self) -> &Ed25519PublicKey {
-        self.key.public_key()
+    /// The Ed25519 public key of the account. Fails if the account is controlled by a key of
+    /// another scheme, see `key()`.
+    pub fn public_key(&self) -> Result<&Ed25519PublicKey> {
+        Ok(self.ed25519_key()?.public_key())
+    }
+
+    pub fn key(&self) -> &LocalAccountKey {
+        &self.key
+    }
+
+    fn ed25519_key(&self) -> Result<&AccountKey> {
+        match &self.key {
+            LocalAccountKey::Ed25519(key) => Ok(key),
+            LocalAccountKey::Secp256k1Ecdsa(_) => Err(anyhow!(
+                "Account {} is not controlled by an Ed25519 key",
+                self.address
+            )),
+        }
     }
 
     pub fn authentication_key(&self) -> AuthenticationKey {
@@ -152,7 +263,7 @@ impl LocalAccount {
         &mut self.sequence_number
     }
 
-    pub fn rotate_key<T: Into<AccountKey>>(&mut self, new_key: T) -> AccountKey {
+    pub fn rotate_key<T: Into<LocalAccountKey>>(&mut self, new_key: T) -> LocalAccountKey {
         std::mem::replace(&mut self.key, new_key.into())
     }
 
@@ -211,9 +322,96 @@ impl From<Ed25519PrivateKey> for AccountKey {
     }
 }
 
+#[derive(Debug)]
+pub struct Secp256k1EcdsaAccountKey {
+    private_key: secp256k1_ecdsa::PrivateKey,
+    public_key: secp256k1_ecdsa::PublicKey,
+    authentication_key: AuthenticationKey,
+}
+
+impl Secp256k1EcdsaAccountKey {
+    pub fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+    {
+        let private_key = secp256k1_ecdsa::PrivateKey::generate(rng);
+        Self::from_private_key(private_key)
+    }
+
+    pub fn from_private_key(private_key: secp256k1_ecdsa::PrivateKey) -> Self {
+        let public_key = secp256k1_ecdsa::PublicKey::from(&private_key);
+        let authentication_key = AuthenticationKey::secp256k1_ecdsa(&public_key);
+
+        Self {
+            private_key,
+            public_key,
+            authentication_key,
+        }
+    }
+
+    pub fn private_key(&self) -> &secp256k1_ecdsa::PrivateKey {
+        &self.private_key
+    }
+
+    pub fn public_key(&self) -> &secp256k1_ecdsa::PublicKey {
+        &self.public_key
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        self.authentication_key
+    }
+}
+
+impl From<secp256k1_ecdsa::PrivateKey> for Secp256k1EcdsaAccountKey {
+    fn from(private_key: secp256k1_ecdsa::PrivateKey) -> Self {
+        Self::from_private_key(private_key)
+    }
+}
+
+/// The key a `LocalAccount` signs transactions with, one per supported signature scheme.
+#[derive(Debug)]
+pub enum LocalAccountKey {
+    Ed25519(AccountKey),
+    Secp256k1Ecdsa(Secp256k1EcdsaAccountKey),
+}
+
+impl LocalAccountKey {
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            Self::Ed25519(key) => key.authentication_key(),
+            Self::Secp256k1Ecdsa(key) => key.authentication_key(),
+        }
+    }
+}
+
+impl From<AccountKey> for LocalAccountKey {
+    fn from(key: AccountKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
+impl From<Ed25519PrivateKey> for LocalAccountKey {
+    fn from(private_key: Ed25519PrivateKey) -> Self {
+        Self::Ed25519(private_key.into())
+    }
+}
+
+impl From<Secp256k1EcdsaAccountKey> for LocalAccountKey {
+    fn from(key: Secp256k1EcdsaAccountKey) -> Self {
+        Self::Secp256k1Ecdsa(key)
+    }
+}
+
+impl From<secp256k1_ecdsa::PrivateKey> for LocalAccountKey {
+    fn from(private_key: secp256k1_ecdsa::PrivateKey) -> Self {
+        Self::Secp256k1Ecdsa(private_key.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::transaction::authenticator::TransactionAuthenticator;
 
     #[test]
     fn test_recover_account_from_derive_path() {
@@ -234,4 +432,63 @@ mod tests {
         // Return an error for empty mnemonic phrase.
         assert!(LocalAccount::from_derive_path(derive_path, "", 0).is_err());
     }
+
+    #[test]
+    fn test_secp256k1_ecdsa_account_signs_transactions() {
+        use crate::{transaction_builder::TransactionFactory, types::chain_id::ChainId};
+
+        let mut rng = rand::rngs::OsRng;
+        let mut sender = LocalAccount::generate_secp256k1_ecdsa(&mut rng);
+        let secondary = LocalAccount::generate(&mut rng);
+        assert_eq!(
+            sender.address(),
+            sender.authentication_key().derived_address()
+        );
+        // There is no Ed25519 key to return.
+        assert!(sender.private_key().is_err());
+        assert!(sender.public_key().is_err());
+        assert!(secondary.public_key().is_ok());
+
+        let factory = TransactionFactory::new(ChainId::test());
+        let txn = sender.sign_with_transaction_builder(factory.transfer(secondary.address(), 1));
+        assert!(matches!(
+            txn.authenticator_ref(),
+            TransactionAuthenticator::Secp256k1Ecdsa { .. }
+        ));
+        assert_eq!(
+            txn.authenticator().sender().authentication_key(),
+            sender.authentication_key()
+        );
+        assert!(txn.clone().check_signature().is_ok());
+
+        let txn = sender.sign_multi_agent_with_transaction_builder(
+            vec![&secondary],
+            factory.transfer(secondary.address(), 1),
+        );
+        assert!(txn.check_signature().is_ok());
+    }
+
+    #[test]
+    fn test_fee_payer_signs_transactions() {
+        use crate::{transaction_builder::TransactionFactory, types::chain_id::ChainId};
+
+        let mut rng = rand::rngs::OsRng;
+        let mut sender = LocalAccount::generate(&mut rng);
+        let fee_payer = LocalAccount::generate_secp256k1_ecdsa(&mut rng);
+
+        let factory = TransactionFactory::new(ChainId::test());
+        let txn = sender.sign_fee_payer_with_transaction_builder(
+            vec![],
+            &fee_payer,
+            factory.transfer(fee_payer.address(), 1),
+        );
+        assert!(txn.is_fee_payer());
+        assert_eq!(
+            txn.authenticator_ref().fee_payer_address(),
+            Some(fee_payer.address())
+        );
+        assert_eq!(sender.sequence_number(), 1);
+        assert_eq!(fee_payer.sequence_number(), 0);
+        assert!(txn.check_signature().is_ok());
+    }
 }
@@ -155,6 +155,8 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    /// Number of connection attempts before giving up. Retries forever if not set.
+    max_connection_attempts: Option<usize>,
 }
 
 impl NetworkClient {
@@ -164,9 +166,17 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            max_connection_attempts: None,
         }
     }
 
+    /// Gives up connecting to the server after the given number of attempts, instead of retrying
+    /// until the server becomes reachable.
+    pub fn with_max_connection_attempts(mut self, max_connection_attempts: usize) -> Self {
+        self.max_connection_attempts = Some(max_connection_attempts);
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(self.service, NetworkMode::Client, method, result)
     }
@@ -242,16 +252,24 @@ impl NetworkClient {
             let mut stream = TcpStream::connect_timeout(&self.server, timeout);
 
             let sleeptime = time::Duration::from_millis(100);
+            let mut attempts = 1;
             while let Err(err) = stream {
                 self.increment_counter(Method::Connect, MethodResult::Failure);
+                let err = err.into();
                 warn!(SecureNetLogSchema::new(
                     self.service,
                     NetworkMode::Client,
                     LogEvent::ConnectionFailed,
                 )
-                .error(&err.into())
+                .error(&err)
                 .remote_peer(&self.server));
 
+                if let Some(max_connection_attempts) = self.max_connection_attempts {
+                    if attempts >= max_connection_attempts {
+                        return Err(err);
+                    }
+                }
+                attempts += 1;
                 thread::sleep(sleeptime);
                 stream = TcpStream::connect_timeout(&self.server, timeout);
             }
@@ -564,6 +582,20 @@ mod test {
         assert_eq!(data, result);
     }
 
+    #[test]
+    fn test_client_max_connection_attempts() {
+        // Nothing listens on the port, so all the connection attempts fail
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut client =
+            NetworkClient::new("test", server_addr, TIMEOUT).with_max_connection_attempts(3);
+
+        assert!(matches!(
+            client.write(&[0, 1, 2, 3]),
+            Err(Error::NetworkError(_))
+        ));
+    }
+
     #[test]
     fn test_server_shutdown() {
         let server_port = utils::get_available_port();
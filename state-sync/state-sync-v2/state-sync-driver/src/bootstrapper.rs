@@ -485,7 +485,8 @@ impl<
                 .await
             },
             _ => {
-                // We're either transaction or output syncing
+                // We're either transaction or output syncing (after restoring
+                // from a backup, outputs are applied from the restored version)
                 self.fetch_missing_transaction_data(
                     highest_synced_version,
                     highest_known_ledger_info,
@@ -705,7 +706,8 @@ impl<
                 Error::UnexpectedError("No higher epoch ending version known!".into())
             })?;
         let data_stream = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 self.streaming_client
                     .get_all_transaction_outputs(
                         next_version,
@@ -1131,7 +1133,8 @@ impl<
 
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs = match bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     utils::apply_transaction_outputs(
                         self.storage_synchronizer.clone(),
@@ -1330,7 +1333,8 @@ impl<
     ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         // Calculate the payload end version
         let num_versions = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     transaction_outputs_with_proof
                         .transactions_and_outputs
@@ -105,12 +105,21 @@ impl StorageReader {
 
     /// Returns the state values range held in the database (lowest to highest).
     /// Note: it is currently assumed that if a node contains a transaction at a
-    /// version, V, the node also contains all state values at V.
+    /// version, V, the node also contains all state values at V (unless the
+    /// state is only partially replicated, in which case no states are served).
     fn fetch_state_values_range(
         &self,
         latest_version: Version,
         transactions_range: &Option<CompleteDataRange<Version>>,
     ) -> aptos_storage_service_types::Result<Option<CompleteDataRange<Version>>, Error> {
+        let partially_replicated = self
+            .storage
+            .is_state_partially_replicated()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        if partially_replicated {
+            return Ok(None);
+        }
+
         let pruner_enabled = self
             .storage
             .is_state_merkle_pruner_enabled()
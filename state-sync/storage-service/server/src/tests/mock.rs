@@ -342,6 +342,8 @@ mock! {
         fn get_epoch_snapshot_prune_window(&self) -> Result<usize>;
 
         fn is_state_merkle_pruner_enabled(&self) -> Result<bool>;
+
+        fn is_state_partially_replicated(&self) -> Result<bool>;
     }
 }
 
@@ -367,6 +369,9 @@ pub fn create_mock_db_for_optimistic_fetch(
         .expect_is_state_merkle_pruner_enabled()
         .returning(move || Ok(true));
     db_reader
+        .expect_is_state_partially_replicated()
+        .returning(move || Ok(false));
+    db_reader
 }
 
 /// Creates a mock database reader
@@ -44,6 +44,9 @@ async fn test_get_storage_server_summary() {
     db_reader
         .expect_is_state_merkle_pruner_enabled()
         .returning(move || Ok(true));
+    db_reader
+        .expect_is_state_partially_replicated()
+        .returning(move || Ok(false));
 
     // Create the storage client and server
     let (mut mock_client, service, mock_time, _) = MockClient::new(Some(db_reader), None);
@@ -104,6 +107,65 @@ async fn test_get_storage_server_summary() {
     );
 }
 
+#[tokio::test]
+async fn test_get_storage_server_summary_partially_replicated() {
+    // Create test data
+    let highest_version = 506;
+    let highest_epoch = 30;
+    let lowest_version = 101;
+    let highest_ledger_info =
+        utils::create_test_ledger_info_with_sigs(highest_epoch, highest_version);
+
+    // Create the mock db reader for a partially replicated state
+    let mut db_reader = mock::create_mock_db_reader();
+    let highest_ledger_info_clone = highest_ledger_info.clone();
+    db_reader
+        .expect_get_latest_ledger_info()
+        .times(1)
+        .returning(move || Ok(highest_ledger_info_clone.clone()));
+    db_reader
+        .expect_get_first_txn_version()
+        .times(1)
+        .returning(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_get_first_write_set_version()
+        .times(1)
+        .returning(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_is_state_partially_replicated()
+        .returning(move || Ok(true));
+
+    // Create the storage client and server
+    let (mut mock_client, service, mock_time, _) = MockClient::new(Some(db_reader), None);
+    tokio::spawn(service.start());
+
+    // Fetch the storage summary to ensure the server is running
+    get_storage_server_summary(&mut mock_client, true)
+        .await
+        .unwrap();
+
+    // Elapse enough time to force a cache update
+    utils::advance_storage_refresh_time(&mock_time).await;
+
+    // Fetch the storage summary again and verify no states are advertised
+    let response = get_storage_server_summary(&mut mock_client, true)
+        .await
+        .unwrap();
+    let expected_data_summary = DataSummary {
+        synced_ledger_info: Some(highest_ledger_info),
+        epoch_ending_ledger_infos: Some(CompleteDataRange::from_genesis(highest_epoch - 1)),
+        transactions: Some(CompleteDataRange::new(lowest_version, highest_version).unwrap()),
+        transaction_outputs: Some(CompleteDataRange::new(lowest_version, highest_version).unwrap()),
+        states: None,
+    };
+    match response.get_data_response().unwrap() {
+        DataResponse::StorageServerSummary(summary) => {
+            assert_eq!(summary.data_summary, expected_data_summary);
+        },
+        response => panic!("Expected a storage server summary but got: {:?}", response),
+    }
+}
+
 /// Sends a storage summary request and processes the response
 async fn get_storage_server_summary(
     mock_client: &mut MockClient,
@@ -14,6 +14,7 @@ use crate::{
 };
 use anyhow::{anyhow, ensure, Context, Result};
 use aptos_crypto::hash::HashValue;
+use aptos_storage_interface::DbReader;
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
@@ -133,6 +134,29 @@ impl BackupHandler {
         Ok(Box::new(iterator))
     }
 
+    /// Gets an iterator which yields every state item changed between `base_version` and
+    /// `version`, ordered by hashed key, with its value at `version` or `None` if it was deleted.
+    pub fn get_state_delta_iter(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, Option<StateValue>)>> + Send + Sync>>
+    {
+        let state_store = Arc::clone(&self.state_store);
+        let iterator = self
+            .state_store
+            .get_state_keys_changed_between(base_version, version)?
+            .enumerate()
+            .map(move |(idx, res)| {
+                BACKUP_STATE_SNAPSHOT_VERSION.set(version as i64);
+                BACKUP_STATE_SNAPSHOT_LEAF_IDX.set(idx as i64);
+                let key = res?;
+                let value = state_store.get_state_value_by_version(&key, version)?;
+                Ok((key, value))
+            });
+        Ok(Box::new(iterator))
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_account_state_range_proof(
         &self,
@@ -68,6 +68,16 @@ impl RestoreHandler {
         )
     }
 
+    pub fn apply_state_delta(
+        &self,
+        base_version: Version,
+        version: Version,
+        changes: &[(StateKey, Option<StateValue>)],
+    ) -> Result<HashValue> {
+        self.state_store
+            .apply_state_delta(base_version, version, changes)
+    }
+
     pub fn reset_state_store(&self) {
         self.state_store.reset();
     }
@@ -838,6 +838,10 @@ impl DbReader for FakeAptosDB {
         self.inner.get_epoch_snapshot_prune_window()
     }
 
+    fn is_state_partially_replicated(&self) -> Result<bool> {
+        self.inner.is_state_partially_replicated()
+    }
+
     fn is_ledger_pruner_enabled(&self) -> Result<bool> {
         self.inner.is_ledger_pruner_enabled()
     }
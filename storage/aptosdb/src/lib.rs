@@ -100,6 +100,7 @@ use aptos_types::{
     state_store::{
         create_empty_sharded_state_updates,
         state_key::StateKey,
+        state_key_filter::StateKeyFilter,
         state_key_prefix::StateKeyPrefix,
         state_storage_usage::StateStorageUsage,
         state_value::{StateValue, StateValueChunkWithProof},
@@ -301,6 +302,7 @@ impl AptosDB {
         buffered_state_target_items: usize,
         hack_for_tests: bool,
         empty_buffered_state_for_restore: bool,
+        replicated_state_keys: Option<StateKeyFilter>,
     ) -> Self {
         let ledger_db = Arc::new(ledger_db);
         let state_merkle_db = Arc::new(state_merkle_db);
@@ -325,6 +327,7 @@ impl AptosDB {
             buffered_state_target_items,
             hack_for_tests,
             empty_buffered_state_for_restore,
+            replicated_state_keys,
         ));
 
         let ledger_pruner =
@@ -358,6 +361,7 @@ impl AptosDB {
         buffered_state_target_items: usize,
         max_num_nodes_per_lru_cache_shard: usize,
         empty_buffered_state_for_restore: bool,
+        replicated_state_keys: Option<StateKeyFilter>,
     ) -> Result<Self> {
         ensure!(
             pruner_config.eq(&NO_OP_STORAGE_PRUNER_CONFIG) || !readonly,
@@ -379,6 +383,7 @@ impl AptosDB {
             buffered_state_target_items,
             readonly,
             empty_buffered_state_for_restore,
+            replicated_state_keys,
         );
 
         if !readonly && enable_indexer {
@@ -406,6 +411,7 @@ impl AptosDB {
             buffered_state_target_items,
             max_num_nodes_per_lru_cache_shard,
             false,
+            None,
         )
     }
 
@@ -427,6 +433,32 @@ impl AptosDB {
             buffered_state_target_items,
             max_num_nodes_per_lru_cache_shard,
             true,
+            None,
+        )
+    }
+
+    /// Opens the DB of a partially replicated node, which only persists the values of the
+    /// `replicated_state_keys`.
+    pub fn open_partially_replicated<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        readonly: bool,
+        pruner_config: PrunerConfig,
+        rocksdb_configs: RocksdbConfigs,
+        enable_indexer: bool,
+        buffered_state_target_items: usize,
+        max_num_nodes_per_lru_cache_shard: usize,
+        replicated_state_keys: StateKeyFilter,
+    ) -> Result<Self> {
+        Self::open_internal(
+            db_root_path,
+            readonly,
+            pruner_config,
+            rocksdb_configs,
+            enable_indexer,
+            buffered_state_target_items,
+            max_num_nodes_per_lru_cache_shard,
+            false,
+            Some(replicated_state_keys),
         )
     }
 
@@ -1695,6 +1727,12 @@ impl DbReader for AptosDB {
     ) -> Result<Option<StateValue>> {
         gauged_api("get_state_value_by_version", || {
             self.error_if_state_kv_pruned("StateValue", version)?;
+            // A value that isn't replicated must not be read as missing, e.g., by the VM.
+            ensure!(
+                self.state_store.is_replicated(state_store_key),
+                "StateValue of {:?} is not replicated by this node.",
+                state_store_key
+            );
 
             self.state_store
                 .get_state_value_by_version(state_store_key, version)
@@ -1950,6 +1988,12 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn is_state_partially_replicated(&self) -> Result<bool> {
+        gauged_api("is_state_partially_replicated", || {
+            Ok(self.state_store.replicated_state_keys.is_some())
+        })
+    }
+
     fn is_ledger_pruner_enabled(&self) -> Result<bool> {
         gauged_api("is_ledger_pruner_enabled", || {
             Ok(self.ledger_pruner.is_pruner_enabled())
@@ -116,6 +116,27 @@ impl StateKvDb {
         self.write_progress(version)
     }
 
+    /// Writes the batches to the shards they belong to, without touching the commit progress,
+    /// for restores which track their own progress.
+    pub(crate) fn commit_raw_sharded_batches(
+        &self,
+        state_kv_metadata_batch: SchemaBatch,
+        sharded_state_kv_batches: [SchemaBatch; NUM_STATE_SHARDS],
+    ) -> Result<()> {
+        COMMIT_POOL.scope(|s| {
+            for (shard_id, state_kv_batch) in sharded_state_kv_batches.into_iter().enumerate() {
+                s.spawn(move |_| {
+                    self.state_kv_db_shards[shard_id]
+                        .write_schemas(state_kv_batch)
+                        .unwrap_or_else(|_| panic!("Failed to commit shard {shard_id}."));
+                });
+            }
+        });
+
+        self.state_kv_metadata_db
+            .write_schemas(state_kv_metadata_batch)
+    }
+
     pub(crate) fn commit_raw_batch(&self, state_kv_batch: SchemaBatch) -> Result<()> {
         // TODO(grao): Support sharding here.
         self.state_kv_metadata_db.write_schemas(state_kv_batch)
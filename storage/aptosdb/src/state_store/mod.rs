@@ -9,6 +9,8 @@ use crate::{
     epoch_by_version::EpochByVersionSchema,
     ledger_db::LedgerDb,
     metrics::{STATE_ITEMS, TOTAL_STATE_BYTES},
+    new_sharded_kv_schema_batch,
+    pruner::pruner_manager::PrunerManager,
     schema::{state_value::StateValueSchema, state_value_index::StateValueIndexSchema},
     stale_state_value_index::StaleStateValueIndexSchema,
     state_kv_db::StateKvDb,
@@ -33,13 +35,16 @@ use aptos_crypto::{
 };
 use aptos_executor_types::in_memory_state_calculator::InMemoryStateCalculator;
 use aptos_infallible::Mutex;
-use aptos_jellyfish_merkle::iterator::JellyfishMerkleIterator;
+use aptos_jellyfish_merkle::{
+    diff_iterator::JellyfishMerkleDiffIterator, iterator::JellyfishMerkleIterator,
+};
 use aptos_logger::info;
 use aptos_schemadb::{ReadOptions, SchemaBatch};
 use aptos_state_view::StateViewId;
 use aptos_storage_interface::{
     async_proof_fetcher::AsyncProofFetcher,
     cached_state_view::{CachedStateView, ShardedStateCache},
+    jmt_update_refs, jmt_updates,
     state_delta::StateDelta,
     DbReader, StateSnapshotReceiver,
 };
@@ -48,6 +53,7 @@ use aptos_types::{
     state_store::{
         create_empty_sharded_state_updates,
         state_key::StateKey,
+        state_key_filter::StateKeyFilter,
         state_key_prefix::StateKeyPrefix,
         state_storage_usage::StateStorageUsage,
         state_value::{StaleStateValueIndex, StateValue, StateValueChunkWithProof},
@@ -61,9 +67,11 @@ use claims::{assert_ge, assert_le};
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
-#[cfg(test)]
-use std::collections::HashMap;
-use std::{collections::HashSet, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+};
 
 pub(crate) mod buffered_state;
 mod state_merkle_batch_committer;
@@ -96,6 +104,9 @@ pub(crate) struct StateDb {
     pub state_merkle_pruner: StateMerklePrunerManager<StaleNodeIndexSchema>,
     pub epoch_snapshot_pruner: StateMerklePrunerManager<StaleNodeIndexCrossEpochSchema>,
     pub state_kv_pruner: StateKvPrunerManager,
+    /// The state keys whose values are persisted, if the state is only partially replicated.
+    /// The state Merkle tree is always maintained in full.
+    pub replicated_state_keys: Option<StateKeyFilter>,
 }
 
 pub(crate) struct StateStore {
@@ -188,8 +199,11 @@ impl DbReader for StateDb {
             .get_with_proof_ext(state_key, version)?;
         Ok((
             match leaf_data {
-                Some((_, (key, version))) => Some(self.expect_value_by_version(&key, version)?),
-                None => None,
+                Some((_, (key, version))) if self.is_replicated(&key) => {
+                    Some(self.expect_value_by_version(&key, version)?)
+                },
+                // The values of the keys that aren't replicated are unknown
+                Some(_) | None => None,
             },
             proof,
         ))
@@ -208,6 +222,13 @@ impl DbReader for StateDb {
 }
 
 impl StateDb {
+    /// Returns true iff the value of the given key is persisted.
+    pub fn is_replicated(&self, state_key: &StateKey) -> bool {
+        self.replicated_state_keys
+            .as_ref()
+            .map_or(true, |filter| filter.matches(state_key))
+    }
+
     /// Get the latest ended epoch strictly before required version, i.e. if the passed in version
     /// ends an epoch, return one epoch early than that.
     pub fn get_previous_epoch_ending(&self, version: Version) -> Result<Option<(u64, Version)>> {
@@ -308,6 +329,7 @@ impl StateStore {
         buffered_state_target_items: usize,
         hack_for_tests: bool,
         empty_buffered_state_for_restore: bool,
+        replicated_state_keys: Option<StateKeyFilter>,
     ) -> Self {
         Self::sync_commit_progress(
             Arc::clone(&ledger_db),
@@ -321,6 +343,7 @@ impl StateStore {
             state_merkle_pruner,
             epoch_snapshot_pruner,
             state_kv_pruner,
+            replicated_state_keys,
         });
         if empty_buffered_state_for_restore {
             let buffered_state = Mutex::new(BufferedState::new(
@@ -450,6 +473,7 @@ impl StateStore {
             state_merkle_pruner,
             epoch_snapshot_pruner,
             state_kv_pruner,
+            replicated_state_keys: None,
         });
         let buffered_state = Self::create_buffered_state_from_latest_snapshot(
             &state_db, 0, /*hack_for_tests=*/ false,
@@ -716,9 +740,11 @@ impl StateStore {
                     .flat_map_iter(|(i, shards)| {
                         let version = first_version + i as Version;
                         let kvs = &shards[shard_id];
-                        kvs.iter().map(move |(k, v)| {
-                            batch.put::<StateValueSchema>(&(k.clone(), version), v)
-                        })
+                        kvs.iter()
+                            .filter(|(k, _)| self.is_replicated(k))
+                            .map(move |(k, v)| {
+                                batch.put::<StateValueSchema>(&(k.clone(), version), v)
+                            })
                     })
                     .collect::<Result<_>>()
             })?;
@@ -732,10 +758,14 @@ impl StateStore {
                 .enumerate()
                 .try_for_each(|(i, updates)| {
                     let version = first_version + i as Version;
-                    updates.iter().flatten().try_for_each(|(k, _)| {
-                        state_kv_metadata_batch
-                            .put::<StateValueIndexSchema>(&(k.clone(), version), &())
-                    })
+                    updates
+                        .iter()
+                        .flatten()
+                        .filter(|(k, _)| self.is_replicated(k))
+                        .try_for_each(|(k, _)| {
+                            state_kv_metadata_batch
+                                .put::<StateValueIndexSchema>(&(k.clone(), version), &())
+                        })
                 })?;
         }
 
@@ -794,6 +824,7 @@ impl StateStore {
                     .iter()
                     .flat_map(|sharded_states| sharded_states.iter().flatten())
                     .map(|(key, _)| key)
+                    .filter(|key| self.is_replicated(key))
                     .collect::<HashSet<_>>();
                 IO_POOL.scope(|s| {
                     for key in key_set {
@@ -836,7 +867,12 @@ impl StateStore {
                     let mut items_delta = 0;
                     let mut bytes_delta = 0;
 
-                    for (key, value) in kvs[shard_id].iter() {
+                    // The values of the keys that aren't replicated are unknown, so only the
+                    // replicated state is accounted for.
+                    for (key, value) in kvs[shard_id]
+                        .iter()
+                        .filter(|(key, _)| self.is_replicated(key))
+                    {
                         if let Some(value) = value {
                             items_delta += 1;
                             bytes_delta += (key.size() + value.size()) as i64;
@@ -906,7 +942,9 @@ impl StateStore {
                 .unwrap();
         }
 
-        if !expected_usage.is_untracked() {
+        // The executor doesn't tell the replicated state values apart, so its usage can only be
+        // checked if the state is fully replicated.
+        if !expected_usage.is_untracked() && self.replicated_state_keys.is_none() {
             ensure!(
                 expected_usage == usage,
                 "Calculated state db usage at version {} not expected. expected: {:?}, calculated: {:?}, base version: {:?}, base version usage: {:?}",
@@ -979,6 +1017,10 @@ impl StateStore {
         first_index: usize,
         chunk_size: usize,
     ) -> Result<StateValueChunkWithProof> {
+        ensure!(
+            self.replicated_state_keys.is_none(),
+            "State value chunks can't be served, the state is only partially replicated."
+        );
         let result_iter = JellyfishMerkleIterator::new_by_index(
             Arc::clone(&self.state_merkle_db),
             version,
@@ -1014,6 +1056,126 @@ impl StateStore {
         })
     }
 
+    /// Returns the keys of all state items created, updated or deleted between the state
+    /// snapshots at `base_version` and `version`, ordered by hashed key.
+    ///
+    /// The keys are found lazily by comparing the two state merkle trees, skipping the subtrees
+    /// that didn't change, so both snapshots must exist and not be pruned yet.
+    pub fn get_state_keys_changed_between(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<impl Iterator<Item = Result<StateKey>> + Send + Sync> {
+        ensure!(
+            base_version < version,
+            "Base version {} must be smaller than version {}.",
+            base_version,
+            version,
+        );
+        let min_readable_merkle_version = self.state_merkle_pruner.get_min_readable_version();
+        ensure!(
+            base_version >= min_readable_merkle_version,
+            "State merkle nodes at version {} are pruned, min available version is {}.",
+            base_version,
+            min_readable_merkle_version,
+        );
+        let min_readable_kv_version = self.state_kv_pruner.get_min_readable_version();
+        ensure!(
+            version >= min_readable_kv_version,
+            "State values at version {} are pruned, min available version is {}.",
+            version,
+            min_readable_kv_version,
+        );
+
+        Ok(JellyfishMerkleDiffIterator::new(
+            Arc::clone(&self.state_merkle_db),
+            base_version,
+            version,
+        )?
+        .map(|res| res.map(|(_hashed_key, key)| key)))
+    }
+
+    /// Applies the changes between the state snapshot at `base_version` and the one at `version`
+    /// on top of the former, which must already exist in the DB, and returns the new root hash.
+    pub fn apply_state_delta(
+        &self,
+        base_version: Version,
+        version: Version,
+        changes: &[(StateKey, Option<StateValue>)],
+    ) -> Result<HashValue> {
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["apply_state_delta"])
+            .start_timer();
+        ensure!(
+            base_version < version,
+            "Base version {} must be smaller than version {}.",
+            base_version,
+            version,
+        );
+
+        let usage = self.get_usage(Some(base_version))?;
+        let mut items_delta = 0i64;
+        let mut bytes_delta = 0i64;
+        let sharded_state_kv_batches = new_sharded_kv_schema_batch();
+        for (key, value) in changes {
+            let batch = &sharded_state_kv_batches[key.get_shard_id() as usize];
+            if let Some((old_version, old_value)) = self
+                .state_db
+                .get_state_value_with_version_by_version(key, base_version)?
+            {
+                items_delta -= 1;
+                bytes_delta -= (key.size() + old_value.size()) as i64;
+                batch.put::<StaleStateValueIndexSchema>(
+                    &StaleStateValueIndex {
+                        stale_since_version: version,
+                        version: old_version,
+                        state_key: key.clone(),
+                    },
+                    &(),
+                )?;
+            }
+            if let Some(value) = value {
+                items_delta += 1;
+                bytes_delta += (key.size() + value.size()) as i64;
+            } else {
+                batch.put::<StaleStateValueIndexSchema>(
+                    &StaleStateValueIndex {
+                        stale_since_version: version,
+                        version,
+                        state_key: key.clone(),
+                    },
+                    &(),
+                )?;
+            }
+            batch.put::<StateValueSchema>(&(key.clone(), version), value)?;
+        }
+        let usage = StateStorageUsage::new(
+            (usage.items() as i64 + items_delta) as usize,
+            (usage.bytes() as i64 + bytes_delta) as usize,
+        );
+
+        let updates = changes
+            .iter()
+            .map(|(key, value)| (key, value.as_ref()))
+            .collect::<HashMap<_, _>>();
+        let (top_levels_batch, sharded_batch, root_hash) =
+            self.state_merkle_db.merklize_value_set(
+                jmt_update_refs(&jmt_updates(&updates)),
+                None,
+                version,
+                Some(base_version),
+                None, // previous epoch ending version
+            )?;
+
+        self.state_kv_db
+            .commit_raw_sharded_batches(SchemaBatch::new(), sharded_state_kv_batches)?;
+        self.state_merkle_db
+            .commit(version, top_levels_batch, sharded_batch)?;
+        self.write_usage(version, usage)?;
+
+        Ok(root_hash)
+    }
+
     // state sync doesn't query for the progress, but keeps its record by itself.
     // TODO: change to async comment once it does like https://github.com/aptos-labs/aptos-core/blob/159b00f3d53e4327523052c1b99dd9889bf13b03/storage/backup/backup-cli/src/backup_types/state_snapshot/restore.rs#L147 or overlap at least two chunks.
     pub fn get_snapshot_receiver(
@@ -1098,14 +1260,25 @@ impl StateValueWriter<StateKey, StateValue> for StateStore {
         &self,
         version: Version,
         node_batch: &StateValueBatch,
-        progress: StateSnapshotProgress,
+        mut progress: StateSnapshotProgress,
     ) -> Result<()> {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["state_value_writer_write_chunk"])
             .start_timer();
+        // Only the replicated state is accounted for, as in `put_stats_and_indices`. The progress
+        // is cumulative, and the values left out of earlier chunks were already subtracted.
+        for ((k, _), v) in node_batch
+            .iter()
+            .filter(|((k, _), _)| !self.is_replicated(k))
+        {
+            if let Some(v) = v {
+                progress.usage.remove_item(k.size() + v.size());
+            }
+        }
         let batch = SchemaBatch::new();
         node_batch
             .par_iter()
+            .filter(|((k, _), _)| self.is_replicated(k))
             .map(|(k, v)| batch.put::<StateValueSchema>(k, v))
             .collect::<Result<Vec<_>>>()?;
         batch.put::<DbMetadataSchema>(
@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of a state snapshot delta manifest, representing changed state items in the key range
+/// [`first_key`, `last_key`] (right side inclusive).
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDeltaChunk {
+    /// index of the first changed item in this chunk over all changed items.
+    pub first_idx: usize,
+    /// index of the last changed item in this chunk over all changed items.
+    pub last_idx: usize,
+    /// key of the first changed item in this chunk.
+    pub first_key: HashValue,
+    /// key of the last changed item in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, with `None` meaning the item is deleted.
+    pub blobs: FileHandle,
+}
+
+/// State snapshot delta backup manifest, representing the changes that turn the state at
+/// `base_version` into the state at `version`.
+///
+/// There are no range proofs for the chunks since the changed items don't form a continuous key
+/// range; instead the root hash after applying all chunks on top of the base state is checked.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDeltaBackup {
+    /// Version of the state snapshot (or delta) these changes are based on.
+    pub base_version: Version,
+    /// Version at which this state snapshot delta is taken.
+    pub version: Version,
+    /// Epoch in which this state snapshot delta is taken.
+    pub epoch: u64,
+    /// Hash of the state tree root at `version`.
+    pub root_hash: HashValue,
+    /// All changed items in chunks.
+    pub chunks: Vec<StateSnapshotDeltaChunk>,
+    /// BCS serialized
+    /// `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, the same as
+    /// `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
@@ -0,0 +1,13 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Incremental state snapshots: instead of the full state, only the state items changed since a
+//! parent state snapshot (or delta) are backed up. A full state snapshot can be rebuilt by
+//! restoring the base state snapshot and applying a chain of deltas on top of it.
+
+pub mod backup;
+pub mod manifest;
+pub mod restore;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,208 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistory,
+        state_snapshot_delta::manifest::{StateSnapshotDeltaBackup, StateSnapshotDeltaChunk},
+    },
+    metrics::OTHER_TIMERS_SECONDS,
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
+        GlobalRestoreOptions, RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use clap::Parser;
+use futures::{stream, TryStreamExt};
+use std::sync::Arc;
+use tokio::time::Instant;
+
+#[derive(Parser)]
+pub struct StateSnapshotDeltaRestoreOpt {
+    #[clap(long = "state-delta-manifest")]
+    pub manifest_handle: FileHandle,
+}
+
+/// Applies a single state snapshot delta on top of the state snapshot at its base version, which
+/// must be the latest state snapshot in the DB before the delta's version.
+///
+/// All changes in a delta are applied as a single batch, so unlike a full state snapshot restore,
+/// an interrupted delta restore starts over from the beginning of the delta.
+pub struct StateSnapshotDeltaRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    manifest_handle: FileHandle,
+    /// Global "target_version" for the entire restore process, if the delta is newer than this,
+    /// nothing will be done, otherwise, this has no effect.
+    target_version: Version,
+    epoch_history: Option<Arc<EpochHistory>>,
+    concurrent_downloads: usize,
+}
+
+impl StateSnapshotDeltaRestoreController {
+    pub fn new(
+        opt: StateSnapshotDeltaRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode: global_opt.run_mode,
+            manifest_handle: opt.manifest_handle,
+            target_version: global_opt.target_version,
+            epoch_history,
+            concurrent_downloads: global_opt.concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        let start = Instant::now();
+        info!("{} started. Manifest: {}", name, self.manifest_handle);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!(time = start.elapsed().as_secs(), "{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl StateSnapshotDeltaRestoreController {
+    fn name(&self) -> String {
+        format!("state snapshot delta {}", self.run_mode.name())
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let manifest: StateSnapshotDeltaBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        if manifest.version > self.target_version {
+            warn!(
+                "Trying to restore state snapshot delta to version {}, which is newer than the target version {}, skipping.",
+                manifest.version,
+                self.target_version,
+            );
+            return Ok(());
+        }
+        ensure!(
+            manifest.base_version < manifest.version,
+            "Bad state snapshot delta, base version {} is not older than version {}.",
+            manifest.base_version,
+            manifest.version,
+        );
+
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            state_root_hash,
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+
+        if !self.run_mode.is_verify() {
+            let latest_snapshot = self.run_mode.get_state_snapshot_before(manifest.version);
+            ensure!(
+                latest_snapshot.map(|(version, _)| version) == Some(manifest.base_version),
+                "State snapshot delta is based on version {}, but the latest state snapshot in the DB before version {} is {:?}.",
+                manifest.base_version,
+                manifest.version,
+                latest_snapshot,
+            );
+        }
+
+        let storage = self.storage.clone();
+        let futs_iter =
+            manifest.chunks.into_iter().map(|chunk| {
+                let storage = storage.clone();
+                async move {
+                    tokio::spawn(async move { Self::read_chunk(&storage, &chunk).await }).await?
+                }
+            });
+        let con = self.concurrent_downloads;
+        let chunks = stream::iter(futs_iter)
+            .buffered_x(con * 2, con)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let changes = chunks.into_iter().flatten().collect::<Vec<_>>();
+        ensure!(
+            changes
+                .windows(2)
+                .all(|pair| pair[0].0.hash() < pair[1].0.hash()),
+            "State snapshot delta is not strictly ordered by key.",
+        );
+
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["apply_state_delta"])
+            .start_timer();
+        let num_changes = changes.len();
+        let run_mode = self.run_mode.clone();
+        let (base_version, version) = (manifest.base_version, manifest.version);
+        let root_hash = tokio::task::spawn_blocking(move || {
+            run_mode.apply_state_delta(base_version, version, &changes)
+        })
+        .await??;
+        if let Some(root_hash) = root_hash {
+            ensure!(
+                root_hash == manifest.root_hash,
+                "Root hash mismatch after applying state snapshot delta. root hash: {}, expected: {}",
+                root_hash,
+                manifest.root_hash,
+            );
+        }
+        info!(
+            base_version = base_version,
+            version = version,
+            num_changes = num_changes,
+            "State snapshot delta applied.",
+        );
+
+        self.run_mode.finish();
+        Ok(())
+    }
+
+    async fn read_chunk(
+        storage: &Arc<dyn BackupStorage>,
+        chunk: &StateSnapshotDeltaChunk,
+    ) -> Result<Vec<(StateKey, Option<StateValue>)>> {
+        let mut file = storage.open_for_read(&chunk.blobs).await?;
+
+        let mut changes: Vec<(StateKey, Option<StateValue>)> = vec![];
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            changes.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        ensure!(
+            changes.len() == chunk.last_idx + 1 - chunk.first_idx,
+            "State snapshot delta chunk {} has {} items, expected {}.",
+            chunk.blobs,
+            changes.len(),
+            chunk.last_idx + 1 - chunk.first_idx,
+        );
+        ensure!(
+            changes.first().map(|(key, _)| key.hash()) == Some(chunk.first_key)
+                && changes.last().map(|(key, _)| key.hash()) == Some(chunk.last_key),
+            "State snapshot delta chunk {} doesn't match the key range in the manifest.",
+            chunk.blobs,
+        );
+
+        Ok(changes)
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
+        state_snapshot_delta::{
+            backup::{StateSnapshotDeltaBackupController, StateSnapshotDeltaBackupOpt},
+            restore::{StateSnapshotDeltaRestoreController, StateSnapshotDeltaRestoreOpt},
+        },
+    },
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
+        ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
+use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use std::{convert::TryInto, sync::Arc};
+use tokio::time::Duration;
+
+fn restore_options(db_dir: &TempPath) -> GlobalRestoreOptions {
+    GlobalRestoreOpt {
+        dry_run: false,
+        db_dir: Some(db_dir.path().to_path_buf()),
+        target_version: None, // max
+        trusted_waypoints: TrustedWaypointOpt::default(),
+        rocksdb_opt: RocksdbOpt::default(),
+        concurrent_downloads: ConcurrentDownloadsOpt::default(),
+        replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+    }
+    .try_into()
+    .unwrap()
+}
+
+#[test]
+fn end_to_end() {
+    let src_db = test_execution_with_storage_impl();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_epoch = src_db.get_latest_epoch_state().unwrap().epoch;
+    let epoch_ending_lis = src_db
+        .get_epoch_ending_ledger_infos(0, latest_epoch)
+        .unwrap()
+        .ledger_info_with_sigs;
+    assert!(epoch_ending_lis.len() >= 3);
+    let versions = epoch_ending_lis
+        .iter()
+        .map(|li| li.ledger_info().version())
+        .collect::<Vec<_>>();
+    let last_epoch = (epoch_ending_lis.len() - 1) as u64;
+    let last_version = *versions.last().unwrap();
+    let state_root_hash = src_db
+        .get_transactions(last_version, 1, last_version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+
+    // Full snapshot at the end of epoch 0, then a chain of deltas through the last epoch.
+    let snapshot_manifest = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch: 0 },
+                global_backup_opt.clone(),
+                client.clone(),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let delta_manifests = [(0, 1), (1, last_epoch)]
+        .into_iter()
+        .map(|(base_epoch, epoch)| {
+            rt.block_on(
+                StateSnapshotDeltaBackupController::new(
+                    StateSnapshotDeltaBackupOpt {
+                        epoch,
+                        base_version: versions[base_epoch as usize],
+                    },
+                    global_backup_opt.clone(),
+                    client.clone(),
+                    Arc::clone(&store),
+                )
+                .run(),
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle: snapshot_manifest,
+                version: versions[0],
+                validate_modules: false,
+                restore_mode: StateSnapshotRestoreMode::Default,
+            },
+            restore_options(&tgt_db_dir),
+            Arc::clone(&store),
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    // Deltas can't be applied out of order.
+    assert!(rt
+        .block_on(
+            StateSnapshotDeltaRestoreController::new(
+                StateSnapshotDeltaRestoreOpt {
+                    manifest_handle: delta_manifests[1].clone(),
+                },
+                restore_options(&tgt_db_dir),
+                Arc::clone(&store),
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .is_err());
+
+    for manifest_handle in delta_manifests {
+        rt.block_on(
+            StateSnapshotDeltaRestoreController::new(
+                StateSnapshotDeltaRestoreOpt { manifest_handle },
+                restore_options(&tgt_db_dir),
+                Arc::clone(&store),
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .unwrap();
+    }
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(last_version + 1)
+            .unwrap()
+            .unwrap(),
+        (last_version, state_root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
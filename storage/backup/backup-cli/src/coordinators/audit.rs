@@ -0,0 +1,590 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::{
+            manifest::EpochEndingBackup,
+            restore::{EpochHistory, EpochHistoryRestoreController},
+        },
+        state_snapshot::manifest::StateSnapshotBackup,
+        state_snapshot_delta::manifest::StateSnapshotDeltaBackup,
+        transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{cache::MetadataCacheOpt, view::MetadataView},
+    storage::{BackupStorage, FileHandle, FileHandleRef},
+    utils::{
+        storage_ext::BackupStorageExt, stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
+        TrustedWaypointOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, proof::TransactionInfoWithProof, transaction::Version,
+};
+use clap::Parser;
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+#[derive(Clone, Copy, Parser)]
+pub struct RestoreThroughputOpt {
+    #[clap(
+        long,
+        default_value = "2000",
+        help = "Assumed number of epoch ending ledger infos restored per second, used to \
+        estimate the restore time."
+    )]
+    pub epoch_endings_per_sec: u64,
+    #[clap(
+        long,
+        default_value = "50000",
+        help = "Assumed number of state values restored per second, used to estimate the \
+        restore time."
+    )]
+    pub state_values_per_sec: u64,
+    #[clap(
+        long,
+        default_value = "1000",
+        help = "Assumed number of transactions replayed per second, used to estimate the \
+        restore time."
+    )]
+    pub transactions_replayed_per_sec: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionRange {
+    pub first_version: Version,
+    pub last_version: Version,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EpochRange {
+    pub first_epoch: u64,
+    pub last_epoch: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadableManifest {
+    pub manifest: FileHandle,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreachableStateSnapshot {
+    pub epoch: u64,
+    pub version: Version,
+    /// Set if this is a state snapshot delta.
+    pub base_version: Option<Version>,
+    pub manifest: FileHandle,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreEstimate {
+    pub target_version: Version,
+    /// The state snapshot a default restore starts with, if any.
+    pub state_snapshot_version: Option<Version>,
+    pub num_epoch_endings: u64,
+    pub num_state_values: u64,
+    pub num_transactions_to_replay: u64,
+    pub estimated_secs: u64,
+    /// Reasons the restore can't reach the target version with the backups in the storage.
+    pub blockers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditReport {
+    pub transaction_version_gaps: Vec<VersionRange>,
+    pub missing_epoch_endings: Vec<EpochRange>,
+    /// Number of epochs, starting from epoch 0, whose ending ledger infos are verified.
+    pub num_verified_epochs: u64,
+    pub epoch_history_error: Option<String>,
+    pub unreachable_state_snapshots: Vec<UnreachableStateSnapshot>,
+    pub unreadable_manifests: Vec<UnreadableManifest>,
+    /// Files in the storage not referred to by any manifest. `None` if the storage doesn't
+    /// support listing files.
+    pub orphaned_files: Option<Vec<FileHandle>>,
+    /// Files referred to by manifests but not found in the storage. `None` if the storage doesn't
+    /// support listing files.
+    pub missing_files: Option<Vec<FileHandle>>,
+    /// Chunks not referred to by any file, relative to the storage root. `None` if the storage
+    /// doesn't keep file content in chunks.
+    pub unreferenced_chunks: Option<Vec<String>>,
+    /// `None` if there's no transaction backup to restore to.
+    pub restore_estimate: Option<RestoreEstimate>,
+}
+
+#[derive(Clone, Copy)]
+enum ManifestKind {
+    EpochEnding,
+    StateSnapshot,
+    StateSnapshotDelta,
+    Transaction,
+}
+
+enum Manifest {
+    EpochEnding(EpochEndingBackup),
+    StateSnapshot(StateSnapshotBackup),
+    StateSnapshotDelta(StateSnapshotDeltaBackup),
+    Transaction(TransactionBackup),
+}
+
+impl Manifest {
+    fn referenced_files(&self) -> Vec<FileHandle> {
+        match self {
+            Manifest::EpochEnding(m) => m.chunks.iter().map(|c| c.ledger_infos.clone()).collect(),
+            Manifest::StateSnapshot(m) => m
+                .chunks
+                .iter()
+                .flat_map(|c| [c.blobs.clone(), c.proof.clone()])
+                .chain(std::iter::once(m.proof.clone()))
+                .collect(),
+            Manifest::StateSnapshotDelta(m) => m
+                .chunks
+                .iter()
+                .map(|c| c.blobs.clone())
+                .chain(std::iter::once(m.proof.clone()))
+                .collect(),
+            Manifest::Transaction(m) => m
+                .chunks
+                .iter()
+                .flat_map(|c| [c.transactions.clone(), c.proof.clone()])
+                .collect(),
+        }
+    }
+}
+
+/// Walks all metadata in a backup storage and reports problems that would make a restore fail
+/// or be incomplete, without touching any DB.
+pub struct AuditCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    concurrent_downloads: usize,
+    target_version: Option<Version>,
+    throughput: RestoreThroughputOpt,
+}
+
+impl AuditCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        trusted_waypoints_opt: TrustedWaypointOpt,
+        concurrent_downloads: usize,
+        target_version: Option<Version>,
+        throughput: RestoreThroughputOpt,
+    ) -> Result<Self> {
+        ensure!(
+            throughput.epoch_endings_per_sec > 0
+                && throughput.state_values_per_sec > 0
+                && throughput.transactions_replayed_per_sec > 0,
+            "Restore throughput must be positive.",
+        );
+        Ok(Self {
+            storage,
+            metadata_cache_opt,
+            trusted_waypoints_opt,
+            concurrent_downloads,
+            target_version,
+            throughput,
+        })
+    }
+
+    pub async fn run(self) -> Result<AuditReport> {
+        info!("Audit coordinator started.");
+        let ret = self.run_impl().await;
+        if let Err(e) = &ret {
+            error!(error = ?e, "Audit coordinator failed.");
+        } else {
+            info!("Audit coordinator exiting with success.");
+        }
+        ret
+    }
+
+    async fn run_impl(self) -> Result<AuditReport> {
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let (manifests, unreadable_manifests) = self.load_manifests(&metadata_view).await;
+        let (num_verified_epochs, epoch_history, epoch_history_error) =
+            match self.verify_epoch_history(&metadata_view).await {
+                Ok(history) => (history.epoch_endings.len() as u64, Some(history), None),
+                Err(e) => (0, None, Some(e.to_string())),
+            };
+        let unreachable_state_snapshots = self
+            .find_unreachable_state_snapshots(&metadata_view, &manifests, epoch_history.as_ref())
+            .await;
+        let (orphaned_files, missing_files) = self.find_orphaned_files(&manifests).await?;
+        let unreferenced_chunks = self.storage.list_unreferenced_chunks().await?;
+        let restore_estimate =
+            self.estimate_restore(&metadata_view, &manifests, &unreachable_state_snapshots)?;
+
+        let report = AuditReport {
+            transaction_version_gaps: metadata_view
+                .transaction_backup_gaps()
+                .into_iter()
+                .map(|(first_version, last_version)| VersionRange {
+                    first_version,
+                    last_version,
+                })
+                .collect(),
+            missing_epoch_endings: metadata_view
+                .missing_epoch_ending_epochs()
+                .into_iter()
+                .map(|(first_epoch, last_epoch)| EpochRange {
+                    first_epoch,
+                    last_epoch,
+                })
+                .collect(),
+            num_verified_epochs,
+            epoch_history_error,
+            unreachable_state_snapshots,
+            unreadable_manifests,
+            orphaned_files,
+            missing_files,
+            unreferenced_chunks,
+            restore_estimate,
+        };
+        info!(
+            transaction_version_gaps = report.transaction_version_gaps.len(),
+            missing_epoch_endings = report.missing_epoch_endings.len(),
+            unreachable_state_snapshots = report.unreachable_state_snapshots.len(),
+            unreadable_manifests = report.unreadable_manifests.len(),
+            orphaned_files = ?report.orphaned_files.as_ref().map(Vec::len),
+            unreferenced_chunks = ?report.unreferenced_chunks.as_ref().map(Vec::len),
+            "Audit finished."
+        );
+        Ok(report)
+    }
+
+    async fn load_manifests(
+        &self,
+        metadata_view: &MetadataView,
+    ) -> (HashMap<FileHandle, Manifest>, Vec<UnreadableManifest>) {
+        let handles = metadata_view
+            .epoch_ending_backups()
+            .iter()
+            .map(|b| (b.manifest.clone(), ManifestKind::EpochEnding))
+            .chain(
+                metadata_view
+                    .state_snapshot_backups()
+                    .iter()
+                    .map(|b| (b.manifest.clone(), ManifestKind::StateSnapshot)),
+            )
+            .chain(
+                metadata_view
+                    .state_snapshot_delta_backups()
+                    .iter()
+                    .map(|b| (b.manifest.clone(), ManifestKind::StateSnapshotDelta)),
+            )
+            .chain(
+                metadata_view
+                    .transaction_backups()
+                    .iter()
+                    .map(|b| (b.manifest.clone(), ManifestKind::Transaction)),
+            )
+            .collect::<Vec<_>>();
+
+        let futs_iter = handles.into_iter().map(|(handle, kind)| async move {
+            let res = self.load_manifest(&handle, kind).await;
+            (handle, res)
+        });
+        let con = self.concurrent_downloads;
+        let loaded = stream::iter(futs_iter)
+            .buffered_x(con * 2, con)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut manifests = HashMap::new();
+        let mut unreadable = Vec::new();
+        for (handle, res) in loaded {
+            match res {
+                Ok(manifest) => {
+                    manifests.insert(handle, manifest);
+                },
+                Err(e) => {
+                    warn!(manifest = %handle, error = %e, "Failed to load manifest.");
+                    unreadable.push(UnreadableManifest {
+                        manifest: handle,
+                        error: e.to_string(),
+                    });
+                },
+            }
+        }
+        (manifests, unreadable)
+    }
+
+    async fn load_manifest(&self, handle: &FileHandleRef, kind: ManifestKind) -> Result<Manifest> {
+        Ok(match kind {
+            ManifestKind::EpochEnding => {
+                let manifest: EpochEndingBackup = self.storage.load_json_file(handle).await?;
+                manifest.verify()?;
+                Manifest::EpochEnding(manifest)
+            },
+            ManifestKind::StateSnapshot => {
+                Manifest::StateSnapshot(self.storage.load_json_file(handle).await?)
+            },
+            ManifestKind::StateSnapshotDelta => {
+                Manifest::StateSnapshotDelta(self.storage.load_json_file(handle).await?)
+            },
+            ManifestKind::Transaction => {
+                let manifest: TransactionBackup = self.storage.load_json_file(handle).await?;
+                manifest.verify()?;
+                Manifest::Transaction(manifest)
+            },
+        })
+    }
+
+    /// Verifies the epoch ending backups continuous from epoch 0.
+    async fn verify_epoch_history(&self, metadata_view: &MetadataView) -> Result<EpochHistory> {
+        let global_opt = GlobalRestoreOptions {
+            target_version: Version::max_value(),
+            trusted_waypoints: Arc::new(self.trusted_waypoints_opt.clone().verify()?),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads: self.concurrent_downloads,
+            replay_concurrency_level: 0, // won't replay, doesn't matter
+        };
+        EpochHistoryRestoreController::new(
+            metadata_view
+                .select_continuous_epoch_ending_backups()
+                .into_iter()
+                .map(|backup| backup.manifest)
+                .collect(),
+            global_opt,
+            self.storage.clone(),
+        )
+        .run()
+        .await
+    }
+
+    /// A state snapshot is reachable if its proof verifies against the verified epoch history.
+    /// A delta additionally requires its base to be reachable.
+    async fn find_unreachable_state_snapshots(
+        &self,
+        metadata_view: &MetadataView,
+        manifests: &HashMap<FileHandle, Manifest>,
+        epoch_history: Option<&EpochHistory>,
+    ) -> Vec<UnreachableStateSnapshot> {
+        let mut reachable_versions = HashSet::new();
+        let mut res = Vec::new();
+
+        for backup in metadata_view.state_snapshot_backups() {
+            let verified = match manifests.get(&backup.manifest) {
+                Some(Manifest::StateSnapshot(manifest)) => {
+                    self.verify_state_root_proof(
+                        backup.epoch,
+                        manifest.version,
+                        manifest.root_hash,
+                        &manifest.proof,
+                        epoch_history,
+                    )
+                    .await
+                },
+                _ => Err(anyhow!("Manifest not readable.")),
+            };
+            match verified {
+                Ok(()) => {
+                    reachable_versions.insert(backup.version);
+                },
+                Err(e) => res.push(UnreachableStateSnapshot {
+                    epoch: backup.epoch,
+                    version: backup.version,
+                    base_version: None,
+                    manifest: backup.manifest.clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        // Sorted by base version, so a base is always decided before the deltas on top of it.
+        for backup in metadata_view.state_snapshot_delta_backups() {
+            let verified = if !reachable_versions.contains(&backup.base_version) {
+                Err(anyhow!(
+                    "Base version {} is not reachable.",
+                    backup.base_version
+                ))
+            } else {
+                match manifests.get(&backup.manifest) {
+                    Some(Manifest::StateSnapshotDelta(manifest)) => {
+                        self.verify_state_root_proof(
+                            backup.epoch,
+                            manifest.version,
+                            manifest.root_hash,
+                            &manifest.proof,
+                            epoch_history,
+                        )
+                        .await
+                    },
+                    _ => Err(anyhow!("Manifest not readable.")),
+                }
+            };
+            match verified {
+                Ok(()) => {
+                    reachable_versions.insert(backup.version);
+                },
+                Err(e) => res.push(UnreachableStateSnapshot {
+                    epoch: backup.epoch,
+                    version: backup.version,
+                    base_version: Some(backup.base_version),
+                    manifest: backup.manifest.clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        res
+    }
+
+    async fn verify_state_root_proof(
+        &self,
+        epoch: u64,
+        version: Version,
+        root_hash: HashValue,
+        proof: &FileHandleRef,
+        epoch_history: Option<&EpochHistory>,
+    ) -> Result<()> {
+        let epoch_history =
+            epoch_history.ok_or_else(|| anyhow!("Epoch history failed to verify."))?;
+        let num_verified_epochs = epoch_history.epoch_endings.len() as u64;
+        // `EpochHistory::verify_ledger_info()` lets epochs newer than the history pass.
+        ensure!(
+            num_verified_epochs > 0 && epoch <= num_verified_epochs,
+            "Epoch {} is not covered by the verified epoch history, which has {} epochs.",
+            epoch,
+            num_verified_epochs,
+        );
+
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            root_hash,
+            state_root_hash,
+        );
+        epoch_history.verify_ledger_info(&li)
+    }
+
+    async fn find_orphaned_files(
+        &self,
+        manifests: &HashMap<FileHandle, Manifest>,
+    ) -> Result<(Option<Vec<FileHandle>>, Option<Vec<FileHandle>>)> {
+        let listed = match self.storage.list_backup_files().await? {
+            Some(listed) => listed.into_iter().collect::<HashSet<_>>(),
+            None => {
+                warn!("Backup storage doesn't support listing files, skipped orphan detection.");
+                return Ok((None, None));
+            },
+        };
+        let referenced = manifests
+            .iter()
+            .flat_map(|(handle, manifest)| {
+                manifest
+                    .referenced_files()
+                    .into_iter()
+                    .chain(std::iter::once(handle.clone()))
+            })
+            .collect::<HashSet<_>>();
+
+        let mut orphaned = listed.difference(&referenced).cloned().collect::<Vec<_>>();
+        orphaned.sort();
+        let mut missing = referenced.difference(&listed).cloned().collect::<Vec<_>>();
+        missing.sort();
+        Ok((Some(orphaned), Some(missing)))
+    }
+
+    /// Estimates a default restore: epoch ending ledger infos up to the target version, the latest
+    /// state snapshot before the target version, then replaying transactions after it.
+    fn estimate_restore(
+        &self,
+        metadata_view: &MetadataView,
+        manifests: &HashMap<FileHandle, Manifest>,
+        unreachable_state_snapshots: &[UnreachableStateSnapshot],
+    ) -> Result<Option<RestoreEstimate>> {
+        let max_transaction_version = match metadata_view.max_transaction_version()? {
+            Some(version) => version,
+            None => return Ok(None),
+        };
+        let target_version = self.target_version.unwrap_or(max_transaction_version);
+        let mut blockers = Vec::new();
+
+        let num_epoch_endings = metadata_view
+            .select_continuous_epoch_ending_backups()
+            .iter()
+            .filter(|b| b.first_version <= target_version)
+            .map(|b| b.last_epoch - b.first_epoch + 1)
+            .sum();
+
+        let state_snapshot = metadata_view.select_state_snapshot(target_version)?;
+        let num_state_values = match &state_snapshot {
+            Some(backup) => {
+                if unreachable_state_snapshots
+                    .iter()
+                    .any(|s| s.base_version.is_none() && s.manifest == backup.manifest)
+                {
+                    blockers.push(format!(
+                        "State snapshot at version {} is not reachable.",
+                        backup.version
+                    ));
+                }
+                match manifests.get(&backup.manifest) {
+                    Some(Manifest::StateSnapshot(manifest)) => manifest
+                        .chunks
+                        .last()
+                        .map_or(0, |chunk| chunk.last_idx as u64 + 1),
+                    _ => 0,
+                }
+            },
+            None => 0,
+        };
+
+        let replay_start = state_snapshot.as_ref().map_or(0, |b| b.version + 1);
+        let num_transactions_to_replay = (target_version + 1).saturating_sub(replay_start);
+        if target_version > max_transaction_version {
+            blockers.push(format!(
+                "Target version {} is newer than the latest backed up transaction {}.",
+                target_version, max_transaction_version,
+            ));
+        }
+        for (first, last) in metadata_view.transaction_backup_gaps() {
+            if first <= target_version && last >= replay_start {
+                blockers.push(format!(
+                    "Transactions in version range [{}, {}] are not backed up.",
+                    first, last,
+                ));
+            }
+        }
+
+        let estimated_secs = (num_epoch_endings as f64
+            / self.throughput.epoch_endings_per_sec as f64
+            + num_state_values as f64 / self.throughput.state_values_per_sec as f64
+            + num_transactions_to_replay as f64
+                / self.throughput.transactions_replayed_per_sec as f64)
+            .ceil() as u64;
+
+        Ok(Some(RestoreEstimate {
+            target_version,
+            state_snapshot_version: state_snapshot.map(|b| b.version),
+            num_epoch_endings,
+            num_state_values,
+            num_transactions_to_replay,
+            estimated_secs,
+            blockers,
+        }))
+    }
+}
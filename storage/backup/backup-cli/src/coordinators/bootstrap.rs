@@ -0,0 +1,161 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata,
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
+use anyhow::{anyhow, Result};
+use aptos_config::config::{
+    BackupRestoreConfig, BackupStorageConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::{AptosDB, GetRestoreHandler};
+use aptos_logger::prelude::*;
+use aptos_types::{transaction::Version, waypoint::Waypoint};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+/// Restores the latest state snapshot in the backup storage (and the transactions up to it)
+/// into the DB of a node, so the node can sync from the snapshot version instead of downloading
+/// the whole state from its peers.
+pub struct BootstrapCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    db_dir: PathBuf,
+    rocksdb_configs: RocksdbConfigs,
+    genesis_waypoint: Waypoint,
+    metadata_cache_opt: MetadataCacheOpt,
+    concurrent_downloads: usize,
+}
+
+impl BootstrapCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        db_dir: PathBuf,
+        rocksdb_configs: RocksdbConfigs,
+        genesis_waypoint: Waypoint,
+        metadata_cache_opt: MetadataCacheOpt,
+        concurrent_downloads: usize,
+    ) -> Self {
+        Self {
+            storage,
+            db_dir,
+            rocksdb_configs,
+            genesis_waypoint,
+            metadata_cache_opt,
+            concurrent_downloads,
+        }
+    }
+
+    /// Creates a coordinator that restores from the backup storage of the given node config.
+    pub async fn new_with_config(
+        config: &BackupRestoreConfig,
+        db_dir: PathBuf,
+        rocksdb_configs: RocksdbConfigs,
+        genesis_waypoint: Waypoint,
+    ) -> Result<Self> {
+        let storage: Arc<dyn BackupStorage> = match &config.backup_storage {
+            Some(BackupStorageConfig::LocalFs { dir }) => Arc::new(LocalFs::new(dir.clone())),
+            Some(BackupStorageConfig::CommandAdapter { config_path }) => Arc::new(
+                CommandAdapter::new(CommandAdapterConfig::load_from_file(config_path).await?),
+            ),
+            None => return Err(anyhow!("No backup storage is configured.")),
+        };
+
+        Ok(Self::new(
+            storage,
+            db_dir,
+            rocksdb_configs,
+            genesis_waypoint,
+            MetadataCacheOpt::new(config.metadata_cache_dir.as_ref()),
+            config.concurrent_downloads,
+        ))
+    }
+
+    /// Returns the version of the restored state snapshot, or `None` if the DB isn't empty, in
+    /// which case it is left untouched.
+    pub async fn run(self) -> Result<Option<Version>> {
+        info!("Bootstrap coordinator started.");
+
+        let ret = self.run_impl().await;
+
+        match &ret {
+            Ok(Some(version)) => info!(
+                version = version,
+                "Bootstrap coordinator restored the DB from the backup."
+            ),
+            Ok(None) => info!("DB is not empty, bootstrap coordinator skipped the restore."),
+            Err(e) => error!(
+                error = ?e,
+                "Bootstrap coordinator failed."
+            ),
+        }
+
+        ret
+    }
+
+    async fn run_impl(self) -> Result<Option<Version>> {
+        // The restore handler owns the DB, so it must be dropped before the node opens the DB
+        let restore_handler = Arc::new(AptosDB::open_kv_only(
+            &self.db_dir,
+            false,                       /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_configs,
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?)
+        .get_restore_handler();
+        let run_mode = RestoreRunMode::Restore { restore_handler };
+        if run_mode.get_next_expected_transaction_version()? > 0 {
+            return Ok(None);
+        }
+
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let max_txn_ver = metadata_view
+            .max_transaction_version()?
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        let target_version = metadata_view
+            .select_state_snapshot(max_txn_ver)?
+            .ok_or_else(|| anyhow!("No state snapshot found before version {}.", max_txn_ver))?
+            .version;
+        info!(
+            "Max transaction version in the backup: {}, restoring the state snapshot at {}",
+            max_txn_ver, target_version
+        );
+
+        // The epoch endings are verified against the genesis waypoint
+        let trusted_waypoints =
+            HashMap::from([(self.genesis_waypoint.version(), self.genesis_waypoint)]);
+        let global_opt = GlobalRestoreOptions {
+            target_version,
+            trusted_waypoints: Arc::new(trusted_waypoints),
+            run_mode: Arc::new(run_mode),
+            concurrent_downloads: self.concurrent_downloads,
+            replay_concurrency_level: num_cpus::get(),
+        };
+        let opt = RestoreCoordinatorOpt {
+            metadata_cache_opt: self.metadata_cache_opt,
+            replay_all: false,
+            ledger_history_start_version: None,
+            skip_epoch_endings: false,
+            use_state_snapshot_deltas: false,
+        };
+        RestoreCoordinator::new(opt, global_opt, self.storage)
+            .run()
+            .await?;
+
+        Ok(Some(target_version))
+    }
+}
@@ -6,6 +6,9 @@ use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        state_snapshot_delta::restore::{
+            StateSnapshotDeltaRestoreController, StateSnapshotDeltaRestoreOpt,
+        },
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
@@ -42,6 +45,13 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Option<Version>,
     #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[clap(
+        long,
+        help = "When restoring into an empty DB, apply the chain of state snapshot deltas on top \
+        of the selected state snapshot, so fewer transactions need to be replayed. Applying the \
+        chain can't be resumed if interrupted, in which case the DB needs to be restored again."
+    )]
+    pub use_state_snapshot_deltas: bool,
 }
 
 pub struct RestoreCoordinator {
@@ -51,6 +61,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Option<Version>,
     skip_epoch_endings: bool,
+    use_state_snapshot_deltas: bool,
 }
 
 impl RestoreCoordinator {
@@ -66,6 +77,7 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            use_state_snapshot_deltas: opt.use_state_snapshot_deltas,
         }
     }
 
@@ -334,8 +346,36 @@ impl RestoreCoordinator {
                 )
                 .run()
                 .await?;
+                let mut state_version = tree_snapshot.version;
+
+                // phase 2.a.1: bring the fresh snapshot closer to the target version with deltas
+                if self.use_state_snapshot_deltas
+                    && restore_mode == StateSnapshotRestoreMode::Default
+                {
+                    let deltas = metadata_view
+                        .select_state_snapshot_delta_chain(tree_snapshot.version, target_version)?;
+                    info!(
+                        num_deltas = deltas.len(),
+                        "Start applying state snapshot deltas on top of snapshot at {}",
+                        tree_snapshot.version,
+                    );
+                    for delta in deltas {
+                        StateSnapshotDeltaRestoreController::new(
+                            StateSnapshotDeltaRestoreOpt {
+                                manifest_handle: delta.manifest,
+                            },
+                            self.global_opt.clone(),
+                            Arc::clone(&self.storage),
+                            epoch_history.clone(),
+                        )
+                        .run()
+                        .await?;
+                        state_version = delta.version;
+                    }
+                }
+
                 replay_version = Some((
-                    tree_snapshot.version + 1,
+                    state_version + 1,
                     false, /*replay entire txn including update tree and KV*/
                 ));
             }
@@ -0,0 +1,129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    coordinators::bootstrap::BootstrapCoordinator,
+    metadata::cache::MetadataCacheOpt,
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        GlobalBackupOpt,
+    },
+};
+use aptos_config::config::RocksdbConfigs;
+use aptos_db::AptosDB;
+use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use aptos_types::waypoint::Waypoint;
+use std::{path::PathBuf, sync::Arc};
+use tokio::time::Duration;
+
+#[test]
+fn test_bootstrap_from_local_backup() {
+    let src_db = test_execution_with_storage_impl();
+    let latest_version = src_db.get_latest_version().unwrap();
+    let latest_epoch = src_db.get_latest_epoch_state().unwrap().epoch;
+    let epoch_ending_lis = src_db
+        .get_epoch_ending_ledger_infos(0, latest_epoch)
+        .unwrap()
+        .ledger_info_with_sigs;
+    let genesis_waypoint = Waypoint::new_epoch_boundary(epoch_ending_lis[0].ledger_info()).unwrap();
+    let snapshot_li = epoch_ending_lis.last().unwrap().ledger_info();
+
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let (rt, port) = start_local_backup_service(Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 2048,
+    };
+    rt.block_on(
+        EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch: 0,
+                end_epoch: latest_epoch,
+            },
+            global_backup_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt {
+                epoch: snapshot_li.epoch(),
+            },
+            global_backup_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: 0,
+                num_transactions: latest_version as usize + 1,
+            },
+            global_backup_opt,
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let bootstrap = || {
+        rt.block_on(
+            BootstrapCoordinator::new(
+                Arc::clone(&store),
+                tgt_db_dir.path().to_path_buf(),
+                RocksdbConfigs::default(),
+                genesis_waypoint,
+                MetadataCacheOpt::new(None::<PathBuf>),
+                4, /* concurrent_downloads */
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+
+    // The empty DB is restored to the latest state snapshot in the backup.
+    assert_eq!(bootstrap(), Some(snapshot_li.version()));
+    let restored = || {
+        let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+        (
+            tgt_db.get_latest_version().unwrap(),
+            tgt_db.get_state_snapshot_before(u64::MAX).unwrap(),
+        )
+    };
+    let (restored_version, restored_snapshot) = restored();
+    assert!(restored_version >= snapshot_li.version());
+    assert_eq!(
+        restored_snapshot,
+        src_db
+            .get_state_snapshot_before(snapshot_li.version() + 1)
+            .unwrap()
+    );
+
+    // The DB is no longer empty, so a second run leaves it untouched.
+    assert_eq!(bootstrap(), None);
+    assert_eq!(restored(), (restored_version, restored_snapshot));
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
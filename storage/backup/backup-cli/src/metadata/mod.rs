@@ -17,6 +17,7 @@ use std::{cmp::Ordering, collections::HashMap, convert::TryInto};
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
+    StateSnapshotDeltaBackup(StateSnapshotDeltaBackupMeta),
     TransactionBackup(TransactionBackupMeta),
     Identity(IdentityMeta),
     CompactionTimestamps(CompactionTimestampsMeta),
@@ -47,6 +48,20 @@ impl Metadata {
         })
     }
 
+    pub fn new_state_snapshot_delta_backup(
+        epoch: u64,
+        base_version: Version,
+        version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::StateSnapshotDeltaBackup(StateSnapshotDeltaBackupMeta {
+            epoch,
+            base_version,
+            version,
+            manifest,
+        })
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -112,6 +127,25 @@ impl Metadata {
         Ok((res, name.parse()?))
     }
 
+    pub fn compact_state_snapshot_delta_backup_range(
+        backup_metas: Vec<StateSnapshotDeltaBackupMeta>,
+    ) -> Result<(Vec<TextLine>, ShellSafeName)> {
+        ensure!(
+            !backup_metas.is_empty(),
+            "compacting an empty metadata vector"
+        );
+        let name = format!(
+            "state_snapshot_delta_compacted_ver_{}_{}.meta",
+            backup_metas[0].version,
+            backup_metas[backup_metas.len() - 1].version
+        );
+        let res: Vec<TextLine> = backup_metas
+            .into_iter()
+            .map(|d| Metadata::StateSnapshotDeltaBackup(d).to_text_line())
+            .collect::<Result<_>>()?;
+        Ok((res, name.parse()?))
+    }
+
     pub fn compact_transaction_backup_range(
         backup_metas: Vec<TransactionBackupMeta>,
     ) -> Result<(Vec<TextLine>, ShellSafeName)> {
@@ -156,6 +190,10 @@ impl Metadata {
                 format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
             },
             Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::StateSnapshotDeltaBackup(d) => format!(
+                "state_snapshot_delta_ver_{}-{}.meta",
+                d.base_version, d.version
+            ),
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version)
             },
@@ -189,6 +227,16 @@ pub struct StateSnapshotBackupMeta {
     pub manifest: FileHandle,
 }
 
+/// Changes to the state between the state snapshot (or delta) at `base_version` and `version`.
+/// Fields are ordered so that deltas sort by the version they are based on.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StateSnapshotDeltaBackupMeta {
+    pub base_version: Version,
+    pub version: Version,
+    pub epoch: u64,
+    pub manifest: FileHandle,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
@@ -5,7 +5,7 @@
 use crate::{
     metadata::{
         CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta, Metadata,
-        StateSnapshotBackupMeta, TransactionBackupMeta,
+        StateSnapshotBackupMeta, StateSnapshotDeltaBackupMeta, TransactionBackupMeta,
     },
     storage::FileHandle,
 };
@@ -19,6 +19,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    state_snapshot_delta_backups: Vec<StateSnapshotDeltaBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
     _identity: Option<IdentityMeta>,
     // The compaction timestamps of the file handles producing this view
@@ -29,6 +30,7 @@ impl MetadataView {
     pub(crate) fn new(metadata_vec: Vec<Metadata>, file_handles: Vec<FileHandle>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut state_snapshot_delta_backups = Vec::new();
         let mut transaction_backups = Vec::new();
         let mut identity = None;
         let mut compaction_timestamps = Vec::new();
@@ -37,6 +39,7 @@ impl MetadataView {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::StateSnapshotDeltaBackup(d) => state_snapshot_delta_backups.push(d),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
                 Metadata::Identity(i) => identity = Some(i),
                 Metadata::CompactionTimestamps(t) => compaction_timestamps.push(t),
@@ -46,6 +49,8 @@ impl MetadataView {
         epoch_ending_backups.dedup();
         state_snapshot_backups.sort_unstable();
         state_snapshot_backups.dedup();
+        state_snapshot_delta_backups.sort_unstable();
+        state_snapshot_delta_backups.dedup();
         transaction_backups.sort_unstable();
         transaction_backups.dedup();
 
@@ -71,6 +76,7 @@ impl MetadataView {
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            state_snapshot_delta_backups,
             transaction_backups,
             _identity: identity,
             compaction_timestamps: compaction_meta_opt,
@@ -125,6 +131,34 @@ impl MetadataView {
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    /// Selects a chain of state snapshot deltas that, applied in order on top of the state snapshot
+    /// at `base_version`, brings the state as close to `target_version` as possible. Each step
+    /// takes the delta reaching the furthest, so the chain is as short as possible.
+    pub fn select_state_snapshot_delta_chain(
+        &self,
+        base_version: Version,
+        target_version: Version,
+    ) -> Result<Vec<StateSnapshotDeltaBackupMeta>> {
+        let mut res = Vec::new();
+        let mut current_version = base_version;
+        while let Some(delta) = self
+            .state_snapshot_delta_backups
+            .iter()
+            .filter(|d| d.base_version == current_version && d.version <= target_version)
+            .max_by_key(|d| d.version)
+        {
+            ensure!(
+                delta.version > current_version,
+                "State snapshot delta doesn't advance the version: {:?}",
+                delta,
+            );
+            current_version = delta.version;
+            res.push(delta.clone());
+        }
+
+        Ok(res)
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -191,6 +225,85 @@ impl MetadataView {
         Ok(res)
     }
 
+    /// Selects epoch ending backups continuous from epoch 0, stopping at the first missing epoch.
+    pub fn select_continuous_epoch_ending_backups(&self) -> Vec<EpochEndingBackupMeta> {
+        let mut next_epoch = 0;
+        let mut res = Vec::new();
+        for backup in self.epoch_ending_backups.iter().sorted() {
+            if backup.first_epoch != next_epoch {
+                if backup.first_epoch > next_epoch {
+                    break;
+                }
+                // overlaps with selected backups
+                continue;
+            }
+            res.push(backup.clone());
+            next_epoch = backup.last_epoch + 1;
+        }
+
+        res
+    }
+
+    /// Version ranges (inclusive) not covered by any transaction backup, from version 0 up to the
+    /// latest backed up transaction.
+    pub fn transaction_backup_gaps(&self) -> Vec<(Version, Version)> {
+        Self::gaps(
+            self.transaction_backups
+                .iter()
+                .map(|b| (b.first_version, b.last_version)),
+        )
+    }
+
+    /// Epoch ranges (inclusive) with no epoch ending backup, from epoch 0 up to the latest epoch
+    /// referred to by any epoch ending backup or state snapshot.
+    pub fn missing_epoch_ending_epochs(&self) -> Vec<(u64, u64)> {
+        let max_epoch = self
+            .epoch_ending_backups
+            .iter()
+            .map(|b| b.last_epoch)
+            .chain(self.state_snapshot_backups.iter().map(|s| s.epoch))
+            .chain(self.state_snapshot_delta_backups.iter().map(|d| d.epoch))
+            .max();
+        match max_epoch {
+            // A sentinel range right after the latest epoch makes the trailing epochs count.
+            Some(max_epoch) => Self::gaps(
+                self.epoch_ending_backups
+                    .iter()
+                    .map(|b| (b.first_epoch, b.last_epoch))
+                    .chain(std::iter::once((max_epoch + 1, max_epoch + 1))),
+            ),
+            None => Vec::new(),
+        }
+    }
+
+    fn gaps(ranges: impl Iterator<Item = (u64, u64)>) -> Vec<(u64, u64)> {
+        let mut next = 0;
+        let mut res = Vec::new();
+        for (first, last) in ranges.sorted() {
+            if first > next {
+                res.push((next, first - 1));
+            }
+            next = next.max(last + 1);
+        }
+        res
+    }
+
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn state_snapshot_delta_backups(&self) -> &[StateSnapshotDeltaBackupMeta] {
+        &self.state_snapshot_delta_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     /// Compact the epoch ending metdata files and merge compaction_cnt files into 1 metadata file
     /// The generated chunks should be sorted based on version
     pub fn compact_backups<T>(backups: &[T], compaction_cnt: usize) -> Result<Vec<&[T]>> {
@@ -229,6 +342,13 @@ impl MetadataView {
         Self::compact_backups(&self.state_snapshot_backups, compaction_cnt)
     }
 
+    pub fn compact_state_delta_backups(
+        &mut self,
+        compaction_cnt: usize,
+    ) -> Result<Vec<&[StateSnapshotDeltaBackupMeta]>> {
+        Self::compact_backups(&self.state_snapshot_delta_backups, compaction_cnt)
+    }
+
     pub fn get_file_handles(&self) -> Vec<FileHandle> {
         self.select_latest_compaction_timestamps()
             .as_ref()
@@ -0,0 +1,103 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Content-defined chunking based on a Gear rolling hash.
+//!
+//! Boundaries only depend on the bytes since the start of the current chunk, so inserting or
+//! removing records early in a file only changes the chunks around the edit, and the remaining
+//! chunks still deduplicate against previous backups.
+
+/// Table of pseudo-random values fed into the rolling hash, one per byte value.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    // splitmix64, seeded with a fixed value so chunk boundaries are stable across releases.
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkerConfig {
+    /// No boundary is placed before this many bytes into a chunk.
+    pub min_size: usize,
+    /// Targeted average chunk size, must be a power of two.
+    pub avg_size: usize,
+    /// A boundary is forced once a chunk reaches this size.
+    pub max_size: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 256 * 1024,
+            avg_size: 1024 * 1024,
+            max_size: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl ChunkerConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.avg_size.is_power_of_two(),
+            "Average chunk size must be a power of two, got {}",
+            self.avg_size,
+        );
+        anyhow::ensure!(
+            0 < self.min_size && self.min_size < self.avg_size && self.avg_size < self.max_size,
+            "Expecting 0 < min_size < avg_size < max_size, got {:?}",
+            self,
+        );
+        Ok(())
+    }
+
+    fn mask(&self) -> u64 {
+        (self.avg_size - self.min_size).next_power_of_two() as u64 - 1
+    }
+
+    /// Returns the length of the first chunk in `data`, or `None` if more data is needed to
+    /// decide where the chunk ends.
+    pub fn next_boundary(&self, data: &[u8]) -> Option<usize> {
+        if data.len() <= self.min_size {
+            return None;
+        }
+        let mask = self.mask();
+        let end = data.len().min(self.max_size);
+        let mut hash = 0u64;
+        for (i, byte) in data.iter().enumerate().take(end).skip(self.min_size) {
+            hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+            if hash & mask == 0 {
+                return Some(i + 1);
+            }
+        }
+        if data.len() >= self.max_size {
+            Some(self.max_size)
+        } else {
+            None
+        }
+    }
+
+    /// Splits the tail of a file into chunks, the last chunk possibly ending without a boundary.
+    pub fn split_all<'a>(&self, mut data: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut res = Vec::new();
+        while let Some(len) = self.next_boundary(data) {
+            let (chunk, rest) = data.split_at(len);
+            res.push(chunk);
+            data = rest;
+        }
+        if !data.is_empty() {
+            res.push(data);
+        }
+        res
+    }
+}
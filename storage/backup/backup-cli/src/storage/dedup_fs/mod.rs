@@ -0,0 +1,683 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod chunker;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::storage::dedup_fs::chunker::ChunkerConfig;
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::{error_notes::ErrorNotes, list_local_backup_files, path_exists, PathToString},
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::{debug, info, warn};
+use async_trait::async_trait;
+use clap::Parser;
+use futures::{future::BoxFuture, ready, stream, Future, FutureExt, StreamExt, TryStreamExt};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN},
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    fs::{create_dir_all, read, read_dir, read_to_string, remove_file, rename, File, OpenOptions},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(Parser, Debug, Serialize, Deserialize)]
+pub struct DedupFsOpt {
+    #[clap(
+        long = "dir",
+        parse(from_os_str),
+        help = "Target local dir to hold backups."
+    )]
+    pub dir: PathBuf,
+    #[clap(
+        long = "encryption-key-file",
+        parse(from_os_str),
+        help = "File holding a hex encoded 32 byte key, used to encrypt chunks at rest. If not \
+        provided, chunks are stored in plain text."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+}
+
+/// A storage backend that stores everything in a local directory, splitting files into
+/// content-defined chunks which are deduplicated across all backups in the directory.
+///
+/// Layout:
+///   chunks/<first two hex digits of chunk id>/<chunk id>: chunk content, possibly encrypted.
+///   <backup name>/<file name>: JSON index listing the chunks of a file.
+///   metadata/<name>: same, for metadata files.
+pub struct DedupFs {
+    /// The path where everything is stored.
+    dir: PathBuf,
+    chunk_store: Arc<ChunkStore>,
+    chunker: ChunkerConfig,
+}
+
+impl DedupFs {
+    const CHUNKS_DIR: &'static str = "chunks";
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(dir: PathBuf, encryption_key: Option<[u8; 32]>) -> Self {
+        Self::new_with_chunker_config(dir, encryption_key, ChunkerConfig::default())
+            .expect("Default chunker config should be valid.")
+    }
+
+    pub fn new_with_chunker_config(
+        dir: PathBuf,
+        encryption_key: Option<[u8; 32]>,
+        chunker: ChunkerConfig,
+    ) -> Result<Self> {
+        chunker.validate()?;
+        let chunk_store = Arc::new(ChunkStore {
+            dir: dir.join(Self::CHUNKS_DIR),
+            cipher: encryption_key.as_ref().map(ChunkCipher::new),
+        });
+        Ok(Self {
+            dir,
+            chunk_store,
+            chunker,
+        })
+    }
+
+    pub async fn new_with_opt(opt: DedupFsOpt) -> Result<Self> {
+        let encryption_key = match &opt.encryption_key_file {
+            Some(path) => Some(load_encryption_key(path).await?),
+            None => None,
+        };
+        Ok(Self::new(opt.dir, encryption_key))
+    }
+
+    pub fn metadata_dir(&self) -> PathBuf {
+        self.dir.join(Self::METADATA_DIR)
+    }
+
+    pub fn metadata_backup_dir(&self) -> PathBuf {
+        self.dir.join(Self::METADATA_BACKUP_DIR)
+    }
+
+    fn writer(
+        &self,
+        index_file: File,
+        index_path: PathBuf,
+        file_handle: FileHandle,
+    ) -> ChunkedFileWriter {
+        ChunkedFileWriter {
+            chunk_store: self.chunk_store.clone(),
+            chunker: self.chunker,
+            file_handle,
+            index_file: Some(index_file),
+            index_path,
+            buf: Vec::new(),
+            index: FileIndex {
+                encrypted: self.chunk_store.is_encrypted(),
+                size: 0,
+                chunks: Vec::new(),
+            },
+            num_new_chunks: 0,
+            pending: None,
+            finished: false,
+            index_saved: false,
+            index_removed: false,
+        }
+    }
+}
+
+async fn load_encryption_key(path: &Path) -> Result<[u8; 32]> {
+    let content = read_to_string(path).await.err_notes(path)?;
+    let bytes = hex::decode(content.trim())
+        .map_err(|e| format_err!("Encryption key is not valid hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format_err!("Expecting a 32 byte encryption key, got {}.", b.len()))
+}
+
+#[async_trait]
+impl BackupStorage for DedupFs {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        create_dir_all(self.dir.join(name.as_ref()))
+            .await
+            .err_notes(self.dir.join(name.as_ref()))?;
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = Path::new(backup_handle)
+            .join(name.as_ref())
+            .path_to_string()?;
+        let abs_path = self.dir.join(&file_handle);
+        let index_file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&abs_path)
+            .await
+            .err_notes(&abs_path)?;
+        let writer = self.writer(index_file, abs_path, file_handle.clone());
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let path = self.dir.join(file_handle);
+        let index: FileIndex = serde_json::from_slice(&read(&path).await.err_notes(&path)?)
+            .map_err(|e| format_err!("Failed to parse chunk index {:?}: {}", path, e))?;
+        ensure!(
+            index.encrypted == self.chunk_store.is_encrypted(),
+            "File {} was saved with encryption {}, but the storage is configured with encryption {}.",
+            file_handle,
+            if index.encrypted { "on" } else { "off" },
+            if self.chunk_store.is_encrypted() { "on" } else { "off" },
+        );
+
+        let chunk_store = self.chunk_store.clone();
+        let reader = stream::iter(index.chunks)
+            .then(move |chunk| {
+                let chunk_store = chunk_store.clone();
+                async move {
+                    chunk_store
+                        .get(&chunk)
+                        .await
+                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+                }
+            })
+            .boxed()
+            .into_async_read()
+            .compat();
+        Ok(Box::new(reader))
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let dir = self.metadata_dir();
+        let rel_path = Path::new(Self::METADATA_DIR);
+
+        let mut res = Vec::new();
+        if path_exists(&dir).await {
+            let mut entries = read_dir(&dir).await.err_notes(&dir)?;
+            while let Some(entry) = entries.next_entry().await.err_notes(&dir)? {
+                res.push(rel_path.join(entry.file_name()).path_to_string()?)
+            }
+        }
+        Ok(res)
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let dir = self.metadata_backup_dir();
+        if !path_exists(&dir).await {
+            create_dir_all(&dir).await.err_notes(&dir)?;
+        }
+
+        let name = Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+
+        // Only the index is moved, the chunks stay shared in the chunks folder.
+        rename(&self.dir.join(file_handle), &dir.join(name))
+            .await
+            .err_notes(file_handle)?;
+        Ok(())
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let dir = self.metadata_dir();
+        create_dir_all(&dir).await.err_notes(name)?; // in case not yet created
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let fh = PathBuf::from(Self::METADATA_DIR)
+            .join(name.as_ref())
+            .path_to_string()?;
+        let path = dir.join(name.as_ref());
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await;
+        match file {
+            Ok(f) => {
+                let mut writer = self.writer(f, path.clone(), fh.clone());
+                writer
+                    .write_all(content.as_bytes())
+                    .await
+                    .err_notes(&path)?;
+                writer.shutdown().await.err_notes(&path)?;
+            },
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                info!("File {} already exists, Skip", name.as_ref());
+            },
+            _ => bail!("Unexpected Error in saving metadata file {}", name.as_ref()),
+        }
+        Ok(fh)
+    }
+
+    async fn list_backup_files(&self) -> Result<Option<Vec<FileHandle>>> {
+        Ok(Some(
+            list_local_backup_files(&self.dir, &[
+                Self::METADATA_DIR,
+                Self::METADATA_BACKUP_DIR,
+                Self::CHUNKS_DIR,
+            ])
+            .await?,
+        ))
+    }
+
+    async fn list_unreferenced_chunks(&self) -> Result<Option<Vec<String>>> {
+        let mut index_files = list_local_backup_files(&self.dir, &[
+            Self::METADATA_DIR,
+            Self::METADATA_BACKUP_DIR,
+            Self::CHUNKS_DIR,
+        ])
+        .await?;
+        for dir_name in [Self::METADATA_DIR, Self::METADATA_BACKUP_DIR] {
+            let dir = self.dir.join(dir_name);
+            if path_exists(&dir).await {
+                let mut entries = read_dir(&dir).await.err_notes(&dir)?;
+                while let Some(entry) = entries.next_entry().await.err_notes(&dir)? {
+                    index_files.push(
+                        Path::new(dir_name)
+                            .join(entry.file_name())
+                            .path_to_string()?,
+                    );
+                }
+            }
+        }
+
+        let mut referenced = HashSet::new();
+        for file_handle in index_files {
+            // Failing instead of skipping, so chunks of an unreadable index are never reported as
+            // safe to delete.
+            let path = self.dir.join(&file_handle);
+            let index: FileIndex = serde_json::from_slice(&read(&path).await.err_notes(&path)?)
+                .map_err(|e| format_err!("Failed to parse chunk index {:?}: {}", path, e))?;
+            referenced.extend(index.chunks.into_iter().map(|chunk| chunk.id));
+        }
+
+        let mut res = Vec::new();
+        let chunks_dir = self.dir.join(Self::CHUNKS_DIR);
+        if !path_exists(&chunks_dir).await {
+            return Ok(Some(res));
+        }
+        let mut prefixes = read_dir(&chunks_dir).await.err_notes(&chunks_dir)?;
+        while let Some(prefix) = prefixes.next_entry().await.err_notes(&chunks_dir)? {
+            let mut chunks = read_dir(prefix.path()).await.err_notes(prefix.path())?;
+            while let Some(chunk) = chunks.next_entry().await.err_notes(prefix.path())? {
+                // Leftover temporary files of interrupted writes don't parse as chunk ids.
+                let is_referenced = chunk
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| HashValue::from_hex(name).ok())
+                    .map_or(false, |id| referenced.contains(&id));
+                if !is_referenced {
+                    res.push(
+                        Path::new(Self::CHUNKS_DIR)
+                            .join(prefix.file_name())
+                            .join(chunk.file_name())
+                            .path_to_string()?,
+                    );
+                }
+            }
+        }
+        res.sort();
+        Ok(Some(res))
+    }
+}
+
+/// Content of the file a `FileHandle` points to.
+#[derive(Debug, Deserialize, Serialize)]
+struct FileIndex {
+    encrypted: bool,
+    size: u64,
+    chunks: Vec<ChunkRef>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ChunkRef {
+    id: HashValue,
+    len: u64,
+}
+
+struct ChunkCipher {
+    id_key: hmac::Key,
+    aead_key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl ChunkCipher {
+    fn new(master_key: &[u8; 32]) -> Self {
+        // Derive separate keys for chunk naming and encryption from the configured key.
+        let derive = |purpose: &[u8]| HashValue::from_iter_sha3([purpose, &master_key[..]]);
+        let id_key = hmac::Key::new(hmac::HMAC_SHA256, derive(b"CHUNK_ID").as_ref());
+        let aead_key = LessSafeKey::new(
+            UnboundKey::new(&aead::AES_256_GCM, derive(b"CHUNK_ENCRYPTION").as_ref())
+                .expect("32 byte key should be valid for AES-256-GCM."),
+        );
+        Self {
+            id_key,
+            aead_key,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Keyed, so that chunk names don't leak the hash of the plain text.
+    fn chunk_id(&self, data: &[u8]) -> HashValue {
+        HashValue::from_slice(hmac::sign(&self.id_key, data).as_ref())
+            .expect("HMAC-SHA256 output should be 32 bytes.")
+    }
+
+    /// Output is nonce || cipher text || tag, the chunk id is authenticated as associated data.
+    fn seal(&self, id: &HashValue, data: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce_bytes)
+            .map_err(|_| format_err!("Failed to generate nonce."))?;
+        let mut in_out = data.to_vec();
+        self.aead_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(id.as_ref()),
+                &mut in_out,
+            )
+            .map_err(|_| format_err!("Failed to encrypt chunk {}.", id))?;
+        let mut res = nonce_bytes.to_vec();
+        res.extend(in_out);
+        Ok(res)
+    }
+
+    fn open(&self, id: &HashValue, mut sealed: Vec<u8>) -> Result<Vec<u8>> {
+        ensure!(sealed.len() >= NONCE_LEN, "Chunk {} is truncated.", id);
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed)
+            .map_err(|_| format_err!("Bad nonce in chunk {}.", id))?;
+        let len = self
+            .aead_key
+            .open_in_place(nonce, Aad::from(id.as_ref()), &mut in_out)
+            .map_err(|_| format_err!("Failed to decrypt chunk {}, wrong key or corrupted.", id))?
+            .len();
+        in_out.truncate(len);
+        Ok(in_out)
+    }
+}
+
+struct ChunkStore {
+    dir: PathBuf,
+    cipher: Option<ChunkCipher>,
+}
+
+impl ChunkStore {
+    fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    fn chunk_id(&self, data: &[u8]) -> HashValue {
+        match &self.cipher {
+            Some(cipher) => cipher.chunk_id(data),
+            None => HashValue::sha3_256_of(data),
+        }
+    }
+
+    fn chunk_path(&self, id: &HashValue) -> PathBuf {
+        let hex = id.to_hex();
+        self.dir.join(&hex[..2]).join(hex)
+    }
+
+    /// Returns false if the chunk already exists in the store.
+    async fn put(&self, id: &HashValue, data: &[u8]) -> Result<bool> {
+        let path = self.chunk_path(id);
+        if path_exists(&path).await {
+            return Ok(false);
+        }
+
+        let content = match &self.cipher {
+            Some(cipher) => cipher.seal(id, data)?,
+            None => data.to_vec(),
+        };
+        let dir = path.parent().expect("Chunk path has a parent.");
+        create_dir_all(dir).await.err_notes(dir)?;
+        // Write to a temporary file first so a crash never leaves a partial chunk behind under
+        // the final name, which would be treated as present by later backups.
+        let tmp_path = dir.join(format!("{}.tmp.{}", id.to_hex(), rand::random::<u64>()));
+        let mut file = File::create(&tmp_path).await.err_notes(&tmp_path)?;
+        file.write_all(&content).await.err_notes(&tmp_path)?;
+        file.sync_all().await.err_notes(&tmp_path)?;
+        rename(&tmp_path, &path).await.err_notes(&path)?;
+        Ok(true)
+    }
+
+    async fn put_all(self: Arc<Self>, chunks: Vec<(HashValue, Vec<u8>)>) -> Result<usize> {
+        let mut num_new = 0;
+        for (id, data) in chunks {
+            if self.put(&id, &data).await? {
+                num_new += 1;
+            }
+        }
+        Ok(num_new)
+    }
+
+    async fn get(&self, chunk: &ChunkRef) -> Result<Vec<u8>> {
+        let path = self.chunk_path(&chunk.id);
+        let content = read(&path).await.err_notes(&path)?;
+        let data = match &self.cipher {
+            Some(cipher) => cipher.open(&chunk.id, content)?,
+            None => content,
+        };
+        ensure!(
+            data.len() as u64 == chunk.len,
+            "Chunk {} has length {}, expecting {}.",
+            chunk.id,
+            data.len(),
+            chunk.len,
+        );
+        let actual_id = self.chunk_id(&data);
+        ensure!(
+            actual_id == chunk.id,
+            "Chunk {} is corrupted, content hashes to {}.",
+            chunk.id,
+            actual_id,
+        );
+        Ok(data)
+    }
+}
+
+/// Buffers written data until chunk boundaries are found, persisting complete chunks in the
+/// background while more data is written. The index is written on `shutdown()`, and removed if
+/// persisting the file fails, so an unfinished file doesn't look like an empty one. A writer
+/// dropped before finishing removes the index as well.
+struct ChunkedFileWriter {
+    chunk_store: Arc<ChunkStore>,
+    chunker: ChunkerConfig,
+    file_handle: FileHandle,
+    index_file: Option<File>,
+    index_path: PathBuf,
+    buf: Vec<u8>,
+    index: FileIndex,
+    num_new_chunks: usize,
+    pending: Option<BoxFuture<'static, Result<usize>>>,
+    finished: bool,
+    index_saved: bool,
+    index_removed: bool,
+}
+
+impl ChunkedFileWriter {
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(fut) = self.pending.as_mut() {
+            let res = ready!(fut.as_mut().poll(cx));
+            self.pending = None;
+            if res.is_err() {
+                self.index_removed = true;
+            }
+            self.num_new_chunks += res.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Makes `fut` the pending operation, removing the index before reporting a failure.
+    fn set_pending(&mut self, fut: impl Future<Output = Result<usize>> + Send + 'static) {
+        let index_path = self.index_path.clone();
+        let file_handle = self.file_handle.clone();
+        self.pending = Some(
+            async move {
+                let res = fut.await;
+                if res.is_err() {
+                    // Chunks already saved are left to be reported as unreferenced by audits.
+                    if let Err(e) = remove_file(&index_path).await {
+                        warn!(
+                            file_handle = file_handle,
+                            error = ?e,
+                            "Failed to remove index of unfinished file."
+                        );
+                    }
+                }
+                res
+            }
+            .boxed(),
+        );
+    }
+
+    /// Takes complete chunks off the buffer, or everything if `is_final`.
+    fn cut_chunks(&mut self, is_final: bool) -> Vec<(HashValue, Vec<u8>)> {
+        let mut offset = 0;
+        let mut chunks = Vec::new();
+        while let Some(len) = self.chunker.next_boundary(&self.buf[offset..]) {
+            chunks.push(self.buf[offset..offset + len].to_vec());
+            offset += len;
+        }
+        if is_final && offset < self.buf.len() {
+            chunks.push(self.buf[offset..].to_vec());
+            offset = self.buf.len();
+        }
+        self.buf.drain(..offset);
+
+        chunks
+            .into_iter()
+            .map(|data| {
+                let id = self.chunk_store.chunk_id(&data);
+                self.index.size += data.len() as u64;
+                self.index.chunks.push(ChunkRef {
+                    id,
+                    len: data.len() as u64,
+                });
+                (id, data)
+            })
+            .collect()
+    }
+}
+
+impl AsyncWrite for ChunkedFileWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        if this.finished {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Writing to {} after shutdown.", this.file_handle),
+            )));
+        }
+
+        this.buf.extend_from_slice(data);
+        if this.buf.len() >= this.chunker.max_size {
+            let chunks = this.cut_chunks(false);
+            this.set_pending(this.chunk_store.clone().put_all(chunks));
+        }
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        if !this.finished {
+            this.finished = true;
+            let chunks = this.cut_chunks(true);
+            let index = serde_json::to_vec(&this.index)?;
+            let mut index_file = this
+                .index_file
+                .take()
+                .expect("Index file is only taken once.");
+            let chunk_store = this.chunk_store.clone();
+            let file_handle = this.file_handle.clone();
+            let size = this.index.size;
+            let num_chunks = this.index.chunks.len();
+            let num_new_chunks_so_far = this.num_new_chunks;
+            this.set_pending(async move {
+                let num_new = chunk_store.put_all(chunks).await?;
+                index_file.write_all(&index).await?;
+                index_file.shutdown().await?;
+                debug!(
+                    file_handle = file_handle,
+                    size = size,
+                    num_chunks = num_chunks,
+                    num_new_chunks = num_new_chunks_so_far + num_new,
+                    "Chunked file saved."
+                );
+                Ok(num_new)
+            });
+            ready!(this.poll_pending(cx))?;
+        }
+        this.index_saved = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for ChunkedFileWriter {
+    /// Safety net for writers abandoned before finishing, failures remove the index already.
+    fn drop(&mut self) {
+        if self.index_saved || self.index_removed {
+            return;
+        }
+        let index_path = self.index_path.clone();
+        let file_handle = self.file_handle.clone();
+        let remove = move || {
+            if let Err(e) = std::fs::remove_file(&index_path) {
+                warn!(
+                    file_handle = file_handle,
+                    error = ?e,
+                    "Failed to remove index of unfinished file."
+                );
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(remove);
+            },
+            Err(_) => remove(),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl,
+};
+use aptos_temppath::TempPath;
+use proptest::{collection::vec, prelude::*};
+use std::{str::FromStr, time::Duration};
+use tokio::{io::AsyncReadExt, runtime::Runtime};
+
+const KEY: [u8; 32] = [7u8; 32];
+
+fn small_chunks() -> ChunkerConfig {
+    ChunkerConfig {
+        min_size: 16,
+        avg_size: 64,
+        max_size: 256,
+    }
+}
+
+async fn write_file(store: &DedupFs, backup: &str, name: &str, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&ShellSafeName::from_str(backup).unwrap())
+        .await
+        .unwrap();
+    let (handle, mut file) = store
+        .create_for_write(&backup_handle, &ShellSafeName::from_str(name).unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    handle
+}
+
+async fn read_file(store: &DedupFs, handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+fn num_chunk_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir.join(DedupFs::CHUNKS_DIR))
+        .unwrap()
+        .map(|prefix_dir| {
+            std::fs::read_dir(prefix_dir.unwrap().path())
+                .unwrap()
+                .count()
+        })
+        .sum()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups(),
+        encrypted in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = DedupFs::new_with_chunker_config(
+            tmpdir.path().to_path_buf(),
+            encrypted.then_some(KEY),
+            small_chunks(),
+        ).unwrap();
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+        encrypted in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = DedupFs::new(tmpdir.path().to_path_buf(), encrypted.then_some(KEY));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+
+    #[test]
+    fn test_chunk_boundaries_independent_of_prefix(
+        prefix in vec(any::<u8>(), 0..2000),
+        content in vec(any::<u8>(), 5000..10000),
+    ) {
+        let chunker = small_chunks();
+        let mut with_prefix = prefix;
+        with_prefix.extend_from_slice(&content);
+
+        let original = chunker.split_all(&content);
+        let shifted = chunker.split_all(&with_prefix);
+        // Boundaries resynchronize after a few chunks, so the tails should be shared.
+        let num_shared = original
+            .iter()
+            .rev()
+            .zip(shifted.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        prop_assert!(num_shared * 2 >= original.len());
+    }
+}
+
+#[tokio::test]
+async fn test_dedup_across_backups() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store =
+        DedupFs::new_with_chunker_config(tmpdir.path().to_path_buf(), Some(KEY), small_chunks())
+            .unwrap();
+
+    let content = (0..20000u32)
+        .flat_map(|i| (i * 7919).to_le_bytes())
+        .collect::<Vec<_>>();
+    let first = write_file(&store, "backup_1", "file", &content).await;
+    let num_chunks = num_chunk_files(tmpdir.path());
+    assert!(num_chunks > 1);
+
+    // Exactly the same content is fully deduplicated.
+    let second = write_file(&store, "backup_2", "file", &content).await;
+    assert_eq!(num_chunk_files(tmpdir.path()), num_chunks);
+
+    // A small edit only adds a few chunks.
+    let mut edited = content.clone();
+    edited[40000] ^= 0xFF;
+    let third = write_file(&store, "backup_3", "file", &edited).await;
+    assert!(num_chunk_files(tmpdir.path()) <= num_chunks + 3);
+
+    assert_eq!(read_file(&store, &first).await.unwrap(), content);
+    assert_eq!(read_file(&store, &second).await.unwrap(), content);
+    assert_eq!(read_file(&store, &third).await.unwrap(), edited);
+}
+
+#[tokio::test]
+async fn test_chunks_are_verified_on_read() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let dir = tmpdir.path().to_path_buf();
+    for encryption_key in [None, Some(KEY)] {
+        let store =
+            DedupFs::new_with_chunker_config(dir.clone(), encryption_key, small_chunks()).unwrap();
+        let backup = if encryption_key.is_some() {
+            "encrypted"
+        } else {
+            "plain"
+        };
+        let handle = write_file(&store, backup, "file", &[1u8; 1000]).await;
+
+        let index: FileIndex =
+            serde_json::from_slice(&std::fs::read(dir.join(&handle)).unwrap()).unwrap();
+        let chunk_path = store.chunk_store.chunk_path(&index.chunks[0].id);
+        let mut chunk = std::fs::read(&chunk_path).unwrap();
+        *chunk.last_mut().unwrap() ^= 1;
+        std::fs::write(&chunk_path, chunk).unwrap();
+
+        assert!(read_file(&store, &handle).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_encryption_mismatch() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let dir = tmpdir.path().to_path_buf();
+
+    let store = DedupFs::new(dir.clone(), Some(KEY));
+    let handle = write_file(&store, "backup", "file", b"secret content").await;
+    assert_eq!(read_file(&store, &handle).await.unwrap(), b"secret content");
+
+    let wrong_key = DedupFs::new(dir.clone(), Some([8u8; 32]));
+    assert!(read_file(&wrong_key, &handle).await.is_err());
+
+    let no_key = DedupFs::new(dir, None);
+    assert!(read_file(&no_key, &handle).await.is_err());
+}
+
+#[tokio::test]
+async fn test_unfinished_file_removed_on_drop() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = DedupFs::new_with_chunker_config(tmpdir.path().to_path_buf(), None, small_chunks())
+        .unwrap();
+
+    let backup_handle = store
+        .create_backup(&ShellSafeName::from_str("backup").unwrap())
+        .await
+        .unwrap();
+    let (handle, mut file) = store
+        .create_for_write(&backup_handle, &ShellSafeName::from_str("file").unwrap())
+        .await
+        .unwrap();
+    file.write_all(&[1u8; 1000]).await.unwrap();
+    file.flush().await.unwrap();
+    assert!(tmpdir.path().join(&handle).exists());
+    drop(file);
+
+    // The index is removed in the background.
+    for _ in 0..100 {
+        if !tmpdir.path().join(&handle).exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!tmpdir.path().join(&handle).exists());
+    assert!(read_file(&store, &handle).await.is_err());
+    // The same name can be written again.
+    let handle = write_file(&store, "backup", "file", &[2u8; 1000]).await;
+    assert_eq!(read_file(&store, &handle).await.unwrap(), vec![2u8; 1000]);
+}
+
+#[tokio::test]
+async fn test_unfinished_file_removed_on_error() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = DedupFs::new_with_chunker_config(tmpdir.path().to_path_buf(), None, small_chunks())
+        .unwrap();
+
+    // Chunks can't be saved while a file takes the place of the chunks dir.
+    std::fs::write(tmpdir.path().join(DedupFs::CHUNKS_DIR), b"").unwrap();
+    let backup_handle = store
+        .create_backup(&ShellSafeName::from_str("backup").unwrap())
+        .await
+        .unwrap();
+    let (handle, mut file) = store
+        .create_for_write(&backup_handle, &ShellSafeName::from_str("file").unwrap())
+        .await
+        .unwrap();
+    file.write_all(&[1u8; 1000]).await.unwrap();
+    assert!(file.flush().await.is_err());
+
+    // The index is already removed when the error is reported.
+    assert!(!tmpdir.path().join(&handle).exists());
+    drop(file);
+    assert!(read_file(&store, &handle).await.is_err());
+}
+
+#[tokio::test]
+async fn test_list_unreferenced_chunks() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let dir = tmpdir.path().to_path_buf();
+    let store = DedupFs::new_with_chunker_config(dir.clone(), Some(KEY), small_chunks()).unwrap();
+    assert_eq!(
+        store.list_unreferenced_chunks().await.unwrap(),
+        Some(vec![])
+    );
+
+    let kept = write_file(&store, "backup_1", "file", &[1u8; 1000]).await;
+    let removed = write_file(&store, "backup_2", "file", &[2u8; 1000]).await;
+    let metadata = store
+        .save_metadata_line(
+            &ShellSafeName::from_str("metadata").unwrap(),
+            &TextLine::new("metadata line").unwrap(),
+        )
+        .await
+        .unwrap();
+    store.backup_metadata_file(&metadata).await.unwrap();
+    assert_eq!(
+        store.list_unreferenced_chunks().await.unwrap(),
+        Some(vec![])
+    );
+
+    let chunk_files = |handle: &FileHandle| {
+        let index: FileIndex =
+            serde_json::from_slice(&std::fs::read(dir.join(handle)).unwrap()).unwrap();
+        index
+            .chunks
+            .iter()
+            .map(|chunk| {
+                store
+                    .chunk_store
+                    .chunk_path(&chunk.id)
+                    .strip_prefix(&dir)
+                    .unwrap()
+                    .path_to_string()
+                    .unwrap()
+            })
+            .collect::<HashSet<_>>()
+    };
+    let mut expected = chunk_files(&removed)
+        .difference(&chunk_files(&kept))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    std::fs::remove_file(dir.join(&removed)).unwrap();
+
+    // Leftover of an interrupted chunk write.
+    let prefix_dir = Path::new(&expected[0]).parent().unwrap().to_path_buf();
+    let tmp_file = prefix_dir.join("00.tmp.1").path_to_string().unwrap();
+    std::fs::write(dir.join(&tmp_file), b"partial").unwrap();
+    expected.push(tmp_file);
+    expected.sort();
+
+    assert_eq!(
+        store.list_unreferenced_chunks().await.unwrap(),
+        Some(expected)
+    );
+    assert_eq!(read_file(&store, &kept).await.unwrap(), vec![1u8; 1000]);
+}
@@ -8,7 +8,7 @@ mod tests;
 use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
 use crate::{
     storage::{BackupStorage, ShellSafeName, TextLine},
-    utils::{error_notes::ErrorNotes, path_exists, PathToString},
+    utils::{error_notes::ErrorNotes, list_local_backup_files, path_exists, PathToString},
 };
 use anyhow::{bail, format_err, Result};
 use aptos_logger::info;
@@ -184,4 +184,11 @@ impl BackupStorage for LocalFs {
             .path_to_string()?;
         Ok(fh)
     }
+
+    async fn list_backup_files(&self) -> Result<Option<Vec<FileHandle>>> {
+        Ok(Some(
+            list_local_backup_files(&self.dir, &[Self::METADATA_DIR, Self::METADATA_BACKUP_DIR])
+                .await?,
+        ))
+    }
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod command_adapter;
+pub mod dedup_fs;
 pub mod local_fs;
 
 #[cfg(test)]
@@ -12,6 +13,7 @@ mod tests;
 
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
+    dedup_fs::{DedupFs, DedupFsOpt},
     local_fs::{LocalFs, LocalFsOpt},
 };
 use anyhow::{ensure, Result};
@@ -23,7 +25,7 @@ use proptest::prelude::*;
 use regex::Regex;
 #[cfg(test)]
 use std::convert::TryInto;
-use std::{convert::TryFrom, ops::Deref, str::FromStr, sync::Arc};
+use std::{convert::TryFrom, ops::Deref, path::PathBuf, str::FromStr, sync::Arc};
 use tokio::io::{AsyncRead, AsyncWrite};
 
 /// String returned by a specific storage implementation to identify a backup, probably a folder name
@@ -185,12 +187,29 @@ pub trait BackupStorage: Send + Sync {
         name: &ShellSafeName,
         lines: &[TextLine],
     ) -> Result<FileHandle>;
+    /// List handles of all files created by `create_for_write()`, across all backups, for audits
+    /// to find files not referred to by any manifest.
+    /// Returns `None` if the storage doesn't support listing files.
+    async fn list_backup_files(&self) -> Result<Option<Vec<FileHandle>>> {
+        Ok(None)
+    }
+    /// List content chunks not referred to by any file, for storages keeping file content apart
+    /// from the files, relative to the storage root.
+    /// Returns `None` if the storage doesn't keep chunks.
+    async fn list_unreferenced_chunks(&self) -> Result<Option<Vec<String>>> {
+        Ok(None)
+    }
 }
 
 #[derive(Parser)]
 pub enum StorageOpt {
     #[clap(about = "Select the LocalFs backup storage type, which is used mainly for tests.")]
     LocalFs(LocalFsOpt),
+    #[clap(
+        about = "Select the DedupFs backup storage type, which stores files in a local dir split \
+    into content-defined chunks, deduplicated across backups and optionally encrypted at rest."
+    )]
+    DedupFs(DedupFsOpt),
     #[clap(
         about = "Select the CommandAdapter backup storage type, which reads shell commands with which \
     it communicates with either a local file system or a remote cloud storage. Compression or other \
@@ -204,6 +223,7 @@ impl StorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
+            StorageOpt::DedupFs(opt) => Arc::new(DedupFs::new_with_opt(opt).await?),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
         })
     }
@@ -213,7 +233,7 @@ impl StorageOpt {
 #[clap(group(
     ArgGroup::new("storage")
     .required(true)
-    .args(&["local-fs-dir", "command-adapter-config"]),
+    .args(&["local-fs-dir", "dedup-fs-dir", "command-adapter-config"]),
 ))]
 pub struct DBToolStorageOpt {
     #[clap(
@@ -221,6 +241,19 @@ pub struct DBToolStorageOpt {
         help = "Select the LocalFs backup storage type, which is used mainly for tests."
     )]
     local_fs_dir: Option<LocalFsOpt>,
+    #[clap(
+        long,
+        help = "Select the DedupFs backup storage type, which stores files in a local dir split \
+    into content-defined chunks, deduplicated across backups."
+    )]
+    dedup_fs_dir: Option<PathBuf>,
+    #[clap(
+        long,
+        requires = "dedup-fs-dir",
+        help = "File holding a hex encoded 32 byte key, used by the DedupFs backup storage to \
+    encrypt chunks at rest."
+    )]
+    dedup_fs_encryption_key_file: Option<PathBuf>,
     #[clap(
         long,
         help = "Select the CommandAdapter backup storage type, which reads shell commands with which \
@@ -235,6 +268,14 @@ impl DBToolStorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
         Ok(if self.local_fs_dir.is_some() {
             Arc::new(LocalFs::new_with_opt(self.local_fs_dir.unwrap()))
+        } else if let Some(dir) = self.dedup_fs_dir {
+            Arc::new(
+                DedupFs::new_with_opt(DedupFsOpt {
+                    dir,
+                    encryption_key_file: self.dedup_fs_encryption_key_file,
+                })
+                .await?,
+            )
         } else {
             Arc::new(CommandAdapter::new_with_opt(self.command_adapter_config.unwrap()).await?)
         })
@@ -46,6 +46,19 @@ pub async fn test_write_and_read_impl(
             assert_eq!(content, &buf);
         }
     }
+
+    if let Some(listed) = store.list_backup_files().await.unwrap() {
+        let expected = backups
+            .iter()
+            .flat_map(|(backup_name, files)| {
+                files
+                    .keys()
+                    .map(move |name| to_file_name(backup_name, name))
+            })
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(listed.into_iter().sorted().collect::<Vec<_>>(), expected);
+    }
 }
 
 pub fn arb_backups(
@@ -11,6 +11,7 @@ pub(crate) mod stream;
 #[cfg(any(test, feature = "testing"))]
 pub mod test_utils;
 
+use crate::utils::error_notes::ErrorNotes;
 use anyhow::{anyhow, Result};
 use aptos_config::config::{
     RocksdbConfig, RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS,
@@ -43,7 +44,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use tokio::fs::metadata;
+use tokio::fs::{metadata, read_dir};
 
 #[derive(Clone, Parser)]
 pub struct GlobalBackupOpt {
@@ -226,6 +227,22 @@ impl RestoreRunMode {
         }
     }
 
+    /// Applies a state snapshot delta on top of the state snapshot at `base_version`, returning
+    /// the resulting root hash, or `None` in verify mode where there is no state to apply to.
+    pub fn apply_state_delta(
+        &self,
+        base_version: Version,
+        version: Version,
+        changes: &[(StateKey, Option<StateValue>)],
+    ) -> Result<Option<HashValue>> {
+        match self {
+            Self::Restore { restore_handler } => restore_handler
+                .apply_state_delta(base_version, version, changes)
+                .map(Some),
+            Self::Verify => Ok(None),
+        }
+    }
+
     pub fn finish(&self) {
         match self {
             Self::Restore { restore_handler } => {
@@ -392,6 +409,37 @@ pub(crate) async fn path_exists(path: &Path) -> bool {
     metadata(&path).await.is_ok()
 }
 
+/// Lists files in a local backup storage dir laid out as `<dir>/<backup>/<file>`, returning them as
+/// file handles relative to `dir`. Top level folders in `skipped_dirs` are not backups.
+pub(crate) async fn list_local_backup_files(
+    dir: &Path,
+    skipped_dirs: &[&str],
+) -> Result<Vec<String>> {
+    let mut res = Vec::new();
+    if !path_exists(dir).await {
+        return Ok(res);
+    }
+    let mut backups = read_dir(dir).await.err_notes(dir)?;
+    while let Some(backup) = backups.next_entry().await.err_notes(dir)? {
+        let backup_name = backup.file_name();
+        if !backup.file_type().await.err_notes(backup.path())?.is_dir()
+            || skipped_dirs.iter().any(|d| backup_name == *d)
+        {
+            continue;
+        }
+        let mut files = read_dir(backup.path()).await.err_notes(backup.path())?;
+        while let Some(file) = files.next_entry().await.err_notes(backup.path())? {
+            res.push(
+                Path::new(&backup_name)
+                    .join(file.file_name())
+                    .path_to_string()?,
+            );
+        }
+    }
+    res.sort();
+    Ok(res)
+}
+
 pub(crate) trait PathToString {
     fn path_to_string(&self) -> Result<String>;
 }
@@ -2,10 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::{
+        audit::{AuditCoordinator, RestoreThroughputOpt},
+        backup::BackupCompactor,
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
 };
+use aptos_types::transaction::Version;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 /// Support compacting and cleaning obsolete metadata files
 #[derive(Subcommand)]
@@ -14,6 +21,11 @@ pub enum Command {
     Compact(CompactionOpt),
     #[clap(about = "Cleanup the backup metadata files")]
     Cleanup(CleanupOpt),
+    #[clap(
+        about = "Audit the backups in a storage, reporting coverage holes, unverifiable state \
+    snapshots, orphaned files and an estimate of the restore time, in JSON."
+    )]
+    Audit(AuditOpt),
 }
 
 #[derive(Parser)]
@@ -48,6 +60,32 @@ pub struct CleanupOpt {
     pub storage: DBToolStorageOpt,
 }
 
+#[derive(Parser)]
+pub struct AuditOpt {
+    #[clap(
+        long,
+        help = "Version to estimate the restore time to. [Defaults to the latest backed up \
+        transaction]"
+    )]
+    pub target_version: Option<Version>,
+    #[clap(
+        long,
+        parse(from_os_str),
+        help = "Write the JSON report to this file instead of stdout."
+    )]
+    pub output_json: Option<PathBuf>,
+    #[clap(flatten)]
+    pub throughput: RestoreThroughputOpt,
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    pub trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+}
+
 impl Command {
     pub async fn run(self) -> Result<()> {
         match self {
@@ -63,6 +101,23 @@ impl Command {
                 );
                 compactor.run().await?
             },
+            Command::Audit(opt) => {
+                let report = AuditCoordinator::new(
+                    opt.storage.init_storage().await?,
+                    opt.metadata_cache_opt,
+                    opt.trusted_waypoints_opt,
+                    opt.concurrent_downloads.get(),
+                    opt.target_version,
+                    opt.throughput,
+                )?
+                .run()
+                .await?;
+                let json = serde_json::to_string_pretty(&report)?;
+                match opt.output_json {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => println!("{}", json),
+                }
+            },
             Command::Cleanup(_) => {
                 // TODO: add cleanup logic for removing obsolete metadata files
             },
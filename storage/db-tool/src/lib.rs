@@ -6,6 +6,7 @@ extern crate core;
 mod backup;
 mod backup_maintenance;
 mod debugger;
+pub mod query;
 mod replay_verify;
 pub mod restore;
 #[cfg(test)]
@@ -27,6 +28,7 @@ pub enum DBTool {
     Debug(debugger::Command),
     #[clap(subcommand)]
     BackupMaintenance(backup_maintenance::Command),
+    Query(query::Opt),
 }
 
 impl DBTool {
@@ -36,6 +38,7 @@ impl DBTool {
             DBTool::Restore(cmd) => cmd.run().await,
             DBTool::ReplayVerify(cmd) => cmd.run().await,
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
+            DBTool::Query(cmd) => cmd.run().await,
             DBTool::Debug(cmd) => cmd.run(),
         }
     }
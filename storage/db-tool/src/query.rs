@@ -0,0 +1,255 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, format_err, Result};
+use aptos_api_types::AsConverter;
+use aptos_backup_cli::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::{
+        ConcurrentDownloadsOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt, RocksdbOpt,
+        TrustedWaypointOpt,
+    },
+};
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_state_view::TStateView;
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader, Order};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    event::EventKey,
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::Version,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use clap::{Parser, Subcommand};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    resolver::MoveResolver,
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Answers read-only queries at a historical version, restoring only the state snapshot before
+/// the version and the transactions after it from a backup.
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, help = "Version at which the query is answered.")]
+    version: Version,
+    #[clap(
+        long,
+        parse(from_os_str),
+        help = "[Defaults to a temporary dir, removed after the query] Dir to restore the DB \
+        into. Pass the same dir to run more queries at the same version without restoring again."
+    )]
+    db_dir: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "[Defaults to the state snapshot selected for the version] Restore the ledger \
+        history (transactions and events) since this version, for event queries reaching \
+        further back."
+    )]
+    ledger_history_start_version: Option<Version>,
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+    #[clap(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(flatten)]
+    replay_concurrency_level: ReplayConcurrencyLevelOpt,
+    #[clap(subcommand)]
+    query: Query,
+}
+
+#[derive(Subcommand)]
+pub enum Query {
+    #[clap(about = "Get a resource under an account, like `GET /accounts/{address}/resource`.")]
+    AccountResource {
+        #[clap(long)]
+        address: AccountAddress,
+        #[clap(long, help = "Like 0x1::account::Account")]
+        resource_type: StructTag,
+    },
+    #[clap(about = "Get an item in a table, like `POST /tables/{handle}/item`.")]
+    TableItem {
+        #[clap(long)]
+        handle: TableHandle,
+        #[clap(long)]
+        key_type: TypeTag,
+        #[clap(long)]
+        value_type: TypeTag,
+        #[clap(long, help = "The key in JSON, like \"0x1\" for an address key.")]
+        key: String,
+    },
+    #[clap(
+        about = "Get events by event key, like `GET /accounts/{address}/events/{creation_number}`."
+    )]
+    Events {
+        #[clap(long)]
+        address: AccountAddress,
+        #[clap(long)]
+        creation_number: u64,
+        #[clap(
+            long,
+            help = "[Defaults to the latest events] First sequence number to get."
+        )]
+        start: Option<u64>,
+        #[clap(long, default_value = "25")]
+        limit: u16,
+    },
+}
+
+impl Opt {
+    pub async fn run(self) -> Result<()> {
+        println!("{}", self.query_json().await?);
+        Ok(())
+    }
+
+    /// Restores the DB to the version and returns the answer to the query in JSON.
+    pub async fn query_json(self) -> Result<String> {
+        let tmp_dir;
+        let db_dir = match self.db_dir {
+            Some(db_dir) => db_dir,
+            None => {
+                tmp_dir = TempPath::new();
+                tmp_dir.create_as_dir()?;
+                tmp_dir.path().to_path_buf()
+            },
+        };
+
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: self.metadata_cache_opt,
+                replay_all: false,
+                ledger_history_start_version: self.ledger_history_start_version,
+                skip_epoch_endings: false,
+                use_state_snapshot_deltas: false,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(db_dir.clone()),
+                target_version: Some(self.version),
+                trusted_waypoints: self.trusted_waypoints_opt,
+                rocksdb_opt: self.rocksdb_opt.clone(),
+                concurrent_downloads: self.concurrent_downloads,
+                replay_concurrency_level: self.replay_concurrency_level,
+            }
+            .try_into()?,
+            self.storage.init_storage().await?,
+        )
+        .run()
+        .await?;
+
+        let db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_opt.into(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        let latest_version = db.get_latest_version()?;
+        ensure!(
+            latest_version >= self.version,
+            "Version {} is not in the backup, the latest restored version is {}.",
+            self.version,
+            latest_version,
+        );
+        info!(version = self.version, "DB restored, answering the query.");
+
+        self.query.run(db, self.version)
+    }
+}
+
+impl Query {
+    fn run(self, db: Arc<dyn DbReader>, version: Version) -> Result<String> {
+        let state_view = db.state_view_at_version(Some(version))?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(db.clone());
+
+        match self {
+            Query::AccountResource {
+                address,
+                resource_type,
+            } => {
+                let bytes = resolver
+                    .get_resource(&address, &resource_type)?
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Resource {} not found under {} at version {}.",
+                            resource_type,
+                            address,
+                            version,
+                        )
+                    })?;
+                Ok(serde_json::to_string_pretty(
+                    &converter.try_into_resource(&resource_type, &bytes)?,
+                )?)
+            },
+            Query::TableItem {
+                handle,
+                key_type,
+                value_type,
+                key,
+            } => {
+                let vm_key = converter.try_into_vm_value(&key_type, serde_json::from_str(&key)?)?;
+                let raw_key = vm_key
+                    .undecorate()
+                    .simple_serialize()
+                    .ok_or_else(|| format_err!("Failed to serialize table key."))?;
+                let bytes = state_view
+                    .get_state_value_bytes(&StateKey::table_item(handle, raw_key))?
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Table item {} not found in table {} at version {}.",
+                            key,
+                            handle.0,
+                            version,
+                        )
+                    })?;
+                Ok(serde_json::to_string_pretty(
+                    &converter.try_into_move_value(&value_type, &bytes)?,
+                )?)
+            },
+            Query::Events {
+                address,
+                creation_number,
+                start,
+                limit,
+            } => {
+                let event_key = EventKey::new(creation_number, address);
+                // Same as the REST API, latest events first if no start is given.
+                let events = match start {
+                    Some(start) => {
+                        db.get_events(&event_key, start, Order::Ascending, limit as u64, version)?
+                    },
+                    None => {
+                        let mut events = db.get_events(
+                            &event_key,
+                            u64::MAX,
+                            Order::Descending,
+                            limit as u64,
+                            version,
+                        )?;
+                        events.reverse();
+                        events
+                    },
+                };
+                Ok(serde_json::to_string_pretty(
+                    &converter.try_into_versioned_events(&events)?,
+                )?)
+            },
+        }
+    }
+}
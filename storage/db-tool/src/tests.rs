@@ -64,6 +64,30 @@ fn test_various_cmd_parsing() {
         "--start-version",
         "Max",
     ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "backup-maintenance",
+        "audit",
+        "--target-version",
+        "100",
+        "--output-json",
+        "audit.json",
+        "--local-fs-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "query",
+        "--version",
+        "100",
+        "--local-fs-dir",
+        ".",
+        "account-resource",
+        "--address",
+        "0x1",
+        "--resource-type",
+        "0x1::account::Account",
+    ]);
 }
 
 fn run_cmd(args: &[&str]) {
@@ -546,4 +570,245 @@ mod compaction_tests {
         .unwrap();
         rt.shutdown_timeout(Duration::from_secs(1));
     }
+
+    #[test]
+    fn test_backup_audit() {
+        let db = test_execution_with_storage_impl();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let backup_dir_str = backup_dir.path().to_str().unwrap();
+        let (rt, port) = start_local_backup_service(db);
+        let server_addr = format!(" http://localhost:{}", port);
+        let backup = |args: &[&str]| {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "backup",
+                "oneoff",
+                "--backup-service-address",
+                server_addr.as_str(),
+            ];
+            cmd.extend(args);
+            cmd.extend(["--local-fs-dir", backup_dir_str]);
+            rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+                .unwrap();
+        };
+
+        // Only epoch 0 ends in the backup, so the snapshot in epoch 2 can't be verified.
+        backup(&["epoch-ending", "--start-epoch", "0", "--end-epoch", "1"]);
+        backup(&["state-snapshot", "--state-snapshot-epoch", "1"]);
+        backup(&["state-snapshot", "--state-snapshot-epoch", "2"]);
+        // Versions 15 to 19 are not backed up.
+        backup(&[
+            "transaction",
+            "--start-version",
+            "0",
+            "--num_transactions",
+            "15",
+        ]);
+        backup(&[
+            "transaction",
+            "--start-version",
+            "20",
+            "--num_transactions",
+            "10",
+        ]);
+        let orphan_dir = backup_dir.path().join("orphaned_backup");
+        std::fs::create_dir(&orphan_dir).unwrap();
+        std::fs::write(orphan_dir.join("0-.chunk"), b"orphan").unwrap();
+
+        let metadata_cache_dir = TempPath::new();
+        let report_path = TempPath::new();
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "backup-maintenance",
+                "audit",
+                "--target-version",
+                "25",
+                "--output-json",
+                report_path.path().to_str().unwrap(),
+                "--metadata-cache-dir",
+                metadata_cache_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir_str,
+            ])
+            .unwrap()
+            .run(),
+        )
+        .unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(report_path.path()).unwrap()).unwrap();
+        assert_eq!(
+            report["transaction_version_gaps"],
+            serde_json::json!([{"first_version": 15, "last_version": 19}])
+        );
+        assert_eq!(
+            report["missing_epoch_endings"],
+            serde_json::json!([{"first_epoch": 1, "last_epoch": 2}])
+        );
+        assert_eq!(report["num_verified_epochs"], 1);
+        let unreachable = report["unreachable_state_snapshots"].as_array().unwrap();
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!(unreachable[0]["epoch"], 2);
+        assert_eq!(
+            report["orphaned_files"],
+            serde_json::json!(["orphaned_backup/0-.chunk"])
+        );
+        assert_eq!(report["missing_files"], serde_json::json!([]));
+        // LocalFs doesn't keep chunks.
+        assert_eq!(report["unreferenced_chunks"], serde_json::Value::Null);
+        let estimate = &report["restore_estimate"];
+        assert_eq!(estimate["target_version"], 25);
+        let replay_start = estimate["state_snapshot_version"]
+            .as_u64()
+            .map_or(0, |version| version + 1);
+        assert_eq!(estimate["num_transactions_to_replay"], 26 - replay_start);
+
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+}
+
+#[cfg(test)]
+mod query_tests {
+    use crate::DBTool;
+    use aptos_backup_cli::utils::test_utils::start_local_backup_service;
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_storage_interface::DbReader;
+    use aptos_temppath::TempPath;
+    use clap::Parser;
+    use std::time::Duration;
+
+    #[test]
+    fn test_query_against_backup() {
+        let db = test_execution_with_storage_impl();
+        let latest_epoch = db.get_latest_epoch_state().unwrap().epoch;
+        let latest_version = db.get_latest_version().unwrap();
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let backup_dir_str = backup_dir.path().to_str().unwrap();
+        let (rt, port) = start_local_backup_service(db);
+        let server_addr = format!(" http://localhost:{}", port);
+        let backup = |args: &[&str]| {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "backup",
+                "oneoff",
+                "--backup-service-address",
+                server_addr.as_str(),
+            ];
+            cmd.extend(args);
+            cmd.extend(["--local-fs-dir", backup_dir_str]);
+            rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+                .unwrap();
+        };
+
+        let end_epoch = latest_epoch.to_string();
+        let num_transactions = (latest_version + 1).to_string();
+        backup(&[
+            "epoch-ending",
+            "--start-epoch",
+            "0",
+            "--end-epoch",
+            end_epoch.as_str(),
+        ]);
+        backup(&["state-snapshot", "--state-snapshot-epoch", "1"]);
+        backup(&[
+            "transaction",
+            "--start-version",
+            "0",
+            "--num_transactions",
+            num_transactions.as_str(),
+        ]);
+
+        let version = latest_version.to_string();
+        let metadata_cache_dir = TempPath::new();
+        let query = |args: &[&str]| -> serde_json::Value {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "query",
+                "--version",
+                version.as_str(),
+                "--metadata-cache-dir",
+                metadata_cache_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir_str,
+            ];
+            cmd.extend(args);
+            let opt = match DBTool::try_parse_from(cmd).unwrap() {
+                DBTool::Query(opt) => opt,
+                _ => unreachable!(),
+            };
+            serde_json::from_str(&rt.block_on(opt.query_json()).unwrap()).unwrap()
+        };
+
+        let resource = query(&[
+            "account-resource",
+            "--address",
+            "0x1",
+            "--resource-type",
+            "0x1::account::Account",
+        ]);
+        assert_eq!(resource["type"], "0x1::account::Account");
+
+        // The total supply of the coin is tracked by an aggregator, which lives in a table.
+        let coin_info = query(&[
+            "account-resource",
+            "--address",
+            "0x1",
+            "--resource-type",
+            "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>",
+        ]);
+        let aggregator = &coin_info["data"]["supply"]["vec"][0]["aggregator"]["vec"][0];
+        let handle = aggregator["handle"].as_str().unwrap();
+        let key = aggregator["key"].to_string();
+        let supply = query(&[
+            "table-item",
+            "--handle",
+            handle,
+            "--key-type",
+            "address",
+            "--value-type",
+            "u128",
+            "--key",
+            key.as_str(),
+        ]);
+        assert!(supply.as_str().unwrap().parse::<u128>().unwrap() > 0);
+
+        let block_resource = query(&[
+            "account-resource",
+            "--address",
+            "0x1",
+            "--resource-type",
+            "0x1::block::BlockResource",
+        ]);
+        let creation_number = block_resource["data"]["new_block_events"]["guid"]["id"]
+            ["creation_num"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        // The first events are older than the state snapshot, so restore the ledger history too.
+        let events = query(&[
+            "--ledger-history-start-version",
+            "0",
+            "events",
+            "--address",
+            "0x1",
+            "--creation-number",
+            creation_number.as_str(),
+            "--start",
+            "0",
+            "--limit",
+            "2",
+        ]);
+        let events = events.as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        for (seq_num, event) in events.iter().enumerate() {
+            assert_eq!(event["type"], "0x1::block::NewBlockEvent");
+            assert_eq!(event["sequence_number"], seq_num.to_string());
+            assert!(event["version"].as_str().unwrap().parse::<u64>().unwrap() <= latest_version);
+        }
+
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
 }
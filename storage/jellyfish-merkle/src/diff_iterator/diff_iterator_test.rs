@@ -0,0 +1,125 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    diff_iterator::JellyfishMerkleDiffIterator,
+    mock_tree_store::MockTreeStore,
+    test_helper::{gen_value, ValueBlob},
+    JellyfishMerkleTree,
+};
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+fn diff(
+    db: &Arc<MockTreeStore<ValueBlob>>,
+    old_version: Version,
+    new_version: Version,
+) -> Vec<HashValue> {
+    JellyfishMerkleDiffIterator::new(Arc::clone(db), old_version, new_version)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect()
+}
+
+#[test]
+fn test_diff_random_updates() {
+    let db = Arc::new(MockTreeStore::default());
+    let tree = JellyfishMerkleTree::new(&*db);
+    let mut rng = StdRng::from_seed([1; 32]);
+
+    let keys: Vec<_> = (0..200)
+        .map(|_| HashValue::random_with_rng(&mut rng))
+        .collect();
+    let mut snapshots: Vec<BTreeMap<HashValue, (HashValue, ValueBlob)>> = vec![];
+    let mut current = BTreeMap::new();
+    for version in 0..20 {
+        let mut updates = BTreeMap::new();
+        for _ in 0..rng.gen_range(1, 30) {
+            let key = keys[rng.gen_range(0, keys.len())];
+            // Deletes a third of the time, only deleting existing keys.
+            let value = if rng.gen_range(0, 3) == 0 && current.contains_key(&key) {
+                None
+            } else {
+                Some(gen_value())
+            };
+            updates.insert(key, value);
+        }
+        let (_root_hash, batch) = tree
+            .put_value_set_test(
+                updates.iter().map(|(k, v)| (*k, v.as_ref())).collect(),
+                version,
+            )
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        for (key, value) in updates {
+            match value {
+                Some(value) => current.insert(key, value),
+                None => current.remove(&key),
+            };
+        }
+        snapshots.push(current.clone());
+    }
+
+    for old_version in 0..20 {
+        for new_version in old_version..20 {
+            let old = &snapshots[old_version as usize];
+            let new = &snapshots[new_version as usize];
+            let expected: Vec<_> = old
+                .keys()
+                .chain(new.keys())
+                .filter(|key| old.get(*key) != new.get(*key))
+                .cloned()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            assert_eq!(
+                diff(&db, old_version, new_version),
+                expected,
+                "diff between {} and {}",
+                old_version,
+                new_version
+            );
+        }
+    }
+}
+
+#[test]
+fn test_diff_leaf_split_and_collapse() {
+    let db = Arc::new(MockTreeStore::default());
+    let tree = JellyfishMerkleTree::new(&*db);
+
+    // Keys sharing a long prefix, so the single leaf at version 0 is split into a deep subtree at
+    // version 1, and collapses back into a leaf at version 2.
+    let key1 = HashValue::from_u64(1);
+    let key2 = HashValue::from_u64(2);
+    let value1 = gen_value();
+    let value2 = gen_value();
+    for (version, updates) in [
+        vec![(key1, Some(&value1))],
+        vec![(key2, Some(&value2))],
+        vec![(key2, None)],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let (_root_hash, batch) = tree
+            .put_value_set_test(updates, version as Version)
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+    }
+
+    assert_eq!(diff(&db, 0, 1), vec![key2]);
+    assert_eq!(diff(&db, 1, 0), vec![key2]);
+    assert_eq!(diff(&db, 1, 2), vec![key2]);
+    assert!(diff(&db, 0, 2).is_empty());
+    assert!(diff(&db, 1, 1).is_empty());
+}
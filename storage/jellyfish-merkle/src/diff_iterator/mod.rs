@@ -0,0 +1,187 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements `JellyfishMerkleDiffIterator`. Initialized with two versions of the
+//! tree, the iterator generates the keys of all leaves that are created, updated or deleted
+//! between them, in key order, by traversing both trees at the same time and skipping the
+//! subtrees whose hashes are the same in both versions.
+
+#[cfg(test)]
+mod diff_iterator_test;
+
+use crate::{
+    node_type::{LeafNode, Node, NodeKey},
+    TreeReader,
+};
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::{nibble::nibble_path::NibblePath, transaction::Version};
+use std::{marker::PhantomData, sync::Arc};
+
+/// One side of a pair of subtrees at the same position of the two trees.
+enum Subtree<K> {
+    Empty,
+    /// Not loaded from storage yet.
+    Stored(NodeKey),
+    /// A leaf, which can be higher up in the tree than the position it's compared at, if the
+    /// other side has more leaves sharing the same prefix.
+    Leaf(LeafNode<K>),
+}
+
+/// A pair of subtrees at the same position, `depth` nibbles deep, to be compared.
+struct PendingDiff<K> {
+    depth: usize,
+    old: Subtree<K>,
+    new: Subtree<K>,
+}
+
+/// The `JellyfishMerkleDiffIterator` implementation.
+pub struct JellyfishMerkleDiffIterator<R, K> {
+    /// The storage engine from which we can read nodes using node keys.
+    reader: Arc<R>,
+
+    /// The stack used for depth first traversal, the next pair to compare is on the top.
+    pending: Vec<PendingDiff<K>>,
+
+    phantom_value: PhantomData<K>,
+}
+
+impl<R, K> JellyfishMerkleDiffIterator<R, K>
+where
+    R: TreeReader<K>,
+    K: crate::Key,
+{
+    /// Constructs a new iterator over the leaves that differ between the tree at `old_version`
+    /// and the one at `new_version`. Both versions need to have a root node in storage.
+    pub fn new(reader: Arc<R>, old_version: Version, new_version: Version) -> Result<Self> {
+        let mut pending = vec![];
+        let old_root = NodeKey::new_empty_path(old_version);
+        let new_root = NodeKey::new_empty_path(new_version);
+        if reader.get_node(&old_root)?.hash() != reader.get_node(&new_root)?.hash() {
+            pending.push(PendingDiff {
+                depth: 0,
+                old: Subtree::Stored(old_root),
+                new: Subtree::Stored(new_root),
+            });
+        }
+        Ok(Self {
+            reader,
+            pending,
+            phantom_value: PhantomData,
+        })
+    }
+
+    fn load(&self, subtree: Subtree<K>) -> Result<Option<Node<K>>> {
+        Ok(match subtree {
+            Subtree::Empty => None,
+            Subtree::Stored(node_key) => match self.reader.get_node(&node_key)? {
+                Node::Null => None,
+                node => Some(node),
+            },
+            Subtree::Leaf(leaf) => Some(Node::Leaf(leaf)),
+        })
+    }
+
+    /// Splits a subtree into its 16 children at `depth`. A leaf goes to the child its key
+    /// belongs to.
+    fn children(
+        node_key: Option<&NodeKey>,
+        node: Option<Node<K>>,
+        depth: usize,
+    ) -> Vec<Subtree<K>> {
+        let mut children: Vec<_> = (0..16).map(|_| Subtree::Empty).collect();
+        match node {
+            Some(Node::Internal(internal)) => {
+                let node_key = node_key.expect("Internal nodes are loaded from storage.");
+                for (nibble, child) in internal.children_sorted() {
+                    children[u8::from(*nibble) as usize] =
+                        Subtree::Stored(node_key.gen_child_node_key(child.version, *nibble));
+                }
+            },
+            Some(Node::Leaf(leaf)) => {
+                let nibble = NibblePath::new_even(leaf.account_key().to_vec()).get_nibble(depth);
+                children[u8::from(nibble) as usize] = Subtree::Leaf(leaf);
+            },
+            Some(Node::Null) | None => (),
+        }
+        children
+    }
+
+    /// Compares a pair of subtrees, returning a changed key if both are at most single leaves,
+    /// otherwise scheduling their children to be compared.
+    fn step(&mut self, diff: PendingDiff<K>) -> Result<Option<(HashValue, K)>> {
+        let PendingDiff { depth, old, new } = diff;
+        let old_key = match &old {
+            Subtree::Stored(node_key) => Some(node_key.clone()),
+            _ => None,
+        };
+        let new_key = match &new {
+            Subtree::Stored(node_key) => Some(node_key.clone()),
+            _ => None,
+        };
+        let (old, new) = (self.load(old)?, self.load(new)?);
+
+        match (old, new) {
+            (None, None) => Ok(None),
+            (Some(Node::Leaf(leaf)), None) | (None, Some(Node::Leaf(leaf))) => {
+                Ok(Some((leaf.account_key(), leaf.value_index().0.clone())))
+            },
+            (Some(Node::Leaf(old)), Some(Node::Leaf(new))) => {
+                if old.account_key() == new.account_key() {
+                    return Ok((old.hash() != new.hash())
+                        .then(|| (new.account_key(), new.value_index().0.clone())));
+                }
+                // Yield the smaller key now, and the larger one on the next step.
+                let (first, second) = if old.account_key() < new.account_key() {
+                    (old, new)
+                } else {
+                    (new, old)
+                };
+                self.pending.push(PendingDiff {
+                    depth,
+                    old: Subtree::Leaf(second),
+                    new: Subtree::Empty,
+                });
+                Ok(Some((first.account_key(), first.value_index().0.clone())))
+            },
+            (old, new) => {
+                let old_children = Self::children(old_key.as_ref(), old, depth);
+                let new_children = Self::children(new_key.as_ref(), new, depth);
+                // Pushed in reverse, so the smallest nibble is compared first.
+                for (old, new) in old_children.into_iter().zip(new_children).rev() {
+                    if let (Subtree::Stored(old_key), Subtree::Stored(new_key)) = (&old, &new) {
+                        // Children at the same version are the same node.
+                        if old_key == new_key {
+                            continue;
+                        }
+                    }
+                    self.pending.push(PendingDiff {
+                        depth: depth + 1,
+                        old,
+                        new,
+                    });
+                }
+                Ok(None)
+            },
+        }
+    }
+}
+
+impl<R, K> Iterator for JellyfishMerkleDiffIterator<R, K>
+where
+    R: TreeReader<K>,
+    K: crate::Key,
+{
+    type Item = Result<(HashValue, K)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(diff) = self.pending.pop() {
+            match self.step(diff) {
+                Ok(Some(changed)) => return Some(Ok(changed)),
+                Ok(None) => (),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
@@ -122,10 +122,21 @@ impl AsyncProofFetcher {
             let proof = reader
                 .get_state_proof_by_version_ext(&state_key, version)
                 .expect("Proof reading should succeed.");
-            // NOTE: Drop the reader here to make sure reader has shorter lifetime than the async
-            // proof fetcher.
-            drop(reader);
             if let Some(root_hash) = root_hash {
+                // A partially replicated DB doesn't have the values of keys outside of its
+                // filter, in which case the proof is only checked against the root hash -- the
+                // SMT is updated from the proof and doesn't need the old value.
+                let value_hash = match (value_hash, proof.leaf()) {
+                    (None, Some(leaf))
+                        if leaf.key() == state_key.hash()
+                            && reader
+                                .is_state_partially_replicated()
+                                .expect("Reading replication mode should succeed.") =>
+                    {
+                        Some(leaf.value_hash())
+                    },
+                    _ => value_hash,
+                };
                 proof
                     .verify_by_hash(root_hash, state_key.hash(), value_hash)
                     .map_err(|err| {
@@ -139,6 +150,9 @@ impl AsyncProofFetcher {
                     })
                     .expect("Failed to verify proof.");
             }
+            // NOTE: Drop the reader here to make sure reader has shorter lifetime than the async
+            // proof fetcher.
+            drop(reader);
             match data_sender.send(Proof {
                 state_key_hash: state_key.hash(),
                 proof,
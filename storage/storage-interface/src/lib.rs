@@ -508,6 +508,12 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns if only part of the state is replicated, in which case state value chunks
+    /// can't be served.
+    fn is_state_partially_replicated(&self) -> Result<bool> {
+        unimplemented!()
+    }
+
     /// Returns if the ledger pruner is enabled.
     fn is_ledger_pruner_enabled(&self) -> Result<bool> {
         unimplemented!()
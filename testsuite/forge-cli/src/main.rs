@@ -1975,7 +1975,7 @@ impl AptosTest for FundAccount {
 
         let account = ctx.random_account();
         let amount = 1000;
-        ctx.create_user_account(account.public_key()).await?;
+        ctx.create_user_account(account.public_key()?).await?;
         ctx.mint(account.address(), amount).await?;
         check_account_balance(&client, account.address(), amount).await?;
 
@@ -1998,8 +1998,8 @@ impl AptosTest for TransferCoins {
         let client = ctx.client();
         let mut payer = ctx.random_account();
         let payee = ctx.random_account();
-        ctx.create_user_account(payer.public_key()).await?;
-        ctx.create_user_account(payee.public_key()).await?;
+        ctx.create_user_account(payer.public_key()?).await?;
+        ctx.create_user_account(payee.public_key()?).await?;
         ctx.mint(payer.address(), 10000).await?;
         check_account_balance(&client, payer.address(), 10000).await?;
 
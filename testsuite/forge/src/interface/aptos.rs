@@ -258,7 +258,7 @@ impl<'t> AptosPublicInfo<'t> {
 
     pub async fn create_and_fund_user_account(&mut self, amount: u64) -> Result<LocalAccount> {
         let account = self.random_account();
-        self.create_user_account(account.public_key()).await?;
+        self.create_user_account(account.public_key()?).await?;
         self.mint(account.address(), amount).await?;
         Ok(account)
     }
@@ -39,7 +39,7 @@ async fn test_error_report() {
 
     let local_account = info.random_account();
     let address = local_account.address();
-    info.create_user_account(local_account.public_key())
+    info.create_user_account(local_account.public_key().unwrap())
         .await
         .unwrap();
     submit_and_check_err(
@@ -16,11 +16,11 @@ async fn test_gas_check() {
     let mut info = swarm.aptos_public_info();
 
     let mut account1 = info.random_account();
-    info.create_user_account(account1.public_key())
+    info.create_user_account(account1.public_key().unwrap())
         .await
         .unwrap();
     let mut account2 = info.random_account();
-    info.create_user_account(account2.public_key())
+    info.create_user_account(account2.public_key().unwrap())
         .await
         .unwrap();
 
@@ -0,0 +1,111 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    smoke_test_environment::SwarmBuilder,
+    test_utils::{assert_balance, create_and_fund_account, transfer_coins, MAX_HEALTHY_WAIT_SECS},
+    txn_emitter::generate_traffic,
+};
+use aptos_forge::{args::TransactionTypeArg, NodeExt, Swarm, SwarmExt, TxnStats};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+async fn run_with_traffic(num_validators: usize, dag_enabled: bool) -> TxnStats {
+    let mut swarm = SwarmBuilder::new_local(num_validators)
+        .with_aptos()
+        .with_init_config(Arc::new(move |_, conf, _| {
+            conf.consensus.dag_consensus.enabled = dag_enabled;
+        }))
+        .build()
+        .await;
+
+    let all_validators = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+    generate_traffic(
+        &mut swarm,
+        &all_validators,
+        Duration::from_secs(20),
+        1,
+        vec![vec![(
+            TransactionTypeArg::CoinTransfer.materialize_default(),
+            1,
+        )]],
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_dag_consensus_with_txn_emitter() {
+    let txn_stat = run_with_traffic(4, true).await;
+    println!("{:?}", txn_stat.rate());
+    // assert some much smaller number than expected, so it doesn't fail under contention
+    assert!(txn_stat.submitted > 30);
+    assert!(txn_stat.committed > 30);
+}
+
+#[tokio::test]
+async fn test_dag_consensus_validator_restart() {
+    let mut swarm = SwarmBuilder::new_local(4)
+        .with_aptos()
+        .with_init_config(Arc::new(|_, conf, _| {
+            conf.consensus.dag_consensus.enabled = true;
+        }))
+        .build()
+        .await;
+    let validator_peer_ids = swarm.validators().map(|v| v.peer_id()).collect::<Vec<_>>();
+    let client_3 = swarm
+        .validator(validator_peer_ids[3])
+        .unwrap()
+        .rest_client();
+    let transaction_factory = swarm.chain_info().transaction_factory();
+
+    let mut account_0 = create_and_fund_account(&mut swarm, 100).await;
+    let account_1 = create_and_fund_account(&mut swarm, 10).await;
+
+    // Stop a validator long enough for the others to prune the rounds it misses
+    let node = swarm.validator_mut(validator_peer_ids[0]).unwrap();
+    node.stop();
+    transfer_coins(
+        &client_3,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        10,
+    )
+    .await;
+    tokio::time::sleep(Duration::from_secs(20)).await;
+    let node = swarm.validator_mut(validator_peer_ids[0]).unwrap();
+    node.start().unwrap();
+    node.wait_until_healthy(Instant::now() + Duration::from_secs(MAX_HEALTHY_WAIT_SECS))
+        .await
+        .unwrap();
+    swarm
+        .wait_for_all_nodes_to_catchup(Duration::from_secs(MAX_HEALTHY_WAIT_SECS))
+        .await
+        .unwrap();
+
+    // The restarted validator is needed for a quorum once another one is down
+    swarm.validator_mut(validator_peer_ids[1]).unwrap().stop();
+    transfer_coins(
+        &client_3,
+        &transaction_factory,
+        &mut account_0,
+        &account_1,
+        10,
+    )
+    .await;
+    assert_balance(&client_3, &account_0, 80).await;
+    assert_balance(&client_3, &account_1, 30).await;
+}
+
+#[ignore]
+#[tokio::test]
+// Runs the same traffic on Jolteon and on DAG, to compare throughput and latency.
+async fn test_dag_consensus_compare_with_jolteon() {
+    let jolteon = run_with_traffic(4, false).await.rate();
+    let dag = run_with_traffic(4, true).await.rate();
+    println!("Jolteon: {:?}", jolteon);
+    println!("DAG: {:?}", dag);
+}
@@ -4,4 +4,5 @@
 mod consensus_fault_tolerance;
 mod consensus_only;
 mod consensusdb_recovery;
+mod dag_consensus;
 mod quorum_store_fault_tolerance;
@@ -45,7 +45,7 @@ async fn test_indexer() {
     let mut chain_info = swarm.chain_info().into_aptos_public_info();
     let factory = chain_info.transaction_factory();
     chain_info
-        .create_user_account(account1.public_key())
+        .create_user_account(account1.public_key().unwrap())
         .await
         .unwrap();
     // TODO(Gas): double check if this is correct
@@ -54,7 +54,7 @@ async fn test_indexer() {
         .await
         .unwrap();
     chain_info
-        .create_user_account(account2.public_key())
+        .create_user_account(account2.public_key().unwrap())
         .await
         .unwrap();
 
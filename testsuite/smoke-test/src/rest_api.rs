@@ -222,7 +222,7 @@ async fn test_bcs() {
         .await
         .unwrap();
     let account = local_account.address();
-    let public_key = local_account.public_key();
+    let public_key = local_account.public_key().unwrap();
     let other_local_account = info
         .create_and_fund_user_account(100_000_000_000)
         .await
@@ -391,7 +391,7 @@ async fn test_bcs() {
         .build();
     let signed_txn = SignedTransaction::new(
         transfer_txn,
-        local_account.public_key().clone(),
+        local_account.public_key().unwrap().clone(),
         Ed25519Signature::dummy_signature(),
     );
 
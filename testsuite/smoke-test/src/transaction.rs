@@ -35,7 +35,7 @@ async fn test_external_transaction_signer() {
     info.mint(sender_address, 10_000_000).await.unwrap();
 
     let receiver = info.random_account();
-    info.create_user_account(receiver.public_key())
+    info.create_user_account(receiver.public_key().unwrap())
         .await
         .unwrap();
     // TODO(Gas): double check if this is correct
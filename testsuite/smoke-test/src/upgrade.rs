@@ -43,6 +43,7 @@ async fn test_upgrade_flow() {
         .aptos_public_info()
         .root_account()
         .private_key()
+        .unwrap()
         .to_encoded_string()
         .unwrap();
 
@@ -232,7 +233,7 @@ async fn test_release_validate_tool_multi_step() {
 
     std::fs::write(
         root_key_path.as_path(),
-        bcs::to_bytes(&env.chain_info().root_account().private_key()).unwrap(),
+        bcs::to_bytes(&env.chain_info().root_account().private_key().unwrap()).unwrap(),
     )
     .unwrap();
 
@@ -50,7 +50,7 @@ impl NetworkLoadTest for QuorumStoreOnChainEnableTest {
         runtime.block_on(async {
 
             let root_cli_index = cli.add_account_with_address_to_cli(
-                swarm.chain_info().root_account().private_key().clone(),
+                swarm.chain_info().root_account().private_key().unwrap().clone(),
                 swarm.chain_info().root_account().address(),
             );
 
@@ -64,6 +64,9 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // Transaction was accepted by Mempool, replacing a pending transaction with the same sender
+    // and sequence number
+    Replaced = 7,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -78,6 +81,7 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::Replaced),
             _ => Err("invalid StatusCode"),
         }
     }
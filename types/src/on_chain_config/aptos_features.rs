@@ -29,6 +29,8 @@ pub enum FeatureFlag {
     STORAGE_SLOT_METADATA = 19,
     CHARGE_INVARIANT_VIOLATION = 20,
     DELEGATION_POOL_PARTIAL_GOVERNANCE_VOTING = 21,
+    SECP256K1_ECDSA_AUTHENTICATOR = 22,
+    FEE_PAYER_ENABLED = 23,
 }
 
 /// Representation of features on chain as a bitset.
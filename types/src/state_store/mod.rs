@@ -6,6 +6,7 @@ use arr_macro::arr;
 use std::collections::HashMap;
 
 pub mod state_key;
+pub mod state_key_filter;
 pub mod state_key_prefix;
 pub mod state_storage_usage;
 pub mod state_value;
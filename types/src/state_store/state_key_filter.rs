@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    access_path::Path,
+    state_store::state_key::{StateKey, StateKeyInner},
+};
+use move_core_types::account_address::AccountAddress;
+use std::collections::HashSet;
+
+/// Selects the state keys that a partially replicated node persists: all the keys under one of
+/// the given accounts, and the resources (of any account) whose type starts with one of the given
+/// prefixes, e.g., `0x1::coin::CoinStore`. Table items are never selected, as they can't be
+/// attributed to an account.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StateKeyFilter {
+    accounts: HashSet<AccountAddress>,
+    resource_prefixes: Vec<String>,
+}
+
+impl StateKeyFilter {
+    pub fn new(
+        accounts: impl IntoIterator<Item = AccountAddress>,
+        resource_prefixes: Vec<String>,
+    ) -> Self {
+        Self {
+            accounts: accounts.into_iter().collect(),
+            resource_prefixes,
+        }
+    }
+
+    /// Returns true iff all the keys under the given account are selected
+    pub fn contains_account(&self, address: &AccountAddress) -> bool {
+        self.accounts.contains(address)
+    }
+
+    /// Returns true iff the given state key is selected
+    pub fn matches(&self, state_key: &StateKey) -> bool {
+        let access_path = match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path,
+            StateKeyInner::TableItem { .. } | StateKeyInner::Raw(_) => return false,
+        };
+        if self.contains_account(&access_path.address) {
+            return true;
+        }
+
+        match bcs::from_bytes::<Path>(&access_path.path) {
+            Ok(Path::Resource(struct_tag)) | Ok(Path::ResourceGroup(struct_tag)) => {
+                let resource_type = struct_tag.to_string();
+                self.resource_prefixes
+                    .iter()
+                    .any(|prefix| resource_type.starts_with(prefix))
+            },
+            Ok(Path::Code(_)) | Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StateKeyFilter;
+    use crate::{
+        access_path::AccessPath,
+        state_store::{state_key::StateKey, table::TableHandle},
+    };
+    use move_core_types::{
+        account_address::AccountAddress,
+        identifier::Identifier,
+        language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
+    };
+
+    fn coin_store_key(address: AccountAddress) -> StateKey {
+        let struct_tag = StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("coin").unwrap(),
+            name: Identifier::new("CoinStore").unwrap(),
+            type_params: vec![TypeTag::Struct(Box::new(StructTag {
+                address: CORE_CODE_ADDRESS,
+                module: Identifier::new("aptos_coin").unwrap(),
+                name: Identifier::new("AptosCoin").unwrap(),
+                type_params: vec![],
+            }))],
+        };
+        StateKey::access_path(AccessPath::resource_access_path(address, struct_tag).unwrap())
+    }
+
+    fn module_key(address: AccountAddress) -> StateKey {
+        let module_id = ModuleId::new(address, Identifier::new("module").unwrap());
+        StateKey::access_path(AccessPath::code_access_path(module_id))
+    }
+
+    #[test]
+    fn test_state_key_filter() {
+        let replicated_account = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+        let other_account = AccountAddress::new([2u8; AccountAddress::LENGTH]);
+        let filter = StateKeyFilter::new(vec![replicated_account], vec![
+            "0x1::coin::CoinStore".to_string()
+        ]);
+
+        // All the keys of the replicated account are selected
+        assert!(filter.contains_account(&replicated_account));
+        assert!(filter.matches(&coin_store_key(replicated_account)));
+        assert!(filter.matches(&module_key(replicated_account)));
+
+        // Only the resources matching a prefix are selected for the other accounts
+        assert!(!filter.contains_account(&other_account));
+        assert!(filter.matches(&coin_store_key(other_account)));
+        assert!(!filter.matches(&module_key(other_account)));
+
+        // Table items are never selected
+        let table_item = StateKey::table_item(TableHandle(replicated_account), vec![1, 2, 3]);
+        assert!(!filter.matches(&table_item));
+    }
+}
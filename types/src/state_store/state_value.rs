@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    on_chain_config::CurrentTimeMicroseconds, proof::SparseMerkleRangeProof,
-    state_store::state_key::StateKey, transaction::Version,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::CurrentTimeMicroseconds,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::state_key::StateKey,
+    transaction::Version,
 };
+use anyhow::{ensure, Result};
 use aptos_crypto::{
     hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
     HashValue,
@@ -200,6 +204,73 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// A state value (or its absence) at a state snapshot version, together with the proofs
+/// connecting it to a ledger info at or after that version: the sparse merkle proof authenticates
+/// the value against the state checkpoint hash of the transaction info at the snapshot version,
+/// which is itself authenticated against the transaction accumulator root of the ledger info.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValueWithProof {
+    pub version: Version,
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+    pub state_value: Option<StateValue>,
+    pub sparse_merkle_proof: SparseMerkleProof,
+}
+
+impl StateValueWithProof {
+    pub fn new(
+        version: Version,
+        ledger_info_with_signatures: LedgerInfoWithSignatures,
+        transaction_info_with_proof: TransactionInfoWithProof,
+        state_value: Option<StateValue>,
+        sparse_merkle_proof: SparseMerkleProof,
+    ) -> Self {
+        Self {
+            version,
+            ledger_info_with_signatures,
+            transaction_info_with_proof,
+            state_value,
+            sparse_merkle_proof,
+        }
+    }
+
+    /// Returns the version of the state snapshot the state value is read at.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Verifies that the state value of the given key at the version of this proof is the one in
+    /// this proof, and that this version is committed by the given ledger info. Note: the
+    /// signatures of the ledger info are not verified here, it is up to the caller to trust the
+    /// given ledger info.
+    pub fn verify(&self, ledger_info: &LedgerInfo, state_key: &StateKey) -> Result<()> {
+        ensure!(
+            self.ledger_info_with_signatures.ledger_info() == ledger_info,
+            "The ledger info in the proof ({:?}) doesn't match the expected one ({:?}).",
+            self.ledger_info_with_signatures.ledger_info(),
+            ledger_info,
+        );
+        ensure!(
+            self.version <= ledger_info.version(),
+            "The version of the proof ({}) is after the version of the ledger info ({}).",
+            self.version,
+            ledger_info.version(),
+        );
+
+        self.transaction_info_with_proof
+            .verify(ledger_info, self.version)?;
+        let state_root_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        self.sparse_merkle_proof.verify(
+            state_root_hash,
+            CryptoHash::hash(state_key),
+            self.state_value.as_ref(),
+        )
+    }
+}
+
 /// Indicates a state value becomes stale since `stale_since_version`.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
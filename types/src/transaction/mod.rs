@@ -7,7 +7,7 @@ use crate::{
     block_metadata::BlockMetadata,
     chain_id::ChainId,
     contract_event::ContractEvent,
-    ledger_info::LedgerInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::{
         accumulator::InMemoryAccumulator, TransactionInfoListWithProof, TransactionInfoWithProof,
     },
@@ -21,6 +21,7 @@ use aptos_crypto::{
     ed25519::*,
     hash::{CryptoHash, EventAccumulatorHasher},
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    secp256k1_ecdsa,
     traits::{signing_message, SigningKey},
     CryptoMaterialError, HashValue,
 };
@@ -283,6 +284,69 @@ impl RawTransaction {
         ))
     }
 
+    /// Signs the given `RawTransaction` as a fee payer transaction, in which the gas is paid by
+    /// `fee_payer_address` rather than by the sender. The sender, the secondary signers and the
+    /// fee payer all sign over the fee payer address, so that it can't be swapped out.
+    ///
+    /// The order and length of the secondary keys provided here have to match the order and
+    /// length of the `secondary_signers`.
+    pub fn sign_fee_payer(
+        self,
+        sender_private_key: &Ed25519PrivateKey,
+        secondary_signers: Vec<AccountAddress>,
+        secondary_private_keys: Vec<&Ed25519PrivateKey>,
+        fee_payer_address: AccountAddress,
+        fee_payer_private_key: &Ed25519PrivateKey,
+    ) -> Result<SignatureCheckedTransaction> {
+        if secondary_private_keys.len() != secondary_signers.len() {
+            return Err(format_err!(
+                "number of secondary private keys and number of secondary signers don't match"
+            ));
+        }
+        let message = RawTransactionWithData::new_multi_agent_with_fee_payer(
+            self.clone(),
+            secondary_signers.clone(),
+            fee_payer_address,
+        );
+        let authenticator = |private_key: &Ed25519PrivateKey| -> Result<AccountAuthenticator> {
+            Ok(AccountAuthenticator::ed25519(
+                Ed25519PublicKey::from(private_key),
+                private_key.sign(&message)?,
+            ))
+        };
+
+        let sender_authenticator = authenticator(sender_private_key)?;
+        let secondary_authenticators = secondary_private_keys
+            .into_iter()
+            .map(authenticator)
+            .collect::<Result<Vec<_>>>()?;
+        let fee_payer_authenticator = authenticator(fee_payer_private_key)?;
+
+        Ok(SignatureCheckedTransaction(
+            SignedTransaction::new_fee_payer(
+                self,
+                sender_authenticator,
+                secondary_signers,
+                secondary_authenticators,
+                fee_payer_address,
+                fee_payer_authenticator,
+            ),
+        ))
+    }
+
+    /// Signs the given `RawTransaction` with a secp256k1 ECDSA key. Note that this consumes the
+    /// `RawTransaction` and turns it into a `SignatureCheckedTransaction`.
+    pub fn sign_secp256k1_ecdsa(
+        self,
+        private_key: &secp256k1_ecdsa::PrivateKey,
+        public_key: secp256k1_ecdsa::PublicKey,
+    ) -> Result<SignatureCheckedTransaction> {
+        let signature = private_key.sign(&self)?;
+        Ok(SignatureCheckedTransaction(
+            SignedTransaction::new_secp256k1_ecdsa(self, public_key, signature),
+        ))
+    }
+
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn multi_sign_for_testing(
         self,
@@ -366,6 +430,11 @@ pub enum RawTransactionWithData {
         raw_txn: RawTransaction,
         secondary_signer_addresses: Vec<AccountAddress>,
     },
+    MultiAgentWithFeePayer {
+        raw_txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: AccountAddress,
+    },
 }
 
 impl RawTransactionWithData {
@@ -378,6 +447,18 @@ impl RawTransactionWithData {
             secondary_signer_addresses,
         }
     }
+
+    pub fn new_multi_agent_with_fee_payer(
+        raw_txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: AccountAddress,
+    ) -> Self {
+        Self::MultiAgentWithFeePayer {
+            raw_txn,
+            secondary_signer_addresses,
+            fee_payer_address,
+        }
+    }
 }
 
 /// Different kinds of transactions.
@@ -522,6 +603,19 @@ impl SignedTransaction {
         }
     }
 
+    pub fn new_secp256k1_ecdsa(
+        raw_txn: RawTransaction,
+        public_key: secp256k1_ecdsa::PublicKey,
+        signature: secp256k1_ecdsa::Signature,
+    ) -> SignedTransaction {
+        let authenticator = TransactionAuthenticator::secp256k1_ecdsa(public_key, signature);
+        SignedTransaction {
+            raw_txn,
+            authenticator,
+            size: OnceCell::new(),
+        }
+    }
+
     pub fn new_multi_agent(
         raw_txn: RawTransaction,
         sender: AccountAuthenticator,
@@ -539,6 +633,27 @@ impl SignedTransaction {
         }
     }
 
+    pub fn new_fee_payer(
+        raw_txn: RawTransaction,
+        sender: AccountAuthenticator,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        secondary_signers: Vec<AccountAuthenticator>,
+        fee_payer_address: AccountAddress,
+        fee_payer_signer: AccountAuthenticator,
+    ) -> Self {
+        SignedTransaction {
+            raw_txn,
+            authenticator: TransactionAuthenticator::fee_payer(
+                sender,
+                secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                fee_payer_signer,
+            ),
+            size: OnceCell::new(),
+        }
+    }
+
     pub fn new_with_authenticator(
         raw_txn: RawTransaction,
         authenticator: TransactionAuthenticator,
@@ -640,6 +755,13 @@ impl SignedTransaction {
         )
     }
 
+    pub fn is_fee_payer(&self) -> bool {
+        matches!(
+            self.authenticator,
+            TransactionAuthenticator::FeePayer { .. }
+        )
+    }
+
     /// Returns the hash when the transaction is commited onchain.
     pub fn committed_hash(self) -> HashValue {
         Transaction::UserTransaction(self).hash()
@@ -691,12 +813,6 @@ impl TransactionWithProof {
             .try_as_signed_user_txn()
             .context("not user transaction")?;
 
-        ensure!(
-            self.version == version,
-            "Version ({}) is not expected ({}).",
-            self.version,
-            version,
-        );
         ensure!(
             signed_transaction.sender() == sender,
             "Sender ({}) not expected ({}).",
@@ -710,6 +826,19 @@ impl TransactionWithProof {
             sequence_number,
         );
 
+        self.verify(ledger_info, version)
+    }
+
+    /// Verifies that this transaction (of any kind) and its events, if any, exist in the ledger
+    /// represented by `ledger_info` at the given `version`.
+    pub fn verify(&self, ledger_info: &LedgerInfo, version: Version) -> Result<()> {
+        ensure!(
+            self.version == version,
+            "Version ({}) is not expected ({}).",
+            self.version,
+            version,
+        );
+
         let txn_hash = self.transaction.hash();
         ensure!(
             txn_hash == self.proof.transaction_info().transaction_hash(),
@@ -735,6 +864,39 @@ impl TransactionWithProof {
     }
 }
 
+/// A transaction with its proof against a ledger info, together with that ledger info.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionWithLedgerInfoProof {
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+    pub transaction_with_proof: TransactionWithProof,
+}
+
+impl TransactionWithLedgerInfoProof {
+    pub fn new(
+        ledger_info_with_signatures: LedgerInfoWithSignatures,
+        transaction_with_proof: TransactionWithProof,
+    ) -> Self {
+        Self {
+            ledger_info_with_signatures,
+            transaction_with_proof,
+        }
+    }
+
+    /// Verifies that the transaction at the given version, and its events if any, are the ones in
+    /// this proof, in the ledger represented by the given ledger info. Note: the signatures of the
+    /// ledger info are not verified here, it is up to the caller to trust the given ledger info.
+    pub fn verify(&self, ledger_info: &LedgerInfo, version: Version) -> Result<()> {
+        ensure!(
+            self.ledger_info_with_signatures.ledger_info() == ledger_info,
+            "The ledger info in the proof ({:?}) doesn't match the expected one ({:?}).",
+            self.ledger_info_with_signatures.ledger_info(),
+            ledger_info,
+        );
+
+        self.transaction_with_proof.verify(ledger_info, version)
+    }
+}
+
 /// The status of VM execution, which contains more detailed failure info
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]